@@ -71,6 +71,7 @@ impl Circuit<Fr> for MyCircuit {
                             bias: Some(self.bias.clone()),
                             padding: [(0, 0); 2],
                             stride: (1, 1),
+                            pad_value: Fr::zero(),
                         }),
                     )
                     .unwrap();
@@ -0,0 +1,111 @@
+// Benchmarks keygen, proving and verification of whole models, calibrated down to the smallest bits and logrows
+// their (fixed) inputs fit in, such that no large SRS is needed
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ezkl::circuit::CheckMode;
+use ezkl::graph::input::GraphData;
+use ezkl::graph::{GraphCircuit, Visibility};
+use ezkl::pfsys::{
+    create_keys, create_proof_circuit_kzg, verify_proof_circuit_kzg, TranscriptType,
+};
+use ezkl::tensor::Tensor;
+use ezkl::RunArgs;
+use halo2_proofs::poly::commitment::ParamsProver;
+use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+use halo2curves::bn256::{Bn256, Fr};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const MODELS: [&str; 2] = ["1l_relu", "1l_mlp"];
+
+fn load_model(model: &str) -> (GraphCircuit, Vec<Vec<Fr>>) {
+    let model_path = format!("examples/onnx/{}/network.onnx", model).into();
+    let data = GraphData::from_path(format!("examples/onnx/{}/input.json", model).into()).unwrap();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let run_args = RunArgs {
+        scale: 7,
+        bits: 16,
+        logrows: 17,
+        batch_size: 1,
+        sequence_length: 1,
+        output_visibility: Visibility::Public,
+        ..Default::default()
+    };
+    let mut circuit = GraphCircuit::from_run_args(&run_args, &model_path).unwrap();
+    let input: Vec<Tensor<Fr>> = runtime.block_on(circuit.load_graph_input(&data)).unwrap();
+    circuit.calibrate(&input).unwrap();
+
+    // rebuild the circuit at the calibrated bits and logrows
+    let run_args = circuit.settings.run_args;
+    let mut circuit = GraphCircuit::from_run_args(&run_args, &model_path).unwrap();
+    let witness = circuit.forward(&input).unwrap();
+    circuit.load_graph_witness(&witness).unwrap();
+    let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+    (circuit, public_inputs)
+}
+
+fn runpipeline(c: &mut Criterion) {
+    let mut group = c.benchmark_group("proving_pipeline");
+
+    for model in MODELS {
+        let (circuit, public_inputs) = load_model(model);
+        // the same params on every run, such that runs are compared on the same srs
+        let params = ParamsKZG::<Bn256>::setup(
+            circuit.settings.run_args.logrows,
+            StdRng::from_seed([0; 32]),
+        );
+
+        group.bench_with_input(BenchmarkId::new("pk", model), &model, |b, &_| {
+            b.iter(|| {
+                create_keys::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(&circuit, &params)
+                    .unwrap();
+            });
+        });
+
+        let pk =
+            create_keys::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(&circuit, &params).unwrap();
+
+        let prove = || {
+            create_proof_circuit_kzg(
+                circuit.clone(),
+                &params,
+                public_inputs.clone(),
+                &pk,
+                TranscriptType::Blake,
+                SingleStrategy::new(&params),
+                CheckMode::UNSAFE,
+            )
+            .unwrap()
+        };
+
+        group.bench_with_input(BenchmarkId::new("prove", model), &model, |b, &_| {
+            b.iter(prove);
+        });
+
+        let snark = prove();
+        let verifier_params = params.verifier_params();
+
+        group.bench_with_input(BenchmarkId::new("verify", model), &model, |b, &_| {
+            b.iter(|| {
+                verify_proof_circuit_kzg(
+                    verifier_params,
+                    snark.clone(),
+                    pk.get_vk(),
+                    SingleStrategy::new(verifier_params),
+                )
+                .unwrap();
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+  name = benches;
+  config = Criterion::default().with_plots().sample_size(10);
+  targets = runpipeline
+}
+criterion_main!(benches);
@@ -177,6 +177,7 @@ where
                         bias: Some(self.l0_params[1].clone()),
                         padding: [(PADDING, PADDING); 2],
                         stride: (STRIDE, STRIDE),
+                        pad_value: F::zero(),
                     };
                     let x = config
                         .layer_config
@@ -0,0 +1,74 @@
+// Lays out a model with a node of a custom ONNX domain (`ai.example.Double`, see examples/onnx/1l_custom_op/gen.py)
+// through a user-defined circuit implementation, registered by domain and op type before the model is loaded.
+use ezkl::circuit::ops::base::BaseOp;
+use ezkl::circuit::region::RegionCtx;
+use ezkl::circuit::BaseConfig;
+use ezkl::fieldutils::felt_to_i128;
+use ezkl::graph::custom::{register_custom_op, CircuitOp};
+use ezkl::graph::input::GraphData;
+use ezkl::graph::{GraphCircuit, Visibility};
+use ezkl::tensor::{Tensor, TensorError, ValTensor};
+use ezkl::RunArgs;
+use halo2_proofs::dev::MockProver;
+use halo2curves::bn256::Fr as F;
+use std::error::Error;
+
+// x -> 2x, which doesn't change the scale of x
+#[derive(Debug)]
+struct Double;
+
+impl CircuitOp for Double {
+    fn eval_float(&self, x: f32) -> f32 {
+        2.0 * x
+    }
+
+    fn f(&self, inputs: &[Tensor<F>], _in_scales: &[u32]) -> Result<Tensor<F>, TensorError> {
+        inputs[0].clone() + inputs[0].clone()
+    }
+
+    fn layout(
+        &self,
+        config: &mut BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        values: &[ValTensor<F>],
+        _in_scales: &[u32],
+    ) -> Result<ValTensor<F>, Box<dyn Error>> {
+        let x = values[0].clone();
+        ezkl::circuit::layouts::pairwise(config, region, &[x.clone(), x], BaseOp::Add)
+    }
+
+    fn is_integer_exact(&self) -> bool {
+        true
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn Error>> {
+    register_custom_op("ai.example", "Double", |_attributes| Box::new(Double))?;
+
+    let run_args = RunArgs {
+        scale: 7,
+        bits: 16,
+        logrows: 17,
+        batch_size: 1,
+        sequence_length: 1,
+        output_visibility: Visibility::Public,
+        ..Default::default()
+    };
+    let mut circuit =
+        GraphCircuit::from_run_args(&run_args, &"examples/onnx/1l_custom_op/network.onnx".into())?;
+
+    let data = GraphData::from_path("examples/onnx/1l_custom_op/input.json".into())?;
+    let input = circuit.load_graph_input(&data).await?;
+    let witness = circuit.forward(&input)?;
+    for (x, y) in input[0].iter().zip(&witness.outputs[0]) {
+        assert_eq!(2 * felt_to_i128(*x), felt_to_i128(*y));
+    }
+
+    circuit.load_graph_witness(&witness)?;
+    let public_inputs = circuit.prepare_public_inputs(&witness)?;
+    let prover = MockProver::run(run_args.logrows, &circuit, public_inputs)?;
+    prover.assert_satisfied();
+    println!("custom op satisfied the circuit's constraints");
+    Ok(())
+}
@@ -0,0 +1,31 @@
+from onnx import helper, TensorProto
+import onnx
+import json
+import math
+import random
+
+
+def main():
+    # a float -> int cast truncating towards zero, then an int -> float cast which leaves the value as is
+    to_int = helper.make_node("Cast", ["input"], ["truncated"], name="to_int", to=TensorProto.INT64)
+    to_float = helper.make_node("Cast", ["truncated"], ["output"], name="to_float", to=TensorProto.FLOAT)
+    shape = ["batch_size", 3]
+    graph = helper.make_graph(
+        [to_int, to_float],
+        "cast",
+        [helper.make_tensor_value_info("input", TensorProto.FLOAT, shape)],
+        [helper.make_tensor_value_info("output", TensorProto.FLOAT, shape)],
+    )
+    model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 13)])
+    model.ir_version = 7
+    onnx.save(model, "network.onnx")
+
+    d = [round(random.uniform(-4, 4), 4) for _ in range(3)]
+    data = dict(input_data=[d], output_data=[[float(math.trunc(x)) for x in d]])
+
+    # Serialize data into file:
+    json.dump(data, open("input.json", 'w'))
+
+
+if __name__ == "__main__":
+    main()
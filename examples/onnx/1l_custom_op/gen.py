@@ -0,0 +1,32 @@
+from onnx import helper, TensorProto
+import onnx
+import json
+import random
+
+
+def main():
+    # a single node of a custom domain, which ezkl lays out through a registered CircuitOp (see examples/custom_op.rs)
+    node = helper.make_node("Double", ["input"], ["output"], name="double", domain="ai.example")
+    shape = ["batch_size", 3]
+    graph = helper.make_graph(
+        [node],
+        "custom_op",
+        [helper.make_tensor_value_info("input", TensorProto.FLOAT, shape)],
+        [helper.make_tensor_value_info("output", TensorProto.FLOAT, shape)],
+    )
+    model = helper.make_model(
+        graph,
+        opset_imports=[helper.make_opsetid("", 13), helper.make_opsetid("ai.example", 1)],
+    )
+    model.ir_version = 7
+    onnx.save(model, "network.onnx")
+
+    d = [round(random.uniform(-1, 1), 4) for _ in range(3)]
+    data = dict(input_data=[d], output_data=[[2 * x for x in d]])
+
+    # Serialize data into file:
+    json.dump(data, open("input.json", 'w'))
+
+
+if __name__ == "__main__":
+    main()
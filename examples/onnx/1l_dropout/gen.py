@@ -0,0 +1,28 @@
+import json
+
+import numpy as np
+import onnx
+from onnx import TensorProto, helper
+
+# a dropout (left in the exported graph rather than folded out) followed by a relu, dropout is the identity at inference
+nodes = [
+    helper.make_node("Dropout", ["input"], ["dropped"], name="dropout", ratio=0.5),
+    helper.make_node("Relu", ["dropped"], ["output"], name="relu"),
+]
+graph = helper.make_graph(
+    nodes,
+    "dropout",
+    [helper.make_tensor_value_info("input", TensorProto.FLOAT, ["batch_size", 3])],
+    [helper.make_tensor_value_info("output", TensorProto.FLOAT, ["batch_size", 3])],
+)
+model = helper.make_model(graph, producer_name="ezkl", opset_imports=[helper.make_opsetid("", 11)])
+model.ir_version = 6
+onnx.save(model, "network.onnx")
+
+x = np.array([[-0.40077725052833557, 2.493845224380493, 0.5796360969543457]])
+data = dict(
+    input_data=[x.flatten().tolist()],
+    input_shapes=[[3]],
+    output_data=[np.maximum(x, 0).flatten().tolist()],
+)
+json.dump(data, open("input.json", "w"), indent=4)
@@ -0,0 +1,35 @@
+import json
+
+import numpy as np
+import onnx
+from onnx import TensorProto, helper
+
+# splits a [batch_size, 6, 4] tensor along axis 1, both unevenly into [1, 2, 3] rows and evenly (without split sizes) into 3 parts
+uneven = ["uneven_0", "uneven_1", "uneven_2"]
+even = ["even_0", "even_1", "even_2"]
+nodes = [
+    helper.make_node("Split", ["input"], uneven, name="split_uneven", axis=1, split=[1, 2, 3]),
+    helper.make_node("Split", ["input"], even, name="split_even", axis=1),
+]
+outputs = [
+    helper.make_tensor_value_info(name, TensorProto.FLOAT, ["batch_size", rows, 4])
+    for name, rows in zip(uneven + even, [1, 2, 3, 2, 2, 2])
+]
+graph = helper.make_graph(
+    nodes,
+    "split",
+    [helper.make_tensor_value_info("input", TensorProto.FLOAT, ["batch_size", 6, 4])],
+    outputs,
+)
+model = helper.make_model(graph, producer_name="ezkl", opset_imports=[helper.make_opsetid("", 11)])
+model.ir_version = 6
+onnx.save(model, "network.onnx")
+
+x = np.array([((i * 5) % 17 - 8) / 8 for i in range(24)]).reshape(1, 6, 4)
+splits = np.split(x, [1, 3], axis=1) + np.split(x, 3, axis=1)
+data = dict(
+    input_data=[x.flatten().tolist()],
+    input_shapes=[[1, 6, 4]],
+    output_data=[s.flatten().tolist() for s in splits],
+)
+json.dump(data, open("input.json", "w"), indent=4)
@@ -0,0 +1,31 @@
+from onnx import helper, TensorProto
+import onnx
+import json
+
+# a batched matmul of [2, 3, 4] x [1, 4, 5] -> [2, 3, 5], the batch dimension of the second input is broadcast
+nodes = [
+    helper.make_node("MatMul", ["a", "b"], ["output"]),
+]
+
+graph = helper.make_graph(
+    nodes,
+    "batched_matmul",
+    [
+        helper.make_tensor_value_info("a", TensorProto.FLOAT, [2, 3, 4]),
+        helper.make_tensor_value_info("b", TensorProto.FLOAT, [1, 4, 5]),
+    ],
+    [helper.make_tensor_value_info("output", TensorProto.FLOAT, [2, 3, 5])],
+)
+
+model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 13)], ir_version=8)
+onnx.checker.check_model(model)
+onnx.save(model, "network.onnx")
+
+data_json = dict(
+    input_data=[
+        [float(i % 5 - 2) for i in range(24)],
+        [float(i % 3 - 1) for i in range(20)],
+    ]
+)
+
+json.dump(data_json, open("input.json", 'w'))
@@ -0,0 +1,30 @@
+from onnx import helper, TensorProto
+import onnx
+import json
+
+# sigmoid(relu(3 * x + 0.5)): a chain of elementwise ops on a single tensor, which fuse into one lookup
+three = helper.make_tensor("three", TensorProto.FLOAT, [], [3.0])
+half = helper.make_tensor("half", TensorProto.FLOAT, [], [0.5])
+
+nodes = [
+    helper.make_node("Mul", ["input", "three"], ["scaled"]),
+    helper.make_node("Add", ["scaled", "half"], ["shifted"]),
+    helper.make_node("Relu", ["shifted"], ["rectified"]),
+    helper.make_node("Sigmoid", ["rectified"], ["output"]),
+]
+
+graph = helper.make_graph(
+    nodes,
+    "elementwise_chain",
+    [helper.make_tensor_value_info("input", TensorProto.FLOAT, ["batch_size", 4])],
+    [helper.make_tensor_value_info("output", TensorProto.FLOAT, ["batch_size", 4])],
+    initializer=[three, half],
+)
+
+model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 13)], ir_version=8)
+onnx.checker.check_model(model)
+onnx.save(model, "network.onnx")
+
+data_json = dict(input_data=[[-2.0, -0.5, 0.0, 1.5]])
+
+json.dump(data_json, open("input.json", 'w'))
@@ -0,0 +1,37 @@
+from onnx import helper, TensorProto
+import onnx
+import json
+
+# every op indexes its axis from the end (axis=-1) of a [batch_size, 2, 3] input:
+# sum(gather(slice(concat(x, x), 1:4), [2, 0])) and softmax(x)
+starts = helper.make_tensor("starts", TensorProto.INT64, [1], [1])
+ends = helper.make_tensor("ends", TensorProto.INT64, [1], [4])
+axes = helper.make_tensor("axes", TensorProto.INT64, [1], [-1])
+indices = helper.make_tensor("indices", TensorProto.INT64, [2], [2, 0])
+
+nodes = [
+    helper.make_node("Concat", ["input", "input"], ["concatenated"], axis=-1),
+    helper.make_node("Slice", ["concatenated", "starts", "ends", "axes"], ["sliced"]),
+    helper.make_node("Gather", ["sliced", "indices"], ["gathered"], axis=-1),
+    helper.make_node("ReduceSum", ["gathered"], ["summed"], axes=[-1], keepdims=1),
+    helper.make_node("Softmax", ["input"], ["softmax"], axis=-1),
+]
+
+graph = helper.make_graph(
+    nodes,
+    "negative_axes",
+    [helper.make_tensor_value_info("input", TensorProto.FLOAT, ["batch_size", 2, 3])],
+    [
+        helper.make_tensor_value_info("summed", TensorProto.FLOAT, ["batch_size", 2, 1]),
+        helper.make_tensor_value_info("softmax", TensorProto.FLOAT, ["batch_size", 2, 3]),
+    ],
+    initializer=[starts, ends, axes, indices],
+)
+
+model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 11)], ir_version=8)
+onnx.checker.check_model(model)
+onnx.save(model, "network.onnx")
+
+data_json = dict(input_data=[[0.0, 0.25, 0.5, 0.75, 1.0, 1.25]])
+
+json.dump(data_json, open("input.json", 'w'))
@@ -0,0 +1,26 @@
+from onnx import helper, TensorProto
+import onnx
+import json
+
+# RMSNormalization (opset 23) over the last axis: x / sqrt(mean(x^2) + epsilon) * scale
+scale = helper.make_tensor("scale", TensorProto.FLOAT, [4], [1.0, 0.5, 2.0, 1.0])
+
+nodes = [
+    helper.make_node("RMSNormalization", ["input", "scale"], ["output"], axis=-1, epsilon=1e-5),
+]
+
+graph = helper.make_graph(
+    nodes,
+    "rms_norm",
+    [helper.make_tensor_value_info("input", TensorProto.FLOAT, ["batch_size", 4])],
+    [helper.make_tensor_value_info("output", TensorProto.FLOAT, ["batch_size", 4])],
+    initializer=[scale],
+)
+
+model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 23)], ir_version=10)
+onnx.checker.check_model(model)
+onnx.save(model, "network.onnx")
+
+data_json = dict(input_data=[[-2.0, -0.5, 0.0, 1.5]])
+
+json.dump(data_json, open("input.json", 'w'))
@@ -0,0 +1,32 @@
+from onnx import helper, TensorProto
+import onnx
+import json
+
+# a single-layer RNN (tanh activation) over a symbolic `sequence_length` axis, with fixed weights such that
+# the hidden states are reproducible: h_t = tanh(W x_t + R h_{t-1} + Wb + Rb)
+hidden_size = 2
+input_size = 2
+
+W = helper.make_tensor("W", TensorProto.FLOAT, [1, hidden_size, input_size], [0.5, -0.25, 0.25, 0.5])
+R = helper.make_tensor("R", TensorProto.FLOAT, [1, hidden_size, hidden_size], [0.5, 0.0, -0.25, 0.5])
+B = helper.make_tensor("B", TensorProto.FLOAT, [1, 2 * hidden_size], [0.125, 0.0, 0.0, -0.125])
+
+node = helper.make_node("RNN", ["input", "W", "R", "B"], ["output"], hidden_size=hidden_size)
+
+graph = helper.make_graph(
+    [node],
+    "rnn_sequence",
+    [helper.make_tensor_value_info("input", TensorProto.FLOAT, ["sequence_length", 1, input_size])],
+    [helper.make_tensor_value_info(
+        "output", TensorProto.FLOAT, ["sequence_length", 1, 1, hidden_size])],
+    initializer=[W, R, B],
+)
+
+model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 13)], ir_version=8)
+onnx.checker.check_model(model)
+onnx.save(model, "network.onnx")
+
+# three steps
+data_json = dict(input_data=[[0.5, -0.5, 0.25, 0.75, -1.0, 0.5]])
+
+json.dump(data_json, open("input.json", 'w'))
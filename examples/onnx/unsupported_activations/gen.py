@@ -0,0 +1,24 @@
+from onnx import helper, TensorProto
+import onnx
+import json
+
+# gelu(mish(x)): activations without a circuit implementation, which the unsupported op policy skips or approximates
+nodes = [
+    helper.make_node("Mish", ["input"], ["mish"]),
+    helper.make_node("Gelu", ["mish"], ["output"]),
+]
+
+graph = helper.make_graph(
+    nodes,
+    "unsupported_activations",
+    [helper.make_tensor_value_info("input", TensorProto.FLOAT, ["batch_size", 4])],
+    [helper.make_tensor_value_info("output", TensorProto.FLOAT, ["batch_size", 4])],
+)
+
+model = helper.make_model(graph, opset_imports=[helper.make_opsetid("", 20)], ir_version=9)
+onnx.checker.check_model(model)
+onnx.save(model, "network.onnx")
+
+data_json = dict(input_data=[[-2.0, -0.5, 0.0, 1.5]])
+
+json.dump(data_json, open("input.json", 'w'))
@@ -0,0 +1,47 @@
+//! Feeds arbitrary (possibly malformed) input data to a model, run with `cargo fuzz run graph_input`.
+//! Malformed data (NaN or infinite floats, the wrong number of inputs or elements, ...) must surface as errors, so any
+//! panic is a bug.
+#![no_main]
+
+use ezkl::graph::input::GraphData;
+use ezkl::graph::{DataSource, GraphCircuit};
+use ezkl::RunArgs;
+use libfuzzer_sys::fuzz_target;
+use std::sync::OnceLock;
+
+fn circuit() -> &'static GraphCircuit {
+    static CIRCUIT: OnceLock<GraphCircuit> = OnceLock::new();
+    CIRCUIT.get_or_init(|| {
+        let run_args = RunArgs {
+            scale: 7,
+            bits: 16,
+            logrows: 17,
+            batch_size: 1,
+            sequence_length: 1,
+            ..Default::default()
+        };
+        let model = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../examples/onnx/1l_mlp/network.onnx"
+        );
+        GraphCircuit::from_run_args(&run_args, &model.into()).unwrap()
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let data: GraphData = match serde_json::from_slice(data) {
+        Ok(data) => data,
+        Err(_) => return,
+    };
+    let file_data = match data.input_data {
+        DataSource::File(file_data) => file_data,
+        _ => return,
+    };
+
+    let mut circuit = circuit().clone();
+    let shapes = circuit.model.graph.input_shapes();
+    let scales = vec![circuit.settings.run_args.scale; shapes.len()];
+    if let Ok(inputs) = circuit.load_file_data(&file_data, &shapes, scales) {
+        let _ = circuit.forward(&inputs);
+    }
+});
@@ -1,7 +1,5 @@
 // ignore file if compiling for wasm
 
-#[cfg(not(target_arch = "wasm32"))]
-use clap::Parser;
 #[cfg(not(target_arch = "wasm32"))]
 use colored_json::ToColoredJson;
 #[cfg(not(target_arch = "wasm32"))]
@@ -9,26 +7,52 @@ use ezkl::commands::Cli;
 #[cfg(not(target_arch = "wasm32"))]
 use ezkl::execute::run;
 #[cfg(not(target_arch = "wasm32"))]
-use ezkl::logger::init_logger;
+use ezkl::logger::init_logger_with_target;
 #[cfg(not(target_arch = "wasm32"))]
-use log::{error, info};
+use log::{error, info, LevelFilter};
 #[cfg(not(target_arch = "wasm32"))]
 use rand::prelude::SliceRandom;
 #[cfg(not(target_arch = "wasm32"))]
 use std::error::Error;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
 
 #[tokio::main(flavor = "current_thread")]
 #[cfg(not(target_arch = "wasm32"))]
 pub async fn main() -> Result<(), Box<dyn Error>> {
-    let args = Cli::parse();
-    init_logger();
-    banner();
-    info!("command: \n {}", &args.as_json()?.to_colored_json_auto()?);
+    let args = Cli::parse_with_config();
+    let level = match args.quiet {
+        true => LevelFilter::Warn,
+        false => LevelFilter::Info,
+    };
+    if args.json {
+        // keep stdout free for the structured result
+        colored::control::set_override(false);
+        init_logger_with_target(env_logger::Target::Stderr, level);
+    } else {
+        init_logger_with_target(env_logger::Target::Stdout, level);
+        banner();
+    }
+    let command = args.as_json()?;
+    info!("command: \n {}", &command.to_colored_json_auto()?);
+    let json = args.json;
+    let start = Instant::now();
     let res = run(args).await;
     match &res {
         Ok(_) => info!("succeeded"),
         Err(e) => error!("failed: {}", e),
     };
+    if json {
+        let command: serde_json::Value = serde_json::from_str(&command)?;
+        let result = serde_json::json!({
+            "success": res.is_ok(),
+            "error": res.as_ref().err().map(|e| e.to_string()),
+            "elapsed_secs": start.elapsed().as_secs_f64(),
+            // the command's arguments, including any paths written to
+            "command": command["command"],
+        });
+        println!("{}", result);
+    }
     res
 }
 
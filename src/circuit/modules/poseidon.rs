@@ -16,6 +16,7 @@ use halo2_proofs::{circuit::*, plonk::*};
 // use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator};
 use rayon::prelude::ParallelIterator;
 use rayon::slice::ParallelSlice;
+use serde::{Deserialize, Serialize};
 
 use std::marker::PhantomData;
 
@@ -81,6 +82,83 @@ impl<S: Spec<Fp, WIDTH, RATE> + Sync, const WIDTH: usize, const RATE: usize, con
     }
 }
 
+/// A path from a leaf of the L-ary Poseidon tree (as hashed by the [PoseidonChip]) to its root.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MerklePath {
+    /// the leaf being revealed
+    pub leaf: Fp,
+    /// the index of the leaf in the hashed message
+    pub index: usize,
+    /// the (zero padded) block of L siblings hashed at each level of the tree, from the leaves up
+    pub blocks: Vec<Vec<Fp>>,
+}
+
+impl<S: Spec<Fp, WIDTH, RATE> + Sync, const WIDTH: usize, const RATE: usize, const L: usize>
+    PoseidonChip<S, WIDTH, RATE, L>
+{
+    /// Hashes a (zero padded) block of L elements
+    fn hash_block(block: &[Fp]) -> Fp {
+        let mut block = block.to_vec();
+        let remainder = block.len() % L;
+
+        if remainder != 0 {
+            block.extend(vec![Fp::ZERO; L - remainder].iter());
+        }
+        halo2_gadgets::poseidon::primitives::Hash::<_, S, ConstantLength<L>, { WIDTH }, { RATE }>::init()
+            .hash(block.try_into().unwrap())
+    }
+
+    /// Produces the Merkle path for the leaf at `index` of `message`, whose root is the output of [Module::run].
+    pub fn merkle_path(
+        message: &[Fp],
+        index: usize,
+    ) -> Result<MerklePath, Box<dyn std::error::Error>> {
+        if index >= message.len() {
+            return Err(format!(
+                "leaf index {} out of range for a message of length {}",
+                index,
+                message.len()
+            )
+            .into());
+        }
+
+        let mut level = message.to_vec();
+        let mut idx = index;
+        let mut blocks = vec![];
+
+        while level.len() > 1 {
+            let start = (idx / L) * L;
+            let end = std::cmp::min(start + L, level.len());
+            let mut block = level[start..end].to_vec();
+            block.extend(vec![Fp::ZERO; L - block.len()]);
+            blocks.push(block);
+
+            level = level.par_chunks(L).map(Self::hash_block).collect();
+            idx /= L;
+        }
+
+        Ok(MerklePath {
+            leaf: message[index],
+            index,
+            blocks,
+        })
+    }
+
+    /// Checks that a [MerklePath] leads to `root`.
+    pub fn verify_merkle_path(path: &MerklePath, root: Fp) -> bool {
+        let mut node = path.leaf;
+        let mut idx = path.index;
+        for block in &path.blocks {
+            if block.len() != L || block[idx % L] != node {
+                return false;
+            }
+            node = Self::hash_block(block);
+            idx /= L;
+        }
+        node == root
+    }
+}
+
 impl<S: Spec<Fp, WIDTH, RATE> + Sync, const WIDTH: usize, const RATE: usize, const L: usize>
     Module<Fp> for PoseidonChip<S, WIDTH, RATE, L>
 {
@@ -317,25 +395,7 @@ impl<S: Spec<Fp, WIDTH, RATE> + Sync, const WIDTH: usize, const RATE: usize, con
 
         // do the Tree dance baby
         while hash_inputs.len() > 1 {
-            let hashes: Vec<Fp> = hash_inputs
-                .par_chunks(L)
-                .map(|block| {
-                    let mut block = block.to_vec();
-                    let remainder = block.len() % L;
-
-                    if remainder != 0 {
-                        block.extend(vec![Fp::ZERO; L - remainder].iter());
-                    }
-                    halo2_gadgets::poseidon::primitives::Hash::<
-                        _,
-                        S,
-                        ConstantLength<L>,
-                        { WIDTH },
-                        { RATE },
-                    >::init()
-                    .hash(block.clone().try_into().unwrap())
-                })
-                .collect();
+            let hashes: Vec<Fp> = hash_inputs.par_chunks(L).map(Self::hash_block).collect();
 
             hash_inputs = hashes;
         }
@@ -384,7 +444,7 @@ mod tests {
         circuit::{Layouter, SimpleFloorPlanner, Value},
         plonk::{Circuit, ConstraintSystem},
     };
-    use halo2curves::ff::Field;
+    use halo2curves::ff::{Field, PrimeField};
 
     const WIDTH: usize = POSEIDON_WIDTH;
     const RATE: usize = POSEIDON_RATE;
@@ -449,6 +509,71 @@ mod tests {
         assert_eq!(prover.verify_par(), Ok(()))
     }
 
+    /// An off-circuit implementation of the Poseidon sponge, written straight from the spec's constants rather than
+    /// with halo2_gadgets, that the in-circuit hash is checked against.
+    fn reference_hash(message: &[Fp]) -> Fp {
+        let (round_constants, mds, _) = PoseidonSpec::constants();
+        let r_f = PoseidonSpec::full_rounds() / 2;
+        let r_p = PoseidonSpec::partial_rounds();
+        let permute = |state: &mut [Fp; WIDTH]| {
+            for (round, constants) in round_constants.iter().enumerate() {
+                for (word, c) in state.iter_mut().zip(constants) {
+                    *word += c;
+                }
+                if round < r_f || round >= r_f + r_p {
+                    for word in state.iter_mut() {
+                        *word = PoseidonSpec::sbox(*word);
+                    }
+                } else {
+                    state[0] = PoseidonSpec::sbox(state[0]);
+                }
+                let mut mixed = [Fp::ZERO; WIDTH];
+                for (i, word) in mixed.iter_mut().enumerate() {
+                    for (j, s) in state.iter().enumerate() {
+                        *word += mds[i][j] * s;
+                    }
+                }
+                *state = mixed;
+            }
+        };
+
+        // a constant length domain: the capacity element encodes the message length, and with a rate of 1 each
+        // element is absorbed by its own permutation
+        let mut state = [Fp::ZERO; WIDTH];
+        state[RATE] = Fp::from_u128((message.len() as u128) << 64);
+        for m in message {
+            state[0] += m;
+            permute(&mut state);
+        }
+        state[0]
+    }
+
+    #[test]
+    fn poseidon_hash_matches_reference() {
+        let rng = rand::rngs::OsRng;
+
+        let message = [Fp::random(rng), Fp::random(rng), Fp::random(rng)];
+        let expected = reference_hash(&message);
+        let output = PoseidonChip::<PoseidonSpec, WIDTH, RATE, 3>::run(message.to_vec()).unwrap();
+        assert_eq!(output, vec![vec![expected]]);
+
+        // the hash constrained in-circuit is the reference's
+        let message: Tensor<ValType<Fp>> =
+            message.into_iter().map(|m| Value::known(m).into()).into();
+        let circuit = HashCircuit::<PoseidonSpec, 3> {
+            message: message.into(),
+            _spec: PhantomData,
+        };
+        let prover = halo2_proofs::dev::MockProver::run(9, &circuit, vec![vec![expected]]).unwrap();
+        assert_eq!(prover.verify_par(), Ok(()));
+
+        // and a different digest isn't accepted
+        let prover =
+            halo2_proofs::dev::MockProver::run(9, &circuit, vec![vec![expected + Fp::ONE]])
+                .unwrap();
+        assert!(prover.verify_par().is_err());
+    }
+
     #[test]
     fn poseidon_hash_longer_input() {
         let rng = rand::rngs::OsRng;
@@ -468,6 +593,26 @@ mod tests {
         assert_eq!(prover.verify_par(), Ok(()))
     }
 
+    #[test]
+    fn poseidon_merkle_paths() {
+        let rng = rand::rngs::OsRng;
+
+        let message: Vec<Fp> = (0..10).map(|_| Fp::random(rng)).collect::<Vec<_>>();
+        let root =
+            PoseidonChip::<PoseidonSpec, WIDTH, RATE, 3>::run(message.clone()).unwrap()[0][0];
+
+        for i in 0..message.len() {
+            let mut path =
+                PoseidonChip::<PoseidonSpec, WIDTH, RATE, 3>::merkle_path(&message, i).unwrap();
+            assert!(PoseidonChip::<PoseidonSpec, WIDTH, RATE, 3>::verify_merkle_path(&path, root));
+            // a tampered leaf should not verify
+            path.leaf += Fp::ONE;
+            assert!(!PoseidonChip::<PoseidonSpec, WIDTH, RATE, 3>::verify_merkle_path(&path, root));
+        }
+
+        assert!(PoseidonChip::<PoseidonSpec, WIDTH, RATE, 3>::merkle_path(&message, 10).is_err());
+    }
+
     #[test]
     #[ignore]
     fn hash_for_a_range_of_input_sizes() {
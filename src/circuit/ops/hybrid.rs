@@ -25,10 +25,29 @@ pub enum HybridOp {
     ReduceMin {
         axes: Vec<usize>,
     },
+    ReduceArgMax {
+        axis: usize,
+    },
+    ReduceArgMin {
+        axis: usize,
+    },
     Softmax {
         scales: (usize, usize),
     },
+    /// RMS normalization over the last axis, of length `dim`, with `scales` the input and output multipliers
+    RmsNorm {
+        scales: (usize, usize),
+        dim: usize,
+        eps: circuit::utils::F32,
+    },
     RangeCheck(Tolerance),
+    Greater,
+    Less,
+    Equal,
+    /// division by a variable tensor, where `scale` is the multiplier of the denominator's scale
+    Div {
+        scale: usize,
+    },
 }
 
 impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
@@ -53,10 +72,41 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
                 vec![],
             ),
             HybridOp::ReduceMin { axes, .. } => (tensor::ops::min_axes(&x, axes)?, vec![]),
+            HybridOp::ReduceArgMax { axis } => (tensor::ops::argmax_axes(&x, *axis)?, vec![]),
+            HybridOp::ReduceArgMin { axis } => (tensor::ops::argmin_axes(&x, *axis)?, vec![]),
             HybridOp::Softmax { scales } => {
                 tensor::ops::nonlinearities::multi_dim_softmax(&x, scales.0, scales.1)
             }
+            HybridOp::RmsNorm { scales, eps, .. } => {
+                tensor::ops::nonlinearities::multi_dim_rms_norm(&x, scales.0, scales.1, eps.0)
+            }
             HybridOp::RangeCheck(..) => (x, vec![]),
+            HybridOp::Greater | HybridOp::Less | HybridOp::Equal => {
+                let y = inputs[1].clone().map(|x| felt_to_i128(x));
+                let res = match self {
+                    HybridOp::Greater => tensor::ops::greater(&x, &y)?,
+                    HybridOp::Less => tensor::ops::less(&x, &y)?,
+                    _ => tensor::ops::equal(&x, &y)?,
+                };
+                // the differences are looked up in the greater than table
+                let diff = (x - y)?;
+                (res, vec![diff.clone(), -diff])
+            }
+            HybridOp::Div { scale } => {
+                let y = inputs[1].clone().map(|x| felt_to_i128(x));
+                let res = tensor::ops::div(&x, &y, *scale)?;
+                // the quotient, the denominators and the bounds on the (doubled) remainders are looked up in the
+                // relu table, see [layouts::div]
+                let numerator = x.map(|x| x * *scale as i128);
+                let twice_remainder = (numerator - (res.clone() * y.clone())?)?.map(|r| 2 * r);
+                let abs_y = y.map(|y| y.abs());
+                let excess = (twice_remainder.clone() - abs_y.clone())?;
+                let deficit = (twice_remainder + abs_y.clone())?.map(|v| 1 - v);
+                (
+                    res.clone(),
+                    vec![res, y.clone(), -y, excess, deficit, abs_y.map(|v| 1 - v)],
+                )
+            }
         };
 
         // convert back to felt
@@ -74,8 +124,15 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
             HybridOp::ReduceMax { .. } => "REDUCEMAX",
             HybridOp::MaxPool2d { .. } => "MAXPOOL2D",
             HybridOp::ReduceMin { .. } => "REDUCEMIN",
+            HybridOp::ReduceArgMax { .. } => "REDUCEARGMAX",
+            HybridOp::ReduceArgMin { .. } => "REDUCEARGMIN",
             HybridOp::Softmax { .. } => "SOFTMAX",
+            HybridOp::RmsNorm { .. } => "RMSNORM",
             HybridOp::RangeCheck(..) => "RANGECHECK",
+            HybridOp::Greater => "GREATER",
+            HybridOp::Less => "LESS",
+            HybridOp::Equal => "EQUAL",
+            HybridOp::Div { .. } => "DIV",
         };
         name.into()
     }
@@ -106,6 +163,12 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
             HybridOp::ReduceMin { axes } => {
                 layouts::min_axes(config, region, values[..].try_into()?, axes)?
             }
+            HybridOp::ReduceArgMax { axis } => {
+                layouts::argmax_axes(config, region, values[..].try_into()?, *axis)?
+            }
+            HybridOp::ReduceArgMin { axis } => {
+                layouts::argmin_axes(config, region, values[..].try_into()?, *axis)?
+            }
             HybridOp::Softmax { scales } => layouts::multi_dim_softmax(
                 config,
                 region,
@@ -113,6 +176,14 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
                 scales.0,
                 scales.1,
             )?,
+            HybridOp::RmsNorm { scales, eps, .. } => layouts::multi_dim_rms_norm(
+                config,
+                region,
+                values[..].try_into()?,
+                scales.0,
+                scales.1,
+                eps.0,
+            )?,
             HybridOp::RangeCheck(tol) => layouts::range_check_percent(
                 config,
                 region,
@@ -121,12 +192,23 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
                 tol.scales.1,
                 tol.val,
             )?,
+            HybridOp::Greater => layouts::greater(config, region, values[..].try_into()?)?,
+            HybridOp::Less => layouts::less(config, region, values[..].try_into()?)?,
+            HybridOp::Equal => layouts::equal(config, region, values[..].try_into()?)?,
+            HybridOp::Div { scale } => {
+                layouts::div(config, region, values[..].try_into()?, *scale)?
+            }
         }))
     }
 
     fn out_scale(&self, in_scales: Vec<u32>, global_scale: u32) -> u32 {
         match self {
             HybridOp::Softmax { .. } => 2 * global_scale,
+            HybridOp::RmsNorm { .. } => in_scales[0] + global_scale,
+            // indices are integers
+            HybridOp::ReduceArgMax { .. } | HybridOp::ReduceArgMin { .. } => 0,
+            // comparisons output booleans
+            HybridOp::Greater | HybridOp::Less | HybridOp::Equal => 0,
             _ => in_scales[0],
         }
     }
@@ -139,16 +221,38 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
                     scale_to_multiplier(global_scale) as usize,
                 ),
             }),
+            HybridOp::RmsNorm { dim, eps, .. } => Box::new(HybridOp::RmsNorm {
+                scales: (
+                    scale_to_multiplier(input_scales[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+                dim: *dim,
+                eps: *eps,
+            }),
+            // the quotient keeps the scale of the numerator
+            HybridOp::Div { .. } => Box::new(HybridOp::Div {
+                scale: scale_to_multiplier(input_scales[1]) as usize,
+            }),
             _ => Box::new(self.clone()),
         }
     }
 
+    fn requires_homogenous_input_scales(&self) -> Vec<usize> {
+        match self {
+            HybridOp::Greater | HybridOp::Less | HybridOp::Equal => vec![0, 1],
+            _ => vec![],
+        }
+    }
+
     fn required_lookups(&self) -> Vec<LookupOp> {
         match self {
             HybridOp::ReduceMax { .. }
             | HybridOp::ReduceMin { .. }
+            | HybridOp::ReduceArgMax { .. }
+            | HybridOp::ReduceArgMin { .. }
             | HybridOp::MaxPool2d { .. }
-            | HybridOp::Abs => Op::<F>::required_lookups(&LookupOp::ReLU { scale: 1 }),
+            | HybridOp::Abs
+            | HybridOp::Div { .. } => Op::<F>::required_lookups(&LookupOp::ReLU { scale: 1 }),
             HybridOp::Softmax { scales } => {
                 vec![
                     LookupOp::Exp { scales: *scales },
@@ -157,6 +261,16 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
                     },
                 ]
             }
+            HybridOp::RmsNorm { scales, dim, .. } => {
+                vec![
+                    LookupOp::Div {
+                        denom: circuit::utils::F32(scales.0 as f32),
+                    },
+                    LookupOp::Rsqrt {
+                        scales: (scales.0 * dim, scales.1),
+                    },
+                ]
+            }
             HybridOp::RangeCheck(tol) => {
                 let mut lookups = vec![];
                 if tol.val > 0.0 {
@@ -170,6 +284,11 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
                 }
                 lookups
             }
+            HybridOp::Greater | HybridOp::Less | HybridOp::Equal => {
+                vec![LookupOp::GreaterThan {
+                    a: circuit::utils::F32(0.),
+                }]
+            }
         }
     }
 
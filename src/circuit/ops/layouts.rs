@@ -1,8 +1,5 @@
 use core::panic;
-use std::{
-    collections::{HashMap, HashSet},
-    error::Error,
-};
+use std::{collections::HashSet, error::Error};
 
 use halo2_proofs::circuit::Value;
 use halo2curves::ff::PrimeField;
@@ -21,8 +18,9 @@ use crate::{
         get_broadcasted_shape,
         ops::{
             accumulated, add, conv as non_accum_conv, deconv as non_accum_deconv,
-            dot as non_accum_dot, einsum as non_accum_einsum, max_pool2d as non_accum_max_pool2d,
-            mult, pack as non_accum_pack, sub, sum as non_accum_sum, sumpool as non_accum_sumpool,
+            dot as non_accum_dot, einsum as non_accum_einsum, gather as non_accum_gather,
+            linear_resize_taps, max_pool2d as non_accum_max_pool2d, mult, pack as non_accum_pack,
+            sub, sum as non_accum_sum, sumpool as non_accum_sumpool, PadMode, ResizeCoordTransform,
         },
         Tensor, TensorError, ValType,
     },
@@ -202,15 +200,15 @@ pub fn einsum<F: PrimeField + TensorType + PartialOrd>(
         return Err(Box::new(TensorError::DimMismatch("einsum".to_string())));
     }
 
-    let mut indices_to_size = HashMap::new();
-    for (i, input) in inputs.iter().enumerate() {
-        for j in 0..inputs_eq[i].len() {
-            let c = inputs_eq[i].chars().nth(j).unwrap();
-            if let std::collections::hash_map::Entry::Vacant(e) = indices_to_size.entry(c) {
-                e.insert(input.dims()[j]);
-            } else if indices_to_size[&c] != input.dims()[j] {
-                return Err(Box::new(TensorError::DimMismatch("einsum".to_string())));
-            }
+    let mut indices_to_size = crate::tensor::ops::einsum_index_sizes(
+        &inputs_eq,
+        &inputs.iter().map(|input| input.dims()).collect::<Vec<_>>(),
+    )?;
+    // broadcast dimensions of 1, e.g the batch dimensions of a batched matmul
+    for (input, eq) in inputs.iter_mut().zip(&inputs_eq) {
+        let dims = eq.chars().map(|c| indices_to_size[&c]).collect::<Vec<_>>();
+        if input.dims() != dims.as_slice() {
+            input.expand(&dims)?;
         }
     }
 
@@ -871,6 +869,7 @@ pub fn sumpool<F: PrimeField + TensorType + PartialOrd>(
             &[input, kernel.clone().into()],
             padding,
             stride,
+            F::ZERO,
         )
         .unwrap();
         res.push(output);
@@ -1083,7 +1082,7 @@ pub fn deconv<F: PrimeField + TensorType + PartialOrd + std::marker::Send + std:
         vec![sliced_expanded_image, deconv_kernel.clone().into()]
     };
 
-    let output = conv(config, region, &conv_input, [(0, 0); 2], (1, 1))?;
+    let output = conv(config, region, &conv_input, [(0, 0); 2], (1, 1), F::ZERO)?;
 
     if matches!(&config.check_mode, CheckMode::SAFE) {
         // during key generation this will be unknown vals so we use this as a flag to check
@@ -1124,6 +1123,7 @@ pub fn conv<F: PrimeField + TensorType + PartialOrd + std::marker::Send + std::m
     values: &[ValTensor<F>],
     padding: [(usize, usize); 2],
     stride: (usize, usize),
+    pad_value: F,
 ) -> Result<ValTensor<F>, Box<dyn Error>> {
     let has_bias = values.len() == 3;
     let (mut image, mut kernel) = (values[0].clone(), values[1].clone());
@@ -1167,7 +1167,7 @@ pub fn conv<F: PrimeField + TensorType + PartialOrd + std::marker::Send + std::m
     let kernel_dims = kernel.dims();
 
     let mut padded_image = image.clone();
-    padded_image.pad(padding)?;
+    padded_image.pad_with_value(padding, pad_value)?;
 
     let (batch_size, output_channels, input_channels, kernel_height, kernel_width) = (
         image_dims[0],
@@ -1288,6 +1288,7 @@ pub fn conv<F: PrimeField + TensorType + PartialOrd + std::marker::Send + std::m
                     .collect::<Vec<Tensor<_>>>(),
                 padding,
                 stride,
+                Value::known(pad_value),
             )
             .map_err(|e| {
                 error!("{}", e);
@@ -1460,6 +1461,74 @@ pub fn resize<F: PrimeField + TensorType + PartialOrd>(
     Ok(output)
 }
 
+/// linear (e.g bilinear) resize layout, each resized axis is a weighted sum of the two nearest neighbours.
+/// The quantized interpolation weights are fixed constants of the circuit.
+pub fn resize_linear<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    scales: &[usize],
+    transform: ResizeCoordTransform,
+    weight_scale: u32,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let mut output = values[0].clone();
+    for (axis, scale) in scales.iter().enumerate() {
+        if *scale == 1 {
+            continue;
+        }
+        let taps = linear_resize_taps(output.dims()[axis], *scale, transform, weight_scale);
+
+        let inner = output.get_inner_tensor()?;
+        let lo: ValTensor<F> =
+            non_accum_gather(&inner, axis, &Tensor::from(taps.iter().map(|t| t.0)))?.into();
+        let hi: ValTensor<F> =
+            non_accum_gather(&inner, axis, &Tensor::from(taps.iter().map(|t| t.1)))?.into();
+
+        let dims = lo.dims().to_vec();
+        let stride = dims[axis + 1..].iter().product::<usize>();
+        let len = dims[axis];
+        let weights =
+            |w: fn(&(usize, usize, i128, i128)) -> i128| -> Result<ValTensor<F>, Box<dyn Error>> {
+                let mut t: Tensor<ValType<F>> = Tensor::from(
+                    (0..dims.iter().product::<usize>())
+                        .map(|i| ValType::Constant(i128_to_felt(w(&taps[(i / stride) % len])))),
+                );
+                t.reshape(&dims);
+                Ok(t.into())
+            };
+        let w_lo = weights(|t| t.2)?;
+        let w_hi = weights(|t| t.3)?;
+
+        let lo = pairwise(config, region, &[lo, w_lo], BaseOp::Mult)?;
+        let hi = pairwise(config, region, &[hi, w_hi], BaseOp::Mult)?;
+        output = pairwise(config, region, &[lo, hi], BaseOp::Add)?;
+    }
+
+    Ok(output)
+}
+
+/// Pad layout
+pub fn pad<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    pads: &[(usize, usize)],
+    mode: &PadMode<F>,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let mut output = match mode {
+        // reflected values are duplicated, so we assign them first such that the copies are constrained to be equal
+        PadMode::Reflect => {
+            let output = region.assign(&config.output, &values[0])?;
+            region.increment(output.len());
+            output
+        }
+        PadMode::Constant(_) => values[0].clone(),
+    };
+    output.pad_axes(pads, &mode.map(|fill| ValType::Constant(*fill)))?;
+
+    Ok(output)
+}
+
 /// Slice layout
 pub fn slice<F: PrimeField + TensorType + PartialOrd>(
     config: &BaseConfig<F>,
@@ -1658,6 +1727,134 @@ pub fn abs<F: PrimeField + TensorType + PartialOrd>(
     Ok(abs)
 }
 
+/// greater than (a > b) layout, outputting a boolean (0/1) tensor. The inputs are broadcast against one another.
+pub fn greater<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    // a > b iff a - b is strictly positive
+    let diff = pairwise(config, region, values, BaseOp::Sub)?;
+    nonlinearity(
+        config,
+        region,
+        &[diff],
+        &LookupOp::GreaterThan { a: utils::F32(0.) },
+    )
+}
+
+/// less than (a < b) layout, outputting a boolean (0/1) tensor. The inputs are broadcast against one another.
+pub fn less<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    greater(config, region, &[values[1].clone(), values[0].clone()])
+}
+
+/// equality (a == b) layout, outputting a boolean (0/1) tensor. The inputs are broadcast against one another.
+pub fn equal<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let greater_mask = greater(config, region, values)?;
+    let less_mask = less(config, region, values)?;
+
+    let unit: ValTensor<F> =
+        Tensor::from(vec![region.assign_constant(&config.inputs[1], F::from(1))?].into_iter())
+            .into();
+    region.next();
+
+    // a == b iff neither a > b nor a < b
+    let not_greater = pairwise(config, region, &[unit, greater_mask], BaseOp::Sub)?;
+    pairwise(config, region, &[not_greater, less_mask], BaseOp::Sub)
+}
+
+/// division layout for a variable denominator: `round(x * scale / y)`, with the inputs broadcast against one
+/// another, see [crate::tensor::ops::div]. The quotient `q` is witnessed and constrained by its remainder
+/// `r = x * scale - q * y` satisfying `-|y| < 2r <= |y|`, with `|y| >= 1`. The interval is half open such that exactly
+/// one quotient satisfies it, including when `x * scale / y` is halfway between two integers. The quotient must fit
+/// in the lookup range, which also rules out the non-integral quotients (field elements) that would satisfy the
+/// remainder constraint.
+///
+/// Per element this costs 6 lookups (one bounding `q`, two for `|y|` and one for each inequality), 2 multiplications
+/// and a handful of additions, where dividing by a constant is a single lookup.
+pub fn div<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+    scale: usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let (mut x, mut y) = (values[0].clone(), values[1].clone());
+    let shape = get_broadcasted_shape(x.dims(), y.dims())?;
+    x.expand(&shape)?;
+    y.expand(&shape)?;
+
+    let mult: ValTensor<F> =
+        Tensor::from(vec![ValType::Constant(F::from(scale as u64))].into_iter()).into();
+    let x = pairwise(config, region, &[x, mult], BaseOp::Mult)?;
+
+    // this is safe because we later constrain it
+    let is_assigned = !x.any_unknowns() && !y.any_unknowns();
+    let mut quotient: Tensor<Value<F>> = if is_assigned {
+        let q = crate::tensor::ops::div(&x.get_int_evals()?, &y.get_int_evals()?, 1)?;
+        q.map(|q| Value::known(i128_to_felt::<F>(q)))
+    } else {
+        Tensor::from(vec![Value::<F>::unknown(); shape.iter().product()].into_iter())
+    };
+    quotient.reshape(&shape);
+
+    let quotient = region.assign(&config.inputs[1], &quotient.into())?;
+    region.increment(quotient.len());
+
+    // the table only holds inputs within the lookup range
+    nonlinearity(
+        config,
+        region,
+        &[quotient.clone()],
+        &LookupOp::ReLU { scale: 1 },
+    )?;
+
+    let product = pairwise(config, region, &[quotient.clone(), y.clone()], BaseOp::Mult)?;
+    let remainder = pairwise(config, region, &[x, product], BaseOp::Sub)?;
+    let twice_remainder = pairwise(config, region, &[remainder.clone(), remainder], BaseOp::Add)?;
+    let abs_y = abs(config, region, &[y])?;
+
+    let unit: ValTensor<F> =
+        Tensor::from(vec![region.assign_constant(&config.inputs[1], F::from(1))?].into_iter())
+            .into();
+    region.next();
+
+    // 2r <= |y| iff relu(2r - |y|) == 0
+    let excess = pairwise(
+        config,
+        region,
+        &[twice_remainder.clone(), abs_y.clone()],
+        BaseOp::Sub,
+    )?;
+    let excess = nonlinearity(config, region, &[excess], &LookupOp::ReLU { scale: 1 })?;
+    constrain_zero(config, region, &excess)?;
+
+    // 2r > -|y| iff 2r + |y| >= 1 iff relu(1 - 2r - |y|) == 0
+    let lower = pairwise(
+        config,
+        region,
+        &[twice_remainder, abs_y.clone()],
+        BaseOp::Add,
+    )?;
+    let lower = pairwise(config, region, &[unit.clone(), lower], BaseOp::Sub)?;
+    let lower = nonlinearity(config, region, &[lower], &LookupOp::ReLU { scale: 1 })?;
+    constrain_zero(config, region, &lower)?;
+
+    // |y| >= 1 iff relu(1 - |y|) == 0
+    let deficit = pairwise(config, region, &[unit, abs_y], BaseOp::Sub)?;
+    let deficit = nonlinearity(config, region, &[deficit], &LookupOp::ReLU { scale: 1 })?;
+    constrain_zero(config, region, &deficit)?;
+
+    Ok(quotient)
+}
+
 /// max layout
 pub fn max<F: PrimeField + TensorType + PartialOrd>(
     config: &BaseConfig<F>,
@@ -1846,6 +2043,163 @@ pub fn min<F: PrimeField + TensorType + PartialOrd>(
     Ok(assigned_min_val)
 }
 
+/// Argmax accumulated layout, along a single axis (which is kept with a dimension of 1)
+pub fn argmax_axes<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    axis: usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    arg_axes(config, region, values, axis, true)
+}
+
+/// Argmin accumulated layout, along a single axis (which is kept with a dimension of 1)
+pub fn argmin_axes<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    axis: usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    arg_axes(config, region, values, axis, false)
+}
+
+fn arg_axes<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    axis: usize,
+    is_max: bool,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let a = &values[0];
+
+    let mut new_dims = a.dims().to_vec();
+    new_dims[axis] = 1;
+
+    let mut res = Tensor::new(None, &new_dims)?;
+
+    let cartesian_coord = new_dims
+        .iter()
+        .map(|x| 0..*x)
+        .multi_cartesian_product()
+        .collect::<Vec<_>>();
+
+    for coord in cartesian_coord.iter() {
+        let mut slice_dims = vec![];
+        for (i, c) in coord.iter().enumerate() {
+            if i == axis {
+                slice_dims.push(0..a.dims()[i]);
+            } else {
+                slice_dims.push(*c..*c + 1);
+            }
+        }
+        let selected = if is_max {
+            argmax(config, region, &[a.get_slice(&slice_dims)?])?
+        } else {
+            argmin(config, region, &[a.get_slice(&slice_dims)?])?
+        };
+        res.set(coord, selected.get_inner_tensor()?[0].clone());
+    }
+
+    Ok(res.into())
+}
+
+/// argmax layout
+pub fn argmax<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    arg_select(config, region, values, true)
+}
+
+/// argmin layout
+pub fn argmin<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    arg_select(config, region, values, false)
+}
+
+/// Lays out the index of the max (or min) of `values`. A one-hot vector is witnessed and constrained to be boolean,
+/// to have a single non-zero entry and to select an element equal to the (constrained) max (or min). The index is then
+/// the dot product of the one-hot vector with the element positions. Where several elements tie the prover could pick any
+/// of them, the honest witness picks the first.
+fn arg_select<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    is_max: bool,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let mut input = values[0].clone();
+    input.flatten();
+    let len = input.len();
+
+    // this is safe because we later constrain it
+    let evals = input.get_int_evals()?;
+    let selected = if evals.is_empty() {
+        None
+    } else {
+        let evals = if is_max { evals } else { evals.map(|x| -x) };
+        Some(crate::tensor::ops::argmax_axes(&evals, 0)?[0] as usize)
+    };
+    let one_hot: Tensor<Value<F>> = Tensor::from((0..len).map(|i| match selected {
+        Some(j) if i == j => Value::known(F::ONE),
+        Some(_) => Value::known(F::ZERO),
+        None => Value::unknown(),
+    }));
+
+    // the one-hot vector is boolean
+    let one_hot = region.assign(&config.inputs[1], &one_hot.into())?;
+    (0..len).for_each(|i| {
+        let (x, y) = config.inputs[1].cartesian_coord(region.offset() + i);
+        let selector = config.selectors.get(&(BaseOp::IsBoolean, x));
+        region.enable(selector, y).unwrap();
+    });
+    region.increment(len);
+
+    let unit: ValTensor<F> =
+        Tensor::from(vec![region.assign_constant(&config.inputs[1], F::from(1))?].into_iter())
+            .into();
+    region.next();
+
+    // ... with a single non-zero entry
+    let count = sum(config, region, &[one_hot.clone()])?;
+    let one_minus_count = pairwise(config, region, &[unit, count], BaseOp::Sub)?;
+    constrain_zero(config, region, &one_minus_count)?;
+
+    // ... which selects the max (or min)
+    let extremum = if is_max {
+        max(config, region, &[input.clone()])?
+    } else {
+        min(config, region, &[input.clone()])?
+    };
+    let selected_val = dot(config, region, &[one_hot.clone(), input])?;
+    let diff = pairwise(config, region, &[selected_val, extremum], BaseOp::Sub)?;
+    constrain_zero(config, region, &diff)?;
+
+    // the index is the position of the non-zero entry
+    let positions: Tensor<ValType<F>> =
+        Tensor::from((0..len).map(|i| ValType::Constant(F::from(i as u64))));
+    dot(config, region, &[one_hot, positions.into()])
+}
+
+/// Constrains every element of `value` to be zero.
+fn constrain_zero<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    value: &ValTensor<F>,
+) -> Result<(), Box<dyn Error>> {
+    region.assign(&config.inputs[1], value)?;
+    (0..value.len()).for_each(|i| {
+        let (x, y) = config.inputs[1].cartesian_coord(region.offset() + i);
+        let selector = config.selectors.get(&(BaseOp::IsZero, x));
+        region.enable(selector, y).unwrap();
+    });
+    region.increment(value.len());
+    Ok(())
+}
+
 /// softmax layout
 pub fn multi_dim_softmax<F: PrimeField + TensorType + PartialOrd>(
     config: &BaseConfig<F>,
@@ -1943,6 +2297,123 @@ pub fn softmax<F: PrimeField + TensorType + PartialOrd>(
     Ok(softmax)
 }
 
+/// rms norm layout along the last axis, each slice along it being normalized by [rms_norm]
+pub fn multi_dim_rms_norm<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    input_scale: usize,
+    output_scale: usize,
+    eps: f32,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let dims = values[0].dims();
+
+    if dims.len() == 1 {
+        return rms_norm(config, region, values, input_scale, output_scale, eps);
+    }
+
+    let cartesian_coord = dims[..dims.len() - 1]
+        .iter()
+        .map(|x| 0..*x)
+        .multi_cartesian_product()
+        .collect::<Vec<_>>();
+
+    let mut outputs = vec![];
+
+    for coord in cartesian_coord {
+        let mut norm_dims = vec![];
+        for c in coord {
+            norm_dims.push(c..c + 1);
+        }
+        norm_dims.push(0..dims[dims.len() - 1]);
+
+        let norm_input = values[0].get_slice(&norm_dims)?;
+
+        outputs.push(
+            rms_norm(
+                config,
+                region,
+                &[norm_input],
+                input_scale,
+                output_scale,
+                eps,
+            )?
+            .get_inner_tensor()?,
+        );
+    }
+
+    let mut res = Tensor::new(Some(&outputs), &[outputs.len()])?.combine()?;
+    res.reshape(dims);
+
+    Ok(res.into())
+}
+
+/// rms norm func, see [tensor::ops::nonlinearities::rms_norm] for the steps and their range limits
+pub fn rms_norm<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    input_scale: usize,
+    output_scale: usize,
+    eps: f32,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let x = values[0].clone();
+    let dim = x.len();
+
+    // squares at twice the input scale, brought back down to the input scale
+    let squares = pairwise(config, region, &[x.clone(), x.clone()], BaseOp::Mult)?;
+    let squares = nonlinearity(
+        config,
+        region,
+        &[squares],
+        &LookupOp::Div {
+            denom: utils::F32(input_scale as f32),
+        },
+    )?;
+
+    let sum_squares = sum(config, region, &[squares])?;
+    let offset: ValTensor<F> = Tensor::from(
+        vec![ValType::Constant(i128_to_felt(
+            tensor::ops::nonlinearities::rms_norm_offset(eps, input_scale, dim),
+        ))]
+        .into_iter(),
+    )
+    .into();
+    let sum_squares = pairwise(config, region, &[sum_squares, offset], BaseOp::Add)?;
+
+    // dividing the sum by dim is folded into the rsqrt lookup
+    let inv_rms = nonlinearity(
+        config,
+        region,
+        &[sum_squares],
+        &LookupOp::Rsqrt {
+            scales: (input_scale * dim, output_scale),
+        },
+    )?;
+
+    // product of x * (1 / rms) = input_scale * output_scale
+    let rms_norm = pairwise(config, region, &[x, inv_rms], BaseOp::Mult)?;
+
+    if matches!(&config.check_mode, CheckMode::SAFE) {
+        // during key generation this will be unknown vals so we use this as a flag to check
+        let mut is_assigned = !rms_norm.any_unknowns();
+        for val in values.iter() {
+            is_assigned = is_assigned && !val.any_unknowns();
+        }
+        if is_assigned {
+            let int_evals = Tensor::new(Some(&values[0].get_int_evals()?), values[0].dims())?;
+            let ref_rms_norm: Tensor<i128> =
+                tensor::ops::nonlinearities::rms_norm(&int_evals, input_scale, output_scale, eps).0;
+
+            let output_int_evals = Tensor::new(Some(&rms_norm.get_int_evals()?), values[0].dims())?;
+
+            assert_eq!(output_int_evals, ref_rms_norm)
+        }
+    };
+
+    Ok(rms_norm)
+}
+
 /// Checks that the percent error between the expected public output and the actual output value
 /// is within the percent error expressed by the `tol` input, where `tol == 1.0` means the percent
 /// error tolerance is 1 percent.
@@ -91,13 +91,52 @@ pub enum LookupOp {
     Erf {
         scales: (usize, usize),
     },
+    /// `x` for non-negative `x` and `alpha * (exp(x) - 1)` otherwise. Like the other activations, the table holds
+    /// the function rounded to the output scale over every quantized input of the table's range, so the error is
+    /// at most half a unit of the output scale plus the error of quantizing the input, which is largest where the
+    /// function is steepest (`x` just below 0, with a slope of `alpha`). The negative branch flattens out towards
+    /// `-alpha`: once `alpha * exp(x)` drops below half a unit of the output scale (below `x = -5.5` at a scale of
+    /// 7 with `alpha = 1`) the table saturates at the quantized `-alpha`, down to the table's lower edge. Inputs
+    /// outside of the table's range can't be looked up
+    Elu {
+        scales: (usize, usize),
+        alpha: utils::F32,
+    },
+    /// `gamma * elu(x)` with the positive branch scaled by `gamma` as well, see [LookupOp::Elu] for the
+    /// accuracy of the table. As `gamma > 1` the positive branch grows faster than the input, so the output range
+    /// of the table is `gamma` times larger than its input range
+    Selu {
+        scales: (usize, usize),
+        alpha: utils::F32,
+        gamma: utils::F32,
+    },
     GreaterThan {
         a: utils::F32,
     },
+    /// Truncation towards zero to an integer, from the input scale `scales.0` to the output scale `scales.1` (as
+    /// multipliers), see [tensor::ops::nonlinearities::trunc]
+    Trunc {
+        scales: (usize, usize),
+    },
     LessThan {
         a: utils::F32,
     },
     Sign,
+    Fused {
+        ops: Vec<FusedOp>,
+        out_scale: u32,
+    },
+}
+
+/// A single elementwise step of a [LookupOp::Fused] chain.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum FusedOp {
+    /// Multiplication by a quantized constant
+    Mult(i128),
+    /// Addition of a quantized constant
+    Add(i128),
+    /// An elementwise lookup
+    Lookup(LookupOp),
 }
 
 impl LookupOp {
@@ -109,6 +148,47 @@ impl LookupOp {
             Op::<F>::f(self, &[x]).unwrap().output[0],
         )
     }
+
+    /// Whether the op is evaluated with integer or correctly rounded (IEEE-754 `+ - * / sqrt`) arithmetic only, and
+    /// hence gives bit-identical results on every platform. Transcendental functions depend on the platform's libm.
+    pub fn is_integer_exact(&self) -> bool {
+        match self {
+            LookupOp::Div { .. }
+            | LookupOp::ReLU { .. }
+            | LookupOp::Max { .. }
+            | LookupOp::Min { .. }
+            | LookupOp::Sqrt { .. }
+            | LookupOp::Rsqrt { .. }
+            | LookupOp::Recip { .. }
+            | LookupOp::LeakyReLU { .. }
+            | LookupOp::GreaterThan { .. }
+            | LookupOp::LessThan { .. }
+            | LookupOp::Trunc { .. }
+            | LookupOp::Sign => true,
+            LookupOp::Sigmoid { .. }
+            | LookupOp::Ln { .. }
+            | LookupOp::Exp { .. }
+            | LookupOp::Cos { .. }
+            | LookupOp::ACos { .. }
+            | LookupOp::Cosh { .. }
+            | LookupOp::ACosh { .. }
+            | LookupOp::Sin { .. }
+            | LookupOp::ASin { .. }
+            | LookupOp::Sinh { .. }
+            | LookupOp::ASinh { .. }
+            | LookupOp::Tan { .. }
+            | LookupOp::ATan { .. }
+            | LookupOp::Tanh { .. }
+            | LookupOp::ATanh { .. }
+            | LookupOp::Erf { .. }
+            | LookupOp::Elu { .. }
+            | LookupOp::Selu { .. } => false,
+            LookupOp::Fused { ops, .. } => ops.iter().all(|op| match op {
+                FusedOp::Lookup(op) => op.is_integer_exact(),
+                FusedOp::Mult(_) | FusedOp::Add(_) => true,
+            }),
+        }
+    }
 }
 
 impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
@@ -146,6 +226,9 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
                 f32::from(*denom).into(),
             )),
             LookupOp::Recip { scale } => Ok(tensor::ops::nonlinearities::recip(&x, *scale as u32)),
+            LookupOp::Trunc { scales } => {
+                Ok(tensor::ops::nonlinearities::trunc(&x, scales.0, scales.1))
+            }
             LookupOp::ReLU { scale } => {
                 Ok(tensor::ops::nonlinearities::leakyrelu(&x, *scale, 0_f64))
             }
@@ -170,6 +253,23 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
             LookupOp::Exp { scales } => {
                 Ok(tensor::ops::nonlinearities::exp(&x, scales.0, scales.1))
             }
+            LookupOp::Elu { scales, alpha } => Ok(tensor::ops::nonlinearities::elu(
+                &x,
+                scales.0,
+                scales.1,
+                alpha.0.into(),
+            )),
+            LookupOp::Selu {
+                scales,
+                alpha,
+                gamma,
+            } => Ok(tensor::ops::nonlinearities::selu(
+                &x,
+                scales.0,
+                scales.1,
+                alpha.0.into(),
+                gamma.0.into(),
+            )),
             LookupOp::Ln { scales } => Ok(tensor::ops::nonlinearities::ln(&x, scales.0, scales.1)),
             LookupOp::Cos { scales } => {
                 Ok(tensor::ops::nonlinearities::cos(&x, scales.0, scales.1))
@@ -207,6 +307,19 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
             LookupOp::Tanh { scales } => {
                 Ok(tensor::ops::nonlinearities::tanh(&x, scales.0, scales.1))
             }
+            LookupOp::Fused { ops, .. } => {
+                let mut res = x;
+                for op in ops {
+                    res = match op {
+                        FusedOp::Mult(c) => res.map(|v| v * c),
+                        FusedOp::Add(c) => res.map(|v| v + c),
+                        FusedOp::Lookup(op) => Op::<F>::f(op, &[res.map(i128_to_felt)])?
+                            .output
+                            .map(felt_to_i128),
+                    };
+                }
+                Ok(res)
+            }
         }?;
 
         let output = res.map(|x| i128_to_felt(x));
@@ -227,6 +340,7 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
             LookupOp::GreaterThan { .. } => "GREATER_THAN".into(),
             LookupOp::LessThan { .. } => "LESS_THAN".into(),
             LookupOp::Recip { scale, .. } => format!("RECIP w/ {}", scale),
+            LookupOp::Trunc { scales } => format!("TRUNC w/ {:?}", scales),
             LookupOp::Div { denom, .. } => format!("DIV w/ {}", denom),
             LookupOp::Ln { scales } => format!("LN w/ {:?}", scales),
             LookupOp::ReLU { scale, .. } => format!("RELU w/ scale {}", scale),
@@ -236,6 +350,12 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
             LookupOp::Erf { scales } => format!("ERF w/ {:?}", scales),
             LookupOp::Rsqrt { scales } => format!("RSQRT w/ {:?}", scales),
             LookupOp::Exp { scales } => format!("EXP w/ {:?}", scales),
+            LookupOp::Elu { scales, alpha } => format!("ELU w/ {:?} /a {}", scales, alpha),
+            LookupOp::Selu {
+                scales,
+                alpha,
+                gamma,
+            } => format!("SELU w/ {:?} /a {} /g {}", scales, alpha, gamma),
             LookupOp::Tan { scales } => format!("TAN w/ {:?}", scales),
             LookupOp::ATan { scales } => format!("ATAN w/ {:?}", scales),
             LookupOp::Tanh { scales } => format!("TANH w/ {:?}", scales),
@@ -248,6 +368,17 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
             LookupOp::ASin { scales } => format!("ASIN w/ {:?}", scales),
             LookupOp::Sinh { scales } => format!("SINH w/ {:?}", scales),
             LookupOp::ASinh { scales } => format!("ASINH w/ {:?}", scales),
+            LookupOp::Fused { ops, .. } => format!(
+                "FUSED [{}]",
+                ops.iter()
+                    .map(|op| match op {
+                        FusedOp::Mult(c) => format!("MULT {}", c),
+                        FusedOp::Add(c) => format!("ADD {}", c),
+                        FusedOp::Lookup(op) => Op::<F>::as_string(op),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
         }
     }
 
@@ -269,6 +400,7 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
     fn out_scale(&self, _: Vec<u32>, global_scale: u32) -> u32 {
         match self {
             LookupOp::Sign | LookupOp::GreaterThan { .. } => 0,
+            LookupOp::Fused { out_scale, .. } => *out_scale,
             _ => global_scale,
         }
     }
@@ -276,6 +408,8 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
     fn rescale(&self, inputs_scale: Vec<u32>, global_scale: u32) -> Box<dyn Op<F>> {
         match self {
             LookupOp::Sign => Box::new(LookupOp::Sign),
+            // the steps of a fused chain were rescaled before being fused
+            LookupOp::Fused { .. } => Box::new(self.clone()),
             LookupOp::Recip { .. } => Box::new(LookupOp::Recip {
                 scale: scale_to_multiplier(inputs_scale[0] + global_scale) as usize,
             }),
@@ -311,6 +445,12 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
                 ),
                 a: *a,
             }),
+            LookupOp::Trunc { .. } => Box::new(LookupOp::Trunc {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+            }),
             LookupOp::Sqrt { .. } => Box::new(LookupOp::Sqrt {
                 scales: (
                     scale_to_multiplier(inputs_scale[0]) as usize,
@@ -413,6 +553,21 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
                     scale_to_multiplier(global_scale) as usize,
                 ),
             }),
+            LookupOp::Elu { alpha, .. } => Box::new(LookupOp::Elu {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+                alpha: *alpha,
+            }),
+            LookupOp::Selu { alpha, gamma, .. } => Box::new(LookupOp::Selu {
+                scales: (
+                    scale_to_multiplier(inputs_scale[0]) as usize,
+                    scale_to_multiplier(global_scale) as usize,
+                ),
+                alpha: *alpha,
+                gamma: *gamma,
+            }),
             LookupOp::GreaterThan { a } => Box::new(LookupOp::GreaterThan {
                 a: utils::F32(((a.0 as f64) * scale_to_multiplier(inputs_scale[0])) as f32),
             }),
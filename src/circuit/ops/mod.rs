@@ -85,6 +85,55 @@ impl<F: PrimeField + TensorType + PartialOrd> Clone for Box<dyn Op<F>> {
 pub struct Input {
     ///
     pub scale: u32,
+    /// An optional affine normalization applied to the input within the circuit.
+    pub normalization: Option<Normalization>,
+}
+
+/// A per-channel affine map `x * mult[c] + shift[c]`, rescaled back down to the scale of `x`, where `c` is the index
+/// of an element along `axis`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Normalization {
+    /// The channel axis.
+    pub axis: usize,
+    /// The per-channel multipliers, at the scale of the input.
+    pub mult: Vec<i128>,
+    /// The per-channel shifts, at twice the scale of the input.
+    pub shift: Vec<i128>,
+}
+
+impl Normalization {
+    /// Broadcasts the multipliers and shifts to tensors of shape `dims`.
+    fn broadcast<F: PrimeField + TensorType + PartialOrd>(
+        &self,
+        dims: &[usize],
+    ) -> Result<(Tensor<F>, Tensor<F>), TensorError> {
+        if dims.len() <= self.axis
+            || dims[self.axis] != self.mult.len()
+            || dims[self.axis] != self.shift.len()
+        {
+            return Err(TensorError::DimMismatch("normalization".to_string()));
+        }
+        let stride = dims[self.axis + 1..].iter().product::<usize>();
+        let len = dims.iter().product::<usize>();
+        let channel = |i: usize| (i / stride) % self.mult.len();
+
+        let mut mult: Tensor<F> = (0..len)
+            .map(|i| crate::fieldutils::i128_to_felt(self.mult[channel(i)]))
+            .into();
+        mult.reshape(dims);
+        let mut shift: Tensor<F> = (0..len)
+            .map(|i| crate::fieldutils::i128_to_felt(self.shift[channel(i)]))
+            .into();
+        shift.reshape(dims);
+        Ok((mult, shift))
+    }
+
+    /// The lookup that returns the normalized input to the scale of the input.
+    fn rescale(&self, scale: u32) -> LookupOp {
+        LookupOp::Div {
+            denom: (2f32.powi(scale as i32)).into(),
+        }
+    }
 }
 
 impl<F: PrimeField + TensorType + PartialOrd> Op<F> for Input {
@@ -97,14 +146,29 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for Input {
     }
 
     fn f(&self, x: &[Tensor<F>]) -> Result<ForwardResult<F>, TensorError> {
-        Ok(ForwardResult {
-            output: x[0].clone(),
-            intermediate_lookups: vec![],
-        })
+        match &self.normalization {
+            None => Ok(ForwardResult {
+                output: x[0].clone(),
+                intermediate_lookups: vec![],
+            }),
+            Some(normalization) => {
+                let (mult, shift) = normalization.broadcast::<F>(x[0].dims())?;
+                let affine = ((x[0].clone() * mult)? + shift)?;
+                let res = Op::<F>::f(&normalization.rescale(self.scale), &[affine.clone()])?;
+                Ok(ForwardResult {
+                    output: res.output,
+                    // the affine map is what gets looked up when rescaling
+                    intermediate_lookups: vec![affine.map(crate::fieldutils::felt_to_i128)],
+                })
+            }
+        }
     }
 
     fn as_string(&self) -> String {
-        "Input".into()
+        match &self.normalization {
+            None => "Input".into(),
+            Some(n) => format!("Input (normalized along axis {})", n.axis),
+        }
     }
 
     fn layout(
@@ -114,6 +178,25 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for Input {
         values: &[ValTensor<F>],
     ) -> Result<Option<ValTensor<F>>, Box<dyn Error>> {
         let value = values[0].clone();
+        if let Some(normalization) = &self.normalization {
+            let (mult, shift) = normalization.broadcast::<F>(value.dims())?;
+            let constant =
+                |t: Tensor<F>| -> ValTensor<F> { t.map(crate::tensor::ValType::Constant).into() };
+            let scaled =
+                layouts::pairwise(config, region, &[value, constant(mult)], base::BaseOp::Mult)?;
+            let shifted = layouts::pairwise(
+                config,
+                region,
+                &[scaled, constant(shift)],
+                base::BaseOp::Add,
+            )?;
+            return Ok(Some(layouts::nonlinearity(
+                config,
+                region,
+                &[shifted],
+                &normalization.rescale(self.scale),
+            )?));
+        }
         if !value.all_prev_assigned() {
             Ok(Some(super::layouts::identity(
                 config,
@@ -129,6 +212,13 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for Input {
         Box::new(self.clone())
     }
 
+    fn required_lookups(&self) -> Vec<LookupOp> {
+        match &self.normalization {
+            None => vec![],
+            Some(normalization) => vec![normalization.rescale(self.scale)],
+        }
+    }
+
     fn is_input(&self) -> bool {
         true
     }
@@ -1,6 +1,11 @@
 use crate::{
     circuit::layouts,
-    tensor::{self, Tensor, TensorError},
+    fieldutils::i128_to_felt,
+    tensor::{
+        self,
+        ops::{PadMode, ResizeMode},
+        Tensor, TensorError,
+    },
 };
 
 use super::{base::BaseOp, *};
@@ -17,6 +22,7 @@ pub enum PolyOp<F: PrimeField + TensorType + PartialOrd> {
         bias: Option<Tensor<F>>,
         padding: [(usize, usize); 2],
         stride: (usize, usize),
+        pad_value: F,
     },
     Downsample {
         axis: usize,
@@ -50,7 +56,10 @@ pub enum PolyOp<F: PrimeField + TensorType + PartialOrd> {
         index: Tensor<usize>,
     },
     Flatten(Vec<usize>),
-    Pad([(usize, usize); 2]),
+    Pad {
+        pads: Vec<(usize, usize)>,
+        mode: PadMode<F>,
+    },
     Sum {
         axes: Vec<usize>,
     },
@@ -68,6 +77,7 @@ pub enum PolyOp<F: PrimeField + TensorType + PartialOrd> {
     Iff,
     Resize {
         scale_factor: Vec<usize>,
+        mode: ResizeMode,
     },
 }
 
@@ -91,7 +101,7 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
             PolyOp::Identity => "IDENTITY".into(),
             PolyOp::Reshape(_) => "RESHAPE".into(),
             PolyOp::Flatten(_) => "FLATTEN".into(),
-            PolyOp::Pad(_) => "PAD".into(),
+            PolyOp::Pad { .. } => "PAD".into(),
             PolyOp::Add => "ADD".into(),
             PolyOp::Mult => "MULT".into(),
             PolyOp::Sub => "SUB".into(),
@@ -118,8 +128,26 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
                 stride,
                 modulo,
             } => tensor::ops::downsample(&inputs[0], *axis, *stride, *modulo),
-            PolyOp::Resize { scale_factor } => tensor::ops::resize(&inputs[0], scale_factor),
-            PolyOp::Iff => tensor::ops::iff(&inputs[0], &inputs[1], &inputs[2]),
+            PolyOp::Resize { scale_factor, mode } => match mode {
+                ResizeMode::Nearest => tensor::ops::resize(&inputs[0], scale_factor),
+                ResizeMode::Linear {
+                    transform,
+                    weight_scale,
+                } => tensor::ops::resize_linear(
+                    &inputs[0],
+                    scale_factor,
+                    *transform,
+                    *weight_scale,
+                    i128_to_felt,
+                ),
+            },
+            PolyOp::Iff => {
+                // the condition of a Where / select has to be boolean once quantized
+                if inputs[0].iter().any(|m| *m != F::ZERO && *m != F::ONE) {
+                    return Err(TensorError::NonBooleanMask);
+                }
+                tensor::ops::iff(&inputs[0], &inputs[1], &inputs[2])
+            }
             PolyOp::Einsum { equation } => tensor::ops::einsum(equation, &inputs),
             PolyOp::Gather { dim, index } => tensor::ops::gather(&inputs[0], *dim, index),
             PolyOp::Identity => Ok(inputs[0].clone()),
@@ -137,11 +165,11 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
                 t.reshape(new_dims);
                 Ok(t)
             }
-            PolyOp::Pad(p) => {
+            PolyOp::Pad { pads, mode } => {
                 if 1 != inputs.len() {
                     return Err(TensorError::DimMismatch("pad inputs".to_string()));
                 }
-                tensor::ops::pad(&inputs[0], *p)
+                tensor::ops::pad_axes(&inputs[0], pads, mode)
             }
             PolyOp::Add => tensor::ops::add(&inputs),
             PolyOp::Neg => tensor::ops::neg(&inputs[0]),
@@ -152,12 +180,13 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
                 bias,
                 padding,
                 stride,
+                pad_value,
             } => {
                 inputs.push(a.clone());
                 if let Some(b) = bias {
                     inputs.push(b.clone());
                 }
-                tensor::ops::conv(&inputs, *padding, *stride)
+                tensor::ops::conv(&inputs, *padding, *stride, *pad_value)
             }
             PolyOp::DeConv {
                 kernel: a,
@@ -235,9 +264,22 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
                 stride,
                 modulo,
             } => layouts::downsample(config, region, values[..].try_into()?, axis, stride, modulo)?,
-            PolyOp::Resize { scale_factor } => {
-                layouts::resize(config, region, values[..].try_into()?, scale_factor)?
-            }
+            PolyOp::Resize { scale_factor, mode } => match mode {
+                ResizeMode::Nearest => {
+                    layouts::resize(config, region, values[..].try_into()?, scale_factor)?
+                }
+                ResizeMode::Linear {
+                    transform,
+                    weight_scale,
+                } => layouts::resize_linear(
+                    config,
+                    region,
+                    values[..].try_into()?,
+                    scale_factor,
+                    *transform,
+                    *weight_scale,
+                )?,
+            },
             PolyOp::Neg => layouts::neg(config, region, values[..].try_into()?)?,
             PolyOp::Iff => layouts::iff(config, region, values[..].try_into()?)?,
             PolyOp::Einsum { equation } => layouts::einsum(config, region, &mut values, equation)?,
@@ -252,12 +294,20 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
                 bias,
                 padding,
                 stride,
+                pad_value,
             } => {
                 values.push(kernel.clone().into());
                 if let Some(bias) = bias {
                     values.push(bias.clone().into());
                 }
-                layouts::conv(config, region, values[..].try_into()?, *padding, *stride)?
+                layouts::conv(
+                    config,
+                    region,
+                    values[..].try_into()?,
+                    *padding,
+                    *stride,
+                    *pad_value,
+                )?
             }
             PolyOp::DeConv {
                 kernel,
@@ -298,13 +348,8 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
             }
             PolyOp::Identity => layouts::identity(config, region, values[..].try_into()?)?,
             PolyOp::Reshape(d) | PolyOp::Flatten(d) => layouts::reshape(values[..].try_into()?, d)?,
-            PolyOp::Pad(p) => {
-                if values.len() != 1 {
-                    return Err(Box::new(TensorError::DimError));
-                }
-                let mut input = values[0].clone();
-                input.pad(*p)?;
-                input
+            PolyOp::Pad { pads, mode } => {
+                layouts::pad(config, region, values[..].try_into()?, pads, mode)?
             }
             PolyOp::Pow(exp) => layouts::pow(config, region, values[..].try_into()?, *exp)?,
             PolyOp::Pack(base, scale) => {
@@ -328,7 +373,14 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
             PolyOp::Neg => in_scales[0],
             PolyOp::MoveAxis { .. } => in_scales[0],
             PolyOp::Downsample { .. } => in_scales[0],
-            PolyOp::Resize { .. } => in_scales[0],
+            PolyOp::Resize { scale_factor, mode } => match mode {
+                ResizeMode::Nearest => in_scales[0],
+                // every linearly resized axis multiplies in a quantized weight
+                ResizeMode::Linear { weight_scale, .. } => {
+                    in_scales[0]
+                        + weight_scale * scale_factor.iter().filter(|s| **s > 1).count() as u32
+                }
+            },
             PolyOp::Iff => in_scales[1],
             PolyOp::Einsum { .. } => {
                 let mut scale = in_scales[0];
@@ -386,7 +438,7 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
             }
             PolyOp::Identity => in_scales[0],
             PolyOp::Reshape(_) | PolyOp::Flatten(_) => in_scales[0],
-            PolyOp::Pad(_) => in_scales[0],
+            PolyOp::Pad { .. } => in_scales[0],
             PolyOp::Pow(pow) => in_scales[0] * (*pow),
             PolyOp::Pack(_, _) => in_scales[0],
             PolyOp::GlobalSumPool => in_scales[0],
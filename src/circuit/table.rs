@@ -1,4 +1,4 @@
-use std::{error::Error, marker::PhantomData};
+use std::{error::Error, marker::PhantomData, ops::RangeInclusive};
 
 use halo2curves::ff::PrimeField;
 
@@ -17,6 +17,20 @@ use crate::circuit::lookup::LookupOp;
 
 use super::Op;
 
+/// The inputs a lookup table of `bits` bits is assigned for, i.e the integers in `[-2^(bits-1), 2^(bits-1)]`.
+pub fn table_range(bits: usize) -> RangeInclusive<i128> {
+    let bound = 2i128.pow(bits as u32 - 1);
+    -bound..=bound
+}
+
+/// Whether a lookup table of `bits` bits has a row for `x`. This holds for any field element, including those beyond
+/// the i128 range, which can't be converted to integers.
+pub fn in_table_range<F: PrimeField + PartialOrd>(x: F, bits: usize) -> bool {
+    let range = table_range(bits);
+    // shifting the table onto `[0, 2^bits]` leaves every element outside of it above its end
+    x - i128_to_felt::<F>(*range.start()) <= i128_to_felt::<F>(range.end() - range.start())
+}
+
 /// Halo2 lookup table for element wise non-linearities.
 #[derive(Clone, Debug)]
 pub struct Table<F: PrimeField> {
@@ -62,11 +76,7 @@ impl<F: PrimeField + TensorType + PartialOrd> Table<F> {
             return Err(Box::new(CircuitError::TableAlreadyAssigned));
         }
 
-        let base = 2i128;
-        let smallest = -base.pow(self.bits as u32 - 1);
-        let largest = base.pow(self.bits as u32 - 1);
-
-        let inputs = Tensor::from(smallest..=largest).map(|x| i128_to_felt(x));
+        let inputs = Tensor::from(table_range(self.bits)).map(|x| i128_to_felt(x));
         let evals = Op::<F>::f(&self.nonlinearity, &[inputs.clone()])?;
 
         self.is_assigned = true;
@@ -551,6 +551,7 @@ mod composition {
 mod conv {
 
     use super::*;
+    use crate::fieldutils::felt_to_i128;
 
     const K: usize = 22;
     const LEN: usize = 100;
@@ -558,6 +559,9 @@ mod conv {
     #[derive(Clone)]
     struct ConvCircuit<F: PrimeField + TensorType + PartialOrd> {
         inputs: Vec<Tensor<F>>,
+        pad_value: F,
+        // the outputs the layout should produce (if checked)
+        expected: Option<Vec<i128>>,
         _marker: PhantomData<F>,
     }
 
@@ -587,7 +591,7 @@ mod conv {
                     || "",
                     |region| {
                         let mut region = RegionCtx::new(region, 0);
-                        config
+                        let output = config
                             .layout(
                                 &mut region,
                                 &[self.inputs[0].clone().into()],
@@ -596,9 +600,17 @@ mod conv {
                                     bias: None,
                                     padding: [(1, 1); 2],
                                     stride: (2, 2),
+                                    pad_value: self.pad_value,
                                 }),
                             )
-                            .map_err(|_| Error::Synthesis)
+                            .map_err(|_| Error::Synthesis)?;
+                        if let (Some(expected), Some(output)) = (&self.expected, output) {
+                            let evals = output.get_int_evals().map_err(|_| Error::Synthesis)?;
+                            if !evals.is_empty() && evals[..] != expected[..] {
+                                return Err(Error::Synthesis);
+                            }
+                        }
+                        Ok(())
                     },
                 )
                 .unwrap();
@@ -633,6 +645,8 @@ mod conv {
 
         let circuit = ConvCircuit::<F> {
             inputs: [image, kernels, bias].to_vec(),
+            pad_value: F::ZERO,
+            expected: None,
             _marker: PhantomData,
         };
 
@@ -664,12 +678,111 @@ mod conv {
 
         let circuit = ConvCircuit::<F> {
             inputs: [image, kernels].to_vec(),
+            pad_value: F::ZERO,
+            expected: None,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+
+    #[test]
+    fn convcircuit_pad_value() {
+        // a 1x1 unit kernel with a stride of 2 samples the corners of the padded image
+        let mut image = Tensor::from((1..=9).map(|i| F::from(i as u64)));
+        image.reshape(&[1, 1, 3, 3]);
+        image.set_visibility(crate::graph::Visibility::Private);
+
+        let mut kernels = Tensor::from([F::ONE].into_iter());
+        kernels.reshape(&[1, 1, 1, 1]);
+        kernels.set_visibility(crate::graph::Visibility::Private);
+
+        let op = PolyOp::Conv {
+            kernel: kernels.clone(),
+            bias: None,
+            padding: [(1, 1); 2],
+            stride: (2, 2),
+            pad_value: F::from(7),
+        };
+        let output = Op::<F>::f(&op, &[image.clone()]).unwrap().output;
+        let expected = vec![7, 7, 7, 7, 5, 7, 7, 7, 7];
+        assert_eq!(output.dims(), &[1, 1, 3, 3]);
+        assert_eq!(output.map(felt_to_i128).to_vec(), expected);
+
+        let circuit = ConvCircuit::<F> {
+            inputs: [image, kernels].to_vec(),
+            pad_value: F::from(7),
+            expected: Some(expected),
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+
+    /// Convolves a [1, 4, 5, 5] image with `kernels` and checks each group of output channels is the conv of its
+    /// group of input channels alone, both off and in-circuit.
+    fn check_grouped_conv(kernels: Tensor<F>, groups: usize) {
+        let mut image = Tensor::from((0..100).map(|i| F::from(i as u64)));
+        image.reshape(&[1, 4, 5, 5]);
+        image.set_visibility(crate::graph::Visibility::Private);
+
+        let conv = |kernel: Tensor<F>| PolyOp::Conv {
+            kernel,
+            bias: None,
+            padding: [(1, 1); 2],
+            stride: (2, 2),
+            pad_value: F::ZERO,
+        };
+        let output = Op::<F>::f(&conv(kernels.clone()), &[image.clone()])
+            .unwrap()
+            .output;
+        assert_eq!(output.dims(), &[1, kernels.dims()[0], 3, 3]);
+
+        let (in_per_group, out_per_group) = (4 / groups, kernels.dims()[0] / groups);
+        for g in 0..groups {
+            let input_channels = g * in_per_group..(g + 1) * in_per_group;
+            let output_channels = g * out_per_group..(g + 1) * out_per_group;
+            let group_image = image.get_slice(&[0..1, input_channels]).unwrap();
+            let group_kernels = kernels.get_slice(&[output_channels.clone()]).unwrap();
+            let expected = Op::<F>::f(&conv(group_kernels), &[group_image])
+                .unwrap()
+                .output;
+            assert_eq!(
+                output.get_slice(&[0..1, output_channels]).unwrap(),
+                expected
+            );
+        }
+
+        let circuit = ConvCircuit::<F> {
+            inputs: [image, kernels].to_vec(),
+            pad_value: F::ZERO,
+            expected: Some(output.map(felt_to_i128).to_vec()),
             _marker: PhantomData,
         };
 
         let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
         prover.assert_satisfied_par();
     }
+
+    #[test]
+    fn convcircuit_depthwise() {
+        // group = in_channels, each channel is convolved with its own kernel
+        let mut kernels = Tensor::from((0..36).map(|i| F::from(i as u64)));
+        kernels.reshape(&[4, 1, 3, 3]);
+        kernels.set_visibility(crate::graph::Visibility::Private);
+        check_grouped_conv(kernels, 4);
+    }
+
+    #[test]
+    fn convcircuit_grouped() {
+        // two groups of two input channels, each producing three output channels
+        let mut kernels = Tensor::from((0..108).map(|i| F::from(i as u64)));
+        kernels.reshape(&[6, 2, 3, 3]);
+        kernels.set_visibility(crate::graph::Visibility::Private);
+        check_grouped_conv(kernels, 2);
+    }
 }
 
 #[cfg(test)]
@@ -751,6 +864,131 @@ mod sumpool {
     }
 }
 
+#[cfg(test)]
+mod resize {
+
+    use super::*;
+    use crate::fieldutils::i128_to_felt;
+    use crate::tensor::ops::{ResizeCoordTransform, ResizeMode};
+
+    const K: usize = 8;
+    const LEN: usize = 16;
+
+    #[derive(Clone)]
+    struct ResizeCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: Vec<ValTensor<F>>,
+        mode: ResizeMode,
+    }
+
+    impl Circuit<F> for ResizeCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN);
+            let b = VarTensor::new_advice(cs, K, LEN);
+            let output = VarTensor::new_advice(cs, K, LEN);
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0);
+                        config
+                            .layout(
+                                &mut region,
+                                &self.inputs.clone(),
+                                Box::new(PolyOp::Resize {
+                                    scale_factor: vec![1, 1, 2, 2],
+                                    mode: self.mode.clone(),
+                                }),
+                            )
+                            .map_err(|_| Error::Synthesis)
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    fn run(input: &[i128], mode: ResizeMode, expected: &[i128]) {
+        let input = Tensor::new(
+            Some(
+                &input
+                    .iter()
+                    .map(|x| i128_to_felt::<F>(*x))
+                    .collect::<Vec<_>>(),
+            ),
+            &[1, 1, 2, 2],
+        )
+        .unwrap();
+
+        let op = PolyOp::Resize {
+            scale_factor: vec![1, 1, 2, 2],
+            mode: mode.clone(),
+        };
+        let output = Op::<F>::f(&op, &[input.clone()]).unwrap().output;
+        let expected = Tensor::new(
+            Some(
+                &expected
+                    .iter()
+                    .map(|x| i128_to_felt::<F>(*x))
+                    .collect::<Vec<_>>(),
+            ),
+            &[1, 1, 4, 4],
+        )
+        .unwrap();
+        assert_eq!(output, expected);
+
+        let circuit = ResizeCircuit::<F> {
+            inputs: [ValTensor::from(input.map(Value::known))].to_vec(),
+            mode,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+
+    #[test]
+    fn resize_nearest_circuit() {
+        run(
+            &[1, 2, 3, 4],
+            ResizeMode::Nearest,
+            &[1, 1, 2, 2, 1, 1, 2, 2, 3, 3, 4, 4, 3, 3, 4, 4],
+        );
+    }
+
+    #[test]
+    fn resize_bilinear_circuit() {
+        // reference: torch.nn.functional.interpolate(x, scale_factor=2, mode="bilinear", align_corners=False)
+        let reference = [
+            0.0, 1.0, 3.0, 4.0, 2.0, 3.0, 5.0, 6.0, 6.0, 7.0, 9.0, 10.0, 8.0, 9.0, 11.0, 12.0,
+        ];
+        // weights are quantized with 2 bits, the output carries a scale of 2 bits per resized axis
+        let expected = reference.map(|x: f64| (x * 16.0) as i128);
+        run(
+            &[0, 4, 8, 12],
+            ResizeMode::Linear {
+                transform: ResizeCoordTransform::HalfPixel,
+                weight_scale: 2,
+            },
+            &expected,
+        );
+    }
+}
+
 #[cfg(test)]
 mod add_w_shape_casting {
     use super::*;
@@ -1661,26 +1899,31 @@ mod relu {
 }
 
 #[cfg(test)]
-mod softmax {
-
+mod prelu {
     use super::*;
-    use halo2_proofs::{
-        circuit::{Layouter, SimpleFloorPlanner, Value},
-        dev::MockProver,
-        plonk::{Circuit, ConstraintSystem, Error},
-    };
+    use crate::circuit::utils::F32;
+    use crate::fieldutils::{felt_to_i128, i128_to_felt};
 
-    const K: usize = 18;
-    const LEN: usize = 3;
-    const SCALE: usize = i128::pow(2, 7) as usize;
+    const K: usize = 10;
+    const LEN: usize = 16;
+    // inputs and slopes are quantized with a multiplier of 2^2
+    const MULT: f64 = 4.0;
+
+    fn leaky_relu() -> LookupOp {
+        LookupOp::LeakyReLU {
+            scale: 1,
+            slope: F32(0.1),
+        }
+    }
 
     #[derive(Clone)]
-    struct SoftmaxCircuit<F: PrimeField + TensorType + PartialOrd> {
-        pub input: ValTensor<F>,
-        _marker: PhantomData<F>,
+    struct PReLUCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+        // per-channel slopes, or a LeakyReLU if unset
+        slopes: Option<ValTensor<F>>,
     }
 
-    impl Circuit<F> for SoftmaxCircuit<F> {
+    impl Circuit<F> for PReLUCircuit<F> {
         type Config = BaseConfig<F>;
         type FloorPlanner = SimpleFloorPlanner;
         type Params = TestParams;
@@ -1688,37 +1931,22 @@ mod softmax {
         fn without_witnesses(&self) -> Self {
             self.clone()
         }
+
         fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
             let a = VarTensor::new_advice(cs, K, LEN);
             let b = VarTensor::new_advice(cs, K, LEN);
             let output = VarTensor::new_advice(cs, K, LEN);
-            let mut config = Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE);
-            let advices = (0..2)
-                .map(|_| VarTensor::new_advice(cs, K, LEN))
-                .collect::<Vec<_>>();
 
-            config
-                .configure_lookup(
-                    cs,
-                    &advices[0],
-                    &advices[1],
-                    16,
-                    &LookupOp::Exp {
-                        scales: (SCALE, SCALE),
-                    },
-                )
-                .unwrap();
-            config
-                .configure_lookup(
-                    cs,
-                    &advices[0],
-                    &advices[1],
-                    16,
-                    &LookupOp::Recip {
-                        scale: SCALE.pow(2),
-                    },
-                )
-                .unwrap();
+            let mut config = BaseConfig::configure(cs, &[a.clone(), b], &output, CheckMode::SAFE);
+            for nl in [
+                leaky_relu(),
+                LookupOp::GreaterThan { a: F32(0.) },
+                LookupOp::Div {
+                    denom: F32(MULT as f32),
+                },
+            ] {
+                config.configure_lookup(cs, &a, &output, 8, &nl).unwrap();
+            }
             config
         }
 
@@ -1728,34 +1956,923 @@ mod softmax {
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
             config.layout_tables(&mut layouter).unwrap();
-            layouter
-                .assign_region(
-                    || "",
-                    |region| {
-                        let mut region = RegionCtx::new(region, 0);
-                        let _output = config
-                            .layout(
-                                &mut region,
-                                &[self.input.clone()],
-                                Box::new(HybridOp::Softmax {
-                                    scales: (SCALE, SCALE),
-                                }),
-                            )
-                            .unwrap();
-                        Ok(())
-                    },
-                )
-                .unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0);
+                    let x = self.input.clone();
+                    let _output = match &self.slopes {
+                        None => config.layout(&mut region, &[x], Box::new(leaky_relu())),
+                        Some(slopes) => layout_prelu(&mut config, &mut region, x, slopes.clone()),
+                    }
+                    .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
 
             Ok(())
         }
     }
 
+    /// How an ONNX PRelu is laid out once tract has expanded it into a comparison, a multiplication by the
+    /// (broadcast) slopes, a rescaling and a select.
+    fn layout_prelu(
+        config: &mut BaseConfig<F>,
+        region: &mut RegionCtx<F>,
+        x: ValTensor<F>,
+        slopes: ValTensor<F>,
+    ) -> Result<Option<ValTensor<F>>, Box<dyn std::error::Error>> {
+        let mask = config
+            .layout(
+                region,
+                &[x.clone()],
+                Box::new(LookupOp::GreaterThan { a: F32(0.) }),
+            )?
+            .unwrap();
+        let scaled = config
+            .layout(region, &[x.clone(), slopes], Box::new(PolyOp::Mult))?
+            .unwrap();
+        let scaled = config
+            .layout(
+                region,
+                &[scaled],
+                Box::new(LookupOp::Div {
+                    denom: F32(MULT as f32),
+                }),
+            )?
+            .unwrap();
+        config.layout(region, &[mask, scaled, x], Box::new(PolyOp::Iff))
+    }
+
+    /// The out-of-circuit counterpart of [layout_prelu].
+    fn forward_prelu(x: &Tensor<F>, slopes: &Tensor<F>) -> Tensor<F> {
+        let f = |op: Box<dyn Op<F>>, inputs: &[Tensor<F>]| op.f(inputs).unwrap().output;
+        let mask = f(Box::new(LookupOp::GreaterThan { a: F32(0.) }), &[x.clone()]);
+        let scaled = f(Box::new(PolyOp::Mult), &[x.clone(), slopes.clone()]);
+        let scaled = f(
+            Box::new(LookupOp::Div {
+                denom: F32(MULT as f32),
+            }),
+            &[scaled],
+        );
+        f(Box::new(PolyOp::Iff), &[mask, scaled, x.clone()])
+    }
+
+    fn quantize(values: &[f64], dims: &[usize]) -> Tensor<F> {
+        let quantized = values
+            .iter()
+            .map(|v| i128_to_felt((v * MULT).round() as i128))
+            .collect::<Vec<F>>();
+        Tensor::new(Some(&quantized), dims).unwrap()
+    }
+
+    fn dequantize(values: &Tensor<F>) -> Vec<i128> {
+        values.iter().map(|v| felt_to_i128(*v)).collect()
+    }
+
     #[test]
-    fn softmax_circuit() {
-        let input = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+    fn leakyrelu_circuit() {
+        let x = (-8..8).map(|i| i as f64 * 0.5).collect::<Vec<_>>();
+        let input = quantize(&x, &[16]);
 
-        let circuit = SoftmaxCircuit::<F> {
+        let expected = x
+            .iter()
+            .map(|v| if *v < 0. { 0.1 * v } else { *v })
+            .collect::<Vec<_>>();
+        let output = Op::<F>::f(&leaky_relu(), &[input.clone()]).unwrap().output;
+        assert_eq!(dequantize(&output), dequantize(&quantize(&expected, &[16])));
+
+        let circuit = PReLUCircuit::<F> {
+            input: ValTensor::from(input.map(Value::known)),
+            slopes: None,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+
+    #[test]
+    fn prelu_per_channel_circuit() {
+        // a [3, 2, 2] input with a slope per channel, broadcast over the spatial dims
+        let x = (0..12).map(|i| (i - 6) as f64 * 0.5).collect::<Vec<_>>();
+        let input = quantize(&x, &[3, 2, 2]);
+        let slopes = [0.25, 0.5, 0.75];
+        let quantized_slopes = quantize(&slopes, &[3, 1, 1]);
+
+        let expected = x
+            .iter()
+            .enumerate()
+            .map(|(i, v)| if *v < 0. { slopes[i / 4] * v } else { *v })
+            .collect::<Vec<_>>();
+        let output = forward_prelu(&input, &quantized_slopes);
+        assert_eq!(output.dims(), &[3, 2, 2]);
+        assert_eq!(
+            dequantize(&output),
+            dequantize(&quantize(&expected, &[3, 2, 2]))
+        );
+
+        let circuit = PReLUCircuit::<F> {
+            input: ValTensor::from(input.map(Value::known)),
+            slopes: Some(ValTensor::from(quantized_slopes.map(Value::known))),
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+}
+
+#[cfg(test)]
+mod elu {
+    use super::*;
+    use crate::circuit::utils::F32;
+    use crate::fieldutils::{felt_to_i128, i128_to_felt};
+
+    const K: usize = 10;
+    const BITS: usize = 8;
+    const LEN: usize = 16;
+    // inputs and outputs are quantized with a multiplier of 2^4, so the table covers [-8, 8)
+    const MULT: usize = 16;
+
+    fn elu() -> LookupOp {
+        LookupOp::Elu {
+            scales: (MULT, MULT),
+            alpha: F32(1.0),
+        }
+    }
+
+    fn selu() -> LookupOp {
+        LookupOp::Selu {
+            scales: (MULT, MULT),
+            alpha: F32(1.673_263_2),
+            gamma: F32(1.050_701),
+        }
+    }
+
+    #[derive(Clone)]
+    struct EluCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+        nl: LookupOp,
+    }
+
+    impl Circuit<F> for EluCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN);
+            let output = VarTensor::new_advice(cs, K, LEN);
+
+            let mut config = BaseConfig::default();
+            for nl in [elu(), selu()] {
+                config.configure_lookup(cs, &a, &output, BITS, &nl).unwrap();
+            }
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0);
+                    config
+                        .layout(
+                            &mut region,
+                            &[self.input.clone()],
+                            Box::new(self.nl.clone()),
+                        )
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+
+            Ok(())
+        }
+    }
+
+    /// Checks `nl` against the float `reference` over every quantized input of the table's range: as the inputs
+    /// are exact, the only error is the rounding of the output to the output scale.
+    fn assert_matches_reference(nl: &LookupOp, reference: impl Fn(f64) -> f64) {
+        let half = 1i128 << (BITS - 1);
+        let x = (-half..half).map(i128_to_felt).collect::<Vec<F>>();
+        let x = Tensor::new(Some(&x), &[2 * half as usize]).unwrap();
+        let output = Op::<F>::f(nl, &[x]).unwrap().output;
+        for (i, y) in (-half..half).zip(output.iter()) {
+            let expected = reference(i as f64 / MULT as f64);
+            let found = felt_to_i128(*y) as f64 / MULT as f64;
+            assert!(
+                (found - expected).abs() <= 0.5 / MULT as f64 + 1e-6,
+                "{}: input {} gave {}, expected {}",
+                Op::<F>::as_string(nl),
+                i,
+                found,
+                expected
+            );
+        }
+    }
+
+    fn run_circuit(nl: LookupOp) {
+        // the table's edges, the steepest part of the negative branch and the saturated tail
+        let x = [
+            -128, -127, -100, -64, -16, -3, -2, -1, 0, 1, 2, 3, 16, 64, 126, 127,
+        ]
+        .iter()
+        .map(|v| Value::known(i128_to_felt::<F>(*v)))
+        .collect::<Vec<_>>();
+        let circuit = EluCircuit::<F> {
+            input: ValTensor::from(Tensor::new(Some(&x), &[LEN]).unwrap()),
+            nl,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+
+    #[test]
+    fn elu_matches_reference() {
+        assert_matches_reference(&elu(), |x| if x < 0. { x.exp_m1() } else { x });
+        // saturates at the quantized -alpha well before the table's lower edge
+        let saturated = Op::<F>::f(
+            &elu(),
+            &[Tensor::new(Some(&[i128_to_felt(-128)]), &[1]).unwrap()],
+        )
+        .unwrap()
+        .output;
+        assert_eq!(felt_to_i128(saturated[0]), -(MULT as i128));
+    }
+
+    #[test]
+    fn selu_matches_reference() {
+        let (alpha, gamma) = (1.673_263_2_f32 as f64, 1.050_701_f32 as f64);
+        assert_matches_reference(&selu(), |x| {
+            gamma * if x < 0. { alpha * x.exp_m1() } else { x }
+        });
+    }
+
+    #[test]
+    fn elu_circuit() {
+        run_circuit(elu());
+    }
+
+    #[test]
+    fn selu_circuit() {
+        run_circuit(selu());
+    }
+}
+
+#[cfg(test)]
+mod iff {
+
+    use super::*;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct IffCircuit<F: PrimeField + TensorType + PartialOrd> {
+        // mask, then the values selected where the mask is 0 and 1 respectively
+        inputs: [ValTensor<F>; 3],
+    }
+
+    impl Circuit<F> for IffCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN);
+            let b = VarTensor::new_advice(cs, K, LEN);
+            let output = VarTensor::new_advice(cs, K, LEN);
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0);
+                    config
+                        .layout(&mut region, &self.inputs.clone(), Box::new(PolyOp::Iff))
+                        .map_err(|_| Error::Synthesis)
+                },
+            )?;
+
+            Ok(())
+        }
+    }
+
+    fn tensor(values: &[u64], dims: &[usize]) -> Tensor<F> {
+        let values = values.iter().map(|v| F::from(*v)).collect::<Vec<_>>();
+        Tensor::new(Some(&values), dims).unwrap()
+    }
+
+    fn circuit(inputs: [&Tensor<F>; 3]) -> IffCircuit<F> {
+        IffCircuit::<F> {
+            inputs: inputs.map(|t| ValTensor::from(t.map(Value::known))),
+        }
+    }
+
+    #[test]
+    fn iffcircuit() {
+        let mask = tensor(&[1, 0, 0, 1], &[2, 2]);
+        let b = tensor(&[1, 2, 3, 4], &[2, 2]);
+        let a = tensor(&[5, 6, 7, 8], &[2, 2]);
+
+        let output = Op::<F>::f(&PolyOp::Iff, &[mask.clone(), b.clone(), a.clone()])
+            .unwrap()
+            .output;
+        assert_eq!(output, tensor(&[5, 2, 3, 8], &[2, 2]));
+
+        let prover = MockProver::run(K as u32, &circuit([&mask, &b, &a]), vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+
+    #[test]
+    fn iffcircuit_broadcast() {
+        // a mask per row, broadcast over the columns
+        let mask = tensor(&[1, 0], &[2, 1]);
+        let b = tensor(&[1, 2, 3, 4], &[2, 2]);
+        let a = tensor(&[5, 6, 7, 8], &[2, 2]);
+
+        let output = Op::<F>::f(&PolyOp::Iff, &[mask.clone(), b.clone(), a.clone()])
+            .unwrap()
+            .output;
+        assert_eq!(output, tensor(&[5, 6, 3, 4], &[2, 2]));
+
+        let prover = MockProver::run(K as u32, &circuit([&mask, &b, &a]), vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+
+    #[test]
+    fn iffcircuit_non_boolean_mask() {
+        let mask = tensor(&[2, 0, 0, 1], &[2, 2]);
+        let b = tensor(&[1, 2, 3, 4], &[2, 2]);
+        let a = tensor(&[5, 6, 7, 8], &[2, 2]);
+
+        assert!(Op::<F>::f(&PolyOp::Iff, &[mask.clone(), b.clone(), a.clone()]).is_err());
+
+        let prover = MockProver::run(K as u32, &circuit([&mask, &b, &a]), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod comparison {
+
+    use super::*;
+    use crate::fieldutils::i128_to_felt;
+
+    const K: usize = 10;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct ComparisonCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        op: HybridOp,
+    }
+
+    impl Circuit<F> for ComparisonCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN);
+            let b = VarTensor::new_advice(cs, K, LEN);
+            let output = VarTensor::new_advice(cs, K, LEN);
+            let mut config = Self::Config::configure(cs, &[a, b.clone()], &output, CheckMode::SAFE);
+            config
+                .configure_lookup(
+                    cs,
+                    &b,
+                    &output,
+                    8,
+                    &LookupOp::GreaterThan {
+                        a: crate::circuit::utils::F32(0.),
+                    },
+                )
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0);
+                    config
+                        .layout(&mut region, &self.inputs.clone(), Box::new(self.op.clone()))
+                        .map_err(|_| Error::Synthesis)
+                },
+            )?;
+
+            Ok(())
+        }
+    }
+
+    fn tensor(values: &[i128], dims: &[usize]) -> Tensor<F> {
+        let values = values.iter().map(|v| i128_to_felt(*v)).collect::<Vec<_>>();
+        Tensor::new(Some(&values), dims).unwrap()
+    }
+
+    fn check(op: HybridOp, a: &Tensor<F>, b: &Tensor<F>, expected: &Tensor<F>) {
+        let output = Op::<F>::f(&op, &[a.clone(), b.clone()]).unwrap().output;
+        assert_eq!(&output, expected);
+
+        let circuit = ComparisonCircuit::<F> {
+            inputs: [a, b].map(|t| ValTensor::from(t.map(Value::known))),
+            op,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+
+    #[test]
+    fn greatercircuit() {
+        let a = tensor(&[1, 5, -3, 2], &[2, 2]);
+        let b = tensor(&[2, 4, -3, -1], &[2, 2]);
+        check(HybridOp::Greater, &a, &b, &tensor(&[0, 1, 0, 1], &[2, 2]));
+    }
+
+    #[test]
+    fn lesscircuit_broadcast() {
+        let a = tensor(&[1, 5, -3, 2], &[2, 2]);
+        let b = tensor(&[2], &[1]);
+        check(HybridOp::Less, &a, &b, &tensor(&[1, 0, 1, 0], &[2, 2]));
+    }
+
+    #[test]
+    fn equalcircuit() {
+        let a = tensor(&[1, 5, -3, 2], &[2, 2]);
+        let b = tensor(&[1, 4, -3, -1], &[2, 2]);
+        check(HybridOp::Equal, &a, &b, &tensor(&[1, 0, 1, 0], &[2, 2]));
+    }
+}
+
+#[cfg(test)]
+mod broadcast_elementwise {
+
+    use super::*;
+    use crate::fieldutils::i128_to_felt;
+
+    const K: usize = 10;
+    const LEN: usize = 6;
+
+    #[derive(Clone)]
+    struct ElementwiseCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        op: Box<dyn Op<F>>,
+    }
+
+    impl Circuit<F> for ElementwiseCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN);
+            let b = VarTensor::new_advice(cs, K, LEN);
+            let output = VarTensor::new_advice(cs, K, LEN);
+            let mut config = Self::Config::configure(cs, &[a, b.clone()], &output, CheckMode::SAFE);
+            config
+                .configure_lookup(cs, &b, &output, 8, &LookupOp::ReLU { scale: 1 })
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0);
+                    config
+                        .layout(&mut region, &self.inputs.clone(), self.op.clone())
+                        .map_err(|_| Error::Synthesis)
+                },
+            )?;
+
+            Ok(())
+        }
+    }
+
+    fn tensor(values: &[i128], dims: &[usize]) -> Tensor<F> {
+        let values = values.iter().map(|v| i128_to_felt(*v)).collect::<Vec<_>>();
+        Tensor::new(Some(&values), dims).unwrap()
+    }
+
+    fn circuit(op: Box<dyn Op<F>>, a: &Tensor<F>, b: &Tensor<F>) -> ElementwiseCircuit<F> {
+        ElementwiseCircuit::<F> {
+            inputs: [a, b].map(|t| ValTensor::from(t.map(Value::known))),
+            op,
+        }
+    }
+
+    fn check(op: Box<dyn Op<F>>, a: &Tensor<F>, b: &Tensor<F>, expected: &Tensor<F>) {
+        let output = op.f(&[a.clone(), b.clone()]).unwrap().output;
+        assert_eq!(&output, expected);
+
+        let prover = MockProver::run(K as u32, &circuit(op, a, b), vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+
+    #[test]
+    fn multcircuit_broadcast() {
+        let a = tensor(&[1, -2, 3, 4, 0, -6], &[2, 3]);
+        let b = tensor(&[2, -1, 3], &[1, 3]);
+        let expected = tensor(&[2, 2, 9, 8, 0, -18], &[2, 3]);
+        check(Box::new(PolyOp::Mult), &a, &b, &expected);
+
+        let b = tensor(&[-3], &[1]);
+        let expected = tensor(&[-3, 6, -9, -12, 0, 18], &[2, 3]);
+        check(Box::new(PolyOp::Mult), &a, &b, &expected);
+    }
+
+    #[test]
+    fn subcircuit_broadcast() {
+        let a = tensor(&[1, -2, 3, 4, 0, -6], &[2, 3]);
+        let b = tensor(&[2, -1, 3], &[1, 3]);
+        let expected = tensor(&[-1, -1, 0, 2, 1, -9], &[2, 3]);
+        check(Box::new(PolyOp::Sub), &a, &b, &expected);
+
+        let b = tensor(&[5], &[1]);
+        let expected = tensor(&[-4, -7, -2, -1, -5, -11], &[2, 3]);
+        check(Box::new(PolyOp::Sub), &a, &b, &expected);
+    }
+
+    #[test]
+    fn divcircuit_broadcast() {
+        let a = tensor(&[6, -7, 5, 1, 0, 3], &[2, 3]);
+        let b = tensor(&[2, 2, -2], &[1, 3]);
+        // halves round such that the remainder is positive
+        let expected = tensor(&[3, -4, -2, 0, 0, -1], &[2, 3]);
+        check(Box::new(HybridOp::Div { scale: 1 }), &a, &b, &expected);
+
+        // the denominator is at a scale of 2, so the numerator is multiplied by 2^1
+        let a = tensor(&[8, -6, 3, 0, 7, -1], &[2, 3]);
+        let b = tensor(&[3], &[1]);
+        let expected = tensor(&[5, -4, 2, 0, 5, -1], &[2, 3]);
+        check(Box::new(HybridOp::Div { scale: 2 }), &a, &b, &expected);
+    }
+
+    #[test]
+    fn divcircuit_by_zero() {
+        let a = tensor(&[6, -7, 5, 1, 0, 3], &[2, 3]);
+        let b = tensor(&[2, 0, -2], &[1, 3]);
+        let op = Box::new(HybridOp::Div { scale: 1 });
+        assert!(Op::<F>::f(op.as_ref(), &[a.clone(), b.clone()]).is_err());
+        assert!(MockProver::run(K as u32, &circuit(op, &a, &b), vec![]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod argmax {
+
+    use super::*;
+    use crate::fieldutils::{felt_to_i128, i128_to_felt};
+
+    const K: usize = 10;
+    const LEN: usize = 10;
+
+    #[derive(Clone)]
+    struct ArgMaxCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+        op: HybridOp,
+        // the index the layout should produce
+        expected: i128,
+    }
+
+    impl Circuit<F> for ArgMaxCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN);
+            let b = VarTensor::new_advice(cs, K, LEN);
+            let output = VarTensor::new_advice(cs, K, LEN);
+
+            let mut config = BaseConfig::configure(cs, &[a.clone(), b], &output, CheckMode::SAFE);
+            config
+                .configure_lookup(cs, &a, &output, 8, &LookupOp::ReLU { scale: 1 })
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0);
+                    let output = config
+                        .layout(
+                            &mut region,
+                            &[self.input.clone()],
+                            Box::new(self.op.clone()),
+                        )
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap();
+                    let evals = output.get_int_evals().map_err(|_| Error::Synthesis)?;
+                    if !evals.is_empty() && evals[0] != self.expected {
+                        return Err(Error::Synthesis);
+                    }
+                    Ok(())
+                },
+            )?;
+
+            Ok(())
+        }
+    }
+
+    fn logits() -> Tensor<F> {
+        let logits = [-3, 12, 5, -40, 27, 27, 0, 9, -1, 2];
+        Tensor::new(Some(&logits.map(i128_to_felt::<F>)), &[LEN]).unwrap()
+    }
+
+    #[test]
+    fn argmaxcircuit() {
+        let input = logits();
+        let op = HybridOp::ReduceArgMax { axis: 0 };
+
+        // ties resolve to the first index, as in onnx
+        let output = Op::<F>::f(&op, &[input.clone()]).unwrap().output;
+        assert_eq!(output.dims(), &[1]);
+        assert_eq!(felt_to_i128(output[0]), 4);
+
+        let circuit = ArgMaxCircuit::<F> {
+            input: ValTensor::from(input.map(Value::known)),
+            op,
+            expected: 4,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+
+    #[test]
+    fn argmincircuit() {
+        let input = logits();
+        let op = HybridOp::ReduceArgMin { axis: 0 };
+
+        let output = Op::<F>::f(&op, &[input.clone()]).unwrap().output;
+        assert_eq!(felt_to_i128(output[0]), 3);
+
+        let circuit = ArgMaxCircuit::<F> {
+            input: ValTensor::from(input.map(Value::known)),
+            op,
+            expected: 3,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+}
+
+#[cfg(test)]
+mod softmax {
+
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+
+    const K: usize = 18;
+    const LEN: usize = 3;
+    const SCALE: usize = i128::pow(2, 7) as usize;
+
+    #[derive(Clone)]
+    struct SoftmaxCircuit<F: PrimeField + TensorType + PartialOrd> {
+        pub input: ValTensor<F>,
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for SoftmaxCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN);
+            let b = VarTensor::new_advice(cs, K, LEN);
+            let output = VarTensor::new_advice(cs, K, LEN);
+            let mut config = Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE);
+            let advices = (0..2)
+                .map(|_| VarTensor::new_advice(cs, K, LEN))
+                .collect::<Vec<_>>();
+
+            config
+                .configure_lookup(
+                    cs,
+                    &advices[0],
+                    &advices[1],
+                    16,
+                    &LookupOp::Exp {
+                        scales: (SCALE, SCALE),
+                    },
+                )
+                .unwrap();
+            config
+                .configure_lookup(
+                    cs,
+                    &advices[0],
+                    &advices[1],
+                    16,
+                    &LookupOp::Recip {
+                        scale: SCALE.pow(2),
+                    },
+                )
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0);
+                        let _output = config
+                            .layout(
+                                &mut region,
+                                &[self.input.clone()],
+                                Box::new(HybridOp::Softmax {
+                                    scales: (SCALE, SCALE),
+                                }),
+                            )
+                            .unwrap();
+                        Ok(())
+                    },
+                )
+                .unwrap();
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn softmax_circuit() {
+        let input = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+
+        let circuit = SoftmaxCircuit::<F> {
+            input: ValTensor::from(input),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+}
+
+#[cfg(test)]
+mod rms_norm {
+
+    use super::*;
+    use crate::circuit::utils::F32;
+    use crate::fieldutils::{felt_to_i128, i128_to_felt};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+
+    const K: usize = 18;
+    const LEN: usize = 4;
+    const SCALE: usize = i128::pow(2, 7) as usize;
+    const EPS: f32 = 1e-5;
+    const INPUT: [f64; LEN] = [0.5, -1.0, 0.25, 0.75];
+
+    fn op() -> HybridOp {
+        HybridOp::RmsNorm {
+            scales: (SCALE, SCALE),
+            dim: LEN,
+            eps: F32(EPS),
+        }
+    }
+
+    fn quantized_input() -> Tensor<i128> {
+        Tensor::from(INPUT.iter().map(|x| (x * SCALE as f64).round() as i128))
+    }
+
+    #[derive(Clone)]
+    struct RmsNormCircuit<F: PrimeField + TensorType + PartialOrd> {
+        pub input: ValTensor<F>,
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for RmsNormCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, LEN);
+            let b = VarTensor::new_advice(cs, K, LEN);
+            let output = VarTensor::new_advice(cs, K, LEN);
+            let mut config = Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE);
+            let advices = (0..2)
+                .map(|_| VarTensor::new_advice(cs, K, LEN))
+                .collect::<Vec<_>>();
+
+            for lookup in Op::<F>::required_lookups(&op()) {
+                config
+                    .configure_lookup(cs, &advices[0], &advices[1], 16, &lookup)
+                    .unwrap();
+            }
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0);
+                        let _output = config
+                            .layout(&mut region, &[self.input.clone()], Box::new(op()))
+                            .unwrap();
+                        Ok(())
+                    },
+                )
+                .unwrap();
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn rms_norm_matches_float_reference() {
+        let input = quantized_input().map(i128_to_felt::<F>);
+        let output = Op::<F>::f(&op(), &[input]).unwrap().output;
+
+        let mean_square = INPUT.iter().map(|x| x * x).sum::<f64>() / LEN as f64;
+        let rms = (mean_square + EPS as f64).sqrt();
+        // the output is at the product of the input and output scales
+        for (x, y) in INPUT.iter().zip(output.iter()) {
+            let y = felt_to_i128(*y) as f64 / (SCALE * SCALE) as f64;
+            assert!((x / rms - y).abs() < 1e-2, "{} vs {}", x / rms, y);
+        }
+    }
+
+    #[test]
+    fn rms_norm_circuit() {
+        let input = quantized_input().map(|x| Value::known(i128_to_felt::<F>(x)));
+
+        let circuit = RmsNormCircuit::<F> {
             input: ValTensor::from(input),
             _marker: PhantomData,
         };
@@ -1,6 +1,8 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 #[cfg(not(target_arch = "wasm32"))]
 use ethers::types::H160;
+use halo2curves::bn256::Fr;
+use halo2curves::ff::PrimeField;
 #[cfg(feature = "python-bindings")]
 use pyo3::{
     conversion::{FromPyObject, PyTryFrom},
@@ -9,15 +11,18 @@ use pyo3::{
     types::PyString,
 };
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::error::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::RunArgs;
 
 use crate::circuit::CheckMode;
+use crate::graph::OutputRange;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::graph::TestDataSource;
-use crate::pfsys::TranscriptType;
+use crate::pfsys::{ProofFormat, TranscriptType};
 
 impl std::fmt::Display for TranscriptType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -91,6 +96,41 @@ impl<'source> FromPyObject<'source> for StrategyType {
     }
 }
 
+#[derive(ValueEnum, Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// The form the Solidity verifier is emitted in
+pub enum VerifierFormat {
+    /// A single self-contained `Verifier` contract, for small circuits
+    Single,
+    /// The verification partitioned into `VerifierLib0`, `VerifierLib1` ... libraries, deployed first, and a thin
+    /// `Verifier` entrypoint running them in turn, for circuits whose verifier exceeds the contract size limit
+    Split,
+    /// Split if the single contract's runtime bytecode would exceed the contract size limit
+    #[default]
+    Auto,
+}
+impl std::fmt::Display for VerifierFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_possible_value()
+            .expect("no values are skipped")
+            .get_name()
+            .fmt(f)
+    }
+}
+#[cfg(feature = "python-bindings")]
+/// Obtains VerifierFormat from PyObject (Required for VerifierFormat to be compatible with Python)
+impl<'source> FromPyObject<'source> for VerifierFormat {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        let trystr = <PyString as PyTryFrom>::try_from(ob)?;
+        let strval = trystr.to_string();
+        match strval.to_lowercase().as_str() {
+            "single" => Ok(VerifierFormat::Single),
+            "split" => Ok(VerifierFormat::Split),
+            "auto" => Ok(VerifierFormat::Auto),
+            _ => Err(PyValueError::new_err("Invalid value for VerifierFormat")),
+        }
+    }
+}
+
 #[derive(clap::ValueEnum, Debug, Default, Copy, Clone, Serialize, Deserialize)]
 /// Determines what the calibration pass should optimize for
 pub enum CalibrationTarget {
@@ -156,6 +196,19 @@ pub struct Cli {
     #[command(subcommand)]
     #[allow(missing_docs)]
     pub command: Commands,
+    /// Print a single structured JSON result to stdout and route all logs to stderr
+    #[arg(long, global = true)]
+    #[serde(default)]
+    pub json: bool,
+    /// Only log warnings and errors (`RUST_LOG` still takes precedence)
+    #[arg(long, global = true)]
+    #[serde(default)]
+    pub quiet: bool,
+    /// Path to a json config file (e.g `{"scale": 7, "srs_path": "kzg.srs"}`) setting any arguments of the command that aren't passed as flags.
+    /// Sharing a config between commands (e.g prove and verify) ensures they use identical settings
+    #[arg(long, global = true)]
+    #[serde(default)]
+    pub config: Option<PathBuf>,
 }
 
 impl Cli {
@@ -173,6 +226,282 @@ impl Cli {
     pub fn from_json(arg_json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(arg_json)
     }
+
+    /// Parses the command line (exiting on error, like [Parser::parse]), filling in any arguments that aren't passed
+    /// as flags from the json config file given by `--config`.
+    pub fn parse_with_config() -> Self {
+        Self::try_parse_from_with_config(std::env::args_os()).unwrap_or_else(|e| e.exit())
+    }
+
+    /// Parses `args`, filling in any arguments that aren't passed as flags from the json config file given by `--config`.
+    /// Flags on the command line override the config's values, and entries the command doesn't take are ignored.
+    pub fn try_parse_from_with_config<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString>,
+    {
+        let mut args = args
+            .into_iter()
+            .map(|a| a.into().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+
+        if let Some(config_path) = find_config_path(&args) {
+            let config = std::fs::read_to_string(&config_path)
+                .map_err(|e| e.to_string())
+                .and_then(|c| {
+                    serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&c)
+                        .map_err(|e| e.to_string())
+                })
+                .map_err(|e| {
+                    clap::Error::raw(
+                        clap::error::ErrorKind::Io,
+                        format!("failed to load config {}: {}\n", config_path.display(), e),
+                    )
+                })?;
+            let config_args = config_to_args(&args, &config);
+            args.extend(config_args);
+        }
+
+        Self::try_parse_from(args)
+    }
+}
+
+/// Finds the value of the `--config` flag (if any).
+fn find_config_path(args: &[String]) -> Option<PathBuf> {
+    args.iter()
+        .enumerate()
+        .find_map(|(i, a)| match a.strip_prefix("--config=") {
+            Some(path) => Some(PathBuf::from(path)),
+            None if a == "--config" => args.get(i + 1).map(PathBuf::from),
+            None => None,
+        })
+}
+
+/// Finds the name of the subcommand being run (the first argument that isn't a global flag).
+fn find_subcommand(args: &[String]) -> Option<&str> {
+    let mut args = args.iter().skip(1);
+    while let Some(a) = args.next() {
+        if a == "--config" {
+            args.next();
+        } else if !a.starts_with('-') {
+            return Some(a);
+        }
+    }
+    None
+}
+
+/// Parses a `key=value` pair.
+fn parse_key_val(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("invalid key=value pair: `{}`", s)),
+    }
+}
+
+/// Parses a challenge felt from a `0x` prefixed big-endian hex string (of at most 32 bytes) or a decimal integer.
+fn parse_challenge(s: &str) -> Result<Fr, String> {
+    let invalid = || format!("invalid challenge: `{}`", s);
+    match s.strip_prefix("0x") {
+        Some(hex_str) => {
+            let hex_str = match hex_str.len() % 2 {
+                0 => hex_str.to_string(),
+                _ => format!("0{}", hex_str),
+            };
+            let bytes = hex::decode(hex_str).map_err(|_| invalid())?;
+            if bytes.len() > 32 {
+                return Err(invalid());
+            }
+            // the field repr is little-endian
+            let mut repr = <Fr as PrimeField>::Repr::default();
+            for (i, b) in bytes.iter().rev().enumerate() {
+                repr.as_mut()[i] = *b;
+            }
+            Option::<Fr>::from(Fr::from_repr(repr)).ok_or_else(invalid)
+        }
+        None => s.parse::<u128>().map(Fr::from_u128).map_err(|_| invalid()),
+    }
+}
+
+/// The placeholders an [OutputTemplate] expands.
+const TEMPLATE_PLACEHOLDERS: [&str; 5] = ["input_stem", "input_name", "index", "timestamp", "hash"];
+
+/// A template for the path of each output of a batch command, expanded per input. The placeholders are
+/// `{input_stem}` (the input's file name without its extension), `{input_name}` (the input's file name), `{index}`
+/// (the input's position in the batch, from 0), `{timestamp}` (the unix time in seconds the batch started at) and
+/// `{hash}` (the first 16 hex digits of the sha256 of the output, e.g of the serialized proof). Outputs that would
+/// expand to the same path are told apart by a `_1`, `_2`, ... suffix, see [OutputPaths].
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OutputTemplate(String);
+
+impl std::str::FromStr for OutputTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("the output template is empty".to_string());
+        }
+        let mut rest = s;
+        while let Some(start) = rest.find(['{', '}']) {
+            if rest[start..].starts_with('}') {
+                return Err(format!("unmatched `}}` in output template `{}`", s));
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("unmatched `{{` in output template `{}`", s))?;
+            let placeholder = &rest[start + 1..start + end];
+            if !TEMPLATE_PLACEHOLDERS.contains(&placeholder) {
+                return Err(format!(
+                    "unknown placeholder `{{{}}}` in output template `{}`, expected one of {:?}",
+                    placeholder, s, TEMPLATE_PLACEHOLDERS
+                ));
+            }
+            rest = &rest[start + end + 1..];
+        }
+        Ok(OutputTemplate(s.to_string()))
+    }
+}
+
+impl std::fmt::Display for OutputTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl OutputTemplate {
+    /// Expands the template for the output `contents` of the `index`th `input` of a batch started at `timestamp`.
+    pub fn expand(&self, input: &Path, index: usize, timestamp: u64, contents: &[u8]) -> PathBuf {
+        let file = |name: Option<&std::ffi::OsStr>| {
+            name.map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+        let mut path = self.0.clone();
+        for (placeholder, value) in [
+            ("{input_stem}", file(input.file_stem())),
+            ("{input_name}", file(input.file_name())),
+            ("{index}", index.to_string()),
+            ("{timestamp}", timestamp.to_string()),
+        ] {
+            path = path.replace(placeholder, &value);
+        }
+        // only hash when needed, as outputs can be large
+        if path.contains("{hash}") {
+            let hash = hex::encode(Sha256::digest(contents));
+            path = path.replace("{hash}", &hash[..16]);
+        }
+        PathBuf::from(path)
+    }
+}
+
+/// Expands an [OutputTemplate] for each input of a batch, suffixing paths already taken by an earlier output of the
+/// batch (`proof.json`, `proof_1.json`, ...) such that no output overwrites another. Files left by earlier runs are
+/// overwritten, use `{timestamp}` or `{hash}` to keep them.
+#[derive(Clone, Debug)]
+pub struct OutputPaths {
+    template: OutputTemplate,
+    timestamp: u64,
+    taken: HashSet<PathBuf>,
+}
+
+impl OutputPaths {
+    /// Starts a batch now.
+    pub fn new(template: OutputTemplate) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self::with_timestamp(template, timestamp)
+    }
+
+    /// Starts a batch at `timestamp`.
+    pub fn with_timestamp(template: OutputTemplate, timestamp: u64) -> Self {
+        OutputPaths {
+            template,
+            timestamp,
+            taken: HashSet::new(),
+        }
+    }
+
+    /// The path of the output `contents` of the `index`th `input`, distinct from those of the outputs before it.
+    pub fn next(&mut self, input: &Path, index: usize, contents: &[u8]) -> PathBuf {
+        let path = self.template.expand(input, index, self.timestamp, contents);
+        let mut candidate = path.clone();
+        let mut suffix = 1;
+        while self.taken.contains(&candidate) {
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let name = match path.extension() {
+                Some(ext) => format!("{}_{}.{}", stem, suffix, ext.to_string_lossy()),
+                None => format!("{}_{}", stem, suffix),
+            };
+            candidate = path.with_file_name(name);
+            suffix += 1;
+        }
+        self.taken.insert(candidate.clone());
+        candidate
+    }
+}
+
+/// Converts the entries of a json config into flags for the subcommand being run, skipping those already passed on the
+/// command line and those the subcommand doesn't take.
+fn config_to_args(
+    args: &[String],
+    config: &serde_json::Map<String, serde_json::Value>,
+) -> Vec<String> {
+    let cli = Cli::command();
+    let subcommand = match find_subcommand(args).and_then(|name| cli.find_subcommand(name)) {
+        Some(s) => s,
+        None => return vec![],
+    };
+
+    let mut config_args = vec![];
+    for (key, value) in config {
+        // accept both the rust (snake case) and the cli (kebab case) names
+        let long = key.replace('_', "-");
+        let arg = match subcommand
+            .get_arguments()
+            .find(|a| a.get_long() == Some(long.as_str()))
+        {
+            Some(arg) => arg,
+            None => {
+                log::debug!("{} doesn't take {}, skipping", subcommand.get_name(), key);
+                continue;
+            }
+        };
+
+        let flag = format!("--{}", long);
+        let is_set = args.iter().any(|a| {
+            a == &flag
+                || a.starts_with(&format!("{}=", flag))
+                || arg.get_short().map_or(false, |s| {
+                    !a.starts_with("--") && a.starts_with(&format!("-{}", s))
+                })
+        });
+        if is_set {
+            continue;
+        }
+
+        let values = match value {
+            serde_json::Value::Bool(b) if !arg.get_action().takes_values() => {
+                if *b {
+                    config_args.push(flag);
+                }
+                continue;
+            }
+            serde_json::Value::Array(values) => values.iter().collect::<Vec<_>>(),
+            value => vec![value],
+        };
+        for value in values {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                v => v.to_string(),
+            };
+            // the `=` form keeps values such as negative numbers from being parsed as flags
+            config_args.push(format!("{}={}", flag, value));
+        }
+    }
+    config_args
 }
 
 #[allow(missing_docs)]
@@ -189,16 +518,110 @@ pub enum Commands {
         args: RunArgs,
     },
 
-    #[cfg(feature = "render")]
-    /// Renders the model circuit to a .png file. For an overview of how to interpret these plots, see https://zcash.github.io/halo2/user/dev-tools.html
+    /// Prints a fingerprint of the model's computation (operations, shapes, constants, scale and bits) that is stable
+    /// across re-exports of the same model, e.g with reordered nodes or different producer metadata
+    #[command(arg_required_else_help = true)]
+    ModelHash {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long)]
+        model: PathBuf,
+        /// proving arguments
+        #[clap(flatten)]
+        args: RunArgs,
+    },
+
+    /// Prints the ONNX opsets the model was exported with, and the ops whose semantics at these opsets ezkl doesn't
+    /// (or may not) follow, see [crate::graph::opset::OpsetReport]
+    #[command(arg_required_else_help = true)]
+    ModelInfo {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long)]
+        model: PathBuf,
+    },
+
+    /// Exports the largest prefix of the model whose ops are supported as a standalone .onnx model, whose outputs are
+    /// the values the rest of the model consumes, such that the supported part of a model can still be proven. Also
+    /// reports the first unsupported node, where support ends
+    #[command(arg_required_else_help = true)]
+    ExportSupported {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long)]
+        model: PathBuf,
+        /// The path to output the supported prefix .onnx file to
+        #[arg(short = 'O', long, default_value = "supported.onnx")]
+        output: PathBuf,
+        /// proving arguments
+        #[clap(flatten)]
+        args: RunArgs,
+    },
+
+    /// Exports the model's weights per layer, quantized as they are used in the circuit, alongside the scale each was
+    /// quantized at, such that they can be re-derived from the float weights of the .onnx file and compared
+    #[command(arg_required_else_help = true)]
+    ExportWeights {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long)]
+        model: PathBuf,
+        /// The path to the circuit settings .json file the circuit was built with
+        #[arg(short = 'S', long)]
+        settings_path: PathBuf,
+        /// The path to output the .json file of quantized weights to
+        #[arg(short = 'O', long, default_value = "weights.json")]
+        output: PathBuf,
+    },
+
+    /// Runs the forward pass the circuit constrains and compares each layer's output against the expected outputs of
+    /// a reference implementation, reporting the max per-element error per layer and flagging the first layer that
+    /// diverges beyond tolerance
+    #[command(arg_required_else_help = true)]
+    ValidateReference {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long)]
+        model: PathBuf,
+        /// The path to the .json data file holding the model's input
+        #[arg(short = 'D', long)]
+        data: PathBuf,
+        /// The path to the .json file of expected layer outputs, mapping node indices (as listed by `table`) to
+        /// flattened float tensors
+        #[arg(long)]
+        reference: PathBuf,
+        /// The largest absolute error per element allowed in a layer's output
+        #[arg(long, default_value = "0.01")]
+        reference_tolerance: f64,
+        /// proving arguments
+        #[clap(flatten)]
+        args: RunArgs,
+    },
+
+    /// Bounds how far each of the circuit's (dequantized) outputs can be from the output of the float model, by
+    /// propagating the quantization error of the model's scale and bits layer by layer through the graph
     #[command(arg_required_else_help = true)]
+    ErrorBounds {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long)]
+        model: PathBuf,
+        /// The largest absolute value of any element of the model's inputs the bounds should hold for
+        #[arg(long)]
+        input_magnitude: f64,
+        /// proving arguments
+        #[clap(flatten)]
+        args: RunArgs,
+    },
+
+    #[cfg(feature = "render")]
+    /// Renders the model circuit's column and region layout to a .png file (also available as `plot-layout`). For an
+    /// overview of how to interpret these plots, see https://zcash.github.io/halo2/user/dev-tools.html
+    #[command(arg_required_else_help = true, alias = "plot-layout")]
     RenderCircuit {
         /// The path to the .onnx model file
         #[arg(short = 'M', long)]
         model: PathBuf,
         /// Path to save the .png circuit render
-        #[arg(short = 'O', long)]
+        #[arg(short = 'O', long, alias = "out")]
         output: PathBuf,
+        /// Draws the region names over the layout, which is only legible for circuits with few regions
+        #[arg(long, default_value = "false")]
+        show_labels: bool,
         /// proving arguments
         #[clap(flatten)]
         args: RunArgs,
@@ -221,6 +644,35 @@ pub enum Commands {
         settings_path: PathBuf,
     },
 
+    /// Quantizes the float inputs (and outputs) of a .json data file, such that the exact quantized values a proof
+    /// uses are frozen in the output
+    #[command(arg_required_else_help = true)]
+    QuantizeInput {
+        /// The path to the .json data file
+        #[arg(short = 'D', long)]
+        data: PathBuf,
+        /// The path to output the quantized .json data file to
+        #[arg(short = 'O', long, default_value = "quantized_input.json")]
+        output: PathBuf,
+        /// The denominator in the fixed point representation to quantize with, as a log base 2 exponent
+        #[arg(long, value_parser = crate::graph::parse_scale)]
+        scale: u32,
+    },
+
+    /// Dequantizes a .json data file quantized with `quantize-input` back to floats
+    #[command(arg_required_else_help = true)]
+    DequantizeInput {
+        /// The path to the quantized .json data file
+        #[arg(short = 'D', long)]
+        data: PathBuf,
+        /// The path to output the dequantized .json data file to
+        #[arg(short = 'O', long, default_value = "dequantized_input.json")]
+        output: PathBuf,
+        /// The denominator in the fixed point representation the data was quantized with, as a log base 2 exponent
+        #[arg(long, value_parser = crate::graph::parse_scale)]
+        scale: u32,
+    },
+
     /// Produces the proving hyperparameters, from run-args
     #[command(arg_required_else_help = true)]
     GenSettings {
@@ -233,6 +685,10 @@ pub enum Commands {
         /// proving arguments
         #[clap(flatten)]
         args: RunArgs,
+        /// Path to a .json file holding the per-channel `axis`, `mean` and `std` to normalize the model's inputs by
+        /// within the circuit
+        #[arg(long)]
+        input_normalization: Option<PathBuf>,
     },
 
     /// Calibrates the proving scale, lookup bits and logrows from a circuit settings file.
@@ -253,6 +709,64 @@ pub enum Commands {
         target: CalibrationTarget,
     },
 
+    /// Searches for the scale and lookup bits whose outputs stay within a target error of the float model's on the
+    /// inputs of a calibration dataset, picking the smallest bits that fit in max-logrows.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[command(arg_required_else_help = true)]
+    Calibrate {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long)]
+        model: PathBuf,
+        /// The path to the .json calibration data file.
+        #[arg(short = 'D', long = "data")]
+        data: PathBuf,
+        /// The largest absolute error of any output allowed, in the units of the model's outputs
+        #[arg(long, default_value = "0.01")]
+        target_error: f64,
+        /// Path to save the circuit settings at the recommended scale, bits and logrows to (optional)
+        #[arg(short = 'O', long)]
+        settings_path: Option<PathBuf>,
+        /// The largest logrows the circuit may need before refusing to run, rather than exhausting the machine's
+        /// memory or running for hours. Defaults to the largest logrows of the public SRS (26)
+        #[arg(long, env = "EZKL_MAX_LOGROWS")]
+        max_logrows: Option<u32>,
+        /// proving arguments
+        #[clap(flatten)]
+        args: RunArgs,
+    },
+
+    /// Scales down the nodes feeding the lookups whose inputs overflow the settings' lookup bits on the inputs of a
+    /// calibration dataset, rather than raising the bits of every lookup, and reports the
+    /// rescaled nodes and the resulting output error. Run after calibrating the settings, and before compiling the model
+    #[cfg(not(target_arch = "wasm32"))]
+    #[command(name = "rebalance-scales", arg_required_else_help = true)]
+    RebalanceScales {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long)]
+        model: PathBuf,
+        /// The path to the .json calibration data file.
+        #[arg(short = 'D', long = "data")]
+        data: PathBuf,
+        /// Path to circuit_settings file to read in AND overwrite.
+        #[arg(short = 'O', long, default_value = "settings.json")]
+        settings_path: PathBuf,
+    },
+
+    /// Estimates the degree of the SRS (trusted setup) a model's circuit needs, and checks an existing SRS against it
+    #[cfg(not(target_arch = "wasm32"))]
+    #[command(name = "required-srs", arg_required_else_help = true)]
+    RequiredSrs {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long)]
+        model: PathBuf,
+        /// The path to an existing srs file to check (optional)
+        #[arg(long)]
+        srs_path: Option<PathBuf>,
+        /// proving arguments
+        #[clap(flatten)]
+        args: RunArgs,
+    },
+
     /// Generates a dummy SRS
     #[command(name = "gen-srs", arg_required_else_help = true)]
     GenSrs {
@@ -281,6 +795,35 @@ pub enum Commands {
         #[arg(long, default_value = "safe")]
         check: CheckMode,
     },
+    /// Extracts the verifier-only params (the few points a KZG verifier uses) from a prover SRS, to ship to verifiers.
+    /// These can be passed to the verify and create-evm-verifier commands, but can't be used to prove
+    #[command(name = "extract-verifier-srs", arg_required_else_help = true)]
+    ExtractVerifierSrs {
+        /// The path to the prover srs file
+        #[arg(long)]
+        srs_path: PathBuf,
+        /// The path to the circuit settings file, the verifier params are sized to its logrows
+        #[arg(short = 'S', long)]
+        settings_path: PathBuf,
+        /// The path to output the verifier params to
+        #[arg(long, default_value = "kzg_verifier.srs")]
+        output_path: PathBuf,
+    },
+    /// Compares two verification keys, e.g to check that regenerated keys haven't changed. Reports which parts of the
+    /// keys differ (circuit shape, fixed and permutation commitments) and exits with an error if they aren't identical
+    #[command(name = "vk-diff", arg_required_else_help = true)]
+    VkDiff {
+        /// The path to the first verification key
+        vk_path: PathBuf,
+        /// The path to the second verification key
+        other_vk_path: PathBuf,
+        /// The path to the circuit settings the first key was generated with
+        #[arg(short = 'S', long)]
+        settings_path: PathBuf,
+        /// The path to the circuit settings the second key was generated with, defaults to `settings_path`
+        #[arg(long)]
+        other_settings_path: Option<PathBuf>,
+    },
     /// Loads model and input and runs mock prover (for testing)
     #[command(arg_required_else_help = true)]
     Mock {
@@ -386,6 +929,10 @@ pub enum Commands {
         /// The path to load circuit params from
         #[arg(short = 'S', long)]
         settings_path: PathBuf,
+        /// The largest logrows the circuit may need before refusing to run, rather than exhausting the machine's
+        /// memory or running for hours. Defaults to the largest logrows of the public SRS (26)
+        #[arg(long, env = "EZKL_MAX_LOGROWS")]
+        max_logrows: Option<u32>,
     },
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -485,6 +1032,128 @@ pub enum Commands {
         /// run sanity checks during calculations (safe or unsafe)
         #[arg(long, default_value = "safe")]
         check_mode: CheckMode,
+        /// The largest logrows the circuit may need before refusing to run, rather than exhausting the machine's
+        /// memory or running for hours. Defaults to the largest logrows of the public SRS (26)
+        #[arg(long, env = "EZKL_MAX_LOGROWS")]
+        max_logrows: Option<u32>,
+        /// The encoding of the output proof file, loading a proof detects it automatically
+        #[arg(
+            long,
+            require_equals = true,
+            num_args = 0..=1,
+            default_value_t = ProofFormat::Json,
+            value_enum
+        )]
+        proof_format: ProofFormat,
+        /// Metadata to attach to the proof as `key=value` (e.g a request id), can be passed several times
+        #[arg(long = "metadata", value_parser = parse_key_val)]
+        metadata: Vec<(String, String)>,
+        /// Leaves the outputs out of the saved proof when the inputs and outputs are public, as the verifier can
+        /// recompute them from the inputs and the compiled model (passed to `verify` with `--compiled-model`).
+        /// Shrinks proofs of models with large outputs
+        #[arg(long, default_value = "false")]
+        elide_outputs: bool,
+        /// The verifier-supplied challenge to bind the proof to, for circuits set up with `--challenge`, as a `0x`
+        /// prefixed big-endian hex felt or a decimal integer. Overrides any challenge in the witness file
+        #[arg(long, value_parser = parse_challenge)]
+        challenge: Option<Fr>,
+        /// The `min,max` bounds (e.g `700,850`) the first output is proven to lie within, for circuits set up with
+        /// `--assert-output-range`. Only the bounds become public inputs. Overrides any bounds in the witness file
+        #[arg(long)]
+        output_range: Option<OutputRange>,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Generates keys, proves and verifies in one go using the in-memory keys and params, a quick end to end check of a model
+    #[command(name = "prove-verify", arg_required_else_help = true)]
+    ProveVerify {
+        /// The path to the .json witness file, which should include both the network input (possibly private) and the network output (public input to the proof)
+        #[arg(short = 'W', long)]
+        witness: PathBuf,
+        /// The path to the compiled model file
+        #[arg(short = 'M', long)]
+        compiled_model: PathBuf,
+        /// The parameter path
+        #[arg(long)]
+        srs_path: PathBuf,
+        /// The path to load circuit params from
+        #[arg(short = 'S', long)]
+        settings_path: PathBuf,
+        #[arg(
+            long,
+            require_equals = true,
+            num_args = 0..=1,
+            default_value_t = TranscriptType::Blake,
+            value_enum
+        )]
+        transcript: TranscriptType,
+        /// run sanity checks during calculations (safe or unsafe)
+        #[arg(long, default_value = "safe")]
+        check_mode: CheckMode,
+        /// A directory to checkpoint the assigned witness and generated keys to, such that a restarted prove resumes
+        /// from the last checkpoint rather than redoing them. Proof creation itself is not resumable.
+        #[arg(long)]
+        checkpoint_dir: Option<PathBuf>,
+        /// Saves the verified proof at this path, expanded for the witness as for `prove-all` (e.g
+        /// `{input_stem}_{hash}.proof`). The proof isn't saved if unset
+        #[arg(long)]
+        output_template: Option<OutputTemplate>,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Proves each of a batch of witnesses with the same keys, saving each proof at the path `--output-template`
+    /// expands to for its witness
+    #[command(name = "prove-all", arg_required_else_help = true)]
+    ProveAll {
+        /// The paths to the .json witness files, one proof is generated per witness
+        #[arg(short = 'W', long = "witness", num_args = 1.., required = true)]
+        witnesses: Vec<PathBuf>,
+        /// The path to the compiled model file
+        #[arg(short = 'M', long)]
+        compiled_model: PathBuf,
+        /// The path to load the desired proving key file
+        #[arg(long)]
+        pk_path: PathBuf,
+        /// The parameter path
+        #[arg(long)]
+        srs_path: PathBuf,
+        /// The path to load circuit params from
+        #[arg(short = 'S', long)]
+        settings_path: PathBuf,
+        #[arg(
+            long,
+            require_equals = true,
+            num_args = 0..=1,
+            default_value_t = TranscriptType::Blake,
+            value_enum
+        )]
+        transcript: TranscriptType,
+        /// run sanity checks during calculations (safe or unsafe)
+        #[arg(long, default_value = "safe")]
+        check_mode: CheckMode,
+        /// The encoding of the output proof files
+        #[arg(
+            long,
+            require_equals = true,
+            num_args = 0..=1,
+            default_value_t = ProofFormat::Json,
+            value_enum
+        )]
+        proof_format: ProofFormat,
+        /// The path of each proof, expanding `{input_stem}` and `{input_name}` (the witness file's name without and
+        /// with its extension), `{index}` (the witness's position in the batch), `{timestamp}` (the unix time the
+        /// batch started at) and `{hash}` (the first 16 hex digits of the proof's sha256). Proofs expanding to the
+        /// same path get a `_1`, `_2`, ... suffix rather than overwriting one another
+        #[arg(long, default_value = "{input_stem}.proof")]
+        output_template: OutputTemplate,
+        /// The largest logrows the circuit may need before refusing to run, rather than exhausting the machine's
+        /// memory or running for hours. Defaults to the largest logrows of the public SRS (26)
+        #[arg(long, env = "EZKL_MAX_LOGROWS")]
+        max_logrows: Option<u32>,
+        /// Metadata to attach to each proof as `key=value` (e.g a batch id), can be passed several times
+        #[arg(long = "metadata", value_parser = parse_key_val)]
+        metadata: Vec<(String, String)>,
+        /// Leaves the outputs out of the saved proofs when the inputs and outputs are public, see `prove`
+        #[arg(long, default_value = "false")]
+        elide_outputs: bool,
     },
     #[cfg(not(target_arch = "wasm32"))]
     /// Creates an EVM verifier for a single proof
@@ -505,6 +1174,10 @@ pub enum Commands {
         /// The path to output the Solidity verifier ABI
         #[arg(long, default_value = "verifier_abi.json")]
         abi_path: PathBuf,
+        /// Whether to emit a single contract or libraries and a thin entrypoint, by default picked from the single
+        /// contract's bytecode size
+        #[arg(long, default_value = "auto")]
+        verifier_format: VerifierFormat,
     },
     #[cfg(not(target_arch = "wasm32"))]
     /// Creates an EVM verifier that attests to on-chain inputs for a single proof
@@ -555,6 +1228,20 @@ pub enum Commands {
         #[arg(long)]
         aggregation_settings: Vec<PathBuf>,
     },
+    /// Computes the commitment to a model's (hashed) params that its vendor publishes, such that clients can check
+    /// proofs are generated with the committed params without the params being disclosed to them
+    #[command(name = "commit-params", arg_required_else_help = true)]
+    CommitParams {
+        /// The path to the compiled model file, set up with `--param-visibility hashed`
+        #[arg(short = 'M', long)]
+        compiled_model: PathBuf,
+        /// The path to load circuit params from
+        #[arg(short = 'S', long)]
+        settings_path: PathBuf,
+        /// The path to output the commitment to
+        #[arg(short = 'O', long, default_value = "params_commitment.json")]
+        output: PathBuf,
+    },
     /// Verifies a proof, returning accept or reject
     #[command(arg_required_else_help = true)]
     Verify {
@@ -570,6 +1257,144 @@ pub enum Commands {
         /// The kzg srs path
         #[arg(long)]
         srs_path: PathBuf,
+        /// The path to the compiled model (optional). If set, checks that the proof commits to the model's (hashed) params.
+        /// Required to recompute the outputs of proofs generated with `--elide-outputs`
+        #[arg(long)]
+        compiled_model: Option<PathBuf>,
+        /// The path to a params commitment published by the model's vendor (see `commit-params`). If set, checks that
+        /// the proof was generated with the committed (hashed) params, without the params themselves
+        #[arg(long)]
+        params_commitment: Option<PathBuf>,
+        /// The challenge the proof must have been generated against, required for circuits set up with
+        /// `--challenge` (formatted as for `prove`)
+        #[arg(long, value_parser = parse_challenge)]
+        challenge: Option<Fr>,
+        /// The `min,max` bounds the proof must assert its first output lies within, required for circuits set up
+        /// with `--assert-output-range`
+        #[arg(long)]
+        output_range: Option<OutputRange>,
+        /// If the proof fails to verify, record the verifier's transcript and retry with the other transcript types,
+        /// reporting whether the proof was merely labelled with the wrong transcript
+        #[arg(long, default_value = "false")]
+        debug_transcript: bool,
+    },
+    /// Generates IPA proving and verifying keys, with params derived deterministically rather than loaded from an srs
+    #[command(name = "setup-ipa", arg_required_else_help = true)]
+    SetupIpa {
+        /// The path to the compiled model file
+        #[arg(short = 'M', long)]
+        compiled_model: PathBuf,
+        /// The path to output the verfication key file
+        #[arg(long, default_value = "vk.key")]
+        vk_path: PathBuf,
+        /// The path to output the proving key file
+        #[arg(long, default_value = "pk.key")]
+        pk_path: PathBuf,
+        /// The path to load circuit params from
+        #[arg(short = 'S', long)]
+        settings_path: PathBuf,
+        /// The largest logrows the circuit may need before refusing to run, rather than exhausting the machine's
+        /// memory or running for hours. Defaults to the largest logrows of the public SRS (26)
+        #[arg(long, env = "EZKL_MAX_LOGROWS")]
+        max_logrows: Option<u32>,
+    },
+    /// Proves with the IPA commitment scheme, which needs no srs. IPA proofs are larger than KZG proofs and much
+    /// slower to verify for large circuits, and can't be verified on-chain
+    #[command(name = "prove-ipa", arg_required_else_help = true)]
+    ProveIpa {
+        /// The path to the .json witness file
+        #[arg(short = 'W', long)]
+        witness: PathBuf,
+        /// The path to the compiled model file
+        #[arg(short = 'M', long)]
+        compiled_model: PathBuf,
+        /// The path to load the proving key generated with `setup-ipa`
+        #[arg(long)]
+        pk_path: PathBuf,
+        /// The path to the desired output file
+        #[arg(long, default_value = "proof.proof")]
+        proof_path: PathBuf,
+        /// The path to load circuit params from
+        #[arg(short = 'S', long)]
+        settings_path: PathBuf,
+        /// run sanity checks during calculations (safe or unsafe)
+        #[arg(long, default_value = "safe")]
+        check_mode: CheckMode,
+        /// The largest logrows the circuit may need before refusing to run, rather than exhausting the machine's
+        /// memory or running for hours. Defaults to the largest logrows of the public SRS (26)
+        #[arg(long, env = "EZKL_MAX_LOGROWS")]
+        max_logrows: Option<u32>,
+    },
+    /// Verifies a proof generated with `prove-ipa`, returning accept or reject
+    #[command(name = "verify-ipa", arg_required_else_help = true)]
+    VerifyIpa {
+        /// The path to load circuit params from
+        #[arg(short = 'S', long)]
+        settings_path: PathBuf,
+        /// The path to the proof file
+        #[arg(long)]
+        proof_path: PathBuf,
+        /// The path to the verification key generated with `setup-ipa`
+        #[arg(long)]
+        vk_path: PathBuf,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Packs a proof, its verification key and settings, and the hash of its params into a single bundle file
+    #[command(name = "create-bundle", arg_required_else_help = true)]
+    CreateBundle {
+        /// The path to load circuit params from
+        #[arg(short = 'S', long)]
+        settings_path: PathBuf,
+        /// The path to the proof file
+        #[arg(long)]
+        proof_path: PathBuf,
+        /// The path to the verification key file
+        #[arg(long)]
+        vk_path: PathBuf,
+        /// The kzg srs path the proof was generated with, only its hash is stored in the bundle
+        #[arg(long)]
+        srs_path: PathBuf,
+        /// The path to output the bundle to
+        #[arg(long, default_value = "proof.bundle")]
+        bundle_path: PathBuf,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Checks the contents of a bundle are consistent with each other and with the srs, then verifies its proof
+    #[command(name = "verify-bundle", arg_required_else_help = true)]
+    VerifyBundle {
+        /// The path to the bundle file
+        #[arg(long)]
+        bundle_path: PathBuf,
+        /// The kzg srs path
+        #[arg(long)]
+        srs_path: PathBuf,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Combines the bundles of two consecutive parts of a split model, where the first's public outputs are the
+    /// second's public inputs, into a single file together with the boundary value between them
+    #[command(name = "combine-split", arg_required_else_help = true)]
+    CombineSplit {
+        /// The path to the bundle of the part of the model computing the boundary
+        #[arg(long)]
+        first_bundle: PathBuf,
+        /// The path to the bundle of the part of the model taking the boundary as its input
+        #[arg(long)]
+        second_bundle: PathBuf,
+        /// The path to output the split proof to
+        #[arg(long, default_value = "proof.split")]
+        split_path: PathBuf,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Checks the boundary of a split proof is both the first part's public outputs and the second part's public
+    /// inputs, then verifies both proofs
+    #[command(name = "verify-split", arg_required_else_help = true)]
+    VerifySplit {
+        /// The path to the split proof file
+        #[arg(long)]
+        split_path: PathBuf,
+        /// The kzg srs path, downsized to the logrows of each part
+        #[arg(long)]
+        srs_path: PathBuf,
     },
     /// Verifies an aggregate proof, returning accept or reject
     #[command(arg_required_else_help = true)]
@@ -647,4 +1472,160 @@ pub enum Commands {
         #[arg(long)]
         proof_path: PathBuf,
     },
+
+    /// Prints a summary of a proof file: its encoding and layout version, transcript, size, public input columns and
+    /// their values, and any metadata
+    #[command(name = "proof-info", arg_required_else_help = true)]
+    ProofInfo {
+        /// The path to the proof file
+        proof_path: PathBuf,
+        /// Path to the circuit settings .json file the proof was generated with (optional), whose scales the columns
+        /// are dequantized at, see [crate::graph::GraphSettings::instance_scales]
+        #[arg(short = 'S', long)]
+        settings_path: Option<PathBuf>,
+        /// The denominator in the fixed point representation to dequantize the public inputs with, as a log base 2
+        /// exponent, overriding the settings' scales. If neither is set (or the settings give a column no scale) the
+        /// columns are printed as (signed) integers
+        #[arg(long, value_parser = crate::graph::parse_scale)]
+        scale: Option<u32>,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Recomputes a proof's public inputs from the compiled model and the input the proof claims to be of (quantized
+    /// with the settings' scale), and reports any public input stored in the proof that differs. This doesn't verify
+    /// the proof, but catches public inputs altered to claim another output than the model computes
+    #[command(name = "audit-public-inputs", arg_required_else_help = true)]
+    AuditPublicInputs {
+        /// The path to the .json data file holding the claimed input
+        #[arg(short = 'D', long)]
+        data: PathBuf,
+        /// The path to the compiled model file
+        #[arg(short = 'M', long)]
+        compiled_model: PathBuf,
+        /// The path to load circuit params from
+        #[arg(short = 'S', long)]
+        settings_path: PathBuf,
+        /// The path to the proof file
+        #[arg(long)]
+        proof_path: PathBuf,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Checks whether a proof's transcript can be verified on-chain. The transcript is intrinsic to a proof, so a
+    /// non-EVM proof can't be converted and has to be reproved with an EVM transcript (optionally done here)
+    #[command(name = "check-transcript", arg_required_else_help = true)]
+    CheckTranscript {
+        /// The path to the proof file
+        #[arg(long)]
+        proof_path: PathBuf,
+        /// If the proof isn't EVM verifiable, reprove it with an EVM transcript and save the new proof here
+        #[arg(
+            long,
+            requires_all = ["witness", "compiled_model", "pk_path", "srs_path", "settings_path"]
+        )]
+        reprove_path: Option<PathBuf>,
+        /// The path to the .json witness file the proof was generated from (for reproving)
+        #[arg(short = 'W', long)]
+        witness: Option<PathBuf>,
+        /// The path to the compiled model file (for reproving)
+        #[arg(short = 'M', long)]
+        compiled_model: Option<PathBuf>,
+        /// The path to the proving key file (for reproving)
+        #[arg(long)]
+        pk_path: Option<PathBuf>,
+        /// The parameter path (for reproving)
+        #[arg(long)]
+        srs_path: Option<PathBuf>,
+        /// The path to load circuit params from (for reproving)
+        #[arg(short = 'S', long)]
+        settings_path: Option<PathBuf>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_with_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("ezkl.json");
+        std::fs::write(
+            &config_path,
+            r#"{"model": "network.onnx", "scale": 4, "bits": 10, "fuse_lookups": true, "srs_path": "kzg.srs"}"#,
+        )
+        .unwrap();
+
+        let args = [
+            "ezkl",
+            "table",
+            "--bits",
+            "12",
+            "--config",
+            config_path.to_str().unwrap(),
+        ];
+        let cli = Cli::try_parse_from_with_config(args).unwrap();
+        match cli.command {
+            Commands::Table { model, args } => {
+                assert_eq!(model, PathBuf::from("network.onnx"));
+                assert_eq!(args.scale, 4);
+                // flags override the config
+                assert_eq!(args.bits, 12);
+                assert!(args.fuse_lookups);
+            }
+            _ => panic!("expected the table command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_challenge() {
+        assert_eq!(parse_challenge("42").unwrap(), Fr::from(42));
+        assert_eq!(parse_challenge("0x2a").unwrap(), Fr::from(42));
+        assert_eq!(parse_challenge("0x102").unwrap(), Fr::from(258));
+        // the modulus isn't a canonical felt
+        let modulus = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+        assert!(parse_challenge(modulus).is_err());
+        assert!(parse_challenge("0xzz").is_err());
+        assert!(parse_challenge("-1").is_err());
+    }
+
+    #[test]
+    fn test_output_template() {
+        let template: OutputTemplate = "proofs/{input_stem}_{index}-{timestamp}.{hash}.proof"
+            .parse()
+            .unwrap();
+        let path = template.expand(Path::new("data/witness.json"), 3, 42, b"proof");
+        let hash = hex::encode(Sha256::digest(b"proof"));
+        assert_eq!(
+            path,
+            PathBuf::from(format!("proofs/witness_3-42.{}.proof", &hash[..16]))
+        );
+        let template: OutputTemplate = "{input_name}.proof".parse().unwrap();
+        assert_eq!(
+            template.expand(Path::new("data/witness.json"), 0, 0, b""),
+            PathBuf::from("witness.json.proof")
+        );
+
+        assert!("".parse::<OutputTemplate>().is_err());
+        assert!("{input}.proof".parse::<OutputTemplate>().is_err());
+        assert!("{input_stem.proof".parse::<OutputTemplate>().is_err());
+        assert!("input_stem}.proof".parse::<OutputTemplate>().is_err());
+
+        // inputs of the same name in different directories don't overwrite one another
+        let mut paths = OutputPaths::with_timestamp("{input_stem}.proof".parse().unwrap(), 0);
+        assert_eq!(
+            paths.next(Path::new("a/witness.json"), 0, b""),
+            PathBuf::from("witness.proof")
+        );
+        assert_eq!(
+            paths.next(Path::new("b/witness.json"), 1, b""),
+            PathBuf::from("witness_1.proof")
+        );
+        assert_eq!(
+            paths.next(Path::new("c/witness.json"), 2, b""),
+            PathBuf::from("witness_2.proof")
+        );
+        assert_eq!(
+            paths.next(Path::new("other.json"), 3, b""),
+            PathBuf::from("other.proof")
+        );
+    }
 }
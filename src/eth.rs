@@ -15,7 +15,9 @@ use ethers::prelude::Wallet;
 use ethers::providers::Middleware;
 use ethers::providers::{Http, Provider};
 use ethers::signers::Signer;
-use ethers::solc::{CompilerInput, Solc};
+#[cfg(not(target_arch = "wasm32"))]
+use ethers::solc::artifacts::BytecodeObject;
+use ethers::solc::{CompilerInput, CompilerOutput, Solc};
 use ethers::types::transaction::eip2718::TypedTransaction;
 use ethers::types::Bytes;
 use ethers::types::TransactionRequest;
@@ -53,6 +55,17 @@ const QUANTIZE_DATA_SOL: &str = include_str!("../contracts/QuantizeData.sol");
 const ATTESTDATA_SOL: &str = include_str!("../contracts/AttestData.sol");
 const VERIFIERBASE_SOL: &str = include_str!("../contracts/VerifierBase.sol");
 
+/// The largest runtime bytecode, in bytes, a contract can be deployed with (EIP-170)
+pub const MAX_RUNTIME_BYTECODE_SIZE: usize = 24577;
+/// The prefix of the names of the libraries the verification of a split verifier is partitioned into (see
+/// [split_verifier_sol]), which are numbered in the order they run
+pub const VERIFIER_LIB: &str = "VerifierLib";
+
+/// The name of the `index`th library of a split verifier, see [VERIFIER_LIB].
+pub fn verifier_library(index: usize) -> String {
+    format!("{}{}", VERIFIER_LIB, index)
+}
+
 /// Return an instance of Anvil and a client for the given RPC URL. If none is provided, a local client is used.
 #[cfg(not(target_arch = "wasm32"))]
 pub async fn setup_eth_backend(
@@ -93,6 +106,10 @@ pub async fn deploy_verifier_via_solidity(
 ) -> Result<ethers::types::Address, Box<dyn Error>> {
     let (_, client) = setup_eth_backend(rpc_url).await?;
 
+    if std::fs::read_to_string(&sol_code_path)?.contains(&format!("library {}", VERIFIER_LIB)) {
+        return deploy_split_verifier(sol_code_path, client, runs).await;
+    }
+
     let (abi, bytecode, runtime_bytecode) =
         get_contract_artifacts(sol_code_path, "Verifier", runs)?;
     let factory = get_sol_contract_factory(abi, bytecode, runtime_bytecode, client.clone())?;
@@ -102,6 +119,85 @@ pub async fn deploy_verifier_via_solidity(
     Ok(addr)
 }
 
+/// Deploys a verifier emitted by [split_verifier_sol]: first its libraries, then the entrypoint linked against them.
+/// Returns the address of the entrypoint, which is the one proofs are verified against.
+#[cfg(not(target_arch = "wasm32"))]
+async fn deploy_split_verifier(
+    sol_code_path: PathBuf,
+    client: EthersClient,
+    runs: Option<usize>,
+) -> Result<ethers::types::Address, Box<dyn Error>> {
+    let compiled = compile_contracts(sol_code_path, runs)?;
+    let mut libraries = vec![];
+    while let Some(library) = compiled.find(&verifier_library(libraries.len())) {
+        let name = verifier_library(libraries.len());
+        let (abi, bytecode, runtime_bytecode) = library.into_parts_or_default();
+        let factory = get_sol_contract_factory(abi, bytecode, runtime_bytecode, client.clone())?;
+        let address = factory.deploy(())?.send().await?.address();
+        info!("verifier library {} deployed at: {:#?}", name, address);
+        libraries.push((library_placeholder(&compiled, &name)?, address));
+    }
+
+    let entrypoint = compiled
+        .find("Verifier")
+        .ok_or("could not find the verifier entrypoint")?;
+    let abi = entrypoint
+        .abi
+        .ok_or("the verifier entrypoint has no abi")?
+        .clone();
+    let bytecode = link_libraries(
+        entrypoint
+            .bin
+            .ok_or("the verifier entrypoint has no bytecode")?,
+        &libraries,
+    )?;
+    let runtime_bytecode = link_libraries(
+        entrypoint
+            .bin_runtime
+            .ok_or("the verifier entrypoint has no runtime bytecode")?,
+        &libraries,
+    )?;
+    let factory = get_sol_contract_factory(abi, bytecode, runtime_bytecode, client)?;
+    let contract = factory.deploy(())?.send().await?;
+    Ok(contract.address())
+}
+
+/// The placeholder solc leaves in the bytecode of the contracts linking against `library`: the first 34 hex digits
+/// of the keccak256 of its fully qualified name (`<source file>:<library>`).
+#[cfg(not(target_arch = "wasm32"))]
+fn library_placeholder(compiled: &CompilerOutput, library: &str) -> Result<String, Box<dyn Error>> {
+    let file = compiled
+        .contracts
+        .iter()
+        .find(|(_, contracts)| contracts.contains_key(library))
+        .map(|(file, _)| file)
+        .ok_or(format!("could not find the source of {}", library))?;
+    let hash = ethers::utils::keccak256(format!("{}:{}", file, library));
+    Ok(format!("__${}$__", &hex::encode(hash)[..34]))
+}
+
+/// Fills the library placeholders of compiled bytecode with the addresses of the `libraries` (pairs of a placeholder,
+/// see [library_placeholder], and an address).
+#[cfg(not(target_arch = "wasm32"))]
+fn link_libraries(
+    object: &BytecodeObject,
+    libraries: &[(String, ethers::types::Address)],
+) -> Result<Bytes, Box<dyn Error>> {
+    match object {
+        BytecodeObject::Bytecode(bytes) => Ok(bytes.clone()),
+        BytecodeObject::Unlinked(code) => {
+            let mut linked = code.clone();
+            for (placeholder, address) in libraries {
+                linked = linked.replace(placeholder, &hex::encode(address.as_bytes()));
+            }
+            if linked.contains("__$") {
+                return Err("the verifier links against a library that wasn't deployed".into());
+            }
+            Ok(Bytes::from(hex::decode(linked.trim_start_matches("0x"))?))
+        }
+    }
+}
+
 ///
 pub async fn deploy_da_verifier_via_solidity(
     settings_path: PathBuf,
@@ -539,7 +635,6 @@ fn get_sol_contract_factory<M: 'static + Middleware>(
     runtime_bytecode: Bytes,
     client: Arc<M>,
 ) -> Result<ContractFactory<M>, Box<dyn Error>> {
-    const MAX_RUNTIME_BYTECODE_SIZE: usize = 24577;
     let size = runtime_bytecode.len();
     debug!("runtime bytecode size: {:#?}", size);
     if size > MAX_RUNTIME_BYTECODE_SIZE {
@@ -560,6 +655,20 @@ pub fn get_contract_artifacts(
     contract_name: &str,
     runs: Option<usize>,
 ) -> Result<(Contract, Bytes, Bytes), Box<dyn Error>> {
+    let compiled = compile_contracts(sol_code_path, runs)?;
+    let (abi, bytecode, runtime_bytecode) = compiled
+        .find(contract_name)
+        .expect("could not find contract")
+        .into_parts_or_default();
+    Ok((abi, bytecode, runtime_bytecode))
+}
+
+/// Compiles the contracts of a solidity file, optionally with optimizer runs set on the Solc compiler
+#[cfg(not(target_arch = "wasm32"))]
+fn compile_contracts(
+    sol_code_path: PathBuf,
+    runs: Option<usize>,
+) -> Result<CompilerOutput, Box<dyn Error>> {
     assert!(sol_code_path.exists());
     // Create the compiler input, enabling the optimizer and setting the optimzer runs.
     let input: CompilerInput = if let Some(r) = runs {
@@ -569,12 +678,198 @@ pub fn get_contract_artifacts(
     } else {
         CompilerInput::new(sol_code_path)?[0].clone()
     };
-    let compiled = Solc::default().compile(&input).unwrap();
-    let (abi, bytecode, runtime_bytecode) = compiled
-        .find(contract_name)
-        .expect("could not find contract")
-        .into_parts_or_default();
-    Ok((abi, bytecode, runtime_bytecode))
+    Ok(Solc::default().compile(&input)?)
+}
+
+/// The runtime bytecode size, in bytes, of each library of a verifier emitted by [split_verifier_sol], in the order
+/// they run.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn verifier_library_sizes(
+    sol_code_path: PathBuf,
+    runs: Option<usize>,
+) -> Result<Vec<usize>, Box<dyn Error>> {
+    let compiled = compile_contracts(sol_code_path, runs)?;
+    let mut sizes = vec![];
+    while let Some(library) = compiled.find(&verifier_library(sizes.len())) {
+        sizes.push(library.into_parts_or_default().2.len());
+    }
+    Ok(sizes)
+}
+
+/// Partitions a verifier emitted by [fix_verifier_sol] into `num_libraries` libraries (see [VERIFIER_LIB]) and a
+/// thin `Verifier` entrypoint keeping the ABI of the single contract, such that each contract stays below the
+/// bytecode size limit however large the verifier is.
+///
+/// The verification is a sequence of statements (reading the transcript, the MSM, the pairing ...) over memory at
+/// fixed addresses, which the libraries run in turn, each taking a contiguous share of the code. A library returns
+/// its memory to the entrypoint, which appends it to the calldata of the next library to restore it from. Appending
+/// leaves the ABI encoding of the instances and the proof untouched, so the calldata offsets hardcoded in the
+/// verification still hold in every library. The functions and constants the statements use are repeated in each.
+pub fn split_verifier_sol(
+    contract: &str,
+    num_instances: u32,
+    num_libraries: usize,
+) -> Result<String, Box<dyn Error>> {
+    let header = &contract[..contract
+        .find("contract Verifier {")
+        .ok_or("could not find the verifier contract")?];
+    let size_limit = Regex::new(r"uint256 constant SIZE_LIMIT = \d+;")?
+        .find(contract)
+        .ok_or("could not find the verifier's SIZE_LIMIT")?
+        .as_str();
+    let transcript_len = Regex::new(r"bytes32\[(\d+)\] memory transcript")?
+        .captures(contract)
+        .ok_or("could not find the verifier's transcript")?[1]
+        .parse::<usize>()?;
+    // the last word may be written past the end of the transcript
+    let state_len = 32 * (transcript_len + 1);
+
+    let start = contract
+        .find("assembly {")
+        .ok_or("could not find the verification")?
+        + "assembly {".len();
+    let end = contract
+        .rfind("} return success;")
+        .ok_or("could not find the end of the verification")?;
+
+    // the top level statements of the verification, spanning a line each but for their inner blocks
+    let mut statements = vec![];
+    let mut statement = String::new();
+    let mut depth = 0i64;
+    for line in contract[start..end]
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+    {
+        depth += line.matches('{').count() as i64 - line.matches('}').count() as i64;
+        statement.push_str(line.trim());
+        statement.push('\n');
+        if depth == 0 {
+            statements.push(std::mem::take(&mut statement));
+        }
+    }
+    if depth != 0 {
+        return Err("the verification has unbalanced braces".into());
+    }
+
+    let constant = Regex::new(r"^let\s+\w+\s*:=\s*0x[0-9a-fA-F]+\s*$")?;
+    let (shared, statements): (Vec<_>, Vec<_>) = statements
+        .into_iter()
+        .partition(|s| s.starts_with("function ") || constant.is_match(s.trim()));
+
+    // each library takes the statements starting in its share of the code
+    let total = statements.iter().map(|s| s.len()).sum::<usize>().max(1);
+    let variable = Regex::new(r"^let\s+(\w+)")?;
+    let mut parts = vec![String::new(); num_libraries];
+    let mut variables = vec![vec![]; num_libraries];
+    let mut done = 0;
+    for statement in statements {
+        let part = std::cmp::min(done * num_libraries / total, num_libraries - 1);
+        if let Some(name) = variable.captures(&statement) {
+            variables[part].push(name[1].to_string());
+        }
+        parts[part].push_str(&statement);
+        done += statement.len();
+    }
+    if parts.iter().any(|p| p.is_empty()) {
+        return Err(format!(
+            "the verification can't be split into {} libraries",
+            num_libraries
+        )
+        .into());
+    }
+    // the memory is carried over, but not the variables
+    for (i, names) in variables.iter().enumerate() {
+        for name in names {
+            let used = Regex::new(&format!(r"\b{}\b", name))?;
+            if parts[i + 1..].iter().any(|p| used.is_match(p)) {
+                return Err(format!(
+                    "the verifier's `{}` is used across the libraries it is split into",
+                    name
+                )
+                .into());
+            }
+        }
+    }
+
+    let mut code = header.to_string();
+    for (i, part) in parts.iter().enumerate() {
+        let restore = match i {
+            0 => String::new(),
+            _ => format!(
+                "calldatacopy(0x80, sub(calldatasize(), {:#x}), {:#x})\n",
+                state_len, state_len
+            ),
+        };
+        let save = match i == num_libraries - 1 {
+            true => String::new(),
+            false => format!(
+                "if iszero(success) {{ revert(0, 0) }}\nreturn(0x80, {:#x})\n",
+                state_len
+            ),
+        };
+        write!(
+            code,
+            "library {} {{
+    function verify(
+        uint256[{}] calldata instances,
+        bytes calldata proof
+    ) external view returns (bool) {{
+        bool success = true;
+        bytes32[{}] memory transcript;
+        assembly {{
+{}{}{}{}        }}
+        return success;
+    }}
+}}
+
+",
+            verifier_library(i),
+            num_instances,
+            transcript_len,
+            restore,
+            shared.concat(),
+            part,
+            save
+        )?;
+    }
+
+    let mut calls = String::new();
+    for i in 0..num_libraries {
+        let calldata = match i {
+            0 => "msg.data".to_string(),
+            _ => "bytes.concat(msg.data, state)".to_string(),
+        };
+        write!(
+            calls,
+            "        (success, state) = address({}).staticcall({});\n",
+            verifier_library(i),
+            calldata
+        )?;
+        if i < num_libraries - 1 {
+            calls.push_str("        if (!success) {\n            return false;\n        }\n");
+        }
+    }
+    write!(
+        code,
+        "contract Verifier {{
+    {}
+
+    function verify(
+        uint256[{}] calldata instances,
+        bytes calldata proof
+    ) public view returns (bool) {{
+        for (uint i = 0; i < instances.length; i++) {{
+            require(instances[i] < SIZE_LIMIT);
+        }}
+        bool success;
+        bytes memory state;
+{}        return success && abi.decode(state, (bool));
+    }}
+}}
+",
+        size_limit, num_instances, calls
+    )?;
+    Ok(code)
 }
 
 use regex::Regex;
@@ -875,7 +1170,7 @@ pub fn fix_verifier_sol(
     let write: Box<&mut dyn Write> = Box::new(&mut contract_slice_string);
 
     for line in modified_lines[16..modified_lines.len() - 7].iter() {
-        write!(write, "{}", line).unwrap();
+        writeln!(write, "{}", line).unwrap();
     }
     writeln!(write, "}} return success; }} }}")?;
 
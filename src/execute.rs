@@ -1,25 +1,54 @@
 use crate::circuit::CheckMode;
 #[cfg(not(target_arch = "wasm32"))]
-use crate::commands::{CalibrationTarget, StrategyType};
+use crate::commands::{CalibrationTarget, StrategyType, VerifierFormat};
 use crate::commands::{Cli, Commands};
+use crate::commands::{OutputPaths, OutputTemplate};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::eth::{deploy_da_verifier_via_solidity, deploy_verifier_via_solidity};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::eth::{fix_verifier_sol, get_contract_artifacts, verify_proof_via_solidity};
-use crate::graph::input::GraphData;
-use crate::graph::{GraphCircuit, GraphSettings, GraphWitness, Model};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::eth::{split_verifier_sol, verifier_library_sizes, MAX_RUNTIME_BYTECODE_SIZE};
+use crate::fieldutils::felt_to_i128;
+use crate::graph::calibration::{
+    calibration_samples, recommend, search_calibration, CalibrationPoint, CALIBRATION_SCALES,
+};
+use crate::graph::columns::AdviceColumnUsage;
+use crate::graph::error_bounds::ErrorBoundReport;
+use crate::graph::input::{FileSourceInner, GraphData};
+use crate::graph::opset::{OpsetReport, OpsetSeverity, MAX_OPSET, MIN_OPSET};
+use crate::graph::rebalance::{rebalance_scales, RebalanceReport};
+use crate::graph::reference::{validate_against_reference, ReferenceOutputs};
+use crate::graph::supported::SupportedPrefix;
+use crate::graph::{
+    onnx, FloatModel, GraphCircuit, GraphSettings, GraphWitness, InputNormalization,
+    InstanceColumn, Model, OutputRange, ParamsCommitment,
+};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::graph::{TestDataSource, TestSources};
-use crate::pfsys::evm::aggregation::AggregationCircuit;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::pfsys::bundle::Bundle;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::pfsys::checkpoint::ProveCheckpoint;
+use crate::pfsys::create_proof_circuit_kzg_with_metrics;
+use crate::pfsys::diagnostics::verify_proof_circuit_kzg_debug;
+use crate::pfsys::evm::aggregation::{verify_aggregate, AggregationCircuit};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::pfsys::evm::evm_verify;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::pfsys::evm::{
     aggregation::gen_aggregation_evm_verifier, single::gen_evm_verifier, DeploymentCode, YulCode,
 };
-use crate::pfsys::{create_keys, load_pk, load_vk, save_params, save_pk, Snark, TranscriptType};
+use crate::pfsys::ipa::{
+    create_proof_circuit_ipa, gen_ipa_params, verify_proof_circuit_ipa, IPAScheme,
+};
+use crate::pfsys::metrics::ProofMetrics;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::pfsys::split::SplitProof;
+use crate::pfsys::{create_keys, create_keys_with_timings};
 use crate::pfsys::{create_proof_circuit_kzg, verify_proof_circuit_kzg};
-use crate::pfsys::{save_vk, srs::*};
+use crate::pfsys::{load_pk, load_vk, save_params, save_pk, Snark, TranscriptType};
+use crate::pfsys::{save_vk, srs::*, ProofFormat};
 use crate::RunArgs;
 #[cfg(not(target_arch = "wasm32"))]
 use ethers::types::H160;
@@ -43,7 +72,7 @@ use instant::Instant;
 use itertools::Itertools;
 #[cfg(not(target_arch = "wasm32"))]
 use log::debug;
-use log::{info, trace};
+use log::{info, trace, warn};
 #[cfg(feature = "render")]
 use plotters::prelude::*;
 #[cfg(not(target_arch = "wasm32"))]
@@ -52,13 +81,15 @@ use rand::Rng;
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 #[cfg(not(target_arch = "wasm32"))]
 use snark_verifier::loader::evm;
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fs::File;
 #[cfg(not(target_arch = "wasm32"))]
 use std::io::ErrorKind::NotFound;
 #[cfg(not(target_arch = "wasm32"))]
 use std::io::{Cursor, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 #[cfg(not(target_arch = "wasm32"))]
 use std::process::Command;
 #[cfg(not(target_arch = "wasm32"))]
@@ -68,6 +99,7 @@ use std::sync::OnceLock;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Duration;
 use thiserror::Error;
+use tract_onnx::prelude::Framework;
 
 #[cfg(not(target_arch = "wasm32"))]
 static _SOLC_REQUIREMENT: OnceLock<bool> = OnceLock::new();
@@ -108,6 +140,9 @@ pub enum ExecutionError {
     /// Shape mismatch in a operation
     #[error("verification failed")]
     VerifyError(Vec<VerifyFailure>),
+    /// The loaded params are too small for the circuit
+    #[error("params support up to k={0} but circuit needs k={1}; regenerate or use a larger SRS")]
+    ParamsTooSmall(u32, u32),
 }
 
 /// Run an ezkl command with given args
@@ -142,18 +177,78 @@ pub async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
             logrows,
             check,
         } => get_srs_cmd(srs_path, settings_path, logrows, check).await,
+        Commands::ExtractVerifierSrs {
+            srs_path,
+            settings_path,
+            output_path,
+        } => extract_verifier_srs(srs_path, settings_path, output_path),
+        Commands::VkDiff {
+            vk_path,
+            other_vk_path,
+            settings_path,
+            other_settings_path,
+        } => vk_diff(vk_path, other_vk_path, settings_path, other_settings_path),
         Commands::Table { model, args } => table(model, args),
+        Commands::ModelHash { model, args } => model_hash(model, args).map(|_| ()),
+        Commands::ModelInfo { model } => model_info(model).map(|_| ()),
+        Commands::ExportSupported {
+            model,
+            output,
+            args,
+        } => export_supported(model, output, args).map(|_| ()),
+        Commands::ErrorBounds {
+            model,
+            input_magnitude,
+            args,
+        } => error_bounds(model, input_magnitude, args).map(|_| ()),
+        Commands::ExportWeights {
+            model,
+            settings_path,
+            output,
+        } => export_weights(model, settings_path, output),
+        Commands::ValidateReference {
+            model,
+            data,
+            reference,
+            reference_tolerance,
+            args,
+        } => validate_reference(model, data, reference, reference_tolerance, args).await,
         #[cfg(feature = "render")]
         Commands::RenderCircuit {
             model,
             output,
+            show_labels,
             args,
-        } => render(model, output, args),
+        } => render(model, output, show_labels, args),
         Commands::GenSettings {
             model,
             settings_path,
             args,
-        } => gen_circuit_settings(model, settings_path, args),
+            input_normalization,
+        } => gen_circuit_settings(model, settings_path, args, input_normalization),
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::RequiredSrs {
+            model,
+            srs_path,
+            args,
+        } => required_srs(model, srs_path, args),
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::Calibrate {
+            model,
+            data,
+            target_error,
+            settings_path,
+            max_logrows,
+            args,
+        } => {
+            auto_calibrate(model, data, target_error, settings_path, max_logrows, args).map(|_| ())
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::RebalanceScales {
+            model,
+            data,
+            settings_path,
+        } => rebalance(model, data, settings_path).map(|_| ()),
         #[cfg(not(target_arch = "wasm32"))]
         Commands::CalibrateSettings {
             model,
@@ -169,6 +264,16 @@ pub async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
         } => gen_witness(compiled_model, data, Some(output), settings_path)
             .await
             .map(|_| ()),
+        Commands::QuantizeInput {
+            data,
+            output,
+            scale,
+        } => quantize_input(data, output, scale),
+        Commands::DequantizeInput {
+            data,
+            output,
+            scale,
+        } => dequantize_input(data, output, scale),
         Commands::Mock {
             model,
             witness,
@@ -181,7 +286,15 @@ pub async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
             settings_path,
             sol_code_path,
             abi_path,
-        } => create_evm_verifier(vk_path, srs_path, settings_path, sol_code_path, abi_path),
+            verifier_format,
+        } => create_evm_verifier(
+            vk_path,
+            srs_path,
+            settings_path,
+            sol_code_path,
+            abi_path,
+            verifier_format,
+        ),
         #[cfg(not(target_arch = "wasm32"))]
         Commands::CreateEVMDataAttestationVerifier {
             vk_path,
@@ -223,7 +336,15 @@ pub async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
             settings_path,
             vk_path,
             pk_path,
-        } => setup(compiled_model, srs_path, settings_path, vk_path, pk_path),
+            max_logrows,
+        } => setup(
+            compiled_model,
+            srs_path,
+            settings_path,
+            vk_path,
+            pk_path,
+            max_logrows,
+        ),
         #[cfg(not(target_arch = "wasm32"))]
         Commands::SetupTestEVMData {
             data,
@@ -256,19 +377,81 @@ pub async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
             strategy,
             settings_path,
             check_mode,
-        } => prove(
-            witness,
-            compiled_model,
+            max_logrows,
+            proof_format,
+            metadata,
+            elide_outputs,
+            challenge,
+            output_range,
+        } => prove(ProveOptions {
+            data_path: witness,
+            compiled_model_path: compiled_model,
             pk_path,
-            Some(proof_path),
+            proof_path: Some(proof_path),
             srs_path,
             transcript,
             strategy,
             settings_path,
             check_mode,
-        )
+            max_logrows,
+            proof_format,
+            metadata: metadata.into_iter().collect(),
+            elide_outputs,
+            challenge,
+            output_range,
+        })
         .await
         .map(|_| ()),
+        Commands::ProveVerify {
+            witness,
+            compiled_model,
+            srs_path,
+            settings_path,
+            transcript,
+            check_mode,
+            checkpoint_dir,
+            output_template,
+        } => prove_verify(
+            witness,
+            compiled_model,
+            srs_path,
+            settings_path,
+            transcript,
+            check_mode,
+            checkpoint_dir,
+            output_template,
+        ),
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::ProveAll {
+            witnesses,
+            compiled_model,
+            pk_path,
+            srs_path,
+            settings_path,
+            transcript,
+            check_mode,
+            proof_format,
+            output_template,
+            max_logrows,
+            metadata,
+            elide_outputs,
+        } => {
+            prove_all(
+                witnesses,
+                compiled_model,
+                pk_path,
+                srs_path,
+                settings_path,
+                transcript,
+                check_mode,
+                proof_format,
+                output_template,
+                max_logrows,
+                metadata.into_iter().collect(),
+                elide_outputs,
+            )
+            .await
+        }
         Commands::MockAggregate {
             aggregation_snarks,
             logrows,
@@ -297,12 +480,85 @@ pub async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
             logrows,
             check_mode,
         ),
+        Commands::CommitParams {
+            compiled_model,
+            settings_path,
+            output,
+        } => commit_params(compiled_model, settings_path, output).map(|_| ()),
         Commands::Verify {
             proof_path,
             settings_path,
             vk_path,
             srs_path,
-        } => verify(proof_path, settings_path, vk_path, srs_path),
+            compiled_model,
+            params_commitment,
+            challenge,
+            output_range,
+            debug_transcript,
+        } => verify(
+            proof_path,
+            settings_path,
+            vk_path,
+            srs_path,
+            compiled_model,
+            params_commitment,
+            challenge,
+            output_range,
+            debug_transcript,
+        ),
+        Commands::SetupIpa {
+            compiled_model,
+            vk_path,
+            pk_path,
+            settings_path,
+            max_logrows,
+        } => setup_ipa(compiled_model, settings_path, vk_path, pk_path, max_logrows),
+        Commands::ProveIpa {
+            witness,
+            compiled_model,
+            pk_path,
+            proof_path,
+            settings_path,
+            check_mode,
+            max_logrows,
+        } => prove_ipa(
+            witness,
+            compiled_model,
+            pk_path,
+            proof_path,
+            settings_path,
+            check_mode,
+            max_logrows,
+        ),
+        Commands::VerifyIpa {
+            settings_path,
+            proof_path,
+            vk_path,
+        } => verify_ipa(proof_path, settings_path, vk_path),
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::CreateBundle {
+            settings_path,
+            proof_path,
+            vk_path,
+            srs_path,
+            bundle_path,
+        } => create_bundle(settings_path, proof_path, vk_path, srs_path, bundle_path),
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::VerifyBundle {
+            bundle_path,
+            srs_path,
+        } => verify_bundle(bundle_path, srs_path),
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::CombineSplit {
+            first_bundle,
+            second_bundle,
+            split_path,
+        } => combine_split(first_bundle, second_bundle, split_path),
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::VerifySplit {
+            split_path,
+            srs_path,
+        } => verify_split(split_path, srs_path),
         Commands::VerifyAggr {
             proof_path,
             vk_path,
@@ -343,6 +599,39 @@ pub async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
             data_attestation,
         } => verify_evm(proof_path, addr, rpc_url, data_attestation).await,
         Commands::PrintProofHex { proof_path } => print_proof_hex(proof_path),
+        Commands::ProofInfo {
+            proof_path,
+            settings_path,
+            scale,
+        } => proof_info(proof_path, settings_path, scale),
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::AuditPublicInputs {
+            data,
+            compiled_model,
+            settings_path,
+            proof_path,
+        } => audit_public_inputs(data, compiled_model, settings_path, proof_path).await,
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::CheckTranscript {
+            proof_path,
+            reprove_path,
+            witness,
+            compiled_model,
+            pk_path,
+            srs_path,
+            settings_path,
+        } => {
+            check_transcript(
+                proof_path,
+                reprove_path,
+                witness,
+                compiled_model,
+                pk_path,
+                srs_path,
+                settings_path,
+            )
+            .await
+        }
     }
 }
 
@@ -352,6 +641,23 @@ pub(crate) fn gen_srs_cmd(srs_path: PathBuf, logrows: u32) -> Result<(), Box<dyn
     Ok(())
 }
 
+pub(crate) fn extract_verifier_srs(
+    srs_path: PathBuf,
+    settings_path: PathBuf,
+    output_path: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    let settings = GraphSettings::load(&settings_path)?;
+    let params: ParamsKZG<Bn256> = load_srs::<KZGCommitmentScheme<Bn256>>(srs_path)?;
+    let verifier_params = extract_verifier_params(&params, settings.run_args.logrows)?;
+    save_verifier_srs(&output_path, &verifier_params)?;
+    info!(
+        "saved verifier params for k={} to {}",
+        verifier_params.k(),
+        output_path.display()
+    );
+    Ok(())
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 async fn fetch_srs(uri: &str) -> Result<Vec<u8>, Box<dyn Error>> {
     let pb = {
@@ -424,6 +730,176 @@ pub(crate) fn table(model: PathBuf, run_args: RunArgs) -> Result<(), Box<dyn Err
     Ok(())
 }
 
+/// Computes the hash of the model's computation, see [Model::canonical_hash].
+pub(crate) fn model_hash(model: PathBuf, run_args: RunArgs) -> Result<String, Box<dyn Error>> {
+    let model = Model::from_run_args(&run_args, &model)?;
+    let hash = model.canonical_hash(&run_args)?;
+    info!("model hash: {}", hash);
+    Ok(hash)
+}
+
+/// Reports the model's opsets and the issues with reading its ops at these opsets, see [OpsetReport].
+pub(crate) fn model_info(model: PathBuf) -> Result<OpsetReport, Box<dyn Error>> {
+    let mut reader = File::open(&model)?;
+    let proto = onnx().proto_model_for_read(&mut reader)?;
+    let report = OpsetReport::new(&proto);
+    for (domain, version) in &report.opsets {
+        info!("opset {}: {}", domain, version);
+    }
+    info!(
+        "ezkl supports ai.onnx opsets {} to {}",
+        MIN_OPSET, MAX_OPSET
+    );
+    for issue in &report.issues {
+        let severity = match issue.severity {
+            OpsetSeverity::Warning => "warning",
+            OpsetSeverity::Error => "error",
+        };
+        info!(
+            "{} for node {} ({}): {}",
+            severity, issue.node, issue.op_type, issue.message
+        );
+    }
+    Ok(report)
+}
+
+/// Exports the largest supported prefix of the model to `output`, see [SupportedPrefix].
+pub(crate) fn export_supported(
+    model: PathBuf,
+    output: PathBuf,
+    run_args: RunArgs,
+) -> Result<SupportedPrefix, Box<dyn Error>> {
+    let mut reader = File::open(&model)?;
+    let proto = onnx().proto_model_for_read(&mut reader)?;
+    let prefix = SupportedPrefix::new(&proto, &run_args)?;
+    if let Some((name, op_type, reason)) = &prefix.first_unsupported {
+        warn!(
+            "support ends at node {} ({}), {} of {} nodes are supported: {}",
+            name, op_type, prefix.num_nodes, prefix.total_nodes, reason
+        );
+    }
+    std::fs::write(&output, prefix.to_bytes())?;
+    info!(
+        "exported the supported prefix of {} nodes to {}",
+        prefix.num_nodes,
+        output.display()
+    );
+    Ok(prefix)
+}
+
+/// Bounds the quantization error of the model's outputs, see [Model::quantization_error_bounds].
+pub(crate) fn error_bounds(
+    model: PathBuf,
+    input_magnitude: f64,
+    run_args: RunArgs,
+) -> Result<ErrorBoundReport, Box<dyn Error>> {
+    let model = Model::from_run_args(&run_args, &model)?;
+    let report = model.quantization_error_bounds(input_magnitude, run_args.bits);
+    for (i, bound) in report.outputs.iter().enumerate() {
+        info!(
+            "output {}: magnitude at most {}, error at most {}",
+            i, bound.magnitude, bound.error
+        );
+    }
+    Ok(report)
+}
+
+/// Exports the model's quantized weights, see [Model::quantized_weights].
+pub(crate) fn export_weights(
+    model: PathBuf,
+    settings_path: PathBuf,
+    output: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    let settings = GraphSettings::load(&settings_path)?;
+    let model = Model::from_run_args(&settings.run_args, &model)?;
+    let weights = model.quantized_weights();
+    serde_json::to_writer(&File::create(&output)?, &weights)?;
+    info!("exported {} weights to {:?}", weights.len(), output);
+    Ok(())
+}
+
+/// Compares the circuit's forward pass against a reference, see [validate_against_reference].
+pub(crate) async fn validate_reference(
+    model: PathBuf,
+    data: PathBuf,
+    reference: PathBuf,
+    tolerance: f64,
+    run_args: RunArgs,
+) -> Result<(), Box<dyn Error>> {
+    let circuit = GraphCircuit::from_run_args(&run_args, &model)?;
+    let data = GraphData::from_path(data)?;
+    let inputs = circuit.load_graph_input(&data).await?;
+    let reference = ReferenceOutputs::from_path(reference)?;
+
+    let report = validate_against_reference(&circuit.model, &inputs, &reference, tolerance)?;
+    for layer in &report.layers {
+        info!(
+            "node {} ({}): max error {} {}",
+            layer.node,
+            layer.op,
+            layer.max_error,
+            if layer.within_tolerance {
+                "ok"
+            } else {
+                "DIVERGES"
+            }
+        );
+    }
+    match report.first_divergent() {
+        Some(layer) => Err(format!(
+            "node {} ({}) diverges from the reference with a max error of {} (tolerance {})",
+            layer.node, layer.op, layer.max_error, tolerance
+        )
+        .into()),
+        None => {
+            info!("all {} layers match the reference", report.layers.len());
+            Ok(())
+        }
+    }
+}
+
+pub(crate) fn vk_diff(
+    vk_path: PathBuf,
+    other_vk_path: PathBuf,
+    settings_path: PathBuf,
+    other_settings_path: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let load = |vk_path: PathBuf, settings_path: &PathBuf| -> Result<_, Box<dyn Error>> {
+        let settings = GraphSettings::load(settings_path)?;
+        load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, settings)
+    };
+    let vk = load(vk_path, &settings_path)?;
+    let other_vk = load(
+        other_vk_path,
+        other_settings_path.as_ref().unwrap_or(&settings_path),
+    )?;
+
+    let diff = crate::pfsys::vk_diff(&vk, &other_vk)?;
+    if diff.identical {
+        info!("verifying keys are identical");
+        return Ok(());
+    }
+
+    let mut parts = vec![];
+    if let Some((info, other_info)) = &diff.shape {
+        parts.push(format!("circuit shape ({:?} vs {:?})", info, other_info));
+    }
+    if !diff.fixed_commitments.is_empty() {
+        parts.push(format!("fixed commitments {:?}", diff.fixed_commitments));
+    }
+    if !diff.permutation_commitments.is_empty() {
+        parts.push(format!(
+            "permutation commitments {:?}",
+            diff.permutation_commitments
+        ));
+    }
+    if parts.is_empty() {
+        // e.g the gates or lookups changed without changing the number of columns
+        parts.push("constraint system".to_string());
+    }
+    Err(format!("verifying keys differ in: {}", parts.join(", ")).into())
+}
+
 pub(crate) async fn gen_witness(
     compiled_model_path: PathBuf,
     data: PathBuf,
@@ -462,17 +938,91 @@ pub(crate) async fn gen_witness(
     Ok(witness)
 }
 
+pub(crate) fn quantize_input(
+    data: PathBuf,
+    output: PathBuf,
+    scale: u32,
+) -> Result<(), Box<dyn Error>> {
+    let data = GraphData::from_path(data)?;
+    data.quantize(scale)?.save(output)?;
+    info!("quantized data at scale {}", scale);
+    Ok(())
+}
+
+pub(crate) fn dequantize_input(
+    data: PathBuf,
+    output: PathBuf,
+    scale: u32,
+) -> Result<(), Box<dyn Error>> {
+    let data = GraphData::from_path(data)?;
+    data.dequantize(scale)?.save(output)?;
+    info!("dequantized data at scale {}", scale);
+    Ok(())
+}
+
 /// Generate a circuit settings file
 pub(crate) fn gen_circuit_settings(
     model_path: PathBuf,
     params_output: PathBuf,
     run_args: RunArgs,
+    input_normalization: Option<PathBuf>,
 ) -> Result<(), Box<dyn Error>> {
-    let circuit = GraphCircuit::from_run_args(&run_args, &model_path)?;
+    let input_normalization = match input_normalization {
+        Some(path) => Some(InputNormalization::from_path(&path)?),
+        None => None,
+    };
+    let circuit = GraphCircuit::from_run_args_normalized(
+        &run_args,
+        &model_path,
+        input_normalization.as_ref(),
+    )?;
     let params = circuit.settings;
+    let usage = AdviceColumnUsage::from_settings(&params);
+    info!("{}", usage);
+    let eliminated = usage.eliminated();
+    match params.run_args.eliminate_dead_columns {
+        true => info!("eliminated {} dead advice columns", eliminated),
+        false if eliminated > 0 => info!(
+            "{} advice columns are dead, set --eliminate-dead-columns to eliminate them",
+            eliminated
+        ),
+        false => {}
+    }
     params.save(&params_output).map_err(Box::<dyn Error>::from)
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn required_srs(
+    model_path: PathBuf,
+    srs_path: Option<PathBuf>,
+    run_args: RunArgs,
+) -> Result<(), Box<dyn Error>> {
+    let circuit = GraphCircuit::from_run_args(&run_args, &model_path)?;
+    let logrows = circuit.settings.min_logrows();
+    info!("you need an SRS of at least degree 2^{}", logrows);
+    if logrows > run_args.logrows {
+        warn!(
+            "the circuit doesn't fit in the requested {} logrows, set logrows to at least {}",
+            run_args.logrows, logrows
+        );
+    }
+
+    if let Some(srs_path) = srs_path {
+        let params: ParamsKZG<Bn256> = load_srs::<KZGCommitmentScheme<Bn256>>(srs_path)?;
+        if params.k() < logrows {
+            return Err(Box::new(ExecutionError::ParamsTooSmall(
+                params.k(),
+                logrows,
+            )));
+        }
+        info!(
+            "the srs supports up to k={}, which is large enough",
+            params.k()
+        );
+    }
+    Ok(())
+}
+
 // not for wasm targets
 #[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn init_spinner() -> ProgressBar {
@@ -558,9 +1108,13 @@ pub(crate) async fn calibrate(
 
                 let original_settings = settings.clone();
 
-                let mut circuit = GraphCircuit::from_run_args(&local_run_args, &model_path)
-                    .map_err(|_| "failed to create circuit from run args")
-                    .unwrap();
+                let mut circuit = GraphCircuit::from_run_args_normalized(
+                    &local_run_args,
+                    &model_path,
+                    settings.input_normalization.as_ref(),
+                )
+                .map_err(|_| "failed to create circuit from run args")
+                .unwrap();
 
                 tokio::task::spawn(async move {
                     let data = circuit
@@ -591,8 +1145,10 @@ pub(crate) async fn calibrate(
                     let found_settings = GraphSettings {
                         run_args: found_run_args,
                         required_lookups: circuit.settings.required_lookups,
+                        model_input_scales: circuit.settings.model_input_scales,
                         model_output_scales: circuit.settings.model_output_scales,
                         num_constraints: circuit.settings.num_constraints,
+                        model_rows: circuit.settings.model_rows,
                         ..original_settings.clone()
                     };
 
@@ -666,9 +1222,108 @@ pub(crate) async fn calibrate(
     Ok(())
 }
 
-pub(crate) async fn mock(
-    compiled_model_path: PathBuf,
-    data_path: PathBuf,
+/// Scales down the nodes feeding the lookups that overflow the lookup bits on the calibration `data`, saving the
+/// rebalanced settings back to `settings_path`, see [rebalance_scales]
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn rebalance(
+    model_path: PathBuf,
+    data: PathBuf,
+    settings_path: PathBuf,
+) -> Result<RebalanceReport, Box<dyn Error>> {
+    let data = GraphData::from_path(data)?;
+    let settings = GraphSettings::load(&settings_path)?;
+    let float_model = FloatModel::from_run_args(&settings.run_args, &model_path)?;
+    let samples = calibration_samples(&data, &float_model)?;
+    debug!("num of calibration batches: {}", samples.len());
+
+    let (settings, report) = rebalance_scales(&model_path, &settings, &samples)?;
+    for node in &report.rescaled {
+        info!(
+            "scaled node {} ({}) down by {} bits, to scale {}",
+            node.node, node.op, node.bits, node.out_scale
+        );
+    }
+    info!(
+        "rescaled {} nodes, max lookup input {} within {} bits, max output error {} (was {})",
+        report.rescaled.len(),
+        report.max_lookup_inputs,
+        settings.run_args.bits,
+        report.max_error,
+        report.max_error_before
+    );
+    settings.save(&settings_path)?;
+    info!("saved the settings to {}", settings_path.display());
+    Ok(report)
+}
+
+/// Searches the scales for the smallest lookup bits whose outputs are within `target_error` of the float model's on
+/// the calibration `data`, see [search_calibration]
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn auto_calibrate(
+    model_path: PathBuf,
+    data: PathBuf,
+    target_error: f64,
+    settings_path: Option<PathBuf>,
+    max_logrows: Option<u32>,
+    run_args: RunArgs,
+) -> Result<CalibrationPoint, Box<dyn Error>> {
+    let data = GraphData::from_path(data)?;
+    let float_model = FloatModel::from_run_args(&run_args, &model_path)?;
+    let samples = calibration_samples(&data, &float_model)?;
+    debug!("num of calibration batches: {}", samples.len());
+
+    let points = search_calibration(
+        &model_path,
+        &run_args,
+        &samples,
+        CALIBRATION_SCALES,
+        max_logrows,
+    )?;
+    for point in &points {
+        info!(
+            "scale {}: bits {}, logrows {}, max output error {}{}",
+            point.scale,
+            point.bits,
+            point.logrows,
+            point.max_error,
+            if point.fits {
+                ""
+            } else {
+                " (exceeds max logrows)"
+            }
+        );
+    }
+
+    let best = recommend(&points, target_error).cloned().ok_or_else(|| {
+        format!(
+            "no scale in {:?} keeps the output error under {} within the max logrows",
+            CALIBRATION_SCALES, target_error
+        )
+    })?;
+    info!(
+        "recommended: scale {}, bits {}, logrows {} (max output error {})",
+        best.scale, best.bits, best.logrows, best.max_error
+    );
+
+    if let Some(settings_path) = settings_path {
+        let run_args = RunArgs {
+            scale: best.scale,
+            bits: best.bits,
+            logrows: best.logrows,
+            ..run_args
+        };
+        GraphCircuit::from_run_args(&run_args, &model_path)?
+            .settings
+            .save(&settings_path)?;
+        info!("saved the settings to {}", settings_path.display());
+    }
+
+    Ok(best)
+}
+
+pub(crate) async fn mock(
+    compiled_model_path: PathBuf,
+    data_path: PathBuf,
     settings_path: PathBuf,
 ) -> Result<(), Box<dyn Error>> {
     // mock should catch any issues by default so we set it to safe
@@ -702,11 +1357,202 @@ pub(crate) async fn mock(
 pub(crate) fn print_proof_hex(proof_path: PathBuf) -> Result<(), Box<dyn Error>> {
     let proof = Snark::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
     for instance in proof.instances {
-        println!("{:?}", instance);
+        info!("{:?}", instance);
     }
     info!("{}", hex::encode(proof.proof));
     Ok(())
 }
+
+pub(crate) fn proof_info(
+    proof_path: PathBuf,
+    settings_path: Option<PathBuf>,
+    scale: Option<u32>,
+) -> Result<(), Box<dyn Error>> {
+    let bytes = std::fs::read(&proof_path)?;
+    let format = ProofFormat::detect(&bytes);
+    let proof = Snark::<Fr, G1Affine>::from_bytes(&bytes, format)?;
+    // the scales follow from the settings, rather than being taken on the proof's word
+    let instance_scales = match settings_path {
+        Some(path) => GraphSettings::load(&path)?.instance_scales(),
+        None => vec![],
+    };
+
+    info!("encoding: {:?} ({} bytes)", format, bytes.len());
+    match format.version(&bytes) {
+        Some(version) => info!("layout version: {}", version),
+        None => info!(
+            "layout version: unversioned ({:?} is self-describing)",
+            format
+        ),
+    }
+    info!("transcript: {:?}", proof.transcript_type);
+    info!("proof size: {} bytes", proof.proof.len());
+    info!(
+        "{} public input column(s) of lengths {:?}",
+        proof.instances.len(),
+        proof.instances.iter().map(|c| c.len()).collect_vec()
+    );
+    for (i, column) in proof.instances.iter().enumerate() {
+        if proof.elided_instances.contains(&i) {
+            info!("column {}: elided, recomputed by the verifier", i);
+            continue;
+        }
+        // an explicit scale overrides those of the settings
+        match scale.or(instance_scales.get(i).copied().flatten()) {
+            Some(scale) => info!(
+                "column {} (dequantized at scale {}): {:?}",
+                i,
+                scale,
+                column
+                    .iter()
+                    .map(|x| FileSourceInner::Field(*x).dequantize(scale).to_float())
+                    .collect_vec()
+            ),
+            None => info!(
+                "column {}: {:?}",
+                i,
+                column.iter().map(|x| felt_to_i128(*x)).collect_vec()
+            ),
+        }
+    }
+    if let Some(hash) = &proof.public_input_hash {
+        info!("public input hash: {:?}", hash);
+    }
+    for (key, value) in &proof.metadata {
+        info!("metadata {}: {}", key, value);
+    }
+    Ok(())
+}
+
+/// Recomputes a proof's public inputs from the model and the claimed input, see [GraphCircuit::audit_public_inputs].
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn audit_public_inputs(
+    data: PathBuf,
+    compiled_model: PathBuf,
+    settings_path: PathBuf,
+    proof_path: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    let circuit_settings = GraphSettings::load(&settings_path)?;
+    let mut circuit = GraphCircuit::preprocessed_from_settings(
+        &circuit_settings,
+        &compiled_model,
+        CheckMode::UNSAFE,
+    )?;
+    let mut proof = Snark::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
+    let elided = std::mem::take(&mut proof.elided_instances);
+    if !elided.is_empty() {
+        circuit.restore_elided_outputs(&mut proof.instances, &elided)?;
+    }
+
+    let data = GraphData::from_path(data)?;
+    let input = circuit.load_graph_input(&data).await?;
+    let mismatches = circuit.audit_public_inputs(&input, &proof.instances)?;
+    for m in &mismatches {
+        warn!(
+            "instance column {} index {}: recomputed {:?}, but the proof holds {:?}",
+            m.column,
+            m.index,
+            m.expected.map(felt_to_i128),
+            m.found.map(felt_to_i128)
+        );
+    }
+    if !mismatches.is_empty() {
+        return Err(format!(
+            "{} public inputs of {} don't match the ones recomputed from the model and input",
+            mismatches.len(),
+            proof_path.display()
+        )
+        .into());
+    }
+    info!("the proof's public inputs match the ones recomputed from the model and input");
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn check_transcript(
+    proof_path: PathBuf,
+    reprove_path: Option<PathBuf>,
+    witness: Option<PathBuf>,
+    compiled_model: Option<PathBuf>,
+    pk_path: Option<PathBuf>,
+    srs_path: Option<PathBuf>,
+    settings_path: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let proof = Snark::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
+    if proof.transcript_type == TranscriptType::EVM {
+        info!("proof uses an EVM transcript and can be verified on-chain");
+        return Ok(());
+    }
+
+    warn!(
+        "proof uses a {:?} transcript and CANNOT be verified on-chain: the transcript is intrinsic to the proof so it can't be converted, it has to be reproved with --transcript=evm",
+        proof.transcript_type
+    );
+
+    match (
+        reprove_path,
+        witness,
+        compiled_model,
+        pk_path,
+        srs_path,
+        settings_path,
+    ) {
+        (
+            Some(reprove_path),
+            Some(witness),
+            Some(compiled_model),
+            Some(pk_path),
+            Some(srs_path),
+            Some(settings_path),
+        ) => {
+            info!("reproving with an EVM transcript");
+            // the reproof is bound to the same challenge as the original
+            let settings = GraphSettings::load(&settings_path)?;
+            let challenge = match settings.run_args.challenge {
+                true => proof
+                    .instances
+                    .get(settings.model_instance_shapes.len() - 1)
+                    .and_then(|c| c.first().copied()),
+                false => None,
+            };
+            // as are the claimed bounds of the output
+            let output_range = settings
+                .instance_layout()
+                .iter()
+                .position(|c| *c == InstanceColumn::OutputRange)
+                .and_then(|i| proof.instances.get(i))
+                .and_then(|c| <[Fr; 2]>::try_from(c.as_slice()).ok())
+                .map(|bounds| {
+                    OutputRange::from_quantized(bounds, settings.model_output_scales[0])
+                });
+            prove(ProveOptions {
+                data_path: witness,
+                compiled_model_path: compiled_model,
+                pk_path,
+                proof_path: Some(reprove_path),
+                srs_path,
+                transcript: TranscriptType::EVM,
+                strategy: StrategyType::Single,
+                settings_path,
+                check_mode: CheckMode::SAFE,
+                max_logrows: None,
+                proof_format: ProofFormat::Json,
+                metadata: proof.metadata.clone(),
+                elide_outputs: !proof.elided_instances.is_empty(),
+                challenge,
+                output_range,
+            })
+            .await?;
+            Ok(())
+        }
+        _ => Err(format!(
+            "{:?} transcript proofs are not EVM verifiable, pass --reprove-path (with the witness, compiled model, pk, srs and settings) to reprove",
+            proof.transcript_type
+        )
+        .into()),
+    }
+}
+
 /// helper function to generate the deployment code from yul code
 #[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn gen_deployment_code(yul_code: YulCode) -> Result<DeploymentCode, Box<dyn Error>> {
@@ -716,9 +1562,20 @@ pub(crate) fn gen_deployment_code(yul_code: YulCode) -> Result<DeploymentCode, B
 }
 
 #[cfg(feature = "render")]
-pub(crate) fn render(model: PathBuf, output: PathBuf, args: RunArgs) -> Result<(), Box<dyn Error>> {
+pub(crate) fn render(
+    model: PathBuf,
+    output: PathBuf,
+    show_labels: bool,
+    args: RunArgs,
+) -> Result<(), Box<dyn Error>> {
     let circuit = GraphCircuit::from_run_args(&args, &model)?;
     info!("Rendering circuit");
+    if circuit.settings.run_args.logrows > 12 {
+        warn!(
+            "rendering all 2^{} rows, the layout is easier to read at a small logrows",
+            circuit.settings.run_args.logrows
+        );
+    }
 
     // Create the area we want to draw on.
     // We could use SVGBackend if we want to render to .svg instead.
@@ -728,8 +1585,8 @@ pub(crate) fn render(model: PathBuf, output: PathBuf, args: RunArgs) -> Result<(
     let root = root.titled("Layout", ("sans-serif", 20))?;
 
     halo2_proofs::dev::CircuitLayout::default()
-        // We hide labels, else most circuits become impossible to decipher because of overlaid text
-        .show_labels(false)
+        // labels are hidden by default, else most circuits become impossible to decipher because of overlaid text
+        .show_labels(show_labels)
         .render(circuit.settings.run_args.logrows, &circuit, &root)?;
     Ok(())
 }
@@ -741,10 +1598,11 @@ pub(crate) fn create_evm_verifier(
     settings_path: PathBuf,
     sol_code_path: PathBuf,
     abi_path: PathBuf,
+    verifier_format: VerifierFormat,
 ) -> Result<(), Box<dyn Error>> {
     check_solc_requirement();
     let circuit_settings = GraphSettings::load(&settings_path)?;
-    let params = load_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
+    let params = load_verifier_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
 
     let num_instance = circuit_settings.total_instances();
 
@@ -756,21 +1614,57 @@ pub(crate) fn create_evm_verifier(
     let mut f = File::create(sol_code_path.clone())?;
     let _ = f.write(yul_code.as_bytes());
 
-    let output = fix_verifier_sol(
-        sol_code_path.clone(),
-        num_instance.iter().sum::<usize>().try_into().unwrap(),
-        None,
-        None,
-    )?;
+    let total_instances = num_instance.iter().sum::<usize>().try_into().unwrap();
+    let output = fix_verifier_sol(sol_code_path.clone(), total_instances, None, None)?;
 
     let mut f = File::create(sol_code_path.clone())?;
     let _ = f.write(output.as_bytes());
 
     // fetch abi of the contract
-    let (abi, _, _) = get_contract_artifacts(sol_code_path, "Verifier", None)?;
-    // save abi to file
+    let (abi, _, runtime_bytecode) =
+        get_contract_artifacts(sol_code_path.clone(), "Verifier", None)?;
+    // save abi to file, the entrypoint of a split verifier keeps the same abi
     serde_json::to_writer(std::fs::File::create(abi_path)?, &abi)?;
 
+    let split = match verifier_format {
+        VerifierFormat::Single => false,
+        VerifierFormat::Split => true,
+        VerifierFormat::Auto => {
+            info!(
+                "single verifier runtime bytecode size: {} bytes",
+                runtime_bytecode.len()
+            );
+            runtime_bytecode.len() > MAX_RUNTIME_BYTECODE_SIZE
+        }
+    };
+    if split {
+        // each library aims for half the limit, leaving room for the code they all repeat, and the verifier is split
+        // further for as long as one of them is still over it
+        let mut num_libraries = std::cmp::max(
+            2,
+            runtime_bytecode
+                .len()
+                .div_ceil(MAX_RUNTIME_BYTECODE_SIZE / 2),
+        );
+        loop {
+            let split = split_verifier_sol(&output, total_instances, num_libraries)?;
+            let mut f = File::create(&sol_code_path)?;
+            f.write_all(split.as_bytes())?;
+            let sizes = verifier_library_sizes(sol_code_path.clone(), None)?;
+            info!(
+                "split the verifier into {} libraries and an entrypoint, of runtime bytecode sizes {:?} bytes",
+                num_libraries, sizes
+            );
+            if sizes
+                .into_iter()
+                .all(|size| size <= MAX_RUNTIME_BYTECODE_SIZE)
+            {
+                break;
+            }
+            num_libraries += 1;
+        }
+    }
+
     Ok(())
 }
 
@@ -787,7 +1681,7 @@ pub(crate) fn create_evm_data_attestation_verifier(
     check_solc_requirement();
 
     let settings = GraphSettings::load(&settings_path)?;
-    let params = load_params_cmd(srs_path, settings.run_args.logrows)?;
+    let params = load_verifier_params_cmd(srs_path, settings.run_args.logrows)?;
 
     let visibility = VarVisibility::from_args(settings.run_args)?;
 
@@ -928,7 +1822,7 @@ pub(crate) fn create_evm_aggregate_verifier(
     circuit_settings: Vec<PathBuf>,
 ) -> Result<(), Box<dyn Error>> {
     check_solc_requirement();
-    let params: ParamsKZG<Bn256> = load_srs::<KZGCommitmentScheme<Bn256>>(srs_path)?;
+    let params = load_verifier_srs(srs_path)?;
 
     let settings: Vec<GraphSettings> = circuit_settings
         .iter()
@@ -980,7 +1874,7 @@ pub(crate) fn compile_model(
     settings_path: PathBuf,
 ) -> Result<(), Box<dyn Error>> {
     let settings = GraphSettings::load(&settings_path)?;
-    let model = Model::from_run_args(&settings.run_args, &model_path)?;
+    let model = Model::from_settings(&settings, &model_path)?;
     model.save(compiled_model)?;
     Ok(())
 }
@@ -991,9 +1885,11 @@ pub(crate) fn setup(
     settings_path: PathBuf,
     vk_path: PathBuf,
     pk_path: PathBuf,
+    max_logrows: Option<u32>,
 ) -> Result<(), Box<dyn Error>> {
     // these aren't real values so the sanity checks are mostly meaningless
     let circuit_settings = GraphSettings::load(&settings_path)?;
+    circuit_settings.check_max_logrows(max_logrows)?;
     let circuit = GraphCircuit::preprocessed_from_settings(
         &circuit_settings,
         &compiled_model,
@@ -1001,11 +1897,15 @@ pub(crate) fn setup(
     )?;
     let params = load_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
 
-    let pk = create_keys::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(&circuit, &params)
-        .map_err(Box::<dyn Error>::from)?;
+    let keygen =
+        create_keys_with_timings::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(&circuit, &params)
+            .map_err(Box::<dyn Error>::from)?;
+    ProofMetrics::new(circuit_settings.run_args.logrows)
+        .with_keygen(&keygen)
+        .report();
 
-    save_vk::<KZGCommitmentScheme<Bn256>>(&vk_path, pk.get_vk())?;
-    save_pk::<KZGCommitmentScheme<Bn256>>(&pk_path, &pk)?;
+    save_vk::<KZGCommitmentScheme<Bn256>>(&vk_path, keygen.pk.get_vk())?;
+    save_pk::<KZGCommitmentScheme<Bn256>>(&pk_path, &keygen.pk)?;
     Ok(())
 }
 
@@ -1052,20 +1952,71 @@ pub(crate) async fn setup_test_evm_witness(
     Ok(())
 }
 
+/// The inputs and options of [prove].
 #[cfg(not(target_arch = "wasm32"))]
-pub(crate) async fn prove(
-    data_path: PathBuf,
-    compiled_model_path: PathBuf,
-    pk_path: PathBuf,
-    proof_path: Option<PathBuf>,
-    srs_path: PathBuf,
-    transcript: TranscriptType,
-    strategy: StrategyType,
-    settings_path: PathBuf,
-    check_mode: CheckMode,
-) -> Result<Snark<Fr, G1Affine>, Box<dyn Error>> {
-    let data = GraphWitness::from_path(data_path)?;
+#[derive(Clone, Debug)]
+pub(crate) struct ProveOptions {
+    /// The witness to prove
+    pub data_path: PathBuf,
+    /// The compiled model the witness was generated with
+    pub compiled_model_path: PathBuf,
+    /// The proving key
+    pub pk_path: PathBuf,
+    /// Where to save the proof, if anywhere
+    pub proof_path: Option<PathBuf>,
+    /// The SRS the proving key was generated with
+    pub srs_path: PathBuf,
+    /// The transcript the proof is made with
+    pub transcript: TranscriptType,
+    /// The strategy the proof is checked with
+    pub strategy: StrategyType,
+    /// The settings of the circuit
+    pub settings_path: PathBuf,
+    /// Whether the circuit is laid out with safety checks
+    pub check_mode: CheckMode,
+    /// Rejects circuits of more than this many log_2 rows
+    pub max_logrows: Option<u32>,
+    /// The format the proof is saved in
+    pub proof_format: ProofFormat,
+    /// User metadata recorded in the proof
+    pub metadata: BTreeMap<String, String>,
+    /// Whether the outputs are left out of the proof's instances
+    pub elide_outputs: bool,
+    /// The challenge supplied by the verifier, for circuits that take one
+    pub challenge: Option<Fr>,
+    /// The bounds the first output is claimed to lie within, for circuits that assert them
+    pub output_range: Option<OutputRange>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn prove(options: ProveOptions) -> Result<Snark<Fr, G1Affine>, Box<dyn Error>> {
+    let ProveOptions {
+        data_path,
+        compiled_model_path,
+        pk_path,
+        proof_path,
+        srs_path,
+        transcript,
+        strategy,
+        settings_path,
+        check_mode,
+        max_logrows,
+        proof_format,
+        metadata,
+        elide_outputs,
+        challenge,
+        output_range,
+    } = options;
+    let mut data = GraphWitness::from_path(data_path)?;
+    if challenge.is_some() {
+        data.challenge = challenge;
+    }
     let circuit_settings = GraphSettings::load(&settings_path)?;
+    if let Some(output_range) = output_range {
+        let scale = circuit_settings.model_output_scales[0];
+        data.output_range = Some(output_range.quantize(scale)?);
+    }
+    circuit_settings.check_max_logrows(max_logrows)?;
     let mut circuit = GraphCircuit::preprocessed_from_settings(
         &circuit_settings,
         &compiled_model_path,
@@ -1076,8 +2027,16 @@ pub(crate) async fn prove(
     let public_inputs = circuit.prepare_public_inputs(&data)?;
 
     let circuit_settings = circuit.settings.clone();
+    let public_input_hash = circuit_settings.public_input_hash();
+    // checked up front, as the circuit is consumed by the prover
+    let elided_instances = match elide_outputs {
+        true => circuit.elide_outputs(&mut public_inputs.clone())?,
+        false => vec![],
+    };
 
-    let params = load_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
+    let logrows = circuit_settings.run_args.logrows;
+
+    let params = load_params_cmd(srs_path, logrows)?;
 
     let pk = load_pk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(pk_path, circuit_settings)
         .map_err(Box::<dyn Error>::from)?;
@@ -1087,10 +2046,10 @@ pub(crate) async fn prove(
     let now = Instant::now();
 
     // creates and verifies the proof
-    let snark = match strategy {
+    let proof = match strategy {
         StrategyType::Single => {
             let strategy = KZGSingleStrategy::new(&params);
-            create_proof_circuit_kzg(
+            create_proof_circuit_kzg_with_metrics(
                 circuit,
                 &params,
                 public_inputs,
@@ -1102,7 +2061,7 @@ pub(crate) async fn prove(
         }
         StrategyType::Accum => {
             let strategy = AccumulatorStrategy::new(&params);
-            create_proof_circuit_kzg(
+            create_proof_circuit_kzg_with_metrics(
                 circuit,
                 &params,
                 public_inputs,
@@ -1113,6 +2072,17 @@ pub(crate) async fn prove(
             )?
         }
     };
+    if let Some(peak_memory) = proof.peak_memory {
+        info!("proving peak memory: {}MB", peak_memory >> 20);
+    }
+    ProofMetrics::new(logrows).with_proof(&proof).report();
+    let mut snark = proof.snark;
+    snark.metadata = metadata;
+    snark.public_input_hash = public_input_hash;
+    for column in &elided_instances {
+        snark.instances[*column].clear();
+    }
+    snark.elided_instances = elided_instances;
     let elapsed = now.elapsed();
     info!(
         "proof took {}.{}",
@@ -1121,12 +2091,179 @@ pub(crate) async fn prove(
     );
 
     if let Some(proof_path) = proof_path {
-        snark.save(&proof_path)?;
+        snark.save_with_format(&proof_path, proof_format)?;
     }
 
     Ok(snark)
 }
 
+/// Generates keys, proves and verifies without writing (and re-reading) the keys or the proof.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn prove_verify(
+    data_path: PathBuf,
+    compiled_model_path: PathBuf,
+    srs_path: PathBuf,
+    settings_path: PathBuf,
+    transcript: TranscriptType,
+    check_mode: CheckMode,
+    checkpoint_dir: Option<PathBuf>,
+    output_template: Option<OutputTemplate>,
+) -> Result<(), Box<dyn Error>> {
+    let circuit_settings = GraphSettings::load(&settings_path)?;
+    let params = load_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
+
+    let checkpoint = match checkpoint_dir {
+        Some(dir) => {
+            let fingerprint = ProveCheckpoint::fingerprint(
+                &[&data_path, &compiled_model_path, &settings_path],
+                &params,
+                check_mode,
+            )?;
+            Some(ProveCheckpoint::open(dir, &fingerprint)?)
+        }
+        None => None,
+    };
+
+    let build_circuit = || -> Result<GraphCircuit, Box<dyn Error>> {
+        let data = GraphWitness::from_path(data_path.clone())?;
+        let mut circuit = GraphCircuit::preprocessed_from_settings(
+            &circuit_settings,
+            &compiled_model_path,
+            check_mode,
+        )?;
+        circuit.load_graph_witness(&data)?;
+        Ok(circuit)
+    };
+    let mut circuit = match &checkpoint {
+        Some(checkpoint) => checkpoint.circuit(build_circuit)?,
+        None => build_circuit()?,
+    };
+    let data = circuit.graph_witness.clone();
+    let public_inputs = circuit.prepare_public_inputs(&data)?;
+
+    let mut metrics = ProofMetrics::new(circuit.settings.run_args.logrows);
+    // the keygen metrics are only known if the keys weren't checkpointed
+    let build_pk = || -> Result<_, Box<dyn Error>> {
+        let keygen = create_keys_with_timings::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(
+            &circuit, &params,
+        )
+        .map_err(Box::<dyn Error>::from)?;
+        metrics = metrics.clone().with_keygen(&keygen);
+        Ok(keygen.pk)
+    };
+    let pk = match &checkpoint {
+        Some(checkpoint) => checkpoint.pk(circuit.settings.clone(), build_pk)?,
+        None => build_pk()?,
+    };
+
+    let strategy = KZGSingleStrategy::new(&params);
+    let proof = create_proof_circuit_kzg_with_metrics(
+        circuit,
+        &params,
+        public_inputs,
+        &pk,
+        transcript,
+        strategy,
+        check_mode,
+    )?;
+    info!(
+        "proof took {}.{}",
+        proof.proof_time.as_secs(),
+        proof.proof_time.subsec_millis()
+    );
+    let metrics = metrics.with_proof(&proof);
+    let snark = proof.snark;
+
+    let now = Instant::now();
+    let strategy = KZGSingleStrategy::new(params.verifier_params());
+    verify_proof_circuit_kzg(
+        params.verifier_params(),
+        snark.clone(),
+        pk.get_vk(),
+        strategy,
+    )?;
+    let elapsed = now.elapsed();
+    info!(
+        "verify took {}.{}",
+        elapsed.as_secs(),
+        elapsed.subsec_millis()
+    );
+    info!("proved and verified");
+    metrics.with_verify_time(elapsed).report();
+
+    if let Some(template) = output_template {
+        let bytes = snark.to_bytes(ProofFormat::Json)?;
+        let proof_path = OutputPaths::new(template).next(&data_path, 0, &bytes);
+        write_output(&proof_path, &bytes)?;
+        info!("saved the proof to {:?}", proof_path);
+    }
+    Ok(())
+}
+
+/// Proves each of `witnesses` as [prove] does, saving the proofs at the paths the output template expands to (creating
+/// their directories), see [OutputTemplate].
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn prove_all(
+    witnesses: Vec<PathBuf>,
+    compiled_model_path: PathBuf,
+    pk_path: PathBuf,
+    srs_path: PathBuf,
+    settings_path: PathBuf,
+    transcript: TranscriptType,
+    check_mode: CheckMode,
+    proof_format: ProofFormat,
+    output_template: OutputTemplate,
+    max_logrows: Option<u32>,
+    metadata: BTreeMap<String, String>,
+    elide_outputs: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut output_paths = OutputPaths::new(output_template);
+    let now = Instant::now();
+    for (i, data_path) in witnesses.iter().enumerate() {
+        let snark = prove(ProveOptions {
+            data_path: data_path.clone(),
+            compiled_model_path: compiled_model_path.clone(),
+            pk_path: pk_path.clone(),
+            proof_path: None,
+            srs_path: srs_path.clone(),
+            transcript,
+            strategy: StrategyType::Single,
+            settings_path: settings_path.clone(),
+            check_mode,
+            max_logrows,
+            proof_format,
+            metadata: metadata.clone(),
+            elide_outputs,
+            challenge: None,
+            output_range: None,
+        })
+        .await?;
+
+        let bytes = snark.to_bytes(proof_format)?;
+        let proof_path = output_paths.next(data_path, i, &bytes);
+        write_output(&proof_path, &bytes)?;
+        info!("proved {:?} to {:?}", data_path, proof_path);
+    }
+    let elapsed = now.elapsed();
+    info!(
+        "proving {} witnesses took {}.{}",
+        witnesses.len(),
+        elapsed.as_secs(),
+        elapsed.subsec_millis()
+    );
+    Ok(())
+}
+
+/// Writes `bytes` to `path`, creating its parent directories (e.g those of an output template).
+#[cfg(not(target_arch = "wasm32"))]
+fn write_output(path: &Path, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub(crate) async fn fuzz(
     compiled_model_path: PathBuf,
@@ -1277,6 +2414,9 @@ pub(crate) async fn fuzz(
             proof: bad_proof_bytes,
             protocol: proof.protocol.clone(),
             transcript_type: transcript,
+            metadata: proof.metadata.clone(),
+            public_input_hash: proof.public_input_hash.clone(),
+            elided_instances: proof.elided_instances.clone(),
         };
 
         verify_proof_circuit_kzg(
@@ -1303,6 +2443,9 @@ pub(crate) async fn fuzz(
             proof: proof.proof.clone(),
             protocol: proof.protocol.clone(),
             transcript_type: transcript,
+            metadata: proof.metadata.clone(),
+            public_input_hash: proof.public_input_hash.clone(),
+            elided_instances: proof.elided_instances.clone(),
         };
 
         verify_proof_circuit_kzg(
@@ -1336,6 +2479,9 @@ pub(crate) async fn fuzz(
                 proof: bad_proof_bytes,
                 protocol: proof.protocol.clone(),
                 transcript_type: transcript,
+                metadata: proof.metadata.clone(),
+                public_input_hash: proof.public_input_hash.clone(),
+                elided_instances: proof.elided_instances.clone(),
             };
 
             let res = evm_verify(deployment_code.clone(), bad_proof);
@@ -1361,6 +2507,9 @@ pub(crate) async fn fuzz(
                 proof: proof.proof.clone(),
                 protocol: proof.protocol.clone(),
                 transcript_type: transcript,
+                metadata: proof.metadata.clone(),
+                public_input_hash: proof.public_input_hash.clone(),
+                elided_instances: proof.elided_instances.clone(),
             };
 
             let res = evm_verify(deployment_code.clone(), bad_proof);
@@ -1518,20 +2667,245 @@ pub(crate) fn aggregate(
     Ok(())
 }
 
+pub(crate) fn commit_params(
+    compiled_model: PathBuf,
+    settings_path: PathBuf,
+    output: PathBuf,
+) -> Result<ParamsCommitment, Box<dyn Error>> {
+    let circuit_settings = GraphSettings::load(&settings_path)?;
+    let circuit = GraphCircuit::preprocessed_from_settings(
+        &circuit_settings,
+        &compiled_model,
+        CheckMode::UNSAFE,
+    )?;
+    let commitment = circuit.params_commitment()?;
+    info!("params commitment: {:?}", commitment.hash);
+    commitment.save(&output)?;
+    Ok(commitment)
+}
+
 pub(crate) fn verify(
     proof_path: PathBuf,
     settings_path: PathBuf,
     vk_path: PathBuf,
     srs_path: PathBuf,
+    compiled_model: Option<PathBuf>,
+    params_commitment: Option<PathBuf>,
+    challenge: Option<Fr>,
+    output_range: Option<OutputRange>,
+    debug_transcript: bool,
+) -> Result<(), Box<dyn Error>> {
+    let circuit_settings = GraphSettings::load(&settings_path)?;
+    let params = load_verifier_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
+    let mut proof = Snark::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
+    // the challenge is an instance, so a proof can't be reused against another challenge without failing to verify
+    circuit_settings.check_challenge(&proof.instances, challenge)?;
+    // the proof only shows the output lies within the bounds it holds, so they have to be the bounds expected
+    circuit_settings.check_output_range(&proof.instances, output_range)?;
+    circuit_settings.check_public_input_hash(proof.public_input_hash.as_ref())?;
+    for (key, value) in &proof.metadata {
+        info!("proof metadata {}: {}", key, value);
+    }
+
+    let elided = std::mem::take(&mut proof.elided_instances);
+    match compiled_model {
+        Some(compiled_model) => {
+            let circuit = GraphCircuit::preprocessed_from_settings(
+                &circuit_settings,
+                &compiled_model,
+                CheckMode::UNSAFE,
+            )?;
+            if !elided.is_empty() {
+                circuit.restore_elided_outputs(&mut proof.instances, &elided)?;
+                info!("recomputed the outputs elided from the proof");
+            }
+            // a compiled model passed only to recompute outputs needn't commit to its params
+            if elided.is_empty() || circuit_settings.run_args.param_visibility.is_hashed() {
+                circuit.check_params_hash(&proof.instances)?;
+                info!(
+                    "proof commits to the params of {}",
+                    compiled_model.display()
+                );
+            }
+        }
+        None if !elided.is_empty() => {
+            return Err(
+                "the proof elides its outputs, pass --compiled-model to recompute them".into(),
+            )
+        }
+        None => {}
+    }
+    if let Some(params_commitment) = params_commitment {
+        let commitment = ParamsCommitment::load(&params_commitment)?;
+        circuit_settings.check_params_commitment(&proof.instances, &commitment)?;
+        info!(
+            "proof commits to the params committed to in {}",
+            params_commitment.display()
+        );
+    }
+    circuit_settings.check_public_inputs_range(&proof.instances)?;
+
+    let strategy = KZGSingleStrategy::new(params.verifier_params());
+    let vk = load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, circuit_settings)?;
+    let now = Instant::now();
+    let result = verify_proof_circuit_kzg(params.verifier_params(), proof.clone(), &vk, strategy);
+    let elapsed = now.elapsed();
+    info!(
+        "verify took {}.{}",
+        elapsed.as_secs(),
+        elapsed.subsec_millis()
+    );
+    info!("verified: {}", result.is_ok());
+    ProofMetrics::new(vk.get_domain().k())
+        .with_verify_time(elapsed)
+        .report();
+    if result.is_err() && debug_transcript {
+        if let Err(diagnostic) =
+            verify_proof_circuit_kzg_debug(params.verifier_params(), &proof, &vk)
+        {
+            warn!("{}", diagnostic);
+            info!(
+                "transcript diagnostic: {}",
+                serde_json::to_string_pretty(&diagnostic)?
+            );
+            return Err(diagnostic.to_string().into());
+        }
+    }
+    result.map_err(|e| e.into())
+}
+
+pub(crate) fn setup_ipa(
+    compiled_model: PathBuf,
+    settings_path: PathBuf,
+    vk_path: PathBuf,
+    pk_path: PathBuf,
+    max_logrows: Option<u32>,
+) -> Result<(), Box<dyn Error>> {
+    let circuit_settings = GraphSettings::load(&settings_path)?;
+    circuit_settings.check_max_logrows(max_logrows)?;
+    let circuit = GraphCircuit::preprocessed_from_settings(
+        &circuit_settings,
+        &compiled_model,
+        CheckMode::UNSAFE,
+    )?;
+    let params = gen_ipa_params(circuit_settings.run_args.logrows);
+
+    let pk = create_keys::<IPAScheme, Fr, GraphCircuit>(&circuit, &params)
+        .map_err(Box::<dyn Error>::from)?;
+
+    save_vk::<IPAScheme>(&vk_path, pk.get_vk())?;
+    save_pk::<IPAScheme>(&pk_path, &pk)?;
+    Ok(())
+}
+
+pub(crate) fn prove_ipa(
+    data_path: PathBuf,
+    compiled_model_path: PathBuf,
+    pk_path: PathBuf,
+    proof_path: PathBuf,
+    settings_path: PathBuf,
+    check_mode: CheckMode,
+    max_logrows: Option<u32>,
+) -> Result<(), Box<dyn Error>> {
+    let data = GraphWitness::from_path(data_path)?;
+    let circuit_settings = GraphSettings::load(&settings_path)?;
+    circuit_settings.check_max_logrows(max_logrows)?;
+    let mut circuit = GraphCircuit::preprocessed_from_settings(
+        &circuit_settings,
+        &compiled_model_path,
+        check_mode,
+    )?;
+
+    circuit.load_graph_witness(&data)?;
+    let public_inputs = circuit.prepare_public_inputs(&data)?;
+    let circuit_settings = circuit.settings.clone();
+    let public_input_hash = circuit_settings.public_input_hash();
+
+    let params = gen_ipa_params(circuit_settings.run_args.logrows);
+    let pk = load_pk::<IPAScheme, Fr, GraphCircuit>(pk_path, circuit_settings)
+        .map_err(Box::<dyn Error>::from)?;
+
+    let now = Instant::now();
+    let mut snark = create_proof_circuit_ipa(circuit, &params, public_inputs, &pk, check_mode)?;
+    snark.public_input_hash = public_input_hash;
+    let elapsed = now.elapsed();
+    info!(
+        "proof took {}.{}",
+        elapsed.as_secs(),
+        elapsed.subsec_millis()
+    );
+    info!("proof size: {} bytes", snark.proof.len());
+
+    snark.save(&proof_path)?;
+    Ok(())
+}
+
+pub(crate) fn verify_ipa(
+    proof_path: PathBuf,
+    settings_path: PathBuf,
+    vk_path: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    let circuit_settings = GraphSettings::load(&settings_path)?;
+    let params = gen_ipa_params(circuit_settings.run_args.logrows);
+    let proof = Snark::load::<IPAScheme>(&proof_path)?;
+    circuit_settings.check_public_input_hash(proof.public_input_hash.as_ref())?;
+    circuit_settings.check_public_inputs_range(&proof.instances)?;
+
+    let vk = load_vk::<IPAScheme, Fr, GraphCircuit>(vk_path, circuit_settings)?;
+    let now = Instant::now();
+    let result = verify_proof_circuit_ipa(&params, &proof, &vk);
+    let elapsed = now.elapsed();
+    info!(
+        "verify took {}.{}",
+        elapsed.as_secs(),
+        elapsed.subsec_millis()
+    );
+    info!("verified: {}", result.is_ok());
+    result
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn create_bundle(
+    settings_path: PathBuf,
+    proof_path: PathBuf,
+    vk_path: PathBuf,
+    srs_path: PathBuf,
+    bundle_path: PathBuf,
 ) -> Result<(), Box<dyn Error>> {
     let circuit_settings = GraphSettings::load(&settings_path)?;
     let params = load_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
     let proof = Snark::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
+    let vk =
+        load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, circuit_settings.clone())?;
+
+    let bundle = Bundle::new(proof, vk, circuit_settings, &params)?;
+    // catch mismatched files when bundling rather than when the recipient verifies
+    bundle.check(&params)?;
+    bundle.save(&bundle_path)?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn verify_bundle(bundle_path: PathBuf, srs_path: PathBuf) -> Result<(), Box<dyn Error>> {
+    let bundle = Bundle::load(&bundle_path)?;
+    if bundle.manifest.version != env!("CARGO_PKG_VERSION") {
+        warn!(
+            "bundle was created with ezkl {} but this is ezkl {}",
+            bundle.manifest.version,
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+    let params = load_verifier_params_cmd(srs_path, bundle.manifest.logrows)?;
+    bundle.check(&params)?;
+    bundle
+        .settings
+        .check_public_inputs_range(&bundle.proof.instances)?;
+    info!("bundle is consistent with the srs");
 
     let strategy = KZGSingleStrategy::new(params.verifier_params());
-    let vk = load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, circuit_settings)?;
     let now = Instant::now();
-    let result = verify_proof_circuit_kzg(params.verifier_params(), proof, &vk, strategy);
+    let result =
+        verify_proof_circuit_kzg(params.verifier_params(), bundle.proof, &bundle.vk, strategy);
     let elapsed = now.elapsed();
     info!(
         "verify took {}.{}",
@@ -1542,20 +2916,75 @@ pub(crate) fn verify(
     result.map_err(|e| e.into())
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn combine_split(
+    first_bundle: PathBuf,
+    second_bundle: PathBuf,
+    split_path: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    let first = Bundle::load(&first_bundle)?;
+    let second = Bundle::load(&second_bundle)?;
+    let split = SplitProof::new(first, second)?;
+    info!(
+        "the parts share a boundary of {} columns",
+        split.boundary.len()
+    );
+    split.save(&split_path)?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn verify_split(split_path: PathBuf, srs_path: PathBuf) -> Result<(), Box<dyn Error>> {
+    let split = SplitProof::load(&split_path)?;
+    let first_params = load_verifier_params_cmd(srs_path.clone(), split.first.manifest.logrows)?;
+    let second_params = load_verifier_params_cmd(srs_path, split.second.manifest.logrows)?;
+    split.check(&first_params, &second_params)?;
+    for part in [&split.first, &split.second] {
+        part.settings
+            .check_public_inputs_range(&part.proof.instances)?;
+    }
+    info!("split proof is consistent with the srs and its boundary");
+
+    let now = Instant::now();
+    let mut verified = true;
+    for (name, part, params) in [
+        ("first", split.first, first_params),
+        ("second", split.second, second_params),
+    ] {
+        let strategy = KZGSingleStrategy::new(params.verifier_params());
+        let result =
+            verify_proof_circuit_kzg(params.verifier_params(), part.proof, &part.vk, strategy);
+        if let Err(e) = result {
+            warn!("the {} proof failed to verify: {:?}", name, e);
+            verified = false;
+        }
+    }
+    let elapsed = now.elapsed();
+    info!(
+        "verify took {}.{}",
+        elapsed.as_secs(),
+        elapsed.subsec_millis()
+    );
+    info!("verified: {}", verified);
+    match verified {
+        true => Ok(()),
+        false => Err("split proof failed to verify".into()),
+    }
+}
+
 pub(crate) fn verify_aggr(
     proof_path: PathBuf,
     vk_path: PathBuf,
     srs_path: PathBuf,
     logrows: u32,
 ) -> Result<(), Box<dyn Error>> {
-    let params = load_params_cmd(srs_path, logrows)?;
+    let params = load_verifier_params_cmd(srs_path, logrows)?;
 
     let proof = Snark::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
 
-    let strategy = AccumulatorStrategy::new(params.verifier_params());
     let vk = load_vk::<KZGCommitmentScheme<Bn256>, Fr, AggregationCircuit>(vk_path, ())?;
     let now = Instant::now();
-    let result = verify_proof_circuit_kzg(&params, proof, &vk, strategy);
+    let result = verify_aggregate(&proof, &vk, &params);
 
     let elapsed = now.elapsed();
     info!(
@@ -1573,10 +3002,33 @@ pub(crate) fn load_params_cmd(
     srs_path: PathBuf,
     logrows: u32,
 ) -> Result<ParamsKZG<Bn256>, Box<dyn Error>> {
-    let mut params: ParamsKZG<Bn256> = load_srs::<KZGCommitmentScheme<Bn256>>(srs_path)?;
+    let params: ParamsKZG<Bn256> = load_srs::<KZGCommitmentScheme<Bn256>>(srs_path)?;
+    downsize_params(params, logrows)
+}
+
+/// helper function for loading the params of verifiers, which may be the verifier-only params written by
+/// extract-verifier-srs, see [load_verifier_srs]
+pub(crate) fn load_verifier_params_cmd(
+    srs_path: PathBuf,
+    logrows: u32,
+) -> Result<ParamsKZG<Bn256>, Box<dyn Error>> {
+    downsize_params(load_verifier_srs(srs_path)?, logrows)
+}
+
+fn downsize_params(
+    mut params: ParamsKZG<Bn256>,
+    logrows: u32,
+) -> Result<ParamsKZG<Bn256>, Box<dyn Error>> {
+    // halo2 panics deep inside proving / verification if the params are too small
+    if logrows > params.k() {
+        return Err(Box::new(ExecutionError::ParamsTooSmall(
+            params.k(),
+            logrows,
+        )));
+    }
     info!("downsizing params to {} logrows", logrows);
     if logrows < params.k() {
-        params.downsize(logrows);
+        downsize_srs(&mut params, logrows)?;
     }
     Ok(params)
 }
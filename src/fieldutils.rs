@@ -1,6 +1,34 @@
 use halo2_proofs::arithmetic::Field;
 /// Utilities for converting from Halo2 PrimeField types to integers (and vice-versa).
 use halo2curves::ff::PrimeField;
+use thiserror::Error;
+
+/// An integer that doesn't fit the signed range of a circuit.
+#[derive(Debug, Error, PartialEq)]
+#[error("{value} is outside of the {bits}-bit signed range [-2^{}, 2^{})", .bits - 1, .bits - 1)]
+pub struct FeltRangeError {
+    /// the offending value
+    pub value: i128,
+    /// the number of bits of the declared range
+    pub bits: usize,
+}
+
+/// Checks that x lies in the signed range [-2^(bits-1), 2^(bits-1)).
+fn check_range(x: i128, bits: usize) -> Result<(), FeltRangeError> {
+    let err = FeltRangeError { value: x, bits };
+    if bits == 0 {
+        return Err(err);
+    }
+    // anything of 128 bits or more fits by construction
+    if bits >= 128 {
+        return Ok(());
+    }
+    let bound = 1i128 << (bits - 1);
+    if x < -bound || x >= bound {
+        return Err(err);
+    }
+    Ok(())
+}
 
 /// Converts an i32 to a PrimeField element.
 pub fn i32_to_felt<F: PrimeField>(x: i32) -> F {
@@ -20,6 +48,22 @@ pub fn i128_to_felt<F: PrimeField>(x: i128) -> F {
     }
 }
 
+/// Checks that a PrimeField element encodes a signed integer in the range [-2^(bits-1), 2^(bits-1)).
+pub fn check_felt_range<F: PrimeField + PartialOrd + Field>(
+    x: F,
+    bits: usize,
+) -> Result<(), FeltRangeError> {
+    // elements beyond the i128 range can't be represented as bounded integers at all
+    let max = F::from_u128(i128::MAX as u128);
+    if x > max && -x > max {
+        return Err(FeltRangeError {
+            value: i128::MAX,
+            bits,
+        });
+    }
+    check_range(felt_to_i128(x), bits)
+}
+
 /// Converts a PrimeField element to an i32.
 pub fn felt_to_i32<F: PrimeField + PartialOrd + Field>(x: F) -> i32 {
     if x > F::from(i32::MAX as u64) {
@@ -71,6 +115,14 @@ mod test {
         assert_eq!(res, F::from(131072));
     }
 
+    #[test]
+    fn test_check_felt_range() {
+        assert!(check_felt_range(-F::from(128), 8).is_ok());
+        assert!(check_felt_range(-F::from(129), 8).is_err());
+        assert!(check_felt_range(F::from(128), 8).is_err());
+        assert!(check_felt_range(F::from_u128(u128::MAX), 64).is_err());
+    }
+
     #[test]
     fn felttoi32() {
         for x in -(2i32.pow(16))..(2i32.pow(16)) {
@@ -0,0 +1,253 @@
+use super::input::{DataSource, FileSourceInner, GraphData};
+use super::{quantize_float, FloatModel, GraphCircuit, GraphError};
+use crate::fieldutils::i128_to_felt;
+use crate::tensor::Tensor;
+use crate::RunArgs;
+use halo2curves::bn256::Fr as Fp;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// The scales [search_calibration] is run over by `ezkl calibrate`.
+pub const CALIBRATION_SCALES: Range<u32> = 1..16;
+
+/// A batch of calibration data: the model's float inputs and the [FloatModel]'s outputs for them, each flattened in
+/// row-major order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalibrationSample {
+    /// The model's inputs.
+    pub inputs: Vec<Vec<f64>>,
+    /// The float model's outputs, the reference the circuit's outputs are compared to.
+    pub outputs: Vec<Vec<f64>>,
+}
+
+/// The circuit parameters calibrated at a scale, and how far the circuit's outputs are from the float model's.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationPoint {
+    /// The scale the model is quantized at.
+    pub scale: u32,
+    /// The smallest lookup bits covering every calibration sample.
+    pub bits: usize,
+    /// The smallest logrows covering every calibration sample.
+    pub logrows: u32,
+    /// The largest absolute difference between any (dequantized) output of the circuit and the float model's.
+    pub max_error: f64,
+    /// Whether the circuit fits in the max logrows searched with, see [crate::graph::GraphSettings::check_max_logrows].
+    pub fits: bool,
+}
+
+/// Splits the inputs of `data` into batches of the `float_model`'s input shapes (which include the batch size), and
+/// runs the float model on each for the reference outputs.
+pub fn calibration_samples(
+    data: &GraphData,
+    float_model: &FloatModel,
+) -> Result<Vec<CalibrationSample>, Box<dyn Error>> {
+    let source = match &data.input_data {
+        DataSource::File(source) => source,
+        _ => {
+            return Err(Box::new(GraphError::InvalidInput(
+                "calibration inputs have to be read from a file".to_string(),
+            )))
+        }
+    };
+    let shapes = float_model.input_shapes()?;
+    if source.len() != shapes.len() {
+        return Err(Box::new(GraphError::InvalidInput(format!(
+            "expected {} tensors but got {}",
+            shapes.len(),
+            source.len()
+        ))));
+    }
+
+    let mut batches: Vec<Vec<Vec<f64>>> = vec![];
+    for (values, shape) in source.iter().zip(&shapes) {
+        let size = shape.iter().product::<usize>();
+        if size == 0 || values.len() % size != 0 {
+            return Err(Box::new(GraphError::InvalidInput(format!(
+                "{} values don't split into batches of shape {:?}",
+                values.len(),
+                shape
+            ))));
+        }
+        for (i, chunk) in values.chunks(size).enumerate() {
+            let chunk = chunk
+                .iter()
+                .map(|x| match x {
+                    // already quantized, at a scale they don't record
+                    FileSourceInner::Field(f) => Err(GraphError::InvalidInput(format!(
+                        "field element {:?} has no float value",
+                        f
+                    ))),
+                    x => Ok(x.to_float()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            match batches.get_mut(i) {
+                Some(batch) => batch.push(chunk),
+                None => batches.push(vec![chunk]),
+            }
+        }
+    }
+    if batches.iter().any(|b| b.len() != shapes.len()) {
+        return Err(Box::new(GraphError::InvalidInput(
+            "the tensors hold different numbers of batches".to_string(),
+        )));
+    }
+
+    batches
+        .into_iter()
+        .map(|inputs| {
+            let outputs = float_model.run(&inputs)?;
+            Ok(CalibrationSample { inputs, outputs })
+        })
+        .collect()
+}
+
+/// Quantizes the model at each of `scales`, calibrates the lookup bits and logrows to the `samples` (see
+/// [GraphCircuit::calibrate]) and measures the circuit's output error against the float model's. Scales the model
+/// can't be quantized or calibrated at are skipped, and those needing more than `max_logrows` don't fit.
+pub fn search_calibration(
+    model_path: &PathBuf,
+    run_args: &RunArgs,
+    samples: &[CalibrationSample],
+    scales: Range<u32>,
+    max_logrows: Option<u32>,
+) -> Result<Vec<CalibrationPoint>, Box<dyn Error>> {
+    let mut points = vec![];
+    for scale in scales {
+        let run_args = RunArgs { scale, ..*run_args };
+        let point = GraphCircuit::from_run_args(&run_args, model_path)
+            .and_then(|mut circuit| calibrate_at(&mut circuit, samples, max_logrows));
+        match point {
+            Ok(point) => points.push(point),
+            Err(e) => debug!("skipping scale {}: {}", scale, e),
+        }
+    }
+    Ok(points)
+}
+
+fn calibrate_at(
+    circuit: &mut GraphCircuit,
+    samples: &[CalibrationSample],
+    max_logrows: Option<u32>,
+) -> Result<CalibrationPoint, Box<dyn Error>> {
+    let scale = circuit.settings.run_args.scale;
+    let input_shapes = circuit.model.graph.input_shapes();
+    let input_scales = circuit.model.graph.get_input_scales();
+
+    let mut bits = 0;
+    let mut logrows = 0;
+    let mut max_error: f64 = 0.0;
+    for sample in samples {
+        let float_inputs = sample
+            .inputs
+            .iter()
+            .zip(&input_shapes)
+            .map(|(values, shape)| {
+                let values = values.iter().map(|x| *x as f32).collect::<Vec<_>>();
+                Tensor::new(Some(&values), shape)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // quantized as [crate::graph::Model::forward_float] does
+        let inputs = float_inputs
+            .iter()
+            .zip(&input_scales)
+            .map(|(t, scale)| {
+                let felts = t
+                    .iter()
+                    .map(|x| quantize_float(&(*x as f64), 0.0, *scale).map(i128_to_felt))
+                    .collect::<Result<Vec<Fp>, _>>()?;
+                Tensor::new(Some(&felts), t.dims())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // ensures we have converged
+        loop {
+            let before = circuit.settings.clone();
+            circuit.calibrate(&inputs)?;
+            if before == circuit.settings {
+                break;
+            }
+        }
+        bits = std::cmp::max(bits, circuit.settings.run_args.bits);
+        logrows = std::cmp::max(logrows, circuit.settings.run_args.logrows);
+
+        let outputs = circuit.model.forward_float(&float_inputs)?;
+        for (output, expected) in outputs.iter().zip(&sample.outputs) {
+            if output.len() != expected.len() {
+                return Err(Box::new(GraphError::InvalidInput(format!(
+                    "the reference output has {} elements, but the model outputs {}",
+                    expected.len(),
+                    output.len()
+                ))));
+            }
+            for (x, e) in output.iter().zip(expected) {
+                let error = (*x as f64 - e).abs();
+                // a NaN reference is never within any target error
+                if error.is_nan() || error > max_error {
+                    max_error = error;
+                }
+            }
+        }
+    }
+
+    let mut settings = circuit.settings.clone();
+    settings.run_args.bits = bits;
+    settings.run_args.logrows = logrows;
+    Ok(CalibrationPoint {
+        scale,
+        bits,
+        logrows,
+        max_error,
+        fits: settings.check_max_logrows(max_logrows).is_ok(),
+    })
+}
+
+/// The point with the smallest lookup bits (then logrows, then scale) whose outputs are within `target_error` of the
+/// float model's and that fits in the max logrows, if any.
+pub fn recommend(points: &[CalibrationPoint], target_error: f64) -> Option<&CalibrationPoint> {
+    points
+        .iter()
+        .filter(|p| p.fits && p.max_error <= target_error)
+        .min_by_key(|p| (p.bits, p.logrows, p.scale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::scale_to_multiplier;
+    use crate::graph::tests::test_run_args;
+
+    #[test]
+    fn test_search_calibration() {
+        let run_args = test_run_args();
+        let path = "examples/onnx/1l_relu/network.onnx".into();
+        let float_model = FloatModel::from_run_args(&run_args, &path).unwrap();
+        let mut data = GraphData::from_path("examples/onnx/1l_relu/input.json".into()).unwrap();
+        // the reference outputs are the float model's, rather than read from the data
+        data.output_data = None;
+        let samples = calibration_samples(&data, &float_model).unwrap();
+        assert_eq!(samples.len(), 1);
+        let relu = samples[0].inputs[0]
+            .iter()
+            .map(|x| (*x as f32).max(0.0) as f64)
+            .collect::<Vec<_>>();
+        assert_eq!(samples[0].outputs, vec![relu]);
+
+        let points = search_calibration(&path, &run_args, &samples, 2..8, None).unwrap();
+        assert_eq!(points.len(), 6);
+        for point in &points {
+            // relu is exact, so the error is that of rounding the input
+            assert!(point.max_error <= 0.5 / scale_to_multiplier(point.scale) + 1e-6);
+            assert!(point.fits);
+        }
+
+        let best = recommend(&points, 0.01).unwrap();
+        assert!(best.max_error <= 0.01);
+        assert!(points
+            .iter()
+            .filter(|p| p.bits < best.bits)
+            .all(|p| p.max_error > 0.01));
+    }
+}
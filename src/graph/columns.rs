@@ -0,0 +1,155 @@
+use super::GraphSettings;
+use crate::tensor::VarTensor;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2curves::bn256::Fr as Fp;
+use serde::{Deserialize, Serialize};
+
+/// The number of advice tensors the model is laid out over (see [super::ModelVars]): the two inputs and the output of
+/// each op. They all have the same number of columns, as the gates of an op span the same column of each.
+pub const MODEL_ADVICE_TENSORS: usize = 3;
+
+/// The fraction of its rows below which a column is reported as underused.
+pub const UNDERUSED_COLUMN_THRESHOLD: f64 = 0.1;
+
+/// How the model's layout uses the columns of its advice tensors. The layout assigns rows contiguously, filling each
+/// column before moving to the next, so a layout of `rows` rows fills every column but the last it reaches, and the
+/// columns allocated beyond that one (by the buffer for duplicated cells, see [VarTensor::new_advice], or for the
+/// rows of the modules, which are laid out over their own columns) are never assigned to at all.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdviceColumnUsage {
+    /// the number of rows of each column
+    pub col_size: usize,
+    /// the rows of the model's region, those of [GraphSettings::model_rows]
+    pub rows: usize,
+    /// the columns allocated per advice tensor when dead columns are kept (to hold
+    /// [GraphSettings::num_constraints] rows)
+    pub allocated: usize,
+}
+
+impl AdviceColumnUsage {
+    /// The usage of the columns of the circuits built with `settings`. The model's advice tensors are the first
+    /// columns [super::ModelVars::new] allocates, so their columns have as many rows as those of a fresh
+    /// [ConstraintSystem]. Settings generated without [GraphSettings::model_rows] are assumed to lay out all of
+    /// [GraphSettings::num_constraints] in the model's region.
+    pub fn from_settings(settings: &GraphSettings) -> Self {
+        let col_size = VarTensor::max_rows(
+            &ConstraintSystem::<Fp>::default(),
+            settings.run_args.logrows as usize,
+        );
+        let rows = match settings.model_rows {
+            0 => settings.num_constraints,
+            rows => rows,
+        };
+        AdviceColumnUsage {
+            col_size,
+            rows,
+            allocated: VarTensor::num_advice_columns(col_size, settings.num_constraints),
+        }
+    }
+
+    /// The usage of columns of `col_size` rows by a layout of `rows` rows, allocated to hold those rows alone.
+    pub fn new(rows: usize, col_size: usize) -> Self {
+        AdviceColumnUsage {
+            col_size,
+            rows,
+            allocated: VarTensor::num_advice_columns(col_size, rows),
+        }
+    }
+
+    /// Packs the layout's cells into the fewest columns that hold them, returning the number of columns per advice
+    /// tensor. The rows of the (dummy) layout already count the cells it duplicates across the boundaries of
+    /// columns of this size, so the last column the layout reaches is the last one assigned to, and an underused
+    /// column (see [AdviceColumnUsage::underused]) can only be that one: every column before it is full, leaving its
+    /// cells no spare rows to move into, and the columns past it are eliminated.
+    pub fn pack(&self) -> usize {
+        std::cmp::max(1, self.rows.div_ceil(self.col_size))
+    }
+
+    /// The number of columns per advice tensor the layout never assigns to.
+    pub fn dead(&self) -> usize {
+        self.allocated.saturating_sub(self.pack())
+    }
+
+    /// The number of rows the layout assigns in each of the allocated columns.
+    pub fn rows_per_column(&self) -> Vec<usize> {
+        (0..self.allocated)
+            .map(|i| {
+                self.rows
+                    .saturating_sub(i * self.col_size)
+                    .min(self.col_size)
+            })
+            .collect()
+    }
+
+    /// The columns (of each advice tensor) in which the layout assigns less than `threshold` of the rows, including
+    /// the dead ones.
+    pub fn underused(&self, threshold: f64) -> Vec<usize> {
+        self.rows_per_column()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, rows)| (*rows as f64) < threshold * self.col_size as f64)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The number of columns, over all the model's advice tensors, that [AdviceColumnUsage::pack] eliminates.
+    pub fn eliminated(&self) -> usize {
+        MODEL_ADVICE_TENSORS * self.dead()
+    }
+}
+
+impl std::fmt::Display for AdviceColumnUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let rows_per_column = self.rows_per_column();
+        write!(
+            f,
+            "the layout's {} rows assign to {} of the {} columns of each of the {} advice tensors ({}% of the last one used)",
+            self.rows,
+            self.pack(),
+            self.allocated,
+            MODEL_ADVICE_TENSORS,
+            100 * rows_per_column[self.pack() - 1] / self.col_size,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dead_columns() {
+        // a layout filling its columns exactly leaves the buffer column dead
+        let usage = AdviceColumnUsage::new(200, 100);
+        assert_eq!(usage.allocated, 3);
+        assert_eq!(usage.pack(), 2);
+        assert_eq!(usage.dead(), 1);
+        assert_eq!(usage.rows_per_column(), vec![100, 100, 0]);
+        assert_eq!(usage.underused(UNDERUSED_COLUMN_THRESHOLD), vec![2]);
+        assert_eq!(usage.eliminated(), 3);
+
+        // a barely used last column is underused, but its cells don't fit in the full columns
+        let usage = AdviceColumnUsage::new(205, 100);
+        assert_eq!(usage.allocated, 3);
+        assert_eq!(usage.dead(), 0);
+        assert_eq!(usage.underused(UNDERUSED_COLUMN_THRESHOLD), vec![2]);
+        assert_eq!(usage.pack(), 3);
+        assert_eq!(usage.eliminated(), 0);
+
+        // a layout fitting in a single column
+        let usage = AdviceColumnUsage::new(10, 100);
+        assert_eq!(usage.allocated, 1);
+        assert_eq!(usage.pack(), 1);
+
+        // the columns allocated for the rows of a module, laid out over its own columns
+        let mut settings = GraphSettings::default();
+        settings.run_args.logrows = 10;
+        let col_size = AdviceColumnUsage::from_settings(&settings).col_size;
+        settings.num_constraints = col_size - 1;
+        settings.model_rows = 10;
+        let usage = AdviceColumnUsage::from_settings(&settings);
+        assert_eq!(usage.allocated, 2);
+        assert_eq!(usage.pack(), 1);
+        assert_eq!(usage.eliminated(), 3);
+    }
+}
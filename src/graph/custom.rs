@@ -0,0 +1,420 @@
+use super::GraphError;
+use crate::circuit::lookup::LookupOp;
+use crate::circuit::region::RegionCtx;
+use crate::circuit::{BaseConfig, ForwardResult, Op};
+use crate::tensor::{Tensor, TensorError, ValTensor};
+use halo2curves::bn256::Fr as Fp;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::sync::{Arc, OnceLock, RwLock};
+#[cfg(not(target_arch = "wasm32"))]
+use tract_onnx::prelude::DatumType;
+#[cfg(not(target_arch = "wasm32"))]
+use tract_onnx::tract_core::ops::element_wise::{ElementWiseMiniOp, ElementWiseOp};
+
+/// The circuit implementation of a user-defined operator, for ONNX nodes (e.g of a custom domain) that ezkl has no
+/// builtin support for. Registered by domain and op type with [register_custom_op].
+///
+/// When a model is loaded a custom node is parsed as a unary elementwise op, which tract evaluates (and infers the
+/// shape of) through [CircuitOp::eval_float]. In the circuit, the inputs are the node's quantized inputs at the scales
+/// `in_scales`.
+pub trait CircuitOp: std::fmt::Debug + Send + Sync {
+    /// The op on a single float, as the model is evaluated on floats.
+    fn eval_float(&self, x: f32) -> f32;
+
+    /// The op on quantized inputs, as the witness is generated. Must agree with the output of [CircuitOp::layout].
+    fn f(&self, inputs: &[Tensor<Fp>], in_scales: &[u32]) -> Result<Tensor<Fp>, TensorError>;
+
+    /// Lays the op out in `region`, returning its output constrained to `values`. Any lookup used here has to be
+    /// included in [CircuitOp::required_lookups].
+    fn layout(
+        &self,
+        config: &mut BaseConfig<Fp>,
+        region: &mut RegionCtx<Fp>,
+        values: &[ValTensor<Fp>],
+        in_scales: &[u32],
+    ) -> Result<ValTensor<Fp>, Box<dyn Error>>;
+
+    /// The scale of the op's output, by default that of its first input.
+    fn out_scale(&self, in_scales: &[u32], global_scale: u32) -> u32 {
+        in_scales.first().copied().unwrap_or(global_scale)
+    }
+
+    /// The lookup tables [CircuitOp::layout] uses, which are configured when the circuit is.
+    fn required_lookups(&self, _in_scales: &[u32]) -> Vec<LookupOp> {
+        vec![]
+    }
+
+    /// Whether [CircuitOp::f] gives bit-identical witnesses across platforms, see [crate::RunArgs::integer_only].
+    fn is_integer_exact(&self) -> bool {
+        false
+    }
+}
+
+/// Builds a [CircuitOp] from the float and integer attributes of its ONNX node.
+pub type CircuitOpBuilder = fn(&BTreeMap<String, f32>) -> Box<dyn CircuitOp>;
+
+type Registry = HashMap<(&'static str, &'static str), CircuitOpBuilder>;
+
+fn registry() -> &'static RwLock<Registry> {
+    static REGISTRY: OnceLock<RwLock<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// The domains of the builtin ONNX operators, which custom ops can't be registered in.
+const BUILTIN_DOMAINS: [&str; 2] = ["", "ai.onnx"];
+
+/// Registers `builder` as the implementation of ONNX nodes of type `op_type` in `domain`, replacing any previous
+/// registration of the same domain and op type. Has to be called before the model is loaded, and before a compiled
+/// circuit using the op is deserialized.
+///
+/// Errors if `domain` is that of the builtin ONNX operators, or if `op_type` is the name of an op ezkl already parses,
+/// which a custom op would otherwise silently take over.
+pub fn register_custom_op(
+    domain: &'static str,
+    op_type: &'static str,
+    builder: CircuitOpBuilder,
+) -> Result<(), GraphError> {
+    if BUILTIN_DOMAINS.contains(&domain) {
+        return Err(GraphError::CustomOp(format!(
+            "{} can't be registered in the builtin onnx domain",
+            op_type
+        )));
+    }
+    if is_builtin_op(op_type) {
+        return Err(GraphError::CustomOp(format!(
+            "{}.{} has the name of a builtin op",
+            domain, op_type
+        )));
+    }
+    registry()
+        .write()
+        .unwrap()
+        .insert((domain, op_type), builder);
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_builtin_op(op_type: &str) -> bool {
+    super::utilities::builtin_onnx()
+        .op_register
+        .0
+        .contains_key(op_type)
+}
+
+// models aren't parsed, so no op type is taken
+#[cfg(target_arch = "wasm32")]
+fn is_builtin_op(_op_type: &str) -> bool {
+    false
+}
+
+/// The op types with a registered [CircuitOp], in any domain.
+pub fn custom_op_types() -> Vec<&'static str> {
+    let registry = registry().read().unwrap();
+    let op_types: BTreeSet<_> = registry.keys().map(|(_, op_type)| *op_type).collect();
+    op_types.into_iter().collect()
+}
+
+fn build_custom_op(
+    domain: &str,
+    op_type: &str,
+    attributes: &BTreeMap<String, f32>,
+) -> Result<Arc<dyn CircuitOp>, GraphError> {
+    match registry().read().unwrap().get(&(domain, op_type)) {
+        Some(builder) => Ok(Arc::from(builder(attributes))),
+        None => Err(GraphError::CustomOp(format!(
+            "no circuit implementation is registered for {}.{}",
+            domain, op_type
+        ))),
+    }
+}
+
+/// A node of a registered custom op type, see [CircuitOp]. Serialized as its domain, op type and attributes, such
+/// that its implementation is looked up afresh in the registry when a compiled circuit is loaded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "CustomOpSpec", into = "CustomOpSpec")]
+pub struct CustomOp {
+    /// The ONNX domain of the op.
+    pub domain: String,
+    /// The ONNX op type.
+    pub op_type: String,
+    /// The float and integer attributes of the ONNX node.
+    pub attributes: BTreeMap<String, f32>,
+    /// The scales of the inputs.
+    pub in_scales: Vec<u32>,
+    op: Arc<dyn CircuitOp>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct CustomOpSpec {
+    domain: String,
+    op_type: String,
+    attributes: BTreeMap<String, f32>,
+    in_scales: Vec<u32>,
+}
+
+impl TryFrom<CustomOpSpec> for CustomOp {
+    type Error = GraphError;
+
+    fn try_from(spec: CustomOpSpec) -> Result<Self, Self::Error> {
+        let op = build_custom_op(&spec.domain, &spec.op_type, &spec.attributes)?;
+        Ok(CustomOp {
+            domain: spec.domain,
+            op_type: spec.op_type,
+            attributes: spec.attributes,
+            in_scales: spec.in_scales,
+            op,
+        })
+    }
+}
+
+impl From<CustomOp> for CustomOpSpec {
+    fn from(op: CustomOp) -> Self {
+        CustomOpSpec {
+            domain: op.domain,
+            op_type: op.op_type,
+            attributes: op.attributes,
+            in_scales: op.in_scales,
+        }
+    }
+}
+
+impl CustomOp {
+    /// Builds the registered op of type `op_type` in `domain`.
+    pub fn new(
+        domain: &str,
+        op_type: &str,
+        attributes: BTreeMap<String, f32>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let op = build_custom_op(domain, op_type, &attributes)?;
+        Ok(CustomOp {
+            domain: domain.to_string(),
+            op_type: op_type.to_string(),
+            attributes,
+            in_scales: vec![],
+            op,
+        })
+    }
+
+    /// See [CircuitOp::is_integer_exact].
+    pub fn is_integer_exact(&self) -> bool {
+        self.op.is_integer_exact()
+    }
+}
+
+impl Op<Fp> for CustomOp {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn f(&self, x: &[Tensor<Fp>]) -> Result<ForwardResult<Fp>, TensorError> {
+        Ok(ForwardResult {
+            output: self.op.f(x, &self.in_scales)?,
+            intermediate_lookups: vec![],
+        })
+    }
+
+    fn as_string(&self) -> String {
+        format!("CUSTOM {}.{}", self.domain, self.op_type)
+    }
+
+    fn layout(
+        &self,
+        config: &mut BaseConfig<Fp>,
+        region: &mut RegionCtx<Fp>,
+        values: &[ValTensor<Fp>],
+    ) -> Result<Option<ValTensor<Fp>>, Box<dyn Error>> {
+        Ok(Some(self.op.layout(
+            config,
+            region,
+            values,
+            &self.in_scales,
+        )?))
+    }
+
+    fn out_scale(&self, in_scales: Vec<u32>, global_scale: u32) -> u32 {
+        self.op.out_scale(&in_scales, global_scale)
+    }
+
+    fn required_lookups(&self) -> Vec<LookupOp> {
+        self.op.required_lookups(&self.in_scales)
+    }
+
+    fn rescale(&self, in_scales: Vec<u32>, _: u32) -> Box<dyn Op<Fp>> {
+        Box::new(CustomOp {
+            in_scales,
+            ..self.clone()
+        })
+    }
+
+    fn clone_dyn(&self) -> Box<dyn Op<Fp>> {
+        Box::new(self.clone()) // Forward to the derive(Clone) impl
+    }
+}
+
+/// A custom op as tract sees it: a unary elementwise op evaluated through [CircuitOp::eval_float].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct TractCustomOp {
+    /// The ONNX domain of the op.
+    pub domain: String,
+    /// The ONNX op type.
+    pub op_type: String,
+    /// The float and integer attributes of the ONNX node.
+    pub attributes: BTreeMap<String, f32>,
+    op: Arc<dyn CircuitOp>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::hash::Hash for TractCustomOp {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.domain.hash(state);
+        self.op_type.hash(state);
+        for (k, v) in &self.attributes {
+            k.hash(state);
+            v.to_bits().hash(state);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ElementWiseMiniOp for TractCustomOp {
+    fn name(&self) -> String {
+        self.op_type.clone()
+    }
+
+    fn eval_in_place(
+        &self,
+        t: &mut tract_onnx::prelude::Tensor,
+        _out_dt: Option<DatumType>,
+    ) -> tract_onnx::prelude::TractResult<()> {
+        for x in t.as_slice_mut::<f32>()? {
+            *x = self.op.eval_float(*x);
+        }
+        Ok(())
+    }
+}
+
+/// Builds the nodes of registered custom op types as a [TractCustomOp], collecting their float and integer attributes.
+/// Nodes of a registered op type but of another domain are rejected.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn parse_custom_op(
+    _ctx: &tract_onnx::model::ParsingContext,
+    node: &tract_onnx::pb::NodeProto,
+) -> tract_onnx::prelude::TractResult<(
+    Box<dyn tract_onnx::tract_hir::infer::InferenceOp>,
+    Vec<String>,
+)> {
+    use tract_onnx::pb::attribute_proto::AttributeType;
+    let attributes = node
+        .attribute
+        .iter()
+        .filter_map(|a| match a.r#type() {
+            AttributeType::Float => Some((a.name.clone(), a.f)),
+            AttributeType::Int => Some((a.name.clone(), a.i as f32)),
+            _ => None,
+        })
+        .collect::<BTreeMap<_, _>>();
+    let op = build_custom_op(&node.domain, &node.op_type, &attributes)
+        .map_err(|e| tract_onnx::prelude::TractError::msg(e.to_string()))?;
+    let op = TractCustomOp {
+        domain: node.domain.clone(),
+        op_type: node.op_type.clone(),
+        attributes,
+        op,
+    };
+    Ok((Box::new(ElementWiseOp(Box::new(op), None)), vec![]))
+}
+
+/// Looks up the [CustomOp] of a node parsed by tract, if it is of a registered custom op type.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn load_custom_op(op: &dyn tract_onnx::prelude::Op) -> Option<CustomOp> {
+    let op = op.downcast_ref::<ElementWiseOp>()?;
+    let op = op.0.downcast_ref::<TractCustomOp>()?;
+    Some(CustomOp {
+        domain: op.domain.clone(),
+        op_type: op.op_type.clone(),
+        attributes: op.attributes.clone(),
+        in_scales: vec![],
+        op: op.op.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::ops::base::BaseOp;
+    use crate::fieldutils::i128_to_felt;
+
+    /// `x -> x + x`
+    #[derive(Debug)]
+    struct Double;
+
+    impl CircuitOp for Double {
+        fn eval_float(&self, x: f32) -> f32 {
+            2.0 * x
+        }
+
+        fn f(&self, inputs: &[Tensor<Fp>], _: &[u32]) -> Result<Tensor<Fp>, TensorError> {
+            inputs[0].clone() + inputs[0].clone()
+        }
+
+        fn layout(
+            &self,
+            config: &mut BaseConfig<Fp>,
+            region: &mut RegionCtx<Fp>,
+            values: &[ValTensor<Fp>],
+            _: &[u32],
+        ) -> Result<ValTensor<Fp>, Box<dyn Error>> {
+            let x = values[0].clone();
+            crate::circuit::layouts::pairwise(config, region, &[x.clone(), x], BaseOp::Add)
+        }
+    }
+
+    #[test]
+    fn test_custom_op_roundtrip() {
+        register_custom_op("ai.test", "TestDouble", |_| Box::new(Double)).unwrap();
+        let op = CustomOp::new(
+            "ai.test",
+            "TestDouble",
+            BTreeMap::from([("k".to_string(), 1.0)]),
+        )
+        .unwrap();
+        let op = op.rescale(vec![7], 7);
+        assert_eq!(op.out_scale(vec![7], 0), 7);
+
+        let input = Tensor::<Fp>::new(Some(&[i128_to_felt(3), i128_to_felt(-2)]), &[2]).unwrap();
+        let output = op.f(&[input]).unwrap().output;
+        assert_eq!(
+            output,
+            Tensor::new(Some(&[i128_to_felt(6), i128_to_felt(-4)]), &[2]).unwrap()
+        );
+
+        let op = op.as_any().downcast_ref::<CustomOp>().unwrap();
+        let serialized = serde_json::to_string(op).unwrap();
+        let deserialized: CustomOp = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.in_scales, vec![7]);
+        assert_eq!(deserialized.attributes, op.attributes);
+
+        assert!(CustomOp::new("ai.test", "TestUnregistered", BTreeMap::new()).is_err());
+        let unregistered = serialized.replace("TestDouble", "TestUnregistered");
+        assert!(serde_json::from_str::<CustomOp>(&unregistered).is_err());
+        // ops are registered per domain
+        assert!(CustomOp::new("ai.other", "TestDouble", BTreeMap::new()).is_err());
+        let other_domain = serialized.replace("ai.test", "ai.other");
+        assert!(serde_json::from_str::<CustomOp>(&other_domain).is_err());
+    }
+
+    #[test]
+    fn test_custom_op_refuses_builtins() {
+        // the builtin domains and the names of the ops ezkl parses can't be taken over
+        assert!(register_custom_op("", "TestDoubleBuiltin", |_| Box::new(Double)).is_err());
+        assert!(register_custom_op("ai.onnx", "TestDoubleBuiltin", |_| Box::new(Double)).is_err());
+        assert!(register_custom_op("ai.test", "Relu", |_| Box::new(Double)).is_err());
+        assert!(register_custom_op("ai.test", "Elu", |_| Box::new(Double)).is_err());
+        assert!(register_custom_op("ai.test", "RMSNormalization", |_| Box::new(Double)).is_err());
+        assert!(!custom_op_types().contains(&"TestDoubleBuiltin"));
+        assert!(!custom_op_types().contains(&"Relu"));
+    }
+}
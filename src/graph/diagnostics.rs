@@ -0,0 +1,198 @@
+use super::GraphCircuit;
+use halo2_proofs::dev::{FailureLocation, MockProver, VerifyFailure};
+use halo2curves::bn256::Fr as Fp;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::ops::Range;
+
+/// Where in the circuit a [CircuitFailure] occurred.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureContext {
+    /// The region the failure occurred in, if any.
+    pub region: Option<String>,
+    /// The row of the failure, relative to the start of the region if the failure occurred within one.
+    pub row: usize,
+    /// The index of the model node laid out at the failing row, if the failure occurred within the model's region.
+    pub node: Option<usize>,
+    /// The operation performed by that node.
+    pub op: Option<String>,
+}
+
+/// A structured reason for the circuit's constraints not being satisfied.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CircuitFailure {
+    /// A value isn't in the lookup table it is looked up in, usually because it falls out of the range covered by
+    /// `bits`.
+    Lookup {
+        /// where the lookup failed
+        context: FailureContext,
+        /// the failure as reported by the mock prover
+        description: String,
+    },
+    /// A gate's constraint isn't satisfied.
+    Constraint {
+        /// where the constraint failed (if known)
+        context: Option<FailureContext>,
+        /// the failure as reported by the mock prover
+        description: String,
+    },
+    /// Two cells constrained to be equal hold different values, e.g public inputs that don't match the witness.
+    Permutation {
+        /// where the copy constraint failed
+        context: FailureContext,
+        /// the failure as reported by the mock prover
+        description: String,
+    },
+    /// A cell used by an enabled gate was never assigned.
+    Unassigned {
+        /// the failure as reported by the mock prover
+        description: String,
+    },
+}
+
+impl CircuitFailure {
+    /// Whether the failure would likely go away by increasing the lookup table range, i.e `bits`.
+    pub fn suggests_more_bits(&self) -> bool {
+        matches!(self, CircuitFailure::Lookup { .. })
+    }
+
+    fn from_verify_failure(
+        failure: &VerifyFailure,
+        node_rows: &[(usize, Range<usize>, String)],
+    ) -> Self {
+        let description = failure.to_string();
+        let context = |location: &FailureLocation| match location {
+            FailureLocation::InRegion { region, offset } => {
+                let region = region.to_string();
+                let node = if region.contains("model") {
+                    node_rows
+                        .iter()
+                        .find(|(_, rows, _)| rows.contains(offset))
+                        .map(|(idx, _, op)| (*idx, op.clone()))
+                } else {
+                    None
+                };
+                FailureContext {
+                    region: Some(region),
+                    row: *offset,
+                    node: node.as_ref().map(|n| n.0),
+                    op: node.map(|n| n.1),
+                }
+            }
+            FailureLocation::OutsideRegion { row } => FailureContext {
+                region: None,
+                row: *row,
+                node: None,
+                op: None,
+            },
+        };
+
+        match failure {
+            VerifyFailure::Lookup { location, .. } => CircuitFailure::Lookup {
+                context: context(location),
+                description,
+            },
+            VerifyFailure::ConstraintNotSatisfied { location, .. } => CircuitFailure::Constraint {
+                context: Some(context(location)),
+                description,
+            },
+            VerifyFailure::ConstraintPoisoned { .. } => CircuitFailure::Constraint {
+                context: None,
+                description,
+            },
+            VerifyFailure::Permutation { location, .. } => CircuitFailure::Permutation {
+                context: context(location),
+                description,
+            },
+            VerifyFailure::CellNotAssigned { .. }
+            | VerifyFailure::InstanceCellNotAssigned { .. } => {
+                CircuitFailure::Unassigned { description }
+            }
+        }
+    }
+}
+
+/// Runs the [MockProver] over `circuit` with `public_inputs`, returning the reasons (if any) the circuit's constraints
+/// aren't satisfied. An empty vector means a proof generated from the same circuit and public inputs would verify.
+pub fn diagnose_failure(
+    circuit: &GraphCircuit,
+    public_inputs: Vec<Vec<Fp>>,
+) -> Result<Vec<CircuitFailure>, Box<dyn Error>> {
+    let prover = MockProver::run(circuit.settings.run_args.logrows, circuit, public_inputs)
+        .map_err(Box::<dyn Error>::from)?;
+    let failures = match prover.verify_par() {
+        Ok(()) => return Ok(vec![]),
+        Err(failures) => failures,
+    };
+
+    let node_rows = circuit
+        .model
+        .node_rows(&circuit.settings.run_args)?
+        .into_iter()
+        .map(|(idx, rows)| {
+            let op = circuit.model.graph.node(idx).map(|n| n.as_str());
+            (idx, rows, op.unwrap_or_default())
+        })
+        .collect::<Vec<_>>();
+
+    Ok(failures
+        .iter()
+        .map(|f| CircuitFailure::from_verify_failure(f, &node_rows))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fieldutils::i128_to_felt;
+    use crate::graph::tests::{relu_circuit, relu_run_args};
+    use crate::tensor::Tensor;
+
+    #[test]
+    fn test_diagnose_failure() {
+        let run_args = relu_run_args();
+        let mut circuit = relu_circuit(&run_args);
+        let mut diagnose = |input: [i128; 3], tamper: bool| {
+            let input = Tensor::new(Some(&input.map(i128_to_felt::<Fp>)), &[1, 3]).unwrap();
+            let witness = circuit.forward(&[input]).unwrap();
+            circuit.load_graph_witness(&witness).unwrap();
+            let mut public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+            if tamper {
+                public_inputs[0][1] += Fp::from(1);
+            }
+            diagnose_failure(&circuit, public_inputs).unwrap()
+        };
+
+        assert!(diagnose([-21, 19, 7], false).is_empty());
+
+        // outputs that don't match the witness fail the copy constraints, which more bits won't fix
+        let failures = diagnose([-21, 19, 7], true);
+        assert!(!failures.is_empty());
+        assert!(failures
+            .iter()
+            .any(|f| matches!(f, CircuitFailure::Permutation { .. })));
+        assert!(!failures.iter().any(|f| f.suggests_more_bits()));
+
+        // an input out of the range of the 6 bit lookup table fails its lookup, within the relu node
+        let failures = diagnose([-21, 40, 7], false);
+        let lookup = failures
+            .iter()
+            .find(|f| f.suggests_more_bits())
+            .unwrap()
+            .clone();
+        match &lookup {
+            CircuitFailure::Lookup { context, .. } => {
+                assert!(context.region.as_ref().unwrap().contains("model"));
+                assert!(context.node.is_some());
+            }
+            _ => unreachable!(),
+        }
+
+        // the failures are structured data a service can log
+        let json = serde_json::to_string(&lookup).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CircuitFailure>(&json).unwrap(),
+            lookup
+        );
+    }
+}
@@ -0,0 +1,491 @@
+use super::model::NodeType;
+use super::node::{Outlet, SupportedOp};
+use super::scale_to_multiplier;
+use crate::circuit::hybrid::HybridOp;
+use crate::circuit::poly::PolyOp;
+use crate::circuit::Op;
+use crate::fieldutils::{felt_to_i128, i128_to_felt};
+use crate::tensor::Tensor;
+use halo2curves::bn256::Fr as Fp;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Bounds on the (float) values a node outputs, for every input to the model within the input magnitude the bounds
+/// were computed for.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorBound {
+    /// An upper bound on the absolute value of any element of the node's output, when computed in floating point
+    /// with the float weights of the .onnx file.
+    pub magnitude: f64,
+    /// An upper bound on the absolute difference between any (dequantized) element of the node's output in the
+    /// circuit and the same element computed in floating point. Infinite where the error can't be bounded, e.g for
+    /// discontinuous operations (comparisons, argmax) or lookups whose inputs may exceed the lookup table.
+    pub error: f64,
+}
+
+impl ErrorBound {
+    const UNBOUNDED: Self = ErrorBound {
+        magnitude: f64::INFINITY,
+        error: f64::INFINITY,
+    };
+
+    /// The bound on a value quantized (by rounding) at a fixed point multiplier of `mult`, whose true magnitude is at
+    /// most `magnitude`.
+    fn quantized(magnitude: f64, mult: f64) -> Self {
+        ErrorBound {
+            magnitude,
+            error: 0.5 / mult,
+        }
+    }
+
+    /// The bound on the sum of `terms` products of the `factors`.
+    fn sum_of_products(factors: &[ErrorBound], terms: f64) -> Self {
+        let magnitude: f64 = factors.iter().map(|f| f.magnitude).product();
+        let with_error: f64 = factors.iter().map(|f| f.magnitude + f.error).product();
+        ErrorBound {
+            magnitude: terms * magnitude,
+            error: terms * (with_error - magnitude),
+        }
+    }
+
+    /// The bound on weights of the .onnx file quantized to `values`, at the scale of `values` (or `default_scale` if it
+    /// has none).
+    fn weights(values: &Tensor<Fp>, default_scale: u32) -> Self {
+        let mult = scale_to_multiplier(values.scale().unwrap_or(default_scale));
+        let max = values
+            .iter()
+            .map(|x| felt_to_i128(*x).unsigned_abs() as f64)
+            .fold(0.0, f64::max);
+        // the float weights are within half a step of their quantized values
+        Self::quantized(max / mult + 0.5 / mult, mult)
+    }
+}
+
+/// The bounds on the outputs of a model, see [super::Model::quantization_error_bounds].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ErrorBoundReport {
+    /// The bounds on the (first) output of every node, by node index.
+    pub nodes: BTreeMap<usize, ErrorBound>,
+    /// The bounds on the model's outputs.
+    pub outputs: Vec<ErrorBound>,
+}
+
+/// Propagates bounds on the quantization error layer by layer through `nodes`, in node order, starting from inputs of
+/// magnitude at most `input_magnitude`, each quantized at its scale. Errors introduced by a node are those of
+/// quantizing its weights (each within half a step of its float value) and of rounding its output (requantizations,
+/// rescalings and lookups each round to the nearest step), to which the errors of its inputs are added as amplified by
+/// the node. Lookups are amplified by their largest slope over their domain, estimated by evaluating them over every
+/// value their input can take, which has to fit within the lookup table of `2^bits` values.
+pub fn quantization_error_bounds(
+    nodes: &BTreeMap<usize, NodeType>,
+    outputs: &[Outlet],
+    input_magnitude: f64,
+    bits: usize,
+) -> ErrorBoundReport {
+    let mut report = ErrorBoundReport::default();
+    for (idx, node) in nodes {
+        let n = match node {
+            NodeType::Node(n) => n,
+            NodeType::SubGraph { .. } => {
+                warn!("can't bound the error of the subgraph at node {}", idx);
+                report.nodes.insert(*idx, ErrorBound::UNBOUNDED);
+                continue;
+            }
+        };
+        let inputs = n
+            .inputs
+            .iter()
+            .map(|(i, _)| {
+                report
+                    .nodes
+                    .get(i)
+                    .copied()
+                    .unwrap_or(ErrorBound::UNBOUNDED)
+            })
+            .collect::<Vec<_>>();
+        let in_mults = n
+            .inputs
+            .iter()
+            .map(|(i, o)| scale_to_multiplier(nodes[i].out_scales()[*o]))
+            .collect::<Vec<_>>();
+        let in_dims = n
+            .inputs
+            .iter()
+            .map(|(i, o)| nodes[i].out_dims()[*o].clone())
+            .collect::<Vec<_>>();
+        let op = OpContext {
+            inputs,
+            in_mults,
+            in_dims,
+            out_mult: scale_to_multiplier(n.out_scale),
+            input_magnitude,
+            bits,
+        };
+        let bound = op.bound(&n.opkind, n.out_scale);
+        if !bound.error.is_finite() {
+            warn!("can't bound the error of node {} ({})", idx, node.as_str());
+        }
+        report.nodes.insert(*idx, bound);
+    }
+    report.outputs = outputs
+        .iter()
+        .map(|(idx, _)| {
+            report
+                .nodes
+                .get(idx)
+                .copied()
+                .unwrap_or(ErrorBound::UNBOUNDED)
+        })
+        .collect();
+    report
+}
+
+/// What bounding a node's output needs to know about its inputs.
+struct OpContext {
+    inputs: Vec<ErrorBound>,
+    in_mults: Vec<f64>,
+    in_dims: Vec<Vec<usize>>,
+    out_mult: f64,
+    input_magnitude: f64,
+    bits: usize,
+}
+
+impl OpContext {
+    fn input(&self, i: usize) -> ErrorBound {
+        self.inputs.get(i).copied().unwrap_or(ErrorBound::UNBOUNDED)
+    }
+
+    /// The bound on an op that passes (or rearranges) its inputs elementwise, without adding any error of its own.
+    fn pass_through(&self) -> ErrorBound {
+        self.inputs.iter().fold(
+            ErrorBound {
+                magnitude: 0.0,
+                error: 0.0,
+            },
+            |acc, b| ErrorBound {
+                magnitude: acc.magnitude.max(b.magnitude),
+                error: acc.error.max(b.error),
+            },
+        )
+    }
+
+    /// The bound on an op that sums `terms` elements of its (first) input.
+    fn sum(&self, terms: f64) -> ErrorBound {
+        let x = self.input(0);
+        ErrorBound {
+            magnitude: terms * x.magnitude,
+            error: terms * x.error,
+        }
+    }
+
+    fn bound(&self, op: &SupportedOp, out_scale: u32) -> ErrorBound {
+        match op {
+            SupportedOp::Input(_) => {
+                ErrorBound::quantized(self.input_magnitude, scale_to_multiplier(out_scale))
+            }
+            SupportedOp::Constant(c) => ErrorBound::weights(&c.quantized_values, out_scale),
+            SupportedOp::Linear(op) => self.linear(op),
+            SupportedOp::Nonlinear(op) => self.lookup(op),
+            SupportedOp::Hybrid(op) => match op {
+                // integer comparisons of values at the same scale, which don't amplify errors
+                HybridOp::Abs
+                | HybridOp::ReduceMax { .. }
+                | HybridOp::ReduceMin { .. }
+                | HybridOp::MaxPool2d { .. }
+                | HybridOp::RangeCheck(_) => self.pass_through(),
+                // discontinuous, or computed through intermediate lookups whose errors aren't modelled
+                _ => ErrorBound::UNBOUNDED,
+            },
+            SupportedOp::Rescaled(op) => {
+                // the inputs are divided down (rounding) to the scale the inner op expects
+                let mut rescaled = OpContext {
+                    inputs: self.inputs.clone(),
+                    in_mults: self.in_mults.clone(),
+                    in_dims: self.in_dims.clone(),
+                    ..*self
+                };
+                for (i, mult) in &op.scale {
+                    if *mult > 1 && *i < rescaled.inputs.len() {
+                        rescaled.in_mults[*i] /= *mult as f64;
+                        rescaled.inputs[*i].error += 0.5 / rescaled.in_mults[*i];
+                    }
+                }
+                rescaled.bound(&op.inner, out_scale)
+            }
+            SupportedOp::Requantized(op) => {
+                let mut bound = self.bound(&op.inner, out_scale);
+                bound.error += 0.5 / self.out_mult;
+                bound
+            }
+            SupportedOp::Unknown(_) | SupportedOp::Custom(_) => ErrorBound::UNBOUNDED,
+        }
+    }
+
+    fn linear(&self, op: &PolyOp<Fp>) -> ErrorBound {
+        match op {
+            PolyOp::Add | PolyOp::Sub => {
+                let (a, b) = (self.input(0), self.input(1));
+                ErrorBound {
+                    magnitude: a.magnitude + b.magnitude,
+                    error: a.error + b.error,
+                }
+            }
+            PolyOp::Mult => ErrorBound::sum_of_products(&[self.input(0), self.input(1)], 1.0),
+            PolyOp::Pow(n) => ErrorBound::sum_of_products(&vec![self.input(0); *n as usize], 1.0),
+            PolyOp::Einsum { equation } => {
+                // each output element sums the products of the inputs over the indices that are contracted away
+                let (inputs, output) = equation.split_once("->").unwrap_or((equation, ""));
+                let mut sizes = BTreeMap::new();
+                for (indices, dims) in inputs.split(',').zip(&self.in_dims) {
+                    for (c, d) in indices.chars().zip(dims) {
+                        sizes.insert(c, *d);
+                    }
+                }
+                let terms: usize = sizes
+                    .iter()
+                    .filter(|(c, _)| !output.contains(**c))
+                    .map(|(_, d)| d)
+                    .product();
+                ErrorBound::sum_of_products(&self.inputs, terms as f64)
+            }
+            PolyOp::Conv { kernel, bias, .. } => self.conv(kernel, bias, 0),
+            PolyOp::DeConv { kernel, bias, .. } => self.conv(kernel, bias, 1),
+            PolyOp::Sum { axes } => {
+                let dims = self.in_dims.first().cloned().unwrap_or_default();
+                self.sum(
+                    axes.iter()
+                        .map(|a| dims.get(*a).copied().unwrap_or(1))
+                        .product::<usize>() as f64,
+                )
+            }
+            PolyOp::SumPool { kernel_shape, .. } => {
+                self.sum((kernel_shape.0 * kernel_shape.1) as f64)
+            }
+            PolyOp::GlobalSumPool => {
+                let dims = self.in_dims.first().cloned().unwrap_or_default();
+                self.sum(dims.iter().rev().take(2).product::<usize>() as f64)
+            }
+            PolyOp::Iff => {
+                // a mask that may be wrong switches between the branches
+                if self.input(0).error > 0.0 {
+                    ErrorBound::UNBOUNDED
+                } else {
+                    OpContext {
+                        inputs: self.inputs[1..].to_vec(),
+                        in_mults: vec![],
+                        in_dims: vec![],
+                        ..*self
+                    }
+                    .pass_through()
+                }
+            }
+            PolyOp::Pack(..) => ErrorBound::UNBOUNDED,
+            PolyOp::Neg
+            | PolyOp::Identity
+            | PolyOp::Reshape(_)
+            | PolyOp::MoveAxis { .. }
+            | PolyOp::Gather { .. }
+            | PolyOp::Flatten(_)
+            | PolyOp::Pad { .. }
+            | PolyOp::Concat { .. }
+            | PolyOp::Slice { .. }
+            | PolyOp::Downsample { .. }
+            | PolyOp::Resize { .. } => self.pass_through(),
+        }
+    }
+
+    /// A (de)convolution sums at most the elements of the kernel for a single output channel (along `out_axis` of
+    /// the kernel), each multiplied by an element of the input.
+    fn conv(&self, kernel: &Tensor<Fp>, bias: &Option<Tensor<Fp>>, out_axis: usize) -> ErrorBound {
+        let x = self.input(0);
+        let k = ErrorBound::weights(kernel, 0);
+        let k_mult = scale_to_multiplier(kernel.scale().unwrap_or(0));
+        let dims = kernel.dims();
+        let channels = dims.get(out_axis).copied().unwrap_or(1).max(1);
+        let inner: usize = dims.iter().skip(out_axis + 1).product();
+        let mut l1 = vec![0.0; channels];
+        let mut counts = vec![0usize; channels];
+        for (i, v) in kernel.iter().enumerate() {
+            let c = (i / inner) % channels;
+            // the float weight is within half a step of the quantized one
+            l1[c] += (felt_to_i128(*v).unsigned_abs() as f64 + 0.5) / k_mult;
+            counts[c] += 1;
+        }
+        let l1 = l1.into_iter().fold(0.0, f64::max);
+        let terms = counts.into_iter().max().unwrap_or(0) as f64;
+        let mut bound = ErrorBound {
+            magnitude: l1 * x.magnitude,
+            error: l1 * x.error + terms * (x.magnitude + x.error) * k.error,
+        };
+        if let Some(bias) = bias {
+            let b = ErrorBound::weights(bias, 0);
+            bound.magnitude += b.magnitude;
+            bound.error += b.error;
+        }
+        bound
+    }
+
+    /// A lookup rounds its output, and amplifies the error of its input by its slope.
+    fn lookup(&self, op: &crate::circuit::lookup::LookupOp) -> ErrorBound {
+        let x = self.input(0);
+        let in_mult = self.in_mults.first().copied().unwrap_or(1.0);
+        let limit = 2i128.pow(self.bits as u32 - 1);
+        let reach = (x.magnitude + x.error) * in_mult;
+        if !reach.is_finite() || reach.ceil() >= limit as f64 {
+            return ErrorBound::UNBOUNDED;
+        }
+        let reach = reach.ceil() as i128;
+        let domain = (-reach..=reach).map(i128_to_felt::<Fp>).collect::<Vec<_>>();
+        let domain = match Tensor::new(Some(&domain), &[domain.len()]) {
+            Ok(domain) => domain,
+            Err(_) => return ErrorBound::UNBOUNDED,
+        };
+        let values = match Op::<Fp>::f(op, &[domain]) {
+            Ok(res) => res
+                .output
+                .iter()
+                .map(|x| felt_to_i128(*x))
+                .collect::<Vec<_>>(),
+            Err(_) => return ErrorBound::UNBOUNDED,
+        };
+        let max = values.iter().map(|v| v.unsigned_abs()).max().unwrap_or(0) as f64;
+        // the slope between neighbouring steps, allowing for the rounding of each
+        let slope = values
+            .windows(2)
+            .map(|w| (w[1] - w[0]).unsigned_abs() + 1)
+            .max()
+            .unwrap_or(1) as f64;
+        ErrorBound {
+            magnitude: max / self.out_mult + 0.5 / self.out_mult,
+            error: slope * in_mult / self.out_mult * x.error + 0.5 / self.out_mult,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{quantize_float, Model};
+    use super::*;
+    use crate::graph::tests::test_run_args;
+    use crate::RunArgs;
+
+    /// The (dequantized) outputs of the circuit on `input`.
+    fn circuit_outputs(model: &Model, input: &[f64], scale: u32) -> Vec<f64> {
+        let quantized = input
+            .iter()
+            .map(|x| i128_to_felt(quantize_float(x, 0.0, scale).unwrap()))
+            .collect::<Vec<Fp>>();
+        let input_shape = model.graph.input_shapes()[0].clone();
+        let quantized = Tensor::new(Some(&quantized), &input_shape).unwrap();
+        let res = model.forward(&[quantized]).unwrap();
+        let mult = scale_to_multiplier(model.graph.get_output_scales()[0]);
+        res.outputs[0]
+            .iter()
+            .map(|x| felt_to_i128(*x) as f64 / mult)
+            .collect()
+    }
+
+    #[test]
+    fn test_error_bounds_hold() {
+        // the float outputs of the examples (computed from their float weights) on their example inputs
+        let conv = (-0.3930284644528181, -0.36590051651000977);
+        let examples: [(&str, Vec<f64>, Vec<f64>); 4] = [
+            (
+                "1l_mlp",
+                vec![1.5417295, 0.5346153, 1.2172532],
+                vec![0.2736675893350005, 0.6484844991557359, 0.0, 0.0],
+            ),
+            (
+                "1l_conv",
+                vec![
+                    0.011350071988999844,
+                    0.03404385969042778,
+                    0.04626564309000969,
+                ],
+                // the 1x1 input is padded by 1, such that only the center of the output sees the input
+                [[conv.1; 4].to_vec(), vec![conv.0], [conv.1; 4].to_vec()].concat(),
+            ),
+            (
+                "1l_sigmoid",
+                vec![
+                    -0.008400974795222282,
+                    0.18489880859851837,
+                    -0.7444106340408325,
+                ],
+                vec![0.49789976865340657, 0.5460934583043727, 0.32204041344714285],
+            ),
+            (
+                "1l_relu",
+                vec![-0.40077725052833557, 2.493845224380493, 0.5796360969543457],
+                vec![0.0, 2.493845224380493, 0.5796360969543457],
+            ),
+        ];
+
+        for (example, input, expected) in examples {
+            let magnitude = input.iter().map(|x| x.abs()).fold(0.0, f64::max);
+            let bound_at = |scale| {
+                let run_args = RunArgs {
+                    scale,
+                    ..test_run_args()
+                };
+                let path = format!("examples/onnx/{}/network.onnx", example).into();
+                let model = Model::from_run_args(&run_args, &path).unwrap();
+                let bound = model
+                    .quantization_error_bounds(magnitude, run_args.bits)
+                    .outputs[0];
+                assert!(bound.error.is_finite(), "{} is unbounded", example);
+                let circuit = circuit_outputs(&model, &input, scale);
+                for (c, e) in circuit.iter().zip(&expected) {
+                    assert!(
+                        e.abs() <= bound.magnitude,
+                        "{}: {} exceeds {:?}",
+                        example,
+                        e,
+                        bound
+                    );
+                    assert!(
+                        (c - e).abs() <= bound.error,
+                        "{}: {} vs {} exceeds {:?}",
+                        example,
+                        c,
+                        e,
+                        bound
+                    );
+                }
+                bound.error
+            };
+            // quantizing more finely tightens the bound
+            assert!(bound_at(8) < bound_at(5), "{}", example);
+        }
+
+        // the error of a comparison can't be bounded
+        let mut nodes = BTreeMap::new();
+        nodes.insert(
+            0,
+            NodeType::Node(super::super::Node {
+                opkind: SupportedOp::Input(crate::circuit::Input {
+                    scale: 7,
+                    normalization: None,
+                }),
+                out_scale: 7,
+                inputs: vec![],
+                out_dims: vec![1],
+                idx: 0,
+            }),
+        );
+        nodes.insert(
+            1,
+            NodeType::Node(super::super::Node {
+                opkind: SupportedOp::Hybrid(HybridOp::Greater),
+                out_scale: 0,
+                inputs: vec![(0, 0), (0, 0)],
+                out_dims: vec![1],
+                idx: 1,
+            }),
+        );
+        let report = quantization_error_bounds(&nodes, &[(1, 0)], 1.0, 16);
+        assert_eq!(report.nodes[&0].error, 0.5 / 128.0);
+        assert!(report.outputs[0].error.is_infinite());
+    }
+}
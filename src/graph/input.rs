@@ -10,9 +10,11 @@ use pyo3::prelude::*;
 use pyo3::types::PyDict;
 #[cfg(feature = "python-bindings")]
 use pyo3::ToPyObject;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{MapAccess, SeqAccess, Visitor};
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::io::Read;
+use std::cmp::Ordering;
 #[cfg(not(target_arch = "wasm32"))]
 use std::thread;
 #[cfg(not(target_arch = "wasm32"))]
@@ -26,14 +28,45 @@ type Call = String;
 type RPCUrl = String;
 
 ///
-#[derive(Clone, Debug, PartialOrd, PartialEq)]
+#[derive(Clone, Debug)]
 pub enum FileSourceInner {
     /// Inner elements of inputs coming from a file
     Float(f64),
+    /// Integer inputs coming from a file, kept exact (without an f64 round trip) for integer-only models
+    Int(i128),
     /// Inner elements of inputs coming from a witness
     Field(Fp),
 }
 
+// JSON integer literals are deserialized as [FileSourceInner::Int] (see below), so they're equal to the integral
+// floats they'd parse to in a float file, such that such files compare as they did before
+impl PartialEq for FileSourceInner {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for FileSourceInner {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        // integers are compared exactly to integral floats, which f64s of up to 2^127 in magnitude convert to
+        let cmp_int = |i: &i128, f: &f64| match f.fract() == 0.0 && f.abs() < 2f64.powi(127) {
+            true => Some(i.cmp(&(*f as i128))),
+            false => (*i as f64).partial_cmp(f),
+        };
+        match (self, other) {
+            (FileSourceInner::Float(a), FileSourceInner::Float(b)) => a.partial_cmp(b),
+            (FileSourceInner::Int(a), FileSourceInner::Int(b)) => a.partial_cmp(b),
+            (FileSourceInner::Field(a), FileSourceInner::Field(b)) => a.partial_cmp(b),
+            (FileSourceInner::Int(a), FileSourceInner::Float(b)) => cmp_int(a, b),
+            (FileSourceInner::Float(a), FileSourceInner::Int(b)) => {
+                cmp_int(b, a).map(Ordering::reverse)
+            }
+            (FileSourceInner::Field(_), _) => Some(Ordering::Greater),
+            (_, FileSourceInner::Field(_)) => Some(Ordering::Less),
+        }
+    }
+}
+
 impl Serialize for FileSourceInner {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -42,6 +75,7 @@ impl Serialize for FileSourceInner {
         match self {
             FileSourceInner::Field(data) => data.serialize(serializer),
             FileSourceInner::Float(data) => data.serialize(serializer),
+            FileSourceInner::Int(data) => data.serialize(serializer),
         }
     }
 }
@@ -55,6 +89,13 @@ impl<'de> Deserialize<'de> for FileSourceInner {
     {
         let this_json: Box<serde_json::value::RawValue> = Deserialize::deserialize(deserializer)?;
 
+        // integer literals (no decimal point or exponent) are kept exact
+        let int_try: Result<i128, _> = serde_json::from_str(this_json.get());
+
+        if let Ok(t) = int_try {
+            return Ok(FileSourceInner::Int(t));
+        }
+
         let first_try: Result<f64, _> = serde_json::from_str(this_json.get());
 
         if let Ok(t) = first_try {
@@ -83,18 +124,76 @@ impl FileSourceInner {
     pub fn new_field(f: Fp) -> Self {
         FileSourceInner::Field(f)
     }
+    /// Create a new FileSourceInner
+    pub fn new_int(i: i128) -> Self {
+        FileSourceInner::Int(i)
+    }
 
-    /// Convert to a field element
-    pub fn to_field(&self, scale: u32) -> Fp {
+    /// Convert to a field element, quantizing at `scale`.
+    /// Errors on NaN and infinite floats and on values too large to be quantized at `scale`.
+    pub fn to_field(&self, scale: u32) -> Result<Fp, GraphError> {
+        let quantize = |x: f64| {
+            quantize_float(&x, 0.0, scale)
+                .map(i128_to_felt)
+                .map_err(|e| GraphError::DataQuantization(format!("{}: {}", x, e)))
+        };
         match self {
-            FileSourceInner::Float(f) => i128_to_felt(quantize_float(f, 0.0, scale).unwrap()),
-            FileSourceInner::Field(f) => *f,
+            FileSourceInner::Float(f) => quantize(*f),
+            FileSourceInner::Int(i) => quantize(*i as f64),
+            FileSourceInner::Field(f) => Ok(*f),
+        }
+    }
+    /// Convert to a field element without quantizing, as used by integer-only models.
+    /// Floats are rejected, as they would be rounded in a platform dependent way.
+    pub fn to_integer_field(&self) -> Result<Fp, GraphError> {
+        match self {
+            FileSourceInner::Int(i) => Ok(i128_to_felt(*i)),
+            FileSourceInner::Field(f) => Ok(*f),
+            FileSourceInner::Float(f) => Err(GraphError::IntegerOnly(format!(
+                "input {} is not an integer",
+                f
+            ))),
+        }
+    }
+    /// Quantizes the element at `scale` into the integer it is loaded as (see [FileSourceInner::to_field]), such that
+    /// the quantized inputs a proof uses can be frozen. Field elements are already quantized and are returned as is.
+    /// Errors if the quantized element is more than half a quantization step away from the original.
+    pub fn quantize(&self, scale: u32) -> Result<Self, GraphError> {
+        let original = match self {
+            FileSourceInner::Field(_) => return Ok(self.clone()),
+            FileSourceInner::Float(f) => *f,
+            FileSourceInner::Int(i) => *i as f64,
+        };
+        let quantized = quantize_float(&original, 0.0, scale)
+            .map_err(|e| GraphError::DataQuantization(format!("{}: {}", original, e)))?;
+
+        let multiplier = super::scale_to_multiplier(scale);
+        let error = (quantized as f64 / multiplier - original).abs();
+        if error > 0.5 / multiplier {
+            return Err(GraphError::DataQuantization(format!(
+                "{} quantized at scale {} is off by {}",
+                original, scale, error
+            )));
+        }
+        Ok(FileSourceInner::Int(quantized))
+    }
+    /// The inverse of [FileSourceInner::quantize]: converts an integer or field element quantized at `scale` back to a
+    /// float. Floats aren't quantized and are returned as is.
+    pub fn dequantize(&self, scale: u32) -> Self {
+        let multiplier = super::scale_to_multiplier(scale);
+        match self {
+            FileSourceInner::Int(i) => FileSourceInner::Float(*i as f64 / multiplier),
+            FileSourceInner::Field(f) => {
+                FileSourceInner::Float(crate::fieldutils::felt_to_i128(*f) as f64 / multiplier)
+            }
+            FileSourceInner::Float(_) => self.clone(),
         }
     }
     /// Convert to a float
     pub fn to_float(&self) -> f64 {
         match self {
             FileSourceInner::Float(f) => *f,
+            FileSourceInner::Int(i) => *i as f64,
             FileSourceInner::Field(f) => crate::fieldutils::felt_to_i128(*f) as f64,
         }
     }
@@ -359,21 +458,46 @@ impl<'de> Deserialize<'de> for DataSource {
     where
         D: Deserializer<'de>,
     {
-        let this_json: Box<serde_json::value::RawValue> = Deserialize::deserialize(deserializer)?;
+        deserializer.deserialize_any(DataSourceVisitor)
+    }
+}
 
-        let first_try: Result<FileSource, _> = serde_json::from_str(this_json.get());
+/// Tells the sources apart by their shape: file data is an array, which is parsed element by element (such that large
+/// inputs read with [GraphData::from_path] are never held as raw text), whereas the other sources are objects.
+struct DataSourceVisitor;
 
-        if let Ok(t) = first_try {
-            return Ok(DataSource::File(t));
+impl<'de> Visitor<'de> for DataSourceVisitor {
+    type Value = DataSource;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter
+            .write_str("file data (an array of arrays of values), an on-chain or a postgres source")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut data: FileSource = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(inner) = seq.next_element()? {
+            data.push(inner);
         }
-        let second_try: Result<OnChainSource, _> = serde_json::from_str(this_json.get());
-        if let Ok(t) = second_try {
+        Ok(DataSource::File(data))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        // the sources held in objects are small, so they are buffered to try each in turn
+        let value = serde_json::Value::deserialize(MapAccessDeserializer::new(map))?;
+
+        if let Ok(t) = OnChainSource::deserialize(&value) {
             return Ok(DataSource::OnChain(t));
         }
         #[cfg(not(target_arch = "wasm32"))]
         {
-            let third_try: Result<PostgresSource, _> = serde_json::from_str(this_json.get());
-            if let Ok(t) = third_try {
+            if let Ok(t) = PostgresSource::deserialize(&value) {
                 return Ok(DataSource::DB(t));
             }
         }
@@ -390,6 +514,10 @@ pub struct GraphData {
     pub input_data: DataSource,
     /// Outputs of the model / computational graph (can be empty vectors if outputs are coming from on-chain).
     pub output_data: Option<DataSource>,
+    /// The scale the file data was frozen at by [GraphData::quantize], if it was. Its integers are then loaded as is
+    /// rather than quantized, see [super::GraphCircuit::load_graph_input].
+    #[serde(default)]
+    pub quantized_scale: Option<u32>,
 }
 
 impl GraphData {
@@ -398,15 +526,15 @@ impl GraphData {
         GraphData {
             input_data,
             output_data: None,
+            quantized_scale: None,
         }
     }
 
     /// Load the model input from a file
     pub fn from_path(path: std::path::PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut file = std::fs::File::open(path)?;
-        let mut data = String::new();
-        file.read_to_string(&mut data)?;
-        serde_json::from_str(&data).map_err(|e| e.into())
+        // parsed as it is read, rather than read into memory as a whole first
+        let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+        serde_json::from_reader(reader).map_err(|e| e.into())
     }
 
     /// Save the model input to a file
@@ -414,6 +542,56 @@ impl GraphData {
         serde_json::to_writer(std::fs::File::create(path)?, &self).map_err(|e| e.into())
     }
 
+    /// Quantizes the file data (inputs and outputs) at `scale` into integers, see [FileSourceInner::quantize]. The
+    /// scale is recorded, such that the integers bypass quantization when loaded.
+    pub fn quantize(&self, scale: u32) -> Result<Self, GraphError> {
+        if let Some(quantized_scale) = self.quantized_scale {
+            return Err(GraphError::DataQuantization(format!(
+                "the data is already quantized at scale {}",
+                quantized_scale
+            )));
+        }
+        let mut data = self.map_file_data(|x| x.quantize(scale))?;
+        data.quantized_scale = Some(scale);
+        Ok(data)
+    }
+
+    /// Dequantizes file data quantized at `scale` by [GraphData::quantize], see [FileSourceInner::dequantize].
+    pub fn dequantize(&self, scale: u32) -> Result<Self, GraphError> {
+        if self.quantized_scale != Some(scale) {
+            return Err(GraphError::DataQuantization(match self.quantized_scale {
+                Some(quantized_scale) => format!(
+                    "the data is quantized at scale {}, not {}",
+                    quantized_scale, scale
+                ),
+                None => "the data isn't quantized".to_string(),
+            }));
+        }
+        self.map_file_data(|x| Ok(x.dequantize(scale)))
+    }
+
+    /// Maps every element of the file data, erroring on data that isn't loaded from a file.
+    fn map_file_data(
+        &self,
+        f: impl Fn(&FileSourceInner) -> Result<FileSourceInner, GraphError>,
+    ) -> Result<Self, GraphError> {
+        let map = |source: &DataSource| match source {
+            DataSource::File(data) => Ok(DataSource::File(
+                data.iter()
+                    .map(|d| d.iter().map(&f).collect::<Result<Vec<_>, _>>())
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            _ => Err(GraphError::DataQuantization(
+                "only data from files can be (de)quantized".to_string(),
+            )),
+        };
+        Ok(GraphData {
+            input_data: map(&self.input_data)?,
+            output_data: self.output_data.as_ref().map(map).transpose()?,
+            quantized_scale: None,
+        })
+    }
+
     ///
     pub fn split_into_batches(
         &self,
@@ -426,16 +604,16 @@ impl GraphData {
         let iterable = match self {
             GraphData {
                 input_data: DataSource::File(data),
-                output_data: _,
+                ..
             } => data.clone(),
             GraphData {
                 input_data: DataSource::OnChain(_),
-                output_data: _,
+                ..
             } => todo!("on-chain data batching not implemented yet"),
             #[cfg(not(target_arch = "wasm32"))]
             GraphData {
                 input_data: DataSource::DB(data),
-                output_data: _,
+                ..
             } => data.fetch_and_format_as_file()?,
         };
 
@@ -521,6 +699,7 @@ impl ToPyObject for FileSourceInner {
         match self {
             FileSourceInner::Field(data) => field_to_vecu64_montgomery(data).to_object(py),
             FileSourceInner::Float(data) => data.to_object(py),
+            FileSourceInner::Int(data) => data.to_object(py),
         }
     }
 }
@@ -533,6 +712,9 @@ impl Serialize for GraphData {
         let mut state = serializer.serialize_struct("GraphData", 4)?;
         state.serialize_field("input_data", &self.input_data)?;
         state.serialize_field("output_data", &self.output_data)?;
+        if let Some(scale) = self.quantized_scale {
+            state.serialize_field("quantized_scale", &scale)?;
+        }
         state.end()
     }
 }
@@ -580,6 +762,105 @@ mod tests {
         assert_eq!(graph_input3, file);
     }
 
+    #[test]
+    fn test_graph_input_from_path() {
+        let mut data = GraphData::new(DataSource::from(vec![vec![0.5, -1.25], vec![3.0]]));
+        data.output_data = Some(DataSource::OnChain(OnChainSource::default()));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("input.json");
+        data.save(path.clone()).unwrap();
+        // file data is parsed as it is read, while objects (on-chain sources) are buffered
+        assert_eq!(GraphData::from_path(path).unwrap(), data);
+
+        assert!(serde_json::from_str::<DataSource>(r#"{"not":"a source"}"#).is_err());
+        assert!(serde_json::from_str::<DataSource>("0.5").is_err());
+    }
+
+    #[test]
+    fn test_integer_inputs_are_exact() {
+        // 2^60 + 1 can't be represented as an f64
+        const JSON: &str = r#"[[1152921504606846977, -3, 0.5]]"#;
+        let source = serde_json::from_str::<DataSource>(JSON).unwrap();
+        let data = match source {
+            DataSource::File(data) => data,
+            _ => panic!("expected file data"),
+        };
+        assert_eq!(data[0][0], FileSourceInner::Int(1152921504606846977));
+        assert_eq!(data[0][1], FileSourceInner::Int(-3));
+        assert_eq!(data[0][2], FileSourceInner::Float(0.5));
+
+        assert_eq!(
+            data[0][0].to_integer_field().unwrap(),
+            i128_to_felt(1152921504606846977)
+        );
+        assert_eq!(data[0][1].to_integer_field().unwrap(), i128_to_felt(-3));
+        assert!(data[0][2].to_integer_field().is_err());
+        // outside integer-only mode integers are quantized like floats
+        assert_eq!(data[0][1].to_field(2).unwrap(), i128_to_felt(-12));
+
+        assert_eq!(serde_json::to_string(&data).unwrap(), JSON.replace(' ', ""));
+    }
+
+    #[test]
+    fn test_float_inputs_round_trip() {
+        // integer literals in a float file are the floats they denote
+        let source = DataSource::from(vec![vec![1.0, 0.5, -2.0, 3.0]]);
+        const JSON: &str = r#"[[1,0.5,-2,3.0]]"#;
+        assert_eq!(serde_json::from_str::<DataSource>(JSON).unwrap(), source);
+
+        let serialized = serde_json::to_string(&source).unwrap();
+        assert_eq!(serialized, r#"[[1.0,0.5,-2.0,3.0]]"#);
+        assert_eq!(
+            serde_json::from_str::<DataSource>(&serialized).unwrap(),
+            source
+        );
+
+        // but compare exactly, where the float is rounded
+        let int = FileSourceInner::Int(1152921504606846977);
+        assert_ne!(int, FileSourceInner::Float(1152921504606846977.0));
+        assert!(int > FileSourceInner::Float(1152921504606846977.0));
+        assert_ne!(FileSourceInner::Int(1), FileSourceInner::Float(1.5));
+    }
+
+    #[test]
+    fn test_quantize_round_trip() {
+        let floats = vec![0.053_262_424, -1.7, 3.0];
+        let data = GraphData::new(DataSource::from(vec![floats.clone()]));
+        let file_data = |data: GraphData| match data.input_data {
+            DataSource::File(d) => d,
+            _ => panic!("expected file data"),
+        };
+
+        for scale in [0, 7, 16] {
+            let quantized = data.quantize(scale).unwrap();
+            assert_eq!(quantized.quantized_scale, Some(scale));
+            assert!(quantized.quantize(scale).is_err());
+            // the frozen inputs are integers, loaded as is
+            for (q, x) in file_data(quantized.clone())[0].iter().zip(&floats) {
+                assert!(matches!(q, FileSourceInner::Int(_)));
+                assert_eq!(
+                    q.to_integer_field().unwrap(),
+                    FileSourceInner::Float(*x).to_field(scale).unwrap()
+                );
+            }
+            let json = serde_json::to_string(&quantized).unwrap();
+            assert!(!json.contains("0x"));
+            let reloaded: GraphData = serde_json::from_str(&json).unwrap();
+            assert_eq!(reloaded.quantized_scale, Some(scale));
+
+            assert!(quantized.dequantize(scale + 1).is_err());
+            assert!(data.dequantize(scale).is_err());
+            let bound = 0.5 / crate::graph::scale_to_multiplier(scale);
+            let dequantized = quantized.dequantize(scale).unwrap();
+            assert_eq!(dequantized.quantized_scale, None);
+            let dequantized = file_data(dequantized);
+            for (d, x) in dequantized[0].iter().zip(&floats) {
+                assert!((d.to_float() - x).abs() <= bound);
+            }
+        }
+    }
+
     //  test for the compatibility with the serialized elements from the mclbn256 library
     #[test]
     fn test_python_compat() {
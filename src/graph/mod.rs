@@ -1,3 +1,15 @@
+/// Searching the scale and lookup bits to quantize a model at, such that its outputs stay within a target error of
+/// the float model's.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod calibration;
+/// Analysis of how the model's layout uses its advice columns, and elimination of those it never assigns to.
+pub mod columns;
+/// User-defined operators, implemented by a registered [custom::CircuitOp].
+pub mod custom;
+/// Structured reasons for a circuit's constraints not being satisfied.
+pub mod diagnostics;
+/// Static bounds on how far a circuit's outputs can be from the model's float outputs.
+pub mod error_bounds;
 /// Representations of a computational graph's inputs.
 pub mod input;
 /// Crate for defining a computational graph and building a ZK-circuit from it.
@@ -6,6 +18,19 @@ pub mod model;
 pub mod modules;
 /// Inner elements of a computational graph that represent a single operation / constraints.
 pub mod node;
+/// Detection of the ONNX opset a model was exported with, and checks of its ops against the semantics of that opset.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod opset;
+/// Optimization passes over a computational graph.
+pub mod passes;
+/// Rebalancing of the scales of the nodes whose values overflow the range of the lookups they feed.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod rebalance;
+/// Validation of a model's forward pass against the layer outputs of a reference implementation.
+pub mod reference;
+/// Extraction of the largest prefix of a model whose ops ezkl supports, as a standalone ONNX model.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod supported;
 /// Helper functions
 pub mod utilities;
 /// Representations of a computational graph's variables.
@@ -15,11 +40,12 @@ use halo2_proofs::circuit::Value;
 pub use input::DataSource;
 use itertools::Itertools;
 
+use self::columns::AdviceColumnUsage;
 #[cfg(not(target_arch = "wasm32"))]
 use self::input::OnChainSource;
 use self::input::{FileSource, GraphData};
 use self::modules::{
-    GraphModules, ModuleConfigs, ModuleForwardResult, ModuleSettings, ModuleSizes,
+    GraphModules, ModuleConfigs, ModuleForwardResult, ModuleSettings, ModuleSizes, PublicInputHash,
 };
 use crate::circuit::lookup::LookupOp;
 use crate::circuit::modules::ModulePlanner;
@@ -74,6 +100,18 @@ pub enum GraphError {
     /// This operation is unsupported
     #[error("unsupported operation in graph")]
     UnsupportedOp,
+    /// A node's op is unsupported, and the [UnsupportedOpPolicy] doesn't allow skipping or approximating it
+    #[error("node {0} has an unsupported op: {1}")]
+    UnsupportedNode(usize, String),
+    /// The outputs can't be requantized to the output scale, see [crate::RunArgs::output_scale]
+    #[error("invalid output scale: {0}")]
+    OutputScale(String),
+    /// A cast between types the circuit can't represent, or to a boolean
+    #[error("node {0} casts {1}, which is unsupported")]
+    UnsupportedCast(usize, String),
+    /// An op can't be read per the semantics of the model's opset, see [opset::OpsetReport]
+    #[error("unsupported opset: {0}")]
+    UnsupportedOpset(String),
     /// A node has missing parameters
     #[error("a node is missing required params: {0}")]
     MissingParams(String),
@@ -98,6 +136,60 @@ pub enum GraphError {
     /// Packing exponent is too large
     #[error("largest packing exponent exceeds max. try reducing the scale")]
     PackingExponent,
+    /// The public inputs don't fit in the requested padded length
+    #[error("circuit has {0} public inputs which exceeds the padded length of {1}")]
+    InstancePadding(usize, usize),
+    /// A public input doesn't fit in the circuit's declared range
+    #[error("public input {1} of instance column {0} is out of range: {2}")]
+    PublicInputRange(usize, usize, crate::fieldutils::FeltRangeError),
+    /// The public inputs don't commit to the circuit's params
+    #[error("failed to check params commitment: {0}")]
+    ParamsCommitment(String),
+    /// The model or its inputs can't be evaluated with integer arithmetic alone
+    #[error("integer-only mode: {0}")]
+    IntegerOnly(String),
+    /// The input normalization doesn't fit the model's inputs
+    #[error("invalid input normalization: {0}")]
+    InvalidNormalization(String),
+    /// Data couldn't be quantized or dequantized
+    #[error("failed to (de)quantize data: {0}")]
+    DataQuantization(String),
+    /// The disclosed input statistics don't fit the model's input
+    #[error("invalid input statistics: {0}")]
+    InvalidInputStatistics(String),
+    /// The model inputs don't fit the model (e.g the wrong number of inputs or elements)
+    #[error("invalid model input: {0}")]
+    InvalidInput(String),
+    /// The scales of the model couldn't be rebalanced to fit its lookups' range
+    #[error("failed to rebalance scales: {0}")]
+    Rebalance(String),
+    /// A proof's hashed public inputs were computed with a different hash function (or parameters) than expected
+    #[error("public input hash mismatch: {0}")]
+    PublicInputHashMismatch(String),
+    /// A proof's outputs can't be elided or recomputed
+    #[error("elided outputs: {0}")]
+    ElidedOutputs(String),
+    /// The public inputs don't have the shape the circuit expects
+    #[error("malformed public inputs: {0}")]
+    MalformedPublicInputs(String),
+    /// The challenge is missing or doesn't match the verifier's
+    #[error("challenge: {0}")]
+    Challenge(String),
+    /// The classification can't be asserted for this model or visibility
+    #[error("classification: {0}")]
+    Classify(String),
+    /// The output range can't be asserted, or the claimed bounds are missing or don't match the verifier's
+    #[error("output range: {0}")]
+    OutputRange(String),
+    /// The input can't be constrained to be sorted, or isn't in canonical order
+    #[error("sorted input: {0}")]
+    SortedInput(String),
+    /// The circuit needs more rows than the configured limit
+    #[error("circuit needs k={0} which exceeds your configured limit of {1}; reduce model size, scale, or split the circuit")]
+    ExceedsMaxLogrows(u32, u32),
+    /// A custom op couldn't be registered or built
+    #[error("custom op: {0}")]
+    CustomOp(String),
 }
 
 const ASSUMED_BLINDING_FACTORS: usize = 7;
@@ -105,6 +197,53 @@ const ASSUMED_BLINDING_FACTORS: usize = 7;
 /// 26
 const MAX_PUBLIC_SRS: u32 = bn256::Fr::S - 2;
 
+/// A public input of a proof that differs from the one recomputed from the model and the claimed input, see
+/// [GraphCircuit::audit_public_inputs].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicInputMismatch {
+    /// the instance column
+    pub column: usize,
+    /// the index within the column
+    pub index: usize,
+    /// the recomputed value, `None` if the proof's column is longer
+    pub expected: Option<Fp>,
+    /// the proof's value, `None` if the proof's column is shorter
+    pub found: Option<Fp>,
+}
+
+/// A commitment to a model's params that its vendor publishes before proving anything with them, such that clients
+/// can check that proofs are generated with the committed weights without the weights being disclosed to them (see
+/// [GraphSettings::check_params_commitment]).
+///
+/// The commitment is the digest of the model's quantized params, flattened in the order the model lays them out,
+/// under the settings' [modules::HashFunction] (Poseidon over BN256, hashed as a tree): the same digest circuits with
+/// hashed params (`--param-visibility hashed`) expose as a public input and constrain to be the hash of the params
+/// they compute with, which stay a private witness. The commitment is binding but unsalted, so it hides the weights
+/// only insofar as they can't be guessed: anyone holding candidate weights (e.g a public checkpoint the model may
+/// have been fine-tuned from without change) can check them against it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParamsCommitment {
+    /// The digest of the params.
+    pub hash: Fp,
+    /// The hash function (and the parameters it is run with) the digest was computed with.
+    pub hash_function: PublicInputHash,
+}
+
+impl ParamsCommitment {
+    /// Saves the commitment to a json file.
+    pub fn save(&self, path: &std::path::PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+        let f = std::fs::File::create(path)?;
+        serde_json::to_writer(std::io::BufWriter::new(f), self)?;
+        Ok(())
+    }
+
+    /// Loads a commitment from a json file.
+    pub fn load(path: &std::path::PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let f = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(f))?)
+    }
+}
+
 /// Result from a forward pass
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct GraphWitness {
@@ -120,6 +259,13 @@ pub struct GraphWitness {
     pub processed_outputs: Option<ModuleForwardResult>,
     /// max lookup input
     pub max_lookup_inputs: i128,
+    /// The verifier-supplied challenge the proof is generated against, see [crate::RunArgs::challenge]
+    #[serde(default)]
+    pub challenge: Option<Fp>,
+    /// The quantized `(min, max)` bounds the prover claims the first output lies within, see
+    /// [crate::RunArgs::assert_output_range]
+    #[serde(default)]
+    pub output_range: Option<[Fp; 2]>,
 }
 
 impl GraphWitness {
@@ -132,6 +278,8 @@ impl GraphWitness {
             processed_params: None,
             processed_outputs: None,
             max_lookup_inputs: 0,
+            challenge: None,
+            output_range: None,
         }
     }
     /// Load the model input from a file
@@ -309,11 +457,22 @@ pub struct GraphSettings {
     pub run_args: RunArgs,
     /// the potential number of constraints in the circuit
     pub num_constraints: usize,
+    /// The rows of the model's region, which unlike [GraphSettings::num_constraints] excludes those of the modules
+    /// (laid out over their own columns). Zero for settings generated without it
+    #[serde(default)]
+    pub model_rows: usize,
     /// total const size
     pub total_const_size: usize,
     /// the shape of public inputs to the model (in order of appearance)
     pub model_instance_shapes: Vec<Vec<usize>>,
-    /// model output scales
+    /// The fixed point scale of each of the model's inputs, as [GraphSettings::model_output_scales] are for its
+    /// outputs. Empty for settings generated without it, whose inputs are at the scale of the run args
+    #[serde(default)]
+    pub model_input_scales: Vec<u32>,
+    /// The fixed point scale of each of the model's outputs, such that an output `y` represents `y / 2^scale`. Sums
+    /// are at the (common) scale of their operands, products at the sum of their operands' scales, and lookups (e.g
+    /// activations) at the global scale, so unless outputs are requantized (see [crate::RunArgs::output_scale]) an
+    /// output's scale is the sum of the scales multiplied together since the last lookup leading up to it
     pub model_output_scales: Vec<u32>,
     /// the of instance cells used by modules
     pub module_sizes: ModuleSizes,
@@ -323,6 +482,71 @@ pub struct GraphSettings {
     pub check_mode: CheckMode,
     /// ezkl version used
     pub version: String,
+    /// normalization applied to the model's inputs within the circuit (if any)
+    #[serde(default)]
+    pub input_normalization: Option<InputNormalization>,
+    /// The number of bits the outputs of the graph's (top-level) nodes are scaled down by as the graph is built, by
+    /// node index, such that the values they feed into lookups fit in the lookups' range (see
+    /// [rebalance::rebalance_scales]). Applied whenever the model is loaded with these settings, see
+    /// [Model::from_settings]
+    #[serde(default)]
+    pub rescaled_nodes: std::collections::BTreeMap<usize, u32>,
+    /// The hash function (and its parameters) the settings were generated with, see
+    /// [GraphSettings::public_input_hash]. The keys are generated from the settings, so this pins the hash the vk's
+    /// circuit computes, see [GraphSettings::check_public_input_hash]
+    #[serde(default)]
+    pub hash_parameters: Option<PublicInputHash>,
+}
+
+/// A per-channel normalization `(x - mean[c]) / std[c]` of the model's inputs, e.g the standardization of images,
+/// proven within the circuit such that the public inputs are the raw data.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct InputNormalization {
+    /// The channel axis of the inputs (including the batch dimension).
+    pub axis: usize,
+    /// The mean of each channel.
+    pub mean: Vec<f32>,
+    /// The standard deviation of each channel.
+    pub std: Vec<f32>,
+}
+
+impl InputNormalization {
+    /// Loads a normalization from a json file.
+    pub fn from_path(path: &std::path::PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let f = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(f))?)
+    }
+
+    /// Quantizes the normalization for inputs at the fixed point `scale`.
+    pub fn quantize(&self, scale: u32) -> Result<crate::circuit::Normalization, GraphError> {
+        if self.mean.len() != self.std.len() {
+            return Err(GraphError::InvalidNormalization(format!(
+                "{} means but {} standard deviations",
+                self.mean.len(),
+                self.std.len()
+            )));
+        }
+        if self.std.iter().any(|s| *s == 0.0) {
+            return Err(GraphError::InvalidNormalization(
+                "standard deviations must be non-zero".to_string(),
+            ));
+        }
+        let multiplier = scale_to_multiplier(scale);
+        Ok(crate::circuit::Normalization {
+            axis: self.axis,
+            mult: self
+                .std
+                .iter()
+                .map(|s| (multiplier / *s as f64).round() as i128)
+                .collect(),
+            shift: self
+                .mean
+                .iter()
+                .zip(&self.std)
+                .map(|(m, s)| (-*m as f64 / *s as f64 * multiplier * multiplier).round() as i128)
+                .collect(),
+        })
+    }
 }
 
 impl GraphSettings {
@@ -333,11 +557,389 @@ impl GraphSettings {
             .iter()
             .map(|x| x.iter().product())
             .collect();
+        let padding = self.instance_padding();
+        if padding > 0 {
+            instances.push(padding);
+        }
         instances.extend(self.module_sizes.num_instances());
 
         instances
     }
 
+    /// number of zero felts appended to the public inputs to reach `run_args.pad_public_inputs`
+    pub fn instance_padding(&self) -> usize {
+        let num_instances = self
+            .model_instance_shapes
+            .iter()
+            .map(|x| x.iter().product::<usize>())
+            .chain(self.module_sizes.num_instances())
+            .sum::<usize>();
+        self.run_args
+            .pad_public_inputs
+            .saturating_sub(num_instances)
+    }
+
+    /// the shapes of the model's instance columns, including the zero padding column (if any)
+    pub fn padded_instance_shapes(&self) -> Vec<Vec<usize>> {
+        let mut shapes = self.model_instance_shapes.clone();
+        let padding = self.instance_padding();
+        if padding > 0 {
+            shapes.push(vec![padding]);
+        }
+        shapes
+    }
+
+    /// The contents of each instance column of circuits with these settings, as [GraphCircuit::instance_layout]
+    /// lays them out, recovered without the model: the number of public outputs follows from the model's output
+    /// scales, and the public inputs take the model's remaining instance columns.
+    pub fn instance_layout(&self) -> Vec<InstanceColumn> {
+        let run_args = &self.run_args;
+        let outputs = match run_args.output_visibility.is_public() {
+            true => run_args
+                .public_outputs
+                .indices(self.model_output_scales.len()),
+            false => vec![],
+        };
+        let num_trailing = !run_args.input_statistics.is_empty() as usize
+            + run_args.classify as usize
+            + run_args.assert_output_range as usize
+            + run_args.challenge as usize;
+        let num_inputs = match run_args.input_visibility.is_public() {
+            true => self
+                .model_instance_shapes
+                .len()
+                .saturating_sub(outputs.len() + num_trailing),
+            false => 0,
+        };
+
+        let mut layout = (0..num_inputs).map(InstanceColumn::Input).collect_vec();
+        layout.extend(outputs.into_iter().map(InstanceColumn::Output));
+        if !run_args.input_statistics.is_empty() {
+            layout.push(InstanceColumn::InputStatistics);
+        }
+        if run_args.classify {
+            layout.push(InstanceColumn::Class);
+        }
+        if run_args.assert_output_range {
+            layout.push(InstanceColumn::OutputRange);
+        }
+        if run_args.challenge {
+            layout.push(InstanceColumn::Challenge);
+        }
+        if self.instance_padding() > 0 {
+            layout.push(InstanceColumn::Padding);
+        }
+        let num_module_columns = self
+            .module_sizes
+            .num_instances()
+            .into_iter()
+            .filter(|n| *n > 0)
+            .count();
+        layout.extend((0..num_module_columns).map(InstanceColumn::Module));
+        layout
+    }
+
+    /// The fixed point scale of each instance column of circuits with these settings (in the order of
+    /// [GraphSettings::instance_layout]), such that a public input `x` of a column at scale `s` represents `x / 2^s`.
+    /// Columns that don't hold fixed point values (the disclosed input statistics, which mix counts and values, the
+    /// challenge, padding and the modules' hashes or ciphertexts) have no scale. The predicted class is an integer, at
+    /// scale 0. As the scales follow from the settings, a verifier holding them needn't trust those of a proof.
+    pub fn instance_scales(&self) -> Vec<Option<u32>> {
+        let input_scale = |i: usize| match self.model_input_scales.is_empty() {
+            true => Some(self.run_args.scale),
+            false => self.model_input_scales.get(i).copied(),
+        };
+        self.instance_layout()
+            .into_iter()
+            .map(|column| match column {
+                InstanceColumn::Input(i) => input_scale(i),
+                InstanceColumn::Output(i) => self.model_output_scales.get(i).copied(),
+                InstanceColumn::Class => Some(0),
+                InstanceColumn::OutputRange => self.model_output_scales.first().copied(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// the smallest logrows the circuit fits in at the current lookup bits: the constraints, lookup tables,
+    /// instance columns and fixed constants all need enough rows.
+    pub fn min_logrows(&self) -> u32 {
+        let log2_ceil = |x: usize| (x.max(1) as f64).log2().ceil() as u32;
+
+        let mut logrows = log2_ceil(self.num_constraints);
+        if !self.required_lookups.is_empty() {
+            // lookup tables span 2^bits rows
+            logrows = std::cmp::max(logrows, self.run_args.bits as u32 + 1);
+        }
+        let max_instance_len = self.total_instances().into_iter().max().unwrap_or(0);
+        logrows = std::cmp::max(logrows, log2_ceil(max_instance_len));
+        logrows = std::cmp::max(logrows, log2_ceil(self.total_const_size) + 1);
+
+        std::cmp::max(logrows, ASSUMED_BLINDING_FACTORS as u32)
+    }
+
+    /// checks that the logrows the circuit needs (the larger of [Self::min_logrows] and the configured logrows)
+    /// are within `max_logrows` (the machine's limit, defaulting to the largest logrows of the public SRS), such that
+    /// oversized circuits fail before any keys or proofs are generated.
+    pub fn check_max_logrows(&self, max_logrows: Option<u32>) -> Result<(), GraphError> {
+        let limit = max_logrows.unwrap_or(MAX_PUBLIC_SRS);
+        let needed = std::cmp::max(self.min_logrows(), self.run_args.logrows);
+        if needed > limit {
+            return Err(GraphError::ExceedsMaxLogrows(needed, limit));
+        }
+        Ok(())
+    }
+
+    /// checks that the model's public inputs fit in the signed range of `run_args.bits`, the range of the lookups
+    /// they feed. Only the inputs are checked: outputs and other derived columns (e.g input statistics) are the
+    /// results of the model's ops and may legitimately exceed it, while module instances (e.g. hashes), the challenge
+    /// and the zero padding are full field elements.
+    pub fn check_public_inputs_range(&self, public_inputs: &[Vec<Fp>]) -> Result<(), GraphError> {
+        for (i, (column, _)) in public_inputs
+            .iter()
+            .zip(self.instance_layout())
+            .enumerate()
+            .filter(|(_, (_, c))| matches!(c, InstanceColumn::Input(_)))
+        {
+            for (j, x) in column.iter().enumerate() {
+                crate::fieldutils::check_felt_range(*x, self.run_args.bits)
+                    .map_err(|e| GraphError::PublicInputRange(i, j, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that `public_inputs` are well-formed for the circuit with these settings and verifying key `vk`, without
+    /// touching a proof: that there are as many columns as the circuit has instance columns, that each column holds
+    /// as many values as the circuit expects (and that fit in the circuit's usable rows), that the model's columns
+    /// fit in the range of the circuit's lookups and that the padding is all zeros. A cheap way to reject malformed
+    /// requests before a full verification, which rejects any public inputs this does on shape grounds.
+    ///
+    /// Outputs elided from a proof have to be restored (see [GraphCircuit::restore_elided_outputs]) beforehand.
+    pub fn validate_public_inputs(
+        &self,
+        vk: &halo2_proofs::plonk::VerifyingKey<bn256::G1Affine>,
+        public_inputs: &[Vec<Fp>],
+    ) -> Result<(), GraphError> {
+        let info = crate::pfsys::vk_info(vk);
+        if public_inputs.len() != info.num_instance_columns {
+            return Err(GraphError::MalformedPublicInputs(format!(
+                "expected {} instance columns, got {}",
+                info.num_instance_columns,
+                public_inputs.len()
+            )));
+        }
+        let usable_rows = (1usize << info.k) - (vk.cs().blinding_factors() + 1);
+        let expected = self.total_instances();
+        for (i, column) in public_inputs.iter().enumerate() {
+            if column.len() > usable_rows {
+                return Err(GraphError::MalformedPublicInputs(format!(
+                    "instance column {} holds {} values but only {} rows are usable",
+                    i,
+                    column.len(),
+                    usable_rows
+                )));
+            }
+            if expected.get(i) != Some(&column.len()) {
+                return Err(GraphError::MalformedPublicInputs(format!(
+                    "instance column {} holds {} values, expected {:?}",
+                    i,
+                    column.len(),
+                    expected.get(i)
+                )));
+            }
+        }
+
+        self.check_public_inputs_range(public_inputs)?;
+        if self.instance_padding() > 0 {
+            let padding = &public_inputs[self.model_instance_shapes.len()];
+            if let Some(j) = padding.iter().position(|x| *x != Fp::zero()) {
+                return Err(GraphError::MalformedPublicInputs(format!(
+                    "padding value {} isn't zero",
+                    j
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// checks that a proof's public inputs hold the params hash of the published `commitment` (see
+    /// [ParamsCommitment]), such that a client knows the proof was generated with the committed weights without
+    /// holding them, unlike [GraphCircuit::check_params_hash]. Within the poseidon instance column the params hash
+    /// comes after a hash per (hashed) model input and before a hash per (hashed) model output.
+    pub fn check_params_commitment(
+        &self,
+        instances: &[Vec<Fp>],
+        commitment: &ParamsCommitment,
+    ) -> Result<(), GraphError> {
+        let run_args = &self.run_args;
+        if !run_args.param_visibility.is_hashed() {
+            return Err(GraphError::ParamsCommitment(
+                "params are not hashed, so proofs don't commit to them".to_string(),
+            ));
+        }
+        let hash_function = run_args.hash_function.public_input_hash();
+        if commitment.hash_function != hash_function {
+            return Err(GraphError::ParamsCommitment(format!(
+                "the commitment was computed with {:?}, but the circuit hashes with {:?}",
+                commitment.hash_function, hash_function
+            )));
+        }
+
+        let column = self
+            .instance_layout()
+            .iter()
+            .position(|c| *c == InstanceColumn::Module(0))
+            .ok_or_else(|| {
+                GraphError::ParamsCommitment("the circuit has no hashed public inputs".to_string())
+            })?;
+        let num_output_hashes = match run_args.output_visibility.is_hashed() {
+            true => self.model_output_scales.len(),
+            false => 0,
+        };
+        let num_hashes = self.module_sizes.num_instances()[0];
+        let found = num_hashes
+            .checked_sub(num_output_hashes + 1)
+            .and_then(|offset| instances.get(column)?.get(offset));
+        match found {
+            Some(hash) if *hash == commitment.hash => Ok(()),
+            _ => Err(GraphError::ParamsCommitment(format!(
+                "expected the committed params hash {:?} in instance column {} but found {:?}",
+                commitment.hash, column, found
+            ))),
+        }
+    }
+
+    /// checks that a proof's challenge (the last of the model's instance columns, see [crate::RunArgs::challenge])
+    /// matches the `expected` value the verifier supplied. Settings without a challenge reject any expected value,
+    /// such that a verifier relying on the challenge can't be handed a proof that isn't bound to it.
+    pub fn check_challenge(
+        &self,
+        instances: &[Vec<Fp>],
+        expected: Option<Fp>,
+    ) -> Result<(), GraphError> {
+        match (self.run_args.challenge, expected) {
+            (false, None) => Ok(()),
+            (false, Some(_)) => Err(GraphError::Challenge(
+                "a challenge was supplied but the circuit doesn't take one".into(),
+            )),
+            (true, None) => Err(GraphError::Challenge(
+                "the circuit takes a challenge but none was supplied to check against".into(),
+            )),
+            (true, Some(expected)) => {
+                let idx = self.model_instance_shapes.len() - 1;
+                match instances.get(idx).map(|c| c.as_slice()) {
+                    Some([challenge]) if *challenge == expected => Ok(()),
+                    Some([challenge]) => Err(GraphError::Challenge(format!(
+                        "the proof was generated against {:?}, but {:?} was expected",
+                        challenge, expected
+                    ))),
+                    _ => Err(GraphError::Challenge(format!(
+                        "instance column {} doesn't hold a challenge",
+                        idx
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// checks that the bounds a proof asserts its first output lies within (see
+    /// [crate::RunArgs::assert_output_range]) are the `expected` bounds the verifier supplied, quantized at the output's
+    /// scale. As for [GraphSettings::check_challenge], settings that don't assert a range reject any expected bounds.
+    pub fn check_output_range(
+        &self,
+        instances: &[Vec<Fp>],
+        expected: Option<OutputRange>,
+    ) -> Result<(), GraphError> {
+        match (self.run_args.assert_output_range, expected) {
+            (false, None) => Ok(()),
+            (false, Some(_)) => Err(GraphError::OutputRange(
+                "bounds were supplied but the circuit doesn't assert an output range".into(),
+            )),
+            (true, None) => Err(GraphError::OutputRange(
+                "the circuit asserts an output range but no bounds were supplied to check against"
+                    .into(),
+            )),
+            (true, Some(expected)) => {
+                let scale = self
+                    .model_output_scales
+                    .first()
+                    .copied()
+                    .unwrap_or_default();
+                let bounds = expected.quantize(scale)?;
+                let idx = self
+                    .instance_layout()
+                    .iter()
+                    .position(|c| *c == InstanceColumn::OutputRange)
+                    .unwrap_or_default();
+                match instances.get(idx).map(|c| c.as_slice()) {
+                    Some(claimed) if claimed == bounds => Ok(()),
+                    Some([min, max]) => Err(GraphError::OutputRange(format!(
+                        "the proof asserts the bounds [{}, {}], but [{}, {}] were expected",
+                        crate::fieldutils::felt_to_i128(*min),
+                        crate::fieldutils::felt_to_i128(*max),
+                        crate::fieldutils::felt_to_i128(bounds[0]),
+                        crate::fieldutils::felt_to_i128(bounds[1])
+                    ))),
+                    _ => Err(GraphError::OutputRange(format!(
+                        "instance column {} doesn't hold an output range",
+                        idx
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// The hash function (and its parameters) that proofs with these settings compute their hashed public inputs
+    /// with, if any of the inputs, params or outputs are hashed.
+    pub fn public_input_hash(&self) -> Option<PublicInputHash> {
+        let run_args = &self.run_args;
+        if run_args.input_visibility.is_hashed()
+            || run_args.param_visibility.is_hashed()
+            || run_args.output_visibility.is_hashed()
+        {
+            Some(run_args.hash_function.public_input_hash())
+        } else {
+            None
+        }
+    }
+
+    /// checks that a proof's hashed public inputs were computed with the hash function (and parameters) these
+    /// settings use, given the hash recorded in the proof. The hash is derived from the verifier's settings (which its
+    /// vk is generated from) rather than taken from the proof, and has to match the [GraphSettings::hash_parameters]
+    /// the settings were generated with. A proof with hashed public inputs that doesn't record its hash is rejected.
+    pub fn check_public_input_hash(
+        &self,
+        recorded: Option<&PublicInputHash>,
+    ) -> Result<(), GraphError> {
+        let expected = self.public_input_hash();
+        if let Some(pinned) = &self.hash_parameters {
+            if expected.as_ref() != Some(pinned) {
+                return Err(GraphError::PublicInputHashMismatch(format!(
+                    "the settings (and their keys) were generated with {:?}, but they hash with {:?}",
+                    pinned, expected
+                )));
+            }
+        }
+        match (expected, recorded) {
+            (Some(expected), Some(recorded)) if expected != *recorded => {
+                Err(GraphError::PublicInputHashMismatch(format!(
+                    "expected {:?}, but the proof was generated with {:?}",
+                    expected, recorded
+                )))
+            }
+            (None, Some(recorded)) => Err(GraphError::PublicInputHashMismatch(format!(
+                "nothing is hashed, but the proof was generated with {:?}",
+                recorded
+            ))),
+            (Some(expected), None) => Err(GraphError::PublicInputHashMismatch(format!(
+                "expected {:?}, but the proof doesn't record its hash",
+                expected
+            ))),
+            _ => Ok(()),
+        }
+    }
+
     /// save params to file
     pub fn save(&self, path: &std::path::PathBuf) -> Result<(), std::io::Error> {
         let encoded = serde_json::to_string(&self)?;
@@ -362,7 +964,7 @@ pub struct GraphConfig {
 }
 
 /// Defines the circuit for a computational graph / model loaded from a `.onnx` file.
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct GraphCircuit {
     /// The model / graph of computations.
     pub model: Model,
@@ -449,6 +1051,19 @@ impl GraphCircuit {
         // number of instances used by modules
         settings.module_sizes = sizes.clone();
 
+        if run_args.pad_public_inputs > 0 {
+            let num_instances = settings.total_instances().iter().sum::<usize>();
+            if num_instances > run_args.pad_public_inputs {
+                return Err(Box::new(GraphError::InstancePadding(
+                    num_instances,
+                    run_args.pad_public_inputs,
+                )));
+            }
+            // the zero padding is constrained within the model region
+            settings.num_constraints += settings.instance_padding();
+        }
+
+        settings.model_rows = settings.num_constraints;
         // as they occupy independent rows
         settings.num_constraints = std::cmp::max(settings.num_constraints, sizes.max_constraints());
 
@@ -486,6 +1101,23 @@ impl GraphCircuit {
         })
     }
 
+    /// Saves the circuit, witness included, to `path` such that it can be proven elsewhere without re-parsing and
+    /// re-quantizing the model.
+    pub fn save(&self, path: &std::path::PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+        let f = std::fs::File::create(path)?;
+        let writer = std::io::BufWriter::new(f);
+        bincode::serialize_into(writer, &self)?;
+        Ok(())
+    }
+
+    /// Loads a circuit saved with [GraphCircuit::save] from `path`.
+    pub fn load(path: &std::path::PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let f = std::fs::File::open(path)?;
+        let reader = std::io::BufReader::new(f);
+        let circuit = bincode::deserialize_from(reader)?;
+        Ok(circuit)
+    }
+
     /// load inputs and outputs for the model
     pub fn load_graph_witness(
         &mut self,
@@ -503,29 +1135,116 @@ impl GraphCircuit {
         &mut self,
         data: &GraphWitness,
     ) -> Result<Vec<Vec<Fp>>, Box<dyn std::error::Error>> {
-        // quantize the supplied data using the provided scale.
-        // the ordering here is important, we want the inputs to come before the outputs
-        // as they are configured in that order as Column<Instances>
-        let mut public_inputs = vec![];
-        if self.settings.run_args.input_visibility.is_public() {
-            public_inputs = self.graph_witness.inputs.clone();
+        // inputs that aren't in canonical order can't be proven, so they're rejected up front
+        if let Some(idx) = self.settings.run_args.sorted_input {
+            let dedup = self.settings.run_args.dedup_sorted_input;
+            let bits = self.settings.run_args.bits;
+            // the differences of consecutive elements (less one when deduplicated) are looked up in the ReLU table,
+            // so they're checked in the field, as the circuit sees them
+            for pair in data.inputs[idx].windows(2) {
+                let gap = pair[1] - pair[0] - Fp::from(dedup as u64);
+                let (lo, hi) = (
+                    crate::fieldutils::felt_to_i128(pair[0]),
+                    crate::fieldutils::felt_to_i128(pair[1]),
+                );
+                if !crate::circuit::table::in_table_range(gap, bits) {
+                    let range = crate::circuit::table::table_range(bits);
+                    return Err(Box::new(GraphError::SortedInput(format!(
+                        "input {} can't be proven sorted: {} is followed by {}, but the differences of consecutive \
+                         elements have to be in the range [{}, {}] of the lookup tables; raise the lookup bits",
+                        idx,
+                        lo,
+                        hi,
+                        range.start(),
+                        range.end()
+                    ))));
+                }
+                if crate::fieldutils::felt_to_i128(gap) < 0 {
+                    return Err(Box::new(GraphError::SortedInput(format!(
+                        "input {} isn't {}: {} is followed by {}",
+                        idx,
+                        match dedup {
+                            true => "strictly ascending",
+                            false => "ascending",
+                        },
+                        lo,
+                        hi
+                    ))));
+                }
+            }
         }
-        if self.settings.run_args.output_visibility.is_public() {
-            public_inputs.extend(self.graph_witness.outputs.clone());
+
+        // the model's columns and any padding are assembled in the order the circuit lays them out
+        let mut pi_inner: Vec<Vec<Fp>> = vec![];
+        for column in self.instance_layout() {
+            match column {
+                InstanceColumn::Input(i) => pi_inner.push(self.graph_witness.inputs[i].clone()),
+                InstanceColumn::Output(i) => pi_inner.push(self.graph_witness.outputs[i].clone()),
+                InstanceColumn::InputStatistics => {
+                    let statistics = self.settings.run_args.input_statistics;
+                    pi_inner.push(statistics.evaluate(&self.graph_witness.inputs[0]));
+                }
+                InstanceColumn::Class => {
+                    // the argmax over the last axis of the first output, as the circuit lays it out
+                    let shape = self.model.graph.output_shapes()[0].clone();
+                    let logits = self.graph_witness.outputs[0]
+                        .iter()
+                        .map(|x| crate::fieldutils::felt_to_i128(*x))
+                        .collect_vec();
+                    let logits = Tensor::new(Some(&logits[..]), &shape)?;
+                    let class = crate::tensor::ops::argmax_axes(&logits, shape.len() - 1)?;
+                    pi_inner.push(
+                        class
+                            .iter()
+                            .map(|c| crate::fieldutils::i128_to_felt(*c))
+                            .collect(),
+                    );
+                }
+                InstanceColumn::OutputRange => {
+                    let [min, max] = self.graph_witness.output_range.ok_or_else(|| {
+                        GraphError::OutputRange(
+                            "the circuit asserts an output range but no bounds were supplied"
+                                .into(),
+                        )
+                    })?;
+                    // bounds that don't contain the output can't be proven, so they're rejected up front
+                    let lo = crate::fieldutils::felt_to_i128(min);
+                    let hi = crate::fieldutils::felt_to_i128(max);
+                    if let Some(x) = self.graph_witness.outputs[0]
+                        .iter()
+                        .map(|x| crate::fieldutils::felt_to_i128(*x))
+                        .find(|x| *x < lo || *x > hi)
+                    {
+                        return Err(Box::new(GraphError::OutputRange(format!(
+                            "the output {} isn't within the bounds [{}, {}]",
+                            x, lo, hi
+                        ))));
+                    }
+                    pi_inner.push(vec![min, max]);
+                }
+                InstanceColumn::Challenge => {
+                    let challenge = self.graph_witness.challenge.ok_or_else(|| {
+                        GraphError::Challenge(
+                            "the circuit takes a challenge but none was supplied".into(),
+                        )
+                    })?;
+                    pi_inner.push(vec![challenge]);
+                }
+                InstanceColumn::Padding => {
+                    pi_inner.push(vec![Fp::zero(); self.settings.instance_padding()])
+                }
+                // the modules' columns are assembled together below
+                InstanceColumn::Module(_) => {}
+            }
         }
         info!(
             "public inputs lengths: {:?}",
-            public_inputs
-                .iter()
-                .map(|i| i.len())
-                .collect::<Vec<usize>>()
+            pi_inner.iter().map(|i| i.len()).collect::<Vec<usize>>()
         );
-        trace!("{:?}", public_inputs);
+        trace!("{:?}", pi_inner);
 
-        let mut pi_inner: Vec<Vec<Fp>> = public_inputs
-            .iter()
-            .map(|i| i.clone().into_iter().collect::<Vec<Fp>>())
-            .collect::<Vec<Vec<Fp>>>();
+        // out of range values wrap into the field and would yield a valid proof of the wrong statement
+        self.settings.check_public_inputs_range(&pi_inner)?;
 
         let module_instances =
             GraphModules::public_inputs(data, VarVisibility::from_args(self.settings.run_args)?);
@@ -544,7 +1263,11 @@ impl GraphCircuit {
         data: &GraphData,
     ) -> Result<Vec<Tensor<Fp>>, Box<dyn std::error::Error>> {
         let shapes = self.model.graph.input_shapes();
-        let scales = vec![self.settings.run_args.scale; shapes.len()];
+        // inputs are at the global scale unless they were scaled down, see [GraphSettings::rescaled_nodes]
+        let scales = self.model.graph.get_input_scales();
+        if let Some(scale) = data.quantized_scale {
+            return self.load_quantized_input(data, scale, &shapes, &scales);
+        }
         self.process_data_source(&data.input_data, shapes, scales)
     }
 
@@ -555,11 +1278,52 @@ impl GraphCircuit {
         data: &GraphData,
     ) -> Result<Vec<Tensor<Fp>>, Box<dyn std::error::Error>> {
         let shapes = self.model.graph.input_shapes();
-        let scales = vec![self.settings.run_args.scale; shapes.len()];
+        // inputs are at the global scale unless they were scaled down, see [GraphSettings::rescaled_nodes]
+        let scales = self.model.graph.get_input_scales();
+        if let Some(scale) = data.quantized_scale {
+            return self.load_quantized_input(data, scale, &shapes, &scales);
+        }
         self.process_data_source(&data.input_data, shapes, scales)
             .await
     }
 
+    /// Loads file data frozen at `scale` by [GraphData::quantize]: its integers are loaded as is, provided the model
+    /// quantizes its inputs at the same scale.
+    fn load_quantized_input(
+        &mut self,
+        data: &GraphData,
+        scale: u32,
+        shapes: &Vec<Vec<usize>>,
+        scales: &[u32],
+    ) -> Result<Vec<Tensor<Fp>>, Box<dyn std::error::Error>> {
+        if let Some(input_scale) = scales.iter().find(|s| **s != scale) {
+            return Err(Box::new(GraphError::DataQuantization(format!(
+                "the data is quantized at scale {} but the model's inputs are at scale {}",
+                scale, input_scale
+            ))));
+        }
+        let file_data = match &data.input_data {
+            DataSource::File(file_data) => file_data,
+            _ => {
+                return Err(Box::new(GraphError::DataQuantization(
+                    "only file data can be quantized".to_string(),
+                )))
+            }
+        };
+        check_input_lens(&file_data.iter().map(|d| d.len()).collect_vec(), shapes)?;
+        let mut inputs = vec![];
+        for (d, shape) in file_data.iter().zip(shapes) {
+            let t = d
+                .par_iter()
+                .map(|x| x.to_integer_field())
+                .collect::<Result<Vec<Fp>, GraphError>>()?;
+            let mut t: Tensor<Fp> = t.into_iter().into();
+            t.reshape(shape);
+            inputs.push(t);
+        }
+        Ok(inputs)
+    }
+
     #[cfg(target_arch = "wasm32")]
     /// Process the data source for the model
     fn process_data_source(
@@ -637,10 +1401,20 @@ impl GraphCircuit {
         shapes: &Vec<Vec<usize>>,
         scales: Vec<u32>,
     ) -> Result<Vec<Tensor<Fp>>, Box<dyn std::error::Error>> {
+        check_input_lens(&file_data.iter().map(|d| d.len()).collect_vec(), shapes)?;
         // quantize the supplied data using the provided scale.
         let mut data: Vec<Tensor<Fp>> = vec![];
         for ((d, shape), scale) in file_data.iter().zip(shapes).zip(scales) {
-            let t: Vec<Fp> = d.par_iter().map(|x| x.to_field(scale)).collect();
+            let t: Vec<Fp> = if self.settings.run_args.integer_only {
+                // integer-only models bypass quantization entirely
+                d.par_iter()
+                    .map(|x| x.to_integer_field())
+                    .collect::<Result<Vec<Fp>, GraphError>>()?
+            } else {
+                d.par_iter()
+                    .map(|x| x.to_field(scale))
+                    .collect::<Result<Vec<Fp>, GraphError>>()?
+            };
 
             let mut t: Tensor<Fp> = t.into_iter().into();
             t.reshape(shape);
@@ -656,7 +1430,7 @@ impl GraphCircuit {
         file_data: &Vec<Vec<Fp>>,
         shapes: &Vec<Vec<usize>>,
     ) -> Result<Vec<Tensor<Fp>>, Box<dyn std::error::Error>> {
-        // quantize the supplied data using the provided scale.
+        check_input_lens(&file_data.iter().map(|d| d.len()).collect_vec(), shapes)?;
         let mut data: Vec<Tensor<Fp>> = vec![];
         for (d, shape) in file_data.iter().zip(shapes) {
             let mut t: Tensor<Fp> = d.clone().into_iter().into();
@@ -666,6 +1440,15 @@ impl GraphCircuit {
         Ok(data)
     }
 
+    /// The range of (fixed point) inputs each lookup operation sees when the model is run on `inputs`. `bits` has to be
+    /// large enough for the lookup tables to cover every range.
+    pub fn lookup_ranges(
+        &self,
+        inputs: &[Tensor<Fp>],
+    ) -> Result<Vec<LookupRange>, Box<dyn std::error::Error>> {
+        Ok(self.model.forward(inputs)?.lookup_ranges)
+    }
+
     /// Calibrate the circuit to the supplied data.
     pub fn calibrate(&mut self, input: &[Tensor<Fp>]) -> Result<(), Box<dyn std::error::Error>> {
         let res = self.forward(input)?;
@@ -693,8 +1476,8 @@ impl GraphCircuit {
                 || self.settings.run_args.output_visibility.is_public()
             {
                 let max_instance_len = self
-                    .model
-                    .instance_shapes()
+                    .settings
+                    .padded_instance_shapes()
                     .iter()
                     .fold(0, |acc, x| std::cmp::max(acc, x.iter().product::<usize>()));
                 let instance_len_logrows = (max_instance_len as f64).log2().ceil() as usize;
@@ -725,6 +1508,244 @@ impl GraphCircuit {
         Ok(())
     }
 
+    /// Runs any hashing (or encryption) of the model's params. This is independent of the model's inputs, so a
+    /// verifier holding the (public, auditable) model can recompute it.
+    pub fn process_params(
+        &self,
+    ) -> Result<Option<ModuleForwardResult>, Box<dyn std::error::Error>> {
+        let visibility = self.settings.run_args.param_visibility;
+        let params = self.model.get_all_params();
+        if !visibility.requires_processing() || params.is_empty() {
+            return Ok(None);
+        }
+        let flattened_params = Tensor::new(Some(&params), &[params.len()])?.combine()?;
+        Ok(Some(GraphModules::forward(
+            &[flattened_params],
+            visibility,
+        )?))
+    }
+
+    /// The commitment to this circuit's params its vendor publishes, see [ParamsCommitment]. The params have to be
+    /// hashed, such that proofs expose the commitment.
+    pub fn params_commitment(&self) -> Result<ParamsCommitment, Box<dyn std::error::Error>> {
+        if !self.settings.run_args.param_visibility.is_hashed() {
+            return Err(Box::new(GraphError::ParamsCommitment(
+                "params have to be hashed to be committed to (see --param-visibility)".to_string(),
+            )));
+        }
+        match self.process_params()? {
+            Some(ModuleForwardResult {
+                poseidon_hash: Some(hash),
+                ..
+            }) if hash.len() == 1 => Ok(ParamsCommitment {
+                hash: hash[0],
+                hash_function: self.settings.run_args.hash_function.public_input_hash(),
+            }),
+            _ => Err(Box::new(GraphError::ParamsCommitment(
+                "the model has no params to commit to".to_string(),
+            ))),
+        }
+    }
+
+    /// Checks that a proof's public inputs commit to this circuit's (hashed) params, such that a verifier knows
+    /// exactly which weights were used, even if the model's inputs are kept private.
+    pub fn check_params_hash(
+        &self,
+        instances: &[Vec<Fp>],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let run_args = &self.settings.run_args;
+        if !run_args.param_visibility.is_hashed() {
+            return Err(Box::new(GraphError::ParamsCommitment(
+                "params are not hashed".to_string(),
+            )));
+        }
+        let expected = match self.process_params()? {
+            Some(ModuleForwardResult {
+                poseidon_hash: Some(hash),
+                ..
+            }) => hash,
+            // a model without params has nothing to commit to
+            _ => return Ok(()),
+        };
+
+        // the poseidon column is the first of the modules'
+        let column = self
+            .instance_index(InstanceColumn::Module(0))
+            .unwrap_or(usize::MAX);
+        // within it, a hash per (hashed) model input comes before the params hash
+        let offset = match run_args.input_visibility.is_hashed() {
+            true => self.model.graph.num_inputs(),
+            false => 0,
+        };
+
+        let found = instances
+            .get(column)
+            .and_then(|c| c.get(offset..offset + expected.len()));
+        if found != Some(&expected[..]) {
+            return Err(Box::new(GraphError::ParamsCommitment(format!(
+                "expected params hash {:?} in instance column {} but found {:?}",
+                expected, column, found
+            ))));
+        }
+        Ok(())
+    }
+
+    /// The circuit's instance columns, in the order they are laid out and the public inputs are assembled: the model's
+    /// (see [Model::instance_layout]), the zero padding (if any) and the modules'. Which columns are present and where
+    /// depends on the visibility flags, so the position of a column should be looked up here rather than assumed.
+    pub fn instance_layout(&self) -> Vec<InstanceColumn> {
+        let mut layout = self
+            .model
+            .instance_layout()
+            .into_iter()
+            .map(|(column, _)| column)
+            .collect_vec();
+        if self.settings.instance_padding() > 0 {
+            layout.push(InstanceColumn::Padding);
+        }
+        // only the modules in use have a column
+        let num_module_columns = self
+            .settings
+            .module_sizes
+            .num_instances()
+            .into_iter()
+            .filter(|n| *n > 0)
+            .count();
+        layout.extend((0..num_module_columns).map(InstanceColumn::Module));
+        layout
+    }
+
+    /// The index of the instance column holding `column`, if the circuit has one, see [GraphCircuit::instance_layout].
+    pub fn instance_index(&self, column: InstanceColumn) -> Option<usize> {
+        self.instance_layout().iter().position(|c| *c == column)
+    }
+
+    /// The instance columns holding the model's outputs, which the verifier can recompute when both the model's
+    /// inputs and outputs are public: the outputs are a deterministic function of the inputs and the model.
+    fn recomputable_output_columns(&self) -> Result<std::ops::Range<usize>, GraphError> {
+        let run_args = &self.settings.run_args;
+        if !run_args.input_visibility.is_public() || !run_args.output_visibility.is_public() {
+            return Err(GraphError::ElidedOutputs(
+                "outputs can only be recomputed when the inputs and outputs are public".to_string(),
+            ));
+        }
+        let layout = self.instance_layout();
+        let outputs = layout
+            .iter()
+            .positions(|c| matches!(c, InstanceColumn::Output(_)))
+            .collect_vec();
+        match (outputs.first(), outputs.last()) {
+            (Some(&start), Some(&end)) => Ok(start..end + 1),
+            _ => Ok(0..0),
+        }
+    }
+
+    /// Recomputes the public inputs a proof of `inputs` should have, by running the model forward and laying out its
+    /// public inputs as [GraphCircuit::prepare_public_inputs] does for proving, and compares them to a proof's
+    /// `instances`. This catches stored public inputs that were altered to claim another output than the model
+    /// computes. The challenge and the claimed output range aren't functions of the inputs so they're taken from
+    /// `instances` (see [GraphSettings::check_challenge] and [GraphSettings::check_output_range] to check them), and
+    /// elided outputs have to be restored beforehand.
+    pub fn audit_public_inputs(
+        &mut self,
+        inputs: &[Tensor<Fp>],
+        instances: &[Vec<Fp>],
+    ) -> Result<Vec<PublicInputMismatch>, Box<dyn std::error::Error>> {
+        let mut witness = self.forward(inputs)?;
+        if self.settings.run_args.challenge {
+            witness.challenge = self
+                .instance_index(InstanceColumn::Challenge)
+                .and_then(|column| instances.get(column))
+                .and_then(|c| c.first().copied());
+        }
+        // nor are the claimed bounds of the output
+        if self.settings.run_args.assert_output_range {
+            witness.output_range = self
+                .instance_index(InstanceColumn::OutputRange)
+                .and_then(|column| instances.get(column))
+                .and_then(|c| <[Fp; 2]>::try_from(c.as_slice()).ok());
+        }
+        self.load_graph_witness(&witness)?;
+        let expected = self.prepare_public_inputs(&witness)?;
+
+        let mut mismatches = vec![];
+        for column in 0..std::cmp::max(expected.len(), instances.len()) {
+            let expected = expected.get(column).map(Vec::as_slice).unwrap_or(&[]);
+            let found = instances.get(column).map(Vec::as_slice).unwrap_or(&[]);
+            for index in 0..std::cmp::max(expected.len(), found.len()) {
+                if expected.get(index) != found.get(index) {
+                    mismatches.push(PublicInputMismatch {
+                        column,
+                        index,
+                        expected: expected.get(index).copied(),
+                        found: found.get(index).copied(),
+                    });
+                }
+            }
+        }
+        Ok(mismatches)
+    }
+
+    /// Recomputes the model's public outputs from the public inputs in `instances`, by running the model forward.
+    pub fn recompute_outputs(
+        &self,
+        instances: &[Vec<Fp>],
+    ) -> Result<Vec<Vec<Fp>>, Box<dyn std::error::Error>> {
+        self.recomputable_output_columns()?;
+        let inputs = instances
+            .iter()
+            .zip(self.model.graph.input_shapes())
+            .map(|(column, shape)| Tensor::new(Some(&column[..]), &shape))
+            .collect::<Result<Vec<_>, _>>()?;
+        let outputs = self.model.forward(&inputs)?.outputs;
+        let heads = self.settings.run_args.public_outputs;
+        Ok(heads
+            .indices(outputs.len())
+            .into_iter()
+            .map(|i| outputs[i].to_vec())
+            .collect())
+    }
+
+    /// Empties the model's output columns of `instances`, which the verifier can recompute with
+    /// [GraphCircuit::restore_elided_outputs], returning the indices of the elided columns. Fails if recomputing the
+    /// outputs doesn't reproduce them, as a verifier would then fail to verify the proof.
+    pub fn elide_outputs(
+        &self,
+        instances: &mut [Vec<Fp>],
+    ) -> Result<Vec<usize>, Box<dyn std::error::Error>> {
+        let columns = self.recomputable_output_columns()?;
+        let recomputed = self.recompute_outputs(instances)?;
+        if instances.get(columns.clone()) != Some(&recomputed[..]) {
+            return Err(Box::new(GraphError::ElidedOutputs(
+                "recomputing the outputs from the inputs doesn't reproduce them".to_string(),
+            )));
+        }
+        for column in columns.clone() {
+            instances[column].clear();
+        }
+        Ok(columns.collect())
+    }
+
+    /// Recomputes the output columns `elided` from `instances` with [GraphCircuit::elide_outputs].
+    pub fn restore_elided_outputs(
+        &self,
+        instances: &mut [Vec<Fp>],
+        elided: &[usize],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let columns = self.recomputable_output_columns()?;
+        if elided != columns.clone().collect_vec() || instances.len() < columns.end {
+            return Err(Box::new(GraphError::ElidedOutputs(format!(
+                "the proof elides instance columns {:?}, but the outputs are in columns {:?}",
+                elided, columns
+            ))));
+        }
+        let recomputed = self.recompute_outputs(instances)?;
+        for (column, values) in columns.zip(recomputed) {
+            instances[column] = values;
+        }
+        Ok(())
+    }
+
     /// Runs the forward pass of the model / graph of computations and any associated hashing.
     pub fn forward(
         &self,
@@ -732,23 +1753,13 @@ impl GraphCircuit {
     ) -> Result<GraphWitness, Box<dyn std::error::Error>> {
         let visibility = VarVisibility::from_args(self.settings.run_args)?;
         let mut processed_inputs = None;
-        let mut processed_params = None;
         let mut processed_outputs = None;
 
         if visibility.input.requires_processing() {
             processed_inputs = Some(GraphModules::forward(inputs, visibility.input)?);
         }
 
-        if visibility.params.requires_processing() {
-            let params = self.model.get_all_params();
-            if !params.is_empty() {
-                let flattened_params = Tensor::new(Some(&params), &[params.len()])?.combine()?;
-                processed_params = Some(GraphModules::forward(
-                    &[flattened_params],
-                    visibility.params,
-                )?);
-            }
-        }
+        let processed_params = self.process_params()?;
 
         let model_results = self.model.forward(inputs)?;
 
@@ -776,6 +1787,8 @@ impl GraphCircuit {
             processed_params,
             processed_outputs,
             max_lookup_inputs: model_results.max_lookup_inputs,
+            challenge: None,
+            output_range: None,
         })
     }
 
@@ -785,8 +1798,30 @@ impl GraphCircuit {
         run_args: &RunArgs,
         model_path: &std::path::PathBuf,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let model = Model::from_run_args(run_args, model_path)?;
-        Self::new(model, *run_args)
+        Self::from_run_args_normalized(run_args, model_path, None)
+    }
+
+    /// Create a new circuit from a set of input data and [RunArgs], normalizing the model's inputs within the circuit
+    /// if an [InputNormalization] is passed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_run_args_normalized(
+        run_args: &RunArgs,
+        model_path: &std::path::PathBuf,
+        input_normalization: Option<&InputNormalization>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut model = Model::from_run_args(run_args, model_path)?;
+        if let Some(normalization) = input_normalization {
+            // the statistics are evaluated over the raw input the prover supplies
+            if !run_args.input_statistics.is_empty() {
+                return Err(Box::new(GraphError::InvalidInputStatistics(
+                    "input statistics can't be disclosed for a normalized input".into(),
+                )));
+            }
+            model.graph.normalize_inputs(normalization)?;
+        }
+        let mut circuit = Self::new(model, *run_args)?;
+        circuit.settings.input_normalization = input_normalization.cloned();
+        Ok(circuit)
     }
 
     ///
@@ -808,7 +1843,7 @@ impl GraphCircuit {
         model_path: &std::path::PathBuf,
         check_mode: CheckMode,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let model = Model::from_run_args(&params.run_args, model_path)?;
+        let model = Model::from_settings(params, model_path)?;
         Self::new_from_settings(model, params.clone(), check_mode)
     }
 
@@ -911,12 +1946,18 @@ impl Circuit<Fp> for GraphCircuit {
     fn configure_with_params(cs: &mut ConstraintSystem<Fp>, params: Self::Params) -> Self::Config {
         let visibility = VarVisibility::from_args(params.run_args).unwrap();
 
+        // dead columns are eliminated from those the model's region alone is laid out over
+        let var_len = match params.run_args.eliminate_dead_columns {
+            true => AdviceColumnUsage::from_settings(&params).rows,
+            false => params.num_constraints,
+        };
         let mut vars = ModelVars::new(
             cs,
             params.run_args.logrows as usize,
-            params.num_constraints,
-            params.model_instance_shapes.clone(),
+            var_len,
+            params.padded_instance_shapes(),
             params.run_args.scale,
+            params.run_args.eliminate_dead_columns,
         );
 
         let base = Model::configure(
@@ -1047,3 +2088,1300 @@ impl Circuit<Fp> for GraphCircuit {
         Ok(())
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// The run args the tests build their circuits with, each test overrides only the fields it exercises.
+    pub(crate) fn test_run_args() -> RunArgs {
+        RunArgs {
+            scale: 7,
+            bits: 16,
+            logrows: 17,
+            batch_size: 1,
+            sequence_length: 1,
+            output_visibility: Visibility::Public,
+            ..Default::default()
+        }
+    }
+
+    /// [test_run_args] with the fewer lookup bits and rows the 1l_relu circuit needs.
+    pub(crate) fn relu_run_args() -> RunArgs {
+        RunArgs {
+            bits: 6,
+            logrows: 8,
+            ..test_run_args()
+        }
+    }
+
+    /// The circuit of the 1l_relu example at `run_args`.
+    pub(crate) fn relu_circuit(run_args: &RunArgs) -> GraphCircuit {
+        GraphCircuit::from_run_args(run_args, &"examples/onnx/1l_relu/network.onnx".into()).unwrap()
+    }
+
+    #[test]
+    fn test_graph_circuit_roundtrip() {
+        use crate::pfsys::{
+            create_keys, create_proof_circuit_kzg, srs::gen_srs, verify_proof_circuit_kzg, vk_diff,
+            TranscriptType,
+        };
+        use halo2_proofs::poly::commitment::ParamsProver;
+        use halo2_proofs::poly::kzg::{commitment::KZGCommitmentScheme, strategy::SingleStrategy};
+        use halo2curves::bn256::Bn256;
+
+        let run_args = relu_run_args();
+        let mut circuit = relu_circuit(&run_args);
+        let input = [-21, 19, 7].map(crate::fieldutils::i128_to_felt::<Fp>);
+        let witness = circuit
+            .forward(&[Tensor::new(Some(&input), &[1, 3]).unwrap()])
+            .unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let circuit_path = dir.path().join("network.circuit");
+        circuit.save(&circuit_path).unwrap();
+        let mut loaded = GraphCircuit::load(&circuit_path).unwrap();
+        assert_eq!(loaded.model, circuit.model);
+        assert_eq!(
+            bincode::serialize(&loaded).unwrap(),
+            bincode::serialize(&circuit).unwrap()
+        );
+
+        // the loaded circuit has the keys of the original, and proves against them
+        let params = gen_srs::<KZGCommitmentScheme<Bn256>>(run_args.logrows);
+        let pk =
+            create_keys::<KZGCommitmentScheme<Bn256>, Fp, GraphCircuit>(&circuit, &params).unwrap();
+        let loaded_pk =
+            create_keys::<KZGCommitmentScheme<Bn256>, Fp, GraphCircuit>(&loaded, &params).unwrap();
+        assert!(vk_diff(pk.get_vk(), loaded_pk.get_vk()).unwrap().identical);
+
+        let public_inputs = loaded
+            .prepare_public_inputs(&loaded.graph_witness.clone())
+            .unwrap();
+        let snark = create_proof_circuit_kzg(
+            loaded,
+            &params,
+            public_inputs,
+            &pk,
+            TranscriptType::EVM,
+            SingleStrategy::new(&params),
+            CheckMode::SAFE,
+        )
+        .unwrap();
+        verify_proof_circuit_kzg(
+            params.verifier_params(),
+            snark,
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_load_older_settings() {
+        // settings generated before the later run args existed
+        let settings =
+            GraphSettings::load(&std::path::PathBuf::from("tests/wasm/settings.json")).unwrap();
+        assert_eq!(settings.run_args.bits, 5);
+        assert_eq!(settings.run_args.pad_public_inputs, 0);
+        assert!(!settings.run_args.fuse_lookups);
+        assert_eq!(settings.run_args.public_outputs, OutputHeads::all());
+        assert!(!settings.run_args.integer_only);
+        assert_eq!(settings.run_args.sorted_input, None);
+    }
+
+    #[test]
+    fn test_max_logrows() {
+        let mut settings = GraphCircuit::default().settings;
+        settings.run_args.logrows = 17;
+        settings.num_constraints = 1 << 20;
+        // defaults to the largest public SRS
+        assert!(settings.check_max_logrows(None).is_ok());
+
+        let err = settings.check_max_logrows(Some(18)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "circuit needs k=20 which exceeds your configured limit of 18; reduce model size, scale, or split the circuit"
+        );
+
+        // the configured logrows count even if the circuit would fit in fewer
+        settings.num_constraints = 1;
+        settings.run_args.logrows = 19;
+        assert!(settings.check_max_logrows(Some(18)).is_err());
+        settings.run_args.logrows = 18;
+        assert!(settings.check_max_logrows(Some(18)).is_ok());
+    }
+
+    #[test]
+    fn test_input_normalization() {
+        use crate::circuit::ops::{Input, Op};
+
+        let normalization = InputNormalization {
+            axis: 1,
+            mean: vec![1.0, 3.0],
+            std: vec![0.5, 2.0],
+        };
+        let quantized = normalization.quantize(1).unwrap();
+        assert_eq!(quantized.mult, vec![4, 1]);
+        assert_eq!(quantized.shift, vec![-8, -6]);
+
+        let input = Input {
+            scale: 1,
+            normalization: Some(quantized),
+        };
+        // 2.0 and 5.0 at scale 1
+        let x = Tensor::<Fp>::new(Some(&[Fp::from(4), Fp::from(10)]), &[1, 2]).unwrap();
+        let res = Op::<Fp>::f(&input, &[x]).unwrap();
+        // (2.0 - 1.0) / 0.5 and (5.0 - 3.0) / 2.0 at scale 1
+        assert_eq!(
+            res.output,
+            Tensor::<Fp>::new(Some(&[Fp::from(4), Fp::from(2)]), &[1, 2]).unwrap()
+        );
+
+        let mismatched = InputNormalization {
+            std: vec![1.0],
+            ..normalization
+        };
+        assert!(mismatched.quantize(1).is_err());
+    }
+
+    #[test]
+    fn test_requantized_product_error() {
+        use crate::circuit::ops::{poly::PolyOp, Op};
+        use crate::fieldutils::{felt_to_i128, i128_to_felt};
+
+        const SCALE: u32 = 4;
+        // weights this small are mostly lost at the activation scale
+        let x = [1.0, 0.5, -0.25, 2.0];
+        let w = [0.02, -0.03, 0.015, 0.025];
+        let expected: f64 = x.iter().zip(w.iter()).map(|(a, b)| a * b).sum();
+
+        let dot = |weight_scale: u32| {
+            let quantize = |v: &[f64], scale: u32| {
+                Tensor::from(
+                    v.iter()
+                        .map(|v| i128_to_felt(quantize_float(v, 0.0, scale).unwrap())),
+                )
+            };
+            let product = SupportedOp::Linear(PolyOp::Einsum {
+                equation: "i,i->".to_string(),
+            });
+            let product_scale = product.out_scale(vec![SCALE, weight_scale], SCALE);
+            assert_eq!(product_scale, SCALE + weight_scale);
+
+            // requantize the product (at the combined scale) back down to the activation scale
+            let op = Requantized {
+                inner: Box::new(product),
+                denom: scale_to_multiplier(product_scale - SCALE) as u128,
+            };
+            assert_eq!(op.out_scale(vec![SCALE, weight_scale], SCALE), SCALE);
+            assert_eq!(op.required_lookups().len(), 1);
+
+            let res = op
+                .f(&[quantize(&x, SCALE), quantize(&w, weight_scale)])
+                .unwrap();
+            felt_to_i128(res.output[0]) as f64 / scale_to_multiplier(SCALE)
+        };
+
+        let single_scale_error = (dot(SCALE) - expected).abs();
+        let combined_scale_error = (dot(3 * SCALE) - expected).abs();
+        assert!(combined_scale_error < single_scale_error);
+        // only the rounding of the requantized output remains
+        assert!(combined_scale_error <= 0.5 / scale_to_multiplier(SCALE));
+    }
+    #[test]
+    fn test_input_statistics() {
+        let statistics: InputStatistics = "sum, nonzero, element:2".parse().unwrap();
+        assert_eq!(statistics.len(), 3);
+        assert_eq!(statistics.to_string(), "sum,nonzero,element:2");
+        assert_eq!(
+            statistics.to_string().parse::<InputStatistics>().unwrap(),
+            statistics
+        );
+        assert!("none".parse::<InputStatistics>().unwrap().is_empty());
+        assert!("mean".parse::<InputStatistics>().is_err());
+        assert!("element:64".parse::<InputStatistics>().is_err());
+
+        let input = [Fp::from(1), Fp::zero(), -Fp::from(3), Fp::from(4)];
+        assert_eq!(
+            statistics.evaluate(&input),
+            vec![Fp::from(2), Fp::from(3), -Fp::from(3)]
+        );
+        assert!(statistics.check(input.len()).is_ok());
+        assert!(statistics.check(2).is_err());
+    }
+    #[test]
+    fn test_malformed_input_is_rejected() {
+        use input::FileSourceInner;
+
+        let mut circuit = GraphCircuit::default();
+        let shapes = vec![vec![1, 2]];
+        let data = |v: Vec<f64>| vec![v.into_iter().map(FileSourceInner::Float).collect_vec()];
+        assert!(circuit
+            .load_file_data(&data(vec![1.0, 2.0]), &shapes, vec![7])
+            .is_ok());
+
+        for bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e300, -1e300] {
+            assert!(circuit
+                .load_file_data(&data(vec![1.0, bad]), &shapes, vec![7])
+                .is_err());
+        }
+        // the wrong number of elements or inputs
+        assert!(circuit
+            .load_file_data(&data(vec![1.0]), &shapes, vec![7])
+            .is_err());
+        assert!(circuit.load_file_data(&vec![], &shapes, vec![]).is_err());
+        assert!(circuit
+            .load_witness_file_data(&vec![vec![Fp::one(); 3]], &shapes)
+            .is_err());
+        assert!(checked_num_elements(&[usize::MAX, 2]).is_err());
+    }
+
+    #[test]
+    fn test_public_input_hash() {
+        let mut settings = GraphSettings::default();
+        // nothing is hashed, so no hash is recorded
+        assert_eq!(settings.public_input_hash(), None);
+        assert!(settings.check_public_input_hash(None).is_ok());
+
+        settings.run_args.input_visibility = Visibility::Hashed;
+        let hash = settings.public_input_hash().unwrap();
+        assert_eq!(hash.function, modules::HashFunction::Poseidon);
+        assert_eq!(hash.arity, modules::POSEIDON_LEN_GRAPH);
+        assert!(settings.check_public_input_hash(Some(&hash)).is_ok());
+        // a proof that doesn't record the hash is rejected
+        assert!(settings.check_public_input_hash(None).is_err());
+
+        // as are proofs against settings whose keys were generated with another hash
+        settings.hash_parameters = settings.public_input_hash();
+        assert!(settings.check_public_input_hash(Some(&hash)).is_ok());
+        let mut pinned = hash.clone();
+        pinned.check = "00".to_string();
+        settings.hash_parameters = Some(pinned);
+        assert!(settings.check_public_input_hash(Some(&hash)).is_err());
+        settings.hash_parameters = None;
+
+        // a proof hashed with different parameters is a clear error
+        let mut other = hash.clone();
+        other.partial_rounds += 1;
+        assert!(matches!(
+            settings.check_public_input_hash(Some(&other)),
+            Err(GraphError::PublicInputHashMismatch(_))
+        ));
+        settings.run_args.input_visibility = Visibility::Private;
+        assert!(settings.check_public_input_hash(Some(&hash)).is_err());
+    }
+
+    #[test]
+    fn test_elided_outputs_are_recomputed() {
+        let run_args = RunArgs {
+            scale: 0,
+            input_visibility: Visibility::Public,
+            ..test_run_args()
+        };
+        let path = "examples/onnx/1l_split/network.onnx".into();
+        let mut circuit = GraphCircuit::from_run_args(&run_args, &path).unwrap();
+        let input = Tensor::new(Some(&(0..24).map(Fp::from).collect_vec()), &[1, 6, 4]).unwrap();
+        let witness = circuit.forward(&[input]).unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+
+        let mut instances = public_inputs.clone();
+        let elided = circuit.elide_outputs(&mut instances).unwrap();
+        assert_eq!(elided, (1..7).collect_vec());
+        assert!(elided.iter().all(|c| instances[*c].is_empty()));
+        circuit
+            .restore_elided_outputs(&mut instances, &elided)
+            .unwrap();
+        assert_eq!(instances, public_inputs);
+
+        // outputs that the inputs don't reproduce can't be elided
+        let mut tampered = public_inputs.clone();
+        tampered[1][0] += Fp::one();
+        assert!(circuit.elide_outputs(&mut tampered).is_err());
+
+        // nor can private outputs
+        circuit.settings.run_args.output_visibility = Visibility::Private;
+        assert!(circuit.elide_outputs(&mut public_inputs.clone()).is_err());
+    }
+
+    #[test]
+    fn test_batched_inferences() {
+        let run_args = |batch_size| RunArgs {
+            batch_size,
+            ..test_run_args()
+        };
+        let path = "examples/onnx/1l_mlp/network.onnx".into();
+        let inputs = (0..4u64)
+            .map(|i| (0..3u64).map(|j| Fp::from((i * 3 + j) * 16)).collect_vec())
+            .collect_vec();
+
+        // the outputs of each inference on its own
+        let single = Model::from_run_args(&run_args(1), &path).unwrap();
+        let expected = inputs
+            .iter()
+            .flat_map(|input| {
+                let input = Tensor::new(Some(input), &[1, 3]).unwrap();
+                single.forward(&[input]).unwrap().outputs[0].to_vec()
+            })
+            .collect_vec();
+
+        // are the outputs of the four inferences proven in a single circuit
+        let mut circuit = GraphCircuit::from_run_args(&run_args(4), &path).unwrap();
+        assert_eq!(circuit.model.graph.output_shapes()[0][0], 4);
+        let batch = Tensor::new(Some(&inputs.concat()), &[4, 3]).unwrap();
+        let witness = circuit.forward(&[batch]).unwrap();
+        assert_eq!(witness.outputs[0], expected);
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+        assert!(diagnostics::diagnose_failure(&circuit, public_inputs)
+            .unwrap()
+            .is_empty());
+
+        // a model without a batch dimension can't be batched
+        let unbatched = "examples/onnx/mnist_gan/network.onnx".into();
+        assert!(Model::from_run_args(&run_args(4), &unbatched).is_err());
+    }
+
+    #[test]
+    fn test_fuse_lookups() {
+        use crate::circuit::lookup::LookupOp;
+
+        let run_args = |fuse_lookups| RunArgs {
+            scale: 4,
+            // scalar constants are only baked into the fused lookup when they are public
+            param_visibility: Visibility::Public,
+            fuse_lookups,
+            ..test_run_args()
+        };
+        // sigmoid(relu(3 * x + 0.5)), see examples/onnx/elementwise_chain/gen.py
+        let path = "examples/onnx/elementwise_chain/network.onnx".into();
+        let input = Tensor::new(
+            Some(&[-32, -8, 0, 24].map(crate::fieldutils::i128_to_felt::<Fp>)),
+            &[1, 4],
+        )
+        .unwrap();
+
+        let mut outputs = vec![];
+        let mut num_constraints = vec![];
+        for fuse_lookups in [false, true] {
+            let mut circuit = GraphCircuit::from_run_args(&run_args(fuse_lookups), &path).unwrap();
+            let fused = circuit
+                .model
+                .graph
+                .nodes
+                .values()
+                .any(|n| matches!(n.opkind(), SupportedOp::Nonlinear(LookupOp::Fused { .. })));
+            assert_eq!(fused, fuse_lookups);
+
+            let witness = circuit.forward(&[input.clone()]).unwrap();
+            circuit.load_graph_witness(&witness).unwrap();
+            let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+            assert!(diagnostics::diagnose_failure(&circuit, public_inputs)
+                .unwrap()
+                .is_empty());
+            outputs.push(witness.outputs);
+            num_constraints.push(circuit.settings.num_constraints);
+        }
+
+        // the whole chain is a single lookup, computing the same outputs in fewer rows
+        assert_eq!(outputs[0], outputs[1]);
+        assert!(num_constraints[1] < num_constraints[0]);
+    }
+
+    #[test]
+    fn test_rms_norm() {
+        use crate::circuit::hybrid::HybridOp;
+
+        let run_args = RunArgs {
+            scale: 5,
+            ..test_run_args()
+        };
+        // x / sqrt(mean(x^2) + 1e-5) * [1, 0.5, 2, 1], see examples/onnx/rms_norm/gen.py
+        let path = "examples/onnx/rms_norm/network.onnx".into();
+        let mut circuit = GraphCircuit::from_run_args(&run_args, &path).unwrap();
+        assert!(circuit
+            .model
+            .graph
+            .nodes
+            .values()
+            .any(|n| matches!(n.opkind(), SupportedOp::Hybrid(HybridOp::RmsNorm { .. }))));
+
+        let x = [-2.0, -0.5, 0.0, 1.5];
+        let input = Tensor::new(
+            Some(&x.map(|x| {
+                crate::fieldutils::i128_to_felt::<Fp>(quantize_float(&x, 0.0, 5).unwrap())
+            })),
+            &[1, 4],
+        )
+        .unwrap();
+        let witness = circuit.forward(&[input]).unwrap();
+
+        let rms = (x.iter().map(|x| x * x).sum::<f64>() / 4.0 + 1e-5).sqrt();
+        let expected = [1.0, 0.5, 2.0, 1.0]
+            .iter()
+            .zip(x)
+            .map(|(scale, x)| x / rms * scale);
+        let multiplier = scale_to_multiplier(circuit.settings.model_output_scales[0]);
+        for (output, expected) in witness.outputs[0].iter().zip(expected) {
+            let output = crate::fieldutils::felt_to_i128(*output) as f64 / multiplier;
+            assert!(
+                (output - expected).abs() < 0.05,
+                "{} != {}",
+                output,
+                expected
+            );
+        }
+
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+        assert!(diagnostics::diagnose_failure(&circuit, public_inputs)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_unsupported_op_policy() {
+        let run_args = |unsupported_ops| RunArgs {
+            scale: 4,
+            unsupported_ops,
+            ..test_run_args()
+        };
+        // gelu(mish(x)), see examples/onnx/unsupported_activations/gen.py
+        let path = "examples/onnx/unsupported_activations/network.onnx".into();
+        assert!(GraphCircuit::from_run_args(&run_args(UnsupportedOpPolicy::Error), &path).is_err());
+
+        let input = [-32, -8, 0, 24];
+        let prove = |policy| {
+            let mut circuit = GraphCircuit::from_run_args(&run_args(policy), &path).unwrap();
+            let felts = input.map(crate::fieldutils::i128_to_felt::<Fp>);
+            let witness = circuit
+                .forward(&[Tensor::new(Some(&felts), &[1, 4]).unwrap()])
+                .unwrap();
+            circuit.load_graph_witness(&witness).unwrap();
+            let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+            assert!(diagnostics::diagnose_failure(&circuit, public_inputs)
+                .unwrap()
+                .is_empty());
+            witness.outputs[0]
+                .iter()
+                .map(|x| crate::fieldutils::felt_to_i128(*x))
+                .collect::<Vec<_>>()
+        };
+
+        // skipped as the identity
+        assert_eq!(prove(UnsupportedOpPolicy::Skip), input.to_vec());
+        // both approximated by the ReLU
+        assert_eq!(prove(UnsupportedOpPolicy::Approximate), vec![0, 0, 0, 24]);
+    }
+
+    #[test]
+    fn test_batched_matmul() {
+        let run_args = RunArgs {
+            scale: 0,
+            ..test_run_args()
+        };
+        // [2, 3, 4] x [1, 4, 5], see examples/onnx/batched_matmul/gen.py
+        let path = "examples/onnx/batched_matmul/network.onnx".into();
+        let mut circuit = GraphCircuit::from_run_args(&run_args, &path).unwrap();
+
+        let a = Tensor::new(
+            Some(&(0..24).map(|i| i % 5 - 2).collect::<Vec<i128>>()),
+            &[2, 3, 4],
+        )
+        .unwrap();
+        let b = Tensor::new(
+            Some(&(0..20).map(|i| i % 3 - 1).collect::<Vec<i128>>()),
+            &[1, 4, 5],
+        )
+        .unwrap();
+        let felts = |t: &Tensor<i128>| t.map(crate::fieldutils::i128_to_felt::<Fp>);
+        let witness = circuit.forward(&[felts(&a), felts(&b)]).unwrap();
+
+        // the batch dimension of `b` is broadcast over the batch of `a`
+        let expected = crate::tensor::ops::matmul(&[a, b]).unwrap();
+        assert_eq!(expected.dims(), &[2, 3, 5]);
+        assert_eq!(witness.outputs[0], felts(&expected).to_vec());
+
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+        assert!(diagnostics::diagnose_failure(&circuit, public_inputs)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_output_scale() {
+        let run_args = RunArgs {
+            scale: 2,
+            ..test_run_args()
+        };
+        let path = "examples/onnx/batched_matmul/network.onnx".into();
+        // values exact at scale 2, such that the products are exact at scale 4
+        let a = (0..24)
+            .map(|i| (i % 7 - 3) as f32 * 0.25)
+            .collect::<Vec<_>>();
+        let b = (0..20)
+            .map(|i| (i % 5 - 2) as f32 * 0.75)
+            .collect::<Vec<_>>();
+        let inputs = [
+            Tensor::new(Some(&a), &[2, 3, 4]).unwrap(),
+            Tensor::new(Some(&b), &[1, 4, 5]).unwrap(),
+        ];
+
+        // the product of two inputs at scale 2 is at scale 4
+        let exact = Model::from_run_args(&run_args, &path).unwrap();
+        assert_eq!(exact.graph.get_output_scales(), vec![4]);
+        let expected = exact.forward_float(&inputs).unwrap();
+
+        let requantized = RunArgs {
+            output_scale: Some(1),
+            ..run_args
+        };
+        let mut circuit = GraphCircuit::from_run_args(&requantized, &path).unwrap();
+        assert_eq!(circuit.settings.model_output_scales, vec![1]);
+        assert_eq!(circuit.settings.instance_scales(), vec![Some(1)]);
+        // rounded to the nearest half
+        let outputs = circuit.model.forward_float(&inputs).unwrap();
+        for (output, expected) in outputs[0].iter().zip(expected[0].iter()) {
+            assert_eq!((output * 2.0).fract(), 0.0);
+            assert!((output - expected).abs() <= 0.25);
+        }
+        assert_ne!(outputs[0], expected[0]);
+
+        let quantized = inputs
+            .iter()
+            .map(|t| {
+                t.map(|x| {
+                    crate::fieldutils::i128_to_felt::<Fp>(
+                        quantize_float(&(x as f64), 0.0, 2).unwrap(),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        let witness = circuit.forward(&quantized).unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+        assert!(diagnostics::diagnose_failure(&circuit, public_inputs)
+            .unwrap()
+            .is_empty());
+
+        // the outputs can only be requantized down
+        let above = RunArgs {
+            output_scale: Some(5),
+            ..run_args
+        };
+        assert!(GraphCircuit::from_run_args(&above, &path).is_err());
+    }
+
+    #[test]
+    fn test_check_params_hash() {
+        let run_args = RunArgs {
+            input_visibility: Visibility::Private,
+            param_visibility: Visibility::Hashed,
+            ..test_run_args()
+        };
+        let path = "examples/onnx/1l_mlp/network.onnx".into();
+        let mut circuit = GraphCircuit::from_run_args(&run_args, &path).unwrap();
+        let input = Tensor::new(Some(&[16, -32, 48].map(Fp::from)), &[1, 3]).unwrap();
+        let witness = circuit.forward(&[input]).unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+        assert!(
+            diagnostics::diagnose_failure(&circuit, public_inputs.clone())
+                .unwrap()
+                .is_empty()
+        );
+
+        // the proven instances commit to the model's weights, while the input stays private
+        circuit.check_params_hash(&public_inputs).unwrap();
+        let column = circuit.instance_index(InstanceColumn::Module(0)).unwrap();
+        let mut tampered = public_inputs.clone();
+        tampered[column][0] += Fp::one();
+        assert!(circuit.check_params_hash(&tampered).is_err());
+
+        // a model with other weights doesn't match them
+        let mut other = circuit.clone();
+        for node in other.model.graph.nodes.values_mut() {
+            if let NodeType::Node(Node {
+                opkind: SupportedOp::Constant(c),
+                ..
+            }) = node
+            {
+                c.quantized_values[0] += Fp::one();
+                break;
+            }
+        }
+        assert!(other.check_params_hash(&public_inputs).is_err());
+
+        // nor can unhashed params be checked
+        circuit.settings.run_args.param_visibility = Visibility::Private;
+        assert!(circuit.check_params_hash(&public_inputs).is_err());
+    }
+
+    #[test]
+    fn test_lookup_ranges() {
+        let run_args = test_run_args();
+        let circuit = relu_circuit(&run_args);
+        let input = [-51, 319, 74].map(crate::fieldutils::i128_to_felt::<Fp>);
+        let input = Tensor::new(Some(&input), &[1, 3]).unwrap();
+        let ranges = circuit.lookup_ranges(&[input.clone()]).unwrap();
+        assert_eq!(ranges.len(), 1);
+        assert_eq!((ranges[0].min, ranges[0].max), (-51, 319));
+        // the bits have to cover the widest range
+        let max = circuit.model.forward(&[input]).unwrap().max_lookup_inputs;
+        assert_eq!(max, 319);
+
+        // the lookups of a recurrent body are recorded under the subgraph node, over every step
+        let run_args = RunArgs {
+            sequence_length: 3,
+            ..run_args
+        };
+        let path = "examples/onnx/rnn_sequence/network.onnx".into();
+        let circuit = GraphCircuit::from_run_args(&run_args, &path).unwrap();
+        let input = [64, -64, 32, 96, -128, 64].map(crate::fieldutils::i128_to_felt::<Fp>);
+        let input = Tensor::new(Some(&input), &[3, 1, 2]).unwrap();
+        let ranges = circuit.lookup_ranges(&[input]).unwrap();
+        assert!(!ranges.is_empty());
+        assert!(ranges
+            .iter()
+            .all(|r| r.node.contains('/') && r.min <= r.max));
+    }
+
+    #[test]
+    fn test_dropout_is_identity() {
+        let run_args = test_run_args();
+        let with_dropout = "examples/onnx/1l_dropout/network.onnx".into();
+        let without_dropout = "examples/onnx/1l_relu/network.onnx".into();
+        let input = [-51, 319, 74].map(crate::fieldutils::i128_to_felt::<Fp>);
+        let input = Tensor::new(Some(&input), &[1, 3]).unwrap();
+
+        let mut circuit = GraphCircuit::from_run_args(&run_args, &with_dropout).unwrap();
+        let expected = GraphCircuit::from_run_args(&run_args, &without_dropout)
+            .unwrap()
+            .forward(&[input.clone()])
+            .unwrap();
+        let witness = circuit.forward(&[input]).unwrap();
+        assert_eq!(witness.outputs, expected.outputs);
+
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+        assert!(diagnostics::diagnose_failure(&circuit, public_inputs)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_instance_layout_follows_visibility() {
+        let model_path = "examples/onnx/1l_relu/network.onnx".into();
+        let input = [-51, 319, 74].map(crate::fieldutils::i128_to_felt::<Fp>);
+        let input = Tensor::new(Some(&input), &[1, 3]).unwrap();
+
+        for (input_visibility, output_visibility, pad_public_inputs) in [
+            (Visibility::Public, Visibility::Public, 0),
+            (Visibility::Public, Visibility::Private, 0),
+            (Visibility::Private, Visibility::Public, 0),
+            (Visibility::Private, Visibility::Public, 8),
+            (Visibility::Public, Visibility::Hashed, 0),
+            (Visibility::Private, Visibility::Hashed, 0),
+        ] {
+            let run_args = RunArgs {
+                input_visibility,
+                output_visibility,
+                pad_public_inputs,
+                ..test_run_args()
+            };
+            let mut circuit = GraphCircuit::from_run_args(&run_args, &model_path).unwrap();
+            let witness = circuit.forward(&[input.clone()]).unwrap();
+            circuit.load_graph_witness(&witness).unwrap();
+            let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+
+            let layout = circuit.instance_layout();
+            assert_eq!(layout.len(), public_inputs.len());
+            // the layout can be recovered from the settings alone, as the split proof checks do
+            assert_eq!(circuit.settings.instance_layout(), layout);
+            let input_column = circuit.instance_index(InstanceColumn::Input(0));
+            let output_column = circuit.instance_index(InstanceColumn::Output(0));
+            assert_eq!(input_column.is_some(), input_visibility.is_public());
+            assert_eq!(output_column.is_some(), output_visibility.is_public());
+            assert_eq!(
+                circuit.instance_index(InstanceColumn::Padding).is_some(),
+                pad_public_inputs > 0
+            );
+            assert_eq!(
+                circuit.instance_index(InstanceColumn::Module(0)).is_some(),
+                output_visibility.is_hashed()
+            );
+            if let Some(column) = input_column {
+                assert_eq!(public_inputs[column], witness.inputs[0]);
+            }
+            if let Some(column) = output_column {
+                assert_eq!(public_inputs[column], witness.outputs[0]);
+            }
+
+            // the circuit lays its instances out in the same order the public inputs are assembled in
+            assert!(diagnostics::diagnose_failure(&circuit, public_inputs)
+                .unwrap()
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn test_quantized_weights() {
+        let run_args = |scale, weight_scale| RunArgs {
+            scale,
+            weight_scale,
+            ..test_run_args()
+        };
+        let path = "examples/onnx/1l_conv/network.onnx".into();
+        let weights = |scale, weight_scale| {
+            Model::from_run_args(&run_args(scale, weight_scale), &path)
+                .unwrap()
+                .quantized_weights()
+        };
+
+        let at_7 = weights(7, None);
+        let kernel = at_7.iter().find(|w| w.name == "kernel").unwrap();
+        assert_eq!(kernel.scale, 7);
+        assert_eq!(kernel.values.len(), kernel.dims.iter().product::<usize>());
+        // biases are added to the product of the input and the kernel, so are at the sum of their scales
+        if let Some(bias) = at_7.iter().find(|w| w.name == "bias") {
+            assert_eq!(bias.scale, 14);
+        }
+
+        // the values are the float weights rounded at the weight's scale, so one more bit of scale doubles them
+        let at_8 = weights(8, None);
+        for (w7, w8) in at_7.iter().zip(&at_8) {
+            assert_eq!((&w7.node, &w7.name), (&w8.node, &w8.name));
+            assert_eq!(w7.scale + 1, w8.scale);
+            for (v7, v8) in w7.values.iter().zip(&w8.values) {
+                assert!((v8 - 2 * v7).abs() <= 1);
+            }
+        }
+
+        // with a separate weight scale, weights are quantized at it
+        let separate = weights(7, Some(10));
+        let kernel = separate.iter().find(|w| w.name == "kernel").unwrap();
+        assert_eq!(kernel.scale, 10);
+    }
+
+    #[test]
+    fn test_challenge_binds_proof() {
+        use crate::pfsys::{create_keys, create_proof_circuit_kzg, srs, verify_proof_circuit_kzg};
+        use halo2_proofs::poly::commitment::ParamsProver;
+        use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+        use halo2_proofs::poly::kzg::strategy::SingleStrategy as KZGSingleStrategy;
+
+        // the challenge is the only instance
+        let run_args = RunArgs {
+            output_visibility: Visibility::Private,
+            challenge: true,
+            ..relu_run_args()
+        };
+        let path = "examples/onnx/1l_identity/network.onnx".into();
+        let mut circuit = GraphCircuit::from_run_args(&run_args, &path).unwrap();
+        assert_eq!(circuit.settings.model_instance_shapes, vec![vec![1]]);
+        let shape = circuit.model.graph.input_shapes()[0].clone();
+        let values = (0..shape.iter().product::<usize>() as u64).map(Fp::from);
+        let input = Tensor::new(Some(&values.collect_vec()), &shape).unwrap();
+        let mut witness = circuit.forward(&[input]).unwrap();
+
+        circuit.load_graph_witness(&witness).unwrap();
+        assert!(circuit.prepare_public_inputs(&witness).is_err());
+
+        let challenge = Fp::from(0xc0ffee);
+        witness.challenge = Some(challenge);
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+        assert_eq!(public_inputs, vec![vec![challenge]]);
+
+        let params = srs::gen_srs::<KZGCommitmentScheme<bn256::Bn256>>(run_args.logrows);
+        let pk =
+            create_keys::<KZGCommitmentScheme<bn256::Bn256>, Fp, GraphCircuit>(&circuit, &params)
+                .unwrap();
+        let snark = create_proof_circuit_kzg(
+            circuit.clone(),
+            &params,
+            public_inputs,
+            &pk,
+            crate::pfsys::TranscriptType::Blake,
+            KZGSingleStrategy::new(&params),
+            CheckMode::SAFE,
+        )
+        .unwrap();
+
+        let settings = &circuit.settings;
+        assert!(settings
+            .check_challenge(&snark.instances, Some(challenge))
+            .is_ok());
+        assert!(settings
+            .check_challenge(&snark.instances, Some(challenge + Fp::one()))
+            .is_err());
+        assert!(settings.check_challenge(&snark.instances, None).is_err());
+        let mut unchallenged = settings.clone();
+        unchallenged.run_args.challenge = false;
+        assert!(unchallenged
+            .check_challenge(&snark.instances, Some(challenge))
+            .is_err());
+
+        let verifier_params = params.verifier_params();
+        let verify = |snark| {
+            let strategy = KZGSingleStrategy::new(verifier_params);
+            verify_proof_circuit_kzg(verifier_params, snark, pk.get_vk(), strategy)
+        };
+        assert!(verify(snark.clone()).is_ok());
+        // the challenge is part of the transcript, so the proof doesn't verify against any other
+        let mut mismatched = snark;
+        mismatched.instances[0][0] += Fp::one();
+        assert!(verify(mismatched).is_err());
+    }
+
+    #[test]
+    fn test_classify_proves_class() {
+        use halo2_proofs::dev::MockProver;
+
+        let run_args = RunArgs {
+            output_visibility: Visibility::Private,
+            classify: true,
+            ..relu_run_args()
+        };
+        let path = "examples/onnx/1l_relu/network.onnx".into();
+        // the class is the only public output
+        let mut public = run_args;
+        public.output_visibility = Visibility::Public;
+        assert!(GraphCircuit::from_run_args(&public, &path).is_err());
+
+        let mut circuit = relu_circuit(&run_args);
+        let shape = circuit.model.graph.input_shapes()[0].clone();
+        let mut class_shape = shape.clone();
+        *class_shape.last_mut().unwrap() = 1;
+        assert_eq!(circuit.settings.model_instance_shapes, vec![class_shape]);
+
+        // the logit at index 1 of the last axis is the largest
+        let last = *shape.last().unwrap();
+        let values = (0..shape.iter().product::<usize>())
+            .map(|i| match i % last {
+                1 => Fp::from(20),
+                j => Fp::from(j as u64),
+            })
+            .collect_vec();
+        let input = Tensor::new(Some(&values), &shape).unwrap();
+        let witness = circuit.forward(&[input]).unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+        assert!(public_inputs[0].iter().all(|c| *c == Fp::one()));
+
+        let prover = MockProver::run(run_args.logrows, &circuit, public_inputs.clone()).unwrap();
+        assert!(prover.verify().is_ok());
+
+        // claiming any other class fails
+        let mut wrong_class = public_inputs;
+        wrong_class[0][0] = Fp::zero();
+        let prover = MockProver::run(run_args.logrows, &circuit, wrong_class).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_output_range_asserts_bounds() {
+        use halo2_proofs::dev::MockProver;
+
+        let run_args = RunArgs {
+            output_visibility: Visibility::Private,
+            assert_output_range: true,
+            ..relu_run_args()
+        };
+        let path = "examples/onnx/1l_relu/network.onnx".into();
+        // only the bounds are public
+        let mut public = run_args;
+        public.output_visibility = Visibility::Public;
+        assert!(GraphCircuit::from_run_args(&public, &path).is_err());
+
+        let mut circuit = relu_circuit(&run_args);
+        assert_eq!(circuit.settings.model_instance_shapes, vec![vec![2]]);
+        let shape = circuit.model.graph.input_shapes()[0].clone();
+        let values = (0..shape.iter().product::<usize>() as u64)
+            .map(|i| Fp::from(i % 8))
+            .collect_vec();
+        let input = Tensor::new(Some(&values), &shape).unwrap();
+        let mut witness = circuit.forward(&[input]).unwrap();
+
+        // the outputs are within [0, 20 / 2^7]
+        let range: OutputRange = "0,0.15625".parse().unwrap();
+        let bounds = range.quantize(run_args.scale).unwrap();
+        assert_eq!(bounds, [Fp::zero(), Fp::from(20)]);
+        witness.output_range = Some(bounds);
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+        assert_eq!(public_inputs, vec![bounds.to_vec()]);
+        let prover = MockProver::run(run_args.logrows, &circuit, public_inputs.clone()).unwrap();
+        assert!(prover.verify().is_ok());
+        circuit
+            .settings
+            .check_output_range(&public_inputs, Some(range))
+            .unwrap();
+        let other: OutputRange = "0,0.125".parse().unwrap();
+        assert!(circuit
+            .settings
+            .check_output_range(&public_inputs, Some(other))
+            .is_err());
+
+        // bounds that don't contain the outputs (some of which are 0) can't be proven
+        let outside = [Fp::from(8), Fp::from(20)];
+        witness.output_range = Some(outside);
+        circuit.load_graph_witness(&witness).unwrap();
+        assert!(circuit.prepare_public_inputs(&witness).is_err());
+        let prover = MockProver::run(run_args.logrows, &circuit, vec![outside.to_vec()]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_sorted_input_is_canonical() {
+        use halo2_proofs::dev::MockProver;
+
+        let run_args = RunArgs {
+            input_visibility: Visibility::Public,
+            sorted_input: Some(0),
+            ..relu_run_args()
+        };
+        let path = "examples/onnx/1l_relu/network.onnx".into();
+        // only a public input's order is seen by the verifier, and the model has a single input
+        let private = RunArgs {
+            input_visibility: Visibility::Private,
+            ..run_args
+        };
+        assert!(GraphCircuit::from_run_args(&private, &path).is_err());
+        let missing = RunArgs {
+            sorted_input: Some(1),
+            ..run_args
+        };
+        assert!(GraphCircuit::from_run_args(&missing, &path).is_err());
+
+        let mut circuit = relu_circuit(&run_args);
+        let shape = circuit.model.graph.input_shapes()[0].clone();
+        let len = shape.iter().product::<usize>();
+        // the relu'd outputs of non-negative inputs are the inputs
+        let prove = |circuit: &mut GraphCircuit, values: Vec<Fp>| {
+            let input = Tensor::new(Some(&values), &shape).unwrap();
+            let witness = circuit.forward(&[input]).unwrap();
+            circuit.load_graph_witness(&witness).unwrap();
+            let prepared = circuit.prepare_public_inputs(&witness);
+            let instances = vec![values.clone(), values];
+            if let Ok(prepared) = &prepared {
+                assert_eq!(*prepared, instances);
+            }
+            let prover = MockProver::run(run_args.logrows, &*circuit, instances).unwrap();
+            (prepared.is_ok(), prover.verify().is_ok())
+        };
+
+        let sorted = (0..len as u64).map(Fp::from).collect_vec();
+        assert_eq!(prove(&mut circuit, sorted.clone()), (true, true));
+        // reordering the input (and so its outputs) fails to prove
+        let mut unsorted = sorted.clone();
+        unsorted.swap(0, len - 1);
+        assert_eq!(prove(&mut circuit, unsorted.clone()), (false, false));
+        // duplicates are sorted, unless deduplicated
+        let mut duplicated = sorted.clone();
+        duplicated[1] = duplicated[0];
+        assert_eq!(prove(&mut circuit, duplicated.clone()), (true, true));
+        // elements further apart than the lookup table covers can't be proven sorted
+        let mut wide = sorted.clone();
+        wide[0] = -Fp::from(1u64 << (run_args.bits - 1));
+        let input = Tensor::new(Some(&wide), &shape).unwrap();
+        let witness = circuit.forward(&[input]).unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+        let err = circuit.prepare_public_inputs(&witness).unwrap_err();
+        assert!(err.to_string().contains("lookup tables"));
+
+        let dedup = RunArgs {
+            dedup_sorted_input: true,
+            ..run_args
+        };
+        let mut circuit = relu_circuit(&dedup);
+        assert_eq!(prove(&mut circuit, sorted), (true, true));
+        assert_eq!(prove(&mut circuit, duplicated), (false, false));
+        assert_eq!(prove(&mut circuit, unsorted), (false, false));
+    }
+
+    #[test]
+    fn test_sorted_input_at_a_scale() {
+        use crate::graph::utilities::quantize_float;
+        use halo2_proofs::dev::MockProver;
+
+        // at a scale of 2 the differences of the quantized inputs are 4 times those of the float inputs
+        let run_args = RunArgs {
+            scale: 2,
+            input_visibility: Visibility::Public,
+            sorted_input: Some(0),
+            ..relu_run_args()
+        };
+        let mut circuit = relu_circuit(&run_args);
+        let shape = circuit.model.graph.input_shapes()[0].clone();
+        let prove = |circuit: &mut GraphCircuit, values: &[f64]| {
+            let values = values
+                .iter()
+                .map(|x| {
+                    crate::fieldutils::i128_to_felt(quantize_float(x, 0.0, run_args.scale).unwrap())
+                })
+                .collect_vec();
+            let input = Tensor::new(Some(&values), &shape).unwrap();
+            let witness = circuit.forward(&[input]).unwrap();
+            circuit.load_graph_witness(&witness).unwrap();
+            let prepared = circuit.prepare_public_inputs(&witness);
+            let instances = vec![values, witness.outputs[0].clone()];
+            let proven = MockProver::run(run_args.logrows, &*circuit, instances)
+                .map_or(false, |prover| prover.verify().is_ok());
+            (prepared.is_ok(), proven)
+        };
+
+        // quantized to [-16, 16, 31], whose first gap is the largest input the lookup tables cover
+        let widest = 2f64.powi(run_args.bits as i32 - 1) / 4.0;
+        assert_eq!(
+            prove(&mut circuit, &[-widest / 2.0, widest / 2.0, 7.75]),
+            (true, true)
+        );
+        assert_eq!(
+            prove(&mut circuit, &[-widest / 2.0 - 0.25, widest / 2.0, 7.75]),
+            (false, false)
+        );
+        assert_eq!(
+            prove(&mut circuit, &[widest / 2.0, -widest / 2.0, 7.75]),
+            (false, false)
+        );
+    }
+
+    #[test]
+    fn test_eliminate_dead_columns() {
+        use crate::graph::columns::AdviceColumnUsage;
+        use halo2_proofs::dev::MockProver;
+
+        let run_args = relu_run_args();
+        // the advice columns allocated, and the rows of each of the model's
+        let configure = |circuit: &GraphCircuit| {
+            let mut cs = ConstraintSystem::default();
+            let config = GraphCircuit::configure_with_params(&mut cs, circuit.params());
+            (
+                cs.num_advice_columns(),
+                config.model_config.vars.advices[0].col_size(),
+            )
+        };
+
+        // pad the public inputs such that the zero padding (laid out in the model's region) fills its first column
+        // exactly, leaving the buffer column for duplicated cells dead
+        let circuit = relu_circuit(&run_args);
+        let usage = AdviceColumnUsage::from_settings(&circuit.settings);
+        assert!(usage.rows < usage.col_size);
+        let num_instances = circuit.settings.total_instances().iter().sum::<usize>();
+        let run_args = RunArgs {
+            pad_public_inputs: num_instances + usage.col_size - usage.rows,
+            ..run_args
+        };
+
+        let mut outputs = vec![];
+        let mut columns = vec![];
+        for eliminate_dead_columns in [false, true] {
+            let run_args = RunArgs {
+                eliminate_dead_columns,
+                ..run_args
+            };
+            let mut circuit = relu_circuit(&run_args);
+            let usage = AdviceColumnUsage::from_settings(&circuit.settings);
+            assert_eq!(usage.rows, usage.col_size);
+            assert_eq!(usage.pack(), 1);
+            assert_eq!(usage.dead(), 1);
+            assert_eq!(usage.eliminated(), 3);
+
+            let shape = circuit.model.graph.input_shapes()[0].clone();
+            let values = (0..shape.iter().product::<usize>()).map(|i| match i % 2 {
+                0 => Fp::from(i as u64),
+                _ => -Fp::from(i as u64),
+            });
+            let input = Tensor::new(Some(&values.collect_vec()), &shape).unwrap();
+            let witness = circuit.forward(&[input]).unwrap();
+            circuit.load_graph_witness(&witness).unwrap();
+            let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+
+            // the layout is sound over the packed columns
+            let prover = MockProver::run(run_args.logrows, &circuit, public_inputs).unwrap();
+            assert!(prover.verify().is_ok());
+            outputs.push(witness.outputs);
+
+            let (num_advice_columns, col_size) = configure(&circuit);
+            assert_eq!(col_size, usage.col_size);
+            columns.push(num_advice_columns);
+        }
+
+        // the narrower circuit computes the same outputs
+        assert_eq!(outputs[0], outputs[1]);
+        assert_eq!(columns[0], columns[1] + 3);
+    }
+
+    #[test]
+    fn test_audit_public_inputs() {
+        let run_args = RunArgs {
+            input_visibility: Visibility::Public,
+            ..relu_run_args()
+        };
+        let path = "examples/onnx/1l_identity/network.onnx".into();
+        let mut circuit = GraphCircuit::from_run_args(&run_args, &path).unwrap();
+        let shape = circuit.model.graph.input_shapes()[0].clone();
+        let values = (0..shape.iter().product::<usize>() as u64).map(Fp::from);
+        let input = Tensor::new(Some(&values.collect_vec()), &shape).unwrap();
+        let witness = circuit.forward(&[input.clone()]).unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+
+        let mut audit = |instances: &[Vec<Fp>]| {
+            circuit
+                .audit_public_inputs(&[input.clone()], instances)
+                .unwrap()
+        };
+        assert!(audit(&public_inputs).is_empty());
+
+        // an output altered after proving
+        let mut tampered = public_inputs.clone();
+        tampered[1][0] += Fp::one();
+        assert_eq!(
+            audit(&tampered),
+            vec![PublicInputMismatch {
+                column: 1,
+                index: 0,
+                expected: Some(public_inputs[1][0]),
+                found: Some(tampered[1][0]),
+            }]
+        );
+
+        let mut truncated = public_inputs.clone();
+        truncated[1].pop();
+        let mismatches = audit(&truncated);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].found, None);
+    }
+
+    #[test]
+    fn test_validate_public_inputs() {
+        use crate::pfsys::{create_keys, srs};
+        use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+
+        let run_args = RunArgs {
+            input_visibility: Visibility::Public,
+            output_visibility: Visibility::Private,
+            pad_public_inputs: 4,
+            ..relu_run_args()
+        };
+        let path = "examples/onnx/1l_identity/network.onnx".into();
+        let mut circuit = GraphCircuit::from_run_args(&run_args, &path).unwrap();
+        let shape = circuit.model.graph.input_shapes()[0].clone();
+        let values = (0..shape.iter().product::<usize>() as u64).map(Fp::from);
+        let input = Tensor::new(Some(&values.collect_vec()), &shape).unwrap();
+        let witness = circuit.forward(&[input]).unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+
+        let params = srs::gen_srs::<KZGCommitmentScheme<bn256::Bn256>>(run_args.logrows);
+        let pk =
+            create_keys::<KZGCommitmentScheme<bn256::Bn256>, Fp, GraphCircuit>(&circuit, &params)
+                .unwrap();
+        let settings = &circuit.settings;
+        let validate =
+            |public_inputs: &[Vec<Fp>]| settings.validate_public_inputs(pk.get_vk(), public_inputs);
+        assert!(validate(&public_inputs).is_ok());
+
+        // a missing or extra column
+        assert!(validate(&public_inputs[..1]).is_err());
+        let mut extra = public_inputs.clone();
+        extra.push(vec![Fp::zero()]);
+        assert!(validate(&extra).is_err());
+        // a short or long column
+        let mut short = public_inputs.clone();
+        short[0].pop();
+        assert!(validate(&short).is_err());
+        let mut long = public_inputs.clone();
+        long[0].push(Fp::zero());
+        assert!(validate(&long).is_err());
+        // an input out of the range of the lookups
+        let mut out_of_range = public_inputs.clone();
+        out_of_range[0][0] = Fp::from(1 << run_args.bits);
+        assert!(matches!(
+            validate(&out_of_range),
+            Err(GraphError::PublicInputRange(0, 0, _))
+        ));
+        // non-zero padding
+        let mut padding = public_inputs.clone();
+        *padding[1].last_mut().unwrap() = Fp::one();
+        assert!(validate(&padding).is_err());
+    }
+
+    #[test]
+    fn test_public_inputs_range() {
+        let run_args = RunArgs {
+            input_visibility: Visibility::Public,
+            input_statistics: "sum".parse().unwrap(),
+            ..relu_run_args()
+        };
+        let settings = relu_circuit(&run_args).settings;
+        let small = vec![Fp::from(31); 3];
+        let large = vec![Fp::from(1 << run_args.bits); 3];
+
+        // outputs and statistics may exceed the range of the lookups, e.g the sum of the inputs
+        let public_inputs = vec![small.clone(), large.clone(), vec![Fp::from(93)]];
+        assert!(settings.check_public_inputs_range(&public_inputs).is_ok());
+        // the inputs can't
+        let public_inputs = vec![large, small, vec![Fp::from(93)]];
+        assert!(matches!(
+            settings.check_public_inputs_range(&public_inputs),
+            Err(GraphError::PublicInputRange(0, 0, _))
+        ));
+    }
+
+    #[test]
+    fn test_params_commitment() {
+        let run_args = RunArgs {
+            input_visibility: Visibility::Hashed,
+            param_visibility: Visibility::Hashed,
+            output_visibility: Visibility::Hashed,
+            ..test_run_args()
+        };
+        let path = "examples/onnx/1l_mlp/network.onnx".into();
+        let mut circuit = GraphCircuit::from_run_args(&run_args, &path).unwrap();
+        // the vendor publishes the commitment, which survives a roundtrip through a file
+        let commitment = circuit.params_commitment().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let commitment_path = dir.path().join("params_commitment.json");
+        commitment.save(&commitment_path).unwrap();
+        assert_eq!(
+            ParamsCommitment::load(&commitment_path).unwrap(),
+            commitment
+        );
+
+        let shape = circuit.model.graph.input_shapes()[0].clone();
+        let values = (0..shape.iter().product::<usize>() as u64).map(Fp::from);
+        let input = Tensor::new(Some(&values.collect_vec()), &shape).unwrap();
+        let witness = circuit.forward(&[input]).unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+
+        // the client checks against the commitment alone, finding the params hash between the input and output hashes
+        let settings = &circuit.settings;
+        assert!(settings
+            .check_params_commitment(&public_inputs, &commitment)
+            .is_ok());
+        assert!(circuit.check_params_hash(&public_inputs).is_ok());
+        let other = ParamsCommitment {
+            hash: commitment.hash + Fp::one(),
+            ..commitment.clone()
+        };
+        assert!(settings
+            .check_params_commitment(&public_inputs, &other)
+            .is_err());
+
+        // the commitment is only exposed by circuits hashing their params
+        let mut public_params = settings.clone();
+        public_params.run_args.param_visibility = Visibility::Public;
+        assert!(public_params
+            .check_params_commitment(&public_inputs, &commitment)
+            .is_err());
+        circuit.settings.run_args.param_visibility = Visibility::Private;
+        assert!(circuit.params_commitment().is_err());
+    }
+}
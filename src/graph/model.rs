@@ -1,18 +1,27 @@
+#[cfg(not(target_arch = "wasm32"))]
+use super::cast_is_identity;
+#[cfg(not(target_arch = "wasm32"))]
+use super::check_degenerate_weights;
+use super::check_input_lens;
+use super::error_bounds::ErrorBoundReport;
 use super::extract_const_quantized_values;
 use super::node::*;
+use super::quantize_float;
 use super::scale_to_multiplier;
 use super::vars::*;
 use super::GraphError;
 use super::GraphSettings;
+use super::InputNormalization;
 use crate::circuit::hybrid::HybridOp;
+use crate::circuit::poly::PolyOp;
 use crate::circuit::region::RegionCtx;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::circuit::Input;
 use crate::circuit::Unknown;
-use crate::fieldutils::felt_to_i128;
+use crate::fieldutils::{felt_to_i128, i128_to_felt};
 use crate::{
-    circuit::{lookup::LookupOp, BaseConfig as PolyConfig, CheckMode, Op},
-    tensor::{Tensor, ValTensor},
+    circuit::{lookup::LookupOp, BaseConfig as PolyConfig, CheckMode, Op, Tolerance},
+    tensor::{Tensor, ValTensor, ValType},
     RunArgs,
 };
 use halo2curves::bn256::Fr as Fp;
@@ -31,18 +40,26 @@ use serde::Serialize;
 use tract_onnx;
 #[cfg(not(target_arch = "wasm32"))]
 use tract_onnx::prelude::{
-    DatumExt, Framework, Graph, InferenceFact, InferenceModelExt, SymbolValues, TypedFact, TypedOp,
+    DatumExt, Framework, Graph, InferenceFact, InferenceModelExt, SymbolValues, TValue, TVec,
+    TypedFact, TypedModel, TypedOp,
 };
 #[cfg(not(target_arch = "wasm32"))]
+use tract_onnx::tract_core::ops::cast::Cast;
+#[cfg(not(target_arch = "wasm32"))]
+use tract_onnx::tract_hir::ops::konst::Const;
+#[cfg(not(target_arch = "wasm32"))]
 use tract_onnx::tract_hir::ops::scan::Scan;
 
 use log::error;
 use log::{debug, info, trace};
+#[cfg(not(target_arch = "wasm32"))]
+use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 use std::collections::HashSet;
 use std::error::Error;
 use std::fs;
 use std::io::Read;
+use std::ops::Range;
 use std::path::PathBuf;
 #[cfg(not(target_arch = "wasm32"))]
 use tabled::Table;
@@ -54,6 +71,61 @@ pub struct ForwardResult {
     pub outputs: Vec<Tensor<Fp>>,
     /// The maximum value of any input to a lookup operation.
     pub max_lookup_inputs: i128,
+    /// The range of inputs seen by each lookup operation, in node order.
+    pub lookup_ranges: Vec<LookupRange>,
+    /// The (fixed point) outputs of every node of the graph, by node index. Nodes within subgraphs are not included.
+    pub node_outputs: BTreeMap<usize, Vec<Tensor<Fp>>>,
+}
+
+/// The (fixed point) range of inputs a lookup operation saw during a forward pass. The lookup table for the
+/// operation has to cover `min..=max`, see [crate::RunArgs::bits].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LookupRange {
+    /// The node's index, nodes within subgraphs are prefixed by the index of the subgraph node (e.g `3/1`).
+    pub node: String,
+    /// The operation performed by the node.
+    pub op: String,
+    /// The smallest value input to the lookup.
+    pub min: i128,
+    /// The largest value input to the lookup.
+    pub max: i128,
+}
+
+impl LookupRange {
+    /// Widens the range of the entry for `node` in `ranges` to include `min..=max`, creating it if needed.
+    fn record(ranges: &mut Vec<LookupRange>, node: String, op: &str, min: i128, max: i128) {
+        match ranges.iter_mut().find(|r| r.node == node) {
+            Some(r) => {
+                r.min = r.min.min(min);
+                r.max = r.max.max(max);
+            }
+            None => ranges.push(LookupRange {
+                node,
+                op: op.to_string(),
+                min,
+                max,
+            }),
+        }
+    }
+}
+
+/// A weight of the model, quantized as it is used in the circuit. A float weight `w` is quantized to
+/// `round(w * 2^scale)`, so the values can be re-derived from the float weights of the .onnx file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuantizedWeight {
+    /// The index of the node holding the weight, nodes within subgraphs are prefixed by the index of the subgraph
+    /// node (e.g `3/1`).
+    pub node: String,
+    /// The operation performed by the node.
+    pub op: String,
+    /// Which of the node's weights this is, e.g `value` for a constant or `kernel` and `bias` for a convolution.
+    pub name: String,
+    /// The denominator in the fixed point representation of the weight, as a log base 2 exponent.
+    pub scale: u32,
+    /// The weight's shape.
+    pub dims: Vec<usize>,
+    /// The quantized values, flattened in row-major order.
+    pub values: Vec<i128>,
 }
 
 /// A circuit configuration for the entirety of a model loaded from an Onnx file.
@@ -77,6 +149,70 @@ pub struct Model {
     pub visibility: VarVisibility,
 }
 
+/// The float model of an Onnx file as tract runs it, without quantizing: the reference the circuit's (dequantized)
+/// outputs are compared to, e.g when calibrating.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug)]
+pub struct FloatModel {
+    model: TypedModel,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FloatModel {
+    /// Loads the float model of the Onnx file at `model_path`, at the batch size and sequence length of `run_args`.
+    pub fn from_run_args(run_args: &RunArgs, model_path: &PathBuf) -> Result<Self, Box<dyn Error>> {
+        let model = Model::load_onnx_using_tract(&mut std::fs::File::open(model_path)?, run_args)?;
+        Ok(FloatModel { model })
+    }
+
+    /// The shapes of the model's inputs.
+    pub fn input_shapes(&self) -> Result<Vec<Vec<usize>>, Box<dyn Error>> {
+        self.model
+            .input_outlets()?
+            .iter()
+            .map(|outlet| {
+                let fact = self.model.outlet_fact(*outlet)?;
+                fact.shape.as_concrete().map(|s| s.to_vec()).ok_or_else(|| {
+                    Box::new(GraphError::InvalidDims(
+                        outlet.node,
+                        format!("input shape {:?} is not concrete", fact.shape),
+                    )) as Box<dyn Error>
+                })
+            })
+            .collect()
+    }
+
+    /// Runs the model on `inputs`, of the model's input shapes and flattened in row-major order. Returns the
+    /// outputs, flattened likewise.
+    pub fn run(&self, inputs: &[Vec<f64>]) -> Result<Vec<Vec<f64>>, Box<dyn Error>> {
+        let input_shapes = self.input_shapes()?;
+        if inputs.len() != input_shapes.len() {
+            return Err(Box::new(GraphError::InvalidDims(
+                0,
+                format!(
+                    "expected {} model inputs but got {}",
+                    input_shapes.len(),
+                    inputs.len()
+                ),
+            )));
+        }
+        let inputs = inputs
+            .iter()
+            .zip(&input_shapes)
+            .map(|(values, shape)| {
+                let values = values.iter().map(|x| *x as f32).collect_vec();
+                Ok(tract_onnx::prelude::Tensor::from_shape(shape, &values)?.into())
+            })
+            .collect::<Result<TVec<TValue>, Box<dyn Error>>>()?;
+
+        let outputs = self.model.clone().into_runnable()?.run(inputs)?;
+        outputs
+            .iter()
+            .map(|output| Ok(output.cast_to::<f64>()?.as_slice::<f64>()?.to_vec()))
+            .collect()
+    }
+}
+
 ///
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum OutputMapping {
@@ -286,6 +422,53 @@ impl NodeType {
     }
 }
 
+/// Feeds a sha256 digest a fixed binary encoding of the fields of [ParsedNodes::canonical_digest]: integers as
+/// little endian u64s and byte strings (including digests) prefixed by their length, such that no two sequences of
+/// fields share an encoding.
+#[cfg(not(target_arch = "wasm32"))]
+struct CanonicalEncoder(Sha256);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CanonicalEncoder {
+    fn new() -> Self {
+        CanonicalEncoder(Sha256::new())
+    }
+
+    fn tag(&mut self, tag: u8) {
+        self.0.update([tag]);
+    }
+
+    fn usize(&mut self, n: usize) {
+        self.0.update((n as u64).to_le_bytes());
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.usize(bytes.len());
+        self.0.update(bytes);
+    }
+
+    fn u32s(&mut self, values: &[u32]) {
+        self.usize(values.len());
+        for v in values {
+            self.usize(*v as usize);
+        }
+    }
+
+    fn dims(&mut self, dims: &[Vec<usize>]) {
+        self.usize(dims.len());
+        for d in dims {
+            self.usize(d.len());
+            for n in d {
+                self.usize(*n);
+            }
+        }
+    }
+
+    fn finish(self) -> [u8; 32] {
+        self.0.finalize().into()
+    }
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
 /// A set of EZKL nodes that represent a computational graph.
 pub struct ParsedNodes {
@@ -295,6 +478,11 @@ pub struct ParsedNodes {
 }
 
 impl ParsedNodes {
+    /// Returns the node with index `idx` (if any).
+    pub fn node(&self, idx: usize) -> Option<&NodeType> {
+        self.nodes.get(&idx)
+    }
+
     /// Returns the number of the computational graph's inputs
     pub fn num_inputs(&self) -> usize {
         let input_nodes = self.inputs.iter();
@@ -338,6 +526,180 @@ impl ParsedNodes {
             .map(|(idx, outlet)| self.nodes.get(idx).unwrap().out_scales()[*outlet])
             .collect_vec()
     }
+
+    /// Fuses chains of elementwise ops (including within subgraphs) into single lookups, see [super::passes::fuse_elementwise_lookups].
+    /// Returns the number of nodes removed.
+    pub fn fuse_elementwise_lookups(&mut self, fuse_consts: bool) -> usize {
+        let mut num_removed = 0;
+        for node in self.nodes.values_mut() {
+            if let NodeType::SubGraph { model, .. } = node {
+                num_removed += model.graph.fuse_elementwise_lookups(fuse_consts);
+            }
+        }
+        num_removed
+            + super::passes::fuse_elementwise_lookups(&mut self.nodes, &self.outputs, fuse_consts)
+    }
+
+    /// Requantizes the graph's outputs down to the fixed point `scale`, see [super::passes::requantize_outputs].
+    /// Returns the number of nodes requantized.
+    pub fn requantize_outputs(&mut self, scale: u32) -> Result<usize, GraphError> {
+        super::passes::requantize_outputs(&mut self.nodes, &self.outputs, scale)
+    }
+
+    /// Removes nodes that forward their input unchanged (including within subgraphs), see
+    /// [super::passes::remove_noop_nodes]. Returns the number of nodes removed.
+    pub fn remove_noop_nodes(&mut self) -> usize {
+        let mut num_removed = 0;
+        for node in self.nodes.values_mut() {
+            if let NodeType::SubGraph { model, .. } = node {
+                num_removed += model.graph.remove_noop_nodes();
+            }
+        }
+        num_removed + super::passes::remove_noop_nodes(&mut self.nodes, &mut self.outputs)
+    }
+
+    /// A sha256 digest of the computation the graph performs: the operations (including their constants), shapes and
+    /// scales of every node the outputs depend on, together with the shapes and scales of the inputs. Each node is
+    /// digested along with the digests of its inputs rather than by its index, so the digest doesn't depend on the
+    /// order in which nodes were exported. Inputs to the graph are told apart by their position.
+    ///
+    /// Every field is written in a fixed binary encoding (see [CanonicalEncoder]) rather than by its serde
+    /// representation: constants are digested by their quantized values and other operations by their positional
+    /// bincode encoding, such that the digest doesn't depend on e.g field names or how floats are formatted.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn canonical_digest(&self) -> Result<String, Box<dyn Error>> {
+        Ok(hex::encode(self.canonical_digest_bytes()?))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn canonical_digest_bytes(&self) -> Result<[u8; 32], Box<dyn Error>> {
+        let mut memo = BTreeMap::new();
+        let mut encoder = CanonicalEncoder::new();
+        encoder.dims(&self.input_shapes());
+        encoder.u32s(&self.get_input_scales());
+        encoder.usize(self.outputs.len());
+        for (idx, slot) in &self.outputs {
+            encoder.bytes(&self.node_digest(*idx, &mut memo)?);
+            encoder.usize(*slot);
+        }
+        Ok(encoder.finish())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn node_digest(
+        &self,
+        idx: usize,
+        memo: &mut BTreeMap<usize, [u8; 32]>,
+    ) -> Result<[u8; 32], Box<dyn Error>> {
+        if let Some(digest) = memo.get(&idx) {
+            return Ok(*digest);
+        }
+        let node = self.nodes.get(&idx).ok_or(GraphError::MissingNode(idx))?;
+        let mut encoder = CanonicalEncoder::new();
+        match node {
+            NodeType::Node(Node {
+                opkind: SupportedOp::Constant(c),
+                ..
+            }) => {
+                encoder.tag(0);
+                encoder.dims(&[c.quantized_values.dims().to_vec()]);
+                encoder.usize(c.quantized_values.len());
+                for v in c.quantized_values.iter() {
+                    encoder.bytes(&felt_to_i128(*v).to_le_bytes());
+                }
+            }
+            NodeType::Node(n) => {
+                encoder.tag(1);
+                encoder.bytes(&bincode::serialize(&n.opkind)?);
+            }
+            NodeType::SubGraph {
+                model,
+                output_mappings,
+                input_mappings,
+                ..
+            } => {
+                encoder.tag(2);
+                encoder.bytes(&model.graph.canonical_digest_bytes()?);
+                encoder.bytes(&bincode::serialize(&(output_mappings, input_mappings))?);
+            }
+        }
+        match self.inputs.iter().position(|i| *i == idx) {
+            Some(position) => {
+                encoder.tag(1);
+                encoder.usize(position);
+            }
+            None => encoder.tag(0),
+        }
+        encoder.dims(&node.out_dims());
+        encoder.u32s(&node.out_scales());
+        let inputs = node.inputs();
+        encoder.usize(inputs.len());
+        for (input, slot) in inputs.iter() {
+            encoder.bytes(&self.node_digest(*input, memo)?);
+            encoder.usize(*slot);
+        }
+        let digest = encoder.finish();
+        memo.insert(idx, digest);
+        Ok(digest)
+    }
+
+    /// Normalizes every input to the graph within the circuit, see [InputNormalization].
+    pub fn normalize_inputs(
+        &mut self,
+        normalization: &InputNormalization,
+    ) -> Result<(), GraphError> {
+        for idx in &self.inputs {
+            let node = match self.nodes.get_mut(idx) {
+                Some(NodeType::Node(n)) => n,
+                _ => return Err(GraphError::MissingNode(*idx)),
+            };
+            let channels = node.out_dims.get(normalization.axis).copied();
+            if channels != Some(normalization.mean.len()) {
+                return Err(GraphError::InvalidNormalization(format!(
+                    "input {} has {:?} channels along axis {} but {} means were provided",
+                    idx,
+                    channels,
+                    normalization.axis,
+                    normalization.mean.len()
+                )));
+            }
+            match &mut node.opkind {
+                SupportedOp::Input(input) => {
+                    input.normalization = Some(normalization.quantize(input.scale)?);
+                }
+                _ => return Err(GraphError::OpMismatch(*idx, node.opkind.as_string())),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks every node (including within subgraphs) can be evaluated in an integer-only model, see
+    /// [SupportedOp::is_integer_exact], and that constants are integral.
+    pub fn check_integer_only(&self) -> Result<(), GraphError> {
+        for (idx, node) in &self.nodes {
+            match node {
+                NodeType::SubGraph { model, .. } => model.graph.check_integer_only()?,
+                NodeType::Node(n) => {
+                    if !n.opkind.is_integer_exact() {
+                        return Err(GraphError::IntegerOnly(format!(
+                            "node {} ({}) isn't evaluated with integer arithmetic",
+                            idx,
+                            n.opkind.as_string()
+                        )));
+                    }
+                    if let SupportedOp::Constant(c) = &n.opkind {
+                        if c.raw_values.iter().any(|x| x.fract() != 0.0) {
+                            return Err(GraphError::IntegerOnly(format!(
+                                "constant node {} has non-integral values",
+                                idx
+                            )));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Model {
@@ -355,9 +717,20 @@ impl Model {
     /// * `run_args` - [RunArgs]
     #[cfg(not(target_arch = "wasm32"))]
     pub fn new(reader: &mut dyn std::io::Read, run_args: RunArgs) -> Result<Self, Box<dyn Error>> {
+        Self::new_rescaled(reader, run_args, &BTreeMap::new())
+    }
+
+    /// Creates a `Model` as [Model::new] does, scaling the outputs of the nodes of `rescaled_nodes` down by the given
+    /// number of bits as the graph is built, see [GraphSettings::rescaled_nodes].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new_rescaled(
+        reader: &mut dyn std::io::Read,
+        run_args: RunArgs,
+        rescaled_nodes: &BTreeMap<usize, u32>,
+    ) -> Result<Self, Box<dyn Error>> {
         let visibility = VarVisibility::from_args(run_args)?;
 
-        let graph = Self::load_onnx_model(reader, &run_args, &visibility)?;
+        let graph = Self::load_onnx_model(reader, &run_args, &visibility, rescaled_nodes)?;
 
         let om = Model { graph, visibility };
 
@@ -391,6 +764,15 @@ impl Model {
         run_args: RunArgs,
         check_mode: CheckMode,
     ) -> Result<GraphSettings, Box<dyn Error>> {
+        if let Some(idx) = run_args.sorted_input {
+            if idx >= self.graph.inputs.len() {
+                return Err(Box::new(GraphError::SortedInput(format!(
+                    "the model has {} inputs, it has no input {}",
+                    self.graph.inputs.len(),
+                    idx
+                ))));
+            }
+        }
         let instance_shapes = self.instance_shapes();
         #[cfg(not(target_arch = "wasm32"))]
         info!(
@@ -431,23 +813,52 @@ impl Model {
             }
         }
 
+        // disclosing the number of non-zero input elements requires their sign
+        let input_len = self.graph.input_shapes()[0].iter().product();
+        run_args.input_statistics.check(input_len)?;
+        if run_args.input_statistics.nonzero {
+            lookup_ops.push(LookupOp::Sign);
+        }
+
+        // asserting the class lays out the argmax of the first output
+        if run_args.classify {
+            let opkind: Box<dyn Op<Fp>> = Box::new(HybridOp::ReduceArgMax { axis: 0 });
+            lookup_ops.extend(opkind.required_lookups());
+        }
+
+        // asserting the output range checks that the output's differences to the bounds are non-negative
+        if run_args.assert_output_range {
+            lookup_ops.push(LookupOp::ReLU { scale: 1 });
+        }
+
+        // as does sorting an input, with the differences of its consecutive elements
+        if run_args.sorted_input.is_some() {
+            lookup_ops.push(LookupOp::ReLU { scale: 1 });
+        }
+
         let set: HashSet<_> = lookup_ops.drain(..).collect(); // dedup
         lookup_ops.extend(set.into_iter().sorted());
 
         let batch_size = self.graph.input_shapes()[0][0];
         assert!(self.graph.input_shapes().iter().all(|x| x[0] == batch_size));
 
-        Ok(GraphSettings {
+        let mut settings = GraphSettings {
             run_args,
             model_instance_shapes: instance_shapes,
             module_sizes: crate::graph::modules::ModuleSizes::default(),
             num_constraints,
             required_lookups: lookup_ops,
+            model_input_scales: self.graph.get_input_scales(),
             model_output_scales: self.graph.get_output_scales(),
             total_const_size,
             check_mode,
             version: env!("CARGO_PKG_VERSION").to_string(),
-        })
+            input_normalization: None,
+            rescaled_nodes: BTreeMap::new(),
+            hash_parameters: None,
+        };
+        settings.hash_parameters = settings.public_input_hash();
+        Ok(settings)
     }
 
     /// Runs a forward pass on sample data !
@@ -456,8 +867,13 @@ impl Model {
     /// * `model_inputs` - A vector of [Tensor]s to use as inputs to the model.
     /// * `run_args` - [RunArgs]
     pub fn forward(&self, model_inputs: &[Tensor<Fp>]) -> Result<ForwardResult, Box<dyn Error>> {
+        check_input_lens(
+            &model_inputs.iter().map(|t| t.len()).collect_vec(),
+            &self.graph.input_shapes(),
+        )?;
         let mut results: BTreeMap<&usize, Vec<Tensor<Fp>>> = BTreeMap::new();
         let mut max_lookup_inputs = 0;
+        let mut lookup_ranges = vec![];
         let mut input_idx = 0;
         for (idx, n) in self.graph.nodes.iter() {
             let mut inputs = vec![];
@@ -485,7 +901,18 @@ impl Model {
             if !n.required_lookups().is_empty() {
                 let mut max = 0;
                 for i in &inputs {
-                    max = max.max(i.iter().map(|x| felt_to_i128(*x).abs()).max().unwrap());
+                    max = max.max(i.iter().map(|x| felt_to_i128(*x).abs()).max().unwrap_or(0));
+                    if let Some((lo, hi)) =
+                        i.iter().map(|x| felt_to_i128(*x)).minmax().into_option()
+                    {
+                        LookupRange::record(
+                            &mut lookup_ranges,
+                            idx.to_string(),
+                            &n.as_str(),
+                            lo,
+                            hi,
+                        );
+                    }
                 }
                 max_lookup_inputs = max_lookup_inputs.max(max);
             }
@@ -501,7 +928,16 @@ impl Model {
                     if !res.intermediate_lookups.is_empty() {
                         let mut max = 0;
                         for i in &res.intermediate_lookups {
-                            max = max.max(i.iter().map(|x| x.abs()).max().unwrap());
+                            max = max.max(i.iter().map(|x| x.abs()).max().unwrap_or(0));
+                            if let Some((lo, hi)) = i.iter().copied().minmax().into_option() {
+                                LookupRange::record(
+                                    &mut lookup_ranges,
+                                    idx.to_string(),
+                                    &Op::<Fp>::as_string(&n.opkind),
+                                    lo,
+                                    hi,
+                                );
+                            }
                         }
                         max_lookup_inputs = max_lookup_inputs.max(max);
                     }
@@ -551,6 +987,15 @@ impl Model {
                         let res = model.forward(&inputs)?;
                         // recursively get the max lookup inputs for subgraphs
                         max_lookup_inputs = max_lookup_inputs.max(res.max_lookup_inputs);
+                        for r in res.lookup_ranges {
+                            LookupRange::record(
+                                &mut lookup_ranges,
+                                format!("{}/{}", idx, r.node),
+                                &r.op,
+                                r.min,
+                                r.max,
+                            );
+                        }
 
                         let mut outlets = BTreeMap::new();
                         for (mappings, outlet_res) in output_mappings.iter().zip(res.outputs) {
@@ -618,29 +1063,90 @@ impl Model {
         let res = ForwardResult {
             outputs,
             max_lookup_inputs,
+            lookup_ranges,
+            node_outputs: results.into_iter().map(|(idx, r)| (*idx, r)).collect(),
         };
 
         Ok(res)
     }
 
-    /// Loads an Onnx model from a specified path.
-    /// # Arguments
-    /// * `reader` - A reader for an Onnx file.
-    /// * `scale` - The scale to use for quantization.
-    /// * `public_params` - Whether to make the params public.
+    /// Runs the quantized computation the circuit constrains, without proving, on inputs already in their fixed point
+    /// representation. Returns the (fixed point) outputs.
+    pub fn forward_quantized(
+        &self,
+        model_inputs: &[Tensor<i128>],
+    ) -> Result<Vec<Tensor<i128>>, Box<dyn Error>> {
+        let inputs = model_inputs
+            .iter()
+            .map(|t| t.map(i128_to_felt::<Fp>))
+            .collect_vec();
+        let res = self.forward(&inputs)?;
+        Ok(res.outputs.iter().map(|t| t.map(felt_to_i128)).collect())
+    }
+
+    /// Runs the quantized computation the circuit constrains, without proving, on float inputs. Inputs are quantized
+    /// at the model's input scales and outputs are dequantized at its output scales.
+    pub fn forward_float(
+        &self,
+        model_inputs: &[Tensor<f32>],
+    ) -> Result<Vec<Tensor<f32>>, Box<dyn Error>> {
+        let input_scales = self.graph.get_input_scales();
+        if model_inputs.len() != input_scales.len() {
+            return Err(Box::new(GraphError::InvalidDims(
+                0,
+                format!(
+                    "expected {} model inputs but got {}",
+                    input_scales.len(),
+                    model_inputs.len()
+                ),
+            )));
+        }
+
+        let inputs = model_inputs
+            .iter()
+            .zip(input_scales)
+            .map(|(t, scale)| {
+                let quantized = t
+                    .iter()
+                    .map(|x| quantize_float(&(*x as f64), 0.0, scale))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Tensor::new(Some(&quantized), t.dims())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let outputs = self.forward_quantized(&inputs)?;
+        Ok(outputs
+            .iter()
+            .zip(self.graph.get_output_scales())
+            .map(|(t, scale)| {
+                let mult = scale_to_multiplier(scale);
+                t.map(|x| (x as f64 / mult) as f32)
+            })
+            .collect())
+    }
+
+    /// Loads the tract graph of an Onnx model, with its input shapes set and batch size and sequence length
+    /// concretized from `run_args`, before it is parsed into nodes.
     #[cfg(not(target_arch = "wasm32"))]
-    fn load_onnx_model(
+    fn load_onnx_using_tract(
         reader: &mut dyn std::io::Read,
         run_args: &RunArgs,
-        visibility: &VarVisibility,
-    ) -> Result<ParsedNodes, Box<dyn Error>> {
-        let start_time = instant::Instant::now();
+    ) -> Result<TypedModel, Box<dyn Error>> {
+        // a defaulted run_args has a sequence length of 0, which we treat as a single step
+        let sequence_length = std::cmp::max(run_args.sequence_length, 1);
 
-        let mut model = tract_onnx::onnx().model_for_read(reader).map_err(|e| {
+        let onnx = super::utilities::onnx();
+        let proto = onnx.proto_model_for_read(reader).map_err(|e| {
+            error!("Error loading model: {}", e);
+            GraphError::ModelLoad
+        })?;
+        super::opset::OpsetReport::new(&proto).check()?;
+        let mut model = onnx.model_for_proto_model(&proto).map_err(|e| {
             error!("Error loading model: {}", e);
             GraphError::ModelLoad
         })?;
 
+        let mut batched = false;
         for (i, id) in model.clone().inputs.iter().enumerate() {
             let input = model.node(id.node);
 
@@ -654,7 +1160,10 @@ impl Model {
                     Ok(x) => x as usize,
                     Err(_e) => {
                         if x.to_string() == "batch_size" {
+                            batched = true;
                             run_args.batch_size
+                        } else if x.to_string() == "sequence_length" {
+                            sequence_length
                         } else {
                             panic!("Unknown dimension {}: {:?}", x.to_string(), x)
                         }
@@ -666,43 +1175,145 @@ impl Model {
 
             model.set_input_fact(i, f32::fact(dims).into())?;
         }
+        // the inferences of a batch are stacked along the symbolic `batch_size` dimension, such that the data holds
+        // their inputs back to back and their outputs are all the circuit's outputs. They share the model's weights,
+        // which each op lays out once for the whole batch, so only the rows of the activations grow with the batch
+        // size. A model without the dimension can't be batched
+        if run_args.batch_size > 1 && !batched {
+            return Err(Box::new(GraphError::MisformedParams(format!(
+                "a batch size of {} requires a model whose inputs have a symbolic `batch_size` dimension",
+                run_args.batch_size
+            ))));
+        }
 
         for (i, _) in model.clone().outputs.iter().enumerate() {
             model.set_output_fact(i, InferenceFact::default()).unwrap();
         }
         // Note: do not optimize the model, as the layout will depend on underlying hardware
-        let model = model.into_typed()?.into_decluttered()?;
+        let model = model.into_typed()?;
+        if run_args.integer_only {
+            // checked before decluttering, which folds constants into ops (e.g conv kernels)
+            Self::check_integral_consts(&model)?;
+        }
+        let model = model.into_decluttered()?;
         let batch_size_sym = model.symbol_table.sym("batch_size");
         let seq_len_sym = model.symbol_table.sym("sequence_length");
         let model = model
             .concretize_dims(
                 &SymbolValues::default().with(&batch_size_sym, run_args.batch_size as i64),
             )?
-            .concretize_dims(&SymbolValues::default().with(&seq_len_sym, 1))?;
+            .concretize_dims(&SymbolValues::default().with(&seq_len_sym, sequence_length as i64))?;
 
         info!("set batch size to {}", run_args.batch_size);
+        info!("set sequence length to {}", sequence_length);
+        Ok(model)
+    }
+
+    /// Loads an Onnx model from a specified path.
+    /// # Arguments
+    /// * `reader` - A reader for an Onnx file.
+    /// * `scale` - The scale to use for quantization.
+    /// * `public_params` - Whether to make the params public.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_onnx_model(
+        reader: &mut dyn std::io::Read,
+        run_args: &RunArgs,
+        visibility: &VarVisibility,
+        rescaled_nodes: &BTreeMap<usize, u32>,
+    ) -> Result<ParsedNodes, Box<dyn Error>> {
+        let start_time = instant::Instant::now();
+        let model = Self::load_onnx_using_tract(reader, run_args)?;
 
         let nodes = Self::nodes_from_graph(
             &model,
             run_args,
             visibility,
             model.inputs.iter().map(|_| run_args.scale).collect(),
+            rescaled_nodes,
         )?;
 
         debug!("\n {}", model);
 
-        let parsed_nodes = ParsedNodes {
+        let mut parsed_nodes = ParsedNodes {
             nodes,
             inputs: model.inputs.iter().map(|o| o.node).collect(),
             outputs: model.outputs.iter().map(|o| (o.node, o.slot)).collect(),
         };
 
+        if run_args.fuse_lookups {
+            // constants are only baked into lookup tables if they are already fixed in the circuit
+            let num_removed =
+                parsed_nodes.fuse_elementwise_lookups(run_args.param_visibility.is_public());
+            info!("fused away {} elementwise nodes", num_removed);
+        }
+
+        // runs last, as other passes can leave no-ops behind
+        let num_removed = parsed_nodes.remove_noop_nodes();
+        info!("removed {} no-op nodes", num_removed);
+
+        if let Some(output_scale) = run_args.output_scale {
+            let num_requantized = parsed_nodes.requantize_outputs(output_scale)?;
+            info!(
+                "requantized {} outputs to scale {}",
+                num_requantized, output_scale
+            );
+        }
+
+        if run_args.integer_only {
+            // without fractional bits no rescaling (and hence rounding) ever happens
+            if run_args.scale != 0 {
+                return Err(Box::new(GraphError::IntegerOnly(format!(
+                    "requires a scale of 0, got {}",
+                    run_args.scale
+                ))));
+            }
+            if let Some(weight_scale) = run_args.weight_scale.filter(|s| *s != 0) {
+                return Err(Box::new(GraphError::IntegerOnly(format!(
+                    "requires a weight scale of 0, got {}",
+                    weight_scale
+                ))));
+            }
+            parsed_nodes.check_integer_only()?;
+        }
+
         let duration = start_time.elapsed();
         trace!("model loading took: {:?}", duration);
 
         Ok(parsed_nodes)
     }
 
+    /// Errors if any float constant of the (not yet decluttered) `model` has non-integral values.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn check_integral_consts(model: &TypedModel) -> Result<(), Box<dyn Error>> {
+        for node in model.nodes() {
+            if let Some(c) = node.op_as::<Const>() {
+                if !c.0.datum_type().is_float() {
+                    continue;
+                }
+                let values = c.0.cast_to::<f64>()?;
+                if values.as_slice::<f64>()?.iter().any(|x| x.fract() != 0.0) {
+                    return Err(Box::new(GraphError::IntegerOnly(format!(
+                        "constant {} has non-integral values",
+                        node.name
+                    ))));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A hex encoded sha256 fingerprint of the model's computation at the `scale` and `bits` of `run_args`, see
+    /// [ParsedNodes::canonical_digest]. Re-exporting the same model (e.g with reordered nodes or different producer
+    /// metadata) yields the same hash, whereas changing an operation, shape or constant doesn't.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn canonical_hash(&self, run_args: &RunArgs) -> Result<String, Box<dyn Error>> {
+        let mut encoder = CanonicalEncoder::new();
+        encoder.u32s(&[run_args.scale]);
+        encoder.usize(run_args.bits);
+        encoder.bytes(&self.graph.canonical_digest_bytes()?);
+        Ok(hex::encode(encoder.finish()))
+    }
+
     /// Formats nodes (including subgraphs) into tables !
     #[cfg(not(target_arch = "wasm32"))]
     pub fn table_nodes(&self) -> String {
@@ -745,12 +1356,14 @@ impl Model {
     /// * `run_args` - [RunArgs]
     /// * `visibility` - Which inputs to the model are public and private (params, inputs, outputs) using [VarVisibility].
     /// * `input_scales` - The scales of the model's inputs.
+    /// * `rescaled_nodes` - The number of bits to scale the outputs of nodes of the graph down by, by node index.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn nodes_from_graph(
         graph: &Graph<TypedFact, Box<dyn TypedOp>>,
         run_args: &RunArgs,
         visibility: &VarVisibility,
         input_scales: Vec<u32>,
+        rescaled_nodes: &BTreeMap<usize, u32>,
     ) -> Result<BTreeMap<usize, NodeType>, Box<dyn Error>> {
         use crate::graph::node_output_shapes;
 
@@ -759,6 +1372,8 @@ impl Model {
         for (i, n) in graph.nodes.iter().enumerate() {
             // Extract the slope layer hyperparams
             match n.op().downcast_ref::<Scan>() {
+                // recurrent nodes are unrolled into one copy of the body per step of the scanned
+                // axis, with the body's weights shared across steps
                 Some(b) => {
                     let model = b.body.clone();
                     let input_scales = n
@@ -766,8 +1381,13 @@ impl Model {
                         .iter()
                         .map(|i| nodes.get(&i.node).unwrap().out_scales()[0])
                         .collect_vec();
-                    let subgraph_nodes =
-                        Self::nodes_from_graph(&model, run_args, visibility, input_scales)?;
+                    let subgraph_nodes = Self::nodes_from_graph(
+                        &model,
+                        run_args,
+                        visibility,
+                        input_scales,
+                        &BTreeMap::new(),
+                    )?;
 
                     let subgraph = ParsedNodes {
                         nodes: subgraph_nodes,
@@ -841,20 +1461,47 @@ impl Model {
                     );
                 }
                 None => {
+                    // the source type of a cast is only known to the graph, casts which leave the fixed point
+                    // values unchanged are laid out as the identity
+                    let cast_is_identity = match n.op().downcast_ref::<Cast>() {
+                        Some(cast) => {
+                            let from = graph.outlet_fact(n.inputs[0])?.datum_type;
+                            cast_is_identity(i, from, cast.to)?
+                        }
+                        None => false,
+                    };
                     let mut n = Node::new(
                         n.clone(),
                         &mut nodes,
                         run_args.scale,
+                        run_args.weight_scale,
                         run_args.param_visibility,
+                        run_args.unsupported_ops,
                         i,
                     )?;
+                    // the raw values of constants are only known until the graph is built
+                    let node = &graph.nodes[i];
+                    if node.outputs.iter().all(|o| o.fact.datum_type.is_float()) {
+                        check_degenerate_weights(&node.name, &n.opkind);
+                    }
+                    if cast_is_identity {
+                        let (input, slot) = n.inputs[0];
+                        n.opkind = SupportedOp::Linear(PolyOp::Identity);
+                        n.out_scale =
+                            nodes.get(&input).ok_or("input not found")?.out_scales()[slot];
+                    }
                     if n.opkind.is_input() {
                         n.opkind = SupportedOp::Input(Input {
                             scale: input_scales[input_idx],
+                            normalization: None,
                         });
                         n.out_scale = n.opkind.out_scale(vec![], 0);
                         input_idx += 1
                     }
+                    // scaled down before any consumer is built, such that consumers take the lowered scale
+                    if let Some(bits) = rescaled_nodes.get(&i) {
+                        super::passes::scale_down(&mut n, *bits)?;
+                    }
                     nodes.insert(i, NodeType::Node(n));
                 }
             }
@@ -893,6 +1540,25 @@ impl Model {
         Model::new(&mut std::fs::File::open(model)?, *run_args)
     }
 
+    /// Creates a `Model` from an Onnx file as the `settings` it was set up with describe it: at the settings' run
+    /// args, with the nodes of [GraphSettings::rescaled_nodes] scaled down and the inputs normalized (if the settings
+    /// normalize them, see [super::InputNormalization]).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_settings(
+        settings: &GraphSettings,
+        model: &std::path::PathBuf,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut model = Model::new_rescaled(
+            &mut std::fs::File::open(model)?,
+            settings.run_args,
+            &settings.rescaled_nodes,
+        )?;
+        if let Some(normalization) = &settings.input_normalization {
+            model.graph.normalize_inputs(normalization)?;
+        }
+        Ok(model)
+    }
+
     /// Configures a model for the circuit
     /// # Arguments
     /// * `meta` - The constraint system.
@@ -957,7 +1623,8 @@ impl Model {
 
         for (i, input_idx) in self.graph.inputs.iter().enumerate() {
             if self.visibility.input.is_public() {
-                results.insert(*input_idx, vec![vars.instances[i].clone()]);
+                let instance = self.instance_var(vars, InstanceColumn::Input(i))?;
+                results.insert(*input_idx, vec![instance]);
             } else {
                 results.insert(*input_idx, vec![inputs[i].clone()]);
             }
@@ -971,7 +1638,12 @@ impl Model {
                 let mut thread_safe_region = RegionCtx::new(region, 0);
 
                 let outputs = self
-                    .layout_nodes(&mut config, &mut thread_safe_region, &mut results)
+                    .layout_nodes(
+                        &mut config,
+                        &mut thread_safe_region,
+                        &mut results,
+                        &mut vec![],
+                    )
                     .map_err(|e| {
                         error!("{}", e);
                         halo2_proofs::plonk::Error::Synthesis
@@ -980,26 +1652,130 @@ impl Model {
                 if run_args.output_visibility == Visibility::Public {
                     let output_scales = self.graph.get_output_scales();
                     let global_scale = scale_to_multiplier(run_args.scale) as usize;
-                    let _ = outputs
+                    // only the selected output heads are constrained to instances, the rest of the
+                    // heads are still laid out above as they share the backbone region
+                    let heads = self.visibility.output_heads.indices(outputs.len());
+                    let _ = heads
                         .iter()
-                        .enumerate()
-                        .map(|(i, output)| {
+                        .map(|&i| {
                             let mut tolerance = run_args.tolerance;
                             tolerance.scales =
                                 (scale_to_multiplier(output_scales[i]) as usize, global_scale);
 
-                            let mut instance_offset = 0;
-                            if self.visibility.input.is_public() {
-                                instance_offset += inputs.len();
-                            };
+                            let instance = self.instance_var(vars, InstanceColumn::Output(i))?;
                             config.base.layout(
                                 &mut thread_safe_region,
-                                &[output.clone(), vars.instances[instance_offset + i].clone()],
+                                &[outputs[i].clone(), instance],
                                 Box::new(HybridOp::RangeCheck(tolerance)),
                             )
                         })
                         .collect_vec();
                 }
+
+                // disclosed statistics of the input take the last of the model's instance columns (before the challenge)
+                let input = results.get(&self.graph.inputs[0]).unwrap()[0].clone();
+                let statistics = self
+                    .layout_input_statistics(&mut config.base, &mut thread_safe_region, &input)
+                    .map_err(|e| {
+                        error!("{}", e);
+                        halo2_proofs::plonk::Error::Synthesis
+                    })?;
+                if let Some(statistics) = statistics {
+                    let instance = self.instance_var(vars, InstanceColumn::InputStatistics)?;
+                    config
+                        .base
+                        .layout(
+                            &mut thread_safe_region,
+                            &[statistics, instance],
+                            Box::new(HybridOp::RangeCheck(Tolerance::default())),
+                        )
+                        .map_err(|e| {
+                            error!("{}", e);
+                            halo2_proofs::plonk::Error::Synthesis
+                        })?;
+                }
+
+                // the class is constrained to its instance, such that the proof fails for any other class
+                let class = self
+                    .layout_class(&mut config.base, &mut thread_safe_region, &outputs[0])
+                    .map_err(|e| {
+                        error!("{}", e);
+                        halo2_proofs::plonk::Error::Synthesis
+                    })?;
+                if let Some(class) = class {
+                    let instance = self.instance_var(vars, InstanceColumn::Class)?;
+                    config
+                        .base
+                        .layout(
+                            &mut thread_safe_region,
+                            &[class, instance],
+                            Box::new(HybridOp::RangeCheck(Tolerance::default())),
+                        )
+                        .map_err(|e| {
+                            error!("{}", e);
+                            halo2_proofs::plonk::Error::Synthesis
+                        })?;
+                }
+
+                // the first output is checked against the claimed bounds, which are the only instances it touches
+                if self.visibility.output_range {
+                    let bounds = self.instance_var(vars, InstanceColumn::OutputRange)?;
+                    self.layout_output_range(
+                        &mut config.base,
+                        &mut thread_safe_region,
+                        &outputs[0],
+                        &bounds,
+                    )
+                    .map_err(|e| {
+                        error!("{}", e);
+                        halo2_proofs::plonk::Error::Synthesis
+                    })?;
+                }
+
+                // the sorted input is its instance, such that the verifier is guaranteed its canonical order
+                if let Some(idx) = self.visibility.sorted_input {
+                    let input = results.get(&self.graph.inputs[idx]).unwrap()[0].clone();
+                    self.layout_sorted_input(&mut config.base, &mut thread_safe_region, &input)
+                        .map_err(|e| {
+                            error!("{}", e);
+                            halo2_proofs::plonk::Error::Synthesis
+                        })?;
+                }
+
+                // the challenge is copied into the circuit, binding the proof to the verifier's value
+                if self.visibility.challenge {
+                    let instance = self.instance_var(vars, InstanceColumn::Challenge)?;
+                    config
+                        .base
+                        .layout(
+                            &mut thread_safe_region,
+                            &[instance.clone(), instance],
+                            Box::new(HybridOp::RangeCheck(Tolerance::default())),
+                        )
+                        .map_err(|e| {
+                            error!("{}", e);
+                            halo2_proofs::plonk::Error::Synthesis
+                        })?;
+                }
+
+                // any instance column beyond the model's own is zero padding
+                if let Some(padding) = vars.instances.get(self.instance_layout().len()) {
+                    let zeros: Tensor<ValType<Fp>> =
+                        vec![ValType::Constant(Fp::zero()); padding.len()]
+                            .into_iter()
+                            .into();
+                    config
+                        .base
+                        .layout(
+                            &mut thread_safe_region,
+                            &[zeros.into(), padding.clone()],
+                            Box::new(HybridOp::RangeCheck(Tolerance::default())),
+                        )
+                        .map_err(|e| {
+                            error!("{}", e);
+                            halo2_proofs::plonk::Error::Synthesis
+                        })?;
+                }
                 info!(
                     "computing proof over {} assigned rows",
                     thread_safe_region.offset()
@@ -1014,11 +1790,13 @@ impl Model {
         Ok(outputs)
     }
 
+    /// Lays out the nodes, recording the rows of the region each node occupies in `node_rows`.
     fn layout_nodes(
         &self,
         config: &mut ModelConfig,
         region: &mut RegionCtx<Fp>,
         results: &mut BTreeMap<usize, Vec<ValTensor<Fp>>>,
+        node_rows: &mut Vec<(usize, Range<usize>)>,
     ) -> Result<Vec<ValTensor<Fp>>, Box<dyn Error>> {
         // index over results to get original inputs
         let orig_inputs: BTreeMap<usize, _> = results
@@ -1052,6 +1830,7 @@ impl Model {
                 values.iter().map(|v| v.dims()).collect_vec()
             );
 
+            let start = region.offset();
             match node {
                 NodeType::Node(n) => {
                     let res = config
@@ -1118,7 +1897,13 @@ impl Model {
                                 .zip(values.clone().into_iter().map(|v| vec![v])),
                         );
 
-                        let res = model.layout_nodes(config, region, &mut subgraph_results)?;
+                        // the rows of subgraph nodes are attributed to the subgraph node itself
+                        let res = model.layout_nodes(
+                            config,
+                            region,
+                            &mut subgraph_results,
+                            &mut vec![],
+                        )?;
 
                         let mut outlets = BTreeMap::new();
 
@@ -1165,6 +1950,7 @@ impl Model {
                     results.insert(*idx, full_results);
                 }
             }
+            node_rows.push((*idx, start..region.offset()));
         }
 
         // we do this so we can support multiple passes of the same model and have deterministic results (Non-assigned inputs etc... etc...)
@@ -1190,6 +1976,26 @@ impl Model {
         run_args: &RunArgs,
         input_shapes: &[Vec<usize>],
     ) -> Result<(usize, usize), Box<dyn Error>> {
+        let (num_rows, num_constants, _) = self.dummy_layout_rows(run_args, input_shapes)?;
+        Ok((num_rows, num_constants))
+    }
+
+    /// The rows of the model's region each (top level) node occupies, ordered by row. Rows past the end of the last
+    /// node hold the range checks of public outputs.
+    pub fn node_rows(
+        &self,
+        run_args: &RunArgs,
+    ) -> Result<Vec<(usize, Range<usize>)>, Box<dyn Error>> {
+        let (_, _, node_rows) = self.dummy_layout_rows(run_args, &self.graph.input_shapes())?;
+        Ok(node_rows)
+    }
+
+    /// Same as [Model::dummy_layout], also returning the rows each node occupies.
+    fn dummy_layout_rows(
+        &self,
+        run_args: &RunArgs,
+        input_shapes: &[Vec<usize>],
+    ) -> Result<(usize, usize, Vec<(usize, Range<usize>)>), Box<dyn Error>> {
         info!("calculating num of constraints using dummy model layout...");
 
         let start_time = instant::Instant::now();
@@ -1218,16 +2024,18 @@ impl Model {
 
         let mut region = RegionCtx::new_dummy(0);
 
-        let outputs = self.layout_nodes(&mut model_config, &mut region, &mut results)?;
+        let mut node_rows = vec![];
+        let outputs =
+            self.layout_nodes(&mut model_config, &mut region, &mut results, &mut node_rows)?;
 
         if run_args.output_visibility == Visibility::Public {
             let _ = outputs
-                .into_iter()
+                .iter()
                 .map(|output| {
                     dummy_config
                         .layout(
                             &mut region,
-                            &[output.clone(), output],
+                            &[output.clone(), output.clone()],
                             Box::new(HybridOp::RangeCheck(run_args.tolerance)),
                         )
                         .unwrap()
@@ -1235,10 +2043,92 @@ impl Model {
                 .collect_vec();
         }
 
+        let input = results.get(&self.graph.inputs[0]).unwrap()[0].clone();
+        if let Some(statistics) =
+            self.layout_input_statistics(&mut dummy_config, &mut region, &input)?
+        {
+            dummy_config.layout(
+                &mut region,
+                &[statistics.clone(), statistics],
+                Box::new(HybridOp::RangeCheck(Tolerance::default())),
+            )?;
+        }
+
+        if let Some(class) = self.layout_class(&mut dummy_config, &mut region, &outputs[0])? {
+            dummy_config.layout(
+                &mut region,
+                &[class.clone(), class],
+                Box::new(HybridOp::RangeCheck(Tolerance::default())),
+            )?;
+        }
+
+        if self.visibility.output_range {
+            let bounds: Tensor<Value<Fp>> =
+                Tensor::from(vec![Value::<Fp>::unknown(); 2].into_iter());
+            self.layout_output_range(&mut dummy_config, &mut region, &outputs[0], &bounds.into())?;
+        }
+
+        if let Some(idx) = self.visibility.sorted_input {
+            let input = results.get(&self.graph.inputs[idx]).unwrap()[0].clone();
+            self.layout_sorted_input(&mut dummy_config, &mut region, &input)?;
+        }
+
+        if self.visibility.challenge {
+            let challenge: Tensor<Value<Fp>> =
+                Tensor::from(vec![Value::<Fp>::unknown()].into_iter());
+            let challenge: ValTensor<Fp> = challenge.into();
+            dummy_config.layout(
+                &mut region,
+                &[challenge.clone(), challenge],
+                Box::new(HybridOp::RangeCheck(Tolerance::default())),
+            )?;
+        }
+
         let duration = start_time.elapsed();
         trace!("dummy model layout took: {:?}", duration);
 
-        Ok((region.offset(), region.total_constants()))
+        Ok((region.offset(), region.total_constants(), node_rows))
+    }
+
+    /// Bounds how far the circuit's (dequantized) outputs can be from the outputs of the float model, for any input
+    /// whose elements are at most `input_magnitude` in absolute value, given the lookup tables of `2^bits` values the
+    /// circuit is built with. See [super::error_bounds::quantization_error_bounds].
+    pub fn quantization_error_bounds(&self, input_magnitude: f64, bits: usize) -> ErrorBoundReport {
+        super::error_bounds::quantization_error_bounds(
+            &self.graph.nodes,
+            &self.graph.outputs,
+            input_magnitude,
+            bits,
+        )
+    }
+
+    /// Retrieves the model's weights (including those within subgraphs), quantized as they are used in the circuit, in
+    /// node order.
+    pub fn quantized_weights(&self) -> Vec<QuantizedWeight> {
+        let mut weights = vec![];
+        for (idx, node) in &self.graph.nodes {
+            match node {
+                NodeType::Node(n) => {
+                    for (name, weight) in n.opkind.weights() {
+                        weights.push(QuantizedWeight {
+                            node: idx.to_string(),
+                            op: node.as_str(),
+                            name: name.to_string(),
+                            scale: weight.scale().unwrap_or(n.out_scale),
+                            dims: weight.dims().to_vec(),
+                            values: weight.iter().map(|x| felt_to_i128(*x)).collect(),
+                        });
+                    }
+                }
+                NodeType::SubGraph { model, .. } => {
+                    weights.extend(model.quantized_weights().into_iter().map(|mut w| {
+                        w.node = format!("{}/{}", idx, w.node);
+                        w
+                    }));
+                }
+            }
+        }
+        weights
     }
 
     /// Retrieves all constants from the model.
@@ -1304,15 +2194,417 @@ impl Model {
         const_idx
     }
 
-    /// Shapes of the computational graph's public inputs (if any)
-    pub fn instance_shapes(&self) -> Vec<Vec<usize>> {
-        let mut instance_shapes = vec![];
+    /// The model's instance columns (if any) and their shapes, in the order they are configured in the circuit: the
+    /// public inputs, the public output heads, the disclosed input statistics, the class and the challenge.
+    pub fn instance_layout(&self) -> Vec<(InstanceColumn, Vec<usize>)> {
+        let mut layout = vec![];
         if self.visibility.input.is_public() {
-            instance_shapes.extend(self.graph.input_shapes());
+            layout.extend(
+                self.graph
+                    .input_shapes()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, shape)| (InstanceColumn::Input(i), shape)),
+            );
         }
         if self.visibility.output.is_public() {
-            instance_shapes.extend(self.graph.output_shapes());
+            let output_shapes = self.graph.output_shapes();
+            layout.extend(
+                self.visibility
+                    .output_heads
+                    .indices(output_shapes.len())
+                    .into_iter()
+                    .map(|i| (InstanceColumn::Output(i), output_shapes[i].clone())),
+            );
+        }
+        let statistics = self.visibility.input_statistics;
+        if !statistics.is_empty() {
+            layout.push((InstanceColumn::InputStatistics, vec![statistics.len()]));
+        }
+        if self.visibility.classify {
+            // the argmax keeps the reduced (last) axis with a dimension of 1
+            let mut shape = self.graph.output_shapes()[0].clone();
+            if let Some(last) = shape.last_mut() {
+                *last = 1;
+            }
+            layout.push((InstanceColumn::Class, shape));
+        }
+        if self.visibility.output_range {
+            layout.push((InstanceColumn::OutputRange, vec![2]));
+        }
+        if self.visibility.challenge {
+            layout.push((InstanceColumn::Challenge, vec![1]));
+        }
+        layout
+    }
+
+    /// Shapes of the computational graph's public inputs (if any), see [Model::instance_layout]
+    pub fn instance_shapes(&self) -> Vec<Vec<usize>> {
+        self.instance_layout()
+            .into_iter()
+            .map(|(_, shape)| shape)
+            .collect()
+    }
+
+    /// The instance column of `vars` holding `column`, see [Model::instance_layout].
+    fn instance_var(
+        &self,
+        vars: &ModelVars<Fp>,
+        column: InstanceColumn,
+    ) -> Result<ValTensor<Fp>, halo2_proofs::plonk::Error> {
+        self.instance_layout()
+            .iter()
+            .position(|(c, _)| *c == column)
+            .and_then(|i| vars.instances.get(i))
+            .cloned()
+            .ok_or_else(|| {
+                error!("the circuit has no instance column for {:?}", column);
+                halo2_proofs::plonk::Error::Synthesis
+            })
+    }
+
+    /// Lays out the disclosed [InputStatistics] of the model `input`, returning them flattened in the order of
+    /// [InputStatistics::evaluate] (or `None` if none are disclosed).
+    fn layout_input_statistics(
+        &self,
+        config: &mut PolyConfig<Fp>,
+        region: &mut RegionCtx<Fp>,
+        input: &ValTensor<Fp>,
+    ) -> Result<Option<ValTensor<Fp>>, Box<dyn Error>> {
+        let statistics = self.visibility.input_statistics;
+        if statistics.is_empty() {
+            return Ok(None);
+        }
+        let mut input = input.clone();
+        input.flatten();
+
+        let mut layout = |values: &[ValTensor<Fp>],
+                          op: Box<dyn Op<Fp>>|
+         -> Result<ValTensor<Fp>, Box<dyn Error>> {
+            config
+                .layout(region, values, op)?
+                .ok_or_else(|| GraphError::InvalidInputStatistics("missing layout".into()).into())
+        };
+
+        let mut stats = vec![];
+        if statistics.sum {
+            stats.push(layout(
+                &[input.clone()],
+                Box::new(PolyOp::Sum { axes: vec![0] }),
+            )?);
+        }
+        if statistics.nonzero {
+            // sign(x)^2 is 1 for non-zero elements and 0 otherwise
+            let sign = layout(&[input.clone()], Box::new(LookupOp::Sign))?;
+            let nonzero = layout(&[sign.clone(), sign], Box::new(PolyOp::Mult))?;
+            stats.push(layout(&[nonzero], Box::new(PolyOp::Sum { axes: vec![0] }))?);
+        }
+        for i in statistics.element_indices() {
+            stats.push(input.get_slice(&[i..i + 1])?);
+        }
+
+        let mut disclosed = stats[0].clone();
+        for stat in stats.into_iter().skip(1) {
+            disclosed = disclosed.concat(stat)?;
+        }
+        disclosed.reshape(&[statistics.len()])?;
+        Ok(Some(disclosed))
+    }
+
+    /// Lays out the class of the first model `output`, its argmax over the last axis (or `None` if the classification
+    /// isn't asserted, see [crate::RunArgs::classify]).
+    fn layout_class(
+        &self,
+        config: &mut PolyConfig<Fp>,
+        region: &mut RegionCtx<Fp>,
+        output: &ValTensor<Fp>,
+    ) -> Result<Option<ValTensor<Fp>>, Box<dyn Error>> {
+        if !self.visibility.classify {
+            return Ok(None);
+        }
+        let axis = output
+            .dims()
+            .len()
+            .checked_sub(1)
+            .ok_or_else(|| GraphError::Classify("the first output is a scalar".into()))?;
+        let class = config
+            .layout(
+                region,
+                &[output.clone()],
+                Box::new(HybridOp::ReduceArgMax { axis }),
+            )?
+            .ok_or_else(|| GraphError::Classify("missing layout".into()))?;
+        Ok(Some(class))
+    }
+
+    /// Lays out the assertion that the first model `output` lies within `bounds`, the instance column holding the
+    /// claimed `(min, max)` (see [crate::RunArgs::assert_output_range]). The bounds are copied into the circuit and
+    /// the differences `output - min` and `max - output` are constrained to equal their ReLU, i.e to be non-negative.
+    /// A difference outside of the lookup table fails the lookup, so the bounds have to be within `2^(bits-1)` of the
+    /// output.
+    fn layout_output_range(
+        &self,
+        config: &mut PolyConfig<Fp>,
+        region: &mut RegionCtx<Fp>,
+        output: &ValTensor<Fp>,
+        bounds: &ValTensor<Fp>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut layout = |values: &[ValTensor<Fp>],
+                          op: Box<dyn Op<Fp>>|
+         -> Result<ValTensor<Fp>, Box<dyn Error>> {
+            config
+                .layout(region, values, op)?
+                .ok_or_else(|| GraphError::OutputRange("missing layout".into()).into())
+        };
+
+        let bounds = layout(
+            &[bounds.clone(), bounds.clone()],
+            Box::new(HybridOp::RangeCheck(Tolerance::default())),
+        )?;
+        let min = bounds.get_slice(&[0..1])?;
+        let max = bounds.get_slice(&[1..2])?;
+        let mut output = output.clone();
+        output.flatten();
+
+        let differences = [
+            layout(&[output.clone(), min], Box::new(PolyOp::Sub))?,
+            layout(&[max, output], Box::new(PolyOp::Sub))?,
+        ];
+        for difference in differences {
+            let relu = layout(&[difference.clone()], Box::new(LookupOp::ReLU { scale: 1 }))?;
+            layout(
+                &[difference, relu],
+                Box::new(HybridOp::RangeCheck(Tolerance::default())),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Lays out the assertion that the model `input` is in canonical order (see [crate::RunArgs::sorted_input]):
+    /// the differences of its consecutive (flattened) elements are constrained to equal their ReLU, i.e to be
+    /// non-negative. When deduplicated, the differences less one are, such that consecutive elements can't be equal.
+    /// A difference outside of the lookup table (see [crate::circuit::table::table_range]) fails the lookup, so it's
+    /// rejected as the witness is laid out.
+    fn layout_sorted_input(
+        &self,
+        config: &mut PolyConfig<Fp>,
+        region: &mut RegionCtx<Fp>,
+        input: &ValTensor<Fp>,
+    ) -> Result<(), Box<dyn Error>> {
+        let relu = LookupOp::ReLU { scale: 1 };
+        let bits = config.tables.get(&relu).map(|table| table.bits);
+        let mut layout = |values: &[ValTensor<Fp>],
+                          op: Box<dyn Op<Fp>>|
+         -> Result<ValTensor<Fp>, Box<dyn Error>> {
+            config
+                .layout(region, values, op)?
+                .ok_or_else(|| GraphError::SortedInput("missing layout".into()).into())
+        };
+
+        let mut input = input.clone();
+        input.flatten();
+        let len = input.len();
+        if len < 2 {
+            return Ok(());
+        }
+        let mut difference = layout(
+            &[input.get_slice(&[1..len])?, input.get_slice(&[0..len - 1])?],
+            Box::new(PolyOp::Sub),
+        )?;
+        if self.visibility.dedup_sorted_input {
+            let mut one = Tensor::new(Some(&[i128_to_felt::<Fp>(1)]), &[1])?;
+            one.set_visibility(Visibility::Public);
+            difference = layout(&[difference, one.into()], Box::new(PolyOp::Sub))?;
+        }
+        // the values are only known when proving, not when generating keys
+        if let Some(bits) = bits {
+            let range = crate::circuit::table::table_range(bits);
+            if let Some(gap) = difference
+                .get_felt_evals()?
+                .iter()
+                .find(|gap| !crate::circuit::table::in_table_range(**gap, bits))
+            {
+                return Err(Box::new(GraphError::SortedInput(format!(
+                    "the difference {:?} of consecutive elements is outside of the range [{}, {}] of the lookup tables",
+                    gap,
+                    range.start(),
+                    range.end()
+                ))));
+            }
+        }
+        let relu = layout(&[difference.clone()], Box::new(relu))?;
+        layout(
+            &[difference, relu],
+            Box::new(HybridOp::RangeCheck(Tolerance::default())),
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::graph::tests::test_run_args;
+
+    #[test]
+    fn test_canonical_hash_ignores_node_order() {
+        let run_args = test_run_args();
+        let path = "examples/onnx/1l_mlp/network.onnx".into();
+        let model = Model::from_run_args(&run_args, &path).unwrap();
+        let hash = model.canonical_hash(&run_args).unwrap();
+        assert_eq!(
+            Model::from_run_args(&run_args, &path)
+                .unwrap()
+                .canonical_hash(&run_args)
+                .unwrap(),
+            hash
+        );
+
+        // renumber the nodes in reverse, as a producer emitting them in another order might
+        let max = *model.graph.nodes.keys().max().unwrap();
+        let renumber = |(idx, slot): &Outlet| (max - idx, *slot);
+        let mut reordered = model.clone();
+        reordered.graph.nodes = model
+            .graph
+            .nodes
+            .iter()
+            .map(|(idx, node)| {
+                let mut node = node.clone();
+                if let NodeType::Node(n) = &mut node {
+                    n.idx = max - idx;
+                    n.inputs = n.inputs.iter().map(renumber).collect();
+                }
+                (max - idx, node)
+            })
+            .collect();
+        reordered.graph.inputs = model.graph.inputs.iter().map(|idx| max - idx).collect();
+        reordered.graph.outputs = model.graph.outputs.iter().map(renumber).collect();
+        assert_eq!(reordered.canonical_hash(&run_args).unwrap(), hash);
+
+        // constants are digested by the quantized values the circuit uses, not their float or bookkeeping fields
+        let mut relabelled = model.clone();
+        for node in relabelled.graph.nodes.values_mut() {
+            if let NodeType::Node(Node {
+                opkind: SupportedOp::Constant(c),
+                ..
+            }) = node
+            {
+                c.raw_values = c.raw_values.map(|x| x + f32::EPSILON);
+                c.num_uses += 1;
+            }
+        }
+        assert_eq!(relabelled.canonical_hash(&run_args).unwrap(), hash);
+
+        // changing the computation changes the hash
+        let rescaled = RunArgs {
+            scale: run_args.scale + 1,
+            ..run_args
+        };
+        assert_ne!(model.canonical_hash(&rescaled).unwrap(), hash);
+        let mut perturbed = model.clone();
+        for node in perturbed.graph.nodes.values_mut() {
+            if let NodeType::Node(Node {
+                opkind: SupportedOp::Constant(c),
+                ..
+            }) = node
+            {
+                c.quantized_values[0] += Fp::from(1);
+                break;
+            }
+        }
+        assert_ne!(perturbed.canonical_hash(&run_args).unwrap(), hash);
+    }
+
+    #[test]
+    fn test_sequence_length() {
+        let run_args = |sequence_length| RunArgs {
+            sequence_length,
+            ..test_run_args()
+        };
+        // h_t = tanh(W x_t + R h_{t-1} + b), see examples/onnx/rnn_sequence/gen.py
+        let path = "examples/onnx/rnn_sequence/network.onnx".into();
+        let w = [[0.5, -0.25], [0.25, 0.5]];
+        let r = [[0.5, 0.0], [-0.25, 0.5]];
+        let b = [0.125, -0.125];
+        let xs: [[f32; 2]; 3] = [[0.5, -0.5], [0.25, 0.75], [-1.0, 0.5]];
+        let mut h = [0f32; 2];
+        let mut expected = vec![];
+        for x in xs {
+            h = [0usize, 1].map(|i| {
+                (w[i][0] * x[0] + w[i][1] * x[1] + r[i][0] * h[0] + r[i][1] * h[1] + b[i]).tanh()
+            });
+            expected.extend(h);
+        }
+
+        // the recurrence is unrolled for three steps, sharing its weights, and outputs every step's hidden state
+        let model = Model::from_run_args(&run_args(3), &path).unwrap();
+        assert_eq!(model.graph.input_shapes(), vec![vec![3, 1, 2]]);
+        let input = Tensor::new(Some(&xs.concat()), &[3, 1, 2]).unwrap();
+        let outputs = model.forward_float(&[input]).unwrap();
+        assert_eq!(outputs[0].dims(), &[3, 1, 1, 2]);
+        for (output, expected) in outputs[0].iter().zip(&expected) {
+            assert!((output - expected).abs() < 0.05);
+        }
+
+        // the body is laid out once per step
+        let num_constraints = |sequence_length| {
+            crate::graph::GraphCircuit::from_run_args(&run_args(sequence_length), &path)
+                .unwrap()
+                .settings
+                .num_constraints
+        };
+        assert!(num_constraints(3) > num_constraints(1));
+    }
+
+    #[test]
+    fn test_forward_without_proving() {
+        let run_args = test_run_args();
+        let path = "examples/onnx/1l_relu/network.onnx".into();
+        let model = Model::from_run_args(&run_args, &path).unwrap();
+        let shape = model.graph.input_shapes()[0].clone();
+
+        // the fixed point outputs are those the circuit's witness is generated from
+        let quantized = Tensor::new(Some(&[-51i128, 319, 74]), &shape).unwrap();
+        let outputs = model.forward_quantized(&[quantized.clone()]).unwrap();
+        assert_eq!(outputs[0].to_vec(), vec![0, 319, 74]);
+        let witness = model.forward(&[quantized.map(i128_to_felt::<Fp>)]).unwrap();
+        assert_eq!(outputs[0], witness.outputs[0].map(felt_to_i128));
+
+        // float inputs are quantized at the input scale and the outputs dequantized at the output scale
+        let input = Tensor::new(Some(&[-0.5f32, 2.5, 0.578125]), &shape).unwrap();
+        let outputs = model.forward_float(&[input]).unwrap();
+        assert_eq!(outputs[0].to_vec(), vec![0.0, 2.5, 0.578125]);
+        assert!(model.forward_float(&[]).is_err());
+    }
+
+    #[test]
+    fn test_split() {
+        let run_args = RunArgs {
+            scale: 0,
+            ..test_run_args()
+        };
+        // a [1, 6, 4] input split along axis 1 into [1, 2, 3] rows, then evenly into three [1, 2, 4] parts
+        let path = "examples/onnx/1l_split/network.onnx".into();
+        let model = Model::from_run_args(&run_args, &path).unwrap();
+        assert_eq!(
+            model.graph.output_shapes(),
+            vec![
+                vec![1, 1, 4],
+                vec![1, 2, 4],
+                vec![1, 3, 4],
+                vec![1, 2, 4],
+                vec![1, 2, 4],
+                vec![1, 2, 4]
+            ]
+        );
+
+        let input = Tensor::new(Some(&(0..24).map(Fp::from).collect_vec()), &[1, 6, 4]).unwrap();
+        let outputs = model.forward(&[input]).unwrap().outputs;
+        let rows = |start: u64, end: u64| (start * 4..end * 4).map(Fp::from).collect_vec();
+        let expected = [(0, 1), (1, 3), (3, 6), (0, 2), (2, 4), (4, 6)];
+        for (output, (start, end)) in outputs.iter().zip(expected) {
+            assert_eq!(output.to_vec(), rows(start, end));
         }
-        instance_shapes
     }
 }
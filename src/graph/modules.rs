@@ -3,10 +3,14 @@ use crate::circuit::modules::poseidon::spec::{PoseidonSpec, POSEIDON_RATE, POSEI
 use crate::circuit::modules::poseidon::{PoseidonChip, PoseidonConfig};
 use crate::circuit::modules::Module;
 use crate::tensor::{Tensor, ValTensor, ValType};
+use halo2_gadgets::poseidon::primitives::Spec;
 use halo2_proofs::circuit::{Layouter, Value};
 use halo2_proofs::plonk::{ConstraintSystem, Error};
 use halo2curves::bn256::Fr as Fp;
+use halo2curves::ff::PrimeField;
 use itertools::Itertools;
+#[cfg(feature = "python-bindings")]
+use pyo3::{types::PyDict, PyObject, Python, ToPyObject};
 use serde::{Deserialize, Serialize};
 
 use super::GraphWitness;
@@ -21,6 +25,96 @@ pub type ModulePoseidon =
 /// Poseidon module config
 pub type ModulePoseidonConfig = PoseidonConfig<POSEIDON_WIDTH, POSEIDON_RATE>;
 
+/// The hash function committing to hashed inputs, params and outputs (see [Visibility::Hashed]). The prover and
+/// verifier have to agree on it, so the parameters it is run with are recorded in proofs, see [PublicInputHash].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum HashFunction {
+    /// Poseidon over BN256 with a width of 2 and a rate of 1, 8 full and 56 partial rounds and an x^5 S-box. The
+    /// round constants and MDS matrix are in [crate::circuit::modules::poseidon::poseidon_params] (generated by
+    /// `generate_params.py`). Messages are hashed as a tree, [POSEIDON_LEN_GRAPH] elements per node.
+    #[default]
+    Poseidon,
+}
+
+impl HashFunction {
+    /// The parameters the hash function is run with.
+    pub fn public_input_hash(&self) -> PublicInputHash {
+        match self {
+            HashFunction::Poseidon => {
+                let check = ModulePoseidon::run(vec![Fp::zero(), Fp::one()])
+                    .map(|hash| hex::encode(hash[0][0].to_repr()))
+                    .unwrap_or_default();
+                PublicInputHash {
+                    function: *self,
+                    width: POSEIDON_WIDTH,
+                    rate: POSEIDON_RATE,
+                    full_rounds: PoseidonSpec::full_rounds(),
+                    partial_rounds: PoseidonSpec::partial_rounds(),
+                    arity: POSEIDON_LEN_GRAPH,
+                    check,
+                }
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for HashFunction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "poseidon" => Ok(HashFunction::Poseidon),
+            _ => Err(format!("unsupported hash function: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for HashFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HashFunction::Poseidon => write!(f, "poseidon"),
+        }
+    }
+}
+
+/// The hash function (and the parameters it is run with) a proof's hashed public inputs were computed with, such
+/// that the verifier can check it reconstructs the same digests.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicInputHash {
+    /// The hash function.
+    pub function: HashFunction,
+    /// The width of the permutation.
+    pub width: usize,
+    /// The number of elements absorbed per permutation.
+    pub rate: usize,
+    /// The number of full rounds.
+    pub full_rounds: usize,
+    /// The number of partial rounds.
+    pub partial_rounds: usize,
+    /// The number of elements hashed per node of the hash tree.
+    pub arity: usize,
+    /// The (hex encoded) digest of the message `[0, 1]`, which pins down the round constants: implementations with
+    /// different constants produce a different digest.
+    pub check: String,
+}
+
+#[cfg(feature = "python-bindings")]
+impl ToPyObject for PublicInputHash {
+    fn to_object(&self, py: Python) -> PyObject {
+        let dict = PyDict::new(py);
+        dict.set_item("function", self.function.to_string())
+            .unwrap();
+        dict.set_item("width", self.width).unwrap();
+        dict.set_item("rate", self.rate).unwrap();
+        dict.set_item("full_rounds", self.full_rounds).unwrap();
+        dict.set_item("partial_rounds", self.partial_rounds)
+            .unwrap();
+        dict.set_item("arity", self.arity).unwrap();
+        dict.set_item("check", &self.check).unwrap();
+        dict.to_object(py)
+    }
+}
+
 ///
 #[derive(Clone, Debug, Default)]
 pub struct ModuleConfigs {
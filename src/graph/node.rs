@@ -1,3 +1,4 @@
+use super::custom::CustomOp;
 #[cfg(not(target_arch = "wasm32"))]
 use super::utilities::node_output_shapes;
 #[cfg(not(target_arch = "wasm32"))]
@@ -131,6 +132,88 @@ impl Op<Fp> for Rescaled {
     }
 }
 
+/// A product (matmul, convolution or elementwise multiplication) whose output is requantized back down to the
+/// activation scale. The output of a product of an input at scale `s_a` and weights at scale `s_w` is at scale
+/// `s_a + s_w`, i.e a real value `x` is represented by `round(x * 2^(s_a + s_w))`. Dividing by `denom = 2^(s_a + s_w - s)`
+/// (rounding to the nearest integer, through a lookup) gives `round(x * 2^s)`, the same value at the activation scale
+/// `s`. See [crate::RunArgs::weight_scale].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Requantized {
+    /// The product whose output is requantized.
+    pub inner: Box<SupportedOp>,
+    /// The (power of two) divisor bringing the product's output down to the activation scale.
+    pub denom: u128,
+}
+
+impl Requantized {
+    fn requantize(&self) -> LookupOp {
+        LookupOp::Div {
+            denom: (self.denom as f32).into(),
+        }
+    }
+}
+
+impl Op<Fp> for Requantized {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn f(&self, x: &[Tensor<Fp>]) -> Result<crate::circuit::ForwardResult<Fp>, TensorError> {
+        let mut res = Op::<Fp>::f(&*self.inner, x)?;
+        let product = res.output.map(felt_to_i128);
+        let requantized =
+            crate::tensor::ops::nonlinearities::const_div(&product, self.denom as f64);
+        res.intermediate_lookups.push(product);
+        res.output = requantized.map(i128_to_felt);
+        Ok(res)
+    }
+
+    fn rescale(&self, _: Vec<u32>, _: u32) -> Box<dyn Op<Fp>> {
+        Box::new(self.clone())
+    }
+
+    fn as_string(&self) -> String {
+        format!("REQUANTIZED {}", self.inner.as_string())
+    }
+
+    fn out_scale(&self, in_scales: Vec<u32>, global_scale: u32) -> u32 {
+        Op::<Fp>::out_scale(&*self.inner, in_scales, global_scale)
+            - crate::graph::mult_to_scale(self.denom as f64)
+    }
+
+    fn requires_homogenous_input_scales(&self) -> Vec<usize> {
+        Op::<Fp>::requires_homogenous_input_scales(&*self.inner)
+    }
+
+    fn required_lookups(&self) -> Vec<LookupOp> {
+        let mut required_lookups = Op::<Fp>::required_lookups(&*self.inner);
+        required_lookups.push(self.requantize());
+        required_lookups
+    }
+
+    fn layout(
+        &self,
+        config: &mut crate::circuit::BaseConfig<Fp>,
+        region: &mut crate::circuit::region::RegionCtx<Fp>,
+        values: &[crate::tensor::ValTensor<Fp>],
+    ) -> Result<Option<crate::tensor::ValTensor<Fp>>, Box<dyn Error>> {
+        let product = match self.inner.layout(config, region, values)? {
+            Some(product) => product,
+            None => return Ok(None),
+        };
+        Ok(Some(crate::circuit::layouts::nonlinearity(
+            config,
+            region,
+            &[product],
+            &self.requantize(),
+        )?))
+    }
+
+    fn clone_dyn(&self) -> Box<dyn Op<Fp>> {
+        Box::new(self.clone()) // Forward to the derive(Clone) impl
+    }
+}
+
 /// A single operation in a [crate::graph::Model].
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SupportedOp {
@@ -148,6 +231,10 @@ pub enum SupportedOp {
     Unknown(Unknown),
     ///
     Rescaled(Rescaled),
+    /// A product requantized down to the activation scale.
+    Requantized(Requantized),
+    /// A user-defined operator, see [super::custom::CircuitOp].
+    Custom(CustomOp),
 }
 
 impl From<Box<dyn Op<Fp>>> for SupportedOp {
@@ -178,11 +265,68 @@ impl From<Box<dyn Op<Fp>>> for SupportedOp {
         if let Some(op) = value.as_any().downcast_ref::<Rescaled>() {
             return SupportedOp::Rescaled(op.clone());
         };
+        if let Some(op) = value.as_any().downcast_ref::<Requantized>() {
+            return SupportedOp::Requantized(op.clone());
+        };
+        if let Some(op) = value.as_any().downcast_ref::<CustomOp>() {
+            return SupportedOp::Custom(op.clone());
+        };
 
         panic!("Unsupported op type")
     }
 }
 
+impl SupportedOp {
+    /// The weights the op carries, by name, quantized as they are used in the circuit: a constant's value, or a
+    /// convolution's kernel and bias.
+    pub fn weights(&self) -> Vec<(&'static str, &Tensor<Fp>)> {
+        match self {
+            SupportedOp::Constant(c) => vec![("value", &c.quantized_values)],
+            SupportedOp::Linear(PolyOp::Conv { kernel, bias, .. })
+            | SupportedOp::Linear(PolyOp::DeConv { kernel, bias, .. }) => {
+                let mut weights = vec![("kernel", kernel)];
+                if let Some(bias) = bias {
+                    weights.push(("bias", bias));
+                }
+                weights
+            }
+            SupportedOp::Rescaled(op) => op.inner.weights(),
+            SupportedOp::Requantized(op) => op.inner.weights(),
+            _ => vec![],
+        }
+    }
+
+    /// Whether the op multiplies its inputs together (matmuls, convolutions and elementwise multiplications), such
+    /// that its output scale is the sum of their scales.
+    pub fn is_product(&self) -> bool {
+        matches!(
+            self,
+            SupportedOp::Linear(
+                PolyOp::Einsum { .. } | PolyOp::Conv { .. } | PolyOp::DeConv { .. } | PolyOp::Mult
+            )
+        )
+    }
+
+    /// Whether the op can be part of an integer-only model (see [crate::RunArgs::integer_only]), i.e. it always gives
+    /// bit-identical witnesses across platforms. All linear ops are supported, as are hybrid ops bar softmax, and lookups
+    /// bar those computing transcendental functions (sigmoid, exp, ln, erf, trigonometric and hyperbolic functions),
+    /// see [LookupOp::is_integer_exact].
+    pub fn is_integer_exact(&self) -> bool {
+        match self {
+            SupportedOp::Nonlinear(op) => op.is_integer_exact(),
+            SupportedOp::Hybrid(HybridOp::Softmax { .. }) => false,
+            SupportedOp::Rescaled(op) => op.inner.is_integer_exact(),
+            SupportedOp::Requantized(op) => op.inner.is_integer_exact(),
+            SupportedOp::Custom(op) => op.is_integer_exact(),
+            SupportedOp::Linear(_)
+            | SupportedOp::Hybrid(_)
+            | SupportedOp::Input(_)
+            | SupportedOp::Constant(_)
+            | SupportedOp::Unknown(_) => true,
+        }
+    }
+}
+
 impl Op<Fp> for SupportedOp {
     fn f(
         &self,
@@ -196,6 +340,8 @@ impl Op<Fp> for SupportedOp {
             SupportedOp::Constant(op) => op.f(inputs),
             SupportedOp::Unknown(op) => op.f(inputs),
             SupportedOp::Rescaled(op) => op.f(inputs),
+            SupportedOp::Requantized(op) => op.f(inputs),
+            SupportedOp::Custom(op) => op.f(inputs),
         }
     }
 
@@ -213,6 +359,8 @@ impl Op<Fp> for SupportedOp {
             SupportedOp::Constant(op) => op.layout(config, region, values),
             SupportedOp::Unknown(op) => op.layout(config, region, values),
             SupportedOp::Rescaled(op) => op.layout(config, region, values),
+            SupportedOp::Requantized(op) => op.layout(config, region, values),
+            SupportedOp::Custom(op) => op.layout(config, region, values),
         }
     }
 
@@ -225,6 +373,8 @@ impl Op<Fp> for SupportedOp {
             SupportedOp::Constant(op) => Op::<Fp>::is_input(op),
             SupportedOp::Unknown(op) => Op::<Fp>::is_input(op),
             SupportedOp::Rescaled(op) => Op::<Fp>::is_input(op),
+            SupportedOp::Requantized(op) => Op::<Fp>::is_input(op),
+            SupportedOp::Custom(op) => Op::<Fp>::is_input(op),
         }
     }
 
@@ -237,6 +387,8 @@ impl Op<Fp> for SupportedOp {
             SupportedOp::Constant(op) => Op::<Fp>::requires_homogenous_input_scales(op),
             SupportedOp::Unknown(op) => Op::<Fp>::requires_homogenous_input_scales(op),
             SupportedOp::Rescaled(op) => Op::<Fp>::requires_homogenous_input_scales(op),
+            SupportedOp::Requantized(op) => Op::<Fp>::requires_homogenous_input_scales(op),
+            SupportedOp::Custom(op) => Op::<Fp>::requires_homogenous_input_scales(op),
         }
     }
 
@@ -249,6 +401,8 @@ impl Op<Fp> for SupportedOp {
             SupportedOp::Constant(op) => Box::new(op.clone()),
             SupportedOp::Unknown(op) => Box::new(op.clone()),
             SupportedOp::Rescaled(op) => Box::new(op.clone()),
+            SupportedOp::Requantized(op) => Box::new(op.clone()),
+            SupportedOp::Custom(op) => Box::new(op.clone()),
         }
     }
 
@@ -261,6 +415,8 @@ impl Op<Fp> for SupportedOp {
             SupportedOp::Constant(op) => Op::<Fp>::as_string(op),
             SupportedOp::Unknown(op) => Op::<Fp>::as_string(op),
             SupportedOp::Rescaled(op) => Op::<Fp>::as_string(op),
+            SupportedOp::Requantized(op) => Op::<Fp>::as_string(op),
+            SupportedOp::Custom(op) => Op::<Fp>::as_string(op),
         }
     }
 
@@ -277,6 +433,8 @@ impl Op<Fp> for SupportedOp {
             SupportedOp::Constant(op) => Op::<Fp>::required_lookups(op),
             SupportedOp::Unknown(op) => Op::<Fp>::required_lookups(op),
             SupportedOp::Rescaled(op) => Op::<Fp>::required_lookups(op),
+            SupportedOp::Requantized(op) => Op::<Fp>::required_lookups(op),
+            SupportedOp::Custom(op) => Op::<Fp>::required_lookups(op),
         }
     }
 
@@ -294,6 +452,8 @@ impl Op<Fp> for SupportedOp {
             SupportedOp::Constant(op) => op.rescale(in_scales, out_scale),
             SupportedOp::Unknown(op) => op.rescale(in_scales, out_scale),
             SupportedOp::Rescaled(op) => op.rescale(in_scales, out_scale),
+            SupportedOp::Requantized(op) => op.rescale(in_scales, out_scale),
+            SupportedOp::Custom(op) => op.rescale(in_scales, out_scale),
         }
     }
 
@@ -306,6 +466,8 @@ impl Op<Fp> for SupportedOp {
             SupportedOp::Constant(op) => Op::<Fp>::out_scale(op, in_scales, global),
             SupportedOp::Unknown(op) => Op::<Fp>::out_scale(op, in_scales, global),
             SupportedOp::Rescaled(op) => Op::<Fp>::out_scale(op, in_scales, global),
+            SupportedOp::Requantized(op) => Op::<Fp>::out_scale(op, in_scales, global),
+            SupportedOp::Custom(op) => Op::<Fp>::out_scale(op, in_scales, global),
         }
     }
 }
@@ -383,14 +545,19 @@ impl Node {
     /// # Arguments:
     /// * `node` - [OnnxNode]
     /// * `other_nodes` - [BTreeMap] of other previously initialized [Node]s in the computational graph.
+    /// * `scale` - The (activation) scale of the circuit.
+    /// * `weight_scale` - The scale constants are quantized at, if different from `scale`, see [crate::RunArgs::weight_scale].
     /// * `public_params` - flag if parameters of model are public
+    /// * `unsupported_ops` - How the node is handled if its op is unsupported, see [crate::RunArgs::unsupported_ops].
     /// * `idx` - The node's unique identifier.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn new(
         node: OnnxNode<TypedFact, Box<dyn TypedOp>>,
         other_nodes: &mut BTreeMap<usize, super::NodeType>,
         scale: u32,
+        weight_scale: Option<u32>,
         param_visibility: Visibility,
+        unsupported_ops: super::UnsupportedOpPolicy,
         idx: usize,
     ) -> Result<Self, Box<dyn Error>> {
         trace!("Create {:?}", node);
@@ -410,8 +577,15 @@ impl Node {
             inputs.push(other_nodes.get(i).ok_or("input not found").unwrap().clone())
         });
 
-        let (mut opkind, deleted_indices) =
-            new_op_from_onnx(idx, scale, param_visibility, node.clone(), &mut inputs)?; // parses the op name
+        let (mut opkind, deleted_indices) = new_op_from_onnx(
+            idx,
+            scale,
+            weight_scale.unwrap_or(scale),
+            param_visibility,
+            unsupported_ops,
+            node.clone(),
+            &mut inputs,
+        )?; // parses the op name
 
         // we can only take the inputs as mutable once -- so we need to collect them first
         other_nodes.extend(
@@ -439,11 +613,24 @@ impl Node {
                 inputs[idx].out_scales()[*outlet]
             })
             .collect();
-        opkind = opkind.rescale(in_scales.clone(), scale).into();
-        let out_scale = match in_scales.len() {
-            0 => scale,
+        // with a separate weight scale, products take their operands at their own scales (rather than rescaling
+        // them to a common scale) and are requantized back down to the activation scale
+        let requantize = weight_scale.is_some() && opkind.is_product();
+        if !requantize {
+            opkind = opkind.rescale(in_scales.clone(), scale).into();
+        }
+        let mut out_scale = match (&opkind, in_scales.len()) {
+            (SupportedOp::Constant(_), _) => weight_scale.unwrap_or(scale),
+            (_, 0) => scale,
             _ => opkind.out_scale(in_scales, scale),
         };
+        if requantize && out_scale > scale {
+            opkind = SupportedOp::Requantized(Requantized {
+                inner: Box::new(opkind),
+                denom: super::scale_to_multiplier(out_scale - scale) as u128,
+            });
+            out_scale = scale;
+        }
 
         // get the output shape
         let out_dims = {
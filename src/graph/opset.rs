@@ -0,0 +1,293 @@
+use super::GraphError;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tract_onnx::pb::{ModelProto, NodeProto};
+
+/// The oldest opset of the default (`ai.onnx`) domain ezkl reads models from.
+pub const MIN_OPSET: i64 = 7;
+/// The newest opset of the default (`ai.onnx`) domain ezkl has checked its ops against. Newer models are still read,
+/// with a warning, as ops whose semantics changed since may be misinterpreted.
+pub const MAX_OPSET: i64 = 18;
+
+/// Ops which take some of their arguments as inputs (rather than attributes) from a given opset on, as
+/// `(op_type, opset, input indices)`. The circuit is laid out for these arguments' values at load time, so they have
+/// to be constants once the model is loaded. An input computed by the model (e.g from the shape of a tensor) may still
+/// be folded into one, which is only known after tract has decluttered the model, so these are warned about.
+const CONSTANT_INPUTS: &[(&str, i64, &[usize])] = &[
+    // min and max moved from attributes to inputs
+    ("Clip", 11, &[1, 2]),
+    ("Pad", 11, &[1, 2]),
+    ("Resize", 11, &[1, 2, 3]),
+    ("ReduceSum", 13, &[1]),
+    ("Split", 13, &[1]),
+    ("Squeeze", 13, &[1]),
+    ("Unsqueeze", 13, &[1]),
+    ("ReduceMax", 18, &[1]),
+    ("ReduceMean", 18, &[1]),
+    ("ReduceMin", 18, &[1]),
+    ("ReduceProd", 18, &[1]),
+    ("ReduceSumSquare", 18, &[1]),
+    ("ReduceL1", 18, &[1]),
+    ("ReduceL2", 18, &[1]),
+];
+
+/// How an [OpsetIssue] affects the circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OpsetSeverity {
+    /// The op may be interpreted differently from the semantics of the model's opset.
+    Warning,
+    /// The op can't be laid out per the semantics of the model's opset.
+    Error,
+}
+
+/// A node (or the model as a whole, with an empty node name and op type) whose semantics at the model's opset ezkl
+/// doesn't (or may not) follow.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpsetIssue {
+    /// The name of the node.
+    pub node: String,
+    /// The op type of the node.
+    pub op_type: String,
+    /// Whether the node is rejected or merely warned about.
+    pub severity: OpsetSeverity,
+    /// What differs at the model's opset.
+    pub message: String,
+}
+
+/// The opsets a model was exported with and the issues with reading its ops at these opsets.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpsetReport {
+    /// The `(domain, version)` opsets the model imports, with the default domain as `ai.onnx`.
+    pub opsets: Vec<(String, i64)>,
+    /// The issues found, see [OpsetSeverity].
+    pub issues: Vec<OpsetIssue>,
+}
+
+fn is_default_domain(domain: &str) -> bool {
+    domain.is_empty() || domain == "ai.onnx"
+}
+
+impl OpsetReport {
+    /// Checks the model's default domain opset against [MIN_OPSET] and [MAX_OPSET], and its ops against the semantic
+    /// changes of the opsets they are read at.
+    pub fn new(model: &ModelProto) -> Self {
+        let opsets = model
+            .opset_import
+            .iter()
+            .map(|opset| {
+                let domain = match is_default_domain(&opset.domain) {
+                    true => "ai.onnx".to_string(),
+                    false => opset.domain.clone(),
+                };
+                (domain, opset.version)
+            })
+            .collect::<Vec<_>>();
+
+        let mut report = OpsetReport {
+            opsets,
+            issues: vec![],
+        };
+
+        let opset = match report.default_opset() {
+            Some(opset) => opset,
+            None => {
+                report.model_issue(
+                    OpsetSeverity::Error,
+                    "the model doesn't import the default (ai.onnx) opset".to_string(),
+                );
+                return report;
+            }
+        };
+        if opset < MIN_OPSET {
+            report.model_issue(
+                OpsetSeverity::Error,
+                format!(
+                    "opset {} is older than the oldest supported opset {}",
+                    opset, MIN_OPSET
+                ),
+            );
+        } else if opset > MAX_OPSET {
+            report.model_issue(
+                OpsetSeverity::Warning,
+                format!(
+                    "opset {} is newer than opset {}, ops whose semantics changed since may be misinterpreted",
+                    opset, MAX_OPSET
+                ),
+            );
+        }
+
+        if let Some(graph) = &model.graph {
+            // initializers and the outputs of constant nodes
+            let mut constants: HashSet<&str> =
+                graph.initializer.iter().map(|t| t.name.as_str()).collect();
+            for node in graph.node.iter().filter(|n| n.op_type == "Constant") {
+                constants.extend(node.output.iter().map(|o| o.as_str()));
+            }
+            for node in graph.node.iter().filter(|n| is_default_domain(&n.domain)) {
+                report.check_node(node, opset, &constants);
+            }
+        }
+
+        report
+    }
+
+    fn model_issue(&mut self, severity: OpsetSeverity, message: String) {
+        self.issues.push(OpsetIssue {
+            node: String::new(),
+            op_type: String::new(),
+            severity,
+            message,
+        });
+    }
+
+    fn check_node(&mut self, node: &NodeProto, opset: i64, constants: &HashSet<&str>) {
+        let mut issue = |severity, message| {
+            self.issues.push(OpsetIssue {
+                node: node.name.clone(),
+                op_type: node.op_type.clone(),
+                severity,
+                message,
+            })
+        };
+
+        for (op_type, since, indices) in CONSTANT_INPUTS {
+            if node.op_type != *op_type || opset < *since {
+                continue;
+            }
+            for i in indices.iter() {
+                match node.input.get(*i) {
+                    // omitted optional inputs
+                    None => {}
+                    Some(input) if input.is_empty() || constants.contains(input.as_str()) => {}
+                    Some(input) => issue(
+                        OpsetSeverity::Warning,
+                        format!(
+                            "from opset {} input {} ({}) is an argument of the op, which has to fold into a constant when the model is loaded",
+                            since, i, input
+                        ),
+                    ),
+                }
+            }
+        }
+
+        // before opset 13 softmax normalizes over the input flattened from `axis` (by default 1) on, whereas
+        // the circuit's softmax is over the last axis
+        if node.op_type == "Softmax" && opset < 13 {
+            let axis = node
+                .attribute
+                .iter()
+                .find(|a| a.name == "axis")
+                .map(|a| a.i)
+                .unwrap_or(1);
+            if axis != -1 {
+                issue(
+                    OpsetSeverity::Warning,
+                    format!(
+                        "before opset 13 softmax is over all axes from axis {} on, which is only the last axis if it is the input's last",
+                        axis
+                    ),
+                );
+            }
+        }
+    }
+
+    /// The version of the default (`ai.onnx`) domain opset.
+    pub fn default_opset(&self) -> Option<i64> {
+        self.opsets
+            .iter()
+            .find(|(domain, _)| domain == "ai.onnx")
+            .map(|(_, version)| *version)
+    }
+
+    /// Logs the warnings, and fails on the first error.
+    pub fn check(&self) -> Result<(), GraphError> {
+        for issue in &self.issues {
+            match issue.severity {
+                OpsetSeverity::Warning => {
+                    warn!("node {} ({}): {}", issue.node, issue.op_type, issue.message)
+                }
+                OpsetSeverity::Error => {
+                    return Err(GraphError::UnsupportedOpset(format!(
+                        "node {} ({}): {}",
+                        issue.node, issue.op_type, issue.message
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tract_onnx::pb::{GraphProto, OperatorSetIdProto, TensorProto};
+
+    fn model(opset: i64, nodes: Vec<NodeProto>) -> ModelProto {
+        ModelProto {
+            opset_import: vec![OperatorSetIdProto {
+                domain: String::new(),
+                version: opset,
+            }],
+            graph: Some(GraphProto {
+                node: nodes,
+                initializer: vec![TensorProto {
+                    name: "min".into(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn clip(inputs: &[&str]) -> NodeProto {
+        NodeProto {
+            name: "clip".into(),
+            op_type: "Clip".into(),
+            input: inputs.iter().map(|i| i.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_opset_report() {
+        // constant (or omitted) clip bounds are fine
+        let report = OpsetReport::new(&model(13, vec![clip(&["x", "min", ""])]));
+        assert_eq!(report.opsets, vec![("ai.onnx".to_string(), 13)]);
+        assert_eq!(report.default_opset(), Some(13));
+        assert!(report.issues.is_empty());
+        assert!(report.check().is_ok());
+
+        // a bound computed by the model is warned about, as it may fold into a constant
+        let report = OpsetReport::new(&model(13, vec![clip(&["x", "min", "y"])]));
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].severity, OpsetSeverity::Warning);
+        assert!(report.check().is_ok());
+
+        // before opset 11 the bounds were attributes
+        let report = OpsetReport::new(&model(10, vec![clip(&["x"])]));
+        assert!(report.issues.is_empty());
+
+        let report = OpsetReport::new(&model(MIN_OPSET - 1, vec![]));
+        assert!(report.check().is_err());
+        let report = OpsetReport::new(&model(MAX_OPSET + 1, vec![]));
+        assert_eq!(report.issues[0].severity, OpsetSeverity::Warning);
+        assert!(report.check().is_ok());
+    }
+
+    #[test]
+    fn test_example_opset() {
+        let mut reader = std::fs::File::open("examples/onnx/1l_relu/network.onnx").unwrap();
+        let proto = tract_onnx::prelude::Framework::proto_model_for_read(
+            &super::super::utilities::onnx(),
+            &mut reader,
+        )
+        .unwrap();
+        let report = OpsetReport::new(&proto);
+        let opset = report.default_opset().unwrap();
+        assert!((MIN_OPSET..=MAX_OPSET).contains(&opset));
+        assert!(report.check().is_ok());
+    }
+}
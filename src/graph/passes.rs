@@ -0,0 +1,633 @@
+use super::model::NodeType;
+use super::node::{Node, Outlet, Requantized, SupportedOp};
+use super::{scale_to_multiplier, GraphError};
+use crate::circuit::lookup::{FusedOp, LookupOp};
+use crate::circuit::poly::PolyOp;
+use crate::fieldutils::felt_to_i128;
+use log::debug;
+use std::collections::{BTreeMap, HashMap};
+
+/// Counts the number of times each node's output is consumed (by other nodes or as a graph output).
+fn num_consumers(nodes: &BTreeMap<usize, NodeType>, outputs: &[Outlet]) -> HashMap<usize, usize> {
+    let mut consumers = HashMap::new();
+    for (idx, _) in nodes
+        .values()
+        .flat_map(|n| n.inputs())
+        .chain(outputs.iter().cloned())
+    {
+        *consumers.entry(idx).or_insert(0) += 1;
+    }
+    consumers
+}
+
+/// Returns the value of a node if it is a scalar constant.
+fn scalar_const(nodes: &BTreeMap<usize, NodeType>, outlet: &Outlet) -> Option<i128> {
+    match nodes.get(&outlet.0) {
+        Some(NodeType::Node(Node {
+            opkind: SupportedOp::Constant(c),
+            ..
+        })) if c.quantized_values.len() == 1 => Some(felt_to_i128(c.quantized_values[0])),
+        _ => None,
+    }
+}
+
+/// Expresses a node as a single elementwise step over one of its inputs (if possible).
+fn as_fused_step(
+    nodes: &BTreeMap<usize, NodeType>,
+    node: &Node,
+    fuse_consts: bool,
+) -> Option<(FusedOp, Outlet)> {
+    let step = match (&node.opkind, node.inputs.as_slice()) {
+        (SupportedOp::Nonlinear(op), [a]) => match op {
+            // don't nest already fused chains
+            LookupOp::Fused { .. } => None,
+            _ => Some((FusedOp::Lookup(op.clone()), *a)),
+        },
+        (SupportedOp::Linear(PolyOp::Neg), [a]) if fuse_consts => Some((FusedOp::Mult(-1), *a)),
+        (SupportedOp::Linear(PolyOp::Mult), [a, b]) if fuse_consts => {
+            match (scalar_const(nodes, a), scalar_const(nodes, b)) {
+                (None, Some(c)) => Some((FusedOp::Mult(c), *a)),
+                (Some(c), None) => Some((FusedOp::Mult(c), *b)),
+                _ => None,
+            }
+        }
+        (SupportedOp::Linear(PolyOp::Add), [a, b]) if fuse_consts => {
+            match (scalar_const(nodes, a), scalar_const(nodes, b)) {
+                (None, Some(c)) => Some((FusedOp::Add(c), *a)),
+                (Some(c), None) => Some((FusedOp::Add(c), *b)),
+                _ => None,
+            }
+        }
+        (SupportedOp::Linear(PolyOp::Sub), [a, b]) if fuse_consts => {
+            scalar_const(nodes, b).map(|c| (FusedOp::Add(-c), *a))
+        }
+        _ => None,
+    }?;
+
+    // broadcasting steps change the shape of their input and can't be fused
+    match nodes.get(&step.1 .0) {
+        Some(input) if input.out_dims().get(step.1 .1) == Some(&node.out_dims) => Some(step),
+        _ => None,
+    }
+}
+
+/// Replaces runs of elementwise nodes (lookups, and multiplications / additions by scalar constants
+/// if `fuse_consts` is set) applied one after the other to the same tensor with a single
+/// [LookupOp::Fused] node, so that the whole chain costs a single lookup.
+///
+/// Only runs containing at least one lookup are fused, and every intermediate node of a run must feed only the next
+/// node of the run. Scalar constants are baked into the fused lookup table, so `fuse_consts` should only be set
+/// when params are already part of the circuit (i.e public).
+///
+/// Returns the number of nodes that were removed from the graph.
+pub fn fuse_elementwise_lookups(
+    nodes: &mut BTreeMap<usize, NodeType>,
+    outputs: &[Outlet],
+    fuse_consts: bool,
+) -> usize {
+    let consumers = num_consumers(nodes, outputs);
+
+    // chains of (node idx, step), with the outlet feeding the head of the chain
+    let mut chains: Vec<(Outlet, Vec<(usize, FusedOp)>)> = vec![];
+    // maps the last node of a chain to the chain's position in `chains`
+    let mut chain_ends: HashMap<usize, usize> = HashMap::new();
+
+    for (idx, node) in nodes.iter() {
+        let node = match node {
+            NodeType::Node(n) => n,
+            NodeType::SubGraph { .. } => continue,
+        };
+        let (step, input) = match as_fused_step(nodes, node, fuse_consts) {
+            Some(s) => s,
+            None => continue,
+        };
+
+        let extends = input.1 == 0 && consumers.get(&input.0) == Some(&1);
+        match chain_ends.remove(&input.0) {
+            Some(c) if extends => {
+                chains[c].1.push((*idx, step));
+                chain_ends.insert(*idx, c);
+            }
+            _ => {
+                chains.push((input, vec![(*idx, step)]));
+                chain_ends.insert(*idx, chains.len() - 1);
+            }
+        }
+    }
+
+    let mut num_removed = 0;
+    for (input, chain) in chains {
+        let has_lookup = chain
+            .iter()
+            .any(|(_, step)| matches!(step, FusedOp::Lookup(_)));
+        if chain.len() < 2 || !has_lookup {
+            continue;
+        }
+
+        let last = chain.last().unwrap().0;
+        let (out_dims, out_scale) = match nodes.get(&last) {
+            Some(NodeType::Node(n)) => (n.out_dims.clone(), n.out_scale),
+            _ => continue,
+        };
+
+        for (idx, _) in &chain[..chain.len() - 1] {
+            nodes.remove(idx);
+        }
+        num_removed += chain.len() - 1;
+
+        let ops = chain.into_iter().map(|(_, step)| step).collect::<Vec<_>>();
+        debug!("fusing {} elementwise ops into node {}", ops.len(), last);
+
+        nodes.insert(
+            last,
+            NodeType::Node(Node {
+                opkind: SupportedOp::Nonlinear(LookupOp::Fused { ops, out_scale }),
+                out_scale,
+                inputs: vec![input],
+                out_dims,
+                idx: last,
+            }),
+        );
+    }
+
+    // the scalar constants of fused steps are now part of the lookup table
+    let consumers = num_consumers(nodes, outputs);
+    nodes.retain(|idx, n| match n {
+        NodeType::Node(Node {
+            opkind: SupportedOp::Constant(_),
+            ..
+        }) if !consumers.contains_key(idx) => {
+            num_removed += 1;
+            false
+        }
+        _ => true,
+    });
+
+    num_removed
+}
+
+/// Returns the input a node forwards unchanged, if the node is a no-op: an identity (e.g a dropout or a rounding
+/// op, which are identities in the circuit), or a reshape or flatten to its input's shape, at its input's scale.
+fn as_noop(nodes: &BTreeMap<usize, NodeType>, node: &Node) -> Option<Outlet> {
+    let input = match node.inputs.as_slice() {
+        [input] => *input,
+        _ => return None,
+    };
+    let forwards = match &node.opkind {
+        SupportedOp::Linear(PolyOp::Identity) => true,
+        SupportedOp::Linear(PolyOp::Reshape(dims)) | SupportedOp::Linear(PolyOp::Flatten(dims)) => {
+            *dims == node.out_dims
+        }
+        _ => false,
+    };
+    let producer = nodes.get(&input.0)?;
+    let unchanged = producer.out_dims().get(input.1) == Some(&node.out_dims)
+        && producer.out_scales().get(input.1) == Some(&node.out_scale);
+    match forwards && unchanged {
+        true => Some(input),
+        false => None,
+    }
+}
+
+/// Removes nodes that forward their input unchanged (see [as_noop]), rewiring their consumers, and the graph
+/// `outputs`, to the producer of the removed node's input directly. No-op nodes don't take any rows in the circuit
+/// but are still visited during layout, so removing them shrinks the graph and speeds up synthesis.
+///
+/// Returns the number of nodes that were removed from the graph.
+pub fn remove_noop_nodes(nodes: &mut BTreeMap<usize, NodeType>, outputs: &mut [Outlet]) -> usize {
+    // nodes are in topological order, so a chain of no-ops resolves to the first producer that isn't one
+    let mut replacements: HashMap<usize, Outlet> = HashMap::new();
+    for (idx, node) in nodes.iter() {
+        if let NodeType::Node(n) = node {
+            if let Some(input) = as_noop(nodes, n) {
+                let input = replacements.get(&input.0).copied().unwrap_or(input);
+                replacements.insert(*idx, input);
+            }
+        }
+    }
+    if replacements.is_empty() {
+        return 0;
+    }
+
+    nodes.retain(|idx, _| !replacements.contains_key(idx));
+    let rewire = |outlet: &mut Outlet| {
+        if let Some(replacement) = replacements.get(&outlet.0) {
+            *outlet = *replacement;
+        }
+    };
+    for node in nodes.values_mut() {
+        match node {
+            NodeType::Node(n) => n.inputs.iter_mut().for_each(rewire),
+            NodeType::SubGraph { inputs, .. } => inputs.iter_mut().for_each(rewire),
+        }
+    }
+    outputs.iter_mut().for_each(rewire);
+
+    debug!("removed {} no-op nodes", replacements.len());
+    replacements.len()
+}
+
+/// Requantizes the nodes producing the graph `outputs` down to the fixed point `scale`, wrapping each in a
+/// [Requantized] op that divides its output by `2^(out_scale - scale)`. Outputs already at `scale` are left as they
+/// are. The producers have to be ops (not inputs, constants or subgraphs) whose output isn't also consumed by other
+/// nodes, as these were built against the producer's original scale.
+///
+/// Returns the number of nodes that were requantized.
+pub fn requantize_outputs(
+    nodes: &mut BTreeMap<usize, NodeType>,
+    outputs: &[Outlet],
+    scale: u32,
+) -> Result<usize, GraphError> {
+    let consumers = num_consumers(nodes, outputs);
+    let mut output_nodes = outputs.iter().map(|(idx, _)| *idx).collect::<Vec<_>>();
+    output_nodes.sort();
+    output_nodes.dedup();
+
+    let mut num_requantized = 0;
+    for idx in output_nodes {
+        let node = match nodes.get_mut(&idx) {
+            Some(NodeType::Node(n)) => n,
+            _ => {
+                return Err(GraphError::OutputScale(format!(
+                    "output node {} is a subgraph, which can't be requantized",
+                    idx
+                )))
+            }
+        };
+        if node.out_scale == scale {
+            continue;
+        }
+        if node.out_scale < scale {
+            return Err(GraphError::OutputScale(format!(
+                "output node {} is at scale {}, below the output scale {}",
+                idx, node.out_scale, scale
+            )));
+        }
+        if matches!(
+            node.opkind,
+            SupportedOp::Input(_) | SupportedOp::Constant(_)
+        ) {
+            return Err(GraphError::OutputScale(format!(
+                "output node {} is an input or constant, which can't be requantized",
+                idx
+            )));
+        }
+        let num_outputs = outputs.iter().filter(|(i, _)| *i == idx).count();
+        if consumers.get(&idx).copied().unwrap_or(0) > num_outputs {
+            return Err(GraphError::OutputScale(format!(
+                "output node {} is also consumed by other nodes",
+                idx
+            )));
+        }
+
+        requantize(node, node.out_scale - scale);
+        num_requantized += 1;
+    }
+
+    debug!("requantized {} output nodes", num_requantized);
+    Ok(num_requantized)
+}
+
+/// Wraps `node` in a [Requantized] op dividing its output by `2^bits`, lowering its scale by `bits`.
+fn requantize(node: &mut Node, bits: u32) {
+    let denom = scale_to_multiplier(bits) as u128;
+    let opkind = std::mem::replace(&mut node.opkind, SupportedOp::Linear(PolyOp::Identity));
+    node.opkind = match opkind {
+        // fold into an existing requantization rather than dividing twice
+        SupportedOp::Requantized(r) => SupportedOp::Requantized(Requantized {
+            inner: r.inner,
+            denom: r.denom * denom,
+        }),
+        opkind => SupportedOp::Requantized(Requantized {
+            inner: Box::new(opkind),
+            denom,
+        }),
+    };
+    node.out_scale -= bits;
+}
+
+/// Scales the output of `node` down by `bits`. Unlike [requantize_outputs] this runs as the graph is built (see
+/// [super::GraphSettings::rescaled_nodes]), such that the nodes consuming `node` are built against its lowered scale.
+/// An input is quantized at the lowered scale, any other op is wrapped in a [Requantized] op that divides its output
+/// by `2^bits`. Constants can't be scaled down, nor can a node by more than its scale.
+pub fn scale_down(node: &mut Node, bits: u32) -> Result<(), GraphError> {
+    if bits > node.out_scale {
+        return Err(GraphError::Rebalance(format!(
+            "node {} is at scale {}, which can't be scaled down by {} bits",
+            node.idx, node.out_scale, bits
+        )));
+    }
+    match &mut node.opkind {
+        SupportedOp::Constant(_) => {
+            return Err(GraphError::Rebalance(format!(
+                "node {} is a constant, which can't be scaled down",
+                node.idx
+            )))
+        }
+        _ if bits == 0 => {}
+        SupportedOp::Input(input) => {
+            input.scale -= bits;
+            node.out_scale -= bits;
+        }
+        _ => requantize(node, bits),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Constant, Input, Op};
+    use crate::fieldutils::i128_to_felt;
+    use crate::graph::Visibility;
+    use crate::tensor::Tensor;
+    use halo2curves::bn256::Fr as Fp;
+
+    fn node(idx: usize, opkind: SupportedOp, inputs: Vec<Outlet>) -> (usize, NodeType) {
+        (
+            idx,
+            NodeType::Node(Node {
+                opkind,
+                out_scale: 0,
+                inputs,
+                out_dims: vec![4],
+                idx,
+            }),
+        )
+    }
+
+    fn constant(idx: usize, value: i128) -> (usize, NodeType) {
+        let mut quantized = Tensor::new(Some(&[i128_to_felt::<Fp>(value)]), &[1]).unwrap();
+        quantized.set_visibility(Visibility::Public);
+        let mut opkind =
+            Constant::new(quantized, Tensor::new(Some(&[value as f32]), &[1]).unwrap());
+        opkind.num_uses = 1;
+        let (idx, mut n) = node(idx, SupportedOp::Constant(opkind), vec![]);
+        if let NodeType::Node(n) = &mut n {
+            n.out_dims = vec![1];
+        }
+        (idx, n)
+    }
+
+    fn forward(nodes: &BTreeMap<usize, NodeType>, input: &Tensor<Fp>, output: usize) -> Tensor<Fp> {
+        let mut results: BTreeMap<usize, Tensor<Fp>> = BTreeMap::new();
+        for (idx, n) in nodes {
+            let inputs = match n.is_input() {
+                true => vec![input.clone()],
+                false => n.inputs().iter().map(|(i, _)| results[i].clone()).collect(),
+            };
+            results.insert(*idx, Op::<Fp>::f(&n.opkind(), &inputs).unwrap().output);
+        }
+        results[&output].clone()
+    }
+
+    #[test]
+    fn fuses_affine_then_relu() {
+        // relu(3 * x + 2) followed by a sigmoid that is also consumed elsewhere
+        let mut nodes: BTreeMap<usize, NodeType> = vec![
+            node(
+                0,
+                SupportedOp::Input(Input {
+                    scale: 0,
+                    normalization: None,
+                }),
+                vec![],
+            ),
+            constant(1, 3),
+            node(2, SupportedOp::Linear(PolyOp::Mult), vec![(0, 0), (1, 0)]),
+            constant(3, 2),
+            node(4, SupportedOp::Linear(PolyOp::Add), vec![(2, 0), (3, 0)]),
+            node(
+                5,
+                SupportedOp::Nonlinear(LookupOp::ReLU { scale: 1 }),
+                vec![(4, 0)],
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let outputs = vec![(5, 0)];
+
+        let input = Tensor::new(Some(&[-3, -1, 0, 5].map(i128_to_felt::<Fp>)), &[4]).unwrap();
+        let expected = forward(&nodes, &input, 5);
+
+        // the fused chain replaces two affine ops and their constants
+        assert_eq!(fuse_elementwise_lookups(&mut nodes, &outputs, true), 4);
+        assert_eq!(nodes.len(), 2);
+        assert!(matches!(
+            nodes[&5].opkind(),
+            SupportedOp::Nonlinear(LookupOp::Fused { .. })
+        ));
+        assert_eq!(nodes[&5].inputs(), vec![(0, 0)]);
+        assert_eq!(forward(&nodes, &input, 5), expected);
+    }
+
+    #[test]
+    fn skips_constants_and_shared_outputs() {
+        let mut nodes: BTreeMap<usize, NodeType> = vec![
+            node(
+                0,
+                SupportedOp::Input(Input {
+                    scale: 0,
+                    normalization: None,
+                }),
+                vec![],
+            ),
+            constant(1, 3),
+            node(2, SupportedOp::Linear(PolyOp::Mult), vec![(0, 0), (1, 0)]),
+            node(
+                3,
+                SupportedOp::Nonlinear(LookupOp::ReLU { scale: 1 }),
+                vec![(2, 0)],
+            ),
+            node(4, SupportedOp::Nonlinear(LookupOp::Sign), vec![(3, 0)]),
+        ]
+        .into_iter()
+        .collect();
+
+        // without fusing constants only the lookups can be fused, but node 3 is also an output
+        let outputs = vec![(3, 0), (4, 0)];
+        assert_eq!(
+            fuse_elementwise_lookups(&mut nodes.clone(), &outputs, false),
+            0
+        );
+
+        let outputs = vec![(4, 0)];
+        assert_eq!(fuse_elementwise_lookups(&mut nodes, &outputs, false), 1);
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(nodes[&4].inputs(), vec![(2, 0)]);
+    }
+
+    #[test]
+    fn removes_noop_nodes() {
+        let reshape = |idx, dims: Vec<usize>, input| {
+            let (idx, mut n) = node(
+                idx,
+                SupportedOp::Linear(PolyOp::Reshape(dims.clone())),
+                vec![input],
+            );
+            if let NodeType::Node(n) = &mut n {
+                n.out_dims = dims;
+            }
+            (idx, n)
+        };
+        let mut nodes: BTreeMap<usize, NodeType> = vec![
+            node(
+                0,
+                SupportedOp::Input(Input {
+                    scale: 0,
+                    normalization: None,
+                }),
+                vec![],
+            ),
+            node(1, SupportedOp::Linear(PolyOp::Identity), vec![(0, 0)]),
+            reshape(2, vec![4], (1, 0)),
+            node(
+                3,
+                SupportedOp::Nonlinear(LookupOp::ReLU { scale: 1 }),
+                vec![(2, 0)],
+            ),
+            node(
+                4,
+                SupportedOp::Linear(PolyOp::Flatten(vec![4])),
+                vec![(3, 0)],
+            ),
+            // an actual reshape is kept, but not the identity following it
+            reshape(5, vec![2, 2], (4, 0)),
+            node(6, SupportedOp::Linear(PolyOp::Identity), vec![(5, 0)]),
+        ]
+        .into_iter()
+        .collect();
+        if let Some(NodeType::Node(n)) = nodes.get_mut(&6) {
+            n.out_dims = vec![2, 2];
+        }
+        let mut outputs = vec![(4, 0), (6, 0)];
+
+        let input = Tensor::new(Some(&[-3, -1, 0, 5].map(i128_to_felt::<Fp>)), &[4]).unwrap();
+        let expected = [forward(&nodes, &input, 4), forward(&nodes, &input, 6)];
+
+        assert_eq!(remove_noop_nodes(&mut nodes, &mut outputs), 4);
+        assert_eq!(nodes.keys().copied().collect::<Vec<_>>(), vec![0, 3, 5]);
+        assert_eq!(nodes[&3].inputs(), vec![(0, 0)]);
+        assert_eq!(nodes[&5].inputs(), vec![(3, 0)]);
+        assert_eq!(outputs, vec![(3, 0), (5, 0)]);
+        assert_eq!(
+            [forward(&nodes, &input, 3), forward(&nodes, &input, 5)],
+            expected
+        );
+    }
+
+    #[test]
+    fn requantizes_outputs() {
+        let with_scale = |(idx, mut n): (usize, NodeType), scale| {
+            if let NodeType::Node(n) = &mut n {
+                n.out_scale = scale;
+            }
+            (idx, n)
+        };
+        // x * x at scale 7 is at scale 14
+        let nodes: BTreeMap<usize, NodeType> = vec![
+            with_scale(
+                node(
+                    0,
+                    SupportedOp::Input(Input {
+                        scale: 7,
+                        normalization: None,
+                    }),
+                    vec![],
+                ),
+                7,
+            ),
+            with_scale(
+                node(1, SupportedOp::Linear(PolyOp::Mult), vec![(0, 0), (0, 0)]),
+                14,
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let outputs = vec![(1, 0)];
+
+        // -3, -1, 0.5 and 2.5
+        let input =
+            Tensor::new(Some(&[-384, -128, 64, 320].map(i128_to_felt::<Fp>)), &[4]).unwrap();
+        let mut requantized = nodes.clone();
+        assert_eq!(
+            requantize_outputs(&mut requantized, &outputs, 7).unwrap(),
+            1
+        );
+        assert_eq!(requantized[&1].out_scales(), vec![7]);
+        assert_eq!(
+            forward(&requantized, &input, 1),
+            Tensor::new(Some(&[1152, 128, 32, 800].map(i128_to_felt::<Fp>)), &[4]).unwrap()
+        );
+
+        // outputs already at the output scale are left as they are
+        assert_eq!(
+            requantize_outputs(&mut requantized, &outputs, 7).unwrap(),
+            0
+        );
+        // requantizing up would add no precision
+        assert!(requantize_outputs(&mut nodes.clone(), &outputs, 15).is_err());
+
+        // nor can an output consumed by other nodes be requantized
+        let mut shared = nodes;
+        shared.extend([node(
+            2,
+            SupportedOp::Nonlinear(LookupOp::ReLU { scale: 1 }),
+            vec![(1, 0)],
+        )]);
+        assert!(requantize_outputs(&mut shared, &[(1, 0), (2, 0)], 7).is_err());
+    }
+
+    #[test]
+    fn scales_nodes_down() {
+        let (_, input) = node(
+            0,
+            SupportedOp::Input(Input {
+                scale: 7,
+                normalization: None,
+            }),
+            vec![],
+        );
+        let mut input = match input {
+            NodeType::Node(n) => Node { out_scale: 7, ..n },
+            _ => unreachable!(),
+        };
+        scale_down(&mut input, 2).unwrap();
+        assert_eq!(input.out_scale, 5);
+        assert!(matches!(
+            input.opkind,
+            SupportedOp::Input(Input { scale: 5, .. })
+        ));
+
+        // ops divide their output by 2^bits, folding into an existing requantization
+        let (_, relu) = node(
+            1,
+            SupportedOp::Nonlinear(LookupOp::ReLU { scale: 1 }),
+            vec![(0, 0)],
+        );
+        let mut relu = match relu {
+            NodeType::Node(n) => Node { out_scale: 5, ..n },
+            _ => unreachable!(),
+        };
+        scale_down(&mut relu, 1).unwrap();
+        scale_down(&mut relu, 2).unwrap();
+        assert_eq!(relu.out_scale, 2);
+        assert!(matches!(
+            &relu.opkind,
+            SupportedOp::Requantized(Requantized { denom: 8, .. })
+        ));
+        let x = Tensor::new(Some(&[-16, 16, 40].map(i128_to_felt::<Fp>)), &[3]).unwrap();
+        assert_eq!(
+            Op::<Fp>::f(&relu.opkind, &[x]).unwrap().output,
+            Tensor::new(Some(&[0, 2, 5].map(i128_to_felt::<Fp>)), &[3]).unwrap()
+        );
+
+        // nor past a scale of 0, nor constants
+        assert!(scale_down(&mut relu, 3).is_err());
+        let mut constant = match constant(2, 3).1 {
+            NodeType::Node(n) => Node { out_scale: 7, ..n },
+            _ => unreachable!(),
+        };
+        assert!(scale_down(&mut constant, 1).is_err());
+    }
+}
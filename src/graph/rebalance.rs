@@ -0,0 +1,293 @@
+use super::calibration::CalibrationSample;
+use super::{
+    quantize_float, scale_to_multiplier, GraphCircuit, GraphError, GraphSettings, Model, NodeType,
+    SupportedOp,
+};
+use crate::fieldutils::{felt_to_i128, i128_to_felt};
+use crate::tensor::Tensor;
+use halo2curves::bn256::Fr as Fp;
+use itertools::Itertools;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::PathBuf;
+
+/// The most rounds of scaling nodes down [rebalance_scales] runs before giving up.
+pub const MAX_REBALANCE_ROUNDS: usize = 64;
+
+/// A node [rebalance_scales] scaled down.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RescaledNode {
+    /// The node's index.
+    pub node: usize,
+    /// The operation performed by the node.
+    pub op: String,
+    /// The number of bits the node's output is scaled down by.
+    pub bits: u32,
+    /// The scale of the node's output, once scaled down.
+    pub out_scale: u32,
+}
+
+/// The nodes [rebalance_scales] scaled down, and how far the model's outputs are from the float model's before and
+/// after.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RebalanceReport {
+    /// The nodes that were scaled down, in node order.
+    pub rescaled: Vec<RescaledNode>,
+    /// The largest value input to any lookup over the calibration samples, once rebalanced.
+    pub max_lookup_inputs: i128,
+    /// The largest absolute difference between any (dequantized) output and the float model's before rebalancing,
+    /// i.e if the lookup bits were raised to fit the overflowing values instead.
+    pub max_error_before: f64,
+    /// The largest absolute difference between any (dequantized) output and the float model's once rebalanced.
+    pub max_error: f64,
+}
+
+/// The lookup inputs and output error of a model over the calibration samples.
+struct Evaluation {
+    /// The largest magnitude input to each node's lookups, in node order. Nodes within subgraphs are prefixed by the
+    /// index of the subgraph node (e.g `3/1`).
+    lookup_inputs: Vec<(String, i128)>,
+    max_lookup_inputs: i128,
+    max_error: f64,
+}
+
+fn evaluate(model: &Model, samples: &[CalibrationSample]) -> Result<Evaluation, Box<dyn Error>> {
+    let input_shapes = model.graph.input_shapes();
+    let input_scales = model.graph.get_input_scales();
+    let output_scales = model.graph.get_output_scales();
+
+    let mut evaluation = Evaluation {
+        lookup_inputs: vec![],
+        max_lookup_inputs: 0,
+        max_error: 0.0,
+    };
+    for sample in samples {
+        let inputs = sample
+            .inputs
+            .iter()
+            .zip(input_shapes.iter().zip(&input_scales))
+            .map(|(values, (shape, scale))| {
+                let felts = values
+                    .iter()
+                    .map(|x| quantize_float(x, 0.0, *scale).map(i128_to_felt))
+                    .collect::<Result<Vec<Fp>, _>>()?;
+                Tensor::new(Some(&felts), shape)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let res = model.forward(&inputs)?;
+
+        for range in res.lookup_ranges {
+            let magnitude = range.min.abs().max(range.max.abs());
+            match evaluation
+                .lookup_inputs
+                .iter_mut()
+                .find(|(node, _)| *node == range.node)
+            {
+                Some((_, max)) => *max = (*max).max(magnitude),
+                None => evaluation.lookup_inputs.push((range.node, magnitude)),
+            }
+        }
+        evaluation.max_lookup_inputs = evaluation.max_lookup_inputs.max(res.max_lookup_inputs);
+
+        for (output, (expected, scale)) in res
+            .outputs
+            .iter()
+            .zip(sample.outputs.iter().zip(&output_scales))
+        {
+            if output.len() != expected.len() {
+                return Err(Box::new(GraphError::InvalidInput(format!(
+                    "the reference output has {} elements, but the model outputs {}",
+                    expected.len(),
+                    output.len()
+                ))));
+            }
+            let mult = scale_to_multiplier(*scale);
+            for (x, e) in output.iter().zip(expected) {
+                let error = (felt_to_i128(*x) as f64 / mult - e).abs();
+                // a NaN reference is never within any error
+                if error.is_nan() || error > evaluation.max_error {
+                    evaluation.max_error = error;
+                }
+            }
+        }
+    }
+    Ok(evaluation)
+}
+
+/// Rebalances the scales of the model at `model_path` set up with `settings`, such that no value fed into a lookup
+/// over the calibration `samples` overflows the range of the settings' lookup bits (`2^(bits - 1)` in magnitude),
+/// without raising the bits of every lookup of the model.
+///
+/// The nodes are visited in order: the inputs of the first node whose lookup inputs overflow by `k` bits are scaled
+/// down by `k` bits (see [super::passes::scale_down]), and the model is rebuilt, its consumers against the lowered
+/// scales, and evaluated again until no lookup overflows. A node's lowered scale only costs precision from that node
+/// on, up to the next product requantized back to the global scale (or any rescaling of the inputs of an op to a
+/// common scale), so the model's accuracy elsewhere is kept. Scaling an input down quantizes it at the lowered scale.
+///
+/// Returns the settings with [GraphSettings::rescaled_nodes] set (and the circuit's lookups and constraints
+/// regenerated), and a report of the nodes that were scaled down and of the output error before and after. Fails if
+/// an overflowing node only takes constants, if a node would have to be scaled down past a scale of 0, or if an
+/// overflowing node is within a subgraph, which isn't rebalanced.
+pub fn rebalance_scales(
+    model_path: &PathBuf,
+    settings: &GraphSettings,
+    samples: &[CalibrationSample],
+) -> Result<(GraphSettings, RebalanceReport), Box<dyn Error>> {
+    let limit = 2i128.pow(settings.run_args.bits as u32 - 1);
+    let mut rebalanced = settings.clone();
+    let max_error_before =
+        evaluate(&Model::from_settings(settings, model_path)?, samples)?.max_error;
+
+    for _ in 0..MAX_REBALANCE_ROUNDS {
+        let model = Model::from_settings(&rebalanced, model_path)?;
+        let evaluation = evaluate(&model, samples)?;
+        let (node, max) = match evaluation
+            .lookup_inputs
+            .iter()
+            .find(|(_, max)| *max > limit)
+        {
+            Some(overflow) => overflow.clone(),
+            None => {
+                let report = RebalanceReport {
+                    rescaled: rebalanced
+                        .rescaled_nodes
+                        .iter()
+                        .map(|(idx, bits)| RescaledNode {
+                            node: *idx,
+                            op: model
+                                .graph
+                                .nodes
+                                .get(idx)
+                                .map(|n| n.as_str())
+                                .unwrap_or_default(),
+                            bits: *bits,
+                            out_scale: model
+                                .graph
+                                .nodes
+                                .get(idx)
+                                .map(|n| n.out_scales()[0])
+                                .unwrap_or_default(),
+                        })
+                        .collect(),
+                    max_lookup_inputs: evaluation.max_lookup_inputs,
+                    max_error_before,
+                    max_error: evaluation.max_error,
+                };
+                let circuit = GraphCircuit::new(model, rebalanced.run_args)?;
+                let rebalanced = GraphSettings {
+                    input_normalization: rebalanced.input_normalization,
+                    rescaled_nodes: rebalanced.rescaled_nodes,
+                    ..circuit.settings
+                };
+                return Ok((rebalanced, report));
+            }
+        };
+
+        let idx = node.parse::<usize>().map_err(|_| {
+            GraphError::Rebalance(format!(
+                "node {} within a subgraph takes lookup inputs of up to {}, beyond the range of {} bits",
+                node, max, settings.run_args.bits
+            ))
+        })?;
+        let bits = ((max as f64 / limit as f64).log2().ceil() as u32).max(1);
+        let producers = model
+            .graph
+            .nodes
+            .get(&idx)
+            .ok_or(GraphError::MissingNode(idx))?
+            .inputs()
+            .into_iter()
+            .map(|(producer, _)| producer)
+            .unique()
+            .filter(|producer| {
+                !matches!(
+                    model.graph.nodes.get(producer),
+                    Some(NodeType::Node(n)) if matches!(n.opkind, SupportedOp::Constant(_))
+                )
+            })
+            .collect_vec();
+        if producers.is_empty() {
+            return Err(Box::new(GraphError::Rebalance(format!(
+                "node {} takes lookup inputs of up to {}, beyond the range of {} bits, from constants only",
+                idx, max, settings.run_args.bits
+            ))));
+        }
+        debug!(
+            "node {} takes lookup inputs of up to {}, scaling nodes {:?} down by {} bits",
+            idx, max, producers, bits
+        );
+        for producer in producers {
+            *rebalanced.rescaled_nodes.entry(producer).or_default() += bits;
+        }
+    }
+
+    Err(Box::new(GraphError::Rebalance(format!(
+        "lookups still overflow after {} rounds of scaling nodes down",
+        MAX_REBALANCE_ROUNDS
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::tests::test_run_args;
+    use crate::RunArgs;
+
+    #[test]
+    fn test_rebalance_scales() {
+        let run_args = RunArgs {
+            bits: 9,
+            ..test_run_args()
+        };
+        let path: PathBuf = "examples/onnx/2l_relu_small/network.onnx".into();
+        let circuit = GraphCircuit::from_run_args(&run_args, &path).unwrap();
+        // relu(relu(x)), whose first lookup takes the input at up to 434 > 2^8
+        let samples = vec![CalibrationSample {
+            inputs: vec![vec![
+                3.394426107406616,
+                1.1624923944473267,
+                -0.5661267638206482,
+            ]],
+            outputs: vec![vec![3.394426107406616, 1.1624923944473267, 0.0]],
+        }];
+        let limit = 2i128.pow(run_args.bits as u32 - 1);
+        assert!(
+            evaluate(&circuit.model, &samples)
+                .unwrap()
+                .max_lookup_inputs
+                > limit
+        );
+
+        let (settings, report) = rebalance_scales(&path, &circuit.settings, &samples).unwrap();
+        // the input is quantized a bit coarser, rather than every lookup taking an extra bit
+        let model = Model::from_settings(&settings, &path).unwrap();
+        assert_eq!(report.rescaled.len(), 1);
+        assert!(model.graph.inputs.contains(&report.rescaled[0].node));
+        assert_eq!(report.rescaled[0].bits, 1);
+        assert_eq!(report.rescaled[0].out_scale, 6);
+        assert_eq!(model.graph.get_input_scales(), vec![6]);
+        assert!(report.max_lookup_inputs <= limit);
+        assert_eq!(
+            evaluate(&model, &samples).unwrap().max_lookup_inputs,
+            report.max_lookup_inputs
+        );
+        // at the cost of (at most) half a step at the lowered scale
+        assert!(report.max_error <= 1.0 / 128.0);
+        assert!(report.max_error_before <= 1.0 / 256.0);
+
+        // rebalancing again changes nothing, and a model whose lookups already fit isn't rescaled
+        let (again, again_report) = rebalance_scales(&path, &settings, &samples).unwrap();
+        assert_eq!(again.rescaled_nodes, settings.rescaled_nodes);
+        assert_eq!(again_report.rescaled, report.rescaled);
+        let wide = RunArgs {
+            bits: 16,
+            ..run_args
+        };
+        let circuit = GraphCircuit::from_run_args(&wide, &path).unwrap();
+        let (settings, report) = rebalance_scales(&path, &circuit.settings, &samples).unwrap();
+        assert!(settings.rescaled_nodes.is_empty());
+        assert!(report.rescaled.is_empty());
+        assert_eq!(report.max_error, report.max_error_before);
+    }
+}
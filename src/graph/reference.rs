@@ -0,0 +1,139 @@
+use super::model::Model;
+use super::{scale_to_multiplier, GraphError};
+use crate::fieldutils::felt_to_i128;
+use crate::tensor::Tensor;
+use halo2curves::bn256::Fr as Fp;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+
+/// The expected (float) outputs of a model's layers, as computed by a reference implementation (e.g PyTorch), keyed
+/// by the index of the node whose first output they are (as listed by `ezkl table`). Each tensor is flattened in
+/// row-major order. Layers that aren't listed aren't checked.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceOutputs(pub BTreeMap<usize, Vec<f64>>);
+
+impl ReferenceOutputs {
+    /// Loads the reference outputs from a .json file.
+    pub fn from_path(path: PathBuf) -> Result<Self, Box<dyn Error>> {
+        let reader = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+/// How closely a layer of the circuit's forward pass matches the reference.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayerComparison {
+    /// The node's index.
+    pub node: usize,
+    /// The operation performed by the node.
+    pub op: String,
+    /// The largest absolute difference between any (dequantized) element of the node's output and the reference.
+    pub max_error: f64,
+    /// Whether `max_error` is within the tolerance the comparison was run with.
+    pub within_tolerance: bool,
+}
+
+/// The result of comparing the circuit's forward pass against [ReferenceOutputs], in node order.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceReport {
+    /// The comparison for each layer listed in the reference.
+    pub layers: Vec<LayerComparison>,
+}
+
+impl ReferenceReport {
+    /// The first layer whose output diverges from the reference beyond tolerance. As errors propagate, this is
+    /// usually the layer to look at.
+    pub fn first_divergent(&self) -> Option<&LayerComparison> {
+        self.layers.iter().find(|l| !l.within_tolerance)
+    }
+}
+
+/// Runs the forward pass the circuit constrains on `inputs` and compares every layer listed in `reference` against it,
+/// dequantizing each node's output at its own scale. `tolerance` is the largest absolute error per element allowed.
+pub fn validate_against_reference(
+    model: &Model,
+    inputs: &[Tensor<Fp>],
+    reference: &ReferenceOutputs,
+    tolerance: f64,
+) -> Result<ReferenceReport, Box<dyn Error>> {
+    let res = model.forward(inputs)?;
+    let mut layers = vec![];
+    for (idx, expected) in reference.0.iter() {
+        let node = model
+            .graph
+            .node(*idx)
+            .ok_or(GraphError::MissingNode(*idx))?;
+        let output = res
+            .node_outputs
+            .get(idx)
+            .and_then(|o| o.first())
+            .ok_or(GraphError::MissingNode(*idx))?;
+        if output.len() != expected.len() {
+            return Err(Box::new(GraphError::InvalidInput(format!(
+                "the reference for node {} has {} elements, but the node outputs {}",
+                idx,
+                expected.len(),
+                output.len()
+            ))));
+        }
+        let mult = scale_to_multiplier(node.out_scales()[0]);
+        let max_error = output
+            .iter()
+            .zip(expected)
+            .map(|(x, e)| (felt_to_i128(*x) as f64 / mult - e).abs())
+            .fold(0.0, f64::max);
+        layers.push(LayerComparison {
+            node: *idx,
+            op: node.as_str(),
+            max_error,
+            within_tolerance: max_error <= tolerance,
+        });
+    }
+    Ok(ReferenceReport { layers })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::tests::test_run_args;
+    use crate::RunArgs;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_validate_against_reference() {
+        let run_args = RunArgs {
+            scale: 0,
+            ..test_run_args()
+        };
+        let path = "examples/onnx/1l_split/network.onnx".into();
+        let model = Model::from_run_args(&run_args, &path).unwrap();
+        let input = Tensor::new(Some(&(0..24).map(Fp::from).collect_vec()), &[1, 6, 4]).unwrap();
+
+        // the circuit's own (exact at scale 0) forward pass is a reference it matches
+        let node_outputs = model.forward(&[input.clone()]).unwrap().node_outputs;
+        let mut reference = ReferenceOutputs(
+            node_outputs
+                .iter()
+                .map(|(idx, o)| (*idx, o[0].iter().map(|x| felt_to_i128(*x) as f64).collect()))
+                .collect(),
+        );
+        let report = validate_against_reference(&model, &[input.clone()], &reference, 0.0).unwrap();
+        assert_eq!(report.layers.len(), node_outputs.len());
+        assert!(report.first_divergent().is_none());
+
+        // perturbing a layer flags it, with its error
+        let (last, expected) = reference.0.iter_mut().last().unwrap();
+        let last = *last;
+        expected[0] += 0.75;
+        let report = validate_against_reference(&model, &[input.clone()], &reference, 0.5).unwrap();
+        let divergent = report.first_divergent().unwrap();
+        assert_eq!(divergent.node, last);
+        assert_eq!(divergent.max_error, 0.75);
+
+        // references that don't fit the node are rejected
+        reference.0.get_mut(&last).unwrap().pop();
+        assert!(validate_against_reference(&model, &[input], &reference, 0.5).is_err());
+    }
+}
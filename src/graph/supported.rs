@@ -0,0 +1,247 @@
+use super::Model;
+use crate::RunArgs;
+use log::{debug, info};
+use prost::Message;
+use std::collections::HashSet;
+use std::error::Error;
+use tract_onnx::pb::{GraphProto, ModelProto, NodeProto, ValueInfoProto};
+
+/// The largest prefix of a model's nodes that ezkl supports, as a standalone ONNX model: the prefix's nodes and the
+/// initializers they use, taking the model inputs the prefix consumes and outputting every value the prefix computes
+/// that the rest of the model (or the model's output) consumes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SupportedPrefix {
+    /// The prefix as a standalone model.
+    pub model: ModelProto,
+    /// The number of nodes of the prefix.
+    pub num_nodes: usize,
+    /// The number of nodes of the whole model.
+    pub total_nodes: usize,
+    /// The first node past the prefix and why the prefix including it doesn't load, as `(name, op_type, reason)`.
+    /// `None` if the whole model is supported.
+    pub first_unsupported: Option<(String, String, String)>,
+}
+
+impl SupportedPrefix {
+    /// Finds the largest prefix of `model`'s nodes (which ONNX lays out in topological order) that loads as an ezkl
+    /// [Model] with `run_args`, which rejects the ops without a circuit implementation (unless
+    /// [crate::RunArgs::unsupported_ops] skips or approximates them) and the ops whose semantics at the model's opset
+    /// ezkl doesn't follow (see [super::opset::OpsetReport]). The prefixes are grown one node at a time up to the
+    /// first node whose prefix doesn't load, such that every shorter prefix is known to load as well. Fails if not
+    /// even the first node loads.
+    pub fn new(model: &ModelProto, run_args: &RunArgs) -> Result<Self, Box<dyn Error>> {
+        let total_nodes = model
+            .graph
+            .as_ref()
+            .map(|g| g.node.len())
+            .unwrap_or_default();
+        let loads = |num_nodes: usize| -> Result<(), Box<dyn Error>> {
+            let prefix = slice_prefix(model, num_nodes)?;
+            Model::new(&mut prefix.encode_to_vec().as_slice(), *run_args).map(|_| ())
+        };
+
+        if loads(total_nodes).is_ok() {
+            info!("all {} nodes of the model are supported", total_nodes);
+            return Ok(SupportedPrefix {
+                model: model.clone(),
+                num_nodes: total_nodes,
+                total_nodes,
+                first_unsupported: None,
+            });
+        }
+
+        // a prefix past an unsupported node may load again (e.g if a later node absorbs it), so rather than
+        // bisecting the prefixes are tried in order
+        let mut first_failure = None;
+        for num_nodes in 1..=total_nodes {
+            if let Err(e) = loads(num_nodes) {
+                debug!("the prefix of {} nodes doesn't load: {}", num_nodes, e);
+                first_failure = Some((num_nodes - 1, e.to_string()));
+                break;
+            }
+        }
+        // the model didn't load whole, so one of its prefixes doesn't
+        let (lo, reason) =
+            first_failure.ok_or("the model doesn't load, but each of its prefixes does")?;
+        if lo == 0 {
+            return Err(format!(
+                "not even the first node of the model is supported: {}",
+                reason
+            )
+            .into());
+        }
+
+        let node = &model.graph.as_ref().ok_or("the model has no graph")?.node[lo];
+        Ok(SupportedPrefix {
+            model: slice_prefix(model, lo)?,
+            num_nodes: lo,
+            total_nodes,
+            first_unsupported: Some((node.name.clone(), node.op_type.clone(), reason)),
+        })
+    }
+
+    /// The prefix as the bytes of an .onnx file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.model.encode_to_vec()
+    }
+}
+
+/// The names of the values `node` consumes, including those the graphs of its attributes (e.g the body of a loop)
+/// capture from the enclosing graph.
+fn node_inputs(node: &NodeProto) -> Vec<&str> {
+    let mut inputs: Vec<&str> = node.input.iter().map(|i| i.as_str()).collect();
+    for attribute in &node.attribute {
+        for graph in attribute.g.iter().chain(&attribute.graphs) {
+            inputs.extend(graph.node.iter().flat_map(node_inputs));
+        }
+    }
+    inputs.retain(|i| !i.is_empty());
+    inputs
+}
+
+/// Slices the first `num_nodes` nodes of `model` into a standalone model, see [SupportedPrefix].
+pub fn slice_prefix(model: &ModelProto, num_nodes: usize) -> Result<ModelProto, Box<dyn Error>> {
+    let graph = model.graph.as_ref().ok_or("the model has no graph")?;
+    if num_nodes > graph.node.len() {
+        return Err(format!(
+            "the model has {} nodes, it has no prefix of {}",
+            graph.node.len(),
+            num_nodes
+        )
+        .into());
+    }
+    let (kept, rest) = graph.node.split_at(num_nodes);
+
+    let consumed: HashSet<&str> = kept.iter().flat_map(node_inputs).collect();
+    let mut consumed_after: HashSet<&str> = rest.iter().flat_map(node_inputs).collect();
+    consumed_after.extend(graph.output.iter().map(|o| o.name.as_str()));
+
+    // the values the rest of the model consumes become outputs, typed as the model records them (if it does)
+    let value_info = |name: &str| {
+        graph
+            .output
+            .iter()
+            .chain(&graph.value_info)
+            .find(|v| v.name == name)
+            .cloned()
+            .unwrap_or_else(|| ValueInfoProto {
+                name: name.to_string(),
+                ..Default::default()
+            })
+    };
+    let mut outputs: Vec<ValueInfoProto> = vec![];
+    for output in kept.iter().flat_map(|n| &n.output) {
+        if consumed_after.contains(output.as_str()) && !outputs.iter().any(|o| o.name == *output) {
+            outputs.push(value_info(output));
+        }
+    }
+    let produced: HashSet<&str> = kept
+        .iter()
+        .flat_map(|n| n.output.iter().map(|o| o.as_str()))
+        .collect();
+
+    let sliced = GraphProto {
+        node: kept.to_vec(),
+        input: graph
+            .input
+            .iter()
+            .filter(|i| consumed.contains(i.name.as_str()))
+            .cloned()
+            .collect(),
+        output: outputs,
+        initializer: graph
+            .initializer
+            .iter()
+            .filter(|t| consumed.contains(t.name.as_str()))
+            .cloned()
+            .collect(),
+        value_info: graph
+            .value_info
+            .iter()
+            .filter(|v| produced.contains(v.name.as_str()))
+            .cloned()
+            .collect(),
+        ..graph.clone()
+    };
+
+    Ok(ModelProto {
+        graph: Some(sliced),
+        ..model.clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::tests::test_run_args;
+    use crate::tensor::Tensor;
+    use halo2curves::bn256::Fr as Fp;
+    use tract_onnx::prelude::Framework;
+
+    #[test]
+    fn test_supported_prefix() {
+        let run_args = test_run_args();
+        let path = "examples/onnx/1l_mlp/network.onnx";
+        let mut reader = std::fs::File::open(path).unwrap();
+        let proto = super::super::utilities::onnx()
+            .proto_model_for_read(&mut reader)
+            .unwrap();
+        let graph = proto.graph.as_ref().unwrap();
+        let num_nodes = graph.node.len();
+
+        // a supported model is kept whole
+        let prefix = SupportedPrefix::new(&proto, &run_args).unwrap();
+        assert_eq!(prefix.num_nodes, num_nodes);
+        assert!(prefix.first_unsupported.is_none());
+
+        // an op ezkl doesn't know of is cut off, along with everything after it
+        let output = graph.output[0].name.clone();
+        let mut extended = proto.clone();
+        let extended_graph = extended.graph.as_mut().unwrap();
+        extended_graph.node.push(NodeProto {
+            name: "unknown".into(),
+            op_type: "NotAnOnnxOp".into(),
+            input: vec![output.clone()],
+            output: vec!["unknown_out".into()],
+            ..Default::default()
+        });
+        extended_graph.node.push(NodeProto {
+            name: "relu".into(),
+            op_type: "Relu".into(),
+            input: vec!["unknown_out".into()],
+            output: vec!["relu_out".into()],
+            ..Default::default()
+        });
+        extended_graph.output = vec![ValueInfoProto {
+            name: "relu_out".into(),
+            ..Default::default()
+        }];
+        let prefix = SupportedPrefix::new(&extended, &run_args).unwrap();
+        assert_eq!(prefix.num_nodes, num_nodes);
+        assert_eq!(prefix.total_nodes, num_nodes + 2);
+        let (name, op_type, _) = prefix.first_unsupported.clone().unwrap();
+        assert_eq!(
+            (name.as_str(), op_type.as_str()),
+            ("unknown", "NotAnOnnxOp")
+        );
+        // the cut value becomes the prefix's output
+        let sliced = prefix.model.graph.as_ref().unwrap();
+        assert_eq!(
+            sliced.output.iter().map(|o| &o.name).collect::<Vec<_>>(),
+            vec![&output]
+        );
+
+        // and the exported prefix computes what the supported model does
+        let exported = Model::new(&mut prefix.to_bytes().as_slice(), run_args).unwrap();
+        let model = Model::new(&mut std::fs::File::open(path).unwrap(), run_args).unwrap();
+        let shape = model.graph.input_shapes()[0].clone();
+        let values = (0..shape.iter().product::<usize>() as u64)
+            .map(|i| Fp::from(i * 16))
+            .collect::<Vec<_>>();
+        let input = Tensor::new(Some(&values), &shape).unwrap();
+        assert_eq!(
+            exported.forward(&[input.clone()]).unwrap().outputs,
+            model.forward(&[input]).unwrap().outputs
+        );
+    }
+}
@@ -7,12 +7,18 @@ use crate::circuit::hybrid::HybridOp;
 use crate::circuit::lookup::LookupOp;
 use crate::circuit::poly::PolyOp;
 use crate::circuit::Op;
+use crate::tensor::ops::normalize_axis;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::tensor::ops::PadMode as TensorPadMode;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::tensor::ops::{ResizeCoordTransform, ResizeMode};
 use crate::tensor::{Tensor, TensorError, TensorType};
 use halo2curves::bn256::Fr as Fp;
 use halo2curves::ff::PrimeField;
 use itertools::Itertools;
 #[cfg(not(target_arch = "wasm32"))]
 use log::{debug, warn};
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 #[cfg(not(target_arch = "wasm32"))]
 use std::sync::Arc;
@@ -21,6 +27,7 @@ use tract_onnx::prelude::{DatumType, Node as OnnxNode, TypedFact, TypedOp};
 #[cfg(not(target_arch = "wasm32"))]
 use tract_onnx::tract_core::ops::{
     array::{Gather, Slice},
+    cast::Cast,
     change_axes::AxisOp,
     cnn::DeconvUnary,
     einsum::EinSum,
@@ -39,18 +46,23 @@ use tract_onnx::tract_hir::{
 };
 
 // Warning: currently ignores stride information
-/// Quantizes an iterable of f32s to a [Tensor] of i32s using a fixed point representation.
+/// Quantizes a float to an integer using a fixed point representation. The multiply, shift and round are done in
+/// f64, so f32 values (weights and constants) should be widened (which is exact), and f64 inputs shouldn't be
+/// narrowed to f32s, whose 24 bit mantissa loses precision at scales above 24.
 /// Arguments
 ///
-/// * `vec` - the vector to quantize.
-/// * `dims` - the dimensionality of the resulting [Tensor].
+/// * `elem` - the value to quantize.
 /// * `shift` - offset used in the fixed point representation.
 /// * `scale` - `2^scale` used in the fixed point representation.
 pub fn quantize_float(elem: &f64, shift: f64, scale: u32) -> Result<i128, TensorError> {
+    if !elem.is_finite() {
+        return Err(TensorError::NonFiniteValue(*elem));
+    }
     let mult = scale_to_multiplier(scale);
     let max_value = ((i128::MAX as f64 - shift) / mult).round(); // the maximum value that can be represented w/o sig bit truncation
+    let min_value = ((i128::MIN as f64 - shift) / mult).round();
 
-    if *elem > max_value {
+    if *elem > max_value || *elem < min_value {
         return Err(TensorError::SigBitTruncationError);
     }
 
@@ -60,16 +72,94 @@ pub fn quantize_float(elem: &f64, shift: f64, scale: u32) -> Result<i128, Tensor
     Ok(scaled)
 }
 
+/// The number of elements of a tensor of shape `dims`, erroring (rather than overflowing) if it doesn't fit a usize.
+pub fn checked_num_elements(dims: &[usize]) -> Result<usize, GraphError> {
+    dims.iter()
+        .try_fold(1usize, |acc, d| acc.checked_mul(*d))
+        .ok_or_else(|| GraphError::InvalidInput(format!("the shape {:?} overflows usize", dims)))
+}
+
+/// Checks `inputs` (of lengths `lens`) fit a model with inputs of shape `shapes`, such that malformed inputs are
+/// rejected with an error instead of panicking when they are reshaped.
+pub fn check_input_lens(lens: &[usize], shapes: &[Vec<usize>]) -> Result<(), GraphError> {
+    if lens.len() != shapes.len() {
+        return Err(GraphError::InvalidInput(format!(
+            "the model takes {} inputs but {} were provided",
+            shapes.len(),
+            lens.len()
+        )));
+    }
+    for (i, (len, shape)) in lens.iter().zip(shapes).enumerate() {
+        let expected = checked_num_elements(shape)?;
+        if *len != expected {
+            return Err(GraphError::InvalidInput(format!(
+                "input {} has {} elements but the model expects a shape of {:?} ({} elements)",
+                i, len, shape, expected
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// Converts a scale (log base 2) to a fixed point multiplier.
 pub fn scale_to_multiplier(scale: u32) -> f64 {
     f64::powf(2., scale as f64)
 }
 
-/// Converts a scale (log base 2) to a fixed point multiplier.
+/// Converts a fixed point multiplier to a scale (log base 2), rounding to the nearest power of two.
+/// Warns if the multiplier isn't a power of two, as the rounding then silently introduces error.
 pub fn mult_to_scale(mult: f64) -> u32 {
+    if !is_power_of_two(mult) {
+        log::warn!(
+            "multiplier {} is not a power of two, rounding to 2^{}",
+            mult,
+            mult.log2().round()
+        );
+    }
     mult.log2().round() as u32
 }
 
+/// Returns true if `mult` is a (non-negative exponent) power of two.
+fn is_power_of_two(mult: f64) -> bool {
+    mult.is_finite() && mult >= 1.0 && mult.log2().fract() == 0.0
+}
+
+/// Converts a fixed point multiplier to a scale (log base 2), erroring if the multiplier isn't a power of two.
+/// Power of two multipliers keep in-circuit requantization a division by a power of two.
+pub fn checked_mult_to_scale(mult: f64) -> Result<u32, String> {
+    if !is_power_of_two(mult) {
+        return Err(format!(
+            "scale multiplier {} is not a power of two (nearest is 2^{} = {})",
+            mult,
+            mult.log2().round().max(0.0),
+            scale_to_multiplier(mult.log2().round().max(0.0) as u32)
+        ));
+    }
+    Ok(mult.log2() as u32)
+}
+
+/// Parses a scale (log base 2) from either an exponent `e`, a power of two `2^e`, or a raw multiplier
+/// prefixed with `x` (e.g `x128`), which must be a power of two.
+pub fn parse_scale(s: &str) -> Result<u32, String> {
+    let s = s.trim();
+    let parse_exp = |e: &str| {
+        e.trim()
+            .parse::<u32>()
+            .map_err(|e| format!("invalid scale exponent: {}", e))
+    };
+    if let Some(e) = s.strip_prefix("2^") {
+        parse_exp(e)
+    } else if let Some(mult) = s.strip_prefix('x') {
+        let mult = mult
+            .trim()
+            .parse::<f64>()
+            .map_err(|e| format!("invalid scale multiplier: {}", e))?;
+        checked_mult_to_scale(mult)
+    } else {
+        parse_exp(s)
+    }
+}
+
 /// Gets the shape of a onnx node's outlets.
 #[cfg(not(target_arch = "wasm32"))]
 pub fn node_output_shapes(
@@ -219,6 +309,273 @@ fn load_eltwise_op(
     Ok(op.clone())
 }
 
+/// The ONNX `Elu` (`gamma` is `None`) and `Selu` activations as a single elementwise op. tract expands both into an
+/// exponential, a comparison and a select, which would be laid out as separate ops, whereas we lay them out as a
+/// single lookup, see [LookupOp::Elu] and [LookupOp::Selu].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct OnnxElu {
+    /// the coefficient of the negative part
+    pub alpha: f32,
+    /// the output scaling of `Selu`
+    pub gamma: Option<f32>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::hash::Hash for OnnxElu {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.alpha.to_bits().hash(state);
+        self.gamma.map(f32::to_bits).hash(state);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl tract_onnx::tract_core::ops::element_wise::ElementWiseMiniOp for OnnxElu {
+    fn name(&self) -> String {
+        match self.gamma {
+            Some(_) => "Selu".to_string(),
+            None => "Elu".to_string(),
+        }
+    }
+
+    fn eval_in_place(
+        &self,
+        t: &mut tract_onnx::prelude::Tensor,
+        _out_dt: Option<DatumType>,
+    ) -> tract_onnx::prelude::TractResult<()> {
+        let gamma = self.gamma.unwrap_or(1.0);
+        for x in t.as_slice_mut::<f32>()? {
+            let elu = if *x < 0.0 {
+                self.alpha * x.exp_m1()
+            } else {
+                *x
+            };
+            *x = gamma * elu;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the `Elu` and `Selu` nodes of an onnx graph as an [OnnxElu], reading the attributes with their ONNX defaults.
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_elu(
+    _ctx: &tract_onnx::model::ParsingContext,
+    node: &tract_onnx::pb::NodeProto,
+) -> tract_onnx::prelude::TractResult<(
+    Box<dyn tract_onnx::tract_hir::infer::InferenceOp>,
+    Vec<String>,
+)> {
+    let op = if node.op_type == "Selu" {
+        OnnxElu {
+            alpha: node.get_attr_opt("alpha")?.unwrap_or(1.673_263_2),
+            gamma: Some(node.get_attr_opt("gamma")?.unwrap_or(1.050_701)),
+        }
+    } else {
+        OnnxElu {
+            alpha: node.get_attr_opt("alpha")?.unwrap_or(1.0),
+            gamma: None,
+        }
+    };
+    Ok((Box::new(ElementWiseOp(Box::new(op), None)), vec![]))
+}
+
+/// The normalization of the ONNX `RMSNormalization` (of opset 23), `x / sqrt(mean(x^2) + epsilon)` with the mean taken
+/// along the axes from `axis` on, which we lay out as [HybridOp::RmsNorm]. Only the last axis is supported. tract has
+/// no builder for the op, which is expanded into this normalization followed by a multiplication with its `scale`.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct OnnxRmsNorm {
+    /// the first axis of the mean
+    pub axis: i64,
+    /// added to the mean of the squares
+    pub epsilon: f32,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::hash::Hash for OnnxRmsNorm {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.axis.hash(state);
+        self.epsilon.to_bits().hash(state);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl tract_onnx::tract_core::ops::element_wise::ElementWiseMiniOp for OnnxRmsNorm {
+    fn name(&self) -> String {
+        "RmsNorm".to_string()
+    }
+
+    fn eval_in_place(
+        &self,
+        t: &mut tract_onnx::prelude::Tensor,
+        _out_dt: Option<DatumType>,
+    ) -> tract_onnx::prelude::TractResult<()> {
+        let rank = t.rank();
+        if normalize_axis(self.axis, rank).ok() != rank.checked_sub(1) {
+            return Err(tract_onnx::prelude::TractError::msg(format!(
+                "rms normalization is only supported along the last axis, not {}",
+                self.axis
+            )));
+        }
+        let dim = t.shape()[rank - 1].max(1);
+        for row in t.as_slice_mut::<f32>()?.chunks_mut(dim) {
+            let mean = row.iter().map(|x| x * x).sum::<f32>() / row.len() as f32;
+            let inv_rms = 1.0 / (mean + self.epsilon).sqrt();
+            for x in row {
+                *x *= inv_rms;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The ONNX `RMSNormalization`, expanded into an [OnnxRmsNorm] of its input and a multiplication with its `scale`
+/// (broadcast against the normalized input).
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Hash)]
+struct RmsNormalization(OnnxRmsNorm);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl tract_onnx::tract_hir::internal::Expansion for RmsNormalization {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "RMSNormalization".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut tract_onnx::tract_hir::internal::Solver<'r>,
+        inputs: &'p [tract_onnx::tract_hir::internal::TensorProxy],
+        outputs: &'p [tract_onnx::tract_hir::internal::TensorProxy],
+    ) -> tract_onnx::tract_hir::internal::InferenceResult {
+        use tract_onnx::tract_hir::internal::*;
+        check_input_arity(inputs, 2)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut tract_onnx::prelude::TypedModel,
+        inputs: &[tract_onnx::prelude::OutletId],
+    ) -> tract_onnx::prelude::TractResult<tract_onnx::prelude::TVec<tract_onnx::prelude::OutletId>>
+    {
+        let normalized = model.wire_node(
+            format!("{}.normalize", prefix),
+            ElementWiseOp(Box::new(self.0.clone()), None),
+            &[inputs[0]],
+        )?;
+        let operands = tract_onnx::tract_core::ops::binary::wire_rank_broadcast(
+            prefix,
+            model,
+            &[normalized[0], inputs[1]],
+        )?;
+        model.wire_node(
+            format!("{}.scale", prefix),
+            tract_onnx::tract_core::ops::math::mul(),
+            &operands,
+        )
+    }
+}
+
+/// Builds the `RMSNormalization` nodes of an onnx graph as a [RmsNormalization], reading the attributes with their ONNX
+/// defaults.
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_rms_norm(
+    _ctx: &tract_onnx::model::ParsingContext,
+    node: &tract_onnx::pb::NodeProto,
+) -> tract_onnx::prelude::TractResult<(
+    Box<dyn tract_onnx::tract_hir::infer::InferenceOp>,
+    Vec<String>,
+)> {
+    let op = OnnxRmsNorm {
+        axis: node.get_attr_opt("axis")?.unwrap_or(-1),
+        epsilon: node.get_attr_opt("epsilon")?.unwrap_or(1e-5),
+    };
+    Ok((
+        tract_onnx::tract_hir::internal::expand(RmsNormalization(op)),
+        vec![],
+    ))
+}
+
+/// ONNX activations which neither tract nor ezkl implement (`Mish` and `Gelu`), parsed as a single elementwise op such
+/// that models using them load and are evaluated on floats, and are handled by the [UnsupportedOpPolicy] (which may
+/// skip or approximate them) when the circuit is built.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Hash)]
+pub struct OnnxActivation {
+    /// the ONNX op type
+    pub op_type: String,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl tract_onnx::tract_core::ops::element_wise::ElementWiseMiniOp for OnnxActivation {
+    fn name(&self) -> String {
+        self.op_type.clone()
+    }
+
+    fn eval_in_place(
+        &self,
+        t: &mut tract_onnx::prelude::Tensor,
+        _out_dt: Option<DatumType>,
+    ) -> tract_onnx::prelude::TractResult<()> {
+        // std has no erf, so `Gelu` is evaluated by its tanh approximation (within 1e-3 of it)
+        let f: fn(f32) -> f32 = match self.op_type.as_str() {
+            "Mish" => |x| x * x.exp().ln_1p().tanh(),
+            _ => |x| {
+                let inner = (2.0 / std::f32::consts::PI).sqrt() * (x + 0.044_715 * x.powi(3));
+                0.5 * x * (1.0 + inner.tanh())
+            },
+        };
+        for x in t.as_slice_mut::<f32>()? {
+            *x = f(*x);
+        }
+        Ok(())
+    }
+}
+
+/// Builds the `Mish` and `Gelu` nodes of an onnx graph as an [OnnxActivation].
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_activation(
+    _ctx: &tract_onnx::model::ParsingContext,
+    node: &tract_onnx::pb::NodeProto,
+) -> tract_onnx::prelude::TractResult<(
+    Box<dyn tract_onnx::tract_hir::infer::InferenceOp>,
+    Vec<String>,
+)> {
+    let op = OnnxActivation {
+        op_type: node.op_type.clone(),
+    };
+    Ok((Box::new(ElementWiseOp(Box::new(op), None)), vec![]))
+}
+
+/// tract's onnx parser with the builders of the ops we lay out differently than tract decomposes them (see
+/// [OnnxElu]) overridden, and of the ops tract doesn't parse (see [OnnxRmsNorm] and [OnnxActivation]) added.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn builtin_onnx() -> tract_onnx::model::Onnx {
+    let mut onnx = tract_onnx::onnx();
+    onnx.op_register.insert("Elu", parse_elu);
+    onnx.op_register.insert("Selu", parse_elu);
+    onnx.op_register.insert("RMSNormalization", parse_rms_norm);
+    onnx.op_register.insert("Mish", parse_activation);
+    onnx.op_register.insert("Gelu", parse_activation);
+    onnx
+}
+
+/// The onnx parser used to load models: [builtin_onnx], with the op types registered through
+/// [super::custom::register_custom_op].
+#[cfg(not(target_arch = "wasm32"))]
+pub fn onnx() -> tract_onnx::model::Onnx {
+    let mut onnx = builtin_onnx();
+    for op_type in super::custom::custom_op_types() {
+        onnx.op_register
+            .insert(op_type, super::custom::parse_custom_op);
+    }
+    onnx
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn load_concat_op(
     op: &dyn tract_onnx::prelude::Op,
@@ -234,6 +591,31 @@ fn load_concat_op(
     Ok(op.clone())
 }
 
+/// Returns the rank of the `i`th input of a node.
+#[cfg(not(target_arch = "wasm32"))]
+fn input_rank(inputs: &[super::NodeType], i: usize) -> usize {
+    inputs[i].out_dims()[0].len()
+}
+
+/// Normalizes (possibly negative) axes for a tensor of rank `rank`, see [normalize_axis]. Duplicates are removed.
+pub fn normalize_axes<A: Copy + TryInto<i64>>(
+    axes: &[A],
+    rank: usize,
+) -> Result<Vec<usize>, TensorError> {
+    let mut normalized = axes
+        .iter()
+        .map(|a| {
+            let axis = (*a)
+                .try_into()
+                .map_err(|_| TensorError::DimMismatch("axis overflows an i64".to_string()))?;
+            normalize_axis(axis, rank)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    normalized.sort();
+    normalized.dedup();
+    Ok(normalized)
+}
+
 /// Extracts a Slice op from an onnx node.
 #[cfg(not(target_arch = "wasm32"))]
 fn load_slice_op(
@@ -251,18 +633,191 @@ fn load_slice_op(
     Ok(op.clone())
 }
 
+/// Extracts a cast op from an onnx node.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_cast_op(
+    op: &dyn tract_onnx::prelude::Op,
+    idx: usize,
+    name: String,
+) -> Result<Cast, Box<dyn std::error::Error>> {
+    let op: &Cast = match op.downcast_ref::<Cast>() {
+        Some(b) => b,
+        None => {
+            return Err(Box::new(GraphError::OpMismatch(idx, name)));
+        }
+    };
+
+    Ok(op.clone())
+}
+
+/// How the values of a [DatumType] are represented in the circuit.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DatumRepr {
+    /// Fixed point values at a scale.
+    Float,
+    /// Integers, at a scale like floats, whose fractional bits are zero.
+    Integer,
+    /// Zeros and ones, at a scale.
+    Bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DatumRepr {
+    /// The representation of `dt`, if it can be represented in the circuit.
+    pub fn of(dt: DatumType) -> Option<Self> {
+        match dt {
+            DatumType::F16 | DatumType::F32 | DatumType::F64 => Some(DatumRepr::Float),
+            DatumType::I8
+            | DatumType::I16
+            | DatumType::I32
+            | DatumType::I64
+            | DatumType::U8
+            | DatumType::U16
+            | DatumType::U32
+            | DatumType::U64 => Some(DatumRepr::Integer),
+            DatumType::Bool => Some(DatumRepr::Bool),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a cast from `from` to `to` (of node `idx`) leaves the circuit's fixed point values unchanged, which is
+/// the case for all casts but those of floats to integers (which truncate the fractional bits). Casts to booleans
+/// from other types, and casts of types the circuit can't represent, are unsupported.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn cast_is_identity(idx: usize, from: DatumType, to: DatumType) -> Result<bool, GraphError> {
+    let unsupported = || GraphError::UnsupportedCast(idx, format!("{:?} to {:?}", from, to));
+    match (DatumRepr::of(from), DatumRepr::of(to)) {
+        (Some(DatumRepr::Float), Some(DatumRepr::Integer)) => Ok(false),
+        (Some(DatumRepr::Bool), Some(DatumRepr::Bool)) => Ok(true),
+        (Some(_), Some(DatumRepr::Bool)) => Err(unsupported()),
+        (Some(_), Some(_)) => Ok(true),
+        _ => Err(unsupported()),
+    }
+}
+
+/// How ops that have no circuit implementation (nor a registered [super::custom::CircuitOp]) are handled when the
+/// graph is built. Anything but [UnsupportedOpPolicy::Error] yields a circuit that no longer exactly matches the model,
+/// which is logged (as a warning) for every node that is skipped or approximated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum UnsupportedOpPolicy {
+    /// Fails to build the graph.
+    #[default]
+    Error,
+    /// Replaces the op by the identity on its first input (its other inputs are dropped), as is exact for ops which
+    /// are no-ops at inference. Ops whose output shape differs from that of their first input can't be skipped.
+    Skip,
+    /// Substitutes the ops listed in [UnsupportedOpPolicy::approximation] by their approximation, and skips the others
+    /// as [UnsupportedOpPolicy::Skip] does.
+    Approximate,
+}
+
+impl UnsupportedOpPolicy {
+    /// The approximation substituted for an unsupported op (see [OnnxActivation]) under
+    /// [UnsupportedOpPolicy::Approximate]:
+    /// * `Gelu` by the ReLU, which is within 0.17 of it, at `x = -0.75`, and converges to it away from 0.
+    /// * `Mish` by the ReLU, which is within 0.31 of it, at `x = -1.19`, and converges to it away from 0.
+    pub fn approximation(&self, op_name: &str) -> Option<SupportedOp> {
+        match (self, op_name) {
+            (UnsupportedOpPolicy::Approximate, "Gelu" | "Mish") => Some(SupportedOp::Nonlinear(
+                crate::circuit::lookup::LookupOp::ReLU { scale: 1 },
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for UnsupportedOpPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(UnsupportedOpPolicy::Error),
+            "skip" => Ok(UnsupportedOpPolicy::Skip),
+            "approximate" => Ok(UnsupportedOpPolicy::Approximate),
+            _ => Err(format!("unsupported op policy: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for UnsupportedOpPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            UnsupportedOpPolicy::Error => write!(f, "error"),
+            UnsupportedOpPolicy::Skip => write!(f, "skip"),
+            UnsupportedOpPolicy::Approximate => write!(f, "approximate"),
+        }
+    }
+}
+
+/// Handles a node whose op is unsupported according to the [UnsupportedOpPolicy].
+#[cfg(not(target_arch = "wasm32"))]
+fn unsupported_op(
+    idx: usize,
+    node: &OnnxNode<TypedFact, Box<dyn TypedOp>>,
+    policy: UnsupportedOpPolicy,
+    inputs: &mut [super::NodeType],
+    deleted_indices: &mut Vec<usize>,
+) -> Result<SupportedOp, Box<dyn std::error::Error>> {
+    let name = node.op().name().to_string();
+    if policy == UnsupportedOpPolicy::Error {
+        return Err(Box::new(GraphError::UnsupportedNode(idx, name)));
+    }
+
+    if let Some(op) = policy.approximation(&name) {
+        warn!(
+            "approximating unsupported op {} (node {}) by {}, the circuit no longer exactly matches the model",
+            name,
+            idx,
+            op.as_string()
+        );
+        return Ok(op);
+    }
+
+    // the identity on the first input is only shape preserving if the op is
+    let input_dims = match (inputs.first(), node.inputs.first()) {
+        (Some(input), Some(outlet)) => input.out_dims().get(outlet.slot).cloned(),
+        _ => None,
+    };
+    let output_dims = node_output_shapes(node)?.into_iter().next().flatten();
+    if input_dims.is_none() || input_dims != output_dims {
+        return Err(Box::new(GraphError::UnsupportedNode(
+            idx,
+            format!(
+                "{} (can't be skipped as its output shape isn't that of its input)",
+                name
+            ),
+        )));
+    }
+
+    warn!(
+        "skipping unsupported op {} (node {}) as the identity, the circuit no longer exactly matches the model",
+        name, idx
+    );
+    for (i, input) in inputs.iter_mut().enumerate().skip(1) {
+        input.decrement_const();
+        deleted_indices.push(i);
+    }
+    Ok(SupportedOp::Linear(PolyOp::Identity))
+}
+
 /// Matches an onnx node to a [crate::circuit::Op].
 /// Arguments
 /// * `idx` - the index of the node in the graph.
 /// * `scale` - the global (circuit) scale.
+/// * `weight_scale` - the scale constants (e.g weights) are quantized at.
 /// * `param_visibility` - [Visibility] of the node.
+/// * `unsupported_ops` - how the node is handled if its op is unsupported, see [UnsupportedOpPolicy].
 /// * `node` - the [OnnxNode] to be matched.
 /// * `inputs` - the node's inputs.
 #[cfg(not(target_arch = "wasm32"))]
 pub fn new_op_from_onnx(
     idx: usize,
     scale: u32,
+    weight_scale: u32,
     param_visibility: Visibility,
+    unsupported_ops: UnsupportedOpPolicy,
     node: OnnxNode<TypedFact, Box<dyn TypedOp>>,
     inputs: &mut [super::NodeType],
 ) -> Result<(SupportedOp, Vec<usize>), Box<dyn std::error::Error>> {
@@ -274,7 +829,7 @@ pub fn new_op_from_onnx(
                 return Err(Box::new(GraphError::InvalidDims(idx, "gather".to_string())));
             };
             let op = load_gather_op(node.op(), idx, node.op().name().to_string())?;
-            let axis = op.axis;
+            let axis = normalize_axis(op.axis as i64, input_rank(inputs, 0))?;
 
             let index: Tensor<usize> = match extract_const_raw_values(inputs[1].opkind()) {
                 Some(c) => c.map(|e| e as usize),
@@ -299,8 +854,9 @@ pub fn new_op_from_onnx(
             let op = load_axis_op(node.op(), idx, node.op().name().to_string())?;
             match op {
                 AxisOp::Move(from, to) => {
-                    let source = from.to_usize()?;
-                    let destination = to.to_usize()?;
+                    let rank = input_rank(inputs, 0);
+                    let source = normalize_axis(from.to_usize()? as i64, rank)?;
+                    let destination = normalize_axis(to.to_usize()? as i64, rank)?;
                     SupportedOp::Linear(crate::circuit::ops::poly::PolyOp::MoveAxis {
                         source,
                         destination,
@@ -311,13 +867,13 @@ pub fn new_op_from_onnx(
         }
         "Concat" | "InferenceConcat" => {
             let op = load_concat_op(node.op(), idx, node.op().name().to_string())?;
-            let axis = op.axis;
+            let axis = normalize_axis(op.axis as i64, input_rank(inputs, 0))?;
             SupportedOp::Linear(crate::circuit::ops::poly::PolyOp::Concat { axis })
         }
         "Slice" => {
             let slice = load_slice_op(node.op(), node.op().name().to_string())?;
 
-            let axis = slice.axis;
+            let axis = normalize_axis(slice.axis as i64, input_rank(inputs, 0))?;
             let start = slice.start.to_usize()?;
             let end = slice.end.to_usize()?;
 
@@ -329,7 +885,11 @@ pub fn new_op_from_onnx(
             // Raw values are always f32
             let raw_value = extract_tensor_value(op.0)?;
             // If bool then don't scale
-            let constant_scale = if dt == DatumType::Bool { 0 } else { scale };
+            let constant_scale = if dt == DatumType::Bool {
+                0
+            } else {
+                weight_scale
+            };
             // Quantize the raw value
             let quantized_value =
                 quantize_tensor(raw_value.clone(), constant_scale, param_visibility)?;
@@ -344,7 +904,7 @@ pub fn new_op_from_onnx(
                 return Err(Box::new(GraphError::InvalidDims(idx, "min".to_string())));
             };
             let op = load_reduce_op(node.op(), idx, node.op().name().to_string())?;
-            let axes = op.axes.into_iter().collect();
+            let axes = normalize_axes(&op.axes[..], input_rank(inputs, 0))?;
 
             SupportedOp::Hybrid(HybridOp::ReduceMin { axes })
         }
@@ -353,16 +913,32 @@ pub fn new_op_from_onnx(
                 return Err(Box::new(GraphError::InvalidDims(idx, "max".to_string())));
             };
             let op = load_reduce_op(node.op(), idx, node.op().name().to_string())?;
-            let axes = op.axes.into_iter().collect();
+            let axes = normalize_axes(&op.axes[..], input_rank(inputs, 0))?;
 
             SupportedOp::Hybrid(HybridOp::ReduceMax { axes })
         }
+        "Reduce<ArgMax(false)>" | "Reduce<ArgMin(false)>" => {
+            if inputs.len() != 1 {
+                return Err(Box::new(GraphError::InvalidDims(idx, "argmax".to_string())));
+            };
+            let op = load_reduce_op(node.op(), idx, node.op().name().to_string())?;
+            let axes = normalize_axes(&op.axes[..], input_rank(inputs, 0))?;
+            if axes.len() != 1 {
+                return Err(Box::new(GraphError::InvalidDims(idx, "argmax".to_string())));
+            }
+
+            if node.op().name() == "Reduce<ArgMax(false)>" {
+                SupportedOp::Hybrid(HybridOp::ReduceArgMax { axis: axes[0] })
+            } else {
+                SupportedOp::Hybrid(HybridOp::ReduceArgMin { axis: axes[0] })
+            }
+        }
         "Reduce<Sum>" => {
             if inputs.len() != 1 {
                 return Err(Box::new(GraphError::InvalidDims(idx, "sum".to_string())));
             };
             let op = load_reduce_op(node.op(), idx, node.op().name().to_string())?;
-            let axes = op.axes.into_iter().collect();
+            let axes = normalize_axes(&op.axes[..], input_rank(inputs, 0))?;
 
             SupportedOp::Linear(PolyOp::Sum { axes })
         }
@@ -450,7 +1026,81 @@ pub fn new_op_from_onnx(
             panic!("should never reach here")
         }
         "QuantizeLinearU8" | "DequantizeLinearF32" => SupportedOp::Linear(PolyOp::Identity),
+        "Cast" => {
+            let op = load_cast_op(node.op(), idx, node.op().name().to_string())?;
+            // the source type isn't known here, casts that leave the values unchanged are replaced by the identity
+            // when the graph is built, see [cast_is_identity]
+            match DatumRepr::of(op.to) {
+                Some(DatumRepr::Integer) => {
+                    SupportedOp::Nonlinear(LookupOp::Trunc { scales: (1, 1) })
+                }
+                Some(DatumRepr::Float) => SupportedOp::Linear(PolyOp::Identity),
+                _ => {
+                    return Err(Box::new(GraphError::UnsupportedCast(
+                        idx,
+                        format!("to {:?}", op.to),
+                    )))
+                }
+            }
+        }
+        "Dropout" => {
+            warn!("treating dropout as the identity, as it is at inference, its ratio is ignored");
+            // remove the ratio and training mode nodes from the inputs
+            for (i, input) in inputs.iter_mut().enumerate().skip(1) {
+                input.decrement_const();
+                deleted_indices.push(i);
+            }
+            SupportedOp::Linear(PolyOp::Identity)
+        }
         "Abs" => SupportedOp::Hybrid(HybridOp::Abs),
+        "Elu" | "Selu" => {
+            let elu_op = load_eltwise_op(node.op(), idx, node.op().name().to_string())?;
+            let elu_op: &OnnxElu = match elu_op.0.downcast_ref::<OnnxElu>() {
+                Some(b) => b,
+                None => {
+                    return Err(Box::new(GraphError::OpMismatch(idx, "elu".to_string())));
+                }
+            };
+            let alpha = crate::circuit::utils::F32(elu_op.alpha);
+            match elu_op.gamma {
+                Some(gamma) => SupportedOp::Nonlinear(LookupOp::Selu {
+                    scales: (1, 1),
+                    alpha,
+                    gamma: crate::circuit::utils::F32(gamma),
+                }),
+                None => SupportedOp::Nonlinear(LookupOp::Elu {
+                    scales: (1, 1),
+                    alpha,
+                }),
+            }
+        }
+        "RmsNorm" => {
+            let rms_norm_op = load_eltwise_op(node.op(), idx, node.op().name().to_string())?;
+            let rms_norm_op: &OnnxRmsNorm = match rms_norm_op.0.downcast_ref::<OnnxRmsNorm>() {
+                Some(b) => b,
+                None => {
+                    return Err(Box::new(GraphError::OpMismatch(
+                        idx,
+                        "rms norm".to_string(),
+                    )));
+                }
+            };
+
+            // if its not the last dim then we don't support it
+            let dims = inputs[0].out_dims()[0].clone();
+            if normalize_axis(rms_norm_op.axis, dims.len())? + 1 != dims.len() {
+                return Err(Box::new(GraphError::InvalidDims(
+                    idx,
+                    "rms norm".to_string(),
+                )));
+            }
+
+            SupportedOp::Hybrid(HybridOp::RmsNorm {
+                scales: (1, 1),
+                dim: dims[dims.len() - 1],
+                eps: crate::circuit::utils::F32(rms_norm_op.epsilon),
+            })
+        }
         "Neg" => SupportedOp::Linear(PolyOp::Neg),
         "Sigmoid" => SupportedOp::Nonlinear(LookupOp::Sigmoid { scales: (1, 1) }),
         "Sqrt" => SupportedOp::Nonlinear(LookupOp::Sqrt { scales: (1, 1) }),
@@ -470,53 +1120,54 @@ pub fn new_op_from_onnx(
         "Acosh" => SupportedOp::Nonlinear(LookupOp::ACosh { scales: (1, 1) }),
         "Atanh" => SupportedOp::Nonlinear(LookupOp::ATanh { scales: (1, 1) }),
         "Erf" => SupportedOp::Nonlinear(LookupOp::Erf { scales: (1, 1) }),
-        "Source" => SupportedOp::Input(crate::circuit::ops::Input { scale }),
+        "Source" => SupportedOp::Input(crate::circuit::ops::Input {
+            scale,
+            normalization: None,
+        }),
         "Add" => SupportedOp::Linear(PolyOp::Add),
         "Sub" => SupportedOp::Linear(PolyOp::Sub),
         "Mul" => SupportedOp::Linear(PolyOp::Mult),
+        "Div" => {
+            // dividing by a single constant is a single lookup, a tensor needs the division gadget
+            match extract_const_raw_values(inputs[1].opkind()) {
+                Some(c) if c.len() == 1 && inputs.len() == 2 => {
+                    if let Some(node) = inputs.last_mut() {
+                        node.decrement_const();
+                        deleted_indices.push(inputs.len() - 1);
+                    }
+                    SupportedOp::Nonlinear(LookupOp::Div {
+                        denom: crate::circuit::utils::F32(c[0]),
+                    })
+                }
+                _ => SupportedOp::Hybrid(HybridOp::Div { scale: 1 }),
+            }
+        }
         "Iff" => SupportedOp::Linear(PolyOp::Iff),
         "Less" => {
-            // Extract the slope layer hyperparams
-            let unit = if let Some(c) = extract_const_raw_values(inputs[0].opkind()) {
-                if c.len() == 1 {
-                    c[0]
-                } else {
-                    todo!()
+            // comparisons against a single constant are a single lookup
+            match extract_const_raw_values(inputs[0].opkind()) {
+                Some(c) if c.len() == 1 && inputs.len() == 2 => {
+                    deleted_indices.push(0);
+                    SupportedOp::Nonlinear(LookupOp::LessThan {
+                        a: crate::circuit::utils::F32(c[0]),
+                    })
                 }
-            } else {
-                return Err(Box::new(GraphError::OpMismatch(idx, "less".to_string())));
-            };
-
-            if inputs.len() == 2 {
-                deleted_indices.push(0);
-                SupportedOp::Nonlinear(LookupOp::LessThan {
-                    a: crate::circuit::utils::F32(unit),
-                })
-            } else {
-                todo!()
+                _ => SupportedOp::Hybrid(HybridOp::Less),
             }
         }
         "Greater" => {
-            // Extract the slope layer hyperparams
-            let unit = if let Some(c) = extract_const_raw_values(inputs[0].opkind()) {
-                if c.len() == 1 {
-                    c[0]
-                } else {
-                    todo!()
+            // comparisons against a single constant are a single lookup
+            match extract_const_raw_values(inputs[0].opkind()) {
+                Some(c) if c.len() == 1 && inputs.len() == 2 => {
+                    deleted_indices.push(0);
+                    SupportedOp::Nonlinear(LookupOp::GreaterThan {
+                        a: crate::circuit::utils::F32(c[0]),
+                    })
                 }
-            } else {
-                return Err(Box::new(GraphError::OpMismatch(idx, "greater".to_string())));
-            };
-
-            if inputs.len() == 2 {
-                deleted_indices.push(0);
-                SupportedOp::Nonlinear(LookupOp::GreaterThan {
-                    a: crate::circuit::utils::F32(unit),
-                })
-            } else {
-                todo!()
+                _ => SupportedOp::Hybrid(HybridOp::Greater),
             }
         }
+        "Equals" => SupportedOp::Hybrid(HybridOp::Equal),
         "EinSum" => {
             // Extract the slope layer hyperparams
             let op: &EinSum = match node.op().downcast_ref::<EinSum>() {
@@ -541,7 +1192,8 @@ pub fn new_op_from_onnx(
             };
 
             // if its not the last dim then we don't support it
-            if softmax_op.axes.to_vec() != vec![inputs[0].out_dims()[0].len() - 1] {
+            let rank = input_rank(inputs, 0);
+            if normalize_axes(&softmax_op.axes[..], rank)? != vec![rank - 1] {
                 return Err(Box::new(GraphError::InvalidDims(
                     idx,
                     "softmax".to_string(),
@@ -634,7 +1286,22 @@ pub fn new_op_from_onnx(
             };
 
             let kernel = extract_tensor_value(conv_node.kernel.clone())?;
-            let kernel = quantize_tensor(kernel, scale, param_visibility)?;
+            let kernel = quantize_tensor(kernel, weight_scale, param_visibility)?;
+
+            // grouped convs (and depthwise convs, where group = in_channels) are laid out per group, the groups being
+            // inferred from the kernel's input channel dimension which holds the number of input channels per group
+            let input_dims = inputs[0].out_dims()[0].clone();
+            let in_channels = input_dims[input_dims.len() - 3];
+            if kernel.dims()[1] * conv_node.group != in_channels
+                || kernel.dims()[0] % conv_node.group != 0
+            {
+                return Err(Box::new(GraphError::MisformedParams(format!(
+                    "conv with {} groups has a kernel of shape {:?} for {} input channels",
+                    conv_node.group,
+                    kernel.dims(),
+                    in_channels
+                ))));
+            }
 
             let bias = match conv_node.bias.clone() {
                 Some(b) => {
@@ -642,7 +1309,7 @@ pub fn new_op_from_onnx(
 
                     let val = quantize_tensor(
                         const_value,
-                        scale + inputs[0].out_scales()[0],
+                        weight_scale + inputs[0].out_scales()[0],
                         param_visibility,
                     )?;
                     Some(val)
@@ -655,6 +1322,8 @@ pub fn new_op_from_onnx(
                 bias,
                 padding,
                 stride,
+                // our fixed point quantization is symmetric so the input's zero-point is always 0
+                pad_value: Fp::zero(),
             })
         }
         "DeconvUnary" => {
@@ -695,7 +1364,7 @@ pub fn new_op_from_onnx(
             };
 
             let kernel = extract_tensor_value(deconv_node.kernel.clone())?;
-            let kernel = quantize_tensor(kernel, scale, param_visibility)?;
+            let kernel = quantize_tensor(kernel, weight_scale, param_visibility)?;
 
             let bias = match deconv_node.bias.clone() {
                 Some(b) => {
@@ -703,7 +1372,7 @@ pub fn new_op_from_onnx(
 
                     let val = quantize_tensor(
                         const_value,
-                        scale + inputs[0].out_scales()[0],
+                        weight_scale + inputs[0].out_scales()[0],
                         param_visibility,
                     )?;
                     Some(val)
@@ -747,30 +1416,92 @@ pub fn new_op_from_onnx(
 
             let resize_node = format!("{:?}", node);
 
-            if !resize_node.contains("interpolator: Nearest")
-                && !resize_node.contains("nearest: Floor")
+            let mode = if resize_node.contains("interpolator: Nearest")
+                || resize_node.contains("nearest: Floor")
             {
-                unimplemented!("Only nearest neighbor interpolation is supported")
-            }
-            let boxed_op = inputs[2].opkind();
-            let scale_factor = if let Some(c) = extract_const_raw_values(boxed_op) {
-                c.map(|x| x as usize).into_iter().collect::<Vec<usize>>()
+                ResizeMode::Nearest
+            } else if resize_node.contains("interpolator: Linear") {
+                let transform = if resize_node.contains("coord_transformer: HalfPixel") {
+                    ResizeCoordTransform::HalfPixel
+                } else if resize_node.contains("coord_transformer: AlignCorners") {
+                    ResizeCoordTransform::AlignCorners
+                } else if resize_node.contains("coord_transformer: Asymmetric") {
+                    ResizeCoordTransform::Asymmetric
+                } else {
+                    return Err(Box::new(GraphError::MisformedParams(
+                        "unsupported resize coordinate transformation".to_string(),
+                    )));
+                };
+                // the interpolation weights are quantized at the global scale
+                ResizeMode::Linear {
+                    transform,
+                    weight_scale: scale,
+                }
+            } else {
+                return Err(Box::new(GraphError::MisformedParams(
+                    "only nearest and linear resize interpolation are supported".to_string(),
+                )));
+            };
+
+            // the scales (or sizes) input is the index following the debug label
+            let input_idx = |label: &str| {
+                resize_node
+                    .split(label)
+                    .nth(1)
+                    .and_then(|s| s.split(')').next())
+                    .and_then(|s| s.parse::<usize>().ok())
+            };
+
+            let in_dims = inputs[0].out_dims()[0].clone();
+            let sizes_input = input_idx("optional_sizes_input: Some(");
+            let scales_input = match input_idx("optional_scales_input: Some(") {
+                Some(i) => Some(i),
+                // default to the scales following the roi
+                None if sizes_input.is_none() && inputs.len() > 2 => Some(2),
+                None => None,
+            };
+            let scale_factor = if let Some(i) = scales_input {
+                match extract_const_raw_values(inputs[i].opkind()) {
+                    Some(c) if c.iter().all(|x| x.fract() == 0.0) => {
+                        c.map(|x| x as usize).into_iter().collect::<Vec<usize>>()
+                    }
+                    _ => {
+                        return Err(Box::new(GraphError::MisformedParams(
+                            "resize scales must be constant integers".to_string(),
+                        )))
+                    }
+                }
+            } else if let Some(i) = sizes_input {
+                match extract_const_raw_values(inputs[i].opkind()) {
+                    Some(c)
+                        if c.len() == in_dims.len()
+                            && c.iter()
+                                .zip(&in_dims)
+                                .all(|(s, d)| *d > 0 && (*s as usize) % d == 0) =>
+                    {
+                        c.iter()
+                            .zip(&in_dims)
+                            .map(|(s, d)| (*s as usize) / d)
+                            .collect::<Vec<usize>>()
+                    }
+                    _ => {
+                        return Err(Box::new(GraphError::MisformedParams(
+                            "resize sizes must be constant integer multiples of the input dims"
+                                .to_string(),
+                        )))
+                    }
+                }
             } else {
                 return Err(Box::new(GraphError::OpMismatch(idx, "Resize".to_string())));
             };
 
-            // remove the resize node from the inputs
-            if let Some(node) = inputs.last_mut() {
-                node.decrement_const();
-                deleted_indices.push(inputs.len() - 1);
-            }
-            // remove the scale factor node from the inputs
-            if let Some(node) = inputs.get_mut(inputs.len() - 2) {
-                node.decrement_const();
-                deleted_indices.push(inputs.len() - 2);
+            // remove the roi, scales and sizes nodes from the inputs
+            for (i, input) in inputs.iter_mut().enumerate().skip(1) {
+                input.decrement_const();
+                deleted_indices.push(i);
             }
 
-            SupportedOp::Linear(PolyOp::Resize { scale_factor })
+            SupportedOp::Linear(PolyOp::Resize { scale_factor, mode })
         }
 
         "SumPool" => {
@@ -822,40 +1553,25 @@ pub fn new_op_from_onnx(
                     return Err(Box::new(GraphError::OpMismatch(idx, "pad".to_string())));
                 }
             };
-            // we only support constant 0 padding
-            if pad_node.mode
-                != PadMode::Constant(tract_onnx::prelude::Arc::new(
-                    tract_onnx::prelude::Tensor::zero::<f32>(&[])?,
-                ))
-            {
-                return Err(Box::new(GraphError::MisformedParams(
-                    "pad mode or pad type".to_string(),
-                )));
-            }
-
-            let padding_len = pad_node.pads.len();
-
-            // we only support symmetrical padding that affects the last 2 dims (height and width params)
-            for (i, pad_params) in pad_node.pads.iter().enumerate() {
-                if (i < padding_len - 2) && ((pad_params.0 != 0) || (pad_params.1 != 0)) {
+            let mode = match &pad_node.mode {
+                PadMode::Constant(fill) => {
+                    let fill = fill.cast_to_scalar::<f32>()?;
+                    // the fill value lives at the same scale as the padded input
+                    let fill = quantize_float(&(fill as f64), 0.0, inputs[0].out_scales()[0])?;
+                    TensorPadMode::Constant(crate::fieldutils::i128_to_felt::<Fp>(fill))
+                }
+                PadMode::Reflect => TensorPadMode::Reflect,
+                _ => {
                     return Err(Box::new(GraphError::MisformedParams(
-                        "ezkl currently only supports padding height and width dimensions"
-                            .to_string(),
+                        "ezkl currently only supports constant and reflect padding".to_string(),
                     )));
                 }
-            }
+            };
 
-            let padding = [
-                (
-                    pad_node.pads[padding_len - 2].0,
-                    pad_node.pads[padding_len - 1].0,
-                ),
-                (
-                    pad_node.pads[padding_len - 2].1,
-                    pad_node.pads[padding_len - 1].1,
-                ),
-            ];
-            SupportedOp::Linear(PolyOp::Pad(padding))
+            SupportedOp::Linear(PolyOp::Pad {
+                pads: pad_node.pads.clone(),
+                mode,
+            })
         }
         "RmAxis" | "Reshape" | "AddAxis" => {
             // Extract the slope layer hyperparams
@@ -868,10 +1584,13 @@ pub fn new_op_from_onnx(
             let new_dims: Vec<usize> = vec![inputs[0].out_dims()[0].iter().product::<usize>()];
             SupportedOp::Linear(PolyOp::Flatten(new_dims))
         }
-        c => {
-            warn!("Unknown op: {}", c);
-            SupportedOp::Unknown(crate::circuit::ops::Unknown)
-        }
+        c => match super::custom::load_custom_op(node.op()) {
+            Some(op) => SupportedOp::Custom(op),
+            None => {
+                warn!("Unknown op: {}", c);
+                unsupported_op(idx, &node, unsupported_ops, inputs, &mut deleted_indices)?
+            }
+        },
     };
 
     Ok((node, deleted_indices))
@@ -910,7 +1629,72 @@ pub fn extract_conv_values(boxed_op: Box<dyn crate::circuit::Op<Fp>>) -> [Option
     [None, None]
 }
 
-/// Converts a tensor to a [ValTensor] with a given scale.
+/// How the quantized weights of a layer are degenerate, which usually means they didn't survive the export.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DegenerateWeights {
+    /// every weight is zero
+    AllZero,
+    /// every weight is the same non-zero value
+    Constant,
+    /// the weights differ as exported but quantize to a single value at the weight scale
+    Collapsed,
+}
+
+impl std::fmt::Display for DegenerateWeights {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DegenerateWeights::AllZero => write!(f, "its weights are all zero"),
+            DegenerateWeights::Constant => write!(f, "its weights are all the same value"),
+            DegenerateWeights::Collapsed => write!(
+                f,
+                "its weights all quantize to the same value, consider raising the weight scale"
+            ),
+        }
+    }
+}
+
+/// Checks the `quantized` weights of a layer, and the `raw` weights they were quantized from if they are still
+/// known. Single weights are never degenerate.
+pub fn degenerate_weights(
+    raw: Option<&Tensor<f32>>,
+    quantized: &Tensor<Fp>,
+) -> Option<DegenerateWeights> {
+    if quantized.len() < 2 || !quantized.iter().all_equal() {
+        return None;
+    }
+    match raw {
+        Some(raw) if !raw.iter().all_equal() => Some(DegenerateWeights::Collapsed),
+        _ if quantized[0] == Fp::zero() => Some(DegenerateWeights::AllZero),
+        _ => Some(DegenerateWeights::Constant),
+    }
+}
+
+/// Warns about the degenerate weights (see [degenerate_weights]) of the matrix constants and conv kernels among
+/// `op`, loaded from the onnx node `name`. Constants of less than 2 non-unit dims (biases, scalars, per-channel
+/// factors) are legitimately all zero or constant, and aren't checked.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn check_degenerate_weights(name: &str, op: &SupportedOp) {
+    let issue = match op {
+        SupportedOp::Constant(c)
+            if c.quantized_values.dims().iter().filter(|d| **d > 1).count() > 1 =>
+        {
+            degenerate_weights(Some(&c.raw_values), &c.quantized_values)
+        }
+        SupportedOp::Linear(PolyOp::Conv { kernel, .. })
+        | SupportedOp::Linear(PolyOp::DeConv { kernel, .. }) => degenerate_weights(None, kernel),
+        _ => None,
+    };
+    if let Some(issue) = issue {
+        warn!(
+            "node {} ({}): {}, check the model was exported correctly",
+            name,
+            op.as_string(),
+            issue
+        );
+    }
+}
+
+/// Converts a tensor to a [ValTensor] with a given scale, quantizing each (widened) value with [quantize_float].
 pub fn quantize_tensor<F: PrimeField + TensorType + PartialOrd>(
     const_value: Tensor<f32>,
     scale: u32,
@@ -986,6 +1770,34 @@ pub fn homogenize_input_scales(
 pub mod tests {
 
     use super::*;
+    use crate::graph::tests::test_run_args;
+
+    #[test]
+    fn test_quantize_float_rounds_in_f64() {
+        let scale = 30;
+        let mult = scale_to_multiplier(scale);
+
+        // the inputs of a data file are f64s, which an f32 multiply-and-round would first narrow to its 24 bit
+        // mantissa, off by more than half a step at scale 30
+        let x = 0.1_f64;
+        let f32_path = ((x as f32) * mult as f32).round() as i128;
+        let f64_path = quantize_float(&x, 0.0, scale).unwrap();
+        assert!((f32_path as f64 / mult - x).abs() > 0.5 / mult);
+        assert!((f64_path as f64 / mult - x).abs() <= 0.5 / mult);
+
+        // the f32 constants of a model are widened, such that their round trip error is at most half a step
+        let values = [1.5e-3_f32, -7.123_456_7, 3.402_823e10, 1e-9];
+        let constants = Tensor::new(Some(&values), &[4]).unwrap();
+        for scale in [7, 16, 24, 30] {
+            let mult = scale_to_multiplier(scale);
+            let quantized =
+                quantize_tensor::<Fp>(constants.clone(), scale, Visibility::Private).unwrap();
+            for (x, q) in values.iter().zip(quantized.iter()) {
+                let dequantized = crate::fieldutils::felt_to_i128(*q) as f64 / mult;
+                assert!((dequantized - *x as f64).abs() <= 0.5 / mult);
+            }
+        }
+    }
 
     #[test]
     fn test_flatten_valtensors() {
@@ -1014,4 +1826,195 @@ pub mod tests {
         assert_eq!(split[2].dims(), vec![5, 2]);
         assert_eq!(split[2].len(), 10);
     }
+
+    #[test]
+    fn test_parse_scale() {
+        assert_eq!(parse_scale("7"), Ok(7));
+        assert_eq!(parse_scale("2^7"), Ok(7));
+        assert_eq!(parse_scale("x128"), Ok(7));
+        assert_eq!(parse_scale("x1"), Ok(0));
+        assert!(parse_scale("x100").is_err());
+        assert!(parse_scale("x0.5").is_err());
+        assert!(parse_scale("2^-1").is_err());
+
+        assert_eq!(checked_mult_to_scale(scale_to_multiplier(12)), Ok(12));
+        assert_eq!(mult_to_scale(100.0), 7);
+    }
+
+    #[test]
+    fn test_negative_axes() {
+        use crate::tensor::ops::{concat, gather, slice, sum_axes};
+
+        let a = Tensor::<i128>::new(Some(&(0..12).collect::<Vec<_>>()), &[2, 2, 3]).unwrap();
+        let rank = a.dims().len();
+        let last = normalize_axis(-1, rank).unwrap();
+        assert_eq!(last, 2);
+
+        // concat
+        let concatenated = concat(&[a.clone(), a.clone()], last).unwrap();
+        assert_eq!(concatenated.dims(), &[2, 2, 6]);
+
+        // slice
+        let sliced = slice(&a, &last, &1, &2).unwrap();
+        assert_eq!(
+            sliced,
+            Tensor::new(Some(&[1, 4, 7, 10]), &[2, 2, 1]).unwrap()
+        );
+
+        // gather
+        let index = Tensor::<usize>::new(Some(&[2]), &[1]).unwrap();
+        let gathered = gather(&a, last, &index).unwrap();
+        assert_eq!(gathered[..], [2, 5, 8, 11]);
+
+        // reduce (duplicate axes referring to the same dim are merged)
+        let axes = normalize_axes(&[-1i64, 2, -3], rank).unwrap();
+        assert_eq!(axes, vec![0, 2]);
+        let summed = sum_axes(&a, &axes).unwrap();
+        assert_eq!(summed, Tensor::new(Some(&[24, 42]), &[1, 2, 1]).unwrap());
+
+        // transpose
+        let mut moved = a.clone();
+        let moved = moved
+            .move_axis(
+                normalize_axis(-1, rank).unwrap(),
+                normalize_axis(-3, rank).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(moved.dims(), &[3, 2, 2]);
+
+        // softmax is only supported over the last axis, however it is expressed
+        assert_eq!(normalize_axes(&[-1i64], rank).unwrap(), vec![rank - 1]);
+
+        assert!(normalize_axes(&[3i64], rank).is_err());
+        assert!(normalize_axes(&[-4i64], rank).is_err());
+    }
+
+    #[test]
+    fn test_negative_axes_in_models() {
+        use crate::graph::Model;
+
+        let run_args = test_run_args();
+        // concat, slice, gather, sum and softmax over axis -1, see examples/onnx/negative_axes/gen.py
+        let path = "examples/onnx/negative_axes/network.onnx".into();
+        let model = Model::from_run_args(&run_args, &path).unwrap();
+        assert_eq!(
+            model.graph.output_shapes(),
+            vec![vec![1, 2, 1], vec![1, 2, 3]]
+        );
+
+        let input = Tensor::new(Some(&[0.0, 0.25, 0.5, 0.75, 1.0, 1.25]), &[1, 2, 3]).unwrap();
+        let outputs = model.forward_float(&[input]).unwrap();
+        // concatenating x with itself and slicing [1, 4) rotates each row, of which [2, 0] are its first two elements
+        assert_eq!(outputs[0].to_vec(), vec![0.25, 1.75]);
+        // each row sums to 1
+        for row in outputs[1].to_vec().chunks(3) {
+            assert!((row.iter().sum::<f32>() - 1.0).abs() < 0.05);
+            assert!(row[0] < row[1] && row[1] < row[2]);
+        }
+    }
+
+    #[test]
+    fn test_unsupported_op_policy() {
+        for policy in [
+            UnsupportedOpPolicy::Error,
+            UnsupportedOpPolicy::Skip,
+            UnsupportedOpPolicy::Approximate,
+        ] {
+            assert_eq!(
+                policy.to_string().parse::<UnsupportedOpPolicy>(),
+                Ok(policy)
+            );
+        }
+        assert!("ignore".parse::<UnsupportedOpPolicy>().is_err());
+        assert_eq!(UnsupportedOpPolicy::default(), UnsupportedOpPolicy::Error);
+
+        // only the approximate policy substitutes approximations, and only for the ops it documents
+        let approximate = UnsupportedOpPolicy::Approximate;
+        assert!(matches!(
+            approximate.approximation("Gelu"),
+            Some(SupportedOp::Nonlinear(_))
+        ));
+        assert!(approximate.approximation("Mish").is_some());
+        assert!(approximate.approximation("NonMaxSuppression").is_none());
+        assert!(UnsupportedOpPolicy::Skip.approximation("Gelu").is_none());
+    }
+
+    #[test]
+    fn test_cast() {
+        use crate::fieldutils::{felt_to_i128, i128_to_felt};
+        use crate::graph::{Model, NodeType};
+
+        // float to integer casts truncate, the others are the identity
+        assert!(!cast_is_identity(0, DatumType::F32, DatumType::I64).unwrap());
+        assert!(cast_is_identity(0, DatumType::I64, DatumType::F32).unwrap());
+        assert!(cast_is_identity(0, DatumType::I64, DatumType::I32).unwrap());
+        assert!(cast_is_identity(0, DatumType::Bool, DatumType::F32).unwrap());
+        assert!(cast_is_identity(0, DatumType::F32, DatumType::Bool).is_err());
+        assert!(cast_is_identity(0, DatumType::String, DatumType::F32).is_err());
+
+        let run_args = test_run_args();
+        let model =
+            Model::from_run_args(&run_args, &"examples/onnx/1l_cast/network.onnx".into()).unwrap();
+        let ops = model
+            .graph
+            .nodes
+            .values()
+            .filter_map(|n| match n {
+                NodeType::Node(n) if !n.opkind.is_input() => Some(n.opkind.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ops.iter()
+                .filter(|op| matches!(op, SupportedOp::Nonlinear(LookupOp::Trunc { .. })))
+                .count(),
+            1
+        );
+        assert!(ops.iter().all(|op| matches!(
+            op,
+            SupportedOp::Nonlinear(LookupOp::Trunc { .. }) | SupportedOp::Linear(PolyOp::Identity)
+        )));
+
+        let mult = scale_to_multiplier(run_args.scale);
+        let input = [2.7183, -1.4142, 0.5772]
+            .iter()
+            .map(|x| i128_to_felt(quantize_float(x, 0.0, run_args.scale).unwrap()))
+            .collect::<Vec<Fp>>();
+        let input = Tensor::new(Some(&input), &[1, 3]).unwrap();
+        let outputs = model.forward(&[input]).unwrap().outputs;
+        let output = outputs[0]
+            .iter()
+            .map(|x| felt_to_i128(*x) as f64 / mult)
+            .collect::<Vec<_>>();
+        assert_eq!(output, vec![2.0, -1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_degenerate_weights() {
+        let quantize = |raw: &[f32], scale| {
+            let raw = Tensor::new(Some(raw), &[raw.len()]).unwrap();
+            let quantized = quantize_tensor::<Fp>(raw.clone(), scale, Visibility::Private).unwrap();
+            degenerate_weights(Some(&raw), &quantized)
+        };
+
+        assert_eq!(quantize(&[0.5, -0.25, 1.0], 7), None);
+        let all_zero = Some(DegenerateWeights::AllZero);
+        assert_eq!(quantize(&[0.0, 0.0, 0.0], 7), all_zero);
+        assert_eq!(
+            quantize(&[0.5, 0.5, 0.5], 7),
+            Some(DegenerateWeights::Constant)
+        );
+        // too small to survive quantization at scale 2
+        assert_eq!(
+            quantize(&[0.01, -0.02, 0.03], 2),
+            Some(DegenerateWeights::Collapsed)
+        );
+        assert_eq!(quantize(&[0.01, -0.02, 0.03], 7), None);
+        // a single weight, e.g a scalar constant
+        assert_eq!(quantize(&[0.0], 7), None);
+
+        // without the raw weights, weights that collapsed to zero look all zero
+        let kernel = Tensor::new(Some(&[Fp::zero(); 4]), &[2, 2]).unwrap();
+        assert_eq!(degenerate_weights(None, &kernel), all_zero);
+    }
 }
@@ -14,6 +14,7 @@ use pyo3::{
 
 use serde::{Deserialize, Serialize};
 
+use super::columns::{AdviceColumnUsage, MODEL_ADVICE_TENSORS};
 use super::*;
 
 /// Label enum to track whether model input, model parameters, and model output are public, private, or hashed
@@ -100,6 +101,80 @@ impl std::fmt::Display for Visibility {
     }
 }
 
+/// The set of model output heads that are exposed as public instances when outputs are public.
+/// Heads outside the set are still computed in the shared model region, they just aren't constrained to public inputs.
+/// Parsed from `all` or a comma separated list of output indices, e.g. `0,2`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OutputHeads(pub u64);
+
+impl Default for OutputHeads {
+    fn default() -> Self {
+        OutputHeads(u64::MAX)
+    }
+}
+
+impl OutputHeads {
+    /// Selects every output head.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Selects the output heads at `indices`.
+    pub fn from_indices(indices: &[usize]) -> Result<Self, String> {
+        let mut mask = 0u64;
+        for i in indices {
+            if *i >= u64::BITS as usize {
+                return Err(format!(
+                    "output head {} is out of range, at most {} heads can be selected",
+                    i,
+                    u64::BITS
+                ));
+            }
+            mask |= 1 << i;
+        }
+        Ok(OutputHeads(mask))
+    }
+
+    /// Whether the output head at `index` is selected.
+    pub fn contains(&self, index: usize) -> bool {
+        index < u64::BITS as usize && (self.0 >> index) & 1 == 1
+    }
+
+    /// Returns the selected indices among `num_outputs` output heads.
+    pub fn indices(&self, num_outputs: usize) -> Vec<usize> {
+        (0..num_outputs).filter(|i| self.contains(*i)).collect()
+    }
+}
+
+impl std::str::FromStr for OutputHeads {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim() == "all" {
+            return Ok(OutputHeads::all());
+        }
+        let indices = s
+            .split(',')
+            .map(|i| {
+                i.trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid output head index: {}", i))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        OutputHeads::from_indices(&indices)
+    }
+}
+
+impl std::fmt::Display for OutputHeads {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if *self == OutputHeads::all() {
+            write!(f, "all")
+        } else {
+            write!(f, "{}", self.indices(u64::BITS as usize).iter().join(","))
+        }
+    }
+}
+
 /// Represents whether the model input, model parameters, and model output are Public or Private to the prover.
 #[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, PartialOrd)]
 pub struct VarVisibility {
@@ -109,6 +184,20 @@ pub struct VarVisibility {
     pub params: Visibility,
     /// Output of the model or computational graph
     pub output: Visibility,
+    /// Which output heads are public when `output` is public
+    pub output_heads: OutputHeads,
+    /// Statistics of the input that are disclosed as public instances
+    pub input_statistics: InputStatistics,
+    /// Whether the circuit takes a verifier-supplied challenge as its last model instance
+    pub challenge: bool,
+    /// Whether the argmax of the first output is exposed as a public instance, see [crate::RunArgs::classify]
+    pub classify: bool,
+    /// Whether the first output is asserted to lie within public bounds, see [crate::RunArgs::assert_output_range]
+    pub output_range: bool,
+    /// The public input constrained to be in canonical order, see [crate::RunArgs::sorted_input]
+    pub sorted_input: Option<usize>,
+    /// Whether the sorted input is also constrained to hold no duplicates, see [crate::RunArgs::dedup_sorted_input]
+    pub dedup_sorted_input: bool,
 }
 impl std::fmt::Display for VarVisibility {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -120,6 +209,205 @@ impl std::fmt::Display for VarVisibility {
     }
 }
 
+/// What an instance column of the circuit holds. The columns are laid out (and the public inputs are assembled) in
+/// the order of [GraphCircuit::instance_layout]: the model's columns, as given by [Model::instance_layout], then any
+/// zero padding and the modules' columns.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum InstanceColumn {
+    /// The model input with this index, when inputs are public.
+    Input(usize),
+    /// The model output with this index, when outputs are public and the output is one of the public heads.
+    Output(usize),
+    /// The disclosed [InputStatistics] of the first model input.
+    InputStatistics,
+    /// The class (the argmax over the last axis) of the first model output, see [crate::RunArgs::classify].
+    Class,
+    /// The `(min, max)` bounds the first model output lies within, see [crate::RunArgs::assert_output_range].
+    OutputRange,
+    /// The verifier-supplied challenge, see [crate::RunArgs::challenge].
+    Challenge,
+    /// Zero felts padding the public inputs, see [crate::RunArgs::pad_public_inputs].
+    Padding,
+    /// The instance column with this index of the modules in use (the poseidon column, then the elgamal one).
+    Module(usize),
+}
+
+/// Statistics of the (possibly private) model input that are disclosed as public instances, such that a verifier
+/// learns e.g the sum of a private input without learning the input itself. The statistics are computed in-circuit,
+/// with the reduction primitives, over the model's first input (flattened and quantized at the input's scale) and are
+/// exposed as a single instance column, in the order:
+///
+/// - `sum`: the sum of the input's elements, at the input's scale.
+/// - `nonzero`: the number of non-zero elements.
+/// - `element:<i>`: the element at flat index `i` (at most 64 elements, `i < 64`), at the input's scale. Several
+///   elements can be disclosed, in increasing order of index.
+///
+/// It is up to the verifier to check the disclosed values against its policy, e.g that the sum is within a range or
+/// that an element equals a specific value. Parsed from `none` or a comma separated list, e.g. `sum,element:3`.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct InputStatistics {
+    /// Disclose the sum of the input
+    pub sum: bool,
+    /// Disclose the number of non-zero elements of the input
+    pub nonzero: bool,
+    /// A mask of the flat indices of the input elements to disclose
+    pub elements: u64,
+}
+
+impl InputStatistics {
+    /// Whether no statistics are disclosed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of disclosed statistics, i.e the length of their instance column.
+    pub fn len(&self) -> usize {
+        self.sum as usize + self.nonzero as usize + self.elements.count_ones() as usize
+    }
+
+    /// The flat indices of the disclosed input elements.
+    pub fn element_indices(&self) -> Vec<usize> {
+        (0..u64::BITS as usize)
+            .filter(|i| (self.elements >> i) & 1 == 1)
+            .collect()
+    }
+
+    /// Checks the disclosed elements are within an input of `input_len` elements.
+    pub fn check(&self, input_len: usize) -> Result<(), GraphError> {
+        match self.element_indices().into_iter().find(|i| *i >= input_len) {
+            Some(i) => Err(GraphError::InvalidInputStatistics(format!(
+                "element {} is out of range for an input of {} elements",
+                i, input_len
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Computes the disclosed statistics of the quantized `input`, as they are laid out in-circuit.
+    pub fn evaluate(&self, input: &[Fp]) -> Vec<Fp> {
+        let mut stats = vec![];
+        if self.sum {
+            stats.push(input.iter().fold(Fp::zero(), |acc, x| acc + x));
+        }
+        if self.nonzero {
+            let count = input.iter().filter(|x| **x != Fp::zero()).count();
+            stats.push(Fp::from(count as u64));
+        }
+        stats.extend(self.element_indices().into_iter().map(|i| input[i]));
+        stats
+    }
+}
+
+impl std::str::FromStr for InputStatistics {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut stats = InputStatistics::default();
+        if s.trim() == "none" {
+            return Ok(stats);
+        }
+        for stat in s.split(',').map(|stat| stat.trim()) {
+            match stat.split_once(':') {
+                None if stat == "sum" => stats.sum = true,
+                None if stat == "nonzero" => stats.nonzero = true,
+                Some(("element", i)) => {
+                    let i = i
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|_| format!("invalid element index: {}", i))?;
+                    if i >= u64::BITS as usize {
+                        return Err(format!(
+                            "element {} is out of range, at most the first {} elements can be disclosed",
+                            i,
+                            u64::BITS
+                        ));
+                    }
+                    stats.elements |= 1 << i;
+                }
+                _ => return Err(format!("unknown input statistic: {}", stat)),
+            }
+        }
+        Ok(stats)
+    }
+}
+
+impl std::fmt::Display for InputStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut stats = vec![];
+        if self.sum {
+            stats.push("sum".to_string());
+        }
+        if self.nonzero {
+            stats.push("nonzero".to_string());
+        }
+        stats.extend(
+            self.element_indices()
+                .into_iter()
+                .map(|i| format!("element:{}", i)),
+        );
+        if stats.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", stats.join(","))
+        }
+    }
+}
+
+/// The bounds a prover claims the first model output lies within, see [crate::RunArgs::assert_output_range]. Given
+/// as floats, quantized at the output's scale, and parsed from `min,max`, e.g. `700,850`.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct OutputRange {
+    /// The smallest value the output may take
+    pub min: f64,
+    /// The largest value the output may take
+    pub max: f64,
+}
+
+impl OutputRange {
+    /// The bounds quantized at `scale`, as the circuit takes them as public inputs.
+    pub fn quantize(&self, scale: u32) -> Result<[Fp; 2], GraphError> {
+        let quantize = |x: &f64| {
+            quantize_float(x, 0.0, scale)
+                .map(crate::fieldutils::i128_to_felt)
+                .map_err(|e| {
+                    GraphError::OutputRange(format!("can't quantize the bound {}: {}", x, e))
+                })
+        };
+        Ok([quantize(&self.min)?, quantize(&self.max)?])
+    }
+
+    /// The bounds a circuit took as public inputs at `scale`, as quantized by [OutputRange::quantize].
+    pub fn from_quantized(bounds: [Fp; 2], scale: u32) -> Self {
+        let dequantize =
+            |x: Fp| crate::fieldutils::felt_to_i128(x) as f64 / scale_to_multiplier(scale);
+        OutputRange {
+            min: dequantize(bounds[0]),
+            max: dequantize(bounds[1]),
+        }
+    }
+}
+
+impl std::str::FromStr for OutputRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid output range `{}`, expected `min,max`", s);
+        let (min, max) = s.split_once(',').ok_or_else(invalid)?;
+        let min = min.trim().parse::<f64>().map_err(|_| invalid())?;
+        let max = max.trim().parse::<f64>().map_err(|_| invalid())?;
+        if min > max {
+            return Err(format!("the output range {} is empty", s));
+        }
+        Ok(OutputRange { min, max })
+    }
+}
+
+impl std::fmt::Display for OutputRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{},{}", self.min, self.max)
+    }
+}
+
 impl VarVisibility {
     /// Read from cli args whether the model input, model parameters, and model output are Public or Private to the prover.
     /// Place in [VarVisibility] struct.
@@ -136,13 +424,46 @@ impl VarVisibility {
             & !output_vis.is_encrypted()
             & !params_vis.is_encrypted()
             & !input_vis.is_encrypted()
+            & args.input_statistics.is_empty()
+            & !args.challenge
+            & !args.classify
+            & !args.assert_output_range
         {
             return Err(Box::new(GraphError::Visibility));
         }
+        if args.classify && output_vis.is_public() {
+            return Err(Box::new(GraphError::Classify(
+                "the class is the only public output, so the outputs can't be public".to_string(),
+            )));
+        }
+        if args.assert_output_range && output_vis.is_public() {
+            return Err(Box::new(GraphError::OutputRange(
+                "only the bounds of the output are public, so the outputs can't be public"
+                    .to_string(),
+            )));
+        }
+        // the verifier only gains the canonical ordering of inputs it sees
+        if args.sorted_input.is_some() && !input_vis.is_public() {
+            return Err(Box::new(GraphError::SortedInput(
+                "only public inputs can be constrained to be sorted".to_string(),
+            )));
+        }
+        if args.dedup_sorted_input && args.sorted_input.is_none() {
+            return Err(Box::new(GraphError::SortedInput(
+                "deduplication requires an input to be sorted".to_string(),
+            )));
+        }
         Ok(Self {
             input: input_vis,
             params: params_vis,
             output: output_vis,
+            output_heads: args.public_outputs,
+            input_statistics: args.input_statistics,
+            challenge: args.challenge,
+            classify: args.classify,
+            output_range: args.assert_output_range,
+            sorted_input: args.sorted_input,
+            dedup_sorted_input: args.dedup_sorted_input,
         })
     }
 }
@@ -157,16 +478,23 @@ pub struct ModelVars<F: PrimeField + TensorType + PartialOrd> {
 }
 
 impl<F: PrimeField + TensorType + PartialOrd> ModelVars<F> {
-    /// Allocate all columns that will be assigned to by a model.
+    /// Allocate all columns that will be assigned to by a model. When `eliminate_dead_columns` is set only the
+    /// columns a layout of `var_len` rows assigns to are allocated, see [AdviceColumnUsage].
     pub fn new(
         cs: &mut ConstraintSystem<F>,
         logrows: usize,
         var_len: usize,
         instance_dims: Vec<Vec<usize>>,
         scale: u32,
+        eliminate_dead_columns: bool,
     ) -> Self {
-        let advices = (0..3)
-            .map(|_| VarTensor::new_advice(cs, logrows, var_len))
+        let usage = AdviceColumnUsage::new(var_len, VarTensor::max_rows(cs, logrows));
+        let num_cols = match eliminate_dead_columns {
+            true => usage.pack(),
+            false => usage.allocated,
+        };
+        let advices = (0..MODEL_ADVICE_TENSORS)
+            .map(|_| VarTensor::new_advice_columns(cs, logrows, num_cols))
             .collect_vec();
         // will be empty if instances dims has len 0
         let instances = (0..instance_dims.len())
@@ -30,7 +30,8 @@
 
 use circuit::Tolerance;
 use clap::Args;
-use graph::Visibility;
+use graph::modules::HashFunction;
+use graph::{InputStatistics, OutputHeads, UnsupportedOpPolicy, Visibility};
 use serde::{Deserialize, Serialize};
 
 /// Methods for configuring tensor operations and assigning values to them in a Halo2 circuit.
@@ -72,16 +73,24 @@ pub struct RunArgs {
     /// The tolerance for error on model outputs
     #[arg(short = 'T', long, default_value = "0")]
     pub tolerance: Tolerance,
-    /// The denominator in the fixed point representation used when quantizing
-    #[arg(short = 'S', long, default_value = "7")]
+    /// The denominator in the fixed point representation used when quantizing, as a log base 2 exponent (`7` or `2^7`).
+    /// A raw multiplier can be passed as `x128`, but must be a power of two
+    #[arg(short = 'S', long, default_value = "7", value_parser = graph::parse_scale)]
     pub scale: u32,
+    /// The scale (as for `scale`) weights and other constants are quantized at, defaulting to `scale`. When set,
+    /// matmuls, convolutions and elementwise multiplications take their operands at their own scales and their outputs,
+    /// at the sum of their operands' scales, are requantized back down to `scale` (see [graph::Requantized]).
+    /// Constants consumed by other ops (e.g a separately added bias) are rescaled to match their other inputs, so
+    /// this should be at least `scale`
+    #[arg(long, value_parser = graph::parse_scale)]
+    pub weight_scale: Option<u32>,
     /// The number of bits used in lookup tables
     #[arg(short = 'B', long, default_value = "16")]
     pub bits: usize,
     /// The log_2 number of rows
     #[arg(short = 'K', long, default_value = "17")]
     pub logrows: u32,
-    /// The number of batches to split the input data into
+    /// The number of inferences proven in a single circuit
     #[arg(long, default_value = "1")]
     pub batch_size: usize,
     /// Flags whether inputs are public, private, hashed
@@ -93,4 +102,74 @@ pub struct RunArgs {
     /// Flags whether params are public, private, hashed
     #[arg(long, default_value = "private")]
     pub param_visibility: Visibility,
+    /// The fixed number of steps recurrent (Scan) nodes are unrolled for. The recurrent body is laid out once per step (sharing its weights), so the number of rows grows linearly with this value
+    #[arg(long, default_value = "1")]
+    #[serde(default)]
+    pub sequence_length: usize,
+    /// Pads the public inputs with zero felts up to this total length (0 disables padding)
+    #[arg(long, default_value = "0")]
+    #[serde(default)]
+    pub pad_public_inputs: usize,
+    /// Fuses chains of elementwise ops (affine ops with constant scalars and lookups) into a single lookup
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub fuse_lookups: bool,
+    /// Which output heads become public inputs when outputs are public: `all` or a comma separated list of output indices (e.g. `0,2`).
+    /// The remaining heads still share the backbone's layout but are not exposed
+    #[arg(long, default_value = "all")]
+    #[serde(default)]
+    pub public_outputs: OutputHeads,
+    /// Statistics of the model's first input that are computed in-circuit and disclosed as public instances (e.g. for
+    /// a private input): `none` or a comma separated list of `sum`, `nonzero` and `element:<i>`, see [graph::InputStatistics]
+    #[arg(long, default_value = "none")]
+    #[serde(default)]
+    pub input_statistics: InputStatistics,
+    /// Runs the model with integer arithmetic only, such that witnesses are bit-identical across platforms.
+    /// Requires a scale of 0, integer inputs (which bypass quantization) and integral weights. Ops that are evaluated
+    /// with transcendental float functions (sigmoid, exp, tanh, softmax, ...) are rejected, see [graph::SupportedOp::is_integer_exact]
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub integer_only: bool,
+    /// The hash function committing to hashed inputs, params and outputs, which is recorded in proofs such that
+    /// verifiers reconstruct the same digests, see [graph::modules::HashFunction]
+    #[arg(long, default_value = "poseidon")]
+    #[serde(default)]
+    pub hash_function: HashFunction,
+    /// Appends a challenge supplied by the verifier as a public instance the proof is bound to
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub challenge: bool,
+    /// Asserts the model's classification rather than disclosing its scores: the argmax over the last axis of the
+    /// first output is computed in the circuit and exposed as the only public output, such that a proof claiming
+    /// any other class fails. The outputs themselves have to be private (or hashed or encrypted)
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub classify: bool,
+    /// Exposes only the claimed bounds of the first output, which is range checked against them
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub assert_output_range: bool,
+    /// Constrains the public model input of this index to be in ascending order
+    #[arg(long)]
+    #[serde(default)]
+    pub sorted_input: Option<usize>,
+    /// Constrains the input picked by `sorted_input` to be strictly ascending, i.e sorted and deduplicated
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub dedup_sorted_input: bool,
+    /// How ops without a circuit implementation are handled when the circuit is built: `error`, `skip` (replacing
+    /// them by the identity) or `approximate` (substituting a documented approximation where there is one, see
+    /// [graph::UnsupportedOpPolicy]). Every skipped or approximated node is logged, as the circuit then no longer
+    /// exactly matches the model
+    #[arg(long, default_value = "error")]
+    #[serde(default)]
+    pub unsupported_ops: UnsupportedOpPolicy,
+    /// The scale the model's outputs are requantized to
+    #[arg(long, value_parser = graph::parse_scale)]
+    #[serde(default)]
+    pub output_scale: Option<u32>,
+    /// Allocate only the advice columns the model's layout assigns to
+    #[arg(long, default_value = "false")]
+    #[serde(default)]
+    pub eliminate_dead_columns: bool,
 }
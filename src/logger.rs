@@ -68,6 +68,12 @@ pub fn format(buf: &mut Formatter, record: &Record<'_>) -> Result<(), std::fmt::
 
 /// initializes the logger
 pub fn init_logger() {
+    init_logger_with_target(env_logger::Target::Stdout, LevelFilter::Info)
+}
+
+/// initializes the logger, writing records up to `level` to the given target (eg. stderr when stdout is reserved for
+/// machine-readable output). `RUST_LOG` overrides the level.
+pub fn init_logger_with_target(target: env_logger::Target, level: LevelFilter) {
     let start = Instant::now();
     let mut builder = Builder::new();
 
@@ -82,8 +88,8 @@ pub fn init_logger() {
                 .replace('\n', &format!("\n{} ", " | ".white().bold()))
         )
     });
-    builder.target(env_logger::Target::Stdout);
-    builder.filter(None, LevelFilter::Info);
+    builder.target(target);
+    builder.filter(None, level);
     if env::var("RUST_LOG").is_ok() {
         builder.parse_filters(&env::var("RUST_LOG").unwrap());
     }
@@ -0,0 +1,344 @@
+use super::{PfSysError, Snark, TranscriptType};
+use crate::graph::{GraphCircuit, GraphSettings};
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2curves::serde::SerdeObject;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Cursor, Read, Write};
+use std::path::PathBuf;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const PROOF_ENTRY: &str = "proof.json";
+const VK_ENTRY: &str = "vk.key";
+const SETTINGS_ENTRY: &str = "settings.json";
+
+/// Describes the contents of a [Bundle], such that a recipient can check they are pairing it with the right params.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// ezkl version the bundle was created with
+    pub version: String,
+    /// the fixed point scale of the circuit
+    pub scale: u32,
+    /// the lookup table bits of the circuit
+    pub bits: usize,
+    /// the log_2 of the number of rows of the circuit
+    pub logrows: u32,
+    /// the transcript the proof was generated with
+    pub transcript_type: TranscriptType,
+    /// hex encoded sha256 of the params (downsized to `logrows`) the proof was generated with, see [params_hash]
+    pub params_hash: String,
+}
+
+/// A proof together with everything needed to verify it, bar the params (which are large and shared across proofs).
+/// Saved as a single tar archive.
+#[derive(Clone, Debug)]
+pub struct Bundle {
+    /// describes the bundle contents
+    pub manifest: BundleManifest,
+    /// the proof
+    pub proof: Snark<Fr, G1Affine>,
+    /// the verifying key for the proof
+    pub vk: VerifyingKey<G1Affine>,
+    /// the settings of the circuit that was proven
+    pub settings: GraphSettings,
+}
+
+/// The hex encoded sha256 of the `k` of `params` and of the points a verifier uses (`g[0]`, `g2` and `s_g2`, which
+/// pin down the trusted setup). This is cheap whatever the size of the srs, and a full srs hashes the same as the
+/// verifier-only params extracted from it, see [super::srs::save_verifier_srs].
+pub fn params_hash(params: &ParamsKZG<Bn256>) -> Result<String, io::Error> {
+    let mut bytes = params.k().to_le_bytes().to_vec();
+    params.get_g()[0].write_raw(&mut bytes)?;
+    params.g2().write_raw(&mut bytes)?;
+    params.s_g2().write_raw(&mut bytes)?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
+impl Bundle {
+    /// Creates a bundle for `proof`, recording the hash of the `params` it was generated with.
+    pub fn new(
+        proof: Snark<Fr, G1Affine>,
+        vk: VerifyingKey<G1Affine>,
+        settings: GraphSettings,
+        params: &ParamsKZG<Bn256>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let manifest = BundleManifest {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            scale: settings.run_args.scale,
+            bits: settings.run_args.bits,
+            logrows: settings.run_args.logrows,
+            transcript_type: proof.transcript_type,
+            params_hash: params_hash(params)?,
+        };
+        Ok(Self {
+            manifest,
+            proof,
+            vk,
+            settings,
+        })
+    }
+
+    /// Saves the bundle as a tar archive to `path`.
+    pub fn save(&self, path: &PathBuf) -> Result<(), Box<dyn Error>> {
+        info!("saving bundle 💾");
+        let f = File::create(path)?;
+        let mut writer = BufWriter::new(f);
+        writer.write_all(&self.to_bytes()?)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Serializes the bundle as a tar archive.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut vk = vec![];
+        self.vk
+            .write(&mut vk, halo2_proofs::SerdeFormat::RawBytes)?;
+
+        Ok(pack(&[
+            (MANIFEST_ENTRY, serde_json::to_vec(&self.manifest)?),
+            (PROOF_ENTRY, serde_json::to_vec(&self.proof)?),
+            (VK_ENTRY, vk),
+            (SETTINGS_ENTRY, serde_json::to_vec(&self.settings)?),
+        ])?)
+    }
+
+    /// Loads a bundle saved with [Bundle::save] from `path`.
+    pub fn load(path: &PathBuf) -> Result<Self, Box<dyn Error>> {
+        info!("loading bundle from {:?}", path);
+        let mut bytes = vec![];
+        BufReader::new(File::open(path)?).read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Deserializes a bundle serialized with [Bundle::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut entries = unpack(bytes)?;
+        let mut entry = |name: &str| {
+            entries
+                .remove(name)
+                .ok_or_else(|| PfSysError::InconsistentBundle(format!("missing {}", name)))
+        };
+
+        let manifest: BundleManifest = serde_json::from_slice(&entry(MANIFEST_ENTRY)?)?;
+        let proof: Snark<Fr, G1Affine> = serde_json::from_slice(&entry(PROOF_ENTRY)?)?;
+        let settings: GraphSettings = serde_json::from_slice(&entry(SETTINGS_ENTRY)?)?;
+        let vk = VerifyingKey::<G1Affine>::read::<_, GraphCircuit>(
+            &mut Cursor::new(entry(VK_ENTRY)?),
+            halo2_proofs::SerdeFormat::RawBytes,
+            settings.clone(),
+        )?;
+
+        Ok(Self {
+            manifest,
+            proof,
+            vk,
+            settings,
+        })
+    }
+
+    /// Checks that the manifest, settings, verifying key and proof agree with one another and that `params` are the
+    /// params the proof was generated with. Doesn't verify the proof itself.
+    pub fn check(&self, params: &ParamsKZG<Bn256>) -> Result<(), PfSysError> {
+        let run_args = &self.settings.run_args;
+        let mismatch = |what: &str, manifest: String, actual: String| {
+            PfSysError::InconsistentBundle(format!(
+                "manifest {} is {} but the bundle holds {}",
+                what, manifest, actual
+            ))
+        };
+        if self.manifest.scale != run_args.scale {
+            return Err(mismatch(
+                "scale",
+                self.manifest.scale.to_string(),
+                run_args.scale.to_string(),
+            ));
+        }
+        if self.manifest.bits != run_args.bits {
+            return Err(mismatch(
+                "bits",
+                self.manifest.bits.to_string(),
+                run_args.bits.to_string(),
+            ));
+        }
+        if self.manifest.logrows != run_args.logrows {
+            return Err(mismatch(
+                "logrows",
+                self.manifest.logrows.to_string(),
+                run_args.logrows.to_string(),
+            ));
+        }
+        if self.vk.get_domain().k() != run_args.logrows {
+            return Err(mismatch(
+                "logrows",
+                run_args.logrows.to_string(),
+                format!("a vk for k={}", self.vk.get_domain().k()),
+            ));
+        }
+        if self.manifest.transcript_type != self.proof.transcript_type {
+            return Err(mismatch(
+                "transcript",
+                format!("{:?}", self.manifest.transcript_type),
+                format!("a {:?} proof", self.proof.transcript_type),
+            ));
+        }
+        self.settings
+            .check_public_input_hash(self.proof.public_input_hash.as_ref())
+            .map_err(|e| PfSysError::InconsistentBundle(e.to_string()))?;
+        let num_instance_columns = self.vk.cs().num_instance_columns();
+        if self.proof.instances.len() != num_instance_columns {
+            return Err(PfSysError::InconsistentBundle(format!(
+                "proof has {} instance columns but the vk expects {}",
+                self.proof.instances.len(),
+                num_instance_columns
+            )));
+        }
+        let hash =
+            params_hash(params).map_err(|e| PfSysError::InconsistentBundle(e.to_string()))?;
+        if hash != self.manifest.params_hash {
+            return Err(PfSysError::InconsistentBundle(format!(
+                "params hash {} doesn't match the bundle's {}, the proof was generated with different params",
+                hash, self.manifest.params_hash
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Writes named `entries` to an in-memory tar archive.
+pub(crate) fn pack(entries: &[(&str, Vec<u8>)]) -> Result<Vec<u8>, io::Error> {
+    let mut builder = tar::Builder::new(vec![]);
+    for (name, bytes) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, name, bytes.as_slice())?;
+    }
+    builder.into_inner()
+}
+
+/// Reads the entries of a tar archive written by [pack].
+pub(crate) fn unpack(bytes: &[u8]) -> Result<HashMap<String, Vec<u8>>, io::Error> {
+    let mut archive = tar::Archive::new(bytes);
+    let mut entries = HashMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        let mut contents = vec![];
+        entry.read_to_end(&mut contents)?;
+        entries.insert(name, contents);
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pfsys::srs::gen_srs;
+    use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+
+    #[test]
+    fn test_bundle_archive_roundtrip() {
+        let entries = pack(&[
+            (MANIFEST_ENTRY, b"{}".to_vec()),
+            (VK_ENTRY, vec![0, 1, 2, 3]),
+        ])
+        .unwrap();
+        let mut unpacked = unpack(&entries).unwrap();
+        assert_eq!(unpacked.len(), 2);
+        assert_eq!(unpacked.remove(MANIFEST_ENTRY).unwrap(), b"{}".to_vec());
+        assert_eq!(unpacked.remove(VK_ENTRY).unwrap(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_params_hash_distinguishes_params() {
+        let params = gen_srs::<KZGCommitmentScheme<Bn256>>(4);
+        let other = gen_srs::<KZGCommitmentScheme<Bn256>>(4);
+        assert_eq!(params_hash(&params).unwrap(), params_hash(&params).unwrap());
+        assert_ne!(params_hash(&params).unwrap(), params_hash(&other).unwrap());
+        // the same setup at another size
+        let mut downsized = params.clone();
+        downsized.downsize(3);
+        assert_ne!(
+            params_hash(&params).unwrap(),
+            params_hash(&downsized).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_bundle_end_to_end() {
+        use crate::graph::tests::relu_run_args;
+        use crate::graph::Visibility;
+        use crate::pfsys::srs::{load_verifier_srs, save_verifier_srs};
+        use crate::pfsys::tests::{prove_relu, relu_setup};
+        use crate::pfsys::verify_proof_circuit_kzg;
+        use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+        use tempfile::Builder;
+
+        let run_args = crate::RunArgs {
+            input_visibility: Visibility::Public,
+            ..relu_run_args()
+        };
+        let (circuit, params, pk) = relu_setup(&run_args);
+        let snark = prove_relu(&circuit, &params, &pk, 0);
+        let bundle = Bundle::new(
+            snark,
+            pk.get_vk().clone(),
+            circuit.settings.clone(),
+            &params,
+        )
+        .unwrap();
+        bundle.check(&params).unwrap();
+
+        let tmp_dir = Builder::new().prefix("example").tempdir().unwrap();
+        let (bundle_path, srs_path) = (
+            tmp_dir.path().join("proof.bundle"),
+            tmp_dir.path().join("kzg_verifier.srs"),
+        );
+        bundle.save(&bundle_path).unwrap();
+        let loaded = Bundle::load(&bundle_path).unwrap();
+        assert_eq!(loaded.manifest, bundle.manifest);
+        assert_eq!(loaded.proof.instances, bundle.proof.instances);
+
+        // the recipient checks and verifies the bundle against the verifier-only params
+        save_verifier_srs(&srs_path, &params).unwrap();
+        let verifier_params = load_verifier_srs(srs_path).unwrap();
+        loaded.check(&verifier_params).unwrap();
+        let strategy = SingleStrategy::new(&verifier_params);
+        assert!(verify_proof_circuit_kzg(
+            &verifier_params,
+            loaded.proof.clone(),
+            &loaded.vk,
+            strategy
+        )
+        .is_ok());
+
+        // but not against other params
+        let other = gen_srs::<KZGCommitmentScheme<Bn256>>(run_args.logrows);
+        assert!(matches!(
+            loaded.check(&other),
+            Err(PfSysError::InconsistentBundle(_))
+        ));
+        // nor with a manifest that disagrees with the settings
+        let mut tampered = loaded.clone();
+        tampered.manifest.bits += 1;
+        assert!(matches!(
+            tampered.check(&params),
+            Err(PfSysError::InconsistentBundle(_))
+        ));
+        // nor with a proof that doesn't match the vk
+        let mut tampered = loaded;
+        tampered.proof.instances.pop();
+        assert!(matches!(
+            tampered.check(&params),
+            Err(PfSysError::InconsistentBundle(_))
+        ));
+    }
+}
@@ -0,0 +1,210 @@
+use super::bundle::Bundle;
+use super::split::{boundary_columns, boundary_scales, is_output, SplitProof};
+use super::{create_proof_circuit_kzg, verify_proof_circuit_kzg, PfSysError, TranscriptType};
+use crate::circuit::CheckMode;
+use crate::graph::GraphCircuit;
+use crate::tensor::Tensor;
+use halo2_proofs::plonk::ProvingKey;
+use halo2_proofs::poly::commitment::ParamsProver;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use log::info;
+use std::error::Error;
+
+/// The inputs of `circuit` taken from the public outputs of the `previous` proof, one input per output. The chained
+/// model has to take its inputs publicly, such that the boundary between the proofs can be checked, and at the scales
+/// the previous model's settings output them.
+pub fn chained_inputs(
+    previous: &Bundle,
+    circuit: &GraphCircuit,
+) -> Result<Vec<Tensor<Fr>>, PfSysError> {
+    let boundary = boundary_columns(previous, "previous", is_output)?;
+    if !circuit.settings.run_args.input_visibility.is_public() {
+        return Err(PfSysError::SplitBoundary(
+            "the chained model has to take its inputs publicly".to_string(),
+        ));
+    }
+    let shapes = circuit.model.graph.input_shapes();
+    if shapes.len() != boundary.len() {
+        return Err(PfSysError::SplitBoundary(format!(
+            "the previous proof has {} public outputs but the chained model takes {} inputs",
+            boundary.len(),
+            shapes.len()
+        )));
+    }
+    let output_scales = boundary_scales(previous, is_output);
+    let input_scales: Vec<Option<u32>> = circuit
+        .model
+        .graph
+        .get_input_scales()
+        .into_iter()
+        .map(Some)
+        .collect();
+    if output_scales != input_scales {
+        return Err(PfSysError::SplitBoundary(format!(
+            "the previous proof outputs scales {:?} but the chained model takes scales {:?}",
+            output_scales, input_scales
+        )));
+    }
+
+    boundary
+        .iter()
+        .zip(shapes)
+        .enumerate()
+        .map(|(i, (values, shape))| {
+            Tensor::new(Some(values), &shape).map_err(|_| {
+                PfSysError::SplitBoundary(format!(
+                    "the previous proof's output {} has {} values but the chained model's input {} has shape {:?}",
+                    i,
+                    values.len(),
+                    i,
+                    shape
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Proves `circuit` on the public outputs of the `previous` proof, generated with `previous_params`, such that the
+/// intermediate values don't have to be trusted to whoever relays them: the previous bundle is checked and its proof
+/// verified before its outputs are taken as the inputs of the chained model (see [chained_inputs]). The two proofs
+/// are returned as a [SplitProof], whose boundary is both the previous proof's public outputs and the chained proof's
+/// public inputs, and which is verified as any other with `ezkl verify-split`.
+pub fn prove_chained(
+    previous: Bundle,
+    previous_params: &ParamsKZG<Bn256>,
+    mut circuit: GraphCircuit,
+    pk: &ProvingKey<G1Affine>,
+    params: &ParamsKZG<Bn256>,
+    transcript: TranscriptType,
+) -> Result<SplitProof, Box<dyn Error>> {
+    previous.check(previous_params)?;
+    previous
+        .settings
+        .check_public_inputs_range(&previous.proof.instances)?;
+    let verifier_params = previous_params.verifier_params();
+    verify_proof_circuit_kzg(
+        verifier_params,
+        previous.proof.clone(),
+        &previous.vk,
+        SingleStrategy::new(verifier_params),
+    )?;
+    info!("verified the previous proof");
+
+    let inputs = chained_inputs(&previous, &circuit)?;
+    let witness = circuit.forward(&inputs)?;
+    circuit.load_graph_witness(&witness)?;
+    let public_inputs = circuit.prepare_public_inputs(&witness)?;
+    let settings = circuit.settings.clone();
+
+    let mut snark = create_proof_circuit_kzg(
+        circuit,
+        params,
+        public_inputs,
+        pk,
+        transcript,
+        SingleStrategy::new(params),
+        CheckMode::SAFE,
+    )?;
+    snark.public_input_hash = settings.public_input_hash();
+
+    let chained = Bundle::new(snark, pk.get_vk().clone(), settings, params)?;
+    Ok(SplitProof::new(previous, chained)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::tests::relu_run_args;
+    use crate::graph::{InstanceColumn, Visibility};
+    use crate::pfsys::{create_keys, srs::gen_srs};
+    use crate::RunArgs;
+    use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+
+    #[test]
+    fn test_prove_chained() {
+        let run_args = RunArgs {
+            input_visibility: Visibility::Public,
+            ..relu_run_args()
+        };
+        let params = gen_srs::<KZGCommitmentScheme<Bn256>>(run_args.logrows);
+        let keyed = |model: &str| {
+            let path = format!("examples/onnx/{}/network.onnx", model).into();
+            let circuit = GraphCircuit::from_run_args(&run_args, &path).unwrap();
+            let pk = create_keys::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(&circuit, &params)
+                .unwrap();
+            (circuit, pk)
+        };
+
+        // the first model is proven as usual
+        let (mut first, first_pk) = keyed("1l_relu");
+        let shape = first.model.graph.input_shapes()[0].clone();
+        let values = (0..shape.iter().product::<usize>() as u64)
+            .map(|i| match i % 2 {
+                0 => Fr::from(i + 1),
+                _ => -Fr::from(i + 1),
+            })
+            .collect::<Vec<_>>();
+        let input = Tensor::new(Some(&values), &shape).unwrap();
+        let witness = first.forward(&[input]).unwrap();
+        first.load_graph_witness(&witness).unwrap();
+        let public_inputs = first.prepare_public_inputs(&witness).unwrap();
+        let settings = first.settings.clone();
+        let mut proof = create_proof_circuit_kzg(
+            first,
+            &params,
+            public_inputs,
+            &first_pk,
+            TranscriptType::Blake,
+            SingleStrategy::new(&params),
+            CheckMode::SAFE,
+        )
+        .unwrap();
+        let previous = Bundle::new(proof, first_pk.get_vk().clone(), settings, &params).unwrap();
+
+        // the second is proven on the first's outputs
+        let (second, second_pk) = keyed("1l_sigmoid");
+        let chained = prove_chained(
+            previous.clone(),
+            &params,
+            second,
+            &second_pk,
+            &params,
+            TranscriptType::Blake,
+        )
+        .unwrap();
+        assert_eq!(chained.boundary, witness.outputs);
+        chained.check(&params, &params).unwrap();
+        let verifier_params = params.verifier_params();
+        for part in [&chained.first, &chained.second] {
+            verify_proof_circuit_kzg(
+                verifier_params,
+                part.proof.clone(),
+                &part.vk,
+                SingleStrategy::new(verifier_params),
+            )
+            .unwrap();
+        }
+
+        // a relayed output that was tampered with doesn't verify, so nothing is chained on it
+        let mut tampered = previous;
+        let column = tampered
+            .settings
+            .instance_layout()
+            .iter()
+            .position(|c| *c == InstanceColumn::Output(0))
+            .unwrap();
+        tampered.proof.instances[column][0] += Fr::from(1);
+        let (second, second_pk) = keyed("1l_sigmoid");
+        assert!(prove_chained(
+            tampered,
+            &params,
+            second,
+            &second_pk,
+            &params,
+            TranscriptType::Blake
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,197 @@
+use super::bundle::params_hash;
+use super::{load_pk, save_pk};
+use crate::circuit::CheckMode;
+use crate::graph::{GraphCircuit, GraphSettings};
+use halo2_proofs::plonk::ProvingKey;
+use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+const FINGERPRINT_ENTRY: &str = "fingerprint";
+const CIRCUIT_ENTRY: &str = "circuit.bin";
+const PK_ENTRY: &str = "pk.key";
+
+/// A directory holding the completed stages of a long running prove, such that a prove that is restarted (e.g after
+/// the machine went down) resumes from the last completed stage. The resumable stages are, in order:
+///
+/// 1. witness assignment: the circuit with its witness loaded, see [ProveCheckpoint::circuit].
+/// 2. key generation: the proving key (and with it the verifying key), see [ProveCheckpoint::pk].
+///
+/// Proof creation itself isn't resumable, as halo2's `create_proof` is monolithic, and is re-run in full from the
+/// checkpointed circuit and keys. Each stage is written to a temporary file that is only moved into place once
+/// complete, so an interrupted stage is redone rather than resumed from a partial file.
+#[derive(Clone, Debug)]
+pub struct ProveCheckpoint {
+    dir: PathBuf,
+}
+
+impl ProveCheckpoint {
+    /// Opens (creating it if needed) the checkpoint directory `dir` for a prove identified by `fingerprint`, which
+    /// should change whenever any of the prove's inputs (witness, model, settings or params) do. Stages checkpointed
+    /// for a different fingerprint are stale and are discarded.
+    pub fn open(dir: PathBuf, fingerprint: &str) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(&dir)?;
+        let checkpoint = Self { dir };
+        let fingerprint_path = checkpoint.path(FINGERPRINT_ENTRY);
+        match fs::read_to_string(&fingerprint_path) {
+            Ok(existing) if existing == fingerprint => {
+                info!("resuming prove from checkpoint {:?}", checkpoint.dir);
+            }
+            Ok(_) => {
+                warn!(
+                    "checkpoint {:?} is for a different prove, discarding it",
+                    checkpoint.dir
+                );
+                for entry in [CIRCUIT_ENTRY, PK_ENTRY] {
+                    let path = checkpoint.path(entry);
+                    if path.exists() {
+                        fs::remove_file(path)?;
+                    }
+                }
+                fs::write(&fingerprint_path, fingerprint)?;
+            }
+            Err(_) => fs::write(&fingerprint_path, fingerprint)?,
+        }
+        Ok(checkpoint)
+    }
+
+    /// Fingerprints a prove by the contents of its input `files`, the `params` it is run with and its `check_mode`
+    /// (which the checkpointed circuit is built with), for use with [ProveCheckpoint::open].
+    pub fn fingerprint(
+        files: &[&PathBuf],
+        params: &ParamsKZG<Bn256>,
+        check_mode: CheckMode,
+    ) -> Result<String, Box<dyn Error>> {
+        let mut hasher = Sha256::new();
+        for file in files {
+            let bytes = fs::read(file)?;
+            hasher.update((bytes.len() as u64).to_le_bytes());
+            hasher.update(bytes);
+        }
+        hasher.update(params_hash(params)?);
+        hasher.update([match check_mode {
+            CheckMode::SAFE => 0u8,
+            CheckMode::UNSAFE => 1u8,
+        }]);
+        Ok(hex::encode(hasher.finalize()))
+    }
+
+    fn path(&self, entry: &str) -> PathBuf {
+        self.dir.join(entry)
+    }
+
+    /// Runs a stage: loads its output from the checkpoint if it was completed, otherwise runs `build` and checkpoints
+    /// its output. A checkpoint that fails to load (e.g because it is corrupt) is rebuilt.
+    fn stage<T>(
+        &self,
+        entry: &str,
+        load: impl FnOnce(&PathBuf) -> Result<T, Box<dyn Error>>,
+        build: impl FnOnce() -> Result<T, Box<dyn Error>>,
+        save: impl FnOnce(&PathBuf, &T) -> Result<(), Box<dyn Error>>,
+    ) -> Result<T, Box<dyn Error>> {
+        let path = self.path(entry);
+        if path.exists() {
+            match load(&path) {
+                Ok(value) => {
+                    info!("loaded {} from checkpoint", entry);
+                    return Ok(value);
+                }
+                Err(e) => warn!(
+                    "failed to load {} from checkpoint ({}), rebuilding",
+                    entry, e
+                ),
+            }
+        }
+        let value = build()?;
+        let tmp = self.path(&format!("{}.tmp", entry));
+        save(&tmp, &value)?;
+        fs::rename(&tmp, &path)?;
+        info!("checkpointed {}", entry);
+        Ok(value)
+    }
+
+    /// The witness assignment stage: the circuit with its witness loaded, built by `build` if not checkpointed.
+    pub fn circuit(
+        &self,
+        build: impl FnOnce() -> Result<GraphCircuit, Box<dyn Error>>,
+    ) -> Result<GraphCircuit, Box<dyn Error>> {
+        self.stage(CIRCUIT_ENTRY, GraphCircuit::load, build, |path, circuit| {
+            circuit.save(path)
+        })
+    }
+
+    /// The key generation stage: the proving key for a circuit with `settings`, generated by `build` if not
+    /// checkpointed.
+    pub fn pk(
+        &self,
+        settings: GraphSettings,
+        build: impl FnOnce() -> Result<ProvingKey<G1Affine>, Box<dyn Error>>,
+    ) -> Result<ProvingKey<G1Affine>, Box<dyn Error>> {
+        self.stage(
+            PK_ENTRY,
+            |path| load_pk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(path.clone(), settings),
+            build,
+            |path, pk| Ok(save_pk::<KZGCommitmentScheme<Bn256>>(path, pk)?),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circuit(scale: u32) -> GraphCircuit {
+        let mut circuit = GraphCircuit::default();
+        circuit.settings.run_args.scale = scale;
+        circuit
+    }
+
+    #[test]
+    fn test_checkpoint_resumes_completed_stages() {
+        let dir = tempfile::Builder::new()
+            .prefix("checkpoint")
+            .tempdir()
+            .unwrap();
+        let dir = dir.path().to_path_buf();
+
+        let checkpoint = ProveCheckpoint::open(dir.clone(), "a").unwrap();
+        let built = checkpoint.circuit(|| Ok(circuit(3))).unwrap();
+        assert_eq!(built.settings.run_args.scale, 3);
+        assert!(!dir.join("circuit.bin.tmp").exists());
+
+        // a restarted prove doesn't redo the stage
+        let checkpoint = ProveCheckpoint::open(dir.clone(), "a").unwrap();
+        let resumed = checkpoint
+            .circuit(|| panic!("the stage was checkpointed"))
+            .unwrap();
+        assert_eq!(resumed.settings.run_args.scale, 3);
+
+        // but a different prove does
+        let checkpoint = ProveCheckpoint::open(dir, "b").unwrap();
+        let rebuilt = checkpoint.circuit(|| Ok(circuit(5))).unwrap();
+        assert_eq!(rebuilt.settings.run_args.scale, 5);
+    }
+
+    #[test]
+    fn test_fingerprint_covers_check_mode() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), b"data").unwrap();
+        let file = file.path().to_path_buf();
+        let params = crate::pfsys::srs::gen_srs::<KZGCommitmentScheme<Bn256>>(2);
+
+        let safe = ProveCheckpoint::fingerprint(&[&file], &params, CheckMode::SAFE).unwrap();
+        assert_eq!(
+            ProveCheckpoint::fingerprint(&[&file], &params, CheckMode::SAFE).unwrap(),
+            safe
+        );
+        // a circuit checkpointed in one mode isn't resumed in the other
+        assert_ne!(
+            ProveCheckpoint::fingerprint(&[&file], &params, CheckMode::UNSAFE).unwrap(),
+            safe
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use super::{Snark, TranscriptType};
+use crate::pfsys::evm::aggregation::PoseidonTranscript;
+use halo2_proofs::plonk::{verify_proof, VerifyingKey};
+use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsVerifierKZG};
+use halo2_proofs::poly::kzg::multiopen::VerifierGWC;
+use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+use halo2_proofs::transcript::{
+    Blake2bRead, Challenge255, EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer,
+};
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2curves::CurveAffine;
+use serde::{Deserialize, Serialize};
+use snark_verifier::loader::native::NativeLoader;
+use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
+use std::io::{self, Cursor};
+use std::ops::Deref;
+
+/// An operation of the verifier on its transcript, in the order the verifier performs them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TranscriptEvent {
+    /// A point known to both parties (e.g a commitment of the vk) was absorbed.
+    CommonPoint,
+    /// A scalar known to both parties (e.g an instance evaluation) was absorbed.
+    CommonScalar,
+    /// A point was read from the proof.
+    ReadPoint,
+    /// A scalar was read from the proof.
+    ReadScalar,
+    /// A challenge was squeezed, holding its value.
+    Challenge(String),
+    /// Reading from the proof failed, e.g as a point isn't encoded as the transcript expects or the proof is short.
+    ReadError(String),
+}
+
+/// Wraps a transcript, recording every [TranscriptEvent] of the verifier on it.
+#[derive(Debug)]
+pub struct RecordingTranscript<T> {
+    inner: T,
+    events: Vec<TranscriptEvent>,
+}
+
+impl<T> RecordingTranscript<T> {
+    /// The events recorded so far.
+    pub fn events(&self) -> &[TranscriptEvent] {
+        &self.events
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>> Transcript<C, E>
+    for RecordingTranscript<T>
+{
+    fn squeeze_challenge(&mut self) -> E {
+        let challenge = self.inner.squeeze_challenge();
+        self.events.push(TranscriptEvent::Challenge(format!(
+            "{:?}",
+            challenge.get_scalar()
+        )));
+        challenge
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.events.push(TranscriptEvent::CommonPoint);
+        self.inner.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.events.push(TranscriptEvent::CommonScalar);
+        self.inner.common_scalar(scalar)
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>> TranscriptRead<C, E>
+    for RecordingTranscript<T>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let point = self.inner.read_point();
+        self.events.push(match &point {
+            Ok(_) => TranscriptEvent::ReadPoint,
+            Err(e) => TranscriptEvent::ReadError(e.to_string()),
+        });
+        point
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let scalar = self.inner.read_scalar();
+        self.events.push(match &scalar {
+            Ok(_) => TranscriptEvent::ReadScalar,
+            Err(e) => TranscriptEvent::ReadError(e.to_string()),
+        });
+        scalar
+    }
+}
+
+impl<R: io::Read, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptReadBuffer<R, C, E>>
+    TranscriptReadBuffer<R, C, E> for RecordingTranscript<T>
+{
+    fn init(reader: R) -> Self {
+        RecordingTranscript {
+            inner: T::init(reader),
+            events: vec![],
+        }
+    }
+}
+
+/// The verifier-side transcript of a proof that failed to verify, see [verify_proof_circuit_kzg_debug].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptDiagnostic {
+    /// The transcript type the proof declares.
+    pub transcript_type: TranscriptType,
+    /// The error verifying with the declared transcript failed with.
+    pub error: String,
+    /// The verifier's events on the declared transcript, up to the failure.
+    pub events: Vec<TranscriptEvent>,
+    /// Another transcript type the proof verifies with, if any, in which case the proof is sound but was labelled
+    /// with the wrong transcript.
+    pub verifies_with: Option<TranscriptType>,
+    /// The verifier's events on the transcript the proof verifies with, those the prover produced.
+    pub expected_events: Vec<TranscriptEvent>,
+    /// The index of the first event where the declared and expected transcripts diverge.
+    pub divergence: Option<usize>,
+}
+
+impl TranscriptDiagnostic {
+    /// Whether the failure is only due to the proof declaring another transcript than it was generated with.
+    pub fn is_transcript_mismatch(&self) -> bool {
+        self.verifies_with.is_some()
+    }
+
+    /// The challenges squeezed from the declared transcript.
+    pub fn challenges(&self) -> Vec<&str> {
+        challenges(&self.events)
+    }
+
+    /// The challenges squeezed from the transcript the proof verifies with.
+    pub fn expected_challenges(&self) -> Vec<&str> {
+        challenges(&self.expected_events)
+    }
+}
+
+impl std::fmt::Display for TranscriptDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.verifies_with {
+            Some(t) => write!(
+                f,
+                "transcript mismatch: the proof declares a {:?} transcript but verifies with {:?}, the transcripts diverge at event {}",
+                self.transcript_type,
+                t,
+                self.divergence.unwrap_or(0)
+            ),
+            None => write!(
+                f,
+                "the proof fails to verify with every transcript ({}), after {} events and {} challenges on its declared {:?} transcript",
+                self.error,
+                self.events.len(),
+                self.challenges().len(),
+                self.transcript_type
+            ),
+        }
+    }
+}
+
+fn challenges(events: &[TranscriptEvent]) -> Vec<&str> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            TranscriptEvent::Challenge(c) => Some(c.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn verify_recorded<
+    E: EncodedChallenge<G1Affine>,
+    TR: TranscriptReadBuffer<Cursor<Vec<u8>>, G1Affine, E>,
+>(
+    snark: &Snark<Fr, G1Affine>,
+    params: &ParamsVerifierKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+) -> (Result<(), halo2_proofs::plonk::Error>, Vec<TranscriptEvent>) {
+    let pi_inner = snark
+        .instances
+        .iter()
+        .map(|e| e.deref())
+        .collect::<Vec<&[Fr]>>();
+    let instances: &[&[&[Fr]]] = &[&pi_inner];
+
+    let mut transcript = <RecordingTranscript<TR> as TranscriptReadBuffer<_, G1Affine, E>>::init(
+        Cursor::new(snark.proof.clone()),
+    );
+    let result = verify_proof::<KZGCommitmentScheme<Bn256>, VerifierGWC<'_, Bn256>, E, _, _>(
+        params,
+        vk,
+        SingleStrategy::new(params),
+        instances,
+        &mut transcript,
+    )
+    .map(|_| ());
+    (result, transcript.events)
+}
+
+fn verify_with_transcript(
+    snark: &Snark<Fr, G1Affine>,
+    params: &ParamsVerifierKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    transcript_type: TranscriptType,
+) -> (Result<(), halo2_proofs::plonk::Error>, Vec<TranscriptEvent>) {
+    match transcript_type {
+        TranscriptType::Blake => {
+            verify_recorded::<Challenge255<_>, Blake2bRead<_, _, _>>(snark, params, vk)
+        }
+        TranscriptType::EVM => {
+            verify_recorded::<_, EvmTranscript<G1Affine, _, _, _>>(snark, params, vk)
+        }
+        TranscriptType::Poseidon => {
+            verify_recorded::<_, PoseidonTranscript<NativeLoader, _>>(snark, params, vk)
+        }
+    }
+}
+
+/// Verifies a KZG proof as [super::verify_proof_circuit_kzg] does, recording the verifier's transcript. If the proof
+/// fails to verify it is retried with the other transcript types, and the returned [TranscriptDiagnostic] tells a
+/// proof labelled with the wrong transcript (which verifies with another) from one that fails with every transcript.
+pub fn verify_proof_circuit_kzg_debug(
+    params: &ParamsVerifierKZG<Bn256>,
+    snark: &Snark<Fr, G1Affine>,
+    vk: &VerifyingKey<G1Affine>,
+) -> Result<(), TranscriptDiagnostic> {
+    let (result, events) = verify_with_transcript(snark, params, vk, snark.transcript_type);
+    let error = match result {
+        Ok(()) => return Ok(()),
+        Err(e) => format!("{:?}", e),
+    };
+
+    let mut diagnostic = TranscriptDiagnostic {
+        transcript_type: snark.transcript_type,
+        error,
+        events,
+        verifies_with: None,
+        expected_events: vec![],
+        divergence: None,
+    };
+    for other in [
+        TranscriptType::Blake,
+        TranscriptType::Poseidon,
+        TranscriptType::EVM,
+    ] {
+        if other == snark.transcript_type {
+            continue;
+        }
+        if let (Ok(()), expected_events) = verify_with_transcript(snark, params, vk, other) {
+            diagnostic.divergence = diagnostic
+                .events
+                .iter()
+                .zip(&expected_events)
+                .position(|(a, b)| a != b)
+                .or(Some(diagnostic.events.len().min(expected_events.len())));
+            diagnostic.verifies_with = Some(other);
+            diagnostic.expected_events = expected_events;
+            break;
+        }
+    }
+    Err(diagnostic)
+}
@@ -1,11 +1,14 @@
 use crate::pfsys::evm::YulCode;
-use crate::pfsys::{Snark, SnarkWitness};
+use crate::pfsys::{verify_proof_circuit_kzg, Snark, SnarkWitness};
 use halo2_proofs::circuit::AssignedCell;
 use halo2_proofs::plonk::{self, VerifyingKey};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
     plonk::{Circuit, ConstraintSystem},
-    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{commitment::ParamsKZG, strategy::SingleStrategy},
+    },
 };
 use halo2_wrong_ecc::{
     integer::rns::Rns,
@@ -17,6 +20,7 @@ use halo2_wrong_ecc::{
 };
 use halo2curves::bn256::{Bn256, Fq, Fr, G1Affine};
 use halo2curves::ff::PrimeField;
+use halo2curves::CurveAffine;
 use itertools::Itertools;
 use log::trace;
 use rand::rngs::OsRng;
@@ -24,13 +28,13 @@ use snark_verifier::{
     loader,
     pcs::{
         kzg::{
-            Gwc19, KzgAccumulator, KzgAs, KzgSuccinctVerifyingKey, LimbsEncoding,
+            Gwc19, KzgAccumulator, KzgAs, KzgDecidingKey, KzgSuccinctVerifyingKey, LimbsEncoding,
             LimbsEncodingInstructions,
         },
-        AccumulationScheme, AccumulationSchemeProver,
+        AccumulationDecider, AccumulationScheme, AccumulationSchemeProver,
     },
     system,
-    util::arithmetic::fe_to_limbs,
+    util::arithmetic::{fe_from_limbs, fe_to_limbs},
     verifier::{self, SnarkVerifier},
 };
 use snark_verifier::{loader::evm::EvmLoader, system::halo2::transcript::evm::EvmTranscript};
@@ -76,6 +80,9 @@ pub enum AggregationError {
     /// proof creation errors
     #[error("Failed to create proof")]
     ProofCreate,
+    /// The accumulator carried in the aggregate proof's instances fails the pairing check
+    #[error("the accumulator of the aggregate proof failed the pairing check")]
+    AccumulatorVerification,
 }
 
 /// Aggregate one or more application snarks of the same shape into a KzgAccumulator
@@ -356,6 +363,40 @@ impl Circuit<Fr> for AggregationCircuit {
     }
 }
 
+/// Verifies an aggregate proof in Rust, making the same checks as the EVM verifier generated by
+/// [gen_aggregation_evm_verifier]: the aggregation circuit's proof is verified against `vk`, and the KZG accumulator
+/// its first `4 * LIMBS` instances encode (the deferred pairing check of the aggregated snarks) is decided with a
+/// pairing check, see [AggregationCircuit::accumulator_indices]. Unlike the EVM verifier this accepts any transcript.
+pub fn verify_aggregate(
+    proof: &Snark<Fr, G1Affine>,
+    vk: &VerifyingKey<G1Affine>,
+    params: &ParamsKZG<Bn256>,
+) -> Result<(), AggregationError> {
+    let strategy = SingleStrategy::new(params.verifier_params());
+    verify_proof_circuit_kzg(params.verifier_params(), proof.clone(), vk, strategy)
+        .map_err(|_| AggregationError::KZGProofVerification)?;
+
+    let limbs = match proof.instances.first() {
+        Some(instances) if instances.len() >= 4 * LIMBS => &instances[..4 * LIMBS],
+        _ => return Err(AggregationError::ProofRead),
+    };
+    let [lhs_x, lhs_y, rhs_x, rhs_y]: [Fq; 4] = limbs
+        .chunks(LIMBS)
+        .map(|limbs| fe_from_limbs::<_, _, LIMBS, BITS>(limbs.try_into().unwrap()))
+        .collect_vec()
+        .try_into()
+        .unwrap();
+    let point = |x, y| -> Result<G1Affine, AggregationError> {
+        Option::from(G1Affine::from_xy(x, y)).ok_or(AggregationError::AccumulatorVerification)
+    };
+    let accumulator =
+        KzgAccumulator::<G1Affine, NativeLoader>::new(point(lhs_x, lhs_y)?, point(rhs_x, rhs_y)?);
+
+    let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+    <As as AccumulationDecider<G1Affine, NativeLoader>>::decide(&dk, accumulator)
+        .map_err(|_| AggregationError::AccumulatorVerification)
+}
+
 /// Create aggregation EVM verifier deployment and sol code.
 pub fn gen_aggregation_evm_verifier(
     params: &ParamsKZG<Bn256>,
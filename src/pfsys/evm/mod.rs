@@ -1,5 +1,13 @@
 #[cfg(not(target_arch = "wasm32"))]
-use halo2curves::bn256::{Fr, G1Affine};
+use halo2_proofs::plonk::VerifyingKey;
+#[cfg(not(target_arch = "wasm32"))]
+use halo2_proofs::poly::kzg::commitment::ParamsVerifierKZG;
+#[cfg(not(target_arch = "wasm32"))]
+use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+#[cfg(not(target_arch = "wasm32"))]
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+#[cfg(not(target_arch = "wasm32"))]
+use halo2curves::ff::PrimeField;
 #[cfg(not(target_arch = "wasm32"))]
 use log::debug;
 use serde::{Deserialize, Serialize};
@@ -14,7 +22,7 @@ use std::path::PathBuf;
 use thiserror::Error;
 
 #[cfg(not(target_arch = "wasm32"))]
-use super::Snark;
+use super::{verify_proof_circuit_kzg, Snark, TranscriptType};
 
 /// Aggregate proof generation for EVM
 pub mod aggregation;
@@ -39,6 +47,9 @@ pub enum EvmVerificationError {
     /// EVM verify errors
     #[error("evm deployment failed")]
     Deploy,
+    /// If calldata doesn't follow the layout the Solidity verifier expects
+    #[error("malformed verifier calldata: {0}")]
+    InvalidCalldata(String),
 }
 /// YulCode type which is just an alias of string
 pub type YulCode = String;
@@ -101,3 +112,92 @@ pub fn evm_verify(
     }
     Ok(())
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+/// Splits calldata laid out for the Solidity verifier (see [encode_calldata]) back into its instances and proof.
+/// `num_instances` is the number of instances in each instance column, as the calldata itself doesn't record it.
+pub fn decode_calldata(
+    calldata: &[u8],
+    num_instances: &[usize],
+) -> Result<(Vec<Vec<Fr>>, Vec<u8>), EvmVerificationError> {
+    let total: usize = num_instances.iter().sum();
+    if calldata.len() < total * 32 {
+        return Err(EvmVerificationError::InvalidCalldata(format!(
+            "expected at least {} bytes for {} instances but got {}",
+            total * 32,
+            total,
+            calldata.len()
+        )));
+    }
+
+    let mut words = calldata.chunks_exact(32);
+    let mut instances = vec![];
+    for (col, num) in num_instances.iter().enumerate() {
+        let mut column = vec![];
+        for row in 0..*num {
+            // instances are encoded as big-endian words whereas the field repr is little-endian
+            let mut repr = <Fr as PrimeField>::Repr::default();
+            repr.as_mut().copy_from_slice(words.next().unwrap());
+            repr.as_mut().reverse();
+            let instance = Option::<Fr>::from(Fr::from_repr(repr)).ok_or_else(|| {
+                EvmVerificationError::InvalidCalldata(format!(
+                    "instance {} of column {} isn't a canonical field element",
+                    row, col
+                ))
+            })?;
+            column.push(instance);
+        }
+        instances.push(column);
+    }
+
+    Ok((instances, calldata[total * 32..].to_vec()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+/// Verifies a proof from the calldata that would be sent to the Solidity verifier, without executing the verifier
+/// itself. The calldata is decoded with [decode_calldata] and the proof verified natively as an EVM transcript proof.
+pub fn verify_from_evm_calldata(
+    calldata: &[u8],
+    num_instances: &[usize],
+    vk: &VerifyingKey<G1Affine>,
+    params: &ParamsVerifierKZG<Bn256>,
+) -> Result<(), Box<dyn Error>> {
+    let (instances, proof) = decode_calldata(calldata, num_instances)?;
+    let snark = Snark {
+        protocol: None,
+        instances,
+        proof,
+        transcript_type: TranscriptType::EVM,
+        metadata: Default::default(),
+        public_input_hash: None,
+        elided_instances: vec![],
+    };
+    let strategy = SingleStrategy::new(params);
+    verify_proof_circuit_kzg(params, snark, vk, strategy)?;
+    Ok(())
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calldata_roundtrip() {
+        let instances = vec![
+            vec![Fr::from(1), Fr::from(2), -Fr::from(3)],
+            vec![Fr::from(u64::MAX)],
+        ];
+        let proof = vec![7u8; 100];
+        let calldata = encode_calldata(&instances, &proof);
+
+        let (decoded_instances, decoded_proof) = decode_calldata(&calldata, &[3, 1]).unwrap();
+        assert_eq!(decoded_instances, instances);
+        assert_eq!(decoded_proof, proof);
+
+        assert!(decode_calldata(&calldata[..64], &[3, 1]).is_err());
+        // the modulus isn't a canonical field element
+        let mut invalid = calldata.clone();
+        invalid[..32].copy_from_slice(&[0xff; 32]);
+        assert!(decode_calldata(&invalid, &[3, 1]).is_err());
+    }
+}
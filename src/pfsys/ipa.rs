@@ -0,0 +1,131 @@
+use super::{create_proof_circuit, verify_proof_circuit, Snark, TranscriptType};
+use crate::circuit::CheckMode;
+use halo2_proofs::plonk::{Circuit, ProvingKey, VerifyingKey};
+use halo2_proofs::poly::commitment::ParamsProver;
+use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+use halo2_proofs::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+use halo2_proofs::poly::ipa::strategy::SingleStrategy;
+use halo2_proofs::transcript::{Blake2bRead, Blake2bWrite, Challenge255};
+use halo2curves::bn256::{Fr, G1Affine};
+use log::info;
+use std::error::Error;
+
+/// The commitment scheme of the IPA proving path. The circuits are defined over the scalar field of BN254, so the
+/// inner product argument commits with BN254's G1, which it uses as a plain prime order group: no pairing, and hence
+/// no structured reference string, is involved.
+pub type IPAScheme = IPACommitmentScheme<G1Affine>;
+
+/// The params of the IPA proving path for circuits of `2^logrows` rows. They are derived deterministically by hashing
+/// to the curve, such that the prover and the verifier each derive the same params without any setup ceremony, and
+/// nothing has to be downloaded or trusted.
+pub fn gen_ipa_params(logrows: u32) -> ParamsIPA<G1Affine> {
+    info!("deriving ipa params for 2^{} rows", logrows);
+    ParamsIPA::<G1Affine>::new(logrows)
+}
+
+/// Proves `circuit` with the IPA commitment scheme. Unlike KZG proofs, whose size and verification cost don't depend
+/// on the circuit, an IPA proof grows with the logarithm of the number of rows (two group elements per round of the
+/// argument) and verifying it takes a multi-scalar multiplication linear in the number of rows, so it is slower to
+/// verify by orders of magnitude for large circuits, and isn't verifiable on-chain or aggregated by ezkl.
+pub fn create_proof_circuit_ipa<C: Circuit<Fr>>(
+    circuit: C,
+    params: &ParamsIPA<G1Affine>,
+    public_inputs: Vec<Vec<Fr>>,
+    pk: &ProvingKey<G1Affine>,
+    check_mode: CheckMode,
+) -> Result<Snark<Fr, G1Affine>, Box<dyn Error>> {
+    create_proof_circuit::<
+        IPAScheme,
+        Fr,
+        _,
+        ProverIPA<_>,
+        VerifierIPA<_>,
+        _,
+        _,
+        Blake2bWrite<_, _, Challenge255<_>>,
+        Blake2bRead<_, _, Challenge255<_>>,
+    >(
+        circuit,
+        public_inputs,
+        params,
+        pk,
+        SingleStrategy::new(params),
+        check_mode,
+        TranscriptType::Blake,
+    )
+}
+
+/// Verifies a proof generated with [create_proof_circuit_ipa]. The verifier params are the prover params, see
+/// [gen_ipa_params].
+pub fn verify_proof_circuit_ipa(
+    params: &ParamsIPA<G1Affine>,
+    proof: &Snark<Fr, G1Affine>,
+    vk: &VerifyingKey<G1Affine>,
+) -> Result<(), Box<dyn Error>> {
+    if proof.transcript_type != TranscriptType::Blake {
+        return Err(format!(
+            "ipa proofs use the blake transcript, the proof is labelled {:?}",
+            proof.transcript_type
+        )
+        .into());
+    }
+    let verifier_params = params.verifier_params();
+    verify_proof_circuit::<Fr, VerifierIPA<_>, _, _, Challenge255<_>, Blake2bRead<_, _, _>>(
+        proof,
+        verifier_params,
+        vk,
+        SingleStrategy::new(verifier_params),
+    )
+    .map_err(Box::<dyn Error>::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::tests::{relu_circuit, relu_run_args};
+    use crate::graph::{GraphCircuit, Visibility};
+    use crate::pfsys::create_keys;
+    use crate::tensor::Tensor;
+    use crate::RunArgs;
+    use halo2_proofs::poly::commitment::Params;
+
+    #[test]
+    fn test_ipa_prove_and_verify() {
+        let run_args = RunArgs {
+            input_visibility: Visibility::Public,
+            ..relu_run_args()
+        };
+        let mut circuit = relu_circuit(&run_args);
+
+        // the params are derived again, as a verifier with no access to the prover's would
+        let params = gen_ipa_params(run_args.logrows);
+        let bytes = |params: &ParamsIPA<G1Affine>| {
+            let mut bytes = vec![];
+            params.write(&mut bytes).unwrap();
+            bytes
+        };
+        assert_eq!(bytes(&gen_ipa_params(run_args.logrows)), bytes(&params));
+        let pk = create_keys::<IPAScheme, Fr, GraphCircuit>(&circuit, &params).unwrap();
+
+        let shape = circuit.model.graph.input_shapes()[0].clone();
+        let values = (0..shape.iter().product::<usize>() as u64)
+            .map(|i| match i % 2 {
+                0 => Fr::from(i + 1),
+                _ => -Fr::from(i + 1),
+            })
+            .collect::<Vec<_>>();
+        let input = Tensor::new(Some(&values), &shape).unwrap();
+        let witness = circuit.forward(&[input]).unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+
+        let proof = create_proof_circuit_ipa(circuit, &params, public_inputs, &pk, CheckMode::SAFE)
+            .unwrap();
+        let verifier_params = gen_ipa_params(run_args.logrows);
+        verify_proof_circuit_ipa(&verifier_params, &proof, pk.get_vk()).unwrap();
+
+        let mut tampered = proof;
+        tampered.instances[0][0] += Fr::from(1);
+        assert!(verify_proof_circuit_ipa(&verifier_params, &tampered, pk.get_vk()).is_err());
+    }
+}
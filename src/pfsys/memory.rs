@@ -0,0 +1,76 @@
+/// How often [measure_peak_memory] samples the resident memory of the process.
+#[cfg(not(target_arch = "wasm32"))]
+const SAMPLE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// The resident memory (RSS) of the process in bytes, where the platform supports querying it (currently linux).
+pub fn current_rss() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb * 1024)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Runs `f`, sampling the resident memory of the process while it runs, and returns its output along with the peak
+/// resident memory (in bytes) seen. The peak is `None` where the platform doesn't support querying it. As the memory is
+/// sampled, allocations that are freed between samples may be missed, so the peak is a lower bound on the true peak.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn measure_peak_memory<T>(f: impl FnOnce() -> T) -> (T, Option<u64>) {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    let initial = match current_rss() {
+        Some(rss) => rss,
+        None => return (f(), None),
+    };
+
+    let done = Arc::new(AtomicBool::new(false));
+    let sampler = {
+        let done = done.clone();
+        std::thread::spawn(move || {
+            let mut peak = initial;
+            while !done.load(Ordering::Relaxed) {
+                peak = peak.max(current_rss().unwrap_or(0));
+                std::thread::sleep(SAMPLE_INTERVAL);
+            }
+            peak
+        })
+    };
+
+    let output = f();
+    let at_end = current_rss().unwrap_or(0);
+    done.store(true, Ordering::Relaxed);
+    let peak = sampler.join().unwrap_or(initial).max(at_end);
+    (output, Some(peak))
+}
+
+/// Runs `f`. Memory can't be queried in wasm, so the peak is always `None`.
+#[cfg(target_arch = "wasm32")]
+pub fn measure_peak_memory<T>(f: impl FnOnce() -> T) -> (T, Option<u64>) {
+    (f(), None)
+}
+
+#[cfg(test)]
+#[cfg(target_os = "linux")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_peak_memory() {
+        let size = 64 << 20;
+        let (len, peak) = measure_peak_memory(|| {
+            let buffer = vec![1u8; size];
+            std::thread::sleep(SAMPLE_INTERVAL * 5);
+            buffer.iter().map(|x| *x as usize).sum::<usize>()
+        });
+        assert_eq!(len, size);
+        // the buffer was resident while sampled
+        assert!(peak.unwrap() >= size as u64);
+    }
+}
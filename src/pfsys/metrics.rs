@@ -0,0 +1,231 @@
+use super::{KeygenResult, ProofResult};
+use halo2curves::ff::PrimeField;
+use halo2curves::serde::SerdeObject;
+use halo2curves::CurveAffine;
+use log::info;
+#[cfg(feature = "prometheus")]
+use prometheus::{Histogram, HistogramOpts, IntCounter, IntGauge, Registry};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The metrics of generating a proof, gathered from the [KeygenResult] and [ProofResult] of its keygen and prove
+/// (and the time taken to verify it). The stages that weren't run (e.g keygen, when the keys were loaded) are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProofMetrics {
+    /// The log2 of the number of rows of the circuit.
+    pub logrows: u32,
+    /// The time taken to generate the verifying and proving keys.
+    pub keygen_time: Option<Duration>,
+    /// The time taken to create (and in safe mode check) the proof.
+    pub proof_time: Option<Duration>,
+    /// The time taken to verify the proof.
+    pub verify_time: Option<Duration>,
+    /// The size of the proof in bytes, excluding its instances.
+    pub proof_size: Option<usize>,
+    /// The peak resident memory (in bytes) of the process over keygen and proving, see
+    /// [super::memory::measure_peak_memory].
+    pub peak_memory: Option<u64>,
+}
+
+impl ProofMetrics {
+    /// Empty metrics of a circuit of `2^logrows` rows.
+    pub fn new(logrows: u32) -> Self {
+        ProofMetrics {
+            logrows,
+            ..Default::default()
+        }
+    }
+
+    /// Records the keygen time and peak memory of `keygen`.
+    pub fn with_keygen<C: CurveAffine>(mut self, keygen: &KeygenResult<C>) -> Self {
+        self.keygen_time = Some(keygen.vk_time + keygen.pk_time);
+        self.record_peak_memory(keygen.peak_memory);
+        self
+    }
+
+    /// Records the proof time, proof size and peak memory of `proof`.
+    pub fn with_proof<F: PrimeField + SerdeObject, C: CurveAffine>(
+        mut self,
+        proof: &ProofResult<F, C>,
+    ) -> Self {
+        self.proof_time = Some(proof.proof_time);
+        self.proof_size = Some(proof.snark.proof.len());
+        self.record_peak_memory(proof.peak_memory);
+        self
+    }
+
+    /// Records the time taken to verify the proof.
+    pub fn with_verify_time(mut self, verify_time: Duration) -> Self {
+        self.verify_time = Some(verify_time);
+        self
+    }
+
+    /// Logs the metrics, and with the `prometheus` feature records them with the process' default registry (see
+    /// [prometheus::default_registry], registering the metrics on first use), which a host running ezkl serves to
+    /// be scraped.
+    pub fn report(&self) {
+        info!(
+            "metrics: {}",
+            serde_json::to_string(self).unwrap_or_else(|e| e.to_string())
+        );
+        #[cfg(feature = "prometheus")]
+        {
+            static DEFAULT: std::sync::OnceLock<Option<PrometheusMetrics>> =
+                std::sync::OnceLock::new();
+            let exporter = DEFAULT.get_or_init(|| {
+                PrometheusMetrics::register(prometheus::default_registry())
+                    .map_err(|e| log::warn!("failed to register the prometheus metrics: {}", e))
+                    .ok()
+            });
+            if let Some(exporter) = exporter {
+                exporter.observe(self);
+            }
+        }
+    }
+
+    fn record_peak_memory(&mut self, peak_memory: Option<u64>) {
+        self.peak_memory = match (self.peak_memory, peak_memory) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+/// The [ProofMetrics] of the proofs generated by a process, as Prometheus metrics registered with a [Registry] the
+/// host exposes to be scraped. Times are histograms in seconds, such that a fleet of provers can be aggregated over;
+/// the proof size, peak memory and logrows are gauges of the last proof observed.
+#[cfg(feature = "prometheus")]
+#[derive(Clone, Debug)]
+pub struct PrometheusMetrics {
+    proofs: IntCounter,
+    keygen_seconds: Histogram,
+    proof_seconds: Histogram,
+    verify_seconds: Histogram,
+    proof_size_bytes: IntGauge,
+    peak_memory_bytes: IntGauge,
+    logrows: IntGauge,
+}
+
+#[cfg(feature = "prometheus")]
+impl PrometheusMetrics {
+    /// Registers the metrics (prefixed with `ezkl_`) with `registry`. Fails if `registry` already holds metrics of
+    /// the same names, e.g if they were registered before.
+    pub fn register(registry: &Registry) -> Result<Self, prometheus::Error> {
+        // from 10ms to ~5.5 hours
+        let seconds = |name: &str, help: &str| {
+            Histogram::with_opts(
+                HistogramOpts::new(name, help)
+                    .buckets(prometheus::exponential_buckets(0.01, 2.0, 21)?),
+            )
+        };
+        let metrics = PrometheusMetrics {
+            proofs: IntCounter::new("ezkl_proofs_total", "number of proofs observed")?,
+            keygen_seconds: seconds("ezkl_keygen_seconds", "time taken to generate the keys")?,
+            proof_seconds: seconds("ezkl_proof_seconds", "time taken to create a proof")?,
+            verify_seconds: seconds("ezkl_verify_seconds", "time taken to verify a proof")?,
+            proof_size_bytes: IntGauge::new(
+                "ezkl_proof_size_bytes",
+                "size of the last proof in bytes",
+            )?,
+            peak_memory_bytes: IntGauge::new(
+                "ezkl_peak_memory_bytes",
+                "peak resident memory in bytes while generating the last proof",
+            )?,
+            logrows: IntGauge::new(
+                "ezkl_logrows",
+                "log2 of the number of rows of the last proof's circuit",
+            )?,
+        };
+        registry.register(Box::new(metrics.proofs.clone()))?;
+        registry.register(Box::new(metrics.keygen_seconds.clone()))?;
+        registry.register(Box::new(metrics.proof_seconds.clone()))?;
+        registry.register(Box::new(metrics.verify_seconds.clone()))?;
+        registry.register(Box::new(metrics.proof_size_bytes.clone()))?;
+        registry.register(Box::new(metrics.peak_memory_bytes.clone()))?;
+        registry.register(Box::new(metrics.logrows.clone()))?;
+        Ok(metrics)
+    }
+
+    /// Records `metrics`. The stages that weren't run aren't observed, and the gauges of those keep their value. Only
+    /// metrics with a proof time (e.g not those of a keygen or verification alone) count as a proof.
+    pub fn observe(&self, metrics: &ProofMetrics) {
+        if metrics.proof_time.is_some() {
+            self.proofs.inc();
+        }
+        self.logrows.set(metrics.logrows as i64);
+        if let Some(t) = metrics.keygen_time {
+            self.keygen_seconds.observe(t.as_secs_f64());
+        }
+        if let Some(t) = metrics.proof_time {
+            self.proof_seconds.observe(t.as_secs_f64());
+        }
+        if let Some(t) = metrics.verify_time {
+            self.verify_seconds.observe(t.as_secs_f64());
+        }
+        if let Some(size) = metrics.proof_size {
+            self.proof_size_bytes.set(size as i64);
+        }
+        if let Some(peak_memory) = metrics.peak_memory {
+            self.peak_memory_bytes.set(peak_memory as i64);
+        }
+    }
+}
+
+/// The metrics of `registry` in the Prometheus text exposition format, as served to a scraper.
+#[cfg(feature = "prometheus")]
+pub fn encode_metrics(registry: &Registry) -> Result<String, prometheus::Error> {
+    use prometheus::Encoder;
+
+    let mut buffer = vec![];
+    prometheus::TextEncoder::new().encode(&registry.gather(), &mut buffer)?;
+    String::from_utf8(buffer).map_err(|e| prometheus::Error::Msg(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proof_metrics_peak_memory() {
+        let mut metrics = ProofMetrics::new(17).with_verify_time(Duration::from_millis(5));
+        metrics.record_peak_memory(None);
+        assert_eq!(metrics.peak_memory, None);
+        metrics.record_peak_memory(Some(2 << 20));
+        metrics.record_peak_memory(Some(1 << 20));
+        metrics.record_peak_memory(None);
+        // the peak over every stage
+        assert_eq!(metrics.peak_memory, Some(2 << 20));
+        assert_eq!(metrics.verify_time, Some(Duration::from_millis(5)));
+        assert_eq!(metrics.keygen_time, None);
+    }
+
+    #[test]
+    #[cfg(feature = "prometheus")]
+    fn test_prometheus_metrics() {
+        let registry = Registry::new();
+        let exporter = PrometheusMetrics::register(&registry).unwrap();
+        // the metrics can only be registered once with a registry
+        assert!(PrometheusMetrics::register(&registry).is_err());
+
+        let metrics = ProofMetrics {
+            logrows: 17,
+            keygen_time: None,
+            proof_time: Some(Duration::from_millis(1500)),
+            verify_time: Some(Duration::from_millis(20)),
+            proof_size: Some(2048),
+            peak_memory: Some(1 << 30),
+        };
+        exporter.observe(&metrics);
+        exporter.observe(&ProofMetrics::new(18));
+
+        let text = encode_metrics(&registry).unwrap();
+        // the second metrics are of no proof
+        assert!(text.contains("ezkl_proofs_total 1"));
+        assert!(text.contains("ezkl_proof_seconds_count 1"));
+        assert!(text.contains("ezkl_proof_seconds_sum 1.5"));
+        assert!(text.contains("ezkl_keygen_seconds_count 0"));
+        assert!(text.contains("ezkl_proof_size_bytes 2048"));
+        assert!(text.contains(&format!("ezkl_peak_memory_bytes {}", 1u64 << 30)));
+        assert!(text.contains("ezkl_logrows 18"));
+    }
+}
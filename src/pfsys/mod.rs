@@ -4,7 +4,43 @@ pub mod evm;
 /// SRS generation, processing, verification and downloading
 pub mod srs;
 
+/// Self-describing proof bundles holding a proof, its vk and settings, and the hash of its params
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bundle;
+
+/// Split proofs holding the bundles of two consecutive parts of a model and the boundary value between them
+#[cfg(not(target_arch = "wasm32"))]
+pub mod split;
+
+/// Proving a model on the public outputs of a verified proof of another model, chaining their proofs
+#[cfg(not(target_arch = "wasm32"))]
+pub mod chain;
+
+/// Proving with the inner product argument commitment scheme, whose params need no trusted setup
+pub mod ipa;
+
+/// Checkpointing of the completed stages of a prove, such that a restarted prove resumes from the last checkpoint
+#[cfg(not(target_arch = "wasm32"))]
+pub mod checkpoint;
+
+/// Loading of params and verifying keys from HTTP(S) URLs, checked against a checksum header
+#[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+pub mod remote;
+
+/// Verification recording the verifier's transcript, to tell a proof labelled with the wrong transcript from an
+/// invalid one
+pub mod diagnostics;
+
+/// Measurement of the peak resident memory of keygen and proving, for sizing the machines proofs are scheduled on
+pub mod memory;
+
+/// The metrics of keygen, proving and verifying, and (with the `prometheus` feature) their export to Prometheus
+pub mod metrics;
+
 use crate::circuit::CheckMode;
+use crate::graph::input::FileSourceInner;
+use crate::graph::modules::PublicInputHash;
+use crate::graph::{GraphSettings, InstanceColumn};
 use crate::pfsys::evm::aggregation::PoseidonTranscript;
 use crate::tensor::TensorType;
 use clap::ValueEnum;
@@ -13,8 +49,9 @@ use halo2_proofs::plonk::{
     create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ProvingKey, VerifyingKey,
 };
 use halo2_proofs::poly::commitment::{CommitmentScheme, Params, ParamsProver, Prover, Verifier};
-use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG, ParamsVerifierKZG};
 use halo2_proofs::poly::kzg::multiopen::{ProverGWC, VerifierGWC};
+use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
 use halo2_proofs::poly::VerificationStrategy;
 use halo2_proofs::transcript::{
     Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, TranscriptReadBuffer,
@@ -32,11 +69,14 @@ use snark_verifier::loader::native::NativeLoader;
 use snark_verifier::system::halo2::transcript::evm::EvmTranscript;
 use snark_verifier::system::halo2::{compile, Config};
 use snark_verifier::verifier::plonk::PlonkProtocol;
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fs::File;
-use std::io::{self, BufReader, BufWriter, Cursor, Write};
+use std::io::{self, BufReader, BufWriter, Cursor, Read, Seek, Write};
 use std::ops::Deref;
 use std::path::PathBuf;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
 use thiserror::Error as thisError;
 
 use halo2curves::bn256::{Bn256, Fr, G1Affine};
@@ -47,6 +87,195 @@ pub enum PfSysError {
     /// Packing exponent is too large
     #[error("largest packing exponent exceeds max. try reducing the scale")]
     PackingExponent,
+    /// The checksum or length in a file's header doesn't match its payload
+    #[error("corrupt or truncated file: {0}")]
+    CorruptFile(String),
+    /// The file was written with a newer header format
+    #[error("unsupported file format version {0} (expected at most {1})")]
+    FileFormatVersion(u32, u32),
+    /// The contents of a proof bundle don't agree with each other or with the supplied params
+    #[error("inconsistent bundle: {0}")]
+    InconsistentBundle(String),
+    /// The parts of a split proof don't share the boundary value between them
+    #[error("split proof boundary mismatch: {0}")]
+    SplitBoundary(String),
+    /// Float public inputs can't be quantized into the instances of a proof
+    #[error("float public inputs: {0}")]
+    FloatPublicInputs(String),
+    /// The public inputs of a proof can't be passed to a predicate over integers
+    #[error("predicate inputs: {0}")]
+    PredicateInputs(String),
+}
+
+/// Magic bytes prefixed to params and verifying key files written by ezkl.
+pub const FILE_MAGIC: &[u8; 8] = b"EZKLFILE";
+/// Version of the header written after [FILE_MAGIC].
+pub const FILE_FORMAT_VERSION: u32 = 1;
+// magic, version (u32), payload length (u64), crc32 (u32)
+const FILE_HEADER_LEN: usize = 8 + 4 + 8 + 4;
+
+/// The CRC32 (IEEE) checksum of `bytes`.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !crc32_update(0xffff_ffff, bytes)
+}
+
+// folds `bytes` into the (pre-inversion) crc32 state `crc`, such that a payload can be checksummed as it's streamed
+fn crc32_update(mut crc: u32, bytes: &[u8]) -> u32 {
+    for b in bytes {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    crc
+}
+
+/// Prepends a header holding [FILE_MAGIC], [FILE_FORMAT_VERSION], the payload length and its [crc32] checksum.
+pub fn add_checksum_header(payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(FILE_HEADER_LEN + payload.len());
+    bytes.extend_from_slice(&checksum_header(payload.len() as u64, crc32(payload)));
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+fn checksum_header(len: u64, checksum: u32) -> [u8; FILE_HEADER_LEN] {
+    let mut header = [0u8; FILE_HEADER_LEN];
+    header[..8].copy_from_slice(FILE_MAGIC);
+    header[8..12].copy_from_slice(&FILE_FORMAT_VERSION.to_le_bytes());
+    header[12..20].copy_from_slice(&len.to_le_bytes());
+    header[20..24].copy_from_slice(&checksum.to_le_bytes());
+    header
+}
+
+// parses the header after [FILE_MAGIC] into the payload length and checksum
+fn parse_checksum_header(header: &[u8]) -> Result<(u64, u32), PfSysError> {
+    if header.len() < FILE_HEADER_LEN {
+        return Err(PfSysError::CorruptFile("header is truncated".to_string()));
+    }
+    let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    if version > FILE_FORMAT_VERSION {
+        return Err(PfSysError::FileFormatVersion(version, FILE_FORMAT_VERSION));
+    }
+    let len = u64::from_le_bytes(header[12..20].try_into().unwrap());
+    let checksum = u32::from_le_bytes(header[20..24].try_into().unwrap());
+    Ok((len, checksum))
+}
+
+fn check_payload(len: u64, checksum: u32, found_len: u64, found: u32) -> Result<(), PfSysError> {
+    if found_len != len {
+        return Err(PfSysError::CorruptFile(format!(
+            "expected {} bytes of payload, found {}",
+            len, found_len
+        )));
+    }
+    if found != checksum {
+        return Err(PfSysError::CorruptFile("checksum mismatch".to_string()));
+    }
+    Ok(())
+}
+
+/// Verifies and strips the header written by [add_checksum_header], returning the payload.
+/// Bytes that don't start with [FILE_MAGIC] (e.g. files written by older versions of ezkl or downloaded SRS) are returned as is.
+pub fn strip_checksum_header(bytes: &[u8]) -> Result<&[u8], PfSysError> {
+    if !bytes.starts_with(FILE_MAGIC) {
+        return Ok(bytes);
+    }
+    let (len, checksum) = parse_checksum_header(bytes)?;
+    let payload = &bytes[FILE_HEADER_LEN..];
+    check_payload(len, checksum, payload.len() as u64, crc32(payload))?;
+    Ok(payload)
+}
+
+/// A [io::Read] or [io::Write] that checksums and counts the bytes streamed through it.
+struct Checksummed<T> {
+    inner: T,
+    crc: u32,
+    len: u64,
+}
+
+impl<T> Checksummed<T> {
+    fn new(inner: T) -> Self {
+        Checksummed {
+            inner,
+            crc: 0xffff_ffff,
+            len: 0,
+        }
+    }
+
+    fn checksum(&self) -> u32 {
+        !self.crc
+    }
+}
+
+impl<R: io::Read> io::Read for Checksummed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.crc = crc32_update(self.crc, &buf[..n]);
+        self.len += n as u64;
+        Ok(n)
+    }
+}
+
+impl<W: io::Write> io::Write for Checksummed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.crc = crc32_update(self.crc, &buf[..n]);
+        self.len += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Opens the file at `path` and streams its payload to `read`, verifying (and skipping) its checksum header, if any.
+/// Files without a header are streamed as is. The checksum is only known once the payload has been read, so a
+/// corrupt payload that `read` fails on is reported as corrupt rather than as the error of `read`.
+pub fn read_checksummed<T>(
+    path: &PathBuf,
+    read: impl FnOnce(&mut dyn io::Read) -> io::Result<T>,
+) -> Result<T, Box<dyn Error>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut magic = vec![];
+    (&mut reader)
+        .take(FILE_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+    if magic != FILE_MAGIC {
+        // not written by ezkl, put back the bytes we've peeked at
+        return Ok(read(&mut Cursor::new(magic).chain(reader))?);
+    }
+
+    let mut header = magic;
+    (&mut reader)
+        .take((FILE_HEADER_LEN - FILE_MAGIC.len()) as u64)
+        .read_to_end(&mut header)?;
+    let (len, checksum) = parse_checksum_header(&header)?;
+    let mut payload = Checksummed::new(reader);
+    let res = read(&mut (&mut payload).take(len));
+    // drain whatever `read` left over (including any trailing bytes) to checksum the full payload
+    io::copy(&mut payload, &mut io::sink())?;
+    check_payload(len, checksum, payload.len, payload.checksum())?;
+    Ok(res?)
+}
+
+/// Creates the file at `path` and streams the payload written by `write` to it, prefixed with a checksum header.
+pub fn write_checksummed(
+    path: &PathBuf,
+    write: impl FnOnce(&mut dyn io::Write) -> io::Result<()>,
+) -> Result<(), io::Error> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    // the length and checksum are only known once the payload has been written, so they're patched in after
+    writer.write_all(&[0u8; FILE_HEADER_LEN])?;
+    let mut payload = Checksummed::new(writer);
+    write(&mut payload)?;
+    let (len, checksum) = (payload.len, payload.checksum());
+    let mut writer = payload.inner;
+    writer.seek(io::SeekFrom::Start(0))?;
+    writer.write_all(&checksum_header(len, checksum))?;
+    writer.flush()?;
+    Ok(())
 }
 
 #[allow(missing_docs)]
@@ -68,6 +297,42 @@ impl ToPyObject for TranscriptType {
     }
 }
 
+/// The encoding a [Snark] is saved in
+#[derive(ValueEnum, Copy, Clone, Default, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ProofFormat {
+    /// human readable json
+    #[default]
+    Json,
+    /// compact binary encoding
+    Bincode,
+    /// compact and self-describing, for verifiers that ship a CBOR parser but no JSON
+    Cbor,
+}
+
+impl ProofFormat {
+    /// Guesses the format of serialized proof `bytes`: json objects open with `{`, CBOR maps with a
+    /// major type 5 head byte (`0xa0..=0xbf`), and anything else is treated as bincode.
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') => ProofFormat::Json,
+            Some(0xa0..=0xbf) => ProofFormat::Cbor,
+            _ => ProofFormat::Bincode,
+        }
+    }
+
+    /// The version of the layout of proof `bytes` serialized in this format: bincode proofs record it after
+    /// [BINCODE_PROOF_MAGIC], whereas json and CBOR proofs are self-describing and unversioned.
+    pub fn version(&self, bytes: &[u8]) -> Option<u32> {
+        match self {
+            ProofFormat::Bincode => bytes
+                .strip_prefix(BINCODE_PROOF_MAGIC.as_slice())
+                .and_then(|versioned| versioned.get(..4))
+                .map(|version| u32::from_le_bytes(version.try_into().unwrap())),
+            ProofFormat::Json | ProofFormat::Cbor => None,
+        }
+    }
+}
+
 /// converts fp into `Vec<u64>` in Montgomery form
 pub fn field_to_vecu64_montgomery<F: PrimeField + SerdeObject + Serialize>(fp: &F) -> [u64; 4] {
     let repr = serde_json::to_string(&fp).unwrap();
@@ -99,8 +364,28 @@ where
     pub proof: Vec<u8>,
     /// transcript type
     pub transcript_type: TranscriptType,
+    /// arbitrary key-value metadata carried alongside the proof (e.g a request id or model version), it isn't
+    /// committed to by the proof and plays no part in verification. JSON and CBOR proofs saved without it load with
+    /// empty metadata.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// the hash function (and its parameters) the snark's hashed public inputs were computed with, if any are
+    /// hashed, such that the verifier can check it reconstructs the same digests
+    #[serde(default)]
+    pub public_input_hash: Option<PublicInputHash>,
+    /// the instance columns left empty in the serialized snark as the verifier can recompute them, these have to be
+    /// restored before verifying, see [crate::graph::GraphCircuit::elide_outputs]
+    #[serde(default)]
+    pub elided_instances: Vec<usize>,
 }
 
+/// Magic bytes prefixed to bincode proofs, followed by the [BINCODE_PROOF_VERSION] (a little-endian u32) of the
+/// layout they're encoded with. Unlike JSON and CBOR, bincode isn't self-describing, so fields added to [Snark]
+/// can't be defaulted when loading older proofs and the layout has to be versioned instead.
+pub const BINCODE_PROOF_MAGIC: &[u8; 8] = b"EZKLPROF";
+/// Version of the bincode layout of [Snark] written after [BINCODE_PROOF_MAGIC].
+pub const BINCODE_PROOF_VERSION: u32 = 1;
+
 #[cfg(feature = "python-bindings")]
 use pyo3::{types::PyDict, PyObject, Python, ToPyObject};
 #[cfg(feature = "python-bindings")]
@@ -121,6 +406,12 @@ where
         dict.set_item("proof", &hex_proof).unwrap();
         dict.set_item("transcript_type", &self.transcript_type)
             .unwrap();
+        dict.set_item("metadata", &self.metadata).unwrap();
+        // such that the snark handed back matches the one saved, elided columns and all
+        dict.set_item("public_input_hash", &self.public_input_hash)
+            .unwrap();
+        dict.set_item("elided_instances", &self.elided_instances)
+            .unwrap();
         dict.to_object(py)
     }
 }
@@ -145,18 +436,31 @@ where
             instances,
             proof,
             transcript_type,
+            metadata: BTreeMap::new(),
+            public_input_hash: None,
+            elided_instances: vec![],
         }
     }
 
     /// Saves the Proof to a specified `proof_path`.
     pub fn save(&self, proof_path: &PathBuf) -> Result<(), Box<dyn Error>> {
+        self.save_with_format(proof_path, ProofFormat::Json)
+    }
+
+    /// Saves the Proof to a specified `proof_path` in the given [ProofFormat].
+    pub fn save_with_format(
+        &self,
+        proof_path: &PathBuf,
+        format: ProofFormat,
+    ) -> Result<(), Box<dyn Error>> {
         let file = std::fs::File::create(proof_path)?;
         let mut writer = BufWriter::new(file);
-        serde_json::to_writer(&mut writer, &self)?;
+        writer.write_all(&self.to_bytes(format)?)?;
+        writer.flush()?;
         Ok(())
     }
 
-    /// Load a json serialized proof from the provided path.
+    /// Load a serialized proof from the provided path. The [ProofFormat] is detected from the file contents.
     pub fn load<Scheme: CommitmentScheme<Curve = C, Scalar = F>>(
         proof_path: &PathBuf,
     ) -> Result<Self, Box<dyn Error>>
@@ -164,8 +468,79 @@ where
         <C as CurveAffine>::ScalarExt: FromUniformBytes<64>,
     {
         trace!("reading proof");
-        let data = std::fs::read_to_string(proof_path)?;
-        serde_json::from_str(&data).map_err(|e| e.into())
+        let data = std::fs::read(proof_path)?;
+        Self::from_bytes(&data, ProofFormat::detect(&data))
+    }
+
+    /// Serializes the proof in the given [ProofFormat].
+    pub fn to_bytes(&self, format: ProofFormat) -> Result<Vec<u8>, Box<dyn Error>> {
+        match format {
+            ProofFormat::Json => Ok(serde_json::to_vec(&self)?),
+            ProofFormat::Bincode => {
+                let mut bytes = BINCODE_PROOF_MAGIC.to_vec();
+                bytes.extend_from_slice(&BINCODE_PROOF_VERSION.to_le_bytes());
+                bincode::serialize_into(&mut bytes, &self)?;
+                Ok(bytes)
+            }
+            ProofFormat::Cbor => {
+                let mut bytes = vec![];
+                ciborium::into_writer(&self, &mut bytes)?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Deserializes a proof from `bytes` in the given [ProofFormat].
+    pub fn from_bytes(bytes: &[u8], format: ProofFormat) -> Result<Self, Box<dyn Error>> {
+        match format {
+            ProofFormat::Json => Ok(serde_json::from_slice(bytes)?),
+            ProofFormat::Bincode => match bytes.strip_prefix(BINCODE_PROOF_MAGIC.as_slice()) {
+                Some(versioned) => {
+                    if versioned.len() < 4 {
+                        return Err(PfSysError::CorruptFile(
+                            "bincode proof version is truncated".to_string(),
+                        )
+                        .into());
+                    }
+                    let (version, payload) = versioned.split_at(4);
+                    match u32::from_le_bytes(version.try_into().unwrap()) {
+                        BINCODE_PROOF_VERSION => Ok(bincode::deserialize(payload)?),
+                        version => Err(PfSysError::FileFormatVersion(
+                            version,
+                            BINCODE_PROOF_VERSION,
+                        )
+                        .into()),
+                    }
+                }
+                None => Err(PfSysError::CorruptFile(format!(
+                    "bincode proof doesn't start with the {:?} header",
+                    std::str::from_utf8(BINCODE_PROOF_MAGIC).unwrap()
+                ))
+                .into()),
+            },
+            ProofFormat::Cbor => Ok(ciborium::from_reader(bytes)?),
+        }
+    }
+    /// Exports the proof and its (flattened) public inputs as the JSON expected by web3 libraries (e.g. ethers.js),
+    /// `{ proof: "0x...", publicSignals: ["0x...", ...] }`. Public signals are 32-byte big-endian words, matching
+    /// the `uint256[]` / `bytes` calldata of the generated Solidity verifier.
+    pub fn to_web3_json(&self) -> serde_json::Value {
+        let public_signals: Vec<String> = self
+            .instances
+            .iter()
+            .flatten()
+            .map(|x| {
+                let mut bytes = x.to_repr().as_ref().to_vec();
+                // field reprs are little-endian, the EVM reads big-endian words
+                bytes.reverse();
+                format!("0x{}", hex::encode(bytes))
+            })
+            .collect();
+
+        serde_json::json!({
+            "proof": format!("0x{}", hex::encode(&self.proof)),
+            "publicSignals": public_signals,
+        })
     }
 }
 
@@ -213,11 +588,53 @@ where
     }
 }
 
+/// A [ProvingKey] along with how long generating its [VerifyingKey] and the [ProvingKey] itself took.
+#[derive(Debug)]
+pub struct KeygenResult<C: CurveAffine> {
+    /// the proving key (which holds the verifying key)
+    pub pk: ProvingKey<C>,
+    /// time taken to generate the verifying key
+    pub vk_time: std::time::Duration,
+    /// time taken to generate the proving key from the verifying key
+    pub pk_time: std::time::Duration,
+    /// peak resident memory (in bytes) of the process during keygen, if the platform supports querying it, see
+    /// [memory::measure_peak_memory]
+    pub peak_memory: Option<u64>,
+}
+
+/// A proof along with how long creating it took and the memory it required.
+#[derive(Debug)]
+pub struct ProofResult<F: PrimeField + SerdeObject, C: CurveAffine> {
+    /// the proof
+    pub snark: Snark<F, C>,
+    /// time taken to create (and in safe mode check) the proof
+    pub proof_time: std::time::Duration,
+    /// peak resident memory (in bytes) of the process while proving, if the platform supports querying it, see
+    /// [memory::measure_peak_memory]
+    pub peak_memory: Option<u64>,
+}
+
 /// Creates a [VerifyingKey] and [ProvingKey] for a [crate::graph::GraphCircuit] (`circuit`) with specific [CommitmentScheme] parameters (`params`).
 pub fn create_keys<Scheme: CommitmentScheme, F: PrimeField + TensorType, C: Circuit<F>>(
     circuit: &C,
     params: &'_ Scheme::ParamsProver,
 ) -> Result<ProvingKey<Scheme::Curve>, halo2_proofs::plonk::Error>
+where
+    C: Circuit<Scheme::Scalar>,
+    <Scheme as CommitmentScheme>::Scalar: FromUniformBytes<64>,
+{
+    create_keys_with_timings::<Scheme, F, C>(circuit, params).map(|keys| keys.pk)
+}
+
+/// Like [create_keys], but also returns how long each keygen step took, for tracking keygen performance programmatically.
+pub fn create_keys_with_timings<
+    Scheme: CommitmentScheme,
+    F: PrimeField + TensorType,
+    C: Circuit<F>,
+>(
+    circuit: &C,
+    params: &'_ Scheme::ParamsProver,
+) -> Result<KeygenResult<Scheme::Curve>, halo2_proofs::plonk::Error>
 where
     C: Circuit<Scheme::Scalar>,
     <Scheme as CommitmentScheme>::Scalar: FromUniformBytes<64>,
@@ -225,19 +642,32 @@ where
     //	Real proof
     let empty_circuit = <C as Circuit<F>>::without_witnesses(circuit);
 
-    // Initialize verifying key
-    let now = Instant::now();
-    trace!("preparing VK");
-    let vk = keygen_vk(params, &empty_circuit)?;
-    let elapsed = now.elapsed();
-    info!("VK took {}.{}", elapsed.as_secs(), elapsed.subsec_millis());
+    let keygen = || -> Result<_, halo2_proofs::plonk::Error> {
+        // Initialize verifying key
+        let now = Instant::now();
+        trace!("preparing VK");
+        let vk = keygen_vk(params, &empty_circuit)?;
+        let vk_time = now.elapsed();
+        info!("VK took {}.{}", vk_time.as_secs(), vk_time.subsec_millis());
 
-    // Initialize the proving key
-    let now = Instant::now();
-    let pk = keygen_pk(params, vk, &empty_circuit)?;
-    let elapsed = now.elapsed();
-    info!("PK took {}.{}", elapsed.as_secs(), elapsed.subsec_millis());
-    Ok(pk)
+        // Initialize the proving key
+        let now = Instant::now();
+        let pk = keygen_pk(params, vk, &empty_circuit)?;
+        let pk_time = now.elapsed();
+        info!("PK took {}.{}", pk_time.as_secs(), pk_time.subsec_millis());
+        Ok((pk, vk_time, pk_time))
+    };
+    let (keys, peak_memory) = memory::measure_peak_memory(keygen);
+    let (pk, vk_time, pk_time) = keys?;
+    if let Some(peak_memory) = peak_memory {
+        info!("keygen peak memory: {}MB", peak_memory >> 20);
+    }
+    Ok(KeygenResult {
+        pk,
+        vk_time,
+        pk_time,
+        peak_memory,
+    })
 }
 
 /// a wrapper around halo2's create_proof
@@ -366,14 +796,86 @@ where
     Scheme::Scalar: PrimeField + SerdeObject + FromUniformBytes<64>,
 {
     info!("loading verification key from {:?}", path);
-    let f = File::open(path)?;
-    let mut reader = BufReader::new(f);
-    VerifyingKey::<Scheme::Curve>::read::<_, C>(
-        &mut reader,
-        halo2_proofs::SerdeFormat::RawBytes,
-        params,
-    )
-    .map_err(Box::<dyn Error>::from)
+    read_checksummed(&path, |mut reader| {
+        VerifyingKey::<Scheme::Curve>::read::<_, C>(
+            &mut reader,
+            halo2_proofs::SerdeFormat::RawBytes,
+            params,
+        )
+    })
+}
+
+/// The shape of the circuit a [VerifyingKey] was generated for.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VkInfo {
+    /// the log_2 of the domain size
+    pub k: u32,
+    /// number of instance columns
+    pub num_instance_columns: usize,
+    /// number of advice columns
+    pub num_advice_columns: usize,
+    /// number of fixed columns (including compressed selectors)
+    pub num_fixed_columns: usize,
+    /// number of lookup arguments
+    pub num_lookups: usize,
+    /// number of custom gates
+    #[serde(default)]
+    pub num_gates: usize,
+}
+
+/// Reads the circuit shape out of a [VerifyingKey]'s constraint system. Useful for matching keys to proofs and params.
+pub fn vk_info<C: CurveAffine>(vk: &VerifyingKey<C>) -> VkInfo {
+    let cs = vk.cs();
+    VkInfo {
+        k: vk.get_domain().k(),
+        num_instance_columns: cs.num_instance_columns(),
+        num_advice_columns: cs.num_advice_columns(),
+        num_fixed_columns: cs.num_fixed_columns(),
+        num_lookups: cs.lookups().len(),
+        num_gates: cs.gates().len(),
+    }
+}
+
+/// The structural parts in which two [VerifyingKey]s differ, see [vk_diff].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VkDiff {
+    /// whether the serialized keys are byte-identical
+    pub identical: bool,
+    /// the circuit shapes of both keys, if they differ (including their domain size `k`)
+    pub shape: Option<(VkInfo, VkInfo)>,
+    /// the indices of the fixed column commitments that differ (or are missing from one of the keys)
+    pub fixed_commitments: Vec<usize>,
+    /// the indices of the permutation commitments that differ (or are missing from one of the keys)
+    pub permutation_commitments: Vec<usize>,
+}
+
+/// Compares two [VerifyingKey]s, e.g to catch keygen that silently changed after a dependency bump altered the
+/// circuit's layout.
+pub fn vk_diff(
+    a: &VerifyingKey<G1Affine>,
+    b: &VerifyingKey<G1Affine>,
+) -> Result<VkDiff, io::Error> {
+    let to_bytes = |vk: &VerifyingKey<G1Affine>| -> Result<Vec<u8>, io::Error> {
+        let mut bytes = vec![];
+        vk.write(&mut bytes, halo2_proofs::SerdeFormat::RawBytes)?;
+        Ok(bytes)
+    };
+    let differing = |a: &[G1Affine], b: &[G1Affine]| {
+        (0..a.len().max(b.len()))
+            .filter(|i| a.get(*i) != b.get(*i))
+            .collect::<Vec<_>>()
+    };
+
+    let (info_a, info_b) = (vk_info(a), vk_info(b));
+    Ok(VkDiff {
+        identical: to_bytes(a)? == to_bytes(b)?,
+        shape: (info_a != info_b).then_some((info_a, info_b)),
+        fixed_commitments: differing(a.fixed_commitments(), b.fixed_commitments()),
+        permutation_commitments: differing(
+            a.permutation().commitments(),
+            b.permutation().commitments(),
+        ),
+    })
 }
 
 /// Loads a [ProvingKey] at `path`.
@@ -424,11 +926,9 @@ where
     Scheme::Scalar: PrimeField + SerdeObject + FromUniformBytes<64>,
 {
     info!("saving verification key 💾");
-    let f = File::create(path)?;
-    let mut writer = BufWriter::new(f);
-    vk.write(&mut writer, halo2_proofs::SerdeFormat::RawBytes)?;
-    writer.flush()?;
-    Ok(())
+    write_checksummed(path, |mut writer| {
+        vk.write(&mut writer, halo2_proofs::SerdeFormat::RawBytes)
+    })
 }
 
 /// Saves [CommitmentScheme] parameters to `path`.
@@ -437,11 +937,7 @@ pub fn save_params<Scheme: CommitmentScheme>(
     params: &'_ Scheme::ParamsVerifier,
 ) -> Result<(), io::Error> {
     info!("saving parameters 💾");
-    let f = File::create(path)?;
-    let mut writer = BufWriter::new(f);
-    params.write(&mut writer)?;
-    writer.flush()?;
-    Ok(())
+    write_checksummed(path, |mut writer| params.write(&mut writer))
 }
 
 /// helper function
@@ -522,12 +1018,47 @@ pub fn create_proof_circuit_kzg<
     }
 }
 
-/// helper function
-pub(crate) fn verify_proof_circuit_kzg<
+/// Like [create_proof_circuit_kzg], but also returns how long proving took and the peak memory it required, for
+/// sizing the machines proofs are scheduled on.
+pub fn create_proof_circuit_kzg_with_metrics<
     'params,
+    C: Circuit<Fr>,
     Strategy: VerificationStrategy<'params, KZGCommitmentScheme<Bn256>, VerifierGWC<'params, Bn256>>,
 >(
+    circuit: C,
     params: &'params ParamsKZG<Bn256>,
+    public_inputs: Vec<Vec<Fr>>,
+    pk: &ProvingKey<G1Affine>,
+    transcript: TranscriptType,
+    strategy: Strategy,
+    check_mode: CheckMode,
+) -> Result<ProofResult<Fr, G1Affine>, Box<dyn Error>> {
+    let now = Instant::now();
+    let (snark, peak_memory) = memory::measure_peak_memory(|| {
+        create_proof_circuit_kzg(
+            circuit,
+            params,
+            public_inputs,
+            pk,
+            transcript,
+            strategy,
+            check_mode,
+        )
+    });
+    Ok(ProofResult {
+        snark: snark?,
+        proof_time: now.elapsed(),
+        peak_memory,
+    })
+}
+
+/// Verifies a KZG proof. Only the verifier params are needed, see [srs::extract_verifier_params] to derive
+/// them from the (much larger) prover SRS.
+pub fn verify_proof_circuit_kzg<
+    'params,
+    Strategy: VerificationStrategy<'params, KZGCommitmentScheme<Bn256>, VerifierGWC<'params, Bn256>>,
+>(
+    params: &'params ParamsVerifierKZG<Bn256>,
     proof: Snark<Fr, G1Affine>,
     vk: &VerifyingKey<G1Affine>,
     strategy: Strategy,
@@ -560,6 +1091,214 @@ pub(crate) fn verify_proof_circuit_kzg<
     }
 }
 
+/// Quantizes `float_public_inputs` into the instances of `snark`, as the prover quantizes float data (see
+/// [crate::graph::input::FileSourceInner::to_field]), such that a verifier holding the expected values as floats
+/// can't round them differently from the prover. There is one vector of floats per instance column of the verifier's
+/// `settings`, in column order, quantized at the scale the settings give the column (see
+/// [GraphSettings::instance_scales]). None of the proof's own instances are kept: the zero padding is laid out from
+/// the settings, and settings with other columns that don't hold fixed point values (e.g the challenge, or the
+/// modules' hashes) can't be verified against floats.
+pub fn quantize_float_public_inputs(
+    snark: &Snark<Fr, G1Affine>,
+    float_public_inputs: &[Vec<f64>],
+    settings: &GraphSettings,
+) -> Result<Vec<Vec<Fr>>, PfSysError> {
+    let layout = settings.instance_layout();
+    if layout.len() != snark.instances.len() {
+        return Err(PfSysError::FloatPublicInputs(format!(
+            "the settings lay out {} instance columns but the proof has {}",
+            layout.len(),
+            snark.instances.len()
+        )));
+    }
+    let num_fixed_point = layout
+        .iter()
+        .filter(|c| **c != InstanceColumn::Padding)
+        .count();
+    if float_public_inputs.len() != num_fixed_point {
+        return Err(PfSysError::FloatPublicInputs(format!(
+            "expected {} columns of floats but got {}",
+            num_fixed_point,
+            float_public_inputs.len()
+        )));
+    }
+
+    let mut floats = float_public_inputs.iter();
+    let mut instances = vec![];
+    for (i, (column, scale)) in layout.iter().zip(settings.instance_scales()).enumerate() {
+        let scale = match (column, scale) {
+            (InstanceColumn::Padding, _) => {
+                instances.push(vec![Fr::from(0); settings.instance_padding()]);
+                continue;
+            }
+            (_, Some(scale)) => scale,
+            (column, None) => {
+                return Err(PfSysError::FloatPublicInputs(format!(
+                    "instance column {} ({:?}) doesn't hold fixed point values",
+                    i, column
+                )))
+            }
+        };
+        // there are as many columns of floats as fixed point columns
+        let values = floats.next().unwrap();
+        if values.len() != snark.instances[i].len() {
+            return Err(PfSysError::FloatPublicInputs(format!(
+                "instance column {} holds {} values but {} floats were supplied",
+                i,
+                snark.instances[i].len(),
+                values.len()
+            )));
+        }
+        let quantized = values
+            .iter()
+            .map(|x| FileSourceInner::new_float(*x).to_field(scale))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PfSysError::FloatPublicInputs(format!("column {}: {}", i, e)))?;
+        instances.push(quantized);
+    }
+    Ok(instances)
+}
+
+/// Verifies a KZG proof as [verify_proof_circuit_kzg] does, against public inputs supplied as floats, which are
+/// quantized with [quantize_float_public_inputs] in place of the proof's own instances.
+pub fn verify_proof_with_float_inputs<
+    'params,
+    Strategy: VerificationStrategy<'params, KZGCommitmentScheme<Bn256>, VerifierGWC<'params, Bn256>>,
+>(
+    params: &'params ParamsVerifierKZG<Bn256>,
+    mut proof: Snark<Fr, G1Affine>,
+    float_public_inputs: &[Vec<f64>],
+    settings: &GraphSettings,
+    vk: &VerifyingKey<G1Affine>,
+    strategy: Strategy,
+) -> Result<Strategy::Output, Box<dyn Error>> {
+    proof.instances = quantize_float_public_inputs(&proof, float_public_inputs, settings)?;
+    Ok(verify_proof_circuit_kzg(params, proof, vk, strategy)?)
+}
+
+/// The instances of `snark` as integers, for [verify_with_predicate]: one vector per fixed point instance column of
+/// the verifier's `settings`, in column order, as for [quantize_float_public_inputs]. Fails if the instances aren't
+/// laid out as the circuit of `settings` and `vk` expects (see [GraphSettings::validate_public_inputs]), or if a
+/// value doesn't fit within an `i32`.
+pub fn predicate_inputs(
+    snark: &Snark<Fr, G1Affine>,
+    settings: &GraphSettings,
+    vk: &VerifyingKey<G1Affine>,
+) -> Result<Vec<Vec<i32>>, PfSysError> {
+    settings
+        .validate_public_inputs(vk, &snark.instances)
+        .map_err(|e| PfSysError::PredicateInputs(e.to_string()))?;
+    snark
+        .instances
+        .iter()
+        .zip(settings.instance_scales())
+        .enumerate()
+        .filter(|(_, (_, scale))| scale.is_some())
+        .map(|(i, (column, _))| {
+            column
+                .iter()
+                .map(|x| {
+                    let value = crate::fieldutils::felt_to_i128(*x);
+                    i32::try_from(value).map_err(|_| {
+                        PfSysError::PredicateInputs(format!(
+                            "instance column {} holds {}, which doesn't fit within an i32",
+                            i, value
+                        ))
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Verifies a KZG proof and checks its public inputs (see [predicate_inputs]) against `predicate`, returning `true`
+/// only if the proof is valid and the predicate accepts the statement it proves, such that a valid proof of an
+/// unwanted statement isn't accepted. A proof that doesn't verify returns `false`, rather than an error; the
+/// predicate is only run on the public inputs of a valid proof.
+pub fn verify_with_predicate(
+    proof: Snark<Fr, G1Affine>,
+    settings: &GraphSettings,
+    vk: &VerifyingKey<G1Affine>,
+    params: &ParamsVerifierKZG<Bn256>,
+    predicate: impl Fn(&[Vec<i32>]) -> bool,
+) -> Result<bool, Box<dyn Error>> {
+    let strategy = halo2_proofs::poly::kzg::strategy::SingleStrategy::new(params);
+    if let Err(e) = verify_proof_circuit_kzg(params, proof.clone(), vk, strategy) {
+        debug!("the proof doesn't verify: {:?}", e);
+        return Ok(false);
+    }
+    let inputs = predicate_inputs(&proof, settings, vk)?;
+    let accepted = predicate(&inputs);
+    if !accepted {
+        debug!("the predicate rejects the public inputs of the proof");
+    }
+    Ok(accepted)
+}
+
+/// Folds a KZG proof into an accumulating `strategy` and returns it for the next call, so that a single accumulator
+/// can be threaded across many verifications. Once every proof has been folded in, finalizing the strategy
+/// checks the accumulated pairing (a single pairing check for the whole batch), see [verify_batch_kzg].
+pub fn verify_into_strategy<'params>(
+    proof: Snark<Fr, G1Affine>,
+    params: &'params ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    strategy: AccumulatorStrategy<'params, Bn256>,
+) -> Result<AccumulatorStrategy<'params, Bn256>, halo2_proofs::plonk::Error> {
+    verify_proof_circuit_kzg(params, proof, vk, strategy)
+}
+
+/// Verifies a batch of KZG proofs for the same `vk`, accumulating them with [verify_into_strategy] and finalizing once.
+pub fn verify_batch_kzg(
+    proofs: Vec<Snark<Fr, G1Affine>>,
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+) -> Result<bool, halo2_proofs::plonk::Error> {
+    let mut strategy = AccumulatorStrategy::new(params);
+    for proof in proofs {
+        strategy = verify_into_strategy(proof, params, vk, strategy)?;
+    }
+    Ok(VerificationStrategy::<
+        '_,
+        KZGCommitmentScheme<Bn256>,
+        VerifierGWC<'_, Bn256>,
+    >::finalize(strategy))
+}
+
+/// An independent verification to run with [verify_parallel]. The (large) params are held behind an [Arc] so that
+/// jobs sharing the same params don't each need their own copy.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug)]
+pub struct VerifyJob {
+    /// the proof to verify
+    pub proof: Snark<Fr, G1Affine>,
+    /// the verifying key of the circuit the proof is for
+    pub vk: Arc<VerifyingKey<G1Affine>>,
+    /// the params the proof was generated with
+    pub params: Arc<ParamsKZG<Bn256>>,
+}
+
+/// Verifies independent KZG proofs, each against its own vk and params, across the rayon thread pool. Unlike
+/// [verify_batch_kzg] the proofs needn't be for the same circuit. Returns, in the order of `jobs`, whether each
+/// proof is valid, or the error that prevented it from being checked (e.g a malformed proof).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn verify_parallel(jobs: Vec<VerifyJob>) -> Vec<Result<bool, halo2_proofs::plonk::Error>> {
+    use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+    use rayon::prelude::*;
+
+    jobs.into_par_iter()
+        .map(|job| {
+            let params = job.params.verifier_params();
+            let strategy = SingleStrategy::new(params);
+            match verify_proof_circuit_kzg(params, job.proof, &job.vk, strategy) {
+                Ok(_) => Ok(true),
+                Err(halo2_proofs::plonk::Error::ConstraintSystemFailure)
+                | Err(halo2_proofs::plonk::Error::Opening) => Ok(false),
+                Err(e) => Err(e),
+            }
+        })
+        .collect()
+}
+
 ////////////////////////
 
 #[cfg(test)]
@@ -568,7 +1307,15 @@ mod tests {
     use std::io::copy;
 
     use super::*;
+    use crate::circuit::ops::poly::PolyOp;
+    use crate::circuit::ops::region::RegionCtx;
+    use crate::circuit::BaseConfig;
+    use crate::graph::tests::{relu_circuit, relu_run_args};
+    use crate::tensor::{Tensor, ValTensor, VarTensor};
+    use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner};
+    use halo2_proofs::plonk::{Advice, Column, ConstraintSystem, Error as PlonkError, Instance};
     use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+    use halo2_proofs::poly::kzg::strategy::SingleStrategy as KZGSingleStrategy;
     use halo2curves::bn256::{Bn256, Fr, G1Affine};
     use tempfile::Builder;
 
@@ -607,6 +1354,326 @@ mod tests {
         assert!(res.is_ok())
     }
 
+    #[test]
+    fn test_corrupt_saved_srs_is_rejected() {
+        let tmp_dir = Builder::new().prefix("example").tempdir().unwrap();
+        let fname = tmp_dir.path().join("kzg.params");
+        let srs = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(1);
+        save_params::<KZGCommitmentScheme<Bn256>>(&fname, &srs).unwrap();
+
+        let mut bytes = std::fs::read(&fname).unwrap();
+        let mut raw = vec![];
+        srs.write(&mut raw).unwrap();
+        // the header patched in after streaming the payload is the one of the payload as a whole
+        assert_eq!(bytes, add_checksum_header(&raw));
+        // flip a bit in the payload
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        std::fs::write(&fname, &bytes).unwrap();
+        let err = srs::load_srs::<KZGCommitmentScheme<Bn256>>(fname.clone()).unwrap_err();
+        assert!(err.to_string().contains("corrupt or truncated"));
+
+        // truncate the payload
+        std::fs::write(&fname, &bytes[..last]).unwrap();
+        let err = srs::load_srs::<KZGCommitmentScheme<Bn256>>(fname.clone()).unwrap_err();
+        assert!(err.to_string().contains("corrupt or truncated"));
+
+        // trailing bytes after the payload
+        let mut trailing = add_checksum_header(&raw);
+        trailing.push(0);
+        std::fs::write(&fname, &trailing).unwrap();
+        let err = srs::load_srs::<KZGCommitmentScheme<Bn256>>(fname.clone()).unwrap_err();
+        assert!(err.to_string().contains("corrupt or truncated"));
+
+        // files without a header are read as raw bytes
+        std::fs::write(&fname, &raw).unwrap();
+        assert!(srs::load_srs::<KZGCommitmentScheme<Bn256>>(fname).is_ok());
+    }
+
+    #[derive(Clone)]
+    struct DotCircuit {
+        inputs: [ValTensor<Fr>; 2],
+    }
+
+    impl Circuit<Fr> for DotCircuit {
+        type Config = BaseConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, 6, 4);
+            let b = VarTensor::new_advice(cs, 6, 4);
+            let output = VarTensor::new_advice(cs, 6, 4);
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), PlonkError> {
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0);
+                    config
+                        .layout(
+                            &mut region,
+                            &self.inputs,
+                            Box::new(PolyOp::Einsum {
+                                equation: "i,i->".to_string(),
+                            }),
+                        )
+                        .map_err(|_| PlonkError::Synthesis)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_verify_with_extracted_verifier_params() {
+        use crate::graph::Visibility;
+
+        let run_args = crate::RunArgs {
+            input_visibility: Visibility::Public,
+            ..relu_run_args()
+        };
+        let (circuit, params, pk) = relu_setup(&run_args);
+        let snark = prove_relu(&circuit, &params, &pk, 0);
+
+        let prover_params = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(10);
+        assert!(srs::extract_verifier_params(&prover_params, 11).is_err());
+        let verifier_params = srs::extract_verifier_params(&params, run_args.logrows).unwrap();
+        assert_eq!(verifier_params.k(), run_args.logrows);
+
+        let tmp_dir = Builder::new().prefix("example").tempdir().unwrap();
+        let (srs_path, verifier_path) = (
+            tmp_dir.path().join("kzg.srs"),
+            tmp_dir.path().join("kzg_verifier.srs"),
+        );
+        save_params::<KZGCommitmentScheme<Bn256>>(&srs_path, &params).unwrap();
+        srs::save_verifier_srs(&verifier_path, &verifier_params).unwrap();
+        // the verifier params are a few points, whatever the size of the srs
+        let verifier_len = std::fs::metadata(&verifier_path).unwrap().len();
+        assert!(verifier_len < 512);
+        assert!(verifier_len * 100 < std::fs::metadata(&srs_path).unwrap().len());
+        // and can't be used to prove
+        assert!(srs::load_srs::<KZGCommitmentScheme<Bn256>>(verifier_path.clone()).is_err());
+
+        let verifier_params = srs::load_verifier_srs(verifier_path).unwrap();
+        assert_eq!(verifier_params.k(), run_args.logrows);
+        assert_eq!(verifier_params.get_g()[0], params.get_g()[0]);
+        assert_eq!(verifier_params.s_g2(), params.s_g2());
+        let strategy = KZGSingleStrategy::new(&verifier_params);
+        assert!(
+            verify_proof_circuit_kzg(&verifier_params, snark.clone(), pk.get_vk(), strategy)
+                .is_ok()
+        );
+        // a full srs loads as is
+        let full_params = srs::load_verifier_srs(srs_path).unwrap();
+        let strategy = KZGSingleStrategy::new(&full_params);
+        assert!(verify_proof_circuit_kzg(&full_params, snark, pk.get_vk(), strategy).is_ok());
+
+        // the verifier params still reject a proof that doesn't verify
+        let mut tampered = prove_relu(&circuit, &params, &pk, 0);
+        tampered.instances[0][0] += Fr::from(1);
+        let strategy = KZGSingleStrategy::new(&verifier_params);
+        assert!(
+            verify_proof_circuit_kzg(&verifier_params, tampered, pk.get_vk(), strategy).is_err()
+        );
+    }
+
+    #[test]
+    fn test_verify_parallel() {
+        // dot products of different lengths at different k, and so with distinct vks
+        let jobs = [(6, 2), (7, 4), (8, 8)]
+            .into_iter()
+            .map(|(logrows, len)| {
+                let params = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(logrows);
+                let values = |offset: u64| {
+                    ValTensor::from(Tensor::from(
+                        (0..len).map(|x| Value::known(Fr::from(x + offset))),
+                    ))
+                };
+                let circuit = DotCircuit {
+                    inputs: [values(1), values(5)],
+                };
+                let pk =
+                    create_keys::<KZGCommitmentScheme<Bn256>, Fr, DotCircuit>(&circuit, &params)
+                        .unwrap();
+                let proof = create_proof_circuit_kzg(
+                    circuit,
+                    &params,
+                    vec![],
+                    &pk,
+                    TranscriptType::Blake,
+                    KZGSingleStrategy::new(&params),
+                    CheckMode::SAFE,
+                )
+                .unwrap();
+                VerifyJob {
+                    proof,
+                    vk: Arc::new(pk.get_vk().clone()),
+                    params: Arc::new(params),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let results = verify_parallel(jobs.clone());
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| matches!(r, Ok(true))));
+
+        // a proof checked against another model's vk doesn't verify
+        let mut mismatched = jobs.clone();
+        mismatched[1].vk = jobs[0].vk.clone();
+        mismatched[1].params = jobs[0].params.clone();
+        let results = verify_parallel(mismatched);
+        assert!(matches!(results[0], Ok(true)));
+        assert!(!matches!(results[1], Ok(true)));
+        assert!(matches!(results[2], Ok(true)));
+    }
+
+    #[test]
+    fn test_vk_info() {
+        use crate::graph::GraphCircuit;
+        use halo2_proofs::plonk::{Circuit, ConstraintSystem};
+
+        let run_args = relu_run_args();
+        let (circuit, _, pk) = relu_setup(&run_args);
+        let info = vk_info(pk.get_vk());
+        assert_eq!(info.k, run_args.logrows);
+        // only the outputs of 1l_relu are public, and its relu is looked up
+        assert_eq!(info.num_instance_columns, 1);
+        assert!(info.num_lookups > 0);
+
+        // the counts are those of the constraint system the circuit configures
+        let mut cs = ConstraintSystem::<Fr>::default();
+        GraphCircuit::configure_with_params(&mut cs, circuit.params());
+        assert_eq!(info.num_instance_columns, cs.num_instance_columns());
+        assert_eq!(info.num_advice_columns, cs.num_advice_columns());
+        assert_eq!(info.num_lookups, cs.lookups().len());
+        assert_eq!(info.num_gates, cs.gates().len());
+        // keygen compresses the selectors into fixed columns
+        assert!(info.num_fixed_columns >= cs.num_fixed_columns());
+        assert!(info.num_fixed_columns <= cs.num_fixed_columns() + cs.num_selectors());
+    }
+
+    #[test]
+    fn test_vk_diff() {
+        // the params are generated once per logrows, such that the vks only differ by their circuits
+        let params_6 = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(6);
+        let params_7 = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(7);
+        let vk = |params: &ParamsKZG<Bn256>, len: u64| {
+            let values = ValTensor::from(Tensor::from((0..len).map(|x| Value::known(Fr::from(x)))));
+            let circuit = DotCircuit {
+                inputs: [values.clone(), values],
+            };
+            create_keys::<KZGCommitmentScheme<Bn256>, Fr, DotCircuit>(&circuit, params)
+                .unwrap()
+                .get_vk()
+                .clone()
+        };
+
+        // keygen is reproducible
+        let diff = vk_diff(&vk(&params_6, 2), &vk(&params_6, 2)).unwrap();
+        assert_eq!(
+            diff,
+            VkDiff {
+                identical: true,
+                ..Default::default()
+            }
+        );
+
+        // a longer dot product enables the gate on more rows, changing the fixed columns
+        let diff = vk_diff(&vk(&params_6, 2), &vk(&params_6, 4)).unwrap();
+        assert!(!diff.identical);
+        assert!(!diff.fixed_commitments.is_empty());
+
+        let diff = vk_diff(&vk(&params_6, 2), &vk(&params_7, 2)).unwrap();
+        assert!(!diff.identical);
+        let (info, other_info) = diff.shape.unwrap();
+        assert_eq!((info.k, other_info.k), (6, 7));
+    }
+
+    #[test]
+    fn test_verify_debug_transcript() {
+        use diagnostics::{verify_proof_circuit_kzg_debug, TranscriptEvent};
+
+        let params = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(6);
+        let values = |v: [u64; 4]| {
+            ValTensor::from(Tensor::from(
+                v.map(|x| Value::known(Fr::from(x))).into_iter(),
+            ))
+        };
+        let circuit = DotCircuit {
+            inputs: [values([1, 2, 3, 4]), values([5, 6, 7, 8])],
+        };
+        let pk =
+            create_keys::<KZGCommitmentScheme<Bn256>, Fr, DotCircuit>(&circuit, &params).unwrap();
+        let snark = create_proof_circuit_kzg(
+            circuit,
+            &params,
+            vec![],
+            &pk,
+            TranscriptType::Blake,
+            KZGSingleStrategy::new(&params),
+            CheckMode::SAFE,
+        )
+        .unwrap();
+        assert!(verify_proof_circuit_kzg_debug(&params, &snark, pk.get_vk()).is_ok());
+
+        // a proof labelled with the wrong transcript verifies with the one it was generated with
+        let mut mislabelled = snark.clone();
+        mislabelled.transcript_type = TranscriptType::EVM;
+        let diagnostic =
+            verify_proof_circuit_kzg_debug(&params, &mislabelled, pk.get_vk()).unwrap_err();
+        assert!(diagnostic.is_transcript_mismatch());
+        assert_eq!(diagnostic.verifies_with, Some(TranscriptType::Blake));
+        assert!(!diagnostic.expected_challenges().is_empty());
+        let divergence = diagnostic.divergence.unwrap();
+        assert_ne!(
+            diagnostic.events.get(divergence),
+            diagnostic.expected_events.get(divergence)
+        );
+
+        // whereas a tampered proof fails with every transcript
+        let mut tampered = snark;
+        let last = tampered.proof.len() - 1;
+        tampered.proof[last] ^= 1;
+        let diagnostic =
+            verify_proof_circuit_kzg_debug(&params, &tampered, pk.get_vk()).unwrap_err();
+        assert!(!diagnostic.is_transcript_mismatch());
+        assert_eq!(diagnostic.transcript_type, TranscriptType::Blake);
+        assert!(diagnostic
+            .events
+            .iter()
+            .any(|e| matches!(e, TranscriptEvent::Challenge(_))));
+    }
+
+    #[test]
+    fn test_create_keys_with_timings() {
+        let params = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(6);
+        let values = |v: [u64; 4]| {
+            ValTensor::from(Tensor::from(
+                v.map(|x| Value::known(Fr::from(x))).into_iter(),
+            ))
+        };
+        let circuit = DotCircuit {
+            inputs: [values([1, 2, 3, 4]), values([5, 6, 7, 8])],
+        };
+        let keys = create_keys_with_timings::<KZGCommitmentScheme<Bn256>, Fr, DotCircuit>(
+            &circuit, &params,
+        )
+        .unwrap();
+        assert_eq!(keys.pk.get_vk().get_domain().k(), 6);
+        assert!(keys.vk_time + keys.pk_time > std::time::Duration::ZERO);
+    }
+
     #[test]
     fn test_snark_serialization_roundtrip() {
         let snark = Snark::<Fr, G1Affine> {
@@ -614,6 +1681,9 @@ mod tests {
             instances: vec![vec![Fr::from(1)], vec![Fr::from(2)]],
             transcript_type: TranscriptType::EVM,
             protocol: None,
+            metadata: Default::default(),
+            public_input_hash: None,
+            elided_instances: vec![],
         };
 
         snark
@@ -627,4 +1697,487 @@ mod tests {
         assert_eq!(snark.proof, snark2.proof);
         assert_eq!(snark.transcript_type, snark2.transcript_type);
     }
+
+    #[test]
+    fn test_snark_cbor_roundtrip() {
+        let snark = Snark::<Fr, G1Affine> {
+            proof: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            instances: vec![vec![Fr::from(1)], vec![Fr::from(2), -Fr::from(3)]],
+            transcript_type: TranscriptType::EVM,
+            protocol: None,
+            metadata: Default::default(),
+            public_input_hash: None,
+            elided_instances: vec![],
+        };
+
+        let tmp_dir = Builder::new().prefix("example").tempdir().unwrap();
+        let fname = tmp_dir.path().join("proof.cbor");
+        snark.save_with_format(&fname, ProofFormat::Cbor).unwrap();
+
+        let bytes = std::fs::read(&fname).unwrap();
+        assert_eq!(ProofFormat::detect(&bytes), ProofFormat::Cbor);
+        assert!(bytes.len() < snark.to_bytes(ProofFormat::Json).unwrap().len());
+
+        let snark2 = Snark::<Fr, G1Affine>::load::<KZGCommitmentScheme<Bn256>>(&fname).unwrap();
+        assert_eq!(snark.instances, snark2.instances);
+        assert_eq!(snark.proof, snark2.proof);
+        assert_eq!(snark.transcript_type, snark2.transcript_type);
+
+        let snark3 = Snark::<Fr, G1Affine>::from_bytes(
+            &snark.to_bytes(ProofFormat::Bincode).unwrap(),
+            ProofFormat::Bincode,
+        )
+        .unwrap();
+        assert_eq!(snark.instances, snark3.instances);
+        assert_eq!(snark.proof, snark3.proof);
+    }
+
+    #[test]
+    fn test_snark_bincode_versions() {
+        let mut snark = Snark::<Fr, G1Affine> {
+            proof: vec![1, 2, 3, 4],
+            instances: vec![vec![Fr::from(1), -Fr::from(2)]],
+            transcript_type: TranscriptType::EVM,
+            protocol: None,
+            metadata: Default::default(),
+            public_input_hash: None,
+            elided_instances: vec![],
+        };
+        snark.metadata.insert("model".to_string(), "v2".to_string());
+
+        let bytes = snark.to_bytes(ProofFormat::Bincode).unwrap();
+        assert!(bytes.starts_with(BINCODE_PROOF_MAGIC));
+        assert_eq!(ProofFormat::detect(&bytes), ProofFormat::Bincode);
+        assert_eq!(
+            ProofFormat::Bincode.version(&bytes),
+            Some(BINCODE_PROOF_VERSION)
+        );
+        let loaded = Snark::<Fr, G1Affine>::from_bytes(&bytes, ProofFormat::Bincode).unwrap();
+        assert_eq!(loaded.metadata, snark.metadata);
+
+        // bincode without the header can't be told apart from garbage
+        let headerless = bincode::serialize(&snark).unwrap();
+        assert_eq!(ProofFormat::Bincode.version(&headerless), None);
+        let err = Snark::<Fr, G1Affine>::from_bytes(&headerless, ProofFormat::Bincode).unwrap_err();
+        assert!(err.to_string().contains("header"));
+
+        // a proof from a newer version of ezkl
+        let mut newer = bytes;
+        newer[8..12].copy_from_slice(&(BINCODE_PROOF_VERSION + 1).to_le_bytes());
+        let err = Snark::<Fr, G1Affine>::from_bytes(&newer, ProofFormat::Bincode).unwrap_err();
+        assert!(err.to_string().contains("unsupported file format version"));
+    }
+
+    #[test]
+    fn test_quantize_float_public_inputs() {
+        // an output at scale 2 followed by two cells of zero padding
+        let settings = GraphSettings {
+            run_args: crate::RunArgs {
+                output_visibility: crate::graph::Visibility::Public,
+                pad_public_inputs: 4,
+                ..Default::default()
+            },
+            model_instance_shapes: vec![vec![2]],
+            model_output_scales: vec![2],
+            ..Default::default()
+        };
+        assert_eq!(settings.instance_scales(), vec![Some(2), None]);
+        let snark = Snark::<Fr, G1Affine> {
+            proof: vec![],
+            instances: vec![vec![Fr::from(0); 2], vec![Fr::from(7); 2]],
+            transcript_type: TranscriptType::Blake,
+            protocol: None,
+            metadata: Default::default(),
+            public_input_hash: None,
+            elided_instances: vec![],
+        };
+
+        let quantize =
+            |floats: &[Vec<f64>]| quantize_float_public_inputs(&snark, floats, &settings);
+        // the padding is laid out from the settings rather than taken from the proof
+        let instances = quantize(&[vec![1.5, -0.3]]).unwrap();
+        assert_eq!(
+            instances,
+            vec![vec![Fr::from(6), -Fr::from(1)], vec![Fr::from(0); 2]]
+        );
+        // the same quantization as the prover's data
+        assert_eq!(
+            instances[0][1],
+            FileSourceInner::new_float(-0.3).to_field(2).unwrap()
+        );
+
+        // a column per fixed point column, and a float per instance
+        assert!(quantize(&[vec![1.5, -0.3], vec![0.0, 0.0]]).is_err());
+        assert!(quantize(&[vec![1.5]]).is_err());
+        assert!(quantize(&[vec![f64::NAN, 0.0]]).is_err());
+
+        // a challenge can't be supplied as a float, and isn't taken on the proof's word either
+        let challenged = GraphSettings {
+            run_args: crate::RunArgs {
+                challenge: true,
+                pad_public_inputs: 0,
+                ..settings.run_args
+            },
+            model_instance_shapes: vec![vec![2], vec![1]],
+            ..settings.clone()
+        };
+        let err =
+            quantize_float_public_inputs(&snark, &[vec![1.5, -0.3]], &challenged).unwrap_err();
+        assert!(err.to_string().contains("doesn't hold fixed point values"));
+        // nor can settings of another layout than the proof's
+        let unpadded = GraphSettings {
+            model_instance_shapes: vec![vec![4]],
+            ..settings.clone()
+        };
+        assert!(quantize_float_public_inputs(&snark, &[vec![1.5, -0.3]], &unpadded).is_err());
+    }
+
+    #[test]
+    fn test_verify_proof_with_float_inputs() {
+        use crate::graph::Visibility;
+
+        let run_args = crate::RunArgs {
+            input_visibility: Visibility::Public,
+            ..relu_run_args()
+        };
+        let (circuit, params, pk) = relu_setup(&run_args);
+        let snark = prove_relu(&circuit, &params, &pk, 0);
+        let settings = circuit.settings.clone();
+        let verify = |floats: &[Vec<f64>]| {
+            let strategy = KZGSingleStrategy::new(params.verifier_params());
+            verify_proof_with_float_inputs(
+                params.verifier_params(),
+                snark.clone(),
+                floats,
+                &settings,
+                pk.get_vk(),
+                strategy,
+            )
+        };
+
+        // the input `1, -2, 3, ...` at scale 7, and its relu
+        let len = snark.instances[0].len();
+        let inputs = (0..len)
+            .map(|i| match i % 2 {
+                0 => (i + 1) as f64 / 128.0,
+                _ => -((i + 1) as f64) / 128.0,
+            })
+            .collect::<Vec<_>>();
+        let outputs = inputs.iter().map(|x| x.max(0.0)).collect::<Vec<_>>();
+        assert!(verify(&[inputs.clone(), outputs.clone()]).is_ok());
+        // floats within half a step of the proven values quantize to them
+        let nearby = outputs.iter().map(|x| x + 0.002).collect::<Vec<_>>();
+        assert!(verify(&[inputs.clone(), nearby]).is_ok());
+
+        // unlike those of another output
+        let mut other = outputs;
+        other[0] += 1.0;
+        assert!(verify(&[inputs.clone(), other]).is_err());
+        let mut other = inputs.clone();
+        other[1] = 0.0;
+        assert!(verify(&[other, inputs.iter().map(|x| x.max(0.0)).collect()]).is_err());
+    }
+
+    /// The 1l_relu model at `run_args`, with its params and proving key.
+    pub(super) fn relu_setup(
+        run_args: &crate::RunArgs,
+    ) -> (
+        crate::graph::GraphCircuit,
+        ParamsKZG<Bn256>,
+        ProvingKey<G1Affine>,
+    ) {
+        use crate::graph::GraphCircuit;
+
+        let circuit = relu_circuit(run_args);
+        let params = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(run_args.logrows);
+        let pk =
+            create_keys::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(&circuit, &params).unwrap();
+        (circuit, params, pk)
+    }
+
+    /// A proof of the [relu_setup] circuit on the input `offset + 1, -(offset + 2), offset + 3, ...`.
+    pub(super) fn prove_relu(
+        circuit: &crate::graph::GraphCircuit,
+        params: &ParamsKZG<Bn256>,
+        pk: &ProvingKey<G1Affine>,
+        offset: u64,
+    ) -> Snark<Fr, G1Affine> {
+        let mut circuit = circuit.clone();
+        let shape = circuit.model.graph.input_shapes()[0].clone();
+        let values = (0..shape.iter().product::<usize>() as u64)
+            .map(|i| match i % 2 {
+                0 => Fr::from(offset + i + 1),
+                _ => -Fr::from(offset + i + 1),
+            })
+            .collect::<Vec<_>>();
+        let input = Tensor::new(Some(&values), &shape).unwrap();
+        let witness = circuit.forward(&[input]).unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+        create_proof_circuit_kzg(
+            circuit,
+            params,
+            public_inputs,
+            pk,
+            TranscriptType::EVM,
+            KZGSingleStrategy::new(params),
+            CheckMode::SAFE,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_verify_batch_kzg() {
+        use crate::graph::Visibility;
+
+        let run_args = crate::RunArgs {
+            input_visibility: Visibility::Public,
+            ..relu_run_args()
+        };
+        let (circuit, params, pk) = relu_setup(&run_args);
+        let vk = pk.get_vk();
+        let proofs = (0..3)
+            .map(|i| prove_relu(&circuit, &params, &pk, i))
+            .collect::<Vec<_>>();
+        assert!(verify_batch_kzg(proofs.clone(), &params, vk).unwrap());
+
+        // the accumulator is threaded through every proof and checked once
+        let mut strategy = AccumulatorStrategy::new(&params);
+        for proof in proofs.clone() {
+            strategy = verify_into_strategy(proof, &params, vk, strategy).unwrap();
+        }
+        assert!(VerificationStrategy::<
+            '_,
+            KZGCommitmentScheme<Bn256>,
+            VerifierGWC<'_, Bn256>,
+        >::finalize(strategy));
+
+        // a single invalid proof fails the whole batch
+        let mut tampered = proofs;
+        tampered[1].instances[1][0] += Fr::from(1);
+        assert!(!matches!(verify_batch_kzg(tampered, &params, vk), Ok(true)));
+    }
+
+    #[test]
+    fn test_verify_with_predicate() {
+        use crate::graph::Visibility;
+        use crate::RunArgs;
+
+        let run_args = RunArgs {
+            input_visibility: Visibility::Public,
+            ..relu_run_args()
+        };
+        let (circuit, params, pk) = relu_setup(&run_args);
+        let snark = prove_relu(&circuit, &params, &pk, 0);
+        let settings = circuit.settings;
+
+        // the inputs alternate in sign, and the relu'd outputs are the positive ones
+        let vk = pk.get_vk();
+        let inputs = predicate_inputs(&snark, &settings, vk).unwrap();
+        assert_eq!(inputs[0][..2], [1, -2]);
+        assert_eq!(inputs[1][..2], [1, 0]);
+        let non_negative = |inputs: &[Vec<i32>]| inputs[1].iter().all(|x| *x >= 0);
+        assert!(
+            verify_with_predicate(snark.clone(), &settings, vk, &params, non_negative).unwrap()
+        );
+        // a valid proof of an unwanted statement is rejected
+        let first_above_one = |inputs: &[Vec<i32>]| inputs[1][0] > 1;
+        assert!(
+            !verify_with_predicate(snark.clone(), &settings, vk, &params, first_above_one).unwrap()
+        );
+        // and so is an invalid proof, whatever the predicate
+        let mut tampered = snark.clone();
+        tampered.instances[1][1] += Fr::from(1);
+        assert!(!verify_with_predicate(tampered, &settings, vk, &params, |_| true).unwrap());
+        // including one whose tampered instances don't fit the predicate's integers
+        let mut hashed = snark.clone();
+        hashed.instances[0][0] = Fr::from(u64::MAX);
+        assert!(!verify_with_predicate(hashed.clone(), &settings, vk, &params, |_| true).unwrap());
+
+        // values that aren't integers of an i32 (e.g hashes) can't be passed to the predicate
+        assert!(predicate_inputs(&hashed, &settings, vk).is_err());
+        // nor can instances laid out for another circuit
+        let mut truncated = snark;
+        truncated.instances.pop();
+        assert!(predicate_inputs(&truncated, &settings, vk).is_err());
+    }
+
+    #[test]
+    fn test_snark_metadata_roundtrip() {
+        let mut snark = Snark::<Fr, G1Affine> {
+            proof: vec![1, 2, 3, 4],
+            instances: vec![vec![Fr::from(1)]],
+            transcript_type: TranscriptType::EVM,
+            protocol: None,
+            metadata: Default::default(),
+            public_input_hash: None,
+            elided_instances: vec![],
+        };
+        snark
+            .metadata
+            .insert("request_id".to_string(), "42".to_string());
+        snark
+            .metadata
+            .insert("model_version".to_string(), "v1.2".to_string());
+
+        for format in [ProofFormat::Json, ProofFormat::Bincode, ProofFormat::Cbor] {
+            let snark2 =
+                Snark::<Fr, G1Affine>::from_bytes(&snark.to_bytes(format).unwrap(), format)
+                    .unwrap();
+            assert_eq!(snark.metadata, snark2.metadata);
+        }
+
+        // proofs serialized before metadata existed load with none
+        let mut json = serde_json::to_value(&snark).unwrap();
+        json.as_object_mut().unwrap().remove("metadata");
+        let old: Snark<Fr, G1Affine> = serde_json::from_value(json).unwrap();
+        assert!(old.metadata.is_empty());
+        assert_eq!(old.proof, snark.proof);
+    }
+
+    #[test]
+    fn test_snark_web3_json() {
+        let snark = Snark::<Fr, G1Affine> {
+            proof: vec![0xde, 0xad, 0xbe, 0xef],
+            instances: vec![vec![Fr::from(1)], vec![-Fr::from(1), Fr::from(256)]],
+            transcript_type: TranscriptType::EVM,
+            protocol: None,
+            metadata: Default::default(),
+            public_input_hash: None,
+            elided_instances: vec![],
+        };
+
+        let expected = serde_json::json!({
+            "proof": "0xdeadbeef",
+            "publicSignals": [
+                "0x0000000000000000000000000000000000000000000000000000000000000001",
+                // p - 1
+                "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000",
+                "0x0000000000000000000000000000000000000000000000000000000000000100",
+            ]
+        });
+        assert_eq!(snark.to_web3_json(), expected);
+    }
+
+    /// Exposes `instances` as the public instances of its proofs, standing in for an aggregation circuit (whose
+    /// instances are the limbs of its accumulator) small enough to be proven in tests.
+    #[derive(Clone)]
+    struct InstanceCircuit {
+        instances: Vec<Value<Fr>>,
+    }
+
+    impl Circuit<Fr> for InstanceCircuit {
+        type Config = (Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            InstanceCircuit {
+                instances: vec![Value::unknown(); self.instances.len()],
+            }
+        }
+
+        fn configure(cs: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let (advice, instance) = (cs.advice_column(), cs.instance_column());
+            cs.enable_equality(advice);
+            cs.enable_equality(instance);
+            (advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), PlonkError> {
+            let cells = layouter.assign_region(
+                || "",
+                |mut region| {
+                    self.instances
+                        .iter()
+                        .enumerate()
+                        .map(|(i, x)| region.assign_advice(|| "", advice, i, || *x))
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+            for (i, cell) in cells.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_verify_aggregate() {
+        use crate::pfsys::evm::aggregation::{
+            verify_aggregate, AggregationCircuit, AggregationError,
+        };
+
+        let params = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(6);
+        let snarks = [[1, 2, 3, 4], [5, 6, 7, 8]].map(|v: [u64; 4]| {
+            let values = |offset: u64| {
+                ValTensor::from(Tensor::from(
+                    v.map(|x| Value::known(Fr::from(x + offset))).into_iter(),
+                ))
+            };
+            let circuit = DotCircuit {
+                inputs: [values(0), values(1)],
+            };
+            let pk = create_keys::<KZGCommitmentScheme<Bn256>, Fr, DotCircuit>(&circuit, &params)
+                .unwrap();
+            create_proof_circuit_kzg(
+                circuit,
+                &params,
+                vec![],
+                &pk,
+                TranscriptType::Poseidon,
+                KZGSingleStrategy::new(&params),
+                CheckMode::SAFE,
+            )
+            .unwrap()
+        });
+        // the accumulator of the snarks, accumulated natively as the aggregation circuit does
+        let limbs = AggregationCircuit::new(&params.get_g()[0].into(), snarks)
+            .unwrap()
+            .instances()
+            .remove(0);
+
+        let prove = |limbs: &[Fr]| {
+            let circuit = InstanceCircuit {
+                instances: limbs.iter().map(|x| Value::known(*x)).collect(),
+            };
+            let pk =
+                create_keys::<KZGCommitmentScheme<Bn256>, Fr, InstanceCircuit>(&circuit, &params)
+                    .unwrap();
+            let snark = create_proof_circuit_kzg(
+                circuit,
+                &params,
+                vec![limbs.to_vec()],
+                &pk,
+                TranscriptType::EVM,
+                KZGSingleStrategy::new(&params),
+                CheckMode::SAFE,
+            )
+            .unwrap();
+            (snark, pk)
+        };
+        let (snark, pk) = prove(&limbs);
+        assert!(verify_aggregate(&snark, pk.get_vk(), &params).is_ok());
+
+        // a valid proof of a tampered accumulator, with its lhs and rhs swapped (both still on the curve), fails the
+        // pairing check
+        let (lhs, rhs) = limbs.split_at(limbs.len() / 2);
+        let (tampered, pk) = prove(&[rhs, lhs].concat());
+        assert!(matches!(
+            verify_aggregate(&tampered, pk.get_vk(), &params),
+            Err(AggregationError::AccumulatorVerification)
+        ));
+
+        // and the accumulator of a proof can't be changed without invalidating the proof
+        let mut tampered = snark;
+        tampered.instances[0][0] += Fr::from(1);
+        assert!(matches!(
+            verify_aggregate(&tampered, pk.get_vk(), &params),
+            Err(AggregationError::KZGProofVerification)
+        ));
+    }
 }
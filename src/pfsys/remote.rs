@@ -0,0 +1,215 @@
+use super::{load_vk, srs::load_srs};
+use crate::tensor::TensorType;
+use halo2_proofs::plonk::{Circuit, VerifyingKey};
+use halo2_proofs::poly::commitment::CommitmentScheme;
+use halo2curves::ff::{FromUniformBytes, PrimeField};
+use halo2curves::serde::SerdeObject;
+use halo2curves::CurveAffine;
+use log::info;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+
+/// The response header holding the hex encoded sha256 digest of the body, which downloads are checked against.
+pub const CHECKSUM_HEADER: &str = "x-checksum-sha256";
+
+/// Errors when loading params or keys from a URL
+#[derive(Error, Debug)]
+pub enum RemoteError {
+    /// The request couldn't be sent or the body couldn't be read
+    #[error("failed to fetch {0}: {1}")]
+    Request(String, reqwest::Error),
+    /// The server responded with an error status
+    #[error("failed to fetch {0}: server responded with {1}")]
+    Status(String, reqwest::StatusCode),
+    /// The response has no (valid) checksum header
+    #[error("response of {0} has no valid x-checksum-sha256 header")]
+    MissingChecksum(String),
+    /// The body doesn't match the checksum header
+    #[error("checksum mismatch for {url}: expected {expected}, found {found}")]
+    ChecksumMismatch {
+        /// the fetched url
+        url: String,
+        /// the digest in the checksum header
+        expected: String,
+        /// the digest of the body
+        found: String,
+    },
+    /// The download couldn't be written to disk
+    #[error("failed to write {0} to disk: {1}")]
+    Io(String, std::io::Error),
+    /// The body is truncated or can't be decoded as the requested object
+    #[error("failed to decode {0}: {1}")]
+    Decode(String, String),
+}
+
+/// Downloads `url` to `path`, streaming the body to disk chunk by chunk (so that it is never held in memory), and
+/// checks it against the sha256 digest in its [CHECKSUM_HEADER] header. The body is written to a `.part` file next to
+/// `path` that is only moved into place once it matches, so `path` never holds a partial or corrupt download.
+pub async fn fetch_checksummed(url: &str, path: &Path) -> Result<(), RemoteError> {
+    info!("fetching {} to {:?}", url, path);
+    let request_err = |e| RemoteError::Request(url.to_string(), e);
+    let io_err = |e| RemoteError::Io(url.to_string(), e);
+    let mut resp = reqwest::get(url).await.map_err(request_err)?;
+    if !resp.status().is_success() {
+        return Err(RemoteError::Status(url.to_string(), resp.status()));
+    }
+    let expected = resp
+        .headers()
+        .get(CHECKSUM_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_lowercase())
+        .filter(|v| v.len() == 64 && v.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| RemoteError::MissingChecksum(url.to_string()))?;
+
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    let part = PathBuf::from(part);
+    let mut file = tokio::fs::File::create(&part).await.map_err(io_err)?;
+    let mut hasher = Sha256::new();
+    let downloaded = async {
+        while let Some(chunk) = resp.chunk().await.map_err(request_err)? {
+            hasher.update(&chunk);
+            file.write_all(&chunk).await.map_err(io_err)?;
+        }
+        file.flush().await.map_err(io_err)
+    }
+    .await;
+    drop(file);
+    let found = hex::encode(hasher.finalize());
+    let checked = downloaded.and_then(|_| match found == expected {
+        true => Ok(()),
+        false => Err(RemoteError::ChecksumMismatch {
+            url: url.to_string(),
+            expected,
+            found,
+        }),
+    });
+    if let Err(e) = checked {
+        let _ = tokio::fs::remove_file(&part).await;
+        return Err(e);
+    }
+    tokio::fs::rename(&part, path).await.map_err(io_err)
+}
+
+/// Like [super::srs::load_srs], but first downloads the params from `url` to `path`, see [fetch_checksummed].
+pub async fn load_srs_from_url<Scheme: CommitmentScheme>(
+    url: &str,
+    path: &Path,
+) -> Result<Scheme::ParamsVerifier, RemoteError> {
+    fetch_checksummed(url, path).await?;
+    load_srs::<Scheme>(path.to_path_buf())
+        .map_err(|e| RemoteError::Decode(url.to_string(), e.to_string()))
+}
+
+/// Like [super::load_vk], but first downloads the key from `url` to `path`, see [fetch_checksummed].
+pub async fn load_vk_from_url<Scheme: CommitmentScheme, F: PrimeField + TensorType, C: Circuit<F>>(
+    url: &str,
+    path: &Path,
+    params: <C as Circuit<Scheme::Scalar>>::Params,
+) -> Result<VerifyingKey<Scheme::Curve>, RemoteError>
+where
+    C: Circuit<Scheme::Scalar>,
+    Scheme::Curve: SerdeObject + CurveAffine,
+    Scheme::Scalar: PrimeField + SerdeObject + FromUniformBytes<64>,
+{
+    fetch_checksummed(url, path).await?;
+    load_vk::<Scheme, F, C>(path.to_path_buf(), params)
+        .map_err(|e| RemoteError::Decode(url.to_string(), e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pfsys::{add_checksum_header, save_params, srs};
+    use halo2_proofs::poly::commitment::Params;
+    use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+    use halo2curves::bn256::Bn256;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Serves `body` with the checksum header `checksum` to a single request, returning the url to fetch.
+    fn serve_once(body: Vec<u8>, checksum: Option<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            let mut head = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\n", body.len());
+            if let Some(checksum) = checksum {
+                head += &format!("{}: {}\r\n", CHECKSUM_HEADER, checksum);
+            }
+            head += "connection: close\r\n\r\n";
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(&body).unwrap();
+        });
+        format!("http://{}/kzg.srs", addr)
+    }
+
+    #[tokio::test]
+    async fn test_load_srs_from_url() {
+        let dir = tempfile::Builder::new().prefix("remote").tempdir().unwrap();
+        let path = dir.path().join("kzg.srs");
+        let params = srs::gen_srs::<KZGCommitmentScheme<Bn256>>(4);
+        save_params::<KZGCommitmentScheme<Bn256>>(&path, &params).unwrap();
+        let body = std::fs::read(&path).unwrap();
+        let checksum = hex::encode(Sha256::digest(&body));
+
+        let dest = dir.path().join("downloaded.srs");
+        let url = serve_once(body.clone(), Some(checksum.clone()));
+        let loaded = load_srs_from_url::<KZGCommitmentScheme<Bn256>>(&url, &dest)
+            .await
+            .unwrap();
+        assert_eq!(loaded.k(), 4);
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+
+        let url = serve_once(body.clone(), Some(checksum.to_uppercase()));
+        assert!(fetch_checksummed(&url, &dest).await.is_ok());
+
+        // failed downloads leave neither the destination nor the partial download behind
+        let failed = dir.path().join("failed.srs");
+        let part = dir.path().join("failed.srs.part");
+        let url = serve_once(body.clone(), None);
+        assert!(matches!(
+            fetch_checksummed(&url, &failed).await,
+            Err(RemoteError::MissingChecksum(_))
+        ));
+
+        let mut corrupted = body.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        let url = serve_once(corrupted, Some(checksum));
+        assert!(matches!(
+            fetch_checksummed(&url, &failed).await,
+            Err(RemoteError::ChecksumMismatch { .. })
+        ));
+        assert!(!failed.exists());
+        assert!(!part.exists());
+
+        // a body that matches its checksum but not its ezkl file header
+        let mut truncated = add_checksum_header(b"payload");
+        truncated.pop();
+        let checksum = hex::encode(Sha256::digest(&truncated));
+        let url = serve_once(truncated, Some(checksum));
+        assert!(matches!(
+            load_srs_from_url::<KZGCommitmentScheme<Bn256>>(&url, &failed).await,
+            Err(RemoteError::Decode(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_url_is_an_error() {
+        // nothing listens on the port once the listener is dropped
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let url = format!("http://{}/kzg.srs", addr);
+        let dir = tempfile::Builder::new().prefix("remote").tempdir().unwrap();
+        assert!(matches!(
+            fetch_checksummed(&url, &dir.path().join("kzg.srs")).await,
+            Err(RemoteError::Request(..))
+        ));
+    }
+}
@@ -0,0 +1,287 @@
+use super::bundle::{pack, unpack, Bundle};
+use super::PfSysError;
+use crate::graph::InstanceColumn;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use halo2curves::bn256::{Bn256, Fr};
+use log::info;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+
+const FIRST_ENTRY: &str = "first.bundle";
+const SECOND_ENTRY: &str = "second.bundle";
+const BOUNDARY_ENTRY: &str = "boundary.json";
+
+/// The proofs of two consecutive parts of a model, where the outputs of the first part are the inputs of the second,
+/// together with the value crossing the boundary between them. Both parts have to make the boundary public: the first
+/// its outputs and the second its inputs. Saved as a single tar archive holding both [Bundle]s.
+#[derive(Clone, Debug)]
+pub struct SplitProof {
+    /// the bundle of the part of the model computing the boundary
+    pub first: Bundle,
+    /// the bundle of the part of the model consuming the boundary
+    pub second: Bundle,
+    /// the public outputs of the first part, column by column, which are the public inputs of the second
+    pub boundary: Vec<Vec<Fr>>,
+}
+
+/// The instances of the columns of the `part` bundle that hold its side of the boundary (its inputs or outputs), as
+/// laid out by [crate::graph::GraphSettings::instance_layout].
+pub(super) fn boundary_columns(
+    bundle: &Bundle,
+    part: &str,
+    is_boundary: fn(&InstanceColumn) -> bool,
+) -> Result<Vec<Vec<Fr>>, PfSysError> {
+    let layout = bundle.settings.instance_layout();
+    if layout.len() != bundle.proof.instances.len() {
+        return Err(PfSysError::SplitBoundary(format!(
+            "the {} proof has {} instance columns but its settings lay out {}",
+            part,
+            bundle.proof.instances.len(),
+            layout.len()
+        )));
+    }
+    let mut columns = vec![];
+    for (i, column) in layout.iter().enumerate() {
+        if !is_boundary(column) {
+            continue;
+        }
+        if bundle.proof.elided_instances.contains(&i) {
+            return Err(PfSysError::SplitBoundary(format!(
+                "the {} proof elides its boundary column {}, restore it before combining",
+                part, i
+            )));
+        }
+        columns.push(bundle.proof.instances[i].clone());
+    }
+    if columns.is_empty() {
+        return Err(PfSysError::SplitBoundary(format!(
+            "the {} proof doesn't make its side of the boundary public",
+            part
+        )));
+    }
+    Ok(columns)
+}
+
+/// The scales of the columns of `bundle` that hold its side of the boundary, as its settings quantize them (see
+/// [crate::graph::GraphSettings::instance_scales]).
+pub(super) fn boundary_scales(
+    bundle: &Bundle,
+    is_boundary: fn(&InstanceColumn) -> bool,
+) -> Vec<Option<u32>> {
+    bundle
+        .settings
+        .instance_layout()
+        .iter()
+        .zip(bundle.settings.instance_scales())
+        .filter(|(column, _)| is_boundary(column))
+        .map(|(_, scale)| scale)
+        .collect()
+}
+
+pub(super) fn is_output(column: &InstanceColumn) -> bool {
+    matches!(column, InstanceColumn::Output(_))
+}
+
+pub(super) fn is_input(column: &InstanceColumn) -> bool {
+    matches!(column, InstanceColumn::Input(_))
+}
+
+impl SplitProof {
+    /// Combines the bundles of two consecutive parts of a model, taking the boundary from the first's public outputs.
+    /// Fails if the second doesn't take the same boundary as its public inputs.
+    pub fn new(first: Bundle, second: Bundle) -> Result<Self, PfSysError> {
+        let boundary = boundary_columns(&first, "first", is_output)?;
+        let split = Self {
+            first,
+            second,
+            boundary,
+        };
+        split.check_boundary()?;
+        Ok(split)
+    }
+
+    /// Checks that the first part's public outputs and the second part's public inputs are both the boundary, and
+    /// that both parts' settings quantize it at the same scales. Doesn't verify the proofs.
+    pub fn check_boundary(&self) -> Result<(), PfSysError> {
+        let outputs = boundary_columns(&self.first, "first", is_output)?;
+        let inputs = boundary_columns(&self.second, "second", is_input)?;
+        if outputs != self.boundary {
+            return Err(PfSysError::SplitBoundary(
+                "the first proof's public outputs aren't the recorded boundary".to_string(),
+            ));
+        }
+        if inputs.len() != outputs.len() {
+            return Err(PfSysError::SplitBoundary(format!(
+                "the first proof outputs {} boundary columns but the second takes {}",
+                outputs.len(),
+                inputs.len()
+            )));
+        }
+        for (i, (output, input)) in outputs.iter().zip(&inputs).enumerate() {
+            if output != input {
+                return Err(PfSysError::SplitBoundary(format!(
+                    "boundary column {} differs: the first proof outputs {} values, the second takes {} values{}",
+                    i,
+                    output.len(),
+                    input.len(),
+                    match output.iter().zip(input).position(|(a, b)| a != b) {
+                        Some(j) => format!(" which first differ at {}", j),
+                        None => "".to_string(),
+                    }
+                )));
+            }
+        }
+        let output_scales = boundary_scales(&self.first, is_output);
+        let input_scales = boundary_scales(&self.second, is_input);
+        if output_scales != input_scales {
+            return Err(PfSysError::SplitBoundary(format!(
+                "the first proof outputs the boundary at scales {:?} but the second takes it at {:?}",
+                output_scales, input_scales
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks each bundle against the params it was proven with (see [Bundle::check]) and the boundary between them
+    /// (see [SplitProof::check_boundary]). Doesn't verify the proofs.
+    pub fn check(
+        &self,
+        first_params: &ParamsKZG<Bn256>,
+        second_params: &ParamsKZG<Bn256>,
+    ) -> Result<(), PfSysError> {
+        self.first.check(first_params)?;
+        self.second.check(second_params)?;
+        self.check_boundary()
+    }
+
+    /// Saves the split proof as a tar archive to `path`.
+    pub fn save(&self, path: &PathBuf) -> Result<(), Box<dyn Error>> {
+        info!("saving split proof 💾");
+        let f = File::create(path)?;
+        let mut writer = BufWriter::new(f);
+        writer.write_all(&pack(&[
+            (FIRST_ENTRY, self.first.to_bytes()?),
+            (SECOND_ENTRY, self.second.to_bytes()?),
+            (BOUNDARY_ENTRY, serde_json::to_vec(&self.boundary)?),
+        ])?)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Loads a split proof saved with [SplitProof::save] from `path`.
+    pub fn load(path: &PathBuf) -> Result<Self, Box<dyn Error>> {
+        info!("loading split proof from {:?}", path);
+        let mut bytes = vec![];
+        BufReader::new(File::open(path)?).read_to_end(&mut bytes)?;
+        let mut entries = unpack(&bytes)?;
+        let mut entry = |name: &str| {
+            entries
+                .remove(name)
+                .ok_or_else(|| PfSysError::InconsistentBundle(format!("missing {}", name)))
+        };
+
+        Ok(Self {
+            first: Bundle::from_bytes(&entry(FIRST_ENTRY)?)?,
+            second: Bundle::from_bytes(&entry(SECOND_ENTRY)?)?,
+            boundary: serde_json::from_slice(&entry(BOUNDARY_ENTRY)?)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::tests::relu_run_args;
+    use crate::graph::Visibility;
+    use crate::pfsys::tests::{prove_relu, relu_setup};
+    use tempfile::Builder;
+
+    /// Two bundles of the relu model, where the second takes the first's outputs as its inputs (though it doesn't
+    /// prove that, which isn't checked when combining), and the indices of their boundary columns.
+    fn split_bundles() -> (Bundle, Bundle, usize, usize) {
+        let run_args = crate::RunArgs {
+            input_visibility: Visibility::Public,
+            ..relu_run_args()
+        };
+        let (circuit, params, pk) = relu_setup(&run_args);
+        let snark = prove_relu(&circuit, &params, &pk, 0);
+        let first = Bundle::new(
+            snark,
+            pk.get_vk().clone(),
+            circuit.settings.clone(),
+            &params,
+        )
+        .unwrap();
+
+        let layout = first.settings.instance_layout();
+        let input = layout.iter().position(is_input).unwrap();
+        let output = layout.iter().position(is_output).unwrap();
+        let mut second = first.clone();
+        second.proof.instances[input] = first.proof.instances[output].clone();
+        (first, second, input, output)
+    }
+
+    #[test]
+    fn test_split_proof_roundtrip() {
+        let (first, second, _, output) = split_bundles();
+        let split = SplitProof::new(first, second).unwrap();
+        assert_eq!(
+            split.boundary,
+            vec![split.first.proof.instances[output].clone()]
+        );
+
+        let tmp_dir = Builder::new().prefix("example").tempdir().unwrap();
+        let path = tmp_dir.path().join("split.proof");
+        split.save(&path).unwrap();
+        let loaded = SplitProof::load(&path).unwrap();
+        assert_eq!(loaded.boundary, split.boundary);
+        assert_eq!(loaded.first.manifest, split.first.manifest);
+        assert_eq!(loaded.first.proof.instances, split.first.proof.instances);
+        assert_eq!(loaded.second.proof.instances, split.second.proof.instances);
+        assert_eq!(loaded.second.settings, split.second.settings);
+        loaded.check_boundary().unwrap();
+
+        // a boundary edited after combining no longer matches the first proof's outputs
+        let mut edited = loaded;
+        edited.boundary[0][0] += Fr::from(1);
+        assert!(edited.check_boundary().is_err());
+    }
+
+    #[test]
+    fn test_split_proof_boundary_mismatch() {
+        let (first, mut second, input, _) = split_bundles();
+        second.proof.instances[input][1] += Fr::from(1);
+        let err = SplitProof::new(first.clone(), second).unwrap_err();
+        assert!(err.to_string().contains("which first differ at 1"));
+
+        // nor can the second take fewer values than the first outputs
+        let (_, mut second, input, _) = split_bundles();
+        second.proof.instances[input].pop();
+        assert!(SplitProof::new(first, second).is_err());
+    }
+
+    #[test]
+    fn test_split_proof_elided_boundary() {
+        let (mut first, second, _, output) = split_bundles();
+        first.proof.elided_instances = vec![output];
+        let err = SplitProof::new(first, second).unwrap_err();
+        assert!(err.to_string().contains("elides its boundary column"));
+
+        let (first, mut second, input, _) = split_bundles();
+        second.proof.elided_instances = vec![input];
+        assert!(SplitProof::new(first, second).is_err());
+    }
+
+    #[test]
+    fn test_split_proof_scale_mismatch() {
+        // the second part quantizes its inputs at another scale than the first outputs them
+        let (first, mut second, _, _) = split_bundles();
+        second.settings.model_input_scales = vec![6];
+        let err = SplitProof::new(first, second).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("at scales [Some(7)] but the second takes it at [Some(6)]"));
+    }
+}
@@ -1,10 +1,12 @@
 use halo2_proofs::poly::commitment::CommitmentScheme;
 use halo2_proofs::poly::commitment::Params;
 use halo2_proofs::poly::commitment::ParamsProver;
+use halo2_proofs::poly::kzg::commitment::{ParamsKZG, ParamsVerifierKZG};
+use halo2curves::bn256::{Bn256, G1Affine, G2Affine};
+use halo2curves::serde::SerdeObject;
 use log::info;
 use std::error::Error;
-use std::fs::File;
-use std::io::BufReader;
+use std::io::{self, Cursor, Read, Write};
 use std::path::PathBuf;
 
 /// for now we use the urls of the powers of tau ceremony from <https://github.com/han0110/halo2-kzg-srs>
@@ -16,12 +18,154 @@ pub fn gen_srs<Scheme: CommitmentScheme>(k: u32) -> Scheme::ParamsProver {
     Scheme::ParamsProver::new(k)
 }
 
-/// Loads the [CommitmentScheme::ParamsVerifier] at `path`.
+/// Magic bytes opening the verifier-only params written by [save_verifier_srs].
+pub const VERIFIER_SRS_MAGIC: &[u8; 8] = b"EZKLVSRS";
+
+// reads (up to) the first bytes of `reader`, which tell verifier-only params apart from a full srs
+fn read_magic(reader: &mut dyn Read) -> io::Result<Vec<u8>> {
+    let mut magic = vec![];
+    (&mut *reader)
+        .take(VERIFIER_SRS_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+    Ok(magic)
+}
+
+/// Loads the [CommitmentScheme::ParamsVerifier] at `path`. Fails on the verifier-only params written by
+/// [save_verifier_srs], which can't be used to prove, see [load_verifier_srs] to load those.
 pub fn load_srs<Scheme: CommitmentScheme>(
     path: PathBuf,
 ) -> Result<Scheme::ParamsVerifier, Box<dyn Error>> {
     info!("loading srs from {:?}", path);
-    let f = File::open(path)?;
-    let mut reader = BufReader::new(f);
-    Params::<'_, Scheme::Curve>::read(&mut reader).map_err(Box::<dyn Error>::from)
+    super::read_checksummed(&path, |reader| {
+        let magic = read_magic(reader)?;
+        if magic == VERIFIER_SRS_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "these are verifier-only params (see extract-verifier-srs) and can't be used to prove",
+            ));
+        }
+        Params::<'_, Scheme::Curve>::read(&mut Cursor::new(magic).chain(reader))
+    })
+}
+
+/// Saves the verifier-only params of `params`: its `k` and the `g[0]`, `g2` and `s_g2` points, which are all a KZG
+/// verifier (and the EVM verifier generated from the params) uses. The file is a few hundred bytes whatever the size
+/// of the srs, see [load_verifier_srs] to load it.
+pub fn save_verifier_srs(path: &PathBuf, params: &ParamsKZG<Bn256>) -> Result<(), io::Error> {
+    info!("saving verifier params 💾");
+    super::write_checksummed(path, |mut writer| {
+        writer.write_all(VERIFIER_SRS_MAGIC)?;
+        writer.write_all(&params.k().to_le_bytes())?;
+        params.get_g()[0].write_raw(&mut writer)?;
+        params.g2().write_raw(&mut writer)?;
+        params.s_g2().write_raw(&mut writer)
+    })
+}
+
+/// Loads the params at `path` for verifying, which may be either a full srs or the verifier-only params written by
+/// [save_verifier_srs]. The latter hold only the `g[0]`, `g2` and `s_g2` points, whatever their `k`: they verify proofs
+/// (and generate EVM verifiers) like the srs they were extracted from, but can't be used to prove.
+pub fn load_verifier_srs(path: PathBuf) -> Result<ParamsVerifierKZG<Bn256>, Box<dyn Error>> {
+    info!("loading verifier srs from {:?}", path);
+    super::read_checksummed(&path, |reader| {
+        let magic = read_magic(reader)?;
+        if magic != VERIFIER_SRS_MAGIC {
+            return Params::<'_, G1Affine>::read(&mut Cursor::new(magic).chain(reader));
+        }
+        let mut k = [0u8; 4];
+        reader.read_exact(&mut k)?;
+        let k = u32::from_le_bytes(k);
+        let mut reader = reader;
+        let g = G1Affine::read_raw(&mut reader)?;
+        let g2 = G2Affine::read_raw(&mut reader)?;
+        let s_g2 = G2Affine::read_raw(&mut reader)?;
+        verifier_params(k, g, g2, s_g2)
+    })
+}
+
+// the largest k of a domain of the bn256 scalar field
+const MAX_K: u32 = 28;
+
+// the params of `2^k` rows holding only the points a KZG verifier uses, and no lagrange basis
+fn verifier_params(
+    k: u32,
+    g: G1Affine,
+    g2: G2Affine,
+    s_g2: G2Affine,
+) -> io::Result<ParamsVerifierKZG<Bn256>> {
+    if k > MAX_K {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("verifier params of k={} exceed the max k={}", k, MAX_K),
+        ));
+    }
+    // halo2 only builds params from existing ones, so params of a single row are read from the points first, in the
+    // layout of [Params::write]: k, the 2^k points of g and then of g_lagrange, g2 and s_g2
+    let mut bytes = 0u32.to_le_bytes().to_vec();
+    g.write_raw(&mut bytes)?;
+    g.write_raw(&mut bytes)?;
+    g2.write_raw(&mut bytes)?;
+    s_g2.write_raw(&mut bytes)?;
+    let params: ParamsKZG<Bn256> = Params::<'_, G1Affine>::read(&mut Cursor::new(bytes))?;
+    Ok(params.from_parts(k, vec![g], Some(vec![]), g2, s_g2))
+}
+
+/// Downsizes `params` to `k` rows. Verifier-only params (see [load_verifier_srs]) have no other points to drop, and
+/// keep their own, while halo2 would recompute the lagrange basis from the `2^k` points it expects.
+pub fn downsize_srs(params: &mut ParamsKZG<Bn256>, k: u32) -> Result<(), io::Error> {
+    if params.get_g().len() == 1 && params.k() > 0 {
+        *params = verifier_params(k, params.get_g()[0], params.g2(), params.s_g2())?;
+    } else {
+        params.downsize(k);
+    }
+    Ok(())
+}
+
+/// Extracts the params for a circuit of `2^logrows` rows from (potentially much larger) prover params. These still
+/// hold the srs up to the circuit's own size, see [save_verifier_srs] for the (much smaller) file to ship to verifiers.
+pub fn extract_verifier_params(
+    params: &ParamsKZG<Bn256>,
+    logrows: u32,
+) -> Result<ParamsVerifierKZG<Bn256>, Box<dyn Error>> {
+    if logrows > params.k() {
+        return Err(format!(
+            "params support up to k={} but the circuit needs k={}",
+            params.k(),
+            logrows
+        )
+        .into());
+    }
+    let mut verifier_params = params.verifier_params().clone();
+    if logrows < verifier_params.k() {
+        verifier_params.downsize(logrows);
+    }
+    Ok(verifier_params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+
+    #[test]
+    fn test_verifier_params_dont_grow_with_k() {
+        let params = gen_srs::<KZGCommitmentScheme<Bn256>>(4);
+        let (g, g2, s_g2) = (params.get_g()[0], params.g2(), params.s_g2());
+        let size = |k| {
+            let params = verifier_params(k, g, g2, s_g2).unwrap();
+            assert_eq!(params.k(), k);
+            assert_eq!(params.n(), 1 << k);
+            let mut bytes = vec![];
+            params.write(&mut bytes).unwrap();
+            bytes.len()
+        };
+        assert_eq!(size(MAX_K), size(4));
+        assert!(verifier_params(MAX_K + 1, g, g2, s_g2).is_err());
+
+        // and keep their points when downsized
+        let mut downsized = verifier_params(MAX_K, g, g2, s_g2).unwrap();
+        downsize_srs(&mut downsized, 4).unwrap();
+        assert_eq!(downsized.k(), 4);
+        assert_eq!(downsized.get_g(), &[g]);
+    }
 }
@@ -1,21 +1,23 @@
 use crate::circuit::{CheckMode, Tolerance};
-use crate::commands::{CalibrationTarget, StrategyType};
+use crate::commands::{CalibrationTarget, StrategyType, VerifierFormat};
 use crate::fieldutils::{felt_to_i128, i128_to_felt};
 use crate::graph::{
-    quantize_float, scale_to_multiplier, GraphCircuit, GraphSettings, Model, Visibility,
+    quantize_float, scale_to_multiplier, GraphCircuit, GraphSettings, Model, OutputHeads,
+    OutputRange, Visibility,
 };
 use crate::pfsys::evm::aggregation::AggregationCircuit;
 use crate::pfsys::{
-    load_pk, save_params, save_vk, srs::gen_srs as ezkl_gen_srs, Snark, TranscriptType,
+    load_pk, save_params, save_vk, srs::gen_srs as ezkl_gen_srs, ProofFormat, Snark, TranscriptType,
 };
 use crate::RunArgs;
 use ethers::types::H160;
 use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
 use halo2curves::bn256::{Bn256, Fr};
-use pyo3::exceptions::{PyIOError, PyRuntimeError};
+use pyo3::exceptions::{PyIOError, PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::wrap_pyfunction;
 use pyo3_log;
+use std::collections::BTreeMap;
 use std::str::FromStr;
 use std::{fs::File, path::PathBuf};
 use tokio::runtime::Runtime;
@@ -29,6 +31,8 @@ struct PyRunArgs {
     #[pyo3(get, set)]
     pub scale: u32,
     #[pyo3(get, set)]
+    pub weight_scale: Option<u32>,
+    #[pyo3(get, set)]
     pub bits: usize,
     #[pyo3(get, set)]
     pub logrows: u32,
@@ -42,6 +46,36 @@ struct PyRunArgs {
     pub batch_size: usize,
     #[pyo3(get, set)]
     pub allocated_constraints: Option<usize>,
+    #[pyo3(get, set)]
+    pub sequence_length: usize,
+    #[pyo3(get, set)]
+    pub pad_public_inputs: usize,
+    #[pyo3(get, set)]
+    pub fuse_lookups: bool,
+    #[pyo3(get, set)]
+    pub public_outputs: Option<Vec<usize>>,
+    #[pyo3(get, set)]
+    pub input_statistics: String,
+    #[pyo3(get, set)]
+    pub integer_only: bool,
+    #[pyo3(get, set)]
+    pub hash_function: String,
+    #[pyo3(get, set)]
+    pub challenge: bool,
+    #[pyo3(get, set)]
+    pub classify: bool,
+    #[pyo3(get, set)]
+    pub assert_output_range: bool,
+    #[pyo3(get, set)]
+    pub sorted_input: Option<usize>,
+    #[pyo3(get, set)]
+    pub dedup_sorted_input: bool,
+    #[pyo3(get, set)]
+    pub unsupported_ops: String,
+    #[pyo3(get, set)]
+    pub output_scale: Option<u32>,
+    #[pyo3(get, set)]
+    pub eliminate_dead_columns: bool,
 }
 
 /// default instantiation of PyRunArgs
@@ -52,6 +86,7 @@ impl PyRunArgs {
         PyRunArgs {
             tolerance: Tolerance::default(),
             scale: 7,
+            weight_scale: None,
             bits: 16,
             logrows: 17,
             input_visibility: "public".into(),
@@ -59,23 +94,68 @@ impl PyRunArgs {
             param_visibility: "private".into(),
             batch_size: 1,
             allocated_constraints: None,
+            sequence_length: 1,
+            pad_public_inputs: 0,
+            fuse_lookups: false,
+            public_outputs: None,
+            input_statistics: "none".into(),
+            integer_only: false,
+            hash_function: "poseidon".into(),
+            challenge: false,
+            classify: false,
+            assert_output_range: false,
+            sorted_input: None,
+            dedup_sorted_input: false,
+            unsupported_ops: "error".into(),
+            output_scale: None,
+            eliminate_dead_columns: false,
         }
     }
 }
 
 /// Conversion between PyRunArgs and RunArgs
-impl From<PyRunArgs> for RunArgs {
-    fn from(py_run_args: PyRunArgs) -> Self {
-        RunArgs {
+impl TryFrom<PyRunArgs> for RunArgs {
+    type Error = PyErr;
+
+    fn try_from(py_run_args: PyRunArgs) -> Result<Self, Self::Error> {
+        Ok(RunArgs {
             tolerance: py_run_args.tolerance,
             scale: py_run_args.scale,
+            weight_scale: py_run_args.weight_scale,
             bits: py_run_args.bits,
             logrows: py_run_args.logrows,
             input_visibility: py_run_args.input_visibility,
             output_visibility: py_run_args.output_visibility,
             param_visibility: py_run_args.param_visibility,
             batch_size: py_run_args.batch_size,
-        }
+            sequence_length: py_run_args.sequence_length,
+            pad_public_inputs: py_run_args.pad_public_inputs,
+            fuse_lookups: py_run_args.fuse_lookups,
+            public_outputs: match py_run_args.public_outputs {
+                Some(heads) => OutputHeads::from_indices(&heads).map_err(PyValueError::new_err)?,
+                None => OutputHeads::all(),
+            },
+            input_statistics: py_run_args
+                .input_statistics
+                .parse()
+                .map_err(PyValueError::new_err)?,
+            integer_only: py_run_args.integer_only,
+            hash_function: py_run_args
+                .hash_function
+                .parse()
+                .map_err(PyValueError::new_err)?,
+            challenge: py_run_args.challenge,
+            classify: py_run_args.classify,
+            assert_output_range: py_run_args.assert_output_range,
+            sorted_input: py_run_args.sorted_input,
+            dedup_sorted_input: py_run_args.dedup_sorted_input,
+            unsupported_ops: py_run_args
+                .unsupported_ops
+                .parse()
+                .map_err(PyValueError::new_err)?,
+            output_scale: py_run_args.output_scale,
+            eliminate_dead_columns: py_run_args.eliminate_dead_columns,
+        })
     }
 }
 
@@ -177,7 +257,7 @@ fn gen_vk_from_pk_aggr(path_to_pk: PathBuf, vk_output_path: PathBuf) -> PyResult
     py_run_args = None
 ))]
 fn table(model: String, py_run_args: Option<PyRunArgs>) -> PyResult<String> {
-    let run_args: RunArgs = py_run_args.unwrap_or_else(PyRunArgs::new).into();
+    let run_args: RunArgs = py_run_args.unwrap_or_else(PyRunArgs::new).try_into()?;
     let mut reader = File::open(model).map_err(|_| PyIOError::new_err("Failed to open model"))?;
     let result = Model::new(&mut reader, run_args);
 
@@ -229,18 +309,22 @@ fn get_srs(
     model,
     output,
     py_run_args = None,
+    input_normalization = None,
 ))]
 fn gen_settings(
     model: PathBuf,
     output: PathBuf,
     py_run_args: Option<PyRunArgs>,
+    input_normalization: Option<PathBuf>,
 ) -> Result<bool, PyErr> {
-    let run_args: RunArgs = py_run_args.unwrap_or_else(PyRunArgs::new).into();
+    let run_args: RunArgs = py_run_args.unwrap_or_else(PyRunArgs::new).try_into()?;
 
-    crate::execute::gen_circuit_settings(model, output, run_args).map_err(|e| {
-        let err_str = format!("Failed to generate settings: {}", e);
-        PyRuntimeError::new_err(err_str)
-    })?;
+    crate::execute::gen_circuit_settings(model, output, run_args, input_normalization).map_err(
+        |e| {
+            let err_str = format!("Failed to generate settings: {}", e);
+            PyRuntimeError::new_err(err_str)
+        },
+    )?;
 
     Ok(true)
 }
@@ -338,6 +422,7 @@ fn mock_aggregate(aggregation_snarks: Vec<PathBuf>, logrows: u32) -> PyResult<bo
     pk_path,
     srs_path,
     settings_path,
+    max_logrows=None,
 ))]
 fn setup(
     model: PathBuf,
@@ -345,8 +430,17 @@ fn setup(
     pk_path: PathBuf,
     srs_path: PathBuf,
     settings_path: PathBuf,
+    max_logrows: Option<u32>,
 ) -> Result<bool, PyErr> {
-    crate::execute::setup(model, srs_path, settings_path, vk_path, pk_path).map_err(|e| {
+    crate::execute::setup(
+        model,
+        srs_path,
+        settings_path,
+        vk_path,
+        pk_path,
+        max_logrows,
+    )
+    .map_err(|e| {
         let err_str = format!("Failed to run setup: {}", e);
         PyRuntimeError::new_err(err_str)
     })?;
@@ -364,6 +458,11 @@ fn setup(
     transcript,
     strategy,
     settings_path,
+    max_logrows=None,
+    metadata=None,
+    elide_outputs=false,
+    challenge=None,
+    output_range=None,
 ))]
 fn prove(
     witness: PathBuf,
@@ -374,20 +473,31 @@ fn prove(
     transcript: TranscriptType,
     strategy: StrategyType,
     settings_path: PathBuf,
+    max_logrows: Option<u32>,
+    metadata: Option<BTreeMap<String, String>>,
+    elide_outputs: bool,
+    challenge: Option<[u64; 4]>,
+    output_range: Option<(f64, f64)>,
 ) -> PyResult<PyObject> {
     let snark = Runtime::new()
         .unwrap()
-        .block_on(crate::execute::prove(
-            witness,
-            model,
+        .block_on(crate::execute::prove(crate::execute::ProveOptions {
+            data_path: witness,
+            compiled_model_path: model,
             pk_path,
             proof_path,
             srs_path,
             transcript,
             strategy,
             settings_path,
-            CheckMode::UNSAFE,
-        ))
+            check_mode: CheckMode::UNSAFE,
+            max_logrows,
+            proof_format: ProofFormat::Json,
+            metadata: metadata.unwrap_or_default(),
+            elide_outputs,
+            challenge: challenge.map(|c| crate::pfsys::vecu64_to_field_montgomery::<Fr>(&c)),
+            output_range: output_range.map(|(min, max)| OutputRange { min, max }),
+        }))
         .map_err(|e| {
             let err_str = format!("Failed to run prove: {}", e);
             PyRuntimeError::new_err(err_str)
@@ -402,14 +512,35 @@ fn prove(
     settings_path,
     vk_path,
     srs_path,
+    compiled_model=None,
+    params_commitment=None,
+    challenge=None,
+    output_range=None,
+    debug_transcript=false,
 ))]
 fn verify(
     proof_path: PathBuf,
     settings_path: PathBuf,
     vk_path: PathBuf,
     srs_path: PathBuf,
+    compiled_model: Option<PathBuf>,
+    params_commitment: Option<PathBuf>,
+    challenge: Option<[u64; 4]>,
+    output_range: Option<(f64, f64)>,
+    debug_transcript: bool,
 ) -> Result<bool, PyErr> {
-    crate::execute::verify(proof_path, settings_path, vk_path, srs_path).map_err(|e| {
+    crate::execute::verify(
+        proof_path,
+        settings_path,
+        vk_path,
+        srs_path,
+        compiled_model,
+        params_commitment,
+        challenge.map(|c| crate::pfsys::vecu64_to_field_montgomery::<Fr>(&c)),
+        output_range.map(|(min, max)| OutputRange { min, max }),
+        debug_transcript,
+    )
+    .map_err(|e| {
         let err_str = format!("Failed to run verify: {}", e);
         PyRuntimeError::new_err(err_str)
     })?;
@@ -523,7 +654,8 @@ fn verify_aggr(
     srs_path,
     settings_path,
     sol_code_path,
-    abi_path
+    abi_path,
+    verifier_format=VerifierFormat::Auto
 ))]
 fn create_evm_verifier(
     vk_path: PathBuf,
@@ -531,12 +663,20 @@ fn create_evm_verifier(
     settings_path: PathBuf,
     sol_code_path: PathBuf,
     abi_path: PathBuf,
+    verifier_format: VerifierFormat,
 ) -> Result<bool, PyErr> {
-    crate::execute::create_evm_verifier(vk_path, srs_path, settings_path, sol_code_path, abi_path)
-        .map_err(|e| {
-            let err_str = format!("Failed to run create_evm_verifier: {}", e);
-            PyRuntimeError::new_err(err_str)
-        })?;
+    crate::execute::create_evm_verifier(
+        vk_path,
+        srs_path,
+        settings_path,
+        sol_code_path,
+        abi_path,
+        verifier_format,
+    )
+    .map_err(|e| {
+        let err_str = format!("Failed to run create_evm_verifier: {}", e);
+        PyRuntimeError::new_err(err_str)
+    })?;
 
     Ok(true)
 }
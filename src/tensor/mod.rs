@@ -48,6 +48,15 @@ pub enum TensorError {
     /// Significant bit truncation when instantiating
     #[error("Significant bit truncation when instantiating")]
     SigBitTruncationError,
+    /// A NaN or infinite value can't be quantized
+    #[error("can't quantize the non-finite value {0}")]
+    NonFiniteValue(f64),
+    /// A select mask holds values other than 0 or 1
+    #[error("select mask must be boolean (0/1) valued")]
+    NonBooleanMask,
+    /// A divisor holds a zero
+    #[error("division by zero")]
+    DivisionByZero,
 }
 
 /// The (inner) type of tensor elements.
@@ -553,8 +562,7 @@ impl<T: Clone + TensorType> Tensor<T> {
             full_indices.push(0..self.dims()[indices.len() + i])
         }
         for e in full_indices.iter().cloned().multi_cartesian_product() {
-            let index = self.get_index(&e);
-            res.push(self[index].clone())
+            res.push(self[self.flat_index(&e)].clone())
         }
         let dims: Vec<usize> = full_indices.iter().map(|e| e.end - e.start).collect();
         // for i in (0..indices.len()).rev() {
@@ -578,15 +586,93 @@ impl<T: Clone + TensorType> Tensor<T> {
     /// assert_eq!(a.get_index(&[1, 0, 1]), 10);
     /// ```
     pub fn get_index(&self, indices: &[usize]) -> usize {
-        assert_eq!(self.dims.len(), indices.len());
-        let mut index = 0;
-        let mut d = 1;
-        for i in (0..indices.len()).rev() {
-            assert!(self.dims[i] > indices[i]);
-            index += indices[i] * d;
-            d *= self.dims[i];
+        self.flat_index(indices)
+    }
+
+    /// The number of elements to step over in the flat array to move by one along each dimension.
+    ///
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// let a = Tensor::<i32>::new(None, &[2, 3, 4]).unwrap();
+    ///
+    /// assert_eq!(a.strides(), vec![12, 4, 1]);
+    /// ```
+    pub fn strides(&self) -> Vec<usize> {
+        let mut strides = vec![1; self.dims.len()];
+        for i in (0..self.dims.len().saturating_sub(1)).rev() {
+            strides[i] = strides[i + 1] * self.dims[i + 1];
+        }
+        strides
+    }
+
+    /// The offset in the flat array of the element at `coords`, the inverse of [Tensor::coords_of].
+    /// Panics if `coords` doesn't have an index per dimension or is out of bounds.
+    ///
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// let a = Tensor::<i32>::new(None, &[2, 3, 4]).unwrap();
+    ///
+    /// assert_eq!(a.flat_index(&[1, 2, 3]), 23);
+    /// assert_eq!(a.flat_index(&[1, 0, 2]), 14);
+    /// ```
+    pub fn flat_index(&self, coords: &[usize]) -> usize {
+        assert_eq!(self.dims.len(), coords.len());
+        // each dimension's stride is the product of the dimensions after it
+        coords
+            .iter()
+            .zip(self.dims.iter())
+            .fold(0, |index, (coord, dim)| {
+                assert!(dim > coord);
+                index * dim + coord
+            })
+    }
+
+    /// The coordinates of the element at offset `flat` in the flat array, the inverse of [Tensor::flat_index].
+    /// Panics if `flat` is out of bounds.
+    ///
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// let a = Tensor::<i32>::new(None, &[2, 3, 4]).unwrap();
+    ///
+    /// assert_eq!(a.coords_of(23), vec![1, 2, 3]);
+    /// assert_eq!(a.coords_of(14), vec![1, 0, 2]);
+    /// ```
+    pub fn coords_of(&self, flat: usize) -> Vec<usize> {
+        assert!(flat < self.len());
+        let mut coords = vec![0; self.dims.len()];
+        let mut rest = flat;
+        for (coord, dim) in coords.iter_mut().zip(self.dims.iter()).rev() {
+            *coord = rest % dim;
+            rest /= dim;
         }
-        index
+        coords
+    }
+
+    /// Iterates over the elements in flat order, each with its coordinates.
+    ///
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// let a = Tensor::<i32>::new(Some(&[1, 2, 3, 4, 5, 6]), &[3, 2]).unwrap();
+    ///
+    /// let mut elements = a.enumerate_indices();
+    /// assert_eq!(elements.next(), Some((vec![0, 0], &1)));
+    /// assert_eq!(elements.nth(2), Some((vec![1, 1], &4)));
+    /// ```
+    pub fn enumerate_indices(&self) -> impl Iterator<Item = (Vec<usize>, &T)> {
+        let mut coords = vec![0; self.dims.len()];
+        self.inner.iter().enumerate().map(move |(i, value)| {
+            if i > 0 {
+                // step to the next element, carrying over the dimensions the coordinates wrap around
+                for (coord, dim) in coords.iter_mut().zip(self.dims.iter()).rev() {
+                    *coord += 1;
+                    if coord < dim {
+                        break;
+                    }
+                    *coord = 0;
+                }
+            }
+            (coords.clone(), value)
+        })
     }
 
     /// Duplicates every nth element
@@ -682,6 +768,26 @@ impl<T: Clone + TensorType> Tensor<T> {
         &self.dims
     }
 
+    /// Reduces the tensor along `axis` with `f`, collapsing the axis to a dimension of 1 if `keepdims` is set and
+    /// removing it otherwise.
+    fn reduce_axis(
+        &self,
+        axis: usize,
+        keepdims: bool,
+        f: impl Fn(&Tensor<T>) -> Result<T, TensorError>,
+    ) -> Result<Tensor<T>, TensorError> {
+        let mut res = ops::reduce_axes(self, &[axis], f)?;
+        if !keepdims {
+            let mut dims = self.dims().to_vec();
+            dims.remove(axis);
+            if dims.is_empty() {
+                dims.push(1);
+            }
+            res.reshape(&dims);
+        }
+        Ok(res)
+    }
+
     ///Reshape the tensor
     /// ```
     /// use ezkl::tensor::Tensor;
@@ -723,44 +829,12 @@ impl<T: Clone + TensorType> Tensor<T> {
         new_dims.remove(source);
         new_dims.insert(destination, self.dims[source]);
 
-        // now reconfigure the elements appropriately in the new array
-        //  eg. if we have a 3x3x3 array and we want to move the 0th axis to the 2nd position
-        //  we need to move the elements at 0, 1, 2, 3, 4, 5, 6, 7, 8 to 0, 3, 6, 1, 4, 7, 2, 5, 8
-        //  so we need to move the elements at 0, 1, 2 to 0, 3, 6
-        //  and the elements at 3, 4, 5 to 1, 4, 7
-        //  and the elements at 6, 7, 8 to 2, 5, 8
-        let cartesian_coords = new_dims
-            .iter()
-            .map(|d| 0..*d)
-            .multi_cartesian_product()
-            .collect::<Vec<Vec<usize>>>();
-
+        // each element moves to its coordinates with the source axis' coordinate moved to the destination
         let mut output = Tensor::new(None, &new_dims)?;
-
-        for coord in cartesian_coords {
-            let mut old_coord = vec![0; self.dims.len()];
-
-            // now fetch the old index
-            for (i, c) in coord.iter().enumerate() {
-                if i == destination {
-                    old_coord[source] = *c;
-                } else if i == source && source < destination {
-                    old_coord[source + 1] = *c;
-                } else if i == source && source > destination {
-                    old_coord[source - 1] = *c;
-                } else if (i < source && source < destination)
-                    || (i < destination && source > destination)
-                {
-                    old_coord[i] = *c;
-                } else if i > source && source < destination {
-                    old_coord[i + 1] = *c;
-                } else if i > destination && source > destination {
-                    old_coord[i - 1] = *c;
-                } else {
-                    panic!()
-                }
-            }
-            output.set(&coord, self.get(&old_coord));
+        for (mut coord, value) in self.enumerate_indices() {
+            let c = coord.remove(source);
+            coord.insert(destination, c);
+            output.set(&coord, value.clone());
         }
 
         Ok(output)
@@ -795,34 +869,11 @@ impl<T: Clone + TensorType> Tensor<T> {
         new_dims[source] = self.dims[destination];
         new_dims[destination] = self.dims[source];
 
-        // now reconfigure the elements appropriately in the new array
-        //  eg. if we have a 3x3x3 array and we want to move the 0th axis to the 2nd position
-        //  we need to move the elements at 0, 1, 2, 3, 4, 5, 6, 7, 8 to 0, 3, 6, 1, 4, 7, 2, 5, 8
-        //  so we need to move the elements at 0, 1, 2 to 0, 3, 6
-        //  and the elements at 3, 4, 5 to 1, 4, 7
-        //  and the elements at 6, 7, 8 to 2, 5, 8
-        let cartesian_coords = new_dims
-            .iter()
-            .map(|d| 0..*d)
-            .multi_cartesian_product()
-            .collect::<Vec<Vec<usize>>>();
-
+        // each element moves to its coordinates with those of the two axes swapped
         let mut output = Tensor::new(None, &new_dims)?;
-
-        for coord in cartesian_coords {
-            let mut old_coord = vec![0; self.dims.len()];
-
-            // now fetch the old index
-            for (i, c) in coord.iter().enumerate() {
-                if i == destination {
-                    old_coord[source] = *c;
-                } else if i == source {
-                    old_coord[destination] = *c;
-                } else {
-                    old_coord[i] = *c;
-                }
-            }
-            output.set(&coord, self.get(&old_coord));
+        for (mut coord, value) in self.enumerate_indices() {
+            coord.swap(source, destination);
+            output.set(&coord, value.clone());
         }
 
         Ok(output)
@@ -1189,6 +1240,61 @@ impl<T: TensorType + Mul<Output = T> + std::marker::Send + std::marker::Sync> Te
     }
 }
 
+impl<T: TensorType + Add<Output = T>> Tensor<T> {
+    /// Sums the tensor along `axis`. The axis is kept with a dimension of 1 if `keepdims` is set.
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// let x = Tensor::<i128>::new(Some(&[2, 15, 2, 1, 1, 0]), &[2, 3]).unwrap();
+    /// let result = x.reduce_sum(1, false).unwrap();
+    /// let expected = Tensor::<i128>::new(Some(&[19, 2]), &[2]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn reduce_sum(&self, axis: usize, keepdims: bool) -> Result<Self, TensorError> {
+        self.reduce_axis(axis, keepdims, |slice| Ok(ops::sum(slice)?[0].clone()))
+    }
+
+    /// Averages the tensor along `axis` (for integer types the mean is truncated, as with integer division).
+    /// The axis is kept with a dimension of 1 if `keepdims` is set.
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// let x = Tensor::<i128>::new(Some(&[2, 16, 3, 1, 1, 0]), &[2, 3]).unwrap();
+    /// let result = x.reduce_mean(1, true).unwrap();
+    /// let expected = Tensor::<i128>::new(Some(&[7, 0]), &[2, 1]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn reduce_mean(&self, axis: usize, keepdims: bool) -> Result<Self, TensorError>
+    where
+        T: Div<Output = T>,
+    {
+        let len = *self.dims().get(axis).ok_or(TensorError::DimError)?;
+        let count = (0..len).fold(T::zero().ok_or(TensorError::WrongMethod)?, |acc, _| {
+            acc + T::one().unwrap()
+        });
+        self.reduce_axis(axis, keepdims, |slice| {
+            Ok(ops::sum(slice)?[0].clone() / count.clone())
+        })
+    }
+}
+
+impl<T: TensorType + Ord> Tensor<T> {
+    /// Takes the max of the tensor along `axis`. The axis is kept with a dimension of 1 if `keepdims` is set.
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// let x = Tensor::<i128>::new(Some(&[2, 15, 2, 1, 1, 0]), &[2, 3]).unwrap();
+    /// let result = x.reduce_max(0, false).unwrap();
+    /// let expected = Tensor::<i128>::new(Some(&[2, 15, 2]), &[3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn reduce_max(&self, axis: usize, keepdims: bool) -> Result<Self, TensorError> {
+        self.reduce_axis(axis, keepdims, |slice| {
+            slice.iter().max().cloned().ok_or(TensorError::DimError)
+        })
+    }
+}
+
 impl<T: TensorType + Div<Output = T> + std::marker::Send + std::marker::Sync> Div for Tensor<T> {
     type Output = Result<Tensor<T>, TensorError>;
     /// Elementwise divide a tensor with another tensor.
@@ -1325,4 +1431,50 @@ mod tests {
         let b = Tensor::<i32>::new(Some(&[1, 4]), &[2, 1]).unwrap();
         assert_eq!(a.get_slice(&[0..2, 0..1]).unwrap(), b);
     }
+
+    #[test]
+    fn tensor_indices() {
+        for dims in [vec![6], vec![2, 3], vec![3, 1, 4], vec![2, 5, 3, 2]] {
+            let values = (0..dims.iter().product()).collect::<Vec<_>>();
+            let a = Tensor::<usize>::new(Some(&values), &dims).unwrap();
+            for (coords, value) in a.enumerate_indices() {
+                assert_eq!(a.flat_index(&coords), *value);
+                assert_eq!(a.coords_of(*value), coords);
+                assert_eq!(a.get(&coords), *value);
+                assert!(coords.iter().zip(&dims).all(|(c, d)| c < d));
+            }
+            assert_eq!(a.enumerate_indices().count(), a.len());
+        }
+
+        let a = Tensor::<usize>::new(None, &[2, 3]).unwrap();
+        assert_eq!(a.coords_of(3), vec![1, 0]);
+        assert_eq!(a.coords_of(5), vec![1, 2]);
+    }
+
+    #[test]
+    fn tensor_reduce() {
+        let a = Tensor::<i128>::new(Some(&(0..24).collect::<Vec<_>>()), &[2, 3, 4]).unwrap();
+
+        let sum = a.reduce_sum(0, false).unwrap();
+        assert_eq!(sum.dims(), &[3, 4]);
+        assert_eq!(sum[..], (0..12).map(|i| 2 * i + 12).collect::<Vec<_>>()[..]);
+
+        let sum = a.reduce_sum(1, true).unwrap();
+        assert_eq!(sum.dims(), &[2, 1, 4]);
+        assert_eq!(sum[..], [12, 15, 18, 21, 48, 51, 54, 57]);
+
+        let max = a.reduce_max(2, false).unwrap();
+        assert_eq!(max.dims(), &[2, 3]);
+        assert_eq!(max[..], [3, 7, 11, 15, 19, 23]);
+
+        let mean = a.reduce_mean(2, true).unwrap();
+        assert_eq!(mean.dims(), &[2, 3, 1]);
+        assert_eq!(mean[..], [1, 5, 9, 13, 17, 21]);
+
+        let mean = a.reduce_mean(1, false).unwrap();
+        assert_eq!(mean.dims(), &[2, 4]);
+        assert_eq!(mean[..], [4, 5, 6, 7, 16, 17, 18, 19]);
+
+        assert!(a.reduce_sum(3, false).is_err());
+    }
 }
@@ -5,6 +5,7 @@ use rayon::{
     iter::IndexedParallelIterator, iter::IntoParallelRefMutIterator, iter::ParallelIterator,
     prelude::IntoParallelRefIterator,
 };
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 pub use std::ops::{Add, Div, Mul, Neg, Sub};
 
@@ -129,7 +130,129 @@ pub fn resize<T: TensorType>(a: &Tensor<T>, scales: &[usize]) -> Result<Tensor<T
     Ok(output)
 }
 
-/// Matrix multiplies two 2D tensors.
+/// The mapping from output to input coordinates used when resizing (see the ONNX Resize spec).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResizeCoordTransform {
+    /// `x_in = (x_out + 0.5) / scale - 0.5`
+    HalfPixel,
+    /// `x_in = x_out * (len_in - 1) / (len_out - 1)`
+    AlignCorners,
+    /// `x_in = x_out / scale`
+    Asymmetric,
+}
+
+/// The interpolation used when resizing a tensor.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ResizeMode {
+    /// Nearest neighbour interpolation (a pure index mapping)
+    Nearest,
+    /// Linear interpolation (e.g bilinear for the spatial axes of an image) between the two nearest neighbours of each resized axis.
+    /// The interpolation weights are quantized with `weight_scale` bits, so the output scale increases by `weight_scale` for every resized axis.
+    Linear {
+        /// the coordinate transformation
+        transform: ResizeCoordTransform,
+        /// the fixed point scale of the quantized interpolation weights
+        weight_scale: u32,
+    },
+}
+
+/// Computes the taps of a 1D linear interpolation resizing an axis of length `len` by `scale`.
+/// For every output index this returns the two input indices being interpolated with their quantized weights, which always sum to `2^weight_scale`.
+/// # Examples
+/// ```
+/// use ezkl::tensor::ops::{linear_resize_taps, ResizeCoordTransform};
+/// let taps = linear_resize_taps(2, 2, ResizeCoordTransform::HalfPixel, 2);
+/// assert_eq!(taps, vec![(0, 1, 4, 0), (0, 1, 3, 1), (0, 1, 1, 3), (1, 1, 4, 0)]);
+/// let taps = linear_resize_taps(2, 2, ResizeCoordTransform::Asymmetric, 1);
+/// assert_eq!(taps, vec![(0, 1, 2, 0), (0, 1, 1, 1), (1, 1, 2, 0), (1, 1, 2, 0)]);
+/// ```
+pub fn linear_resize_taps(
+    len: usize,
+    scale: usize,
+    transform: ResizeCoordTransform,
+    weight_scale: u32,
+) -> Vec<(usize, usize, i128, i128)> {
+    let out_len = len * scale;
+    let one = 1i128 << weight_scale;
+    (0..out_len)
+        .map(|o| {
+            let x = match transform {
+                ResizeCoordTransform::HalfPixel => (o as f64 + 0.5) / scale as f64 - 0.5,
+                ResizeCoordTransform::AlignCorners => {
+                    if out_len > 1 {
+                        o as f64 * (len - 1) as f64 / (out_len - 1) as f64
+                    } else {
+                        0.0
+                    }
+                }
+                ResizeCoordTransform::Asymmetric => o as f64 / scale as f64,
+            };
+            let x = x.clamp(0.0, (len - 1) as f64);
+            let lo = x.floor() as usize;
+            let hi = std::cmp::min(lo + 1, len - 1);
+            let w_hi = ((x - lo as f64) * one as f64).round() as i128;
+            (lo, hi, one - w_hi, w_hi)
+        })
+        .collect()
+}
+
+/// Resize using linear interpolation between the two nearest neighbours of every axis with a scale greater than 1.
+/// The quantized weights (see [linear_resize_taps]) are converted to `T` using `weight`.
+/// # Arguments
+/// * `a` - Tensor
+/// * `scales` - Vector of scales
+/// * `transform` - The coordinate transformation
+/// * `weight_scale` - The fixed point scale of the interpolation weights
+/// * `weight` - Converts a quantized weight to `T`
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::{resize_linear, ResizeCoordTransform};
+/// let a = Tensor::<i128>::new(Some(&[0, 4, 8, 12]), &[1, 1, 2, 2]).unwrap();
+/// let result = resize_linear(&a, &[1, 1, 2, 2], ResizeCoordTransform::HalfPixel, 2, |w| w).unwrap();
+/// // the output carries the scale of both axes' weights (2^2 * 2^2)
+/// let expected = Tensor::<i128>::new(
+///     Some(&[0, 1, 3, 4, 2, 3, 5, 6, 6, 7, 9, 10, 8, 9, 11, 12].map(|x| x * 16)),
+///     &[1, 1, 4, 4],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn resize_linear<T: TensorType + Add<Output = T> + Mul<Output = T>>(
+    a: &Tensor<T>,
+    scales: &[usize],
+    transform: ResizeCoordTransform,
+    weight_scale: u32,
+    weight: impl Fn(i128) -> T,
+) -> Result<Tensor<T>, TensorError> {
+    if scales.len() != a.dims().len() {
+        return Err(TensorError::DimMismatch("resize".to_string()));
+    }
+
+    let mut output = a.clone();
+    for (axis, scale) in scales.iter().enumerate() {
+        if *scale == 1 {
+            continue;
+        }
+        let taps = linear_resize_taps(output.dims()[axis], *scale, transform, weight_scale);
+        let lo = Tensor::from(taps.iter().map(|t| t.0));
+        let hi = Tensor::from(taps.iter().map(|t| t.1));
+        let lo = gather(&output, axis, &lo)?;
+        let hi = gather(&output, axis, &hi)?;
+
+        // stride of the resized axis in the flattened output
+        let stride = lo.dims()[axis + 1..].iter().product::<usize>();
+        let len = lo.dims()[axis];
+        output = lo.enum_map(|i, x| {
+            let (_, _, w_lo, w_hi) = taps[(i / stride) % len];
+            Ok::<_, TensorError>(x * weight(w_lo) + hi[i].clone() * weight(w_hi))
+        })?;
+    }
+
+    Ok(output)
+}
+
+/// Matrix multiplies two tensors. Leading (batch) dimensions are broadcast against one another per numpy's
+/// matmul semantics, and the matrices of each batch are multiplied.
 /// # Arguments
 ///
 /// * `inputs` - Vector of tensors of length 2
@@ -149,6 +272,36 @@ pub fn resize<T: TensorType>(a: &Tensor<T>, scales: &[usize]) -> Result<Tensor<T
 /// let result = matmul(&vec![k, x]).unwrap();
 /// let expected = Tensor::<i128>::new(Some(&[26, 7, 11, 3, 15, 3, 7, 2]), &[2, 4]).unwrap();
 /// assert_eq!(result, expected);
+///
+/// // batched
+/// let a = Tensor::<i128>::new(Some(&(0..24).collect::<Vec<_>>()), &[2, 3, 4]).unwrap();
+/// let b = Tensor::<i128>::new(Some(&(0..40).collect::<Vec<_>>()), &[2, 4, 5]).unwrap();
+/// let result = matmul(&vec![a.clone(), b]).unwrap();
+/// let expected = Tensor::<i128>::new(
+///     Some(&[
+///         70, 76, 82, 88, 94, 190, 212, 234, 256, 278, 310, 348, 386, 424, 462, 1510, 1564, 1618,
+///         1672, 1726, 1950, 2020, 2090, 2160, 2230, 2390, 2476, 2562, 2648, 2734,
+///     ]),
+///     &[2, 3, 5],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+///
+/// // the batch dimension of a single matrix is broadcast
+/// let b = Tensor::<i128>::new(Some(&(0..20).collect::<Vec<_>>()), &[4, 5]).unwrap();
+/// let result = matmul(&vec![a, b]).unwrap();
+/// let expected = Tensor::<i128>::new(
+///     Some(&[
+///         70, 76, 82, 88, 94, 190, 212, 234, 256, 278, 310, 348, 386, 424, 462, 430, 484, 538, 592,
+///         646, 550, 620, 690, 760, 830, 670, 756, 842, 928, 1014,
+///     ]),
+///     &[2, 3, 5],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+///
+/// // inner dimensions must match
+/// let b = Tensor::<i128>::new(Some(&(0..15).collect::<Vec<_>>()), &[3, 5]).unwrap();
+/// let a = Tensor::<i128>::new(Some(&(0..24).collect::<Vec<_>>()), &[2, 3, 4]).unwrap();
+/// assert!(matmul(&vec![a, b]).is_err());
 /// ```
 pub fn matmul<
     T: TensorType + Mul<Output = T> + Add<Output = T> + std::marker::Send + std::marker::Sync,
@@ -164,12 +317,10 @@ pub fn matmul<
         b.reshape(&[b.dims()[0], 1]);
     }
 
-    if (inputs.len() != 2)
-        || (a.dims()[a.dims().len() - 1] != b.dims()[a.dims().len() - 2])
-        || (a.dims()[0..a.dims().len() - 2] != b.dims()[0..a.dims().len() - 2])
-    {
+    if inputs.len() != 2 {
         return Err(TensorError::DimMismatch("matmul".to_string()));
     }
+    let (a, b) = broadcast_matmul_batches(&a, &b)?;
 
     let mut dims = Vec::from(&a.dims()[0..a.dims().len() - 2]);
     dims.push(a.dims()[a.dims().len() - 2]);
@@ -209,6 +360,68 @@ pub fn matmul<
     Ok(output)
 }
 
+/// Broadcasts the leading (batch) dimensions of two matmul operands against one another per numpy's semantics
+/// (aligned from the right, with dimensions of 1 stretched), such that both operands have the same batch dimensions.
+fn broadcast_matmul_batches<T: TensorType + Send + Sync>(
+    a: &Tensor<T>,
+    b: &Tensor<T>,
+) -> Result<(Tensor<T>, Tensor<T>), TensorError> {
+    let (a_rank, b_rank) = (a.dims().len(), b.dims().len());
+    if a_rank < 2 || b_rank < 2 || a.dims()[a_rank - 1] != b.dims()[b_rank - 2] {
+        return Err(TensorError::DimMismatch("matmul".to_string()));
+    }
+
+    let (a_batch, b_batch) = (&a.dims()[..a_rank - 2], &b.dims()[..b_rank - 2]);
+    let rank = a_batch.len().max(b_batch.len());
+    // left pads batch dimensions with 1s up to `rank`
+    let padded = |batch: &[usize]| {
+        let mut dims = vec![1; rank - batch.len()];
+        dims.extend(batch);
+        dims
+    };
+    let (a_batch, b_batch) = (padded(a_batch), padded(b_batch));
+
+    let mut batch = Vec::with_capacity(rank);
+    for (da, db) in a_batch.iter().zip(&b_batch) {
+        if da != db && *da != 1 && *db != 1 {
+            return Err(TensorError::DimMismatch("matmul".to_string()));
+        }
+        batch.push(*da.max(db));
+    }
+
+    let broadcast = |t: &Tensor<T>, t_batch: Vec<usize>| {
+        let matrix = &t.dims()[t.dims().len() - 2..];
+        let mut t = t.clone();
+        t.reshape(&[t_batch, matrix.to_vec()].concat());
+        t.expand(&[batch.clone(), matrix.to_vec()].concat())
+    };
+    Ok((broadcast(a, a_batch)?, broadcast(b, b_batch)?))
+}
+
+/// The size of each index of the einsum `inputs_eq` over inputs of `dims`. An index of size 1 in some inputs is
+/// broadcast against the size it has in the others per numpy's semantics, as the batch dimensions of a batched
+/// matmul are (see [broadcast_matmul_batches]), so the inputs have to be expanded to these sizes before summing.
+pub(crate) fn einsum_index_sizes(
+    inputs_eq: &[&str],
+    dims: &[&[usize]],
+) -> Result<HashMap<char, usize>, TensorError> {
+    let mut indices_to_size = HashMap::new();
+    for (eq, dims) in inputs_eq.iter().zip(dims) {
+        if eq.len() != dims.len() {
+            return Err(TensorError::DimMismatch("einsum".to_string()));
+        }
+        for (c, d) in eq.chars().zip(dims.iter()) {
+            let size = indices_to_size.entry(c).or_insert(*d);
+            if *size == 1 {
+                *size = *d;
+            } else if *d != 1 && *size != *d {
+                return Err(TensorError::DimMismatch("einsum".to_string()));
+            }
+        }
+    }
+    Ok(indices_to_size)
+}
+
 /// Computes the einstein sum of a set of tensors.
 /// # Arguments
 /// * `equation` - Einstein summation equation
@@ -362,6 +575,12 @@ pub fn matmul<
 /// let expected = Tensor::<i128>::new(Some(&[41, 68]), &[2, 1]).unwrap();
 /// assert_eq!(result, expected);
 ///
+/// // batch dimensions of 1 are broadcast, as in a batched matmul
+/// let a = Tensor::<i128>::new(Some(&(0..24).collect::<Vec<_>>()), &[2, 3, 4]).unwrap();
+/// let b = Tensor::<i128>::new(Some(&(0..20).collect::<Vec<_>>()), &[1, 4, 5]).unwrap();
+/// let result = einsum("bmk,bkn->bmn", &[a.clone(), b.clone()]).unwrap();
+/// assert_eq!(result, ezkl::tensor::ops::matmul(&[a, b]).unwrap());
+///
 /// ```
 pub fn einsum<
     T: TensorType + Mul<Output = T> + Add<Output = T> + std::marker::Send + std::marker::Sync,
@@ -381,17 +600,17 @@ pub fn einsum<
         return Err(TensorError::DimMismatch("einsum".to_string()));
     }
 
-    let mut indices_to_size = HashMap::new();
-    for (i, input) in inputs.iter().enumerate() {
-        for j in 0..inputs_eq[i].len() {
-            let c = inputs_eq[i].chars().nth(j).unwrap();
-            if let std::collections::hash_map::Entry::Vacant(e) = indices_to_size.entry(c) {
-                e.insert(input.dims()[j]);
-            } else if indices_to_size[&c] != input.dims()[j] {
-                return Err(TensorError::DimMismatch("einsum".to_string()));
-            }
-        }
-    }
+    let mut indices_to_size = einsum_index_sizes(
+        &inputs_eq,
+        &inputs.iter().map(|input| input.dims()).collect::<Vec<_>>(),
+    )?;
+    let inputs = inputs
+        .iter()
+        .zip(&inputs_eq)
+        .map(|(input, eq)| {
+            input.expand(&eq.chars().map(|c| indices_to_size[&c]).collect::<Vec<_>>())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
     // maps unrepresented indices in the output to a trivial 1
     for c in output_eq.chars() {
@@ -628,6 +847,81 @@ pub fn neg<T: TensorType + Neg<Output = T> + std::marker::Send + std::marker::Sy
     Ok(-t.clone())
 }
 
+/// Elementwise compares two (broadcastable) tensors through the sign of their difference, outputting 1 where
+/// `pred(a - b)` holds and 0 elsewhere.
+fn compare(
+    a: &Tensor<i128>,
+    b: &Tensor<i128>,
+    pred: impl Fn(i128) -> bool,
+) -> Result<Tensor<i128>, TensorError> {
+    crate::tensor::get_broadcasted_shape(a.dims(), b.dims())
+        .map_err(|_| TensorError::DimMismatch("comparison".to_string()))?;
+    Ok((a.clone() - b.clone())?.map(|d| i128::from(pred(d))))
+}
+
+/// Elementwise greater than (`a > b`), outputting a boolean (0/1) tensor.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor, broadcast against `a`
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::greater;
+/// let x = Tensor::<i128>::new(Some(&[1, 5, 3, 2]), &[2, 2]).unwrap();
+/// let k = Tensor::<i128>::new(Some(&[2, 4, 3, 1]), &[2, 2]).unwrap();
+/// let result = greater(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[0, 1, 0, 1]), &[2, 2]).unwrap();
+/// assert_eq!(result, expected);
+///
+/// // broadcasts a single value
+/// let k = Tensor::<i128>::new(Some(&[2]), &[1]).unwrap();
+/// let result = greater(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[0, 1, 1, 0]), &[2, 2]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn greater(a: &Tensor<i128>, b: &Tensor<i128>) -> Result<Tensor<i128>, TensorError> {
+    compare(a, b, |d| d > 0)
+}
+
+/// Elementwise less than (`a < b`), outputting a boolean (0/1) tensor.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor, broadcast against `a`
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::less;
+/// let x = Tensor::<i128>::new(Some(&[1, 5, 3, 2]), &[2, 2]).unwrap();
+/// let k = Tensor::<i128>::new(Some(&[2, 4, 3, 1]), &[2, 2]).unwrap();
+/// let result = less(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[1, 0, 0, 0]), &[2, 2]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn less(a: &Tensor<i128>, b: &Tensor<i128>) -> Result<Tensor<i128>, TensorError> {
+    compare(a, b, |d| d < 0)
+}
+
+/// Elementwise equality (`a == b`), outputting a boolean (0/1) tensor.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor, broadcast against `a`
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::equal;
+/// let x = Tensor::<i128>::new(Some(&[1, 5, 3, 2]), &[2, 2]).unwrap();
+/// let k = Tensor::<i128>::new(Some(&[2, 4, 3, 1]), &[2, 2]).unwrap();
+/// let result = equal(&x, &k).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[0, 0, 1, 0]), &[2, 2]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn equal(a: &Tensor<i128>, b: &Tensor<i128>) -> Result<Tensor<i128>, TensorError> {
+    compare(a, b, |d| d == 0)
+}
+
 /// Elementwise multiplies multiple tensors.
 /// # Arguments
 ///
@@ -674,6 +968,62 @@ pub fn mult<T: TensorType + Mul<Output = T> + std::marker::Send + std::marker::S
     Ok(output)
 }
 
+/// Elementwise divides `a` by `b`, broadcasting them against one another, with the quotients rounded to the nearest
+/// integer. Halves are rounded such that the remainder `a * scale - q * b` is positive (down when dividing by a
+/// positive number and up when dividing by a negative one), i.e `q` is the unique integer with `-|b| < 2r <= |b|`.
+/// `a` is multiplied by `scale` first, such that when `scale` is the multiplier of `b`'s scale the quotient of two
+/// fixed point tensors has the scale of `a`.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Tensor
+/// * `scale` - Single value
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::div;
+/// let x = Tensor::<i128>::new(
+///     Some(&[6, -7, 5, 1, 0, 3]),
+///     &[2, 3],
+/// ).unwrap();
+/// let k = Tensor::<i128>::new(
+///     Some(&[2, 2, -2]),
+///     &[1, 3],
+/// ).unwrap();
+/// let result = div(&x, &k, 1).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[3, -4, -2, 0, 0, -1]), &[2, 3]).unwrap();
+/// assert_eq!(result, expected);
+///
+/// // at a scale of 2^2, 1.5 / 0.75 = 2
+/// let x = Tensor::<i128>::new(Some(&[6]), &[1]).unwrap();
+/// let k = Tensor::<i128>::new(Some(&[3]), &[1]).unwrap();
+/// let result = div(&x, &k, 4).unwrap();
+/// assert_eq!(result, Tensor::<i128>::new(Some(&[8]), &[1]).unwrap());
+///
+/// // dividing by zero is an error
+/// let k = Tensor::<i128>::new(Some(&[0]), &[1]).unwrap();
+/// assert!(div(&x, &k, 1).is_err());
+/// ```
+pub fn div(a: &Tensor<i128>, b: &Tensor<i128>, scale: usize) -> Result<Tensor<i128>, TensorError> {
+    let shape = crate::tensor::get_broadcasted_shape(a.dims(), b.dims())
+        .map_err(|e| TensorError::DimMismatch(e.to_string()))?;
+    let (a, b) = (a.expand(&shape)?, b.expand(&shape)?);
+
+    let quotients = a
+        .iter()
+        .zip(b.iter())
+        .map(|(n, d)| {
+            if *d == 0 {
+                return Err(TensorError::DivisionByZero);
+            }
+            let (n, abs_d) = (n * scale as i128, d.abs());
+            // 2 * (n - q * d) lies in (-|d|, |d|]
+            Ok(-d.signum() * (abs_d - 2 * n).div_euclid(2 * abs_d))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Tensor::new(Some(&quotients), &shape)
+}
+
 /// Rescale a tensor with a const integer (similar to const_mult).
 /// # Arguments
 ///
@@ -796,6 +1146,28 @@ pub fn downsample<T: TensorType>(
     Ok(output)
 }
 
+/// Normalizes an axis that may be negative (counting back from the last axis, as per the ONNX spec) for a tensor of
+/// rank `rank`, erroring if it is out of range.
+/// # Examples
+/// ```
+/// use ezkl::tensor::ops::normalize_axis;
+/// assert_eq!(normalize_axis(-1, 3).unwrap(), 2);
+/// assert_eq!(normalize_axis(-3, 3).unwrap(), 0);
+/// assert_eq!(normalize_axis(1, 3).unwrap(), 1);
+/// assert!(normalize_axis(3, 3).is_err());
+/// assert!(normalize_axis(-4, 3).is_err());
+/// ```
+pub fn normalize_axis(axis: i64, rank: usize) -> Result<usize, TensorError> {
+    let normalized = if axis < 0 { axis + rank as i64 } else { axis };
+    if normalized < 0 || normalized >= rank as i64 {
+        return Err(TensorError::DimMismatch(format!(
+            "axis {} is out of range for a tensor of rank {}",
+            axis, rank
+        )));
+    }
+    Ok(normalized as usize)
+}
+
 /// Gathers a tensor along a dimension.
 /// # Arguments
 /// * `input` - Tensor
@@ -828,61 +1200,51 @@ pub fn gather<T: TensorType>(
 
     assert!(index.dims().len() == 1, "Index must be 1D for now");
 
-    // Allocate memory for the output tensor
-    let mut output = Tensor::new(None, &output_size)?;
-    let cartesian_coord = output_size
-        .iter()
-        .map(|x| 0..*x)
-        .multi_cartesian_product()
+    // each output element is taken from the input at its coordinates, with the gathered dim's coordinate indexed
+    let output = Tensor::<usize>::new(None, &output_size)?
+        .enumerate_indices()
+        .map(|(mut coord, _)| {
+            coord[dim] = index[coord[dim]];
+            input.get(&coord)
+        })
         .collect::<Vec<_>>();
 
-    output = output.enum_map(|i, _: T| {
-        let coord = cartesian_coord[i].clone();
-        let index_val = index.get(&[coord[dim]]);
-        let new_coord = coord
-            .iter()
-            .enumerate()
-            .map(|(i, x)| if i == dim { index_val } else { *x })
-            .collect::<Vec<_>>();
-
-        Ok(input.get(&new_coord))
-    })?;
-
-    // Reshape the output tensor
-    output.reshape(&output_size);
-
-    Ok(output)
+    Tensor::new(Some(&output), &output_size)
 }
 
-/// Sums a tensor along specific axes.
+/// Reduces a tensor along specific axes, applying `f` to each slice spanning the reduced axes.
+/// The reduced axes are kept in the output with a dimension of 1.
 /// # Arguments
 ///
 /// * `a` - Tensor
-/// * `b` - Single value
+/// * `axes` - the axes to reduce over
+/// * `f` - the reduction applied to each slice
 /// # Examples
 /// ```
 /// use ezkl::tensor::Tensor;
-/// use ezkl::tensor::ops::sum_axes;
+/// use ezkl::tensor::ops::reduce_axes;
 /// let x = Tensor::<i128>::new(
 ///     Some(&[2, 15, 2, 1, 1, 0]),
 ///     &[2, 3],
 /// ).unwrap();
-/// let result = sum_axes(&x, &[1]).unwrap();
+/// let result = reduce_axes(&x, &[0], |s| Ok(s[0] * s[1])).unwrap();
 /// let expected = Tensor::<i128>::new(
-///     Some(&[19, 2]),
-///     &[2, 1],
+///     Some(&[2, 15, 0]),
+///     &[1, 3],
 /// ).unwrap();
 /// assert_eq!(result, expected);
 /// ```
-pub fn sum_axes<T: TensorType + Add<Output = T>>(
+pub fn reduce_axes<T: TensorType>(
     a: &Tensor<T>,
     axes: &[usize],
+    f: impl Fn(&Tensor<T>) -> Result<T, TensorError>,
 ) -> Result<Tensor<T>, TensorError> {
-    // calculate value of output
-
     if axes.is_empty() {
         return Ok(a.clone());
     }
+    if axes.iter().any(|axis| *axis >= a.dims().len()) {
+        return Err(TensorError::DimError);
+    }
 
     let mut new_dims = vec![];
     for i in 0..a.dims().len() {
@@ -902,21 +1264,48 @@ pub fn sum_axes<T: TensorType + Add<Output = T>>(
         .collect::<Vec<_>>();
 
     for coord in cartesian_coord.iter() {
-        let mut sum_dims = vec![];
+        let mut slice_dims = vec![];
         for (i, c) in coord.iter().enumerate() {
             if axes.contains(&i) {
-                sum_dims.push(0..a.dims()[i]);
+                slice_dims.push(0..a.dims()[i]);
             } else {
-                sum_dims.push(*c..*c + 1);
+                slice_dims.push(*c..*c + 1);
             }
         }
 
-        res.set(coord, sum(&a.get_slice(&sum_dims)?)?[0].clone());
+        res.set(coord, f(&a.get_slice(&slice_dims)?)?);
     }
 
     Ok(res)
 }
 
+/// Sums a tensor along specific axes.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `b` - Single value
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::sum_axes;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 15, 2, 1, 1, 0]),
+///     &[2, 3],
+/// ).unwrap();
+/// let result = sum_axes(&x, &[1]).unwrap();
+/// let expected = Tensor::<i128>::new(
+///     Some(&[19, 2]),
+///     &[2, 1],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn sum_axes<T: TensorType + Add<Output = T>>(
+    a: &Tensor<T>,
+    axes: &[usize],
+) -> Result<Tensor<T>, TensorError> {
+    reduce_axes(a, axes, |slice| Ok(sum(slice)?[0].clone()))
+}
+
 /// Mins a tensor along specific axes.
 /// # Arguments
 ///
@@ -941,43 +1330,9 @@ pub fn min_axes<T: TensorType + Add<Output = T> + std::cmp::Ord>(
     a: &Tensor<T>,
     axes: &[usize],
 ) -> Result<Tensor<T>, TensorError> {
-    // calculate value of output
-
-    if axes.is_empty() {
-        return Ok(a.clone());
-    }
-
-    let mut new_dims = vec![];
-    for i in 0..a.dims().len() {
-        if !axes.contains(&i) {
-            new_dims.push(a.dims()[i]);
-        } else {
-            new_dims.push(1);
-        }
-    }
-
-    let mut res = Tensor::new(None, &new_dims)?;
-
-    let cartesian_coord = new_dims
-        .iter()
-        .map(|x| 0..*x)
-        .multi_cartesian_product()
-        .collect::<Vec<_>>();
-
-    for coord in cartesian_coord.iter() {
-        let mut sum_dims = vec![];
-        for (i, c) in coord.iter().enumerate() {
-            if axes.contains(&i) {
-                sum_dims.push(0..a.dims()[i]);
-            } else {
-                sum_dims.push(*c..*c + 1);
-            }
-        }
-
-        res.set(coord, a.get_slice(&sum_dims)?.iter().min().unwrap().clone());
-    }
-
-    Ok(res)
+    reduce_axes(a, axes, |slice| {
+        slice.iter().min().cloned().ok_or(TensorError::DimError)
+    })
 }
 
 /// Abs a tensor.
@@ -1032,39 +1387,65 @@ pub fn max_axes<T: TensorType + Add<Output = T> + std::cmp::Ord>(
     a: &Tensor<T>,
     axes: &[usize],
 ) -> Result<Tensor<T>, TensorError> {
-    // calculate value of output
-
-    let mut new_dims = vec![];
-    for i in 0..a.dims().len() {
-        if !axes.contains(&i) {
-            new_dims.push(a.dims()[i]);
-        } else {
-            new_dims.push(1);
-        }
-    }
-
-    let mut res = Tensor::new(None, &new_dims)?;
-
-    let cartesian_coord = new_dims
-        .iter()
-        .map(|x| 0..*x)
-        .multi_cartesian_product()
-        .collect::<Vec<_>>();
+    reduce_axes(a, axes, |slice| {
+        slice.iter().max().cloned().ok_or(TensorError::DimError)
+    })
+}
 
-    for coord in cartesian_coord.iter() {
-        let mut sum_dims = vec![];
-        for (i, c) in coord.iter().enumerate() {
-            if axes.contains(&i) {
-                sum_dims.push(0..a.dims()[i]);
-            } else {
-                sum_dims.push(*c..*c + 1);
+/// Returns the index of the (first) maximum of a tensor along `axis`. The reduced axis is kept with a dimension of 1.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `axis` - the axis to reduce over
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::argmax_axes;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 15, 2, 1, 1, 0]),
+///     &[2, 3],
+/// ).unwrap();
+/// let result = argmax_axes(&x, 1).unwrap();
+/// let expected = Tensor::<i128>::new(
+///     Some(&[1, 0]),
+///     &[2, 1],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn argmax_axes(a: &Tensor<i128>, axis: usize) -> Result<Tensor<i128>, TensorError> {
+    reduce_axes(a, &[axis], |slice| {
+        let mut best: Option<(usize, i128)> = None;
+        for (i, v) in slice.iter().enumerate() {
+            if best.map_or(true, |(_, b)| *v > b) {
+                best = Some((i, *v));
             }
         }
+        best.map(|(i, _)| i as i128).ok_or(TensorError::DimError)
+    })
+}
 
-        res.set(coord, a.get_slice(&sum_dims)?.iter().max().unwrap().clone());
-    }
-
-    Ok(res)
+/// Returns the index of the (first) minimum of a tensor along `axis`. The reduced axis is kept with a dimension of 1.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `axis` - the axis to reduce over
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::argmin_axes;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 15, 2, 1, 1, 0]),
+///     &[2, 3],
+/// ).unwrap();
+/// let result = argmin_axes(&x, 0).unwrap();
+/// let expected = Tensor::<i128>::new(
+///     Some(&[1, 1, 1]),
+///     &[1, 3],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn argmin_axes(a: &Tensor<i128>, axis: usize) -> Result<Tensor<i128>, TensorError> {
+    argmax_axes(&a.map(|x| -x), axis)
 }
 
 /// Applies convolution over a 3D tensor of shape C x H x W (and adds a bias).
@@ -1073,6 +1454,7 @@ pub fn max_axes<T: TensorType + Add<Output = T> + std::cmp::Ord>(
 /// * `inputs` - A vector of tensors holding in order: input image, convolution kernel, convolution bias.
 /// * `padding` - Tuple of padding values in x and y directions.
 /// * `stride` - Tuple of stride values in x and y directions.
+/// * `pad_value` - The value the padded borders of the image are filled with.
 /// # Examples
 /// ```
 /// // expected ouputs are taken from pytorch torch.nn.functional.conv2d
@@ -1092,7 +1474,7 @@ pub fn max_axes<T: TensorType + Add<Output = T> + std::cmp::Ord>(
 ///     Some(&[0]),
 ///     &[1],
 /// ).unwrap();
-/// let result = conv::<i128>(&[x, k, b], [(0, 0); 2], (1, 1)).unwrap();
+/// let result = conv::<i128>(&[x, k, b], [(0, 0); 2], (1, 1), 0).unwrap();
 /// let expected = Tensor::<i128>::new(Some(&[31, 16, 8, 26]), &[1, 1, 2, 2]).unwrap();
 /// assert_eq!(result, expected);
 ///
@@ -1110,7 +1492,7 @@ pub fn max_axes<T: TensorType + Add<Output = T> + std::cmp::Ord>(
 ///     &[2],
 /// ).unwrap();
 ///
-/// let result = conv::<i128>(&[x, k, b], [(0, 0); 2], (1, 1)).unwrap();
+/// let result = conv::<i128>(&[x, k, b], [(0, 0); 2], (1, 1), 0).unwrap();
 /// let expected = Tensor::<i128>::new(Some(&[32, 17, 9, 27, 34, 20, 13, 26]), &[1, 2, 2, 2]).unwrap();
 /// assert_eq!(result, expected);
 ///
@@ -1128,9 +1510,16 @@ pub fn max_axes<T: TensorType + Add<Output = T> + std::cmp::Ord>(
 ///     &[4],
 /// ).unwrap();
 ///
-/// let result = conv::<i128>(&[x, k, b], [(0, 0); 2], (1, 1)).unwrap();
+/// let result = conv::<i128>(&[x, k, b], [(0, 0); 2], (1, 1), 0).unwrap();
 /// let expected = Tensor::<i128>::new(Some(&[65, 36, 21, 52, 73, 48, 37, 48, 65, 36, 21, 52, 73, 48, 37, 48]), &[1, 4, 2, 2]).unwrap();
 /// assert_eq!(result, expected);
+///
+/// // padding with a non-zero value (e.g a quantization zero-point)
+/// let x = Tensor::<i128>::new(Some(&[1, 2, 3, 4]), &[1, 1, 2, 2]).unwrap();
+/// let k = Tensor::<i128>::new(Some(&[1, 1, 1, 1]), &[1, 1, 2, 2]).unwrap();
+/// let result = conv::<i128>(&[x, k], [(1, 1); 2], (1, 1), 3).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[10, 9, 11, 10, 10, 12, 12, 13, 13]), &[1, 1, 3, 3]).unwrap();
+/// assert_eq!(result, expected);
 /// ```
 pub fn conv<
     T: TensorType + Mul<Output = T> + Add<Output = T> + std::marker::Sync + std::marker::Send,
@@ -1138,6 +1527,7 @@ pub fn conv<
     inputs: &[Tensor<T>],
     padding: [(usize, usize); 2],
     stride: (usize, usize),
+    pad_value: T,
 ) -> Result<Tensor<T>, TensorError> {
     let has_bias = inputs.len() == 3;
     let (image, kernel) = (&mut inputs[0].clone(), &inputs[1]);
@@ -1179,7 +1569,7 @@ pub fn conv<
 
     let (image_height, image_width) = (image_dims[2], image_dims[3]);
 
-    let padded_image = pad::<T>(image, padding)?;
+    let padded_image = pad_with_value::<T>(image, padding, pad_value)?;
 
     let vert_slides = (image_height + padding[0].0 + padding[1].0 - kernel_height) / stride.0 + 1;
     let horz_slides = (image_width + padding[0].1 + padding[1].1 - kernel_width) / stride.1 + 1;
@@ -1524,7 +1914,7 @@ pub fn deconv<
         vec![sliced_expanded_image, deconv_kernel.clone()]
     };
 
-    let output = conv(&input, [(0, 0); 2], (1, 1))?;
+    let output = conv(&input, [(0, 0); 2], (1, 1), T::zero().unwrap())?;
 
     Ok(output)
 }
@@ -1767,6 +2157,36 @@ pub fn dot<T: TensorType + Mul<Output = T> + Add<Output = T>>(
 pub fn pad<T: TensorType>(
     image: &Tensor<T>,
     padding: [(usize, usize); 2],
+) -> Result<Tensor<T>, TensorError> {
+    pad_with_value(image, padding, T::zero().unwrap())
+}
+
+/// Pads a 4D tensor of shape `B x C x H x W` to a tensor of shape `B x C x (H + 2xPADDING) x (W + 2xPADDING)` using `value`.
+/// # Arguments
+///
+/// * `image` - Tensor.
+/// * `padding` - Tuple of padding values in x and y directions.
+/// * `value` - The value the padded region is filled with.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::pad_with_value;
+///
+/// let x = Tensor::<i128>::new(
+///     Some(&[5, 2, 3, 0]),
+///     &[1, 1, 2, 2],
+/// ).unwrap();
+/// let result = pad_with_value::<i128>(&x, [(1, 0), (0, 1)], 7).unwrap();
+/// let expected = Tensor::<i128>::new(
+///     Some(&[7, 7, 7, 5, 2, 7, 3, 0, 7]),
+///     &[1, 1, 3, 3],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn pad_with_value<T: TensorType>(
+    image: &Tensor<T>,
+    padding: [(usize, usize); 2],
+    value: T,
 ) -> Result<Tensor<T>, TensorError> {
     if image.dims().len() != 4 {
         return Err(TensorError::DimMismatch("pad".to_string()));
@@ -1783,23 +2203,126 @@ pub fn pad<T: TensorType>(
     let padded_height = height + padding_before.0 + padding_after.0;
     let padded_width = width + padding_before.1 + padding_after.1;
 
-    let mut output =
-        Tensor::<T>::new(None, &[batch_size, channels, padded_height, padded_width]).unwrap();
-
-    for b in 0..batch_size {
-        for channel in 0..channels {
-            for row in 0..height {
-                for col in 0..width {
-                    output.set(
-                        &[b, channel, row + padding_before.0, col + padding_before.1],
-                        image.get(&[b, channel, row, col]).clone(),
-                    );
-                }
-            }
+    let padded_dims = [batch_size, channels, padded_height, padded_width];
+    let mut output = Tensor::<T>::new(
+        Some(&vec![value; padded_dims.iter().product()]),
+        &padded_dims,
+    )?;
+
+    for (coord, value) in image.enumerate_indices() {
+        output.set(
+            &[
+                coord[0],
+                coord[1],
+                coord[2] + padding_before.0,
+                coord[3] + padding_before.1,
+            ],
+            value.clone(),
+        );
+    }
+
+    output.reshape(&padded_dims);
+    Ok(output)
+}
+
+/// The ways in which [pad_axes] can fill the padded region of a tensor.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PadMode<T> {
+    /// Fill the padded region with a constant value.
+    Constant(T),
+    /// Mirror the tensor about its edges (the edge values themselves are not repeated).
+    Reflect,
+}
+
+impl<T> PadMode<T> {
+    /// Maps the fill value (if any) of the padding mode.
+    pub fn map<G>(&self, f: impl Fn(&T) -> G) -> PadMode<G> {
+        match self {
+            PadMode::Constant(c) => PadMode::Constant(f(c)),
+            PadMode::Reflect => PadMode::Reflect,
         }
     }
+}
+
+/// Pads each axis of a tensor by a (before, after) number of elements.
+/// # Arguments
+///
+/// * `image` - Tensor.
+/// * `pads` - (before, after) padding for each axis of the tensor.
+/// * `mode` - How the padded region is filled.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::{pad_axes, PadMode};
+///
+/// let x = Tensor::<i128>::new(
+///     Some(&[5, 2, 3, 0, 4, -1, 3, 1, 6]),
+///     &[1, 1, 3, 3],
+/// ).unwrap();
+/// let result = pad_axes::<i128>(&x, &[(0, 0), (0, 0), (1, 1), (1, 1)], &PadMode::Constant(7)).unwrap();
+/// let expected = Tensor::<i128>::new(
+///     Some(&[7, 7, 7, 7, 7, 7, 5, 2, 3, 7, 7, 0, 4, -1, 7, 7, 3, 1, 6, 7, 7, 7, 7, 7, 7]),
+///     &[1, 1, 5, 5],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+///
+/// let result = pad_axes::<i128>(&x, &[(0, 0), (0, 0), (1, 1), (1, 1)], &PadMode::Reflect).unwrap();
+/// let expected = Tensor::<i128>::new(
+///     Some(&[4, 0, 4, -1, 4, 2, 5, 2, 3, 2, 4, 0, 4, -1, 4, 1, 3, 1, 6, 1, 4, 0, 4, -1, 4]),
+///     &[1, 1, 5, 5],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn pad_axes<T: TensorType>(
+    image: &Tensor<T>,
+    pads: &[(usize, usize)],
+    mode: &PadMode<T>,
+) -> Result<Tensor<T>, TensorError> {
+    let dims = image.dims().to_vec();
+    if pads.len() != dims.len() {
+        return Err(TensorError::DimMismatch("pad".to_string()));
+    }
+    // reflecting can't reach further than the tensor itself
+    if matches!(mode, PadMode::Reflect)
+        && pads
+            .iter()
+            .zip(dims.iter())
+            .any(|((before, after), d)| before >= d || after >= d)
+    {
+        return Err(TensorError::DimMismatch("reflect pad".to_string()));
+    }
+
+    let padded_dims = dims
+        .iter()
+        .zip(pads.iter())
+        .map(|(d, (before, after))| d + before + after)
+        .collect::<Vec<_>>();
 
-    output.reshape(&[batch_size, channels, padded_height, padded_width]);
+    let output = padded_dims
+        .iter()
+        .map(|d| 0..*d)
+        .multi_cartesian_product()
+        .map(|coord| {
+            let mut src = Vec::with_capacity(coord.len());
+            for ((c, (before, _)), d) in coord.iter().zip(pads.iter()).zip(dims.iter()) {
+                let (i, d) = (*c as isize - *before as isize, *d as isize);
+                if (0..d).contains(&i) {
+                    src.push(i as usize);
+                    continue;
+                }
+                match mode {
+                    PadMode::Constant(fill) => return fill.clone(),
+                    PadMode::Reflect => {
+                        let reflected = if i < 0 { -i } else { 2 * (d - 1) - i };
+                        src.push(reflected as usize);
+                    }
+                }
+            }
+            image.get(&src)
+        });
+
+    let mut output: Tensor<T> = output.into();
+    output.reshape(&padded_dims);
     Ok(output)
 }
 
@@ -2040,6 +2563,89 @@ pub mod nonlinearities {
         output
     }
 
+    /// Elementwise applies elu (`x` for non-negative `x`, `alpha * (exp(x) - 1)` otherwise) to a tensor of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// * `alpha` - The value elu saturates to for large negative inputs (negated)
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::elu;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[-256, -64, 0, 64, 256]),
+    ///     &[5],
+    /// ).unwrap();
+    /// let result = elu(&x, 256, 256, 1.0);
+    /// let expected = Tensor::<i128>::new(Some(&[-162, -57, 0, 64, 256]), &[5]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn elu(
+        a: &Tensor<i128>,
+        scale_input: usize,
+        scale_output: usize,
+        alpha: f64,
+    ) -> Tensor<i128> {
+        // calculate value of output
+        let mut output: Tensor<i128> = a.clone();
+
+        for (i, a_i) in a.iter().enumerate() {
+            let kix = (*a_i as f64) / (scale_input as f64);
+            let fout = match kix < 0.0 {
+                true => alpha * kix.exp_m1(),
+                false => kix,
+            };
+            let rounded = (fout * scale_output as f64).round();
+            output[i] = rounded as i128;
+        }
+        output
+    }
+
+    /// Elementwise applies selu (`gamma * x` for positive `x`, `gamma * alpha * (exp(x) - 1)` otherwise) to a tensor
+    /// of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// * `alpha` - As for [elu]
+    /// * `gamma` - The slope of the positive part
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::selu;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[-128, -64, 0, 64, 256]),
+    ///     &[5],
+    /// ).unwrap();
+    /// let result = selu(&x, 256, 256, 1.6732632, 1.0507010);
+    /// let expected = Tensor::<i128>::new(Some(&[-177, -100, 0, 67, 269]), &[5]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn selu(
+        a: &Tensor<i128>,
+        scale_input: usize,
+        scale_output: usize,
+        alpha: f64,
+        gamma: f64,
+    ) -> Tensor<i128> {
+        // calculate value of output
+        let mut output: Tensor<i128> = a.clone();
+
+        for (i, a_i) in a.iter().enumerate() {
+            let kix = (*a_i as f64) / (scale_input as f64);
+            let fout = match kix > 0.0 {
+                true => gamma * kix,
+                false => gamma * alpha * kix.exp_m1(),
+            };
+            let rounded = (fout * scale_output as f64).round();
+            output[i] = rounded as i128;
+        }
+        output
+    }
+
     /// Elementwise applies exponential to a tensor of integers.
     /// # Arguments
     ///
@@ -2232,6 +2838,105 @@ pub mod nonlinearities {
         ((exp * inv_denom).unwrap(), intermediate_values)
     }
 
+    /// The integer offset added to the sum of squares of an RMS normalization over `dim` elements before taking its
+    /// reciprocal square root: `eps` at the scale of the sum (`scale_input * dim`), and at least one unit such that the
+    /// rsqrt lookup is never evaluated at zero (where it saturates).
+    pub fn rms_norm_offset(eps: f32, scale_input: usize, dim: usize) -> i128 {
+        ((eps as f64) * (scale_input * dim) as f64).round().max(1.0) as i128
+    }
+
+    /// Applies [rms_norm] along the last axis of `a`, normalizing each of its slices along that axis independently.
+    pub fn multi_dim_rms_norm(
+        a: &Tensor<i128>,
+        scale_input: usize,
+        scale_output: usize,
+        eps: f32,
+    ) -> (Tensor<i128>, Vec<Tensor<i128>>) {
+        let dims = a.dims();
+
+        if dims.len() == 1 {
+            return rms_norm(a, scale_input, scale_output, eps);
+        }
+
+        let mut intermediate_values = vec![];
+
+        let cartesian_coord = dims[..dims.len() - 1]
+            .iter()
+            .map(|x| 0..*x)
+            .multi_cartesian_product()
+            .collect::<Vec<_>>();
+
+        let mut outputs = vec![];
+
+        for coord in cartesian_coord {
+            let mut norm_dims = vec![];
+            for c in coord {
+                norm_dims.push(c..c + 1);
+            }
+            norm_dims.push(0..dims[dims.len() - 1]);
+
+            let norm_input = a.get_slice(&norm_dims).unwrap();
+
+            let res = rms_norm(&norm_input, scale_input, scale_output, eps);
+
+            outputs.push(res.0);
+            intermediate_values.extend(res.1);
+        }
+
+        let mut res = Tensor::new(Some(&outputs), &[outputs.len()])
+            .unwrap()
+            .combine()
+            .unwrap();
+        res.reshape(dims);
+
+        (res, intermediate_values)
+    }
+
+    /// Applies RMS normalization, `x / sqrt(mean(x^2) + eps)`, to a vector of integers.
+    ///
+    /// The steps match the circuit's layout: the squares are brought back down to the input scale (a division lookup
+    /// over `x^2`, at twice the input scale), summed, offset by [rms_norm_offset], and the reciprocal square root of
+    /// their mean is looked up at the output scale and multiplied into `x`. The output is thus at the product of the
+    /// input and output scales. As both the squares and the offset sum of squares are lookup inputs, they have to lie
+    /// within the range of the lookup tables (`2^(bits - 1)`), which bounds `|x|` to `2^((bits - 1) / 2)` at the input
+    /// scale. The mean is rounded at the input scale, such that vectors with a small RMS relative to `1 / scale_input`
+    /// are normalized coarsely (see [rsqrt]).
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// * `eps` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::rms_norm;
+    /// let x = Tensor::<i128>::new(Some(&[64, -128, 32, 96]), &[4]).unwrap();
+    /// let result = rms_norm(&x, 128, 128, 1e-5).0;
+    /// // at the product of the input and output scales
+    /// let expected = Tensor::<i128>::new(Some(&[11968, -23936, 5984, 17952]), &[4]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn rms_norm(
+        a: &Tensor<i128>,
+        scale_input: usize,
+        scale_output: usize,
+        eps: f32,
+    ) -> (Tensor<i128>, Vec<Tensor<i128>>) {
+        let mut intermediate_values = vec![];
+
+        let squares = (a.clone() * a.clone()).unwrap();
+        intermediate_values.push(squares.clone());
+        let squares = const_div(&squares, scale_input as f64);
+
+        let offset = Tensor::from([rms_norm_offset(eps, scale_input, a.len())].into_iter());
+        let sum_squares = (sum(&squares).unwrap() + offset).unwrap();
+        intermediate_values.push(sum_squares.clone());
+        let inv_rms = rsqrt(&sum_squares, scale_input * a.len(), scale_output);
+
+        ((a.clone() * inv_rms).unwrap(), intermediate_values)
+    }
+
     /// Applies range_check_percent
     /// # Arguments
     ///
@@ -2307,6 +3012,9 @@ pub mod nonlinearities {
     }
 
     /// Elementwise applies reciprocal square root to a tensor of integers.
+    ///
+    /// The input is rounded at `scale_input`, so the relative error of the output grows towards zero, where the
+    /// output overflows (`rsqrt(0)` saturates to `i128::MAX`); negative inputs have no square root and map to 0.
     /// # Arguments
     ///
     /// * `a` - Tensor
@@ -2953,6 +3661,30 @@ pub mod nonlinearities {
         output
     }
 
+    /// Elementwise truncation towards zero of fixed point values at scale `scale_input` to integers at scale
+    /// `scale_output`, as an ONNX cast from a float to an integer type does.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `scale_output` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::trunc;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[348, -181, 73, -73, 256, 0]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let result = trunc(&x, 128, 128);
+    /// let expected = Tensor::<i128>::new(Some(&[256, -128, 0, 0, 256, 0]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn trunc(a: &Tensor<i128>, scale_input: usize, scale_output: usize) -> Tensor<i128> {
+        // integer division rounds towards zero
+        a.map(|a_i| (a_i / scale_input as i128) * scale_output as i128)
+    }
+
     /// Elementwise inverse.
     /// # Arguments
     ///
@@ -3176,11 +3908,7 @@ pub mod accumulated {
             b.reshape(&[b.dims()[0], 1]);
         }
 
-        if (a.dims()[a.dims().len() - 1] != b.dims()[a.dims().len() - 2])
-            || (a.dims()[0..a.dims().len() - 2] != b.dims()[0..a.dims().len() - 2])
-        {
-            return Err(TensorError::DimMismatch("matmul".to_string()));
-        }
+        let (a, b) = broadcast_matmul_batches(&a, &b)?;
 
         let mut dims = Vec::from(&a.dims()[0..a.dims().len() - 2]);
         dims.push(a.dims()[a.dims().len() - 2]);
@@ -1,5 +1,5 @@
 use super::{
-    ops::{intercalate_values, pad, resize},
+    ops::{intercalate_values, pad, pad_axes, pad_with_value, resize, PadMode},
     *,
 };
 use halo2_proofs::{arithmetic::Field, plonk::Instance};
@@ -571,6 +571,46 @@ impl<F: PrimeField + TensorType + PartialOrd> ValTensor<F> {
         Ok(())
     }
 
+    /// Calls `pad_with_value` on the inner [Tensor], filling the padded region with the constant `value`.
+    pub fn pad_with_value(
+        &mut self,
+        padding: [(usize, usize); 2],
+        value: F,
+    ) -> Result<(), TensorError> {
+        match self {
+            ValTensor::Value {
+                inner: v, dims: d, ..
+            } => {
+                *v = pad_with_value(v, padding, ValType::Constant(value))?;
+                *d = v.dims().to_vec();
+            }
+            ValTensor::Instance { .. } => {
+                return Err(TensorError::WrongMethod);
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `pad_axes` on the inner [Tensor].
+    pub fn pad_axes(
+        &mut self,
+        pads: &[(usize, usize)],
+        mode: &PadMode<ValType<F>>,
+    ) -> Result<(), TensorError> {
+        match self {
+            ValTensor::Value {
+                inner: v, dims: d, ..
+            } => {
+                *v = pad_axes(v, pads, mode)?;
+                *d = v.dims().to_vec();
+            }
+            ValTensor::Instance { .. } => {
+                return Err(TensorError::WrongMethod);
+            }
+        }
+        Ok(())
+    }
+
     /// Calls `len` on the inner [Tensor].
     pub fn len(&self) -> usize {
         match self {
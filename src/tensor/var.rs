@@ -36,14 +36,33 @@ impl VarTensor {
         logrows: usize,
         capacity: usize,
     ) -> Self {
+        let max_rows = Self::max_rows(cs, logrows);
+        let num_cols = Self::num_advice_columns(max_rows, capacity);
+        Self::new_advice_columns(cs, logrows, num_cols)
+    }
+
+    /// The number of rows of each advice column allocated in `cs`, those not reserved for blinding factors.
+    pub fn max_rows<F: PrimeField>(cs: &ConstraintSystem<F>, logrows: usize) -> usize {
         let base = 2u32;
-        let max_rows = base.pow(logrows as u32) as usize - cs.blinding_factors() - 1;
+        base.pow(logrows as u32) as usize - cs.blinding_factors() - 1
+    }
 
-        let mut modulo = (capacity / max_rows) + 1;
+    /// The number of columns of `max_rows` rows [VarTensor::new_advice] allocates to hold `capacity` cells.
+    pub fn num_advice_columns(max_rows: usize, capacity: usize) -> usize {
+        let modulo = (capacity / max_rows) + 1;
         // we add a buffer for duplicated rows (we get at most 1 duplicated row per column)
-        modulo = ((capacity + modulo) / max_rows) + 1;
+        ((capacity + modulo) / max_rows) + 1
+    }
+
+    /// Create a new VarTensor::Advice of exactly `num_cols` columns
+    pub fn new_advice_columns<F: PrimeField>(
+        cs: &mut ConstraintSystem<F>,
+        logrows: usize,
+        num_cols: usize,
+    ) -> Self {
+        let max_rows = Self::max_rows(cs, logrows);
         let mut advices = vec![];
-        for _ in 0..modulo {
+        for _ in 0..num_cols {
             let col = cs.advice_column();
             cs.enable_equality(col);
             advices.push(col);
@@ -32,7 +32,7 @@ pub fn init_panic_hook() {
 }
 
 use crate::graph::{GraphCircuit, GraphSettings};
-use crate::pfsys::{create_proof_circuit_kzg, verify_proof_circuit_kzg};
+use crate::pfsys::{create_proof_circuit_kzg, strip_checksum_header, verify_proof_circuit_kzg};
 
 /// Converts 4 u64s to a field element
 #[wasm_bindgen]
@@ -139,7 +139,8 @@ pub fn verify(
     circuit_settings_ser: wasm_bindgen::Clamped<Vec<u8>>,
     params_ser: wasm_bindgen::Clamped<Vec<u8>>,
 ) -> bool {
-    let mut reader = std::io::BufReader::new(&params_ser[..]);
+    let params_ser = strip_checksum_header(&params_ser[..]).unwrap();
+    let mut reader = std::io::BufReader::new(params_ser);
     let params: ParamsKZG<Bn256> =
         halo2_proofs::poly::commitment::Params::<'_, G1Affine>::read(&mut reader).unwrap();
 
@@ -148,7 +149,8 @@ pub fn verify(
 
     let snark: crate::pfsys::Snark<Fr, G1Affine> = serde_json::from_slice(&proof_js[..]).unwrap();
 
-    let mut reader = std::io::BufReader::new(&vk[..]);
+    let vk = strip_checksum_header(&vk[..]).unwrap();
+    let mut reader = std::io::BufReader::new(vk);
     let vk = VerifyingKey::<G1Affine>::read::<_, GraphCircuit>(
         &mut reader,
         halo2_proofs::SerdeFormat::RawBytes,
@@ -177,7 +179,8 @@ pub fn prove(
     params_ser: wasm_bindgen::Clamped<Vec<u8>>,
 ) -> Vec<u8> {
     // read in kzg params
-    let mut reader = std::io::BufReader::new(&params_ser[..]);
+    let params_ser = strip_checksum_header(&params_ser[..]).unwrap();
+    let mut reader = std::io::BufReader::new(params_ser);
     let params: ParamsKZG<Bn256> =
         halo2_proofs::poly::commitment::Params::<'_, G1Affine>::read(&mut reader).unwrap();
 
@@ -141,7 +141,7 @@ mod native_tests {
         "mnist_gan",
     ];
 
-    const TESTS: [&str; 43] = [
+    const TESTS: [&str; 45] = [
         "1l_mlp",
         "1l_slice",
         "1l_concat",
@@ -187,6 +187,8 @@ mod native_tests {
         "lstm",
         "rnn",
         "quantize_dequantize",
+        "1l_split",
+        "1l_dropout",
     ];
 
     const TESTS_AGGR: [&str; 20] = [
@@ -331,7 +333,7 @@ mod native_tests {
 
 
 
-            seq!(N in 0..=42 {
+            seq!(N in 0..=44 {
 
             #(#[test_case(TESTS[N])])*
             fn model_serialization_(test: &str) {
@@ -559,6 +561,15 @@ mod native_tests {
                test_dir.close().unwrap();
             }
 
+            #(#[test_case(TESTS[N])])*
+            fn kzg_prove_verify_(test: &str) {
+                crate::native_tests::init_binary();
+                let test_dir = TempDir::new(test).unwrap();
+                let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(test_dir.path().to_str().unwrap(), test);
+               kzg_prove_verify(path, test.to_string());
+               test_dir.close().unwrap();
+            }
+
             #(#[test_case(TESTS[N])])*
             fn kzg_fuzz_(test: &str) {
                 crate::native_tests::init_binary();
@@ -711,19 +722,54 @@ mod native_tests {
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(test_dir.path().to_str().unwrap(), test);
                     let _anvil_child = crate::native_tests::start_anvil();
-                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "private", "public");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "private", "public", 0, &["auto"]);
                     test_dir.close().unwrap();
 
                 }
 
 
+                #(#[test_case(TESTS_EVM[N])])*
+                fn kzg_evm_verifier_formats_prove_and_verify_(test: &str) {
+                    crate::native_tests::init_binary();
+                    let test_dir = TempDir::new(test).unwrap();
+                    let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(test_dir.path().to_str().unwrap(), test);
+                    let _anvil_child = crate::native_tests::start_anvil();
+                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "private", "public", 0, &["single", "split"]);
+                    test_dir.close().unwrap();
+                }
+
+                #[test]
+                fn kzg_evm_over_limit_verifier_prove_and_verify_() {
+                    let test = "1l_relu";
+                    crate::native_tests::init_binary();
+                    let test_dir = TempDir::new(test).unwrap();
+                    let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(test_dir.path().to_str().unwrap(), test);
+                    let _anvil_child = crate::native_tests::start_anvil();
+                    // the verifier evaluates every public input in turn, so padding them makes it exceed the contract size limit
+                    let srs_path = kzg_evm_prove_and_verify(path, test.to_string(), "public", "private", "public", 2048, &["auto", "split"]);
+                    assert!(!crate::native_tests::deploy_evm_verifier(path, test, &srs_path, "single"));
+                    test_dir.close().unwrap();
+                }
+
+                #[test]
+                fn kzg_evm_web3_json_prove_and_verify_() {
+                    let test = "1l_relu";
+                    crate::native_tests::init_binary();
+                    let test_dir = TempDir::new(test).unwrap();
+                    let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(test_dir.path().to_str().unwrap(), test);
+                    let _anvil_child = crate::native_tests::start_anvil();
+                    kzg_evm_prove_and_verify(path, test.to_string(), "public", "private", "public", 0, &["auto"]);
+                    crate::native_tests::kzg_evm_web3_json_verify(path, test);
+                    test_dir.close().unwrap();
+                }
+
                 #(#[test_case(TESTS_EVM[N])])*
                 fn kzg_evm_hashed_input_prove_and_verify_(test: &str) {
                     crate::native_tests::init_binary();
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(test_dir.path().to_str().unwrap(), test);
                     let mut _anvil_child = crate::native_tests::start_anvil();
-                    kzg_evm_prove_and_verify(path, test.to_string(), "hashed", "private", "private");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "hashed", "private", "private", 0, &["auto"]);
                     test_dir.close().unwrap();
                 }
 
@@ -733,7 +779,7 @@ mod native_tests {
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(test_dir.path().to_str().unwrap(), test);
                     let _anvil_child = crate::native_tests::start_anvil();
-                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "hashed", "public");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "hashed", "public", 0, &["auto"]);
                     test_dir.close().unwrap();
 
                 }
@@ -744,7 +790,7 @@ mod native_tests {
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(test_dir.path().to_str().unwrap(), test);
                     let _anvil_child = crate::native_tests::start_anvil();
-                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "private", "hashed");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "private", "hashed", 0, &["auto"]);
                     test_dir.close().unwrap();
 
                 }
@@ -1046,6 +1092,7 @@ mod native_tests {
                 &format!("--input-visibility={}", input_visibility),
                 &format!("--param-visibility={}", param_visibility),
                 &format!("--output-visibility={}", output_visibility),
+                &format!("--pad-public-inputs={}", pad_public_inputs),
             ])
             .status()
             .expect("failed to execute process");
@@ -1830,6 +1877,84 @@ mod native_tests {
         assert!(status.success());
     }
 
+    // prove and verify in one go, with the in-memory keys
+    fn kzg_prove_verify(test_dir: &str, example_name: String) {
+        let settings_path = format!("{}/{}/settings.json", test_dir, example_name);
+
+        let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+            .args([
+                "gen-settings",
+                "-M",
+                format!("{}/{}/network.onnx", test_dir, example_name).as_str(),
+                format!("--settings-path={}", settings_path).as_str(),
+            ])
+            .status()
+            .expect("failed to execute process");
+        assert!(status.success());
+
+        let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+            .args([
+                "calibrate-settings",
+                "--data",
+                format!("{}/{}/input.json", test_dir, example_name).as_str(),
+                "-M",
+                format!("{}/{}/network.onnx", test_dir, example_name).as_str(),
+                format!("--settings-path={}", settings_path).as_str(),
+            ])
+            .status()
+            .expect("failed to execute process");
+        assert!(status.success());
+
+        let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+            .args([
+                "compile-model",
+                "-M",
+                format!("{}/{}/network.onnx", test_dir, example_name).as_str(),
+                "--compiled-model",
+                format!("{}/{}/network.onnx", test_dir, example_name).as_str(),
+                format!("--settings-path={}", settings_path).as_str(),
+            ])
+            .status()
+            .expect("failed to execute process");
+        assert!(status.success());
+
+        let srs_path = init_params(test_dir, settings_path.clone().into());
+        let srs_path = format!("--srs-path={}", srs_path);
+
+        let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+            .args([
+                "gen-witness",
+                "-D",
+                format!("{}/{}/input.json", test_dir, example_name).as_str(),
+                "-M",
+                format!("{}/{}/network.onnx", test_dir, example_name).as_str(),
+                format!("--settings-path={}", settings_path).as_str(),
+                "-O",
+                format!("{}/{}/input.json", test_dir, example_name).as_str(),
+            ])
+            .status()
+            .expect("failed to execute process");
+        assert!(status.success());
+
+        let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+            .args([
+                "prove-verify",
+                "-W",
+                format!("{}/{}/input.json", test_dir, example_name).as_str(),
+                "-M",
+                format!("{}/{}/network.onnx", test_dir, example_name).as_str(),
+                &srs_path,
+                format!("--settings-path={}", settings_path).as_str(),
+                "--output-template",
+                &format!("{}/{}/proof.pf", test_dir, example_name),
+            ])
+            .status()
+            .expect("failed to execute process");
+        assert!(status.success());
+        // the verified proof is saved
+        assert!(std::path::Path::new(&format!("{}/{}/proof.pf", test_dir, example_name)).exists());
+    }
+
     // prove-serialize-verify, the usual full path
     fn kzg_fuzz(
         test_dir: &str,
@@ -1910,7 +2035,9 @@ mod native_tests {
         input_visibility: &str,
         param_visibility: &str,
         output_visibility: &str,
-    ) {
+        pad_public_inputs: usize,
+        verifier_formats: &[&str],
+    ) -> String {
         let anvil_url = ANVIL_URL.as_str();
 
         let settings_path = format!("{}/{}/settings.json", test_dir, example_name);
@@ -2009,76 +2136,154 @@ mod native_tests {
             .expect("failed to execute process");
         assert!(status.success());
 
-        let vk_arg = format!("{}/{}/key.vk", test_dir, example_name);
         let rpc_arg = format!("--rpc-url={}", anvil_url);
-        let addr_path_arg = format!("--addr-path={}/{}/addr.txt", test_dir, example_name);
-        let settings_arg = format!("--settings-path={}", settings_path);
 
-        // create the verifier
-        let mut args = vec![
-            "create-evm-verifier",
-            &srs_path,
-            "--vk-path",
-            &vk_arg,
-            &settings_arg,
-        ];
+        // every format of the verifier verifies the same proof
+        for verifier_format in verifier_formats {
+            assert!(deploy_evm_verifier(
+                test_dir,
+                &example_name,
+                &srs_path,
+                verifier_format
+            ));
 
-        let sol_arg = format!("{}/{}/kzg.sol", test_dir, example_name);
+            // read in the address
+            let addr = std::fs::read_to_string(format!("{}/{}/addr.txt", test_dir, example_name))
+                .expect("failed to read address file");
 
-        // create everything to test the pipeline
-        args.push("--sol-code-path");
-        args.push(sol_arg.as_str());
+            let deployed_addr_arg = format!("--addr={}", addr);
 
-        let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
-            .args(&args)
-            .status()
-            .expect("failed to execute process");
-        assert!(status.success());
-
-        // deploy the verifier
-        let mut args = vec![
-            "deploy-evm-verifier",
-            rpc_arg.as_str(),
-            addr_path_arg.as_str(),
-        ];
+            // now verify the proof
+            let pf_arg = format!("{}/{}/proof.pf", test_dir, example_name);
+            let mut args = vec![
+                "verify-evm",
+                "--proof-path",
+                pf_arg.as_str(),
+                rpc_arg.as_str(),
+                deployed_addr_arg.as_str(),
+            ];
 
-        args.push("--sol-code-path");
-        args.push(sol_arg.as_str());
+            let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+                .args(&args)
+                .status()
+                .expect("failed to execute process");
+            assert!(status.success());
+            // As sanity check, add example that should fail.
+            args[2] = PF_FAILURE;
+            let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+                .args(args)
+                .status()
+                .expect("failed to execute process");
+            assert!(!status.success());
+        }
+        srs_path
+    }
 
+    // creates the verifier of `example_name` in `verifier_format` and deploys it, returning whether it deployed
+    fn deploy_evm_verifier(
+        test_dir: &str,
+        example_name: &str,
+        srs_path: &str,
+        verifier_format: &str,
+    ) -> bool {
+        let vk_arg = format!("{}/{}/key.vk", test_dir, example_name);
+        let settings_arg = format!(
+            "--settings-path={}/{}/settings.json",
+            test_dir, example_name
+        );
+        let format_arg = format!("--verifier-format={}", verifier_format);
+        let sol_arg = format!("{}/{}/kzg.sol", test_dir, example_name);
         let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
-            .args(&args)
+            .args([
+                "create-evm-verifier",
+                srs_path,
+                "--vk-path",
+                &vk_arg,
+                &settings_arg,
+                &format_arg,
+                "--sol-code-path",
+                &sol_arg,
+            ])
             .status()
             .expect("failed to execute process");
         assert!(status.success());
 
-        // read in the address
+        let rpc_arg = format!("--rpc-url={}", ANVIL_URL.as_str());
+        let addr_path_arg = format!("--addr-path={}/{}/addr.txt", test_dir, example_name);
+        Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+            .args([
+                "deploy-evm-verifier",
+                &rpc_arg,
+                &addr_path_arg,
+                "--sol-code-path",
+                &sol_arg,
+            ])
+            .status()
+            .expect("failed to execute process")
+            .success()
+    }
+
+    // verifies the proof of `example_name` against its deployed verifier as a web3 library would, from the JSON of
+    // [ezkl::pfsys::Snark::to_web3_json]
+    fn kzg_evm_web3_json_verify(test_dir: &str, example_name: &str) {
+        use ezkl::pfsys::Snark;
+        use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+        use halo2curves::bn256::{Bn256, Fr, G1Affine};
+
+        let proof_path = format!("{}/{}/proof.pf", test_dir, example_name).into();
+        let snark = Snark::<Fr, G1Affine>::load::<KZGCommitmentScheme<Bn256>>(&proof_path).unwrap();
         let addr = std::fs::read_to_string(format!("{}/{}/addr.txt", test_dir, example_name))
             .expect("failed to read address file");
 
-        let deployed_addr_arg = format!("--addr={}", addr);
+        let web3_json = snark.to_web3_json();
+        assert!(call_web3_verifier(&addr, &web3_json));
+        // tampering with a public signal is rejected
+        let mut tampered = web3_json.clone();
+        let mut signal = tampered["publicSignals"][0].as_str().unwrap().to_string();
+        let last = match signal.pop() {
+            Some('0') => '1',
+            _ => '0',
+        };
+        signal.push(last);
+        tampered["publicSignals"][0] = serde_json::Value::String(signal);
+        assert!(!call_web3_verifier(&addr, &tampered));
+    }
 
-        // now verify the proof
-        let pf_arg = format!("{}/{}/proof.pf", test_dir, example_name);
-        let mut args = vec![
-            "verify-evm",
-            "--proof-path",
-            pf_arg.as_str(),
-            rpc_arg.as_str(),
-            deployed_addr_arg.as_str(),
-        ];
+    // calls `verify(uint256[], bytes)` of the verifier at `addr` with the calldata built from `web3_json`, returning
+    // whether the verifier accepted the proof
+    fn call_web3_verifier(addr: &str, web3_json: &serde_json::Value) -> bool {
+        use ethers::abi::Token;
+        use ethers::providers::{Http, Middleware, Provider};
+        use ethers::types::transaction::eip2718::TypedTransaction;
+        use ethers::types::{TransactionRequest, H160, U256};
 
-        let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
-            .args(&args)
-            .status()
-            .expect("failed to execute process");
-        assert!(status.success());
-        // As sanity check, add example that should fail.
-        args[2] = PF_FAILURE;
-        let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
-            .args(args)
-            .status()
-            .expect("failed to execute process");
-        assert!(!status.success());
+        let decode = |hex_string: &serde_json::Value| {
+            hex::decode(hex_string.as_str().unwrap().trim_start_matches("0x")).unwrap()
+        };
+        let public_signals: Vec<Token> = web3_json["publicSignals"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|signal| Token::Uint(U256::from_big_endian(&decode(signal))))
+            .collect();
+        let mut calldata =
+            ethers::utils::id(format!("verify(uint256[{}],bytes)", public_signals.len())).to_vec();
+        calldata.extend(ethers::abi::encode(&[
+            Token::FixedArray(public_signals),
+            Token::Bytes(decode(&web3_json["proof"])),
+        ]));
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(addr.trim().parse::<H160>().unwrap())
+            .data(calldata)
+            .into();
+        let provider = Provider::<Http>::try_from(ANVIL_URL.as_str()).unwrap();
+        let result = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(provider.call(&tx, None));
+        matches!(result, Ok(bytes) if bytes.last() == Some(&1))
     }
 
     fn kzg_evm_on_chain_input_prove_and_verify(
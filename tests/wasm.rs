@@ -141,6 +141,9 @@ mod wasm32 {
             protocol: og_proof.protocol,
             instances: vec![vec![Fr::from(0); 32]],
             transcript_type: ezkl::pfsys::TranscriptType::EVM,
+            metadata: Default::default(),
+            public_input_hash: None,
+            elided_instances: vec![],
         };
         let proof = serde_json::to_string(&proof).unwrap().into_bytes();
 